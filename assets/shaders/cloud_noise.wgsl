@@ -0,0 +1,26 @@
+// Noise shared by the cloud layer and the terrain: both shade clouds with the same cloud_density, so
+// the shadows on the ground fall under the clouds that cast them
+
+#define_import_path trowback::cloud_noise
+
+fn hash(cell: vec2<f32>) -> f32 {
+    return fract(sin(dot(cell, vec2<f32>(127.1, 311.7))) * 43758.5453);
+}
+
+// Smoothed value noise, from 0 to 1
+fn value_noise(position: vec2<f32>) -> f32 {
+    let cell = floor(position);
+    let t = fract(position);
+    let s = t * t * (3.0 - 2.0 * t);
+    let top = mix(hash(cell), hash(cell + vec2<f32>(1.0, 0.0)), s.x);
+    let bottom = mix(hash(cell + vec2<f32>(0.0, 1.0)), hash(cell + vec2<f32>(1.0, 1.0)), s.x);
+    return mix(top, bottom, s.y);
+}
+
+// How thick the clouds are over a point on the ground plane, from 0 to 1, for the sheet's drift offset,
+// noise scale, coverage and edge softness
+fn cloud_density(position: vec2<f32>, offset: vec2<f32>, scale: f32, coverage: f32, softness: f32) -> f32 {
+    let p = (position + offset) / scale;
+    let n = value_noise(p) * 0.55 + value_noise(p * 2.3 + vec2<f32>(41.0, 7.0)) * 0.3 + value_noise(p * 5.1 + vec2<f32>(13.0, 57.0)) * 0.15;
+    return smoothstep(1.0 - coverage, 1.0 - coverage + softness, n);
+}
@@ -0,0 +1,33 @@
+// Cloud layer: a flat sheet of drifting noise high over the camera, thick where the noise clears the
+// coverage and fading out toward the edge of the sheet so its border never shows
+
+#import bevy_pbr::{
+    forward_io::VertexOutput,
+    mesh_view_bindings::view,
+}
+#import trowback::cloud_noise::cloud_density
+
+// Matches CloudSettings in clouds.rs
+struct CloudSettings {
+    color: vec4<f32>,
+    offset: vec2<f32>,
+    scale: f32,
+    coverage: f32,
+    softness: f32,
+    opacity: f32,
+    fade_start: f32,
+    fade_end: f32,
+}
+
+@group(2) @binding(0) var<uniform> clouds: CloudSettings;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let world = in.world_position.xyz;
+    let density = cloud_density(world.xz, clouds.offset, clouds.scale, clouds.coverage, clouds.softness);
+    let distance = length(world.xz - view.world_position.xz);
+    let fade = 1.0 - smoothstep(clouds.fade_start, clouds.fade_end, distance);
+    // Thick middles are a little darker than the thin edges, as if lit from above
+    let color = clouds.color.rgb * (1.0 - density * 0.25);
+    return vec4<f32>(color, density * clouds.opacity * fade);
+}
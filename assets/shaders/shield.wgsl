@@ -0,0 +1,39 @@
+// Shield bubble: a see-through sphere, brighter toward its rim, with a ring of ripples spreading over
+// the surface from where the last boulder bounced off
+
+#import bevy_pbr::{
+    forward_io::VertexOutput,
+    mesh_view_bindings::view,
+}
+
+// Matches ShieldSettings in shield.rs
+struct ShieldSettings {
+    color: vec4<f32>,
+    ripple_direction: vec3<f32>,
+    ripple_front: f32,
+    ripple_strength: f32,
+}
+
+@group(2) @binding(0) var<uniform> shield: ShieldSettings;
+
+const RIPPLE_WIDTH: f32 = 0.5; // Arc in radians the ripple ring spans
+const RIPPLE_WAVES: f32 = 20.0; // Wave crests per radian inside the ring
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    // On a sphere the normal points straight out from the middle
+    let normal = normalize(in.world_normal);
+    let to_view = normalize(view.world_position.xyz - in.world_position.xyz);
+    let rim = pow(1.0 - abs(dot(normal, to_view)), 3.0);
+
+    // Arc from the impact point, and how far this point is from the spreading ring
+    let arc = acos(clamp(dot(normal, shield.ripple_direction), -1.0, 1.0));
+    let offset = arc - shield.ripple_front;
+    let ring = 1.0 - smoothstep(0.0, RIPPLE_WIDTH, abs(offset));
+    let waves = 0.5 + 0.5 * cos(offset * RIPPLE_WAVES);
+    let ripple = ring * waves * shield.ripple_strength;
+
+    let alpha = shield.color.a * (0.3 + 0.7 * rim) + ripple * 0.6;
+    let color = shield.color.rgb * (1.0 + ripple * 2.0);
+    return vec4<f32>(color, clamp(alpha, 0.0, 1.0));
+}
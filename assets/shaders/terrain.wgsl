@@ -0,0 +1,75 @@
+// Terrain surface: standard PBR lighting on a ground color picked by slope and large-scale noise, with a
+// detail normal map projected straight down that fades out with distance from the camera, and the cloud
+// layer's shadows drifting over it
+
+#import bevy_pbr::{
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::{alpha_discard, apply_pbr_lighting, main_pass_post_lighting_processing},
+    forward_io::{VertexOutput, FragmentOutput},
+    mesh_view_bindings::view,
+}
+#import trowback::cloud_noise::{cloud_density, value_noise}
+
+// Matches TerrainSettings in terrain_material.rs
+struct TerrainSettings {
+    grass_color: vec4<f32>,
+    dry_color: vec4<f32>,
+    rock_color: vec4<f32>,
+    macro_scale: f32,
+    macro_strength: f32,
+    detail_scale: f32,
+    detail_strength: f32,
+    detail_distance: f32,
+    slope_start: f32,
+    slope_end: f32,
+    cloud_offset: vec2<f32>,
+    cloud_slant: vec2<f32>,
+    cloud_scale: f32,
+    cloud_coverage: f32,
+    cloud_softness: f32,
+    cloud_altitude: f32,
+    cloud_shadow: f32,
+}
+
+@group(2) @binding(100) var<uniform> terrain: TerrainSettings;
+@group(2) @binding(101) var detail_normal_texture: texture_2d<f32>;
+@group(2) @binding(102) var detail_normal_sampler: sampler;
+
+// Two octaves of noise for color patches tens of meters across
+fn macro_noise(position: vec2<f32>) -> f32 {
+    return value_noise(position) * 0.65 + value_noise(position * 2.7 + vec2<f32>(17.0, 31.0)) * 0.35;
+}
+
+@fragment
+fn fragment(in: VertexOutput, @builtin(front_facing) is_front: bool) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+    let world = in.world_position.xyz;
+    let normal = normalize(in.world_normal);
+
+    // Grass and drier grass in broad patches, each patch a little lighter or darker, and rock on the steeps
+    let patches = macro_noise(world.xz / terrain.macro_scale);
+    let shade = value_noise(world.xz / (terrain.macro_scale * 0.37) + vec2<f32>(5.0, 9.0));
+    var ground = mix(terrain.grass_color.rgb, terrain.dry_color.rgb, smoothstep(0.35, 0.65, patches));
+    ground *= 1.0 + (shade - 0.5) * terrain.macro_strength;
+    let rock = smoothstep(terrain.slope_start, terrain.slope_end, 1.0 - normal.y);
+    var color = mix(ground, terrain.rock_color.rgb, rock);
+
+    // Follow the sunlight back up to the cloud layer and darken the ground under thick cloud. Bevy's
+    // directional light has no cookie, so the shadow is painted on rather than cast
+    let under = world.xz + terrain.cloud_slant * (terrain.cloud_altitude - world.y);
+    let density = cloud_density(under, terrain.cloud_offset, terrain.cloud_scale, terrain.cloud_coverage, terrain.cloud_softness);
+    color *= 1.0 - density * terrain.cloud_shadow;
+    pbr_input.material.base_color = vec4<f32>(color * pbr_input.material.base_color.rgb, pbr_input.material.base_color.a);
+
+    // Lean the surface by the detail map, red along X and green along Z, fading out before it would shimmer
+    let distance = length(view.world_position - world);
+    let strength = terrain.detail_strength * (1.0 - smoothstep(terrain.detail_distance * 0.5, terrain.detail_distance, distance));
+    let detail = textureSample(detail_normal_texture, detail_normal_sampler, world.xz / terrain.detail_scale).rgb * 2.0 - 1.0;
+    pbr_input.N = normalize(normal + vec3<f32>(detail.x, 0.0, detail.y) * strength);
+
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+    return out;
+}
@@ -0,0 +1,52 @@
+use bevy::prelude::*;
+use crate::assets::palette::ColorPalette;
+use crate::camera::CursorRingAssets;
+use crate::player::Player;
+use crate::preload::SphereTextures;
+
+// Player-selectable accessibility options
+#[derive(Resource, Default)]
+pub struct AccessibilitySettings {
+    // Colors used for the ball texture and the target cursor
+    pub palette: ColorPalette,
+}
+
+// Swap the ball texture and recolor the cursor ring when the palette changes
+pub fn apply_color_palette(
+    settings: Res<AccessibilitySettings>,
+    player_query: Query<&MeshMaterial3d<StandardMaterial>, With<Player>>,
+    ring_assets: Res<CursorRingAssets>,
+    sphere_textures: Res<SphereTextures>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let palette = settings.palette;
+
+    // Every palette's texture was generated during preload, so this only rebinds a handle
+    for material_handle in player_query.iter() {
+        if let Some(material) = materials.get_mut(&material_handle.0) {
+            material.base_color_texture = Some(sphere_textures.get(palette));
+        }
+    }
+
+    for (handle, color) in [
+        (&ring_assets.in_range_material, palette.in_range_color()),
+        (&ring_assets.out_of_range_material, palette.out_of_range_color()),
+    ] {
+        if let Some(material) = materials.get_mut(handle) {
+            material.base_color = color;
+            material.emissive = color.into();
+        }
+    }
+}
+
+// Plugin for accessibility options
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<AccessibilitySettings>()
+            .add_systems(Update, apply_color_palette
+                .run_if(resource_changed::<AccessibilitySettings>.and(not(resource_added::<AccessibilitySettings>))));
+    }
+}
@@ -0,0 +1,287 @@
+use bevy::prelude::*;
+use crate::game_events::GameEvent;
+use crate::photo_mode::PhotoModeState;
+use crate::player::{Player, PlayerPhysics};
+use crate::save::SaveData;
+use crate::sim::SimClock;
+use crate::{GameState, RunStart};
+
+// Achievement constants
+const LONG_SHOT_DISTANCE: f32 = 100.0; // Throw distance a target has to be destroyed from for Long Shot
+const SURVIVOR_MODE: &str = "Target Rush"; // Mod whose waves count for Survivor, the one the game ships with
+const SURVIVOR_WAVE: u32 = 5; // Wave of it to survive for Survivor, its last
+const MARATHON_DISTANCE: f32 = 1000.0; // Distance to roll without stopping for Marathon
+const STREAK_STEP: f32 = 250.0; // Distance between rolling streak events
+const STOPPED_SPEED: f32 = 0.5; // Horizontal speed in m/s below which the ball counts as stopped
+const WARP_DISTANCE: f32 = 20.0; // Movement in one frame too large to have been rolled, like a teleport
+const TOAST_DURATION: f32 = 4.0; // Seconds an unlock toast stays up
+
+// Every achievement there is, each unlocked once per profile
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Achievement {
+    FirstTarget,
+    LongShot,
+    Survivor,
+    Marathon,
+    HoleInOne,
+    GiantSlayer,
+}
+
+impl Achievement {
+    // Every achievement, in the order shown on the achievements screen
+    pub const ALL: [Achievement; 6] = [
+        Achievement::FirstTarget,
+        Achievement::LongShot,
+        Achievement::Survivor,
+        Achievement::Marathon,
+        Achievement::HoleInOne,
+        Achievement::GiantSlayer,
+    ];
+
+    // Stable id stored in the save file
+    pub fn id(self) -> &'static str {
+        match self {
+            Achievement::FirstTarget => "first_target",
+            Achievement::LongShot => "long_shot",
+            Achievement::Survivor => "survivor",
+            Achievement::Marathon => "marathon",
+            Achievement::HoleInOne => "hole_in_one",
+            Achievement::GiantSlayer => "giant_slayer",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Achievement::FirstTarget => "First Blood",
+            Achievement::LongShot => "Long Shot",
+            Achievement::Survivor => "Survivor",
+            Achievement::Marathon => "Marathon",
+            Achievement::HoleInOne => "Hole in One",
+            Achievement::GiantSlayer => "Giant Slayer",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Achievement::FirstTarget => "Destroy a target",
+            Achievement::LongShot => "Destroy a target from 100 m away",
+            Achievement::Survivor => "Clear all five waves of Target Rush",
+            Achievement::Marathon => "Roll 1 km without stopping",
+            Achievement::HoleInOne => "Sink a golf hole in one stroke",
+            Achievement::GiantSlayer => "Defeat the boss",
+        }
+    }
+
+    // Whether a game event earns this achievement
+    pub fn earned_by(self, event: &GameEvent) -> bool {
+        match (self, event) {
+            (Achievement::FirstTarget, GameEvent::TargetDestroyed { .. }) => true,
+            (Achievement::LongShot, GameEvent::TargetDestroyed { distance, .. }) => *distance >= LONG_SHOT_DISTANCE,
+            (Achievement::Survivor, GameEvent::WaveSurvived { mode, wave }) => mode == SURVIVOR_MODE && *wave >= SURVIVOR_WAVE,
+            (Achievement::Marathon, GameEvent::RollingStreak { distance }) => *distance >= MARATHON_DISTANCE,
+            (Achievement::HoleInOne, GameEvent::HoleFinished { strokes, sunk, .. }) => *sunk && *strokes == 1,
+            (Achievement::GiantSlayer, GameEvent::BossDefeated { .. }) => true,
+            _ => false,
+        }
+    }
+}
+
+// How far the ball has rolled since it last stopped, for the rolling streak events
+#[derive(Resource, Default)]
+pub struct RollingStreak {
+    pub distance: f32,
+    // Streak steps already reported
+    reported: u32,
+    last_position: Option<Vec3>,
+}
+
+// The unlock toast on screen and the unlocks waiting behind it
+#[derive(Resource, Default)]
+pub struct AchievementToast {
+    pub active: Option<Achievement>,
+    // Seconds until the active toast hides itself
+    pub timer: f32,
+    pub queue: Vec<Achievement>,
+}
+
+// Marker for the toast panel
+#[derive(Component)]
+pub struct AchievementToastRoot;
+
+// Marker for the toast text
+#[derive(Component)]
+pub struct AchievementToastText;
+
+// Spawn the toast panel at the top of the screen, hidden until something unlocks
+pub fn spawn_achievement_toast(mut commands: Commands) {
+    commands
+        .spawn((
+            AchievementToastRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                // Below the compass and the time banner
+                top: Val::Px(140.0),
+                left: Val::Percent(50.0),
+                width: Val::Px(320.0),
+                margin: UiRect::left(Val::Px(-160.0)),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                border: UiRect::all(Val::Px(2.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.08, 0.06, 0.02, 0.85)),
+            BorderColor(Color::srgb(0.95, 0.75, 0.2)),
+            BorderRadius::all(Val::Px(6.0)),
+            GlobalZIndex(5),
+            PickingBehavior::IGNORE,
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Achievement unlocked"),
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.95, 0.75, 0.2)),
+            ));
+            parent.spawn((
+                AchievementToastText,
+                Text::new(""),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+        });
+}
+
+// Measure how far the ball rolls without stopping and report each step of the streak. Nothing rolls
+// while the clock is held, and a rewind moves the ball back without rolling it, so the position is
+// picked up afresh once the clock runs again
+pub fn track_rolling_streak(
+    mut streak: ResMut<RollingStreak>,
+    clock: Res<SimClock>,
+    player_query: Query<(&Transform, &PlayerPhysics), With<Player>>,
+    mut game_events: EventWriter<GameEvent>,
+) {
+    if clock.is_paused() {
+        streak.last_position = None;
+        return;
+    }
+    let Ok((transform, physics)) = player_query.get_single() else {
+        return;
+    };
+    let position = transform.translation;
+    let step = streak.last_position.map_or(0.0, |last| Vec2::new(position.x - last.x, position.z - last.z).length());
+    streak.last_position = Some(position);
+    let stopped = Vec2::new(physics.velocity.x, physics.velocity.z).length() < STOPPED_SPEED;
+    if stopped || step > WARP_DISTANCE {
+        streak.distance = 0.0;
+        streak.reported = 0;
+        return;
+    }
+    streak.distance += step;
+    let steps = (streak.distance / STREAK_STEP) as u32;
+    if steps > streak.reported {
+        streak.reported = steps;
+        game_events.send(GameEvent::RollingStreak { distance: steps as f32 * STREAK_STEP });
+    }
+}
+
+// Start a streak afresh when a run starts with the player moved to the spawn point
+pub fn reset_rolling_streak(mut streak: ResMut<RollingStreak>) {
+    *streak = RollingStreak::default();
+}
+
+// Unlock whatever the game events earn and queue a toast for each new one; only touch the save when
+// something is new
+pub fn evaluate_achievements(
+    mut events: EventReader<GameEvent>,
+    mut save: ResMut<SaveData>,
+    mut toast: ResMut<AchievementToast>,
+) {
+    for event in events.read() {
+        for achievement in Achievement::ALL {
+            if achievement.earned_by(event) && !save.has_achievement(achievement.id()) {
+                save.unlock_achievement(achievement.id());
+                toast.queue.push(achievement);
+            }
+        }
+    }
+}
+
+// Show queued unlocks one at a time
+pub fn update_achievement_toast(
+    mut toast: ResMut<AchievementToast>,
+    time: Res<Time<Real>>,
+    mut root_query: Query<&mut Visibility, With<AchievementToastRoot>>,
+    mut text_query: Query<&mut Text, With<AchievementToastText>>,
+) {
+    if toast.active.is_some() {
+        toast.timer -= time.delta_secs();
+        if toast.timer <= 0.0 {
+            toast.active = None;
+        }
+    }
+
+    if toast.active.is_none() && !toast.queue.is_empty() {
+        let achievement = toast.queue.remove(0);
+        toast.active = Some(achievement);
+        toast.timer = TOAST_DURATION;
+        for mut text in text_query.iter_mut() {
+            text.0 = achievement.name().to_string();
+        }
+    }
+
+    let wanted = if toast.active.is_some() { Visibility::Inherited } else { Visibility::Hidden };
+    for mut visibility in root_query.iter_mut() {
+        visibility.set_if_neq(wanted);
+    }
+}
+
+// Hide the toast outside of gameplay; queued unlocks wait for the next time play resumes
+pub fn hide_achievement_toast(mut query: Query<&mut Visibility, With<AchievementToastRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// One line per achievement for the achievements screen, with whether it is unlocked
+pub fn achievement_lines(save: &SaveData) -> Vec<(String, bool)> {
+    Achievement::ALL
+        .iter()
+        .map(|achievement| {
+            let unlocked = save.has_achievement(achievement.id());
+            let mark = if unlocked { "[x]" } else { "[ ]" };
+            (format!("{} {}: {}", mark, achievement.name(), achievement.description()), unlocked)
+        })
+        .collect()
+}
+
+// Plugin for achievements and their unlock toasts
+pub struct AchievementPlugin;
+
+impl Plugin for AchievementPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<RollingStreak>()
+            .init_resource::<AchievementToast>()
+            .add_systems(Startup, spawn_achievement_toast)
+            .add_systems(RunStart, reset_rolling_streak)
+            .add_systems(OnExit(GameState::Playing), hide_achievement_toast)
+            // Events can arrive on the way out of a run, so unlocks are checked in every state
+            .add_systems(Update, evaluate_achievements)
+            .add_systems(Update, track_rolling_streak
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)))
+            .add_systems(Update, update_achievement_toast
+                .after(evaluate_achievements)
+                .run_if(in_state(GameState::Playing))
+                // Photo mode hides the UI, so don't bring the toast back mid-shot
+                .run_if(in_state(PhotoModeState::Inactive)));
+    }
+}
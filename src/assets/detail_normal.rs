@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use bevy::image::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor};
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+// Detail normal map constants
+const SIZE: usize = 256; // Texture size; the pattern tiles across its edges
+const OCTAVES: [(usize, f32); 3] = [(8, 1.0), (16, 0.5), (32, 0.25)]; // Noise cells across the texture and their weight
+const BUMP_HEIGHT: f32 = 6.0; // How steep the bumps are, in pixels of height per unit of noise
+
+// Lattice value for a noise cell, wrapped so the texture tiles
+fn lattice(x: usize, y: usize, cells: usize) -> f32 {
+    let mut hash = ((x % cells) as u32).wrapping_mul(0x8da6_b343) ^ ((y % cells) as u32).wrapping_mul(0xd816_3841) ^ cells as u32;
+    hash = (hash ^ (hash >> 13)).wrapping_mul(0x5bd1_e995);
+    hash ^= hash >> 15;
+    hash as f32 / u32::MAX as f32
+}
+
+// Smoothed value noise over the texture with the given number of cells across
+fn value_noise(x: f32, y: f32, cells: usize) -> f32 {
+    let (fx, fy) = (x * cells as f32, y * cells as f32);
+    let (cx, cy) = (fx.floor() as usize, fy.floor() as usize);
+    let (tx, ty) = (fx.fract(), fy.fract());
+    let (sx, sy) = (tx * tx * (3.0 - 2.0 * tx), ty * ty * (3.0 - 2.0 * ty));
+    let top = lattice(cx, cy, cells) * (1.0 - sx) + lattice(cx + 1, cy, cells) * sx;
+    let bottom = lattice(cx, cy + 1, cells) * (1.0 - sx) + lattice(cx + 1, cy + 1, cells) * sx;
+    top * (1.0 - sy) + bottom * sy
+}
+
+// Generate a tiling normal map of small lumps and pebbles for close-up terrain. The terrain shader
+// projects it straight down onto the ground, so red leans the surface along X and green along Z.
+pub fn create_detail_normal_map() -> Image {
+    let heights: Vec<f32> = (0..SIZE * SIZE)
+        .map(|i| {
+            let (x, y) = ((i % SIZE) as f32 / SIZE as f32, (i / SIZE) as f32 / SIZE as f32);
+            OCTAVES.iter().map(|(cells, weight)| value_noise(x, y, *cells) * weight).sum()
+        })
+        .collect();
+    let height = |x: usize, y: usize| heights[(y % SIZE) * SIZE + x % SIZE];
+
+    let mut rgba = vec![0; SIZE * SIZE * 4];
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            // Slope from the neighbours on either side, wrapping at the edges
+            let dx = (height(x + 1, y) - height(x + SIZE - 1, y)) * BUMP_HEIGHT;
+            let dy = (height(x, y + 1) - height(x, y + SIZE - 1)) * BUMP_HEIGHT;
+            let normal = Vec3::new(-dx, -dy, 1.0).normalize();
+            let i = (y * SIZE + x) * 4;
+            rgba[i] = ((normal.x * 0.5 + 0.5) * 255.0) as u8;
+            rgba[i + 1] = ((normal.y * 0.5 + 0.5) * 255.0) as u8;
+            rgba[i + 2] = ((normal.z * 0.5 + 0.5) * 255.0) as u8;
+            rgba[i + 3] = 255;
+        }
+    }
+
+    // Normals are data, not color, so no sRGB
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: SIZE as u32,
+            height: SIZE as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &rgba,
+        TextureFormat::Rgba8Unorm,
+        bevy::render::render_asset::RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::Repeat,
+        address_mode_v: ImageAddressMode::Repeat,
+        ..ImageSamplerDescriptor::linear()
+    });
+    image
+}
@@ -1,2 +1,6 @@
 // Export assets modules
 pub mod sphere_texture;
+pub mod palette;
+pub mod models;
+pub mod detail_normal;
+pub mod particle_texture;
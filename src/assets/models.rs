@@ -0,0 +1,179 @@
+use bevy::prelude::*;
+use bevy::asset::RecursiveDependencyLoadState;
+use bevy::transform::TransformSystem;
+use serde::Deserialize;
+use std::collections::HashMap;
+use crate::player::{Player, PlayerPhysics};
+use crate::props::PropKind;
+use crate::sim::interpolate_positions;
+
+// Model constants
+const MODELS_CONFIG_PATH: &str = "config/models.ron";
+const RIDER_HEIGHT: f32 = 0.5; // Ball radius, so the rider stands on top of it
+const RIDER_TURN_SPEED: f32 = 0.5; // Slowest the ball rolls for the rider to turn toward where it is going
+const RIDER_TURN_RATE: f32 = 8.0; // How quickly the rider turns to face the way the ball rolls
+
+// Which glTF files to show in place of the built-in shapes, read from config/models.ron. Paths are relative
+// to the assets folder; a path without a #label uses the file's first scene. Anything left out keeps its
+// built-in shape.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ModelManifest {
+    // Scenes by structure ID, such as "catapult" or "teleporter"
+    pub structures: HashMap<String, String>,
+    // Scenes by prop kind, such as "crate", "barrel" or "wall"
+    pub props: HashMap<String, String>,
+    // A character riding the player ball
+    pub rider: Option<String>,
+}
+
+impl ModelManifest {
+    // Read the manifest, with no models if it is missing or invalid
+    pub fn load() -> Self {
+        match std::fs::read_to_string(MODELS_CONFIG_PATH) {
+            Ok(contents) => match ron::from_str(&contents) {
+                Ok(manifest) => manifest,
+                Err(error) => {
+                    println!("Invalid model config {}: {}", MODELS_CONFIG_PATH, error);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+// Scene handles for the models in the manifest. Lookups only return scenes that finished loading along
+// with their meshes and textures, so a missing or broken file falls back to the built-in shape.
+#[derive(Resource, Default)]
+pub struct ModelRegistry {
+    structures: HashMap<String, Handle<Scene>>,
+    props: HashMap<String, Handle<Scene>>,
+    rider: Option<Handle<Scene>>,
+}
+
+impl ModelRegistry {
+    fn loaded(handle: Option<&Handle<Scene>>, asset_server: &AssetServer) -> Option<Handle<Scene>> {
+        handle.filter(|handle| asset_server.is_loaded_with_dependencies(handle.id())).cloned()
+    }
+
+    // Scene for a structure ID, if one is loaded
+    pub fn structure(&self, id: &str, asset_server: &AssetServer) -> Option<Handle<Scene>> {
+        Self::loaded(self.structures.get(id), asset_server)
+    }
+
+    // Scene for a kind of prop, if one is loaded
+    pub fn prop(&self, kind: PropKind, asset_server: &AssetServer) -> Option<Handle<Scene>> {
+        Self::loaded(self.props.get(&kind.label().to_lowercase()), asset_server)
+    }
+
+    // Scene for the character riding the ball, if one is loaded
+    pub fn rider(&self, asset_server: &AssetServer) -> Option<Handle<Scene>> {
+        Self::loaded(self.rider.as_ref(), asset_server)
+    }
+
+    // Whether every model has loaded or failed, for the preload screen to wait on
+    pub fn settled(&self, asset_server: &AssetServer) -> bool {
+        self.structures
+            .values()
+            .chain(self.props.values())
+            .chain(self.rider.iter())
+            .all(|handle| matches!(
+                asset_server.get_recursive_dependency_load_state(handle.id()),
+                Some(RecursiveDependencyLoadState::Loaded | RecursiveDependencyLoadState::Failed(_))
+            ))
+    }
+}
+
+// A character model standing on a player's ball; a separate entity so it stays upright while the ball rolls
+#[derive(Component)]
+pub struct Rider {
+    pub player: Entity,
+}
+
+// Start loading a scene from a manifest path
+fn load_scene(asset_server: &AssetServer, path: &str) -> Handle<Scene> {
+    if path.contains('#') {
+        asset_server.load(path.to_string())
+    } else {
+        asset_server.load(GltfAssetLabel::Scene(0).from_asset(path.to_string()))
+    }
+}
+
+// Read the manifest and start loading every model in it; PreStartup so the preload screen waits for them
+pub fn load_models(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let manifest = ModelManifest::load();
+    let load_all = |paths: &HashMap<String, String>| -> HashMap<String, Handle<Scene>> {
+        paths
+            .iter()
+            .map(|(id, path)| (id.to_lowercase(), load_scene(&asset_server, path)))
+            .collect()
+    };
+    commands.insert_resource(ModelRegistry {
+        structures: load_all(&manifest.structures),
+        props: load_all(&manifest.props),
+        rider: manifest.rider.as_deref().map(|path| load_scene(&asset_server, path)),
+    });
+}
+
+// Put the rider on every ball that doesn't have one yet
+pub fn spawn_riders(
+    mut commands: Commands,
+    models: Res<ModelRegistry>,
+    asset_server: Res<AssetServer>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    rider_query: Query<&Rider>,
+) {
+    let Some(scene) = models.rider(&asset_server) else {
+        return;
+    };
+    for (player, transform) in player_query.iter() {
+        if rider_query.iter().any(|rider| rider.player == player) {
+            continue;
+        }
+        commands.spawn((
+            Rider { player },
+            SceneRoot(scene.clone()),
+            Transform::from_translation(transform.translation + Vec3::Y * RIDER_HEIGHT),
+            Name::new("Rider"),
+        ));
+    }
+}
+
+// Keep each rider on top of its ball, turning to face the way it rolls; runs after interpolation so the
+// rider moves as smoothly as the ball under it
+pub fn follow_player_with_rider(
+    mut commands: Commands,
+    mut rider_query: Query<(Entity, &Rider, &mut Transform)>,
+    player_query: Query<(&Transform, &PlayerPhysics), (With<Player>, Without<Rider>)>,
+    time: Res<Time>,
+) {
+    for (entity, rider, mut transform) in rider_query.iter_mut() {
+        let Ok((player_transform, physics)) = player_query.get(rider.player) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        transform.translation = player_transform.translation + Vec3::Y * RIDER_HEIGHT;
+        let heading = physics.velocity.with_y(0.0);
+        if heading.length() > RIDER_TURN_SPEED {
+            // glTF characters face +Z
+            let facing = Quat::from_rotation_y(f32::atan2(heading.x, heading.z));
+            let blend = 1.0 - (-RIDER_TURN_RATE * time.delta_secs()).exp();
+            transform.rotation = transform.rotation.slerp(facing, blend);
+        }
+    }
+}
+
+// Plugin for the glTF models that can stand in for structures, props and the ball's rider
+pub struct ModelPlugin;
+
+impl Plugin for ModelPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(PreStartup, load_models)
+            .add_systems(Update, spawn_riders)
+            .add_systems(PostUpdate, follow_player_with_rider
+                .after(interpolate_positions)
+                .before(TransformSystem::TransformPropagate));
+    }
+}
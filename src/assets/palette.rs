@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+
+// Color schemes for the player ball and the target cursor.
+// The colorblind palettes avoid the hue pairs each condition confuses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorPalette {
+    #[default]
+    Default,
+    // Red-green (green-weak): distinguish by blue/orange and lightness
+    Deuteranopia,
+    // Red-green (red-weak): like deuteranopia but without dark reds
+    Protanopia,
+    // Blue-yellow: distinguish by red/cyan and lightness
+    Tritanopia,
+}
+
+impl ColorPalette {
+    pub const ALL: [ColorPalette; 4] = [
+        ColorPalette::Default,
+        ColorPalette::Deuteranopia,
+        ColorPalette::Protanopia,
+        ColorPalette::Tritanopia,
+    ];
+
+    // Cycle order for the settings menu
+    pub fn next(self) -> Self {
+        match self {
+            ColorPalette::Default => ColorPalette::Deuteranopia,
+            ColorPalette::Deuteranopia => ColorPalette::Protanopia,
+            ColorPalette::Protanopia => ColorPalette::Tritanopia,
+            ColorPalette::Tritanopia => ColorPalette::Default,
+        }
+    }
+
+    // Colors of the eight ball segments
+    pub fn segment_colors(self) -> [[u8; 3]; 8] {
+        match self {
+            ColorPalette::Default => [
+                [200, 50, 50],
+                [50, 50, 200],
+                [200, 200, 50],
+                [50, 200, 50],
+                [200, 50, 200],
+                [200, 120, 50],
+                [230, 230, 230],
+                [40, 40, 40],
+            ],
+            // Okabe-Ito colors
+            ColorPalette::Deuteranopia => [
+                [230, 159, 0],
+                [86, 180, 233],
+                [0, 158, 115],
+                [240, 228, 66],
+                [0, 114, 178],
+                [213, 94, 0],
+                [230, 230, 230],
+                [40, 40, 40],
+            ],
+            // Okabe-Ito with the vermillion lightened, since reds read dark
+            ColorPalette::Protanopia => [
+                [230, 159, 0],
+                [86, 180, 233],
+                [0, 158, 115],
+                [240, 228, 66],
+                [0, 114, 178],
+                [204, 121, 167],
+                [230, 230, 230],
+                [40, 40, 40],
+            ],
+            ColorPalette::Tritanopia => [
+                [220, 50, 32],
+                [0, 150, 150],
+                [250, 140, 160],
+                [0, 90, 90],
+                [160, 30, 30],
+                [130, 130, 130],
+                [240, 240, 240],
+                [40, 40, 40],
+            ],
+        }
+    }
+
+    // Cursor ring color when the target is within throw range
+    pub fn in_range_color(self) -> Color {
+        match self {
+            ColorPalette::Default => Color::srgba(0.2, 1.0, 0.3, 0.8),
+            ColorPalette::Deuteranopia | ColorPalette::Protanopia => Color::srgba(0.34, 0.7, 0.91, 0.8),
+            ColorPalette::Tritanopia => Color::srgba(0.0, 0.75, 0.75, 0.8),
+        }
+    }
+
+    // Cursor ring color when the target is out of throw range
+    pub fn out_of_range_color(self) -> Color {
+        match self {
+            ColorPalette::Default => Color::srgba(1.0, 0.2, 0.2, 0.8),
+            ColorPalette::Deuteranopia | ColorPalette::Protanopia => Color::srgba(0.9, 0.62, 0.0, 0.8),
+            ColorPalette::Tritanopia => Color::srgba(0.9, 0.2, 0.45, 0.8),
+        }
+    }
+}
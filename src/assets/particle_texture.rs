@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+// Particle texture constants
+const SIZE: usize = 64; // Texture size
+const FALLOFF: f32 = 2.0; // How sharply the dot fades toward its edge; higher keeps more of it solid
+
+// Generate a soft white dot that fades to transparent at its edge, tinted per particle by vertex color
+pub fn create_particle_texture() -> Image {
+    let mut rgba = vec![0; SIZE * SIZE * 4];
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let nx = (x as f32 + 0.5) / SIZE as f32 * 2.0 - 1.0;
+            let ny = (y as f32 + 0.5) / SIZE as f32 * 2.0 - 1.0;
+            let distance = (nx * nx + ny * ny).sqrt();
+            let alpha = (1.0 - distance).clamp(0.0, 1.0).powf(1.0 / FALLOFF);
+            let i = (y * SIZE + x) * 4;
+            rgba[i] = 255;
+            rgba[i + 1] = 255;
+            rgba[i + 2] = 255;
+            rgba[i + 3] = (alpha * 255.0) as u8;
+        }
+    }
+
+    Image::new_fill(
+        Extent3d {
+            width: SIZE as u32,
+            height: SIZE as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &rgba,
+        TextureFormat::Rgba8UnormSrgb,
+        bevy::render::render_asset::RenderAssetUsages::default(),
+    )
+}
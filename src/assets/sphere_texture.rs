@@ -1,10 +1,12 @@
 use bevy::prelude::*;
 use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use super::palette::ColorPalette;
 
 // Generate a simple procedural texture for the sphere
 // This creates a simple billiard ball style texture with colored segments
-pub fn create_sphere_texture() -> Image {
+pub fn create_sphere_texture(palette: ColorPalette) -> Image {
     let size = 256; // Texture size
+    let colors = palette.segment_colors();
     let mut rgba = vec![0; size * size * 4];
     
     for y in 0..size {
@@ -30,57 +32,12 @@ pub fn create_sphere_texture() -> Image {
             let segments = 8;
             let segment_id = ((angle / std::f32::consts::PI * segments as f32 / 2.0) + segments as f32) as usize % segments;
             
-            // Alternating colors for segments
-            match segment_id {
-                0 => {
-                    rgba[i] = 200;     // R
-                    rgba[i + 1] = 50;  // G
-                    rgba[i + 2] = 50;  // B
-                    rgba[i + 3] = 255; // A
-                }
-                1 => {
-                    rgba[i] = 50;      // R
-                    rgba[i + 1] = 50;  // G
-                    rgba[i + 2] = 200; // B
-                    rgba[i + 3] = 255; // A
-                }
-                2 => {
-                    rgba[i] = 200;     // R
-                    rgba[i + 1] = 200; // G
-                    rgba[i + 2] = 50;  // B
-                    rgba[i + 3] = 255; // A
-                }
-                3 => {
-                    rgba[i] = 50;      // R
-                    rgba[i + 1] = 200; // G
-                    rgba[i + 2] = 50;  // B
-                    rgba[i + 3] = 255; // A
-                }
-                4 => {
-                    rgba[i] = 200;     // R
-                    rgba[i + 1] = 50;  // G
-                    rgba[i + 2] = 200; // B
-                    rgba[i + 3] = 255; // A
-                }
-                5 => {
-                    rgba[i] = 200;     // R
-                    rgba[i + 1] = 120; // G
-                    rgba[i + 2] = 50;  // B
-                    rgba[i + 3] = 255; // A
-                }
-                6 => {
-                    rgba[i] = 230;     // R
-                    rgba[i + 1] = 230; // G
-                    rgba[i + 2] = 230; // B
-                    rgba[i + 3] = 255; // A
-                }
-                _ => {
-                    rgba[i] = 40;      // R
-                    rgba[i + 1] = 40;  // G
-                    rgba[i + 2] = 40;  // B
-                    rgba[i + 3] = 255; // A
-                }
-            }
+            // Alternating colors for segments, taken from the active palette
+            let [r, g, b] = colors[segment_id];
+            rgba[i] = r;       // R
+            rgba[i + 1] = g;   // G
+            rgba[i + 2] = b;   // B
+            rgba[i + 3] = 255; // A
             
             // Add a circle pattern in the middle of each segment
             let segment_angle = angle - (segment_id as f32 * std::f32::consts::PI / (segments as f32 / 2.0));
@@ -0,0 +1,364 @@
+use bevy::prelude::*;
+use bevy::input::mouse::MouseMotion;
+use rand::Rng;
+use std::f32::consts::TAU;
+use crate::camera::FollowCamera;
+use crate::menu::MainMenuRoot;
+use crate::platforms::PlatformSurfaces;
+use crate::player::{player_spawn_position, roll_sphere, PlayerPhysics, RollInput};
+use crate::projectile::{ballistic_position, solve_ballistic};
+use crate::terrain::get_terrain_height;
+use crate::GameState;
+use crate::tuning::LiveTuning;
+
+// Attract mode constants
+const ATTRACT_DELAY: f32 = 30.0; // Seconds without input at the main menu before the demo starts
+const MOTION_THRESHOLD: f32 = 2.0; // Pixels of mouse movement in a frame that count as input
+const DEMO_RADIUS: f32 = 25.0; // The demo stays this close to the spawn point, inside the terrain built for the menu
+const DEMO_TARGET_COUNT: usize = 4; // Target posts the demo ball shoots at
+const GOAL_REACHED_DISTANCE: f32 = 2.0; // Distance at which the demo ball picks a new spot to roll to
+const DEMO_DRIVE: f32 = 0.8; // Fraction of full input the demo ball rolls with
+const FIRE_INTERVAL: f32 = 2.5; // Seconds between demo throws
+const FIRE_RANGE: f32 = 30.0; // Demo throws only go at targets closer than this
+const FLIGHT_TIME: f32 = 1.4; // Seconds a demo boulder is in the air
+const HIT_RADIUS: f32 = 2.0; // Demo boulders landing this close to a target knock it over
+const ORBIT_RADIUS: f32 = 14.0; // Distance of the camera from the demo ball
+const ORBIT_HEIGHT: f32 = 6.0; // Height of the camera above the demo ball
+const ORBIT_SPEED: f32 = 0.15; // Radians per second the camera circles the ball
+const CAMERA_CLEARANCE: f32 = 2.0; // Minimum height of the camera above the terrain
+const CAMERA_SMOOTHING: f32 = 3.0; // How quickly the camera catches up with the ball
+
+// Whether the attract demo is playing behind the main menu
+#[derive(States, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum AttractState {
+    #[default]
+    Inactive,
+    Active,
+}
+
+// Idle tracking and the demo's camera and throw timers
+#[derive(Resource, Default)]
+pub struct AttractMode {
+    // Seconds since the last input at the main menu
+    pub idle: f32,
+    // Where the camera was when the demo started, restored when it ends
+    pub camera_home: Option<Transform>,
+    pub orbit_angle: f32,
+    pub fire_timer: f32,
+}
+
+// Marker for everything spawned for the demo, removed when it ends
+#[derive(Component)]
+pub struct AttractScene;
+
+// The scripted ball; rolls between random spots with its own physics
+#[derive(Component)]
+pub struct AttractBall {
+    pub goal: Vec3,
+}
+
+// A post the demo ball shoots at
+#[derive(Component)]
+pub struct AttractTarget;
+
+// A boulder thrown by the demo ball
+#[derive(Component)]
+pub struct AttractBoulder {
+    pub start: Vec3,
+    pub velocity: Vec3,
+    pub age: f32,
+}
+
+// Marker for the "press any key" banner
+#[derive(Component)]
+pub struct AttractBanner;
+
+// Random spot on the ground within the demo area
+fn random_spot(rng: &mut impl Rng) -> Vec3 {
+    let spawn = player_spawn_position();
+    let angle = rng.gen_range(0.0..TAU);
+    let distance = rng.gen_range(0.0..DEMO_RADIUS);
+    let x = spawn.x + angle.cos() * distance;
+    let z = spawn.z + angle.sin() * distance;
+    Vec3::new(x, get_terrain_height(x, z), z)
+}
+
+// Whether any key, button or real mouse movement happened this frame
+fn any_input(
+    keys: &ButtonInput<KeyCode>,
+    mouse_buttons: &ButtonInput<MouseButton>,
+    motion_events: &mut EventReader<MouseMotion>,
+    gamepads: &Query<&Gamepad>,
+) -> bool {
+    let motion: f32 = motion_events.read().map(|motion| motion.delta.length()).sum();
+    keys.get_just_pressed().next().is_some()
+        || mouse_buttons.get_just_pressed().next().is_some()
+        || motion > MOTION_THRESHOLD
+        || gamepads.iter().any(|gamepad| gamepad.get_just_pressed().next().is_some())
+}
+
+// Count idle time at the main menu and start the demo once it runs out
+pub fn watch_for_idle(
+    mut attract: ResMut<AttractMode>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut motion_events: EventReader<MouseMotion>,
+    gamepads: Query<&Gamepad>,
+    mut next_state: ResMut<NextState<AttractState>>,
+    time: Res<Time>,
+) {
+    if any_input(&keys, &mouse_buttons, &mut motion_events, &gamepads) {
+        attract.idle = 0.0;
+        return;
+    }
+    attract.idle += time.delta_secs();
+    if attract.idle >= ATTRACT_DELAY {
+        next_state.set(AttractState::Active);
+    }
+}
+
+// Any input ends the demo and brings the menu back
+pub fn stop_on_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut motion_events: EventReader<MouseMotion>,
+    gamepads: Query<&Gamepad>,
+    mut next_state: ResMut<NextState<AttractState>>,
+) {
+    if any_input(&keys, &mouse_buttons, &mut motion_events, &gamepads) {
+        next_state.set(AttractState::Inactive);
+    }
+}
+
+// Leaving the main menu always ends the demo
+pub fn stop_attract(mut next_state: ResMut<NextState<AttractState>>) {
+    next_state.set(AttractState::Inactive);
+}
+
+// Build the banner telling the player how to get back to the menu
+pub fn spawn_attract_banner(mut commands: Commands) {
+    commands.spawn((
+        AttractBanner,
+        Text::new("Press any key"),
+        TextFont {
+            font_size: 32.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(60.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Visibility::Hidden,
+    ));
+}
+
+// Hide the menu, remember the camera and set up the demo ball and its targets
+pub fn start_attract(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut attract: ResMut<AttractMode>,
+    camera_query: Query<&Transform, With<FollowCamera>>,
+    mut menu_query: Query<&mut Visibility, (With<MainMenuRoot>, Without<AttractBanner>)>,
+    mut banner_query: Query<&mut Visibility, (With<AttractBanner>, Without<MainMenuRoot>)>,
+) {
+    let mut rng = rand::thread_rng();
+    attract.camera_home = camera_query.get_single().ok().copied();
+    attract.orbit_angle = rng.gen_range(0.0..TAU);
+    attract.fire_timer = FIRE_INTERVAL;
+    for mut visibility in menu_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+    for mut visibility in banner_query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+
+    let start = random_spot(&mut rng) + Vec3::Y;
+    commands.spawn((
+        AttractScene,
+        AttractBall { goal: random_spot(&mut rng) },
+        PlayerPhysics {
+            prev_position: start,
+            ..default()
+        },
+        Mesh3d(meshes.add(Sphere::new(0.5))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.2, 0.5, 0.9),
+            perceptual_roughness: 0.4,
+            ..default()
+        })),
+        Transform::from_translation(start),
+    ));
+
+    let post_mesh = meshes.add(Cylinder::new(0.3, 2.0));
+    let post_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.9, 0.2, 0.2),
+        emissive: LinearRgba::rgb(0.6, 0.1, 0.1),
+        ..default()
+    });
+    for _ in 0..DEMO_TARGET_COUNT {
+        let spot = random_spot(&mut rng);
+        commands.spawn((
+            AttractScene,
+            AttractTarget,
+            Mesh3d(post_mesh.clone()),
+            MeshMaterial3d(post_material.clone()),
+            Transform::from_translation(spot + Vec3::Y),
+        ));
+    }
+}
+
+// Clear the demo away and put the camera and menu back as they were
+pub fn end_attract(
+    mut commands: Commands,
+    mut attract: ResMut<AttractMode>,
+    scene_query: Query<Entity, With<AttractScene>>,
+    mut camera_query: Query<&mut Transform, With<FollowCamera>>,
+    mut menu_query: Query<&mut Visibility, (With<MainMenuRoot>, Without<AttractBanner>)>,
+    mut banner_query: Query<&mut Visibility, (With<AttractBanner>, Without<MainMenuRoot>)>,
+) {
+    for entity in scene_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if let (Some(home), Ok(mut camera_transform)) = (attract.camera_home.take(), camera_query.get_single_mut()) {
+        *camera_transform = home;
+    }
+    attract.idle = 0.0;
+    for mut visibility in menu_query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+    for mut visibility in banner_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Roll the demo ball from one random spot to the next
+pub fn drive_attract_ball(
+    mut ball_query: Query<(&mut Transform, &mut PlayerPhysics, &mut AttractBall)>,
+    platforms: Res<PlatformSurfaces>,
+    tuning: Res<LiveTuning>,
+    time: Res<Time>,
+) {
+    let mut rng = rand::thread_rng();
+    for (mut transform, mut physics, mut ball) in ball_query.iter_mut() {
+        let offset = Vec3::new(ball.goal.x - transform.translation.x, 0.0, ball.goal.z - transform.translation.z);
+        if offset.length() < GOAL_REACHED_DISTANCE {
+            ball.goal = random_spot(&mut rng);
+        }
+        let direction = offset.normalize_or_zero() * DEMO_DRIVE;
+        roll_sphere(&mut transform, &mut physics, &tuning.0.player, &platforms.0, RollInput::steer(direction), time.delta_secs());
+    }
+}
+
+// Lob a boulder at the nearest target in range every so often
+pub fn fire_attract_boulders(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut attract: ResMut<AttractMode>,
+    ball_query: Query<&Transform, With<AttractBall>>,
+    target_query: Query<&Transform, With<AttractTarget>>,
+    tuning: Res<LiveTuning>,
+    time: Res<Time>,
+) {
+    attract.fire_timer -= time.delta_secs();
+    if attract.fire_timer > 0.0 {
+        return;
+    }
+    attract.fire_timer = FIRE_INTERVAL;
+    let Ok(ball_transform) = ball_query.get_single() else {
+        return;
+    };
+    let start = ball_transform.translation + Vec3::Y;
+    let Some(target) = target_query
+        .iter()
+        .map(|transform| transform.translation)
+        .filter(|position| position.distance(start) < FIRE_RANGE)
+        .min_by(|a, b| a.distance(start).total_cmp(&b.distance(start)))
+    else {
+        return;
+    };
+    let landing = Vec3::new(target.x, get_terrain_height(target.x, target.z), target.z);
+    commands.spawn((
+        AttractScene,
+        AttractBoulder {
+            start,
+            velocity: solve_ballistic(&tuning.0.projectile, start, landing, FLIGHT_TIME),
+            age: 0.0,
+        },
+        Mesh3d(meshes.add(Sphere::new(0.3))),
+        MeshMaterial3d(materials.add(Color::srgb(0.4, 0.4, 0.4))),
+        Transform::from_translation(start),
+    ));
+}
+
+// Fly demo boulders and knock over any target they land by, standing it up again somewhere new
+pub fn update_attract_boulders(
+    mut commands: Commands,
+    mut boulder_query: Query<(Entity, &mut Transform, &mut AttractBoulder)>,
+    mut target_query: Query<&mut Transform, (With<AttractTarget>, Without<AttractBoulder>)>,
+    tuning: Res<LiveTuning>,
+    time: Res<Time>,
+) {
+    let mut rng = rand::thread_rng();
+    for (entity, mut transform, mut boulder) in boulder_query.iter_mut() {
+        boulder.age += time.delta_secs();
+        let position = ballistic_position(&tuning.0.projectile, boulder.start, boulder.velocity, boulder.age);
+        transform.translation = position;
+        if position.y > get_terrain_height(position.x, position.z) {
+            continue;
+        }
+        commands.entity(entity).despawn_recursive();
+        for mut target_transform in target_query.iter_mut() {
+            if target_transform.translation.distance(position) < HIT_RADIUS {
+                target_transform.translation = random_spot(&mut rng) + Vec3::Y;
+            }
+        }
+    }
+}
+
+// Circle the camera slowly around the demo ball
+pub fn orbit_attract_camera(
+    mut attract: ResMut<AttractMode>,
+    ball_query: Query<&Transform, With<AttractBall>>,
+    mut camera_query: Query<&mut Transform, (With<FollowCamera>, Without<AttractBall>)>,
+    time: Res<Time>,
+) {
+    let (Ok(ball_transform), Ok(mut camera_transform)) = (ball_query.get_single(), camera_query.get_single_mut()) else {
+        return;
+    };
+    attract.orbit_angle += ORBIT_SPEED * time.delta_secs();
+    let ball = ball_transform.translation;
+    let angle = attract.orbit_angle;
+    let mut wanted = ball + Vec3::new(angle.cos() * ORBIT_RADIUS, ORBIT_HEIGHT, angle.sin() * ORBIT_RADIUS);
+    wanted.y = wanted.y.max(get_terrain_height(wanted.x, wanted.z) + CAMERA_CLEARANCE);
+    let blend = (CAMERA_SMOOTHING * time.delta_secs()).min(1.0);
+    camera_transform.translation = camera_transform.translation.lerp(wanted, blend);
+    camera_transform.look_at(ball, Vec3::Y);
+}
+
+// Plugin for the idle attract demo at the main menu
+pub struct AttractPlugin;
+
+impl Plugin for AttractPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_state::<AttractState>()
+            .init_resource::<AttractMode>()
+            .add_systems(Startup, spawn_attract_banner)
+            .add_systems(OnEnter(AttractState::Active), start_attract)
+            .add_systems(OnExit(AttractState::Active), end_attract)
+            .add_systems(OnExit(GameState::MainMenu), stop_attract)
+            .add_systems(Update, watch_for_idle
+                .run_if(in_state(GameState::MainMenu))
+                .run_if(in_state(AttractState::Inactive)))
+            .add_systems(Update, (stop_on_input, drive_attract_ball, fire_attract_boulders, update_attract_boulders, orbit_attract_camera)
+                .chain()
+                .run_if(in_state(AttractState::Active)));
+    }
+}
@@ -0,0 +1,613 @@
+use bevy::prelude::*;
+use bevy::audio::{DefaultSpatialScale, SpatialScale, Volume};
+use serde::{Deserialize, Serialize};
+use crate::boss::Boss;
+use crate::enemy::{Enemy, EnemyProximity};
+use crate::game_events::GameEvent;
+use crate::menu::MenuAction;
+use crate::player::{Player, PlayerPhysics};
+use crate::projectile::{Ammo, ProjectileImpactEvent};
+use crate::race::RaceState;
+use crate::settings::Settings;
+use crate::terrain::TERRAIN_HEIGHT_SCALE;
+use crate::tuning::LiveTuning;
+use crate::GameState;
+
+// Music playlists inside the assets folder, played in order and looped
+const EXPLORATION_TRACKS: [&str; 3] = ["music/exploration_1.ogg", "music/exploration_2.ogg", "music/exploration_3.ogg"];
+const COMBAT_TRACKS: [&str; 2] = ["music/combat_1.ogg", "music/combat_2.ogg"];
+const CROSSFADE_TIME: f32 = 2.5; // Seconds for one track to fade out while the next fades in
+const CALM_DELAY: f32 = 4.0; // Seconds after enemies back off before the music calms down
+
+// Spatial sound constants
+pub const LISTENER_EAR_GAP: f32 = 0.3; // Distance between the listener's ears
+const SPATIAL_SCALE: f32 = 0.1; // Shrinks world distances for panning; loudness falls off by the rolloff below instead
+const REFERENCE_DISTANCE: f32 = 5.0; // Sounds closer than this play at full volume
+const MAX_AUDIBLE_DISTANCE: f32 = 120.0; // Sounds farther than this are silent
+const ROLLOFF: f32 = 1.0; // How quickly sounds fade past the reference distance
+const BIG_IMPACT_RADIUS: f32 = 3.0; // Impacts with a blast radius at least this big use the heavy sound
+
+// Ambient layer constants
+const AMBIENT_RESPONSE: f32 = 1.5; // How quickly ambient layers follow their target loudness
+const WIND_BASE: f32 = 0.15; // Wind loudness on the ground standing still
+const WIND_ALTITUDE_GAIN: f32 = 0.45; // Extra wind at WIND_MAX_ALTITUDE
+const WIND_SPEED_GAIN: f32 = 0.4; // Extra wind at the player's top speed
+const WIND_MAX_ALTITUDE: f32 = TERRAIN_HEIGHT_SCALE * 2.0; // Height at which the wind stops growing
+const WIND_PITCH_GAIN: f32 = 0.3; // Wind plays this much faster at top speed
+const BIRDSONG_LEVEL: f32 = 0.5; // Birdsong loudness in full daylight
+const CRICKETS_LEVEL: f32 = 0.4; // Cricket loudness at full night
+const RAIN_LEVEL: f32 = 0.8; // Rain loudness in the heaviest rain
+
+// UI sound constants
+const UI_GAIN: f32 = 0.6; // Interface sounds sit under the game's own sounds
+const LOW_AMMO: u32 = 1; // Boulders left when the low ammo warning sounds
+
+// Mixer channels every sound plays through; master scales all the others
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AudioBus {
+    Master,
+    Music,
+    Effects,
+    Ambient,
+}
+
+impl AudioBus {
+    pub const ALL: [AudioBus; 4] = [AudioBus::Master, AudioBus::Music, AudioBus::Effects, AudioBus::Ambient];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AudioBus::Master => "Master",
+            AudioBus::Music => "Music",
+            AudioBus::Effects => "Effects",
+            AudioBus::Ambient => "Ambient",
+        }
+    }
+}
+
+// Output level of each bus after mutes and the master bus, kept in step with the settings
+#[derive(Resource, Debug, Clone, Default)]
+pub struct AudioMixer {
+    outputs: [f32; 4],
+}
+
+impl AudioMixer {
+    pub fn from_settings(settings: &Settings) -> Self {
+        let level = |bus: AudioBus| if settings.is_muted(bus) { 0.0 } else { settings.bus_volume(bus) };
+        let master = level(AudioBus::Master);
+        Self {
+            outputs: AudioBus::ALL.map(|bus| if bus == AudioBus::Master { master } else { master * level(bus) }),
+        }
+    }
+
+    // Final volume for sounds on a bus
+    pub fn output(&self, bus: AudioBus) -> f32 {
+        self.outputs[bus as usize]
+    }
+}
+
+// A sound playing through a bus, at its own gain (fades, distance) before the bus volume
+#[derive(Component, Debug, Clone, Copy)]
+pub struct BusSound {
+    pub bus: AudioBus,
+    pub gain: f32,
+}
+
+// Everything needed to play a sound through a bus; spawn this rather than a bare AudioPlayer
+pub fn bus_sound(
+    mixer: &AudioMixer,
+    bus: AudioBus,
+    source: Handle<AudioSource>,
+    playback: PlaybackSettings,
+    gain: f32,
+) -> (AudioPlayer, PlaybackSettings, BusSound) {
+    (
+        AudioPlayer::new(source),
+        playback.with_volume(Volume::new(gain * mixer.output(bus))),
+        BusSound { bus, gain },
+    )
+}
+
+// Follow settings changes to bus volumes and mutes
+pub fn sync_mixer(settings: Res<Settings>, mut mixer: ResMut<AudioMixer>) {
+    *mixer = AudioMixer::from_settings(&settings);
+}
+
+// Set every playing sound to its gain through its bus
+pub fn mix_buses(
+    mixer: Res<AudioMixer>,
+    sound_query: Query<(&BusSound, Option<&AudioSink>, Option<&SpatialAudioSink>)>,
+) {
+    for (sound, sink, spatial_sink) in sound_query.iter() {
+        let volume = sound.gain * mixer.output(sound.bus);
+        if let Some(sink) = sink {
+            sink.set_volume(volume);
+        }
+        if let Some(sink) = spatial_sink {
+            sink.set_volume(volume);
+        }
+    }
+}
+
+// Which playlist fits what is happening
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MusicMood {
+    Exploration,
+    Combat,
+}
+
+impl MusicMood {
+    fn index(self) -> usize {
+        match self {
+            MusicMood::Exploration => 0,
+            MusicMood::Combat => 1,
+        }
+    }
+}
+
+// Loaded playlists and where each one is up to
+#[derive(Resource)]
+pub struct MusicPlayer {
+    pub mood: MusicMood,
+    playlists: [Vec<Handle<AudioSource>>; 2],
+    next_track: [usize; 2],
+    // Seconds left before switching back to exploration after combat ends
+    calm_timer: Option<f32>,
+}
+
+impl MusicPlayer {
+    // The next track of a playlist, advancing it
+    fn next_track(&mut self, mood: MusicMood) -> Option<Handle<AudioSource>> {
+        let playlist = &self.playlists[mood.index()];
+        if playlist.is_empty() {
+            return None;
+        }
+        let index = self.next_track[mood.index()] % playlist.len();
+        self.next_track[mood.index()] = index + 1;
+        Some(playlist[index].clone())
+    }
+}
+
+// A playing music track; its BusSound gain is how far it has faded in, from 0 to 1
+#[derive(Component)]
+pub struct MusicTrack {
+    pub mood: MusicMood,
+}
+
+// Start loading the playlists; tracks stream from disk as they play
+pub fn load_music(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let load = |paths: &[&str]| paths.iter().map(|path| asset_server.load(*path)).collect::<Vec<_>>();
+    commands.insert_resource(MusicPlayer {
+        mood: MusicMood::Exploration,
+        playlists: [load(&EXPLORATION_TRACKS), load(&COMBAT_TRACKS)],
+        next_track: [0, 0],
+        calm_timer: None,
+    });
+}
+
+// Switch to combat music as soon as enemies close in, and back once they have stayed away a while
+pub fn update_music_mood(
+    mut music: ResMut<MusicPlayer>,
+    mut events: EventReader<EnemyProximity>,
+    time: Res<Time>,
+) {
+    for event in events.read() {
+        match event {
+            EnemyProximity::Engaged => {
+                music.mood = MusicMood::Combat;
+                music.calm_timer = None;
+            }
+            EnemyProximity::Disengaged => music.calm_timer = Some(CALM_DELAY),
+        }
+    }
+    if let Some(timer) = music.calm_timer {
+        let timer = timer - time.delta_secs();
+        if timer <= 0.0 {
+            music.mood = MusicMood::Exploration;
+            music.calm_timer = None;
+        } else {
+            music.calm_timer = Some(timer);
+        }
+    }
+}
+
+// Fade tracks of the current mood in and the rest out, starting the next track when one ends
+pub fn crossfade_music(
+    mut commands: Commands,
+    mut music: ResMut<MusicPlayer>,
+    mut track_query: Query<(Entity, &MusicTrack, &mut BusSound, Option<&AudioSink>)>,
+    mixer: Res<AudioMixer>,
+    time: Res<Time>,
+) {
+    let step = time.delta_secs() / CROSSFADE_TIME;
+    let mut playing = false;
+
+    for (entity, track, mut sound, sink) in track_query.iter_mut() {
+        let finished = sink.is_some_and(|sink| sink.empty());
+        let current = track.mood == music.mood && !finished;
+        sound.gain = if current { (sound.gain + step).min(1.0) } else { (sound.gain - step).max(0.0) };
+        if finished || (!current && sound.gain <= 0.0) {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        playing |= current;
+    }
+
+    if !playing {
+        let mood = music.mood;
+        if let Some(source) = music.next_track(mood) {
+            commands.spawn((
+                bus_sound(&mixer, AudioBus::Music, source, PlaybackSettings::ONCE, 0.0),
+                MusicTrack { mood },
+            ));
+        }
+    }
+}
+
+// Sound effects placed in the world
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundEffect {
+    // A boulder hitting the ground
+    Impact,
+    HeavyImpact,
+    // A target or prop blowing apart
+    Explosion,
+    // A catapult or the boss hurling a boulder
+    Launch,
+    // An enemy ramming the player
+    EnemyRam,
+    // The boss landing a slam
+    BossSlam,
+    // Looping rumble of a rolling enemy
+    EnemyRoll,
+}
+
+impl SoundEffect {
+    const ALL: [SoundEffect; 7] = [
+        SoundEffect::Impact,
+        SoundEffect::HeavyImpact,
+        SoundEffect::Explosion,
+        SoundEffect::Launch,
+        SoundEffect::EnemyRam,
+        SoundEffect::BossSlam,
+        SoundEffect::EnemyRoll,
+    ];
+
+    fn path(self) -> &'static str {
+        match self {
+            SoundEffect::Impact => "sounds/impact.ogg",
+            SoundEffect::HeavyImpact => "sounds/impact_heavy.ogg",
+            SoundEffect::Explosion => "sounds/explosion.ogg",
+            SoundEffect::Launch => "sounds/launch.ogg",
+            SoundEffect::EnemyRam => "sounds/enemy_ram.ogg",
+            SoundEffect::BossSlam => "sounds/boss_slam.ogg",
+            SoundEffect::EnemyRoll => "sounds/enemy_roll.ogg",
+        }
+    }
+}
+
+// Play a one-shot sound effect at a point in the world
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlaySound {
+    pub effect: SoundEffect,
+    pub position: Vec3,
+}
+
+// A looping sound carried by an entity, kept at the right loudness as it moves
+#[derive(Component)]
+pub struct SoundEmitter {
+    pub effect: SoundEffect,
+}
+
+// Loudness of a sound this far from the listener, from 1 down to 0
+pub fn attenuation(distance: f32) -> f32 {
+    if distance >= MAX_AUDIBLE_DISTANCE {
+        return 0.0;
+    }
+    (REFERENCE_DISTANCE / distance.max(REFERENCE_DISTANCE)).powf(ROLLOFF)
+}
+
+// Play requested sound effects where they happened, attenuated by distance from the listener
+pub fn play_sounds(
+    mut commands: Commands,
+    mut events: EventReader<PlaySound>,
+    listener_query: Query<&GlobalTransform, With<SpatialListener>>,
+    asset_server: Res<AssetServer>,
+    mixer: Res<AudioMixer>,
+) {
+    let listener = listener_query.get_single().map(|transform| transform.translation()).ok();
+    for event in events.read() {
+        let distance = listener.map_or(0.0, |listener| listener.distance(event.position));
+        let gain = attenuation(distance);
+        if gain <= 0.0 || mixer.output(AudioBus::Effects) <= 0.0 {
+            continue;
+        }
+        let source = asset_server.load(event.effect.path());
+        commands.spawn((
+            bus_sound(&mixer, AudioBus::Effects, source, PlaybackSettings::DESPAWN.with_spatial(true), gain),
+            Transform::from_translation(event.position),
+        ));
+    }
+}
+
+// Turn boulder landings and destroyed targets and props into sounds
+pub fn sounds_from_events(
+    mut impact_events: EventReader<ProjectileImpactEvent>,
+    mut game_events: EventReader<GameEvent>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    for impact in impact_events.read() {
+        let effect = if impact.radius >= BIG_IMPACT_RADIUS { SoundEffect::HeavyImpact } else { SoundEffect::Impact };
+        sounds.send(PlaySound { effect, position: impact.position });
+    }
+    for event in game_events.read() {
+        match event {
+            GameEvent::TargetDestroyed { position, .. } | GameEvent::PropDestroyed { position, .. } => {
+                sounds.send(PlaySound { effect: SoundEffect::Explosion, position: *position });
+            }
+            _ => {}
+        }
+    }
+}
+
+// Give new enemies and the boss a rolling rumble that follows them around
+pub fn attach_enemy_emitters(
+    mut commands: Commands,
+    enemy_query: Query<Entity, Or<(Added<Enemy>, Added<Boss>)>>,
+    asset_server: Res<AssetServer>,
+    mixer: Res<AudioMixer>,
+) {
+    for entity in enemy_query.iter() {
+        let effect = SoundEffect::EnemyRoll;
+        let source = asset_server.load(effect.path());
+        let emitter = commands
+            .spawn((
+                bus_sound(&mixer, AudioBus::Effects, source, PlaybackSettings::LOOP.with_spatial(true), 0.0),
+                SoundEmitter { effect },
+                Transform::default(),
+            ))
+            .id();
+        commands.entity(entity).add_child(emitter);
+    }
+}
+
+// Keep looping emitters as loud as their distance from the listener allows
+pub fn attenuate_emitters(
+    mut emitter_query: Query<(&GlobalTransform, &mut BusSound), With<SoundEmitter>>,
+    listener_query: Query<&GlobalTransform, With<SpatialListener>>,
+) {
+    let Ok(listener) = listener_query.get_single() else {
+        return;
+    };
+    for (transform, mut sound) in emitter_query.iter_mut() {
+        sound.gain = attenuation(listener.translation().distance(transform.translation()));
+    }
+}
+
+// What the ambient layers are blended from: 0 is night and 1 full day, rain from 0 dry to 1 pouring.
+// The sky sets daylight from the sun's height and the weather sets the rain
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct AmbientConditions {
+    pub daylight: f32,
+    pub rain: f32,
+}
+
+impl Default for AmbientConditions {
+    fn default() -> Self {
+        Self { daylight: 1.0, rain: 0.0 }
+    }
+}
+
+// Looping background sounds of the world
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmbientLayer {
+    Wind,
+    Birdsong,
+    Crickets,
+    Rain,
+}
+
+impl AmbientLayer {
+    pub const ALL: [AmbientLayer; 4] = [AmbientLayer::Wind, AmbientLayer::Birdsong, AmbientLayer::Crickets, AmbientLayer::Rain];
+
+    fn path(self) -> &'static str {
+        match self {
+            AmbientLayer::Wind => "ambient/wind.ogg",
+            AmbientLayer::Birdsong => "ambient/birdsong.ogg",
+            AmbientLayer::Crickets => "ambient/crickets.ogg",
+            AmbientLayer::Rain => "ambient/rain.ogg",
+        }
+    }
+}
+
+// A playing ambient layer; its BusSound gain is its current loudness, from 0 to 1
+#[derive(Component)]
+pub struct AmbientTrack {
+    pub layer: AmbientLayer,
+}
+
+// Start every ambient layer silent; they fade in once a run is underway
+pub fn spawn_ambient_layers(mut commands: Commands, asset_server: Res<AssetServer>, mixer: Res<AudioMixer>) {
+    for layer in AmbientLayer::ALL {
+        let source = asset_server.load(layer.path());
+        commands.spawn((
+            bus_sound(&mixer, AudioBus::Ambient, source, PlaybackSettings::LOOP, 0.0),
+            AmbientTrack { layer },
+        ));
+    }
+}
+
+// Blend the ambient layers from the conditions and how high and fast the player is going
+pub fn blend_ambient_layers(
+    mut track_query: Query<(&AmbientTrack, &mut BusSound, Option<&AudioSink>)>,
+    player_query: Query<(&Transform, &PlayerPhysics), With<Player>>,
+    conditions: Res<AmbientConditions>,
+    game_state: Res<State<GameState>>,
+    tuning: Res<LiveTuning>,
+    time: Res<Time>,
+) {
+    let playing = *game_state.get() == GameState::Playing;
+    let (altitude, speed) = player_query.get_single().map_or((0.0, 0.0), |(transform, physics)| {
+        let velocity = physics.velocity;
+        (
+            (transform.translation.y / WIND_MAX_ALTITUDE).clamp(0.0, 1.0),
+            (Vec2::new(velocity.x, velocity.z).length() / tuning.0.player.max_speed).clamp(0.0, 1.0),
+        )
+    });
+    let daylight = conditions.daylight.clamp(0.0, 1.0);
+    let blend = (AMBIENT_RESPONSE * time.delta_secs()).min(1.0);
+
+    for (track, mut sound, sink) in track_query.iter_mut() {
+        let target = match track.layer {
+            AmbientLayer::Wind => (WIND_BASE + altitude * WIND_ALTITUDE_GAIN + speed * WIND_SPEED_GAIN).min(1.0),
+            // Birds quiet down in the rain
+            AmbientLayer::Birdsong => daylight * (1.0 - conditions.rain) * BIRDSONG_LEVEL,
+            AmbientLayer::Crickets => (1.0 - daylight) * CRICKETS_LEVEL,
+            AmbientLayer::Rain => conditions.rain * RAIN_LEVEL,
+        };
+        let target = if playing { target } else { 0.0 };
+        sound.gain += (target - sound.gain) * blend;
+        if let (AmbientLayer::Wind, Some(sink)) = (track.layer, sink) {
+            sink.set_speed(1.0 + speed * WIND_PITCH_GAIN);
+        }
+    }
+}
+
+// Interface and HUD feedback sounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UiSound {
+    Hover,
+    Click,
+    Confirm,
+    Back,
+    LowAmmo,
+    Checkpoint,
+}
+
+impl UiSound {
+    const ALL: [UiSound; 6] = [
+        UiSound::Hover,
+        UiSound::Click,
+        UiSound::Confirm,
+        UiSound::Back,
+        UiSound::LowAmmo,
+        UiSound::Checkpoint,
+    ];
+
+    fn path(self) -> &'static str {
+        match self {
+            UiSound::Hover => "sounds/ui_hover.ogg",
+            UiSound::Click => "sounds/ui_click.ogg",
+            UiSound::Confirm => "sounds/ui_confirm.ogg",
+            UiSound::Back => "sounds/ui_back.ogg",
+            UiSound::LowAmmo => "sounds/low_ammo.ogg",
+            UiSound::Checkpoint => "sounds/checkpoint.ogg",
+        }
+    }
+
+    // Sound for clicking a menu button: leaving a page, committing to something, or anything else
+    pub fn for_action(action: MenuAction) -> Self {
+        match action {
+            MenuAction::Back | MenuAction::Resume | MenuAction::MainMenu => UiSound::Back,
+            MenuAction::Play(_)
+            | MenuAction::PlayScript(_)
+            | MenuAction::StartGame
+            | MenuAction::Retry
+            | MenuAction::CreateProfile
+            | MenuAction::LoadSlot(_)
+            | MenuAction::SaveSlot(_) => UiSound::Confirm,
+            _ => UiSound::Click,
+        }
+    }
+}
+
+// Play an interface sound, unpositioned
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlayUiSound(pub UiSound);
+
+// Every sound effect and interface sound file, for loading them ahead of time
+pub fn sound_paths() -> impl Iterator<Item = &'static str> {
+    SoundEffect::ALL.into_iter().map(SoundEffect::path).chain(UiSound::ALL.into_iter().map(UiSound::path))
+}
+
+// Play requested interface sounds on the effects bus
+pub fn play_ui_sounds(
+    mut commands: Commands,
+    mut events: EventReader<PlayUiSound>,
+    asset_server: Res<AssetServer>,
+    mixer: Res<AudioMixer>,
+) {
+    if mixer.output(AudioBus::Effects) <= 0.0 {
+        events.clear();
+        return;
+    }
+    for PlayUiSound(sound) in events.read() {
+        let source = asset_server.load(sound.path());
+        commands.spawn(bus_sound(&mixer, AudioBus::Effects, source, PlaybackSettings::DESPAWN, UI_GAIN));
+    }
+}
+
+// Hover and click sounds for every menu button
+pub fn button_sounds(
+    button_query: Query<(Entity, &Interaction, Option<&MenuAction>), (Changed<Interaction>, With<Button>)>,
+    mut hovered: Local<Option<Entity>>,
+    mut sounds: EventWriter<PlayUiSound>,
+) {
+    for (entity, interaction, action) in button_query.iter() {
+        match interaction {
+            // Moving back onto a button after clicking it isn't a new hover
+            Interaction::Hovered if *hovered != Some(entity) => {
+                *hovered = Some(entity);
+                sounds.send(PlayUiSound(UiSound::Hover));
+            }
+            Interaction::Pressed => {
+                let sound = action.map_or(UiSound::Click, |action| UiSound::for_action(*action));
+                sounds.send(PlayUiSound(sound));
+            }
+            Interaction::None if *hovered == Some(entity) => *hovered = None,
+            _ => {}
+        }
+    }
+}
+
+// Warn when the boulders are nearly gone and chime at each race checkpoint
+pub fn hud_sounds(
+    ammo: Res<Ammo>,
+    race: Res<RaceState>,
+    mut last_ammo: Local<Option<u32>>,
+    mut last_gate: Local<usize>,
+    mut sounds: EventWriter<PlayUiSound>,
+) {
+    if last_ammo.is_some_and(|last| ammo.current < last && ammo.current <= LOW_AMMO) {
+        sounds.send(PlayUiSound(UiSound::LowAmmo));
+    }
+    *last_ammo = Some(ammo.current);
+
+    if race.next_gate > *last_gate {
+        sounds.send(PlayUiSound(UiSound::Checkpoint));
+    }
+    *last_gate = race.next_gate;
+}
+
+// Plugin for the audio mixer, background music, spatial and interface sound effects and ambient layers
+pub struct AudioPlugin;
+
+impl Plugin for AudioPlugin {
+    fn build(&self, app: &mut App) {
+        // Main loads the settings before the plugins build
+        let mixer = app.world().get_resource::<Settings>().map(AudioMixer::from_settings).unwrap_or_default();
+        app
+            .insert_resource(mixer)
+            .add_event::<PlaySound>()
+            .add_event::<PlayUiSound>()
+            .init_resource::<AmbientConditions>()
+            .insert_resource(DefaultSpatialScale(SpatialScale::new(SPATIAL_SCALE)))
+            .add_systems(Startup, (load_music, spawn_ambient_layers))
+            .add_systems(Update, (update_music_mood, crossfade_music).chain())
+            .add_systems(Update, (sounds_from_events, play_sounds).chain())
+            .add_systems(Update, (attach_enemy_emitters, attenuate_emitters, blend_ambient_layers))
+            .add_systems(Update, ((button_sounds, hud_sounds), play_ui_sounds).chain())
+            .add_systems(Update, sync_mixer.run_if(resource_changed::<Settings>))
+            // After every sound has set its gain for the frame
+            .add_systems(PostUpdate, mix_buses);
+    }
+}
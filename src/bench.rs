@@ -0,0 +1,262 @@
+use bevy::prelude::*;
+use bevy::ecs::system::BoxedSystem;
+use bevy::state::app::StatesPlugin;
+use rand::Rng;
+use std::f32::consts::TAU;
+use std::time::{Duration, Instant};
+use crate::assets::palette::ColorPalette;
+use crate::assets::sphere_texture::create_sphere_texture;
+use crate::budget::WorkBudgets;
+use crate::camera::CameraShake;
+use crate::debug::DebugStats;
+use crate::difficulty::Difficulty;
+use crate::platforms::PlatformSurfaces;
+use crate::player::{apply_physics, move_player, spawn_player, Player};
+use crate::projectile::{compute_launch, setup_projectile_assets, spawn_boulder, update_projectiles, vary_launch, BoulderLaunch, Projectile, ProjectileAssets, ProjectileHitEvent, ProjectileImpactEvent, PROJECTILE_BLAST_RADIUS};
+use crate::replay::{BUTTON_DASH, BUTTON_JUMP};
+use crate::sim::{advance_simulation, SimInput, Simulation, SIM_TICK_RATE};
+use crate::spatial::SpatialGrid;
+use crate::terrain::{generate_pending_chunks, get_terrain_height, manage_terrain_chunks, world_seed, ChunkManager, TerrainConfig, TerrainPlugin};
+use crate::terrain_material::TerrainMaterial;
+use crate::tuning::LiveTuning;
+use crate::GameState;
+
+// Benchmark constants
+pub const DEFAULT_STEPS: u32 = 3000; // Fixed ticks simulated when --steps isn't given, 50 seconds of play
+pub const DEFAULT_PROJECTILES: usize = 2000; // Boulders kept in the air when --projectiles isn't given
+const WARMUP_TIMEOUT: Duration = Duration::from_secs(60); // Longest wait for the terrain around the start
+const SCRIPT_TURN_RATE: f32 = 0.4; // Radians per second the scripted player turns
+const SCRIPT_WEAVE_RATE: f32 = 0.7; // How fast the scripted player weaves side to side
+const SCRIPT_JUMP_INTERVAL: u32 = 90; // Ticks between scripted jumps
+const SCRIPT_DASH_INTERVAL: u32 = 240; // Ticks between scripted dashes
+const THROW_MIN_DISTANCE: f32 = 5.0; // Nearest a benchmark boulder lands to the player
+const THROW_MAX_DISTANCE: f32 = 40.0; // Farthest a benchmark boulder lands from the player
+const THROW_STREAM: u64 = 0xBE_7C; // Random stream for benchmark throws
+
+// What a benchmark run simulates, from the command line
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct BenchConfig {
+    // Fixed ticks to simulate
+    pub steps: u32,
+    // Boulders kept in the air at once
+    pub projectiles: usize,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self { steps: DEFAULT_STEPS, projectiles: DEFAULT_PROJECTILES }
+    }
+}
+
+// One system run by the benchmark and the time it has taken so far
+struct TimedSystem {
+    name: &'static str,
+    system: BoxedSystem,
+    total: Duration,
+    worst: Duration,
+}
+
+impl TimedSystem {
+    fn new<M>(name: &'static str, system: impl IntoSystem<(), (), M>, world: &mut World) -> Self {
+        let mut system: BoxedSystem = Box::new(IntoSystem::into_system(system));
+        system.initialize(world);
+        Self { name, system, total: Duration::ZERO, worst: Duration::ZERO }
+    }
+
+    // Run the system once, commands included, and add its time to the totals
+    fn run(&mut self, world: &mut World) {
+        let start = Instant::now();
+        self.system.run((), world);
+        let elapsed = start.elapsed();
+        self.total += elapsed;
+        self.worst = self.worst.max(elapsed);
+    }
+}
+
+// Put the ball at the usual start
+fn spawn_sim_player(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut textures: ResMut<Assets<Image>>,
+) {
+    let texture = textures.add(create_sphere_texture(ColorPalette::default()));
+    spawn_player(&mut commands, &mut meshes, &mut materials, texture);
+}
+
+// Drive the ball like a player would: always rolling, slowly turning and weaving, with regular jumps
+// and dashes. Everything comes from the tick so every run sees the same input.
+fn script_input(mut input: ResMut<SimInput>, simulation: Res<Simulation>) {
+    let seconds = simulation.elapsed();
+    input.move_axis = Vec2::new((seconds * SCRIPT_WEAVE_RATE).sin() * 0.5, 1.0).normalize();
+    input.yaw = seconds * SCRIPT_TURN_RATE;
+    input.held = 0;
+    if simulation.tick % SCRIPT_JUMP_INTERVAL == 0 {
+        input.pressed |= BUTTON_JUMP;
+    }
+    if simulation.tick % SCRIPT_DASH_INTERVAL == 0 {
+        input.pressed |= BUTTON_DASH;
+    }
+}
+
+// Throw boulders around the ball until the configured number are in the air
+fn keep_projectiles_flying(
+    mut commands: Commands,
+    mut projectile_assets: ResMut<ProjectileAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    config: Res<BenchConfig>,
+    simulation: Res<Simulation>,
+    player_query: Query<&Transform, With<Player>>,
+    projectile_query: Query<&Projectile>,
+    tuning: Res<LiveTuning>,
+) {
+    let tuning = &tuning.0.projectile;
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let in_air = projectile_query.iter().filter(|projectile| !projectile.stuck).count();
+    let mut rng = simulation.rng(THROW_STREAM);
+    for _ in in_air..config.projectiles {
+        let angle = rng.gen_range(0.0..TAU);
+        let distance = rng.gen_range(THROW_MIN_DISTANCE..THROW_MAX_DISTANCE);
+        let landing = player_transform.translation.xz() + Vec2::from_angle(angle) * distance;
+        let target = Vec3::new(landing.x, get_terrain_height(landing.x, landing.y), landing.y);
+        let launch = compute_launch(tuning, player_transform.translation, target, THROW_MAX_DISTANCE);
+        spawn_boulder(
+            &mut commands,
+            &mut projectile_assets,
+            &mut materials,
+            tuning,
+            BoulderLaunch {
+                start: launch.start_position,
+                target,
+                velocity: vary_launch(launch.initial_velocity, &mut rng),
+                color: Color::srgb(0.5, 0.45, 0.4),
+                blast_radius: PROJECTILE_BLAST_RADIUS,
+            },
+        );
+    }
+}
+
+// Build a windowless app with just the engine pieces and resources the simulation needs, the terrain
+// streaming and the player, starting in a game state. The benchmark and the headless test app both start
+// from this and add the systems they run; call finish and cleanup once they are in.
+pub fn build_windowless_app(terrain: Option<TerrainConfig>, state: GameState) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin, AssetPlugin::default()))
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<TerrainMaterial>()
+        .init_asset::<Image>();
+    if let Some(terrain) = terrain {
+        app.insert_resource(terrain);
+    }
+    app
+        .insert_state(state)
+        .init_resource::<Simulation>()
+        .init_resource::<SimInput>()
+        .init_resource::<CameraShake>()
+        .init_resource::<DebugStats>()
+        .init_resource::<Difficulty>()
+        .init_resource::<PlatformSurfaces>()
+        .init_resource::<LiveTuning>()
+        .init_resource::<WorkBudgets>()
+        .add_plugins(TerrainPlugin)
+        .add_systems(Startup, spawn_sim_player);
+    app
+}
+
+// The benchmark's app; its systems are run by hand so each can be timed
+fn build_bench_app(config: BenchConfig, terrain: Option<TerrainConfig>) -> App {
+    let mut app = build_windowless_app(terrain, GameState::Loading);
+    app
+        .insert_resource(config)
+        .init_resource::<SpatialGrid>()
+        .add_event::<ProjectileHitEvent>()
+        .add_event::<ProjectileImpactEvent>()
+        .add_systems(Startup, setup_projectile_assets);
+    app.finish();
+    app.cleanup();
+    app
+}
+
+// Run the simulation without a window for a fixed number of ticks and print how long each system took,
+// so physics and terrain slowdowns show up as numbers that can be compared between builds
+pub fn run_bench(config: BenchConfig, terrain: Option<TerrainConfig>) {
+    let mut app = build_bench_app(config, terrain);
+    println!(
+        "Benchmark: {} steps, {} boulders in the air, world seed {:08X}",
+        config.steps,
+        config.projectiles,
+        world_seed()
+    );
+
+    // Stream in the terrain around the start the way the loading screen waits for it
+    let warmup_start = Instant::now();
+    loop {
+        app.update();
+        let chunk_manager = app.world().resource::<ChunkManager>();
+        if chunk_manager.pending_count() == 0 && !chunk_manager.loaded_chunks.is_empty() {
+            println!(
+                "Terrain ready: {} chunks in {:.1} ms",
+                chunk_manager.loaded_chunks.len(),
+                warmup_start.elapsed().as_secs_f64() * 1000.0
+            );
+            break;
+        }
+        if warmup_start.elapsed() > WARMUP_TIMEOUT {
+            println!("Terrain still loading after {:?}, benchmarking anyway", WARMUP_TIMEOUT);
+            break;
+        }
+    }
+
+    // One tick of gameplay in SimSet order, then the terrain streaming that follows the ball
+    let world = app.world_mut();
+    let mut systems = vec![
+        TimedSystem::new("script_input", script_input, world),
+        TimedSystem::new("keep_projectiles_flying", keep_projectiles_flying, world),
+        TimedSystem::new("move_player", move_player, world),
+        TimedSystem::new("apply_physics", apply_physics, world),
+        TimedSystem::new("update_projectiles", update_projectiles, world),
+        TimedSystem::new("advance_simulation", advance_simulation, world),
+        TimedSystem::new("manage_terrain_chunks", manage_terrain_chunks, world),
+        TimedSystem::new("generate_pending_chunks", generate_pending_chunks, world),
+    ];
+
+    let tick = Duration::from_secs_f64(1.0 / SIM_TICK_RATE);
+    let run_start = Instant::now();
+    for _ in 0..config.steps {
+        // Every system sees exactly one tick of time, however long the last one took to compute
+        world.resource_mut::<Time>().advance_by(tick);
+        for timed in systems.iter_mut() {
+            timed.run(world);
+        }
+        world.resource_mut::<Events<ProjectileHitEvent>>().update();
+        world.resource_mut::<Events<ProjectileImpactEvent>>().update();
+    }
+    let run_time = run_start.elapsed();
+
+    let measured: Duration = systems.iter().map(|timed| timed.total).sum();
+    let steps = config.steps.max(1);
+    println!("{:<26}{:>12}{:>12}{:>12}{:>8}", "system", "total ms", "mean us", "worst us", "share");
+    for timed in systems.iter() {
+        println!(
+            "{:<26}{:>12.2}{:>12.1}{:>12.1}{:>7.1}%",
+            timed.name,
+            timed.total.as_secs_f64() * 1000.0,
+            timed.total.as_secs_f64() * 1_000_000.0 / steps as f64,
+            timed.worst.as_secs_f64() * 1_000_000.0,
+            timed.total.as_secs_f64() * 100.0 / measured.as_secs_f64().max(f64::EPSILON)
+        );
+    }
+    let projectiles = world.query::<&Projectile>().iter(world).count();
+    let chunk_manager = world.resource::<ChunkManager>();
+    println!(
+        "{} steps in {:.1} ms ({:.0} steps/s), {} boulders and {} chunks at the end",
+        config.steps,
+        run_time.as_secs_f64() * 1000.0,
+        config.steps as f64 / run_time.as_secs_f64().max(f64::EPSILON),
+        projectiles,
+        chunk_manager.loaded_chunks.len()
+    );
+}
@@ -0,0 +1,441 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+use std::f32::consts::TAU;
+use crate::terrain::get_terrain_height;
+use crate::player::{Player, PlayerPhysics};
+use crate::projectile::ProjectileImpactEvent;
+use crate::catapult::Catapult;
+use crate::teleport::Teleporter;
+use crate::score::{spawn_score_popup, Score};
+use crate::photo_mode::PhotoModeState;
+use crate::sim::{placement_rng, SimSet};
+use crate::spatial::{Spatial, SpatialGrid};
+use crate::mods::ModPacks;
+use crate::GameState;
+
+// Block structure constants
+const BLOCK_SIZE: f32 = 0.8; // Edge length of a block
+const BLOCK_HALF: f32 = BLOCK_SIZE * 0.5;
+const TOWER_HEIGHT: usize = 7; // Blocks stacked in a tower
+const WALL_WIDTH: usize = 6; // Columns in a wall
+const WALL_HEIGHT: usize = 3; // Blocks stacked in each wall column
+const STRUCTURE_MIN_DISTANCE: f32 = 8.0; // Closest a structure is built to its landmark
+const STRUCTURE_MAX_DISTANCE: f32 = 12.0; // Farthest a structure is built from its landmark
+const BLAST_IMPULSE: f32 = 7.0; // Speed a block is thrown with by a boulder landing next to it
+const RAM_SPEED: f32 = 3.0; // Player speed needed to knock a block loose by rolling into it
+const RAM_TRANSFER: f32 = 0.8; // Share of the player's speed into a block passed on to it
+const KNOCK_TRANSFER: f32 = 0.7; // Share of a moving block's speed passed on to a block it hits
+const SPIN_PER_SPEED: f32 = 1.5; // Tumble rate per unit of speed a block is knocked with
+const BLOCK_GRAVITY: f32 = 9.8;
+const BLOCK_FRICTION: f32 = 0.85; // Speed kept per tick while touching the ground or another block
+const SETTLE_SPEED: f32 = 0.2; // Blocks slower than this come to rest
+const STACK_IMPACT_SPEED: f32 = 3.0; // Blocks landing on a resting block slower than this settle on it rather than knock it
+const SUPPORT_TOLERANCE: f32 = 0.05; // Gap below a block still counted as resting on something
+const TOPPLE_DISTANCE: f32 = 0.5; // Distance a block must move from where it was built to count as toppled
+const TOPPLE_TILT: f32 = 0.6; // Tilt in radians that counts as toppled
+const BLOCK_POINTS: u32 = 25; // Score per block the player topples
+const PLAYER_RADIUS: f32 = 0.5;
+const BLOCK_STREAM: u64 = 10; // Simulation random stream for placing structures
+const STRUCTURES_FILE: &str = "structures.ron"; // Structure shapes file in a mod pack
+
+// Shapes a knockdown structure is built in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StructureKind {
+    Tower,
+    Wall,
+    // A shape from a mod pack, by its index in StructureDefinitions
+    Custom(usize),
+}
+
+// A structure shape from a mod pack's structures.ron, as columns of blocks standing on the ground
+#[derive(Deserialize, Debug, Clone)]
+pub struct StructureDefinition {
+    pub name: String,
+    // Each column's offset from the structure's center, across and along its facing in block widths,
+    // and how many blocks it stacks
+    pub columns: Vec<(f32, f32, usize)>,
+}
+
+// Structure shapes added by mod packs, built alongside towers and walls
+#[derive(Resource, Default)]
+pub struct StructureDefinitions(pub Vec<StructureDefinition>);
+
+// One block of a knockdown structure; rests until struck, then tumbles until it settles again
+#[derive(Component)]
+pub struct Block {
+    pub velocity: Vec3,
+    // Angular velocity as a scaled axis
+    pub spin: Vec3,
+    pub resting: bool,
+    // Where the block was built, for telling when it has been toppled
+    pub home: Vec3,
+    pub toppled: bool,
+    // Whether the player set this block moving, directly or through other blocks, so only their knocks score
+    pub by_player: bool,
+}
+
+impl Block {
+    // Set the block moving with an impulse, tipping it over in the direction it is pushed
+    fn knock(&mut self, impulse: Vec3, by_player: bool) {
+        self.resting = false;
+        self.velocity += impulse;
+        self.spin += Vec3::Y.cross(impulse.with_y(0.0)) * SPIN_PER_SPEED;
+        self.by_player |= by_player;
+    }
+}
+
+// Shared mesh and materials for blocks
+#[derive(Resource)]
+pub struct BlockAssets {
+    pub mesh: Handle<Mesh>,
+    // Alternated between rows so the layers read clearly
+    pub materials: [Handle<StandardMaterial>; 2],
+}
+
+// Point on a block's box nearest to a world position
+fn closest_point(transform: &Transform, point: Vec3) -> Vec3 {
+    let local = transform.rotation.inverse() * (point - transform.translation);
+    transform.translation + transform.rotation * local.clamp(Vec3::splat(-BLOCK_HALF), Vec3::splat(BLOCK_HALF))
+}
+
+// Stack a column of blocks on the terrain
+fn spawn_column(commands: &mut Commands, assets: &BlockAssets, x: f32, z: f32, height: usize, yaw: f32) {
+    let ground = get_terrain_height(x, z);
+    for level in 0..height {
+        let position = Vec3::new(x, ground + BLOCK_HALF + level as f32 * BLOCK_SIZE, z);
+        commands.spawn((
+            Block {
+                velocity: Vec3::ZERO,
+                spin: Vec3::ZERO,
+                resting: true,
+                home: position,
+                toppled: false,
+                by_player: false,
+            },
+            Spatial::new(Vec3::splat(BLOCK_HALF).length()),
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(assets.materials[level % 2].clone()),
+            Transform::from_translation(position).with_rotation(Quat::from_rotation_y(yaw)),
+            Name::new("Block"),
+        ));
+    }
+}
+
+// Build a structure of blocks centered on a point
+fn spawn_structure(
+    commands: &mut Commands,
+    assets: &BlockAssets,
+    definitions: &StructureDefinitions,
+    kind: StructureKind,
+    center: Vec2,
+    yaw: f32,
+) {
+    let along = Vec2::new(yaw.cos(), -yaw.sin());
+    match kind {
+        StructureKind::Tower => spawn_column(commands, assets, center.x, center.y, TOWER_HEIGHT, yaw),
+        StructureKind::Wall => {
+            for column in 0..WALL_WIDTH {
+                let offset = (column as f32 - (WALL_WIDTH - 1) as f32 * 0.5) * BLOCK_SIZE;
+                let spot = center + along * offset;
+                spawn_column(commands, assets, spot.x, spot.y, WALL_HEIGHT, yaw);
+            }
+        }
+        StructureKind::Custom(index) => {
+            let Some(definition) = definitions.0.get(index) else {
+                return;
+            };
+            let facing = Vec2::new(yaw.sin(), yaw.cos());
+            for &(across, forward, height) in &definition.columns {
+                let spot = center + (along * across + facing * forward) * BLOCK_SIZE;
+                spawn_column(commands, assets, spot.x, spot.y, height, yaw);
+            }
+        }
+    }
+}
+
+// Create the shared block mesh and materials
+pub fn setup_block_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut material = |color: Color| materials.add(StandardMaterial {
+        base_color: color,
+        perceptual_roughness: 0.8,
+        ..default()
+    });
+    commands.insert_resource(BlockAssets {
+        mesh: meshes.add(Cuboid::from_length(BLOCK_SIZE)),
+        materials: [material(Color::srgb(0.85, 0.75, 0.55)), material(Color::srgb(0.7, 0.35, 0.25))],
+    });
+}
+
+// Read the structure shapes from every mod pack's structures.ron
+pub fn load_structure_definitions(mut commands: Commands, packs: Option<Res<ModPacks>>) {
+    let mut definitions = Vec::new();
+    for path in packs.map(|packs| packs.files(STRUCTURES_FILE)).unwrap_or_default() {
+        let loaded = std::fs::read_to_string(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|contents| ron::from_str::<Vec<StructureDefinition>>(&contents).map_err(|error| error.to_string()));
+        match loaded {
+            Ok(loaded) => {
+                for definition in &loaded {
+                    println!("Loaded structure {} from {}", definition.name, path.display());
+                }
+                definitions.extend(loaded);
+            }
+            Err(error) => println!("Invalid structures {}: {}", path.display(), error),
+        }
+    }
+    commands.insert_resource(StructureDefinitions(definitions));
+}
+
+// Build a structure near each new landmark
+pub fn spawn_block_structures(
+    mut commands: Commands,
+    assets: Res<BlockAssets>,
+    definitions: Res<StructureDefinitions>,
+    landmark_query: Query<&Transform, Or<(Added<Catapult>, Added<Teleporter>)>>,
+) {
+    for transform in landmark_query.iter() {
+        let mut rng = placement_rng(transform.translation, BLOCK_STREAM);
+        let angle = rng.gen_range(0.0..TAU);
+        let distance = rng.gen_range(STRUCTURE_MIN_DISTANCE..STRUCTURE_MAX_DISTANCE);
+        let center = Vec2::new(transform.translation.x, transform.translation.z) + Vec2::from_angle(angle) * distance;
+        // Without mod shapes this draws from the stream as it always has, so worlds stay the same
+        let kind = if definitions.0.is_empty() {
+            if rng.gen_bool(0.5) { StructureKind::Tower } else { StructureKind::Wall }
+        } else {
+            match rng.gen_range(0..definitions.0.len() + 2) {
+                0 => StructureKind::Tower,
+                1 => StructureKind::Wall,
+                index => StructureKind::Custom(index - 2),
+            }
+        };
+        spawn_structure(&mut commands, &assets, &definitions, kind, center, rng.gen_range(0.0..TAU));
+    }
+}
+
+// Remove every block at the end of a run
+pub fn despawn_blocks(mut commands: Commands, query: Query<Entity, With<Block>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Knock blocks caught in a boulder's blast away from the impact
+pub fn blast_blocks(
+    mut impact_events: EventReader<ProjectileImpactEvent>,
+    spatial_grid: Res<SpatialGrid>,
+    mut block_query: Query<(&Transform, &mut Block)>,
+) {
+    for event in impact_events.read() {
+        for entity in spatial_grid.nearby(event.position, event.radius) {
+            let Ok((transform, mut block)) = block_query.get_mut(entity) else {
+                continue;
+            };
+            let distance = closest_point(transform, event.position).distance(event.position);
+            if distance > event.radius {
+                continue;
+            }
+            let strength = 1.0 - distance / event.radius;
+            let away = (transform.translation - event.position).with_y(0.0).normalize_or_zero();
+            block.knock((away + Vec3::Y * 0.5) * BLAST_IMPULSE * strength, !event.hostile);
+        }
+    }
+}
+
+// Blocks stop the player; rolling into one fast enough knocks it loose
+pub fn ram_blocks(
+    mut player_query: Query<(&mut Transform, &mut PlayerPhysics), With<Player>>,
+    mut block_query: Query<(&Transform, &mut Block), Without<Player>>,
+    spatial_grid: Res<SpatialGrid>,
+) {
+    let Ok((mut player_transform, mut physics)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for entity in spatial_grid.nearby(player_transform.translation, PLAYER_RADIUS) {
+        let Ok((transform, mut block)) = block_query.get_mut(entity) else {
+            continue;
+        };
+        let nearest = closest_point(transform, player_transform.translation);
+        let offset = player_transform.translation - nearest;
+        if offset.length() >= PLAYER_RADIUS {
+            continue;
+        }
+        let normal = offset.normalize_or(Vec3::Y);
+
+        // Speed into the block; a fast enough hit hands part of it on, a slow one just stops
+        let closing_speed = -physics.velocity.dot(normal);
+        if closing_speed > 0.0 {
+            if closing_speed >= RAM_SPEED {
+                block.knock(-normal * closing_speed * RAM_TRANSFER, true);
+                physics.velocity += normal * closing_speed * RAM_TRANSFER;
+            } else {
+                physics.velocity += normal * closing_speed;
+            }
+            physics.momentum = physics.velocity;
+        }
+        player_transform.translation = nearest + normal * PLAYER_RADIUS;
+    }
+}
+
+// Wake resting blocks that have lost what was holding them up
+pub fn wake_unsupported_blocks(mut block_query: Query<(&Transform, &mut Block)>) {
+    let supports: Vec<Vec3> = block_query
+        .iter()
+        .filter(|(_, block)| block.resting)
+        .map(|(transform, _)| transform.translation)
+        .collect();
+
+    for (transform, mut block) in block_query.iter_mut().filter(|(_, block)| block.resting) {
+        let position = transform.translation;
+        let ground = get_terrain_height(position.x, position.z) + BLOCK_HALF;
+        if position.y - ground <= SUPPORT_TOLERANCE {
+            continue;
+        }
+        let supported = supports.iter().any(|below| {
+            let drop = position.y - below.y;
+            let horizontal = Vec2::new(position.x - below.x, position.z - below.z).length();
+            drop > BLOCK_HALF && drop <= BLOCK_SIZE + SUPPORT_TOLERANCE && horizontal < BLOCK_HALF
+        });
+        if !supported {
+            block.resting = false;
+        }
+    }
+}
+
+// Let moving blocks fall and tumble, settling once they come to a stop on the ground
+pub fn move_blocks(mut block_query: Query<(&mut Transform, &mut Block)>, time: Res<Time>) {
+    let delta = time.delta_secs();
+    for (mut transform, mut block) in block_query.iter_mut().filter(|(_, block)| !block.resting) {
+        block.velocity.y -= BLOCK_GRAVITY * delta;
+        transform.translation += block.velocity * delta;
+        transform.rotation = Quat::from_scaled_axis(block.spin * delta) * transform.rotation;
+
+        let ground = get_terrain_height(transform.translation.x, transform.translation.z) + BLOCK_HALF;
+        if transform.translation.y <= ground {
+            transform.translation.y = ground;
+            block.velocity.y = block.velocity.y.max(0.0);
+            block.velocity *= BLOCK_FRICTION;
+            block.spin *= BLOCK_FRICTION;
+            if block.velocity.length() < SETTLE_SPEED {
+                block.velocity = Vec3::ZERO;
+                block.spin = Vec3::ZERO;
+                block.resting = true;
+            }
+        }
+    }
+}
+
+// Separate overlapping blocks and pass a moving block's speed on to the blocks it runs into
+pub fn collide_blocks(mut block_query: Query<(&mut Transform, &mut Block)>) {
+    let mut pairs = block_query.iter_combinations_mut();
+    while let Some([(mut transform_a, mut a), (mut transform_b, mut b)]) = pairs.fetch_next() {
+        if a.resting && b.resting {
+            continue;
+        }
+        let offset = transform_b.translation - transform_a.translation;
+        let distance = offset.length();
+        if distance >= BLOCK_SIZE || distance < 0.0001 {
+            continue;
+        }
+        let normal = offset / distance;
+        let overlap = BLOCK_SIZE - distance;
+
+        // Speed the blocks are closing at along the line between them
+        let approach = (a.velocity - b.velocity).dot(normal);
+        let moving_on_top = match (a.resting, b.resting) {
+            (true, false) => normal.y > 0.7,
+            (false, true) => normal.y < -0.7,
+            _ => false,
+        };
+        if moving_on_top && approach < STACK_IMPACT_SPEED {
+            // A gentle landing on a resting block settles onto it like it would on the ground
+            let moving = if a.resting { &mut b } else { &mut a };
+            let into = moving.velocity.dot(normal);
+            moving.velocity -= normal * into;
+            if moving.velocity.length() < SETTLE_SPEED {
+                moving.velocity = Vec3::ZERO;
+                moving.spin = Vec3::ZERO;
+                moving.resting = true;
+            }
+        } else if approach > 0.0 {
+            let impulse = normal * approach * KNOCK_TRANSFER;
+            if b.resting {
+                b.knock(impulse, a.by_player);
+            } else {
+                b.velocity += impulse;
+                b.by_player |= a.by_player;
+            }
+            if a.resting {
+                a.knock(-impulse, b.by_player);
+            } else {
+                a.velocity -= impulse;
+                a.by_player |= b.by_player;
+            }
+        }
+
+        // Push apart; a resting block holds its place and the moving one gives way
+        match (a.resting, b.resting) {
+            (true, _) => transform_b.translation += normal * overlap,
+            (_, true) => transform_a.translation -= normal * overlap,
+            _ => {
+                transform_a.translation -= normal * overlap * 0.5;
+                transform_b.translation += normal * overlap * 0.5;
+            }
+        }
+        for block in [&mut a, &mut b] {
+            if !block.resting {
+                block.velocity *= BLOCK_FRICTION;
+                block.spin *= BLOCK_FRICTION;
+            }
+        }
+    }
+}
+
+// Award score for each block the player has knocked out of place
+pub fn score_toppled_blocks(
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    mut block_query: Query<(&Transform, &mut Block)>,
+) {
+    for (transform, mut block) in block_query.iter_mut().filter(|(_, block)| !block.toppled) {
+        let moved = transform.translation.distance(block.home) > TOPPLE_DISTANCE;
+        let tilted = (transform.rotation * Vec3::Y).angle_between(Vec3::Y) > TOPPLE_TILT;
+        if !moved && !tilted {
+            continue;
+        }
+        block.toppled = true;
+        if block.by_player {
+            score.points += BLOCK_POINTS;
+            spawn_score_popup(&mut commands, transform.translation, format!("+{}", BLOCK_POINTS));
+        }
+    }
+}
+
+// Plugin for knockdown block structures
+pub struct BlockPlugin;
+
+impl Plugin for BlockPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, (setup_block_assets, load_structure_definitions))
+            .add_systems(OnExit(GameState::GameOver), despawn_blocks)
+            .add_systems(Update, spawn_block_structures)
+            .add_systems(FixedUpdate, (
+                blast_blocks,
+                ram_blocks,
+                wake_unsupported_blocks,
+                move_blocks,
+                collide_blocks,
+                score_toppled_blocks,
+            )
+                .chain()
+                .in_set(SimSet::Constraints)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)));
+    }
+}
@@ -0,0 +1,527 @@
+use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::TAU;
+use crate::terrain::{add_deformation, clear_deformations, get_terrain_height, ChunkManager, Deformation};
+use crate::player::{player_spawn_position, Health, Player, PlayerPhysics};
+use crate::projectile::{solve_ballistic, spawn_boulder, vary_launch, BoulderLaunch, Hostile, ProjectileAssets, ProjectileImpactEvent};
+use crate::sim::{SimSet, Simulation};
+use crate::camera::CameraShake;
+use crate::score::{spawn_score_popup, Score};
+use crate::game_events::GameEvent;
+use crate::difficulty::Difficulty;
+use crate::photo_mode::PhotoModeState;
+use crate::minimap::MinimapBlip;
+use crate::compass::CompassMarker;
+use crate::audio::{PlaySound, SoundEffect};
+use crate::tuning::LiveTuning;
+use crate::{GameMode, GameState, RunStart};
+
+// Boss constants
+const BOSS_NAME: &str = "The Boulder King";
+const BOSS_RADIUS: f32 = 2.5; // Radius of the boss ball
+const BOSS_HEALTH: f32 = 300.0; // Hit points at the start of the fight
+const BOSS_START_DISTANCE: f32 = 20.0; // Distance from the spawn point the boss starts
+const ARENA_RADIUS: f32 = 40.0; // Radius of the arena around the spawn point
+const ARENA_BOUNCE: f32 = 0.5; // Fraction of outward speed turned back at the boundary
+const ARENA_SEGMENTS: usize = 64; // Line segments in the drawn boundary
+const BOSS_PLAYER_RADIUS: f32 = 0.5;
+const BOSS_GRAVITY: f32 = 20.0; // Heavier than the player so slams land hard
+const PROWL_SPEED: f32 = 2.0; // Speed the boss rolls toward the player between attacks
+const IDLE_TIME: f32 = 2.0; // Seconds between attacks
+const WIND_UP_TIME: f32 = 0.8; // Seconds the boss shudders before charging
+const CHARGE_SPEED: f32 = 14.0; // Speed of a charging roll
+const CHARGE_TIME: f32 = 1.5; // Seconds a charge lasts
+const BARRAGE_SHOTS: u32 = 5; // Boulders in a barrage
+const BARRAGE_INTERVAL: f32 = 0.3; // Seconds between barrage shots
+const BARRAGE_SPREAD: f32 = 4.0; // Distance around the player barrage shots scatter
+const BARRAGE_FLIGHT_TIME: f32 = 1.4; // Seconds a barrage boulder is in the air
+const BARRAGE_BLAST_RADIUS: f32 = 2.0; // Impact radius of the boss's boulders
+const SLAM_AIR_TIME: f32 = 1.2; // Seconds a slam leap spends in the air
+const SLAM_RADIUS: f32 = 6.0; // Reach of a slam's shockwave
+const SLAM_DAMAGE: f32 = 30.0; // Health taken by a slam at point blank range
+const SLAM_LIFT: f32 = 6.0; // Upward speed a slam throws the player with
+const CRATER_RADIUS: f32 = 5.0; // Radius of the crater a slam leaves
+const CRATER_DEPTH: f32 = 1.2; // Depth of the crater a slam leaves
+const CHARGE_DAMAGE: f32 = 20.0; // Health taken by being run over mid-charge
+const BUMP_DAMAGE: f32 = 5.0; // Health taken by touching the boss otherwise
+const CONTACT_COOLDOWN: f32 = 1.0; // Seconds before the boss can hurt the player by contact again
+const KNOCKBACK_SPEED: f32 = 9.0; // Horizontal speed the boss knocks the player away with
+const BOULDER_DAMAGE: f32 = 10.0; // Boss health taken per player boulder
+const DEFEAT_POINTS: u32 = 5000; // Score for beating the boss
+const BOSS_COLOR: Color = Color::srgb(0.35, 0.3, 0.28);
+const ARENA_COLOR: Color = Color::srgb(1.0, 0.4, 0.2);
+const BOSS_STREAM: u64 = 2; // Simulation random stream for placing the boss
+const BOSS_ATTACK_STREAM: u64 = 14; // Simulation random stream for barrage shots
+
+// Attacks unlock as the boss loses health
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BossPhase {
+    // Charging rolls only
+    Charging,
+    // Charges and boulder barrages
+    Barrage,
+    // Charges, barrages and terrain-shattering slams
+    Slam,
+}
+
+impl BossPhase {
+    pub fn number(self) -> u32 {
+        match self {
+            BossPhase::Charging => 1,
+            BossPhase::Barrage => 2,
+            BossPhase::Slam => 3,
+        }
+    }
+}
+
+// What the boss is doing right now
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BossAction {
+    // Rolling toward the player until the next attack
+    Prowl { timer: f32 },
+    // Shuddering in place before a charge
+    WindUp { timer: f32, direction: Vec3 },
+    // Rolling flat out in a straight line
+    Charge { timer: f32, direction: Vec3 },
+    // Lobbing boulders around the player
+    Barrage { shots_left: u32, timer: f32 },
+    // Leaping to come down on the player
+    Slam { vertical_speed: f32, horizontal: Vec3 },
+}
+
+// The boss ball
+#[derive(Component)]
+pub struct Boss {
+    pub health: f32,
+    pub action: BossAction,
+    // Attacks made so far, for cycling through the phase's attacks
+    pub attacks: u32,
+    // Seconds until contact can hurt the player again
+    pub contact_cooldown: f32,
+}
+
+impl Boss {
+    pub fn phase(&self) -> BossPhase {
+        let fraction = self.health / BOSS_HEALTH;
+        if fraction > 2.0 / 3.0 {
+            BossPhase::Charging
+        } else if fraction > 1.0 / 3.0 {
+            BossPhase::Barrage
+        } else {
+            BossPhase::Slam
+        }
+    }
+
+    // The next attack, cycling through those the phase allows
+    fn next_attack(&mut self, to_player: Vec3) -> BossAction {
+        let attacks = self.phase().number();
+        let choice = self.attacks % attacks;
+        self.attacks += 1;
+        match choice {
+            0 => BossAction::WindUp { timer: WIND_UP_TIME, direction: to_player.normalize_or(Vec3::X) },
+            1 => BossAction::Barrage { shots_left: BARRAGE_SHOTS, timer: 0.0 },
+            // Leap so as to come down where the player is now
+            _ => BossAction::Slam {
+                vertical_speed: 0.5 * BOSS_GRAVITY * SLAM_AIR_TIME,
+                horizontal: to_player / SLAM_AIR_TIME,
+            },
+        }
+    }
+}
+
+// The circle the fight is confined to
+#[derive(Resource, Default)]
+pub struct BossArena {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+// Marker for the boss health bar panel
+#[derive(Component)]
+pub struct BossHudRoot;
+
+// Marker for the boss health bar fill
+#[derive(Component)]
+pub struct BossHudBar;
+
+// Marker for the boss name and phase text
+#[derive(Component)]
+pub struct BossHudText;
+
+// Keep a ball inside the arena, turning back the part of its velocity heading out
+fn confine_to_arena(arena: &BossArena, position: &mut Vec3, velocity: &mut Vec3, radius: f32) {
+    let offset = Vec2::new(position.x - arena.center.x, position.z - arena.center.z);
+    let limit = arena.radius - radius;
+    if offset.length() <= limit {
+        return;
+    }
+    let outward = offset.normalize();
+    position.x = arena.center.x + outward.x * limit;
+    position.z = arena.center.z + outward.y * limit;
+    let outward = Vec3::new(outward.x, 0.0, outward.y);
+    let speed_out = velocity.dot(outward);
+    if speed_out > 0.0 {
+        *velocity -= outward * speed_out * (1.0 + ARENA_BOUNCE);
+    }
+}
+
+// Put the boss in the arena when a boss fight starts
+pub fn spawn_boss(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut arena: ResMut<BossArena>,
+    mode: Res<GameMode>,
+    simulation: Res<Simulation>,
+) {
+    if *mode != GameMode::BossFight {
+        return;
+    }
+    let spawn = player_spawn_position();
+    *arena = BossArena { center: spawn, radius: ARENA_RADIUS };
+
+    let angle = simulation.rng(BOSS_STREAM).gen_range(0.0..TAU);
+    let x = spawn.x + angle.cos() * BOSS_START_DISTANCE;
+    let z = spawn.z + angle.sin() * BOSS_START_DISTANCE;
+    commands.spawn((
+        Boss {
+            health: BOSS_HEALTH,
+            action: BossAction::Prowl { timer: IDLE_TIME * 2.0 },
+            attacks: 0,
+            contact_cooldown: 0.0,
+        },
+        Mesh3d(meshes.add(Sphere::new(BOSS_RADIUS))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: BOSS_COLOR,
+            perceptual_roughness: 0.95,
+            ..default()
+        })),
+        Transform::from_xyz(x, get_terrain_height(x, z) + BOSS_RADIUS, z),
+        Name::new(BOSS_NAME),
+        MinimapBlip { color: Color::srgb(1.0, 0.1, 0.1) },
+        CompassMarker { color: Color::srgb(1.0, 0.1, 0.1) },
+    ));
+}
+
+// Remove the boss and fill in its craters at the end of a run
+pub fn despawn_boss(
+    mut commands: Commands,
+    mut chunk_manager: ResMut<ChunkManager>,
+    boss_query: Query<Entity, With<Boss>>,
+) {
+    for entity in boss_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    clear_deformations(&mut commands, &mut chunk_manager);
+}
+
+// Run the boss's attack pattern and move it over the terrain
+pub fn update_boss(
+    mut commands: Commands,
+    mut projectile_assets: ResMut<ProjectileAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut chunk_manager: ResMut<ChunkManager>,
+    mut camera_shake: ResMut<CameraShake>,
+    arena: Res<BossArena>,
+    difficulty: Res<Difficulty>,
+    mut boss_query: Query<(&mut Transform, &mut Boss), Without<Player>>,
+    mut player_query: Query<(&Transform, &mut PlayerPhysics, &mut Health), With<Player>>,
+    mut sounds: EventWriter<PlaySound>,
+    simulation: Res<Simulation>,
+    tuning: Res<LiveTuning>,
+    time: Res<Time>,
+) {
+    let Ok((mut transform, mut boss)) = boss_query.get_single_mut() else {
+        return;
+    };
+    let Ok((player_transform, mut player_physics, mut player_health)) = player_query.get_single_mut() else {
+        return;
+    };
+    let delta = time.delta_secs();
+    let player_pos = player_transform.translation;
+    let position = transform.translation;
+    let to_player = Vec3::new(player_pos.x - position.x, 0.0, player_pos.z - position.z);
+    let ground = get_terrain_height(position.x, position.z) + BOSS_RADIUS;
+    let mut velocity = Vec3::ZERO;
+    let mut airborne = false;
+
+    boss.action = match boss.action {
+        BossAction::Prowl { timer } => {
+            velocity = to_player.normalize_or(Vec3::X) * PROWL_SPEED;
+            if timer - delta <= 0.0 {
+                boss.next_attack(to_player)
+            } else {
+                BossAction::Prowl { timer: timer - delta }
+            }
+        }
+        BossAction::WindUp { timer, direction } => {
+            // Shudder in place so the charge can be read coming
+            transform.translation.x += (simulation.elapsed() * 60.0).sin() * 0.05;
+            if timer - delta <= 0.0 {
+                BossAction::Charge { timer: CHARGE_TIME, direction }
+            } else {
+                BossAction::WindUp { timer: timer - delta, direction }
+            }
+        }
+        BossAction::Charge { timer, direction } => {
+            velocity = direction * CHARGE_SPEED;
+            if timer - delta <= 0.0 {
+                BossAction::Prowl { timer: IDLE_TIME }
+            } else {
+                BossAction::Charge { timer: timer - delta, direction }
+            }
+        }
+        BossAction::Barrage { shots_left, timer } => {
+            if shots_left == 0 {
+                BossAction::Prowl { timer: IDLE_TIME }
+            } else if timer - delta > 0.0 {
+                BossAction::Barrage { shots_left, timer: timer - delta }
+            } else {
+                let mut rng = simulation.rng(BOSS_ATTACK_STREAM);
+                let angle = rng.gen_range(0.0..TAU);
+                let spread = rng.gen_range(0.0..BARRAGE_SPREAD);
+                let x = player_pos.x + angle.cos() * spread;
+                let z = player_pos.z + angle.sin() * spread;
+                let landing = Vec3::new(x, get_terrain_height(x, z), z);
+                let start = position + Vec3::Y * BOSS_RADIUS;
+                let boulder = spawn_boulder(
+                    &mut commands,
+                    &mut projectile_assets,
+                    &mut materials,
+                    &tuning.0.projectile,
+                    BoulderLaunch {
+                        start,
+                        target: landing,
+                        velocity: vary_launch(solve_ballistic(&tuning.0.projectile, start, landing, BARRAGE_FLIGHT_TIME), &mut rng),
+                        color: BOSS_COLOR,
+                        blast_radius: BARRAGE_BLAST_RADIUS,
+                    },
+                );
+                commands.entity(boulder).insert(Hostile);
+                sounds.send(PlaySound { effect: SoundEffect::Launch, position: start });
+                BossAction::Barrage { shots_left: shots_left - 1, timer: BARRAGE_INTERVAL / difficulty.enemy_fire_rate() }
+            }
+        }
+        BossAction::Slam { vertical_speed, horizontal } => {
+            airborne = true;
+            velocity = horizontal + Vec3::Y * vertical_speed;
+            let vertical_speed = vertical_speed - BOSS_GRAVITY * delta;
+            let landed = vertical_speed < 0.0 && position.y + vertical_speed * delta <= ground;
+            if landed {
+                // Shatter the ground and throw the player clear
+                add_deformation(&mut commands, &mut chunk_manager, Deformation {
+                    center: Vec2::new(position.x, position.z),
+                    radius: CRATER_RADIUS,
+                    depth: CRATER_DEPTH,
+                });
+                camera_shake.add_trauma(0.8);
+                sounds.send(PlaySound { effect: SoundEffect::BossSlam, position });
+                let offset = player_pos - position;
+                let distance = Vec2::new(offset.x, offset.z).length();
+                if distance < SLAM_RADIUS {
+                    let strength = 1.0 - distance / SLAM_RADIUS;
+                    player_health.current -= SLAM_DAMAGE * strength * difficulty.damage_taken();
+                    let away = Vec3::new(offset.x, 0.0, offset.z).normalize_or(Vec3::X);
+                    let knock = away * KNOCKBACK_SPEED * strength + Vec3::Y * SLAM_LIFT;
+                    player_physics.velocity += knock;
+                    player_physics.momentum += knock;
+                    player_physics.grounded = false;
+                }
+                BossAction::Prowl { timer: IDLE_TIME }
+            } else {
+                BossAction::Slam { vertical_speed, horizontal }
+            }
+        }
+    };
+
+    // Move, keeping to the ground unless mid-leap, and inside the arena
+    let mut next = transform.translation + velocity * delta;
+    let next_ground = get_terrain_height(next.x, next.z) + BOSS_RADIUS;
+    if !airborne || next.y < next_ground {
+        next.y = next_ground;
+    }
+    confine_to_arena(&arena, &mut next, &mut velocity, BOSS_RADIUS);
+    let travelled = Vec3::new(next.x - transform.translation.x, 0.0, next.z - transform.translation.z);
+    transform.translation = next;
+    if travelled.length_squared() > 0.0001 {
+        // Roll over the ground like the player ball does
+        let axis = Vec3::new(travelled.z, 0.0, -travelled.x).normalize();
+        transform.rotate(Quat::from_axis_angle(axis, travelled.length() / BOSS_RADIUS));
+    }
+
+    // Contact: separate the balls and knock the player away, hard if mid-charge
+    boss.contact_cooldown = (boss.contact_cooldown - delta).max(0.0);
+    let offset = player_pos - transform.translation;
+    let contact_distance = BOSS_RADIUS + BOSS_PLAYER_RADIUS;
+    if offset.length() < contact_distance && boss.contact_cooldown <= 0.0 {
+        boss.contact_cooldown = CONTACT_COOLDOWN;
+        let charging = matches!(boss.action, BossAction::Charge { .. });
+        let damage = if charging { CHARGE_DAMAGE } else { BUMP_DAMAGE };
+        player_health.current -= damage * difficulty.damage_taken();
+        let away = Vec3::new(offset.x, 0.0, offset.z).normalize_or(Vec3::X);
+        let knock = away * KNOCKBACK_SPEED + Vec3::Y * 3.0;
+        player_physics.velocity += knock;
+        player_physics.momentum += knock;
+        player_physics.grounded = false;
+        camera_shake.add_trauma(if charging { 0.6 } else { 0.2 });
+    }
+}
+
+// Keep the player inside the arena during a boss fight
+pub fn enforce_arena(arena: Res<BossArena>, mut player_query: Query<(&mut Transform, &mut PlayerPhysics), With<Player>>) {
+    for (mut transform, mut physics) in player_query.iter_mut() {
+        let mut velocity = physics.velocity;
+        confine_to_arena(&arena, &mut transform.translation, &mut velocity, BOSS_PLAYER_RADIUS);
+        if velocity != physics.velocity {
+            physics.velocity = velocity;
+            physics.momentum = velocity;
+        }
+    }
+}
+
+// Draw the arena boundary hugging the terrain
+pub fn draw_arena(mut gizmos: Gizmos, arena: Res<BossArena>) {
+    let points = (0..=ARENA_SEGMENTS).map(|i| {
+        let angle = i as f32 / ARENA_SEGMENTS as f32 * TAU;
+        let x = arena.center.x + angle.cos() * arena.radius;
+        let z = arena.center.z + angle.sin() * arena.radius;
+        Vec3::new(x, get_terrain_height(x, z) + 0.3, z)
+    });
+    gizmos.linestrip(points, ARENA_COLOR);
+}
+
+// Take damage from the player's boulders and end the fight when the boss breaks
+pub fn damage_boss(
+    mut commands: Commands,
+    mut impact_events: EventReader<ProjectileImpactEvent>,
+    mut boss_query: Query<(Entity, &Transform, &mut Boss)>,
+    mut score: ResMut<Score>,
+    mut game_events: EventWriter<GameEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Ok((entity, transform, mut boss)) = boss_query.get_single_mut() else {
+        return;
+    };
+    for event in impact_events.read().filter(|event| !event.hostile) {
+        if event.position.distance(transform.translation) > event.radius + BOSS_RADIUS {
+            continue;
+        }
+        let phase = boss.phase();
+        boss.health -= BOULDER_DAMAGE;
+        spawn_score_popup(&mut commands, transform.translation + Vec3::Y * BOSS_RADIUS, format!("-{}", BOULDER_DAMAGE));
+        if boss.phase() != phase && boss.health > 0.0 {
+            game_events.send(GameEvent::BossPhase { name: BOSS_NAME.to_string(), phase: boss.phase().number() });
+        }
+    }
+
+    if boss.health <= 0.0 {
+        commands.entity(entity).despawn_recursive();
+        score.points += DEFEAT_POINTS;
+        game_events.send(GameEvent::BossDefeated { name: BOSS_NAME.to_string() });
+        next_state.set(GameState::GameOver);
+    }
+}
+
+// Build the boss health bar under the compass
+pub fn spawn_boss_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            BossHudRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(48.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                BossHudText,
+                Text::new(BOSS_NAME),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(400.0),
+                        height: Val::Px(14.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.8)),
+                ))
+                .with_children(|bar| {
+                    bar.spawn((
+                        BossHudBar,
+                        Node {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.85, 0.15, 0.1)),
+                    ));
+                });
+        });
+}
+
+// Show the health bar while playing a boss fight
+pub fn show_boss_hud(mode: Res<GameMode>, mut query: Query<&mut Visibility, With<BossHudRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = if *mode == GameMode::BossFight { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+// Hide the health bar outside of gameplay
+pub fn hide_boss_hud(mut query: Query<&mut Visibility, With<BossHudRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Fill the bar from the boss's health and name the phase
+pub fn update_boss_hud(
+    boss_query: Query<&Boss>,
+    mut bar_query: Query<&mut Node, With<BossHudBar>>,
+    mut text_query: Query<&mut Text, With<BossHudText>>,
+) {
+    let Ok(boss) = boss_query.get_single() else {
+        return;
+    };
+    for mut node in bar_query.iter_mut() {
+        node.width = Val::Percent((boss.health / BOSS_HEALTH).clamp(0.0, 1.0) * 100.0);
+    }
+    for mut text in text_query.iter_mut() {
+        text.0 = format!("{}   Phase {}/3", BOSS_NAME, boss.phase().number());
+    }
+}
+
+// Plugin for the boss fight mode
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<BossArena>()
+            .add_systems(Startup, spawn_boss_hud)
+            .add_systems(RunStart, spawn_boss)
+            .add_systems(OnExit(GameState::GameOver), despawn_boss)
+            .add_systems(OnEnter(GameState::Playing), show_boss_hud)
+            .add_systems(OnExit(GameState::Playing), hide_boss_hud)
+            .add_systems(FixedUpdate, (update_boss.in_set(SimSet::Movement), enforce_arena.in_set(SimSet::Constraints))
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive))
+                .run_if(resource_equals(GameMode::BossFight)))
+            .add_systems(Update, (damage_boss, update_boss_hud, draw_arena)
+                .chain()
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive))
+                .run_if(resource_equals(GameMode::BossFight)));
+    }
+}
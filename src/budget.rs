@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+// Bevy's clock types also work in the browser, where std's Instant panics
+use bevy::utils::{Duration, Instant};
+use crate::settings::Settings;
+
+// How much of each frame the queues of heavy work may use, from the settings file
+#[derive(Resource, Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkBudgets {
+    // Milliseconds per frame for spawning finished terrain chunks
+    pub chunk_ms: f32,
+    // Milliseconds per frame for each pass that scatters pickups, pads, ferries and hazards over new chunks
+    pub scatter_ms: f32,
+    // Seconds between writes of the save file while it keeps changing
+    pub save_interval_secs: f32,
+}
+
+impl Default for WorkBudgets {
+    fn default() -> Self {
+        Self {
+            chunk_ms: 2.0,
+            scatter_ms: 1.0,
+            save_interval_secs: 5.0,
+        }
+    }
+}
+
+// A slice of frame time that work can be done in, starting when it is made
+pub struct FrameBudget {
+    start: Instant,
+    limit: Duration,
+}
+
+impl FrameBudget {
+    pub fn start(milliseconds: f32) -> Self {
+        Self {
+            start: Instant::now(),
+            limit: Duration::from_secs_f32(milliseconds.max(0.0) / 1000.0),
+        }
+    }
+
+    // Whether the slice is used up
+    pub fn exhausted(&self) -> bool {
+        self.start.elapsed() >= self.limit
+    }
+}
+
+// Work items waiting for frame time, handled oldest first
+pub struct WorkQueue<T> {
+    items: VecDeque<T>,
+}
+
+impl<T> Default for WorkQueue<T> {
+    fn default() -> Self {
+        Self { items: VecDeque::new() }
+    }
+}
+
+impl<T> WorkQueue<T> {
+    pub fn extend(&mut self, items: impl IntoIterator<Item = T>) {
+        self.items.extend(items);
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    // Handle items until the budget runs out, always at least one so the queue never stalls
+    pub fn run(&mut self, milliseconds: f32, mut work: impl FnMut(T)) {
+        let budget = FrameBudget::start(milliseconds);
+        while let Some(item) = self.items.pop_front() {
+            work(item);
+            if budget.exhausted() {
+                break;
+            }
+        }
+    }
+}
+
+// Follow the budgets in the settings, such as after switching profiles
+pub fn apply_work_budgets(settings: Res<Settings>, mut budgets: ResMut<WorkBudgets>) {
+    *budgets = settings.work_budgets;
+}
+
+// Plugin for the frame budgets of heavy work; the Settings resource is loaded in main before it builds
+pub struct BudgetPlugin;
+
+impl Plugin for BudgetPlugin {
+    fn build(&self, app: &mut App) {
+        let budgets = app.world().get_resource::<Settings>().map(|settings| settings.work_budgets).unwrap_or_default();
+        app
+            .insert_resource(budgets)
+            .add_systems(Update, apply_work_budgets
+                .run_if(resource_changed::<Settings>.and(not(resource_added::<Settings>))));
+    }
+}
@@ -1,7 +1,23 @@
 use bevy::prelude::*;
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::render::mesh::{Indices, PrimitiveTopology};
 use bevy::window::PrimaryWindow;
-use crate::player::Player;
-use crate::terrain::get_terrain_height;
+use noise::{NoiseFn, Perlin};
+use serde::Deserialize;
+use crate::player::{Player, PlayerPhysics};
+use crate::tuning::{LiveTuning, DEFAULT_TUNING};
+use crate::projectile::{in_throw_range, ThrowCharge, PROJECTILE_BLAST_RADIUS};
+use crate::progression::Upgrades;
+use crate::terrain::{get_terrain_height, raycast_terrain};
+use crate::photo_mode::PhotoModeState;
+use crate::intro::intro_finished;
+use crate::network::spectating;
+use crate::audio::LISTENER_EAR_GAP;
+use crate::GameState;
+use crate::assets::palette::ColorPalette;
+use crate::sky::distance_fog;
+use crate::graphics::RenderScale;
+use crate::multiball::SplitBall;
 
 // Component for tracking the camera that follows the player
 #[derive(Component)]
@@ -12,35 +28,365 @@ pub struct FollowCamera;
 pub struct TargetCursor;
 
 // Resource to track mouse position and cursor target
-#[derive(Resource)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
 pub struct MouseLook {
     pub cursor_position: Vec2,
     pub target_position: Vec3,
     pub is_initialized: bool,
 }
 
+// Farthest distance along the cursor ray that can be targeted
+const CURSOR_MAX_RANGE: f32 = 150.0;
+// Cursor ring shape
+const CURSOR_RING_SEGMENTS: usize = 48; // Segments around the ring
+const CURSOR_RING_WIDTH: f32 = 0.15; // Width of the ring band
+const CURSOR_RING_LIFT: f32 = 0.05; // Height above the terrain to avoid z-fighting
+
+// Mesh and materials for the terrain-conforming cursor ring
+#[derive(Resource)]
+pub struct CursorRingAssets {
+    pub mesh: Handle<Mesh>,
+    pub in_range_material: Handle<StandardMaterial>,
+    pub out_of_range_material: Handle<StandardMaterial>,
+}
+
+// Camera configuration file, read once at startup
+const CAMERA_CONFIG_PATH: &str = "config/camera.ron";
+
+// Named camera presets, cycled with the C key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum CameraPreset {
+    Close,
+    #[default]
+    Action,
+    Tactical,
+}
+
+impl CameraPreset {
+    // The preset that follows this one when cycling
+    pub fn next(self) -> Self {
+        match self {
+            CameraPreset::Close => CameraPreset::Action,
+            CameraPreset::Action => CameraPreset::Tactical,
+            CameraPreset::Tactical => CameraPreset::Close,
+        }
+    }
+}
+
+// Values a single preset applies to the camera
+#[derive(Debug, Clone, Deserialize)]
+pub struct CameraPresetConfig {
+    // Follow distance from the player
+    pub distance: f32,
+    // Orbit pitch (radians above the horizon)
+    pub pitch: f32,
+    // How quickly the camera position follows its target
+    pub position_smoothness: f32,
+    // How much the look target leans from the player toward the cursor (0..1)
+    pub cursor_weight: f32,
+    // How quickly the camera rotation follows its look target
+    pub rotation_smoothness: f32,
+}
+
+// Contents of the camera configuration file
+#[derive(Debug, Clone, Deserialize)]
+pub struct CameraConfig {
+    pub default_preset: CameraPreset,
+    pub close: CameraPresetConfig,
+    pub action: CameraPresetConfig,
+    pub tactical: CameraPresetConfig,
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        // Action matches the original fixed offset (-3, 3.5, 6) and smoothing values
+        let offset = Vec3::new(-3.0, 3.5, 6.0);
+        let horizontal = Vec2::new(offset.x, offset.z).length();
+        Self {
+            default_preset: CameraPreset::Action,
+            close: CameraPresetConfig {
+                distance: 4.5,
+                pitch: 0.3,
+                position_smoothness: 7.0,
+                cursor_weight: 0.4,
+                rotation_smoothness: 10.0,
+            },
+            action: CameraPresetConfig {
+                distance: offset.length(),
+                pitch: offset.y.atan2(horizontal),
+                position_smoothness: 5.0,
+                cursor_weight: 0.6,
+                rotation_smoothness: 8.0,
+            },
+            tactical: CameraPresetConfig {
+                distance: 16.0,
+                pitch: 1.3,
+                position_smoothness: 3.0,
+                cursor_weight: 0.3,
+                rotation_smoothness: 5.0,
+            },
+        }
+    }
+}
+
+impl CameraConfig {
+    // Read the config file, falling back to the built-in presets if it is missing or invalid
+    pub fn load() -> Self {
+        match std::fs::read_to_string(CAMERA_CONFIG_PATH) {
+            Ok(contents) => match ron::from_str(&contents) {
+                Ok(config) => config,
+                Err(error) => {
+                    println!("Invalid camera config {}: {}", CAMERA_CONFIG_PATH, error);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+    
+    // Look up the values for a preset
+    pub fn preset(&self, preset: CameraPreset) -> &CameraPresetConfig {
+        match preset {
+            CameraPreset::Close => &self.close,
+            CameraPreset::Action => &self.action,
+            CameraPreset::Tactical => &self.tactical,
+        }
+    }
+}
+
+// Resource holding camera tuning and the persistent zoom level
+#[derive(Resource)]
+pub struct CameraSettings {
+    // Presets loaded from the config file and the one currently applied
+    pub config: CameraConfig,
+    pub preset: CameraPreset,
+    // How quickly the camera position follows its target
+    pub position_smoothness: f32,
+    // How much the look target leans from the player toward the cursor (0..1)
+    pub cursor_weight: f32,
+    // How quickly the camera rotation follows its look target
+    pub rotation_smoothness: f32,
+    // Distance the scroll wheel is steering toward
+    pub target_distance: f32,
+    // Smoothed follow distance actually applied to the camera
+    pub follow_distance: f32,
+    // Clamp range for the follow distance
+    pub min_distance: f32,
+    pub max_distance: f32,
+    // Distance change per scroll line
+    pub zoom_speed: f32,
+    // How quickly the follow distance catches up to the target distance
+    pub zoom_smoothness: f32,
+    // Orbit angles around the player (radians); yaw 0 places the camera on +Z
+    pub yaw: f32,
+    pub pitch: f32,
+    // Clamp range for the orbit pitch
+    pub min_pitch: f32,
+    pub max_pitch: f32,
+    // Radians of orbit per pixel of mouse drag
+    pub orbit_sensitivity: f32,
+    // Clearance kept between the camera and the terrain surface
+    pub collision_margin: f32,
+    // How quickly the camera backs out again once terrain stops blocking it
+    pub collision_recovery: f32,
+    // Follow distance currently allowed by terrain occlusion
+    pub collision_distance: f32,
+    // Field of view (radians) at rest and at the player's max speed
+    pub base_fov: f32,
+    pub max_speed_fov: f32,
+    // Extra trailing distance added at the player's max speed
+    pub speed_distance_bonus: f32,
+    // Exponent shaping the speed response; above 1 keeps slow rolling calm
+    pub speed_curve_exponent: f32,
+    // How quickly the speed effects follow changes in speed
+    pub speed_response: f32,
+    // Seconds of horizontal velocity the camera leads the player by
+    pub look_ahead_time: f32,
+    // Smoothed 0..1 speed factor driving FOV and trailing distance
+    pub speed_factor: f32,
+    // Height above the player for the top-down tactical view
+    pub tactical_height: f32,
+    // How quickly the camera moves into and out of the tactical view
+    pub tactical_transition_speed: f32,
+    // 0 = normal follow camera, 1 = fully in the tactical view
+    pub tactical_blend: f32,
+}
+
+impl CameraSettings {
+    // Unit direction from the player toward the camera for the current orbit angles
+    pub fn orbit_direction(&self) -> Vec3 {
+        Vec3::new(
+            self.yaw.sin() * self.pitch.cos(),
+            self.pitch.sin(),
+            self.yaw.cos() * self.pitch.cos(),
+        )
+    }
+    
+    // Rotation that turns camera-relative input into world space
+    pub fn yaw_rotation(&self) -> Quat {
+        Quat::from_rotation_y(self.yaw)
+    }
+    
+    // Apply a preset; distance and pitch are steered toward so the switch is smooth
+    pub fn apply_preset(&mut self, preset: CameraPreset) {
+        let values = self.config.preset(preset).clone();
+        self.preset = preset;
+        self.target_distance = values.distance.clamp(self.min_distance, self.max_distance);
+        self.pitch = values.pitch.clamp(self.min_pitch, self.max_pitch);
+        self.position_smoothness = values.position_smoothness;
+        self.cursor_weight = values.cursor_weight;
+        self.rotation_smoothness = values.rotation_smoothness;
+    }
+    
+    // Build settings from the config file with its default preset applied
+    pub fn load() -> Self {
+        let config = CameraConfig::load();
+        let preset = config.default_preset;
+        let mut settings = Self { config, ..default() };
+        settings.apply_preset(preset);
+        // Start at the preset distance instead of zooming in from the default
+        settings.follow_distance = settings.target_distance;
+        settings.collision_distance = settings.target_distance;
+        settings
+    }
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        // Start at the original fixed offset (-3, 3.5, 6)
+        let offset = Vec3::new(-3.0, 3.5, 6.0);
+        let distance = offset.length();
+        let horizontal = Vec2::new(offset.x, offset.z).length();
+        let tuned = DEFAULT_TUNING.camera;
+        Self {
+            config: CameraConfig::default(),
+            preset: CameraPreset::Action,
+            position_smoothness: 5.0,
+            cursor_weight: 0.6,
+            rotation_smoothness: 8.0,
+            target_distance: distance,
+            follow_distance: distance,
+            min_distance: tuned.min_distance,
+            max_distance: tuned.max_distance,
+            zoom_speed: tuned.zoom_speed,
+            zoom_smoothness: tuned.zoom_smoothness,
+            yaw: offset.x.atan2(offset.z),
+            pitch: offset.y.atan2(horizontal),
+            min_pitch: 0.1,
+            max_pitch: 1.4,
+            orbit_sensitivity: 0.005,
+            collision_margin: tuned.collision_margin,
+            collision_recovery: tuned.collision_recovery,
+            collision_distance: distance,
+            base_fov: tuned.base_fov,
+            max_speed_fov: tuned.max_speed_fov,
+            speed_distance_bonus: tuned.speed_distance_bonus,
+            speed_curve_exponent: tuned.speed_curve_exponent,
+            speed_response: tuned.speed_response,
+            look_ahead_time: tuned.look_ahead_time,
+            speed_factor: 0.0,
+            tactical_height: tuned.tactical_height,
+            tactical_transition_speed: tuned.tactical_transition_speed,
+            tactical_blend: 0.0,
+        }
+    }
+}
+
+// Trauma-based camera shake; systems add trauma and the camera turns it into decaying noise
+#[derive(Resource)]
+pub struct CameraShake {
+    // Current trauma in 0..1, shake strength is trauma squared
+    pub trauma: f32,
+    // Trauma lost per second
+    pub decay: f32,
+    // Largest positional offset at full trauma
+    pub max_offset: f32,
+    // Largest roll (radians) at full trauma
+    pub max_roll: f32,
+    // Noise sampling speed, higher is more jittery
+    pub frequency: f32,
+    // Offset and roll applied last frame, removed before smoothing
+    applied_offset: Vec3,
+    applied_roll: f32,
+    noise: Perlin,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        Self {
+            trauma: 0.0,
+            decay: 1.2,
+            max_offset: 0.4,
+            max_roll: 0.06,
+            frequency: 18.0,
+            applied_offset: Vec3::ZERO,
+            applied_roll: 0.0,
+            noise: Perlin::new(7),
+        }
+    }
+}
+
+impl CameraShake {
+    // Add trauma from an event such as a hard landing or impact
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+    
+    // Sample the shake offset and roll at the given time
+    fn sample(&self, time: f32) -> (Vec3, f32) {
+        let shake = self.trauma * self.trauma;
+        if shake <= 0.0 {
+            return (Vec3::ZERO, 0.0);
+        }
+        // Separate noise rows for each axis so they move independently
+        let t = (time * self.frequency) as f64;
+        let offset = Vec3::new(
+            self.noise.get([t, 0.5]) as f32,
+            self.noise.get([t, 10.5]) as f32,
+            self.noise.get([t, 20.5]) as f32,
+        ) * self.max_offset * shake;
+        let roll = self.noise.get([t, 30.5]) as f32 * self.max_roll * shake;
+        (offset, roll)
+    }
+}
+
 // Setup the camera and targeting cursor
 pub fn spawn_camera(commands: &mut Commands, meshes: &mut ResMut<Assets<Mesh>>, materials: &mut ResMut<Assets<StandardMaterial>>) {
     // Spawn the camera
     commands.spawn((
         Camera3d::default(),
         FollowCamera,
+        // Menus and the HUD stay on this camera, in the left half when the screen is split
+        IsDefaultUiCamera,
+        // Sound effects are heard from the camera
+        SpatialListener::new(LISTENER_EAR_GAP),
+        distance_fog(),
         Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
     ));
     
-    // Create a simple targeting cursor (small red sphere)
+    // Create the targeting cursor, a ring draped over the terrain at the blast radius
+    let ring_material = |color: Color| StandardMaterial {
+        base_color: color,
+        emissive: color.into(),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        cull_mode: None,
+        ..default()
+    };
+    let ring_assets = CursorRingAssets {
+        mesh: meshes.add(create_cursor_ring_mesh(Vec3::ZERO, PROJECTILE_BLAST_RADIUS)),
+        in_range_material: materials.add(ring_material(ColorPalette::default().in_range_color())),
+        out_of_range_material: materials.add(ring_material(ColorPalette::default().out_of_range_color())),
+    };
     commands.spawn((
         TargetCursor,
-        Mesh3d(meshes.add(Sphere::new(0.2).mesh())),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: Color::srgba(1.0, 0.0, 0.0, 0.7),
-            emissive: Color::srgba(1.0, 0.2, 0.2, 1.0).into(),
-            alpha_mode: AlphaMode::Blend,
-            ..default()
-        })),
+        Mesh3d(ring_assets.mesh.clone()),
+        MeshMaterial3d(ring_assets.out_of_range_material.clone()),
         Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
         Visibility::Hidden,
     ));
+    commands.insert_resource(ring_assets);
     
     // Initialize the MouseLook resource
     commands.insert_resource(MouseLook {
@@ -50,6 +396,41 @@ pub fn spawn_camera(commands: &mut Commands, meshes: &mut ResMut<Assets<Mesh>>,
     });
 }
 
+// Build a flat ring around center whose vertices follow the terrain surface.
+// Vertices are relative to center so the cursor transform can sit at the hit point.
+pub fn create_cursor_ring_mesh(center: Vec3, radius: f32) -> Mesh {
+    let mut positions = Vec::with_capacity(CURSOR_RING_SEGMENTS * 2);
+    let mut normals = Vec::with_capacity(CURSOR_RING_SEGMENTS * 2);
+    let mut indices = Vec::with_capacity(CURSOR_RING_SEGMENTS * 6);
+    
+    let inner_radius = (radius - CURSOR_RING_WIDTH).max(0.0);
+    for i in 0..CURSOR_RING_SEGMENTS {
+        let angle = i as f32 / CURSOR_RING_SEGMENTS as f32 * std::f32::consts::TAU;
+        let direction = Vec3::new(angle.cos(), 0.0, angle.sin());
+        for ring_radius in [inner_radius, radius] {
+            let offset = direction * ring_radius;
+            let world_x = center.x + offset.x;
+            let world_z = center.z + offset.z;
+            let y = get_terrain_height(world_x, world_z) - center.y + CURSOR_RING_LIFT;
+            positions.push([offset.x, y, offset.z]);
+            normals.push([0.0, 1.0, 0.0]);
+        }
+        
+        // Two triangles joining this segment to the next, wrapping at the end
+        let inner = (i * 2) as u32;
+        let outer = inner + 1;
+        let next_inner = (((i + 1) % CURSOR_RING_SEGMENTS) * 2) as u32;
+        let next_outer = next_inner + 1;
+        indices.extend_from_slice(&[inner, next_inner, outer, outer, next_inner, next_outer]);
+    }
+    
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, Default::default());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_indices(Indices::U32(indices));
+    mesh
+}
+
 // Split the camera handling into separate systems to avoid borrow checker issues
 
 // System to update cursor position from mouse input
@@ -70,7 +451,8 @@ pub fn cursor_raycasting(
     // Remove unused player_query
     camera_query: Query<(&Camera, &GlobalTransform), With<FollowCamera>>,
     mut cursor_query: Query<(&mut Transform, &mut Visibility), With<TargetCursor>>,
-    mut mouse_look: ResMut<MouseLook>
+    mut mouse_look: ResMut<MouseLook>,
+    render_scale: Res<RenderScale>,
 ) {
     // Exit early if needed components aren't available
     if let (Ok((camera, camera_transform)), Some(cursor_position)) = (
@@ -78,85 +460,259 @@ pub fn cursor_raycasting(
         if mouse_look.cursor_position != Vec2::ZERO { Some(mouse_look.cursor_position) } else { None }
     ) {
         // Cast a ray from the cursor position into the 3D world
+        // Below full render scale the camera draws into a smaller image than the window
+        let cursor_position = render_scale.window_to_viewport(cursor_position);
         if let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_position) {
-            // Calculate the point where the ray intersects the terrain
-            let mut hit_position = Vec3::ZERO;
-            let mut hit_found = false;
+            let direction = *ray.direction;
             
-            // We'll check multiple points along the ray to find where it hits the terrain
-            let ray_start = ray.origin + ray.direction * 5.0;
+            // Find where the ray meets the terrain; if it never does (aiming at the sky or past
+            // the horizon), project the farthest point of the ray down onto the terrain instead
+            let hit_position = raycast_terrain(ray.origin, direction, CURSOR_MAX_RANGE)
+                .unwrap_or_else(|| {
+                    let far_point = ray.origin + direction * CURSOR_MAX_RANGE;
+                    Vec3::new(far_point.x, get_terrain_height(far_point.x, far_point.z), far_point.z)
+                });
             
-            // Sample multiple points along the ray
-            for i in 0..20 {
-                let distance = i as f32 * 2.0;
-                let sample_pos = ray_start + ray.direction * distance;
-                let terrain_height = get_terrain_height(sample_pos.x, sample_pos.z);
-                
-                // Check if this sample is at or below the terrain height
-                if sample_pos.y <= terrain_height {
-                    hit_position = Vec3::new(sample_pos.x, terrain_height, sample_pos.z);
-                    hit_found = true;
-                    break;
-                }
-            }
+            // Update the cursor position
+            mouse_look.target_position = hit_position;
+            mouse_look.is_initialized = true;
             
-            // If we found a hit, update the cursor position
-            if hit_found {
-                mouse_look.target_position = hit_position;
-                mouse_look.is_initialized = true;
-                
-                // Update the cursor mesh
-                if let Ok((mut cursor_transform, mut visibility)) = cursor_query.get_single_mut() {
-                    cursor_transform.translation = hit_position + Vec3::new(0.0, 0.1, 0.0); // Slightly above terrain
-                    *visibility = Visibility::Visible;
-                }
+            // Update the cursor mesh
+            if let Ok((mut cursor_transform, mut visibility)) = cursor_query.get_single_mut() {
+                // The ring mesh carries its own lift above the terrain
+                cursor_transform.translation = hit_position;
+                *visibility = Visibility::Visible;
             }
         }
     }
 }
 
+// System to drape the cursor ring over the terrain and color it by whether the target is in range
+pub fn update_cursor_ring(
+    mouse_look: Res<MouseLook>,
+    charge: Res<ThrowCharge>,
+    upgrades: Res<Upgrades>,
+    ring_assets: Res<CursorRingAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    player_query: Query<&Transform, With<Player>>,
+    mut cursor_query: Query<&mut MeshMaterial3d<StandardMaterial>, With<TargetCursor>>,
+    tuning: Res<LiveTuning>,
+) {
+    if !mouse_look.is_initialized {
+        return;
+    }
+    
+    // Rebuild the ring so it hugs the terrain around the current target
+    if let Some(mesh) = meshes.get_mut(&ring_assets.mesh) {
+        *mesh = create_cursor_ring_mesh(mouse_look.target_position, upgrades.blast_radius());
+    }
+    
+    if let (Ok(player_transform), Ok(mut material)) = (
+        player_query.get_single(),
+        cursor_query.get_single_mut()
+    ) {
+        let in_range = in_throw_range(&tuning.0.projectile, player_transform.translation, mouse_look.target_position, charge.level);
+        let wanted = if in_range {
+            &ring_assets.in_range_material
+        } else {
+            &ring_assets.out_of_range_material
+        };
+        if material.0 != *wanted {
+            material.0 = wanted.clone();
+        }
+    }
+}
+
+// Walk from the player toward the camera and return the farthest distance that stays above the terrain
+fn terrain_clear_distance(origin: Vec3, direction: Vec3, max_distance: f32, margin: f32) -> f32 {
+    let steps = 24;
+    for i in 1..=steps {
+        let distance = max_distance * i as f32 / steps as f32;
+        let sample = origin + direction * distance;
+        if sample.y < get_terrain_height(sample.x, sample.z) + margin {
+            // Back off to the last sample that was still clear
+            return max_distance * (i - 1) as f32 / steps as f32;
+        }
+    }
+    max_distance
+}
+
+// System to orbit the camera around the player while the right mouse button is held
+pub fn orbit_camera(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut motion_events: EventReader<MouseMotion>,
+    mut settings: ResMut<CameraSettings>,
+) {
+    // Always drain the events so stale motion doesn't apply when the drag starts
+    let drag: Vec2 = motion_events.read().map(|event| event.delta).sum();
+    
+    if !mouse_buttons.pressed(MouseButton::Right) {
+        return;
+    }
+    
+    // Dragging right swings the camera left around the player, dragging down raises it
+    settings.yaw -= drag.x * settings.orbit_sensitivity;
+    settings.pitch = (settings.pitch + drag.y * settings.orbit_sensitivity)
+        .clamp(settings.min_pitch, settings.max_pitch);
+}
+
+// System to cycle camera presets with the C key
+pub fn cycle_camera_preset(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<CameraSettings>,
+) {
+    if keys.just_pressed(KeyCode::KeyC) {
+        let next = settings.preset.next();
+        settings.apply_preset(next);
+        println!("Camera preset: {:?}", next);
+    }
+}
+
+// System to blend into the top-down tactical view while Tab is held
+pub fn update_tactical_view(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<CameraSettings>,
+    time: Res<Time>,
+) {
+    let target = if keys.pressed(KeyCode::Tab) { 1.0 } else { 0.0 };
+    let step = settings.tactical_transition_speed * time.delta_secs();
+    settings.tactical_blend += (target - settings.tactical_blend).clamp(-step, step);
+}
+
 // System to update camera position based on player and cursor
 pub fn update_camera_position(
-    player_query: Query<&Transform, With<Player>>,
-    mut camera_query: Query<&mut Transform, (With<FollowCamera>, Without<Player>)>,
+    player_query: Query<(&Transform, &PlayerPhysics), With<Player>>,
+    split_query: Query<&Transform, (With<SplitBall>, Without<FollowCamera>)>,
+    mut camera_query: Query<(&mut Transform, &mut Projection), (With<FollowCamera>, Without<Player>)>,
     mouse_look: Res<MouseLook>,
+    mut settings: ResMut<CameraSettings>,
+    mut shake: ResMut<CameraShake>,
+    mut scroll_events: EventReader<MouseWheel>,
+    tuning: Res<LiveTuning>,
     time: Res<Time>,
 ) {
+    // Scroll up zooms in, scroll down zooms out
+    for event in scroll_events.read() {
+        let lines = match event.unit {
+            MouseScrollUnit::Line => event.y,
+            // Touchpads report pixels, roughly 20 pixels per line
+            MouseScrollUnit::Pixel => event.y / 20.0,
+        };
+        settings.target_distance = (settings.target_distance - lines * settings.zoom_speed)
+            .clamp(settings.min_distance, settings.max_distance);
+    }
+    
+    // Smoothly approach the requested zoom level
+    let zoom_t = (settings.zoom_smoothness * time.delta_secs()).min(1.0);
+    settings.follow_distance += (settings.target_distance - settings.follow_distance) * zoom_t;
+    
     // Exit early if player or camera isn't available
-    if let (Ok(player_transform), Ok(mut camera_transform)) = (
+    if let (Ok((player_transform, physics)), Ok((mut camera_transform, mut projection))) = (
         player_query.get_single(),
         camera_query.get_single_mut()
     ) {
+        // Follow the middle of the balls while a multiball has split the player
+        let focus = split_query
+            .iter()
+            .fold(player_transform.translation, |sum, split| sum + split.translation)
+            / (split_query.iter().len() + 1) as f32;
+
+        // Remove last frame's shake so smoothing works from the steady pose
+        camera_transform.translation -= shake.applied_offset;
+        camera_transform.rotate_local_z(-shake.applied_roll);
+        
+        // Ease toward the current speed ratio so FOV and distance don't jitter with bumps
+        let horizontal_velocity = Vec3::new(physics.velocity.x, 0.0, physics.velocity.z);
+        let speed_ratio = (horizontal_velocity.length() / tuning.0.player.max_speed).clamp(0.0, 1.0);
+        let target_speed_factor = speed_ratio.powf(settings.speed_curve_exponent);
+        let speed_t = (settings.speed_response * time.delta_secs()).min(1.0);
+        settings.speed_factor += (target_speed_factor - settings.speed_factor) * speed_t;
+        
+        // Widen the view as the player picks up speed
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov = settings.base_fov + (settings.max_speed_fov - settings.base_fov) * settings.speed_factor;
+        }
+        
+        // Frame a point ahead of the player along its direction of travel
+        let look_ahead = horizontal_velocity * settings.look_ahead_time;
+        
         // Calculate a dynamic camera offset that maintains player view but angles toward cursor
-        let base_offset = Vec3::new(-3.0, 3.5, 6.0);
+        // The offset direction follows the orbit angles while its length follows the zoom level,
+        // trailing further behind at speed
+        let base_offset = settings.orbit_direction()
+            * (settings.follow_distance + settings.speed_distance_bonus * settings.speed_factor);
+        
+        // Find how far back the camera can sit before a hill gets in the way
+        let clear_distance = terrain_clear_distance(
+            focus,
+            base_offset.normalize(),
+            base_offset.length(),
+            settings.collision_margin,
+        ).max(1.0);
+        if clear_distance < settings.collision_distance {
+            // Snap in immediately so the view is never blocked
+            settings.collision_distance = clear_distance;
+        } else {
+            // Ease back out once the obstruction is gone
+            let recovery_t = (settings.collision_recovery * time.delta_secs()).min(1.0);
+            settings.collision_distance += (clear_distance - settings.collision_distance) * recovery_t;
+        }
         
         // Calculate the desired camera position (behind and above the player)
-        let target_position = player_transform.translation + base_offset;
+        let target_position = focus
+            + base_offset.normalize() * settings.collision_distance;
         
         // Smoothly interpolate the camera position
-        let smoothness = 5.0;
         camera_transform.translation = camera_transform.translation.lerp(
             target_position, 
-            smoothness * time.delta_secs()
+            (settings.position_smoothness * time.delta_secs()).min(1.0)
         );
         
+        // The interpolation can still cut through a ridge, so never let the camera dip below the surface
+        let camera_pos = camera_transform.translation;
+        let min_camera_height = get_terrain_height(camera_pos.x, camera_pos.z) + settings.collision_margin;
+        if camera_transform.translation.y < min_camera_height {
+            camera_transform.translation.y = min_camera_height;
+        }
+        
         // Make camera look at player or cursor based on mouse state
         if mouse_look.is_initialized {
             // Calculate a blended look target between player and cursor
             // This keeps the player in view while angling toward the cursor
-            let player_pos = player_transform.translation + Vec3::new(0.0, 0.5, 0.0);
-            let cursor_weight = 0.6; // Adjust this to change how much the camera focuses on cursor vs player
-            let look_target = player_pos.lerp(mouse_look.target_position, cursor_weight);
+            let player_pos = focus + Vec3::new(0.0, 0.5, 0.0) + look_ahead;
+            let look_target = player_pos.lerp(mouse_look.target_position, settings.cursor_weight);
             
             // Smoothly rotate the camera to look at the target
             let target_rotation = Transform::from_translation(camera_transform.translation)
                 .looking_at(look_target, Vec3::Y).rotation;
-            camera_transform.rotation = camera_transform.rotation.slerp(target_rotation, 8.0 * time.delta_secs());
+            camera_transform.rotation = camera_transform.rotation.slerp(
+                target_rotation,
+                (settings.rotation_smoothness * time.delta_secs()).min(1.0)
+            );
         } else {
             // Default to looking at player if mouse not initialized
-            let look_target = player_transform.translation + Vec3::new(0.0, 0.5, 0.0);
+            let look_target = focus + Vec3::new(0.0, 0.5, 0.0) + look_ahead;
             camera_transform.look_at(look_target, Vec3::Y);
         }
+        
+        // Lift toward the tactical view, looking straight down with camera-forward at the top of the screen
+        if settings.tactical_blend > 0.0 {
+            let blend = settings.tactical_blend * settings.tactical_blend * (3.0 - 2.0 * settings.tactical_blend);
+            let tactical_position = focus + Vec3::Y * settings.tactical_height;
+            let screen_up = settings.yaw_rotation() * Vec3::NEG_Z;
+            let tactical_rotation = Transform::from_translation(tactical_position)
+                .looking_to(Vec3::NEG_Y, screen_up).rotation;
+            camera_transform.translation = camera_transform.translation.lerp(tactical_position, blend);
+            camera_transform.rotation = camera_transform.rotation.slerp(tactical_rotation, blend);
+        }
+        
+        // Layer the shake on top of the steady pose and let the trauma decay
+        let (shake_offset, shake_roll) = shake.sample(time.elapsed_secs());
+        camera_transform.translation += shake_offset;
+        camera_transform.rotate_local_z(shake_roll);
+        shake.applied_offset = shake_offset;
+        shake.applied_roll = shake_roll;
+        shake.trauma = (shake.trauma - shake.decay * time.delta_secs()).max(0.0);
     }
 }
 
@@ -167,11 +723,35 @@ impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
         // Add systems in a specific order and ensure they don't conflict on component access
         app
+            .insert_resource(CameraSettings::load())
+            .init_resource::<CameraShake>()
+            .register_type::<MouseLook>()
             // First update the mouse position (just tracks mouse movement)
             .add_systems(Update, update_mouse_position)
             // Then handle cursor raycasting in a separate system group to avoid conflicts
-            .add_systems(Update, cursor_raycasting.after(update_mouse_position))
-            // Finally update camera position
-            .add_systems(Update, update_camera_position.after(cursor_raycasting));
+            .add_systems(Update, cursor_raycasting
+                .after(update_mouse_position)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive))
+                .run_if(not(spectating)))
+            .add_systems(Update, update_cursor_ring
+                .after(cursor_raycasting)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive))
+                .run_if(not(spectating)))
+            // Orbit input only touches the settings resource
+            .add_systems(Update, (orbit_camera, cycle_camera_preset, update_tactical_view)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)))
+            // Finally update camera position (photo mode and spectators drive the camera themselves)
+            .add_systems(Update, update_camera_position
+                .after(cursor_raycasting)
+                .after(orbit_camera)
+                .after(cycle_camera_preset)
+                .after(update_tactical_view)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive))
+                .run_if(intro_finished)
+                .run_if(not(spectating)));
     }
 }
@@ -0,0 +1,286 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::f32::consts::TAU;
+use crate::terrain::{find_hilltop, get_terrain_height};
+use crate::player::{player_spawn_position, Player};
+use crate::enemy::Enemy;
+use crate::compass::CompassMarker;
+use crate::waypoint::Waypoint;
+use crate::game_events::GameEvent;
+use crate::score::{spawn_score_popup, Score};
+use crate::photo_mode::PhotoModeState;
+use crate::sim::{SimSet, Simulation};
+use crate::{GameMode, GameState, RunStart};
+
+// Capture zone constants
+const ZONE_RADIUS: f32 = 6.0; // Radius of a capture zone
+const ZONE_MIN_DISTANCE: f32 = 30.0; // Closest a new zone appears to the player
+const ZONE_MAX_DISTANCE: f32 = 70.0; // Farthest a search for a new zone's hilltop starts
+const CAPTURE_TIME: f32 = 8.0; // Seconds inside an uncontested zone to capture it
+const CAPTURE_DECAY: f32 = 0.5; // Capture lost per second, as a fraction of CAPTURE_TIME, while away or contested
+const CAPTURE_POINTS: u32 = 1000; // Points for each capture
+const CAPTURE_STREAM: u64 = 8; // Simulation random stream for placing zones
+const ZONE_BAR_WIDTH: f32 = 220.0;
+const ZONE_BAR_HEIGHT: f32 = 10.0;
+const ZONE_COLOR: Color = Color::srgba(0.3, 0.9, 1.0, 0.25);
+const CONTESTED_COLOR: Color = Color::srgba(1.0, 0.3, 0.2, 0.3);
+
+// The zone to hold; relocates to another hilltop once captured
+#[derive(Component)]
+pub struct CaptureZone;
+
+// Progress on the current zone
+#[derive(Resource, Default)]
+pub struct CaptureState {
+    // Capture meter in 0..1
+    pub progress: f32,
+    pub player_inside: bool,
+    // An enemy is inside the zone, stopping the capture
+    pub contested: bool,
+    // Zones captured this run
+    pub captures: u32,
+}
+
+// Materials for the zone's normal and contested looks
+#[derive(Resource)]
+pub struct CaptureZoneMaterials {
+    pub normal: Handle<StandardMaterial>,
+    pub contested: Handle<StandardMaterial>,
+}
+
+// Marker for the capture meter panel
+#[derive(Component)]
+pub struct CaptureHudRoot;
+
+// Marker for the capture meter fill
+#[derive(Component)]
+pub struct CaptureHudBar;
+
+// Marker for the capture status text
+#[derive(Component)]
+pub struct CaptureHudText;
+
+// Hilltop for the next zone, away from the player
+fn pick_zone_spot(from: Vec3, rng: &mut StdRng) -> Vec3 {
+    let mut spot = Vec2::new(from.x + ZONE_MIN_DISTANCE, from.z);
+    for _ in 0..10 {
+        let angle = rng.gen_range(0.0..TAU);
+        let distance = rng.gen_range(ZONE_MIN_DISTANCE..ZONE_MAX_DISTANCE);
+        spot = find_hilltop(Vec2::new(from.x + angle.cos() * distance, from.z + angle.sin() * distance));
+        if spot.distance(Vec2::new(from.x, from.z)) >= ZONE_MIN_DISTANCE {
+            break;
+        }
+    }
+    Vec3::new(spot.x, get_terrain_height(spot.x, spot.y), spot.y)
+}
+
+// Create the zone materials
+pub fn setup_capture_materials(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    let mut material = |color: Color| materials.add(StandardMaterial {
+        base_color: color,
+        emissive: (color.to_linear() * 2.0).into(),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+    commands.insert_resource(CaptureZoneMaterials {
+        normal: material(ZONE_COLOR),
+        contested: material(CONTESTED_COLOR),
+    });
+}
+
+// Place the first zone when a king-of-the-hill run starts
+pub fn spawn_capture_zone(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    materials: Res<CaptureZoneMaterials>,
+    mut capture: ResMut<CaptureState>,
+    mode: Res<GameMode>,
+    simulation: Res<Simulation>,
+) {
+    if *mode != GameMode::KingOfTheHill {
+        return;
+    }
+    *capture = CaptureState::default();
+    commands.spawn((
+        CaptureZone,
+        Mesh3d(meshes.add(Cylinder::new(ZONE_RADIUS, 3.0))),
+        MeshMaterial3d(materials.normal.clone()),
+        Transform::from_translation(pick_zone_spot(player_spawn_position(), &mut simulation.rng(CAPTURE_STREAM)) + Vec3::Y * 1.0),
+        Name::new("Capture Zone"),
+        Waypoint {
+            label: "Zone".to_string(),
+            color: ZONE_COLOR.with_alpha(1.0),
+        },
+        CompassMarker { color: ZONE_COLOR.with_alpha(1.0) },
+    ));
+}
+
+// Remove the zone at the end of a run
+pub fn despawn_capture_zone(
+    mut commands: Commands,
+    mut capture: ResMut<CaptureState>,
+    zone_query: Query<Entity, With<CaptureZone>>,
+) {
+    for entity in zone_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    *capture = CaptureState::default();
+}
+
+// Fill the meter while the player holds the zone alone, and move the zone once it is captured
+pub fn update_capture(
+    mut commands: Commands,
+    mut capture: ResMut<CaptureState>,
+    mut score: ResMut<Score>,
+    mut game_events: EventWriter<GameEvent>,
+    materials: Res<CaptureZoneMaterials>,
+    player_query: Query<&Transform, With<Player>>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<Player>)>,
+    mut zone_query: Query<(&mut Transform, &mut MeshMaterial3d<StandardMaterial>), (With<CaptureZone>, Without<Player>, Without<Enemy>)>,
+    simulation: Res<Simulation>,
+    time: Res<Time>,
+) {
+    let (Ok(player_transform), Ok((mut zone_transform, mut material))) = (
+        player_query.get_single(),
+        zone_query.get_single_mut()
+    ) else {
+        return;
+    };
+    let center = zone_transform.translation;
+    let inside = |position: Vec3| Vec2::new(position.x - center.x, position.z - center.z).length() <= ZONE_RADIUS;
+
+    let player_inside = inside(player_transform.translation);
+    let contested = enemy_query.iter().any(|transform| inside(transform.translation));
+    if capture.player_inside != player_inside {
+        capture.player_inside = player_inside;
+    }
+    if capture.contested != contested {
+        capture.contested = contested;
+        material.0 = if contested { materials.contested.clone() } else { materials.normal.clone() };
+    }
+
+    let delta = time.delta_secs();
+    if player_inside && !contested {
+        capture.progress = (capture.progress + delta / CAPTURE_TIME).min(1.0);
+    } else if capture.progress > 0.0 {
+        capture.progress = (capture.progress - delta * CAPTURE_DECAY / CAPTURE_TIME).max(0.0);
+    }
+
+    if capture.progress >= 1.0 {
+        capture.progress = 0.0;
+        capture.captures += 1;
+        score.points += CAPTURE_POINTS;
+        spawn_score_popup(&mut commands, center, format!("+{}", CAPTURE_POINTS));
+        game_events.send(GameEvent::ZoneCaptured { count: capture.captures });
+        zone_transform.translation = pick_zone_spot(player_transform.translation, &mut simulation.rng(CAPTURE_STREAM)) + Vec3::Y * 1.0;
+    }
+}
+
+// Build the capture meter under the compass
+pub fn spawn_capture_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            CaptureHudRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(48.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                CaptureHudText,
+                Text::new(""),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(ZONE_BAR_WIDTH),
+                        height: Val::Px(ZONE_BAR_HEIGHT),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.8)),
+                ))
+                .with_children(|bar| {
+                    bar.spawn((
+                        CaptureHudBar,
+                        Node {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(ZONE_COLOR.with_alpha(1.0)),
+                    ));
+                });
+        });
+}
+
+// Show the capture meter while playing king of the hill
+pub fn show_capture_hud(mode: Res<GameMode>, mut query: Query<&mut Visibility, With<CaptureHudRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = if *mode == GameMode::KingOfTheHill { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+// Hide the capture meter outside of gameplay
+pub fn hide_capture_hud(mut query: Query<&mut Visibility, With<CaptureHudRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Fill the meter and describe the zone's state
+pub fn update_capture_hud(
+    capture: Res<CaptureState>,
+    mut bar_query: Query<(&mut Node, &mut BackgroundColor), With<CaptureHudBar>>,
+    mut text_query: Query<&mut Text, With<CaptureHudText>>,
+) {
+    for (mut node, mut color) in bar_query.iter_mut() {
+        node.width = Val::Percent(capture.progress * 100.0);
+        color.0 = if capture.contested { CONTESTED_COLOR } else { ZONE_COLOR }.with_alpha(1.0);
+    }
+    let status = match (capture.player_inside, capture.contested) {
+        (_, true) => "Contested!",
+        (true, false) => "Capturing",
+        (false, false) => "Get to the zone",
+    };
+    for mut text in text_query.iter_mut() {
+        text.0 = format!("{}   Captured: {}", status, capture.captures);
+    }
+}
+
+// Plugin for the king-of-the-hill mode
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<CaptureState>()
+            .add_systems(Startup, (setup_capture_materials, spawn_capture_hud))
+            .add_systems(RunStart, spawn_capture_zone)
+            .add_systems(OnExit(GameState::GameOver), despawn_capture_zone)
+            .add_systems(OnEnter(GameState::Playing), show_capture_hud)
+            .add_systems(OnExit(GameState::Playing), hide_capture_hud)
+            .add_systems(FixedUpdate, update_capture
+                .in_set(SimSet::Constraints)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive))
+                .run_if(resource_equals(GameMode::KingOfTheHill)))
+            .add_systems(Update, update_capture_hud
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive))
+                .run_if(resource_equals(GameMode::KingOfTheHill)));
+    }
+}
@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::FRAC_PI_2;
+use crate::terrain::{find_hilltop, get_terrain_height};
+use crate::player::{player_spawn_position, Player, PlayerPhysics};
+use crate::projectile::{solve_ballistic, spawn_boulder, vary_launch, BoulderLaunch, Hostile, Projectile, ProjectileAssets, PROJECTILE_BLAST_RADIUS};
+use crate::sim::{SimSet, Simulation};
+use crate::photo_mode::PhotoModeState;
+use crate::difficulty::Difficulty;
+use crate::intro::intro_finished;
+use crate::minimap::MinimapBlip;
+use crate::compass::CompassMarker;
+use crate::audio::{PlaySound, SoundEffect};
+use crate::assets::models::ModelRegistry;
+use crate::tuning::LiveTuning;
+use crate::{GameState, RunStart};
+
+// Catapult constants
+const CATAPULT_COUNT: usize = 3; // Emplacements placed at the start of a run
+const CATAPULT_MIN_DISTANCE: f32 = 30.0; // Closest a catapult is placed to the spawn point
+const CATAPULT_MAX_DISTANCE: f32 = 70.0; // Farthest a search for a hilltop starts from the spawn point
+const CATAPULT_SPACING: f32 = 20.0; // Minimum distance between two catapults
+const PLACEMENT_ATTEMPTS: usize = 30; // Hilltop searches before giving up on more catapults
+const CATAPULT_RANGE: f32 = 45.0; // Catapults fire at a player closer than this
+const CATAPULT_RELOAD: f32 = 4.0; // Seconds between shots from one catapult
+const CATAPULT_LAUNCH_HEIGHT: f32 = 1.6; // Height above the base that boulders leave from
+const FLIGHT_TIME_BASE: f32 = 1.5; // Flight time of a shot at point blank range
+const FLIGHT_TIME_PER_METER: f32 = 0.04; // Extra flight time per meter to the target
+pub const HOSTILE_BOULDER_COLOR: Color = Color::srgb(0.45, 0.25, 0.2);
+const WARNING_COLOR: Color = Color::srgb(1.0, 0.2, 0.1);
+const CATAPULT_STREAM: u64 = 1; // Simulation random stream for placing catapults
+const CATAPULT_FIRE_STREAM: u64 = 13; // Simulation random stream for catapult shots
+
+// A stationary enemy catapult that lobs boulders at the player
+#[derive(Component)]
+pub struct Catapult {
+    // Seconds until the next shot
+    pub reload_timer: f32,
+}
+
+// Flight time for a shot covering a horizontal distance
+fn flight_time(distance: f32) -> f32 {
+    FLIGHT_TIME_BASE + distance * FLIGHT_TIME_PER_METER
+}
+
+// Place catapults on hilltops around the spawn point
+pub fn spawn_catapults(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    simulation: Res<Simulation>,
+    models: Res<ModelRegistry>,
+    asset_server: Res<AssetServer>,
+) {
+    let mut rng = simulation.rng(CATAPULT_STREAM);
+    let model = models.structure("catapult", &asset_server);
+    let spawn = player_spawn_position();
+    let base_mesh = meshes.add(Cuboid::new(1.6, 0.8, 1.6));
+    let arm_mesh = meshes.add(Cuboid::new(0.2, 0.2, 2.2));
+    let wood = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.45, 0.3, 0.15),
+        perceptual_roughness: 0.9,
+        ..default()
+    });
+
+    let mut placed: Vec<Vec2> = Vec::new();
+    for _ in 0..PLACEMENT_ATTEMPTS {
+        if placed.len() >= CATAPULT_COUNT {
+            break;
+        }
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let distance = rng.gen_range(CATAPULT_MIN_DISTANCE..CATAPULT_MAX_DISTANCE);
+        let top = find_hilltop(Vec2::new(spawn.x + angle.cos() * distance, spawn.z + angle.sin() * distance));
+
+        // Keep clear of the spawn point and of the other catapults
+        let too_close = top.distance(Vec2::new(spawn.x, spawn.z)) < CATAPULT_MIN_DISTANCE
+            || placed.iter().any(|other| other.distance(top) < CATAPULT_SPACING);
+        if too_close {
+            continue;
+        }
+        placed.push(top);
+
+        let y = get_terrain_height(top.x, top.y) + 0.4;
+        let mut catapult = commands.spawn((
+            Catapult { reload_timer: rng.gen_range(0.0..CATAPULT_RELOAD) + CATAPULT_RELOAD },
+            Transform::from_xyz(top.x, y, top.y),
+            Visibility::default(),
+            Name::new("Catapult"),
+            MinimapBlip { color: Color::srgb(1.0, 0.5, 0.1) },
+            CompassMarker { color: Color::srgb(1.0, 0.5, 0.1) },
+        ));
+        match &model {
+            // The model stands on the ground, below the middle of the base
+            Some(scene) => {
+                catapult.with_children(|parent| {
+                    parent.spawn((SceneRoot(scene.clone()), Transform::from_xyz(0.0, -0.4, 0.0)));
+                });
+            }
+            None => {
+                catapult
+                    .insert((Mesh3d(base_mesh.clone()), MeshMaterial3d(wood.clone())))
+                    .with_children(|parent| {
+                        // Throwing arm, cocked back
+                        parent.spawn((
+                            Mesh3d(arm_mesh.clone()),
+                            MeshMaterial3d(wood.clone()),
+                            Transform::from_xyz(0.0, 0.8, 0.0).with_rotation(Quat::from_rotation_x(0.6)),
+                        ));
+                    });
+            }
+        }
+    }
+}
+
+// Remove every catapult so the next run places its own
+pub fn despawn_catapults(mut commands: Commands, catapult_query: Query<Entity, With<Catapult>>) {
+    for entity in catapult_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Fire at where the player will be when the boulder arrives
+pub fn fire_catapults(
+    mut commands: Commands,
+    mut projectile_assets: ResMut<ProjectileAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut catapult_query: Query<(&mut Catapult, &mut Transform), Without<Player>>,
+    player_query: Query<(&Transform, &PlayerPhysics), With<Player>>,
+    difficulty: Res<Difficulty>,
+    mut sounds: EventWriter<PlaySound>,
+    simulation: Res<Simulation>,
+    tuning: Res<LiveTuning>,
+    time: Res<Time>,
+) {
+    let tuning = &tuning.0.projectile;
+    let Ok((player_transform, physics)) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation;
+    let mut rng = simulation.rng(CATAPULT_FIRE_STREAM);
+
+    for (mut catapult, mut transform) in catapult_query.iter_mut() {
+        catapult.reload_timer -= time.delta_secs();
+        let offset = player_pos - transform.translation;
+        if catapult.reload_timer > 0.0 || Vec2::new(offset.x, offset.z).length() > CATAPULT_RANGE {
+            continue;
+        }
+        catapult.reload_timer = CATAPULT_RELOAD / difficulty.enemy_fire_rate();
+
+        // Lead the player: predict where they will be, then refine the flight time for that spot
+        let start_pos = transform.translation + Vec3::Y * CATAPULT_LAUNCH_HEIGHT;
+        let mut predicted = player_pos;
+        let mut time_of_flight = 0.0;
+        for _ in 0..2 {
+            let to_target = predicted - start_pos;
+            time_of_flight = flight_time(Vec2::new(to_target.x, to_target.z).length());
+            let ahead = player_pos + Vec3::new(physics.velocity.x, 0.0, physics.velocity.z) * time_of_flight;
+            predicted = Vec3::new(ahead.x, get_terrain_height(ahead.x, ahead.z), ahead.z);
+        }
+
+        // Turn to face the shot
+        transform.rotation = Quat::from_rotation_y(f32::atan2(-offset.x, -offset.z));
+
+        let initial_velocity = vary_launch(solve_ballistic(tuning, start_pos, predicted, time_of_flight), &mut rng);
+        let boulder = spawn_boulder(
+            &mut commands,
+            &mut projectile_assets,
+            &mut materials,
+            tuning,
+            BoulderLaunch {
+                start: start_pos,
+                target: predicted,
+                velocity: initial_velocity,
+                color: HOSTILE_BOULDER_COLOR,
+                blast_radius: PROJECTILE_BLAST_RADIUS,
+            },
+        );
+        commands.entity(boulder).insert(Hostile);
+        sounds.send(PlaySound { effect: SoundEffect::Launch, position: start_pos });
+    }
+}
+
+// Mark where incoming hostile boulders will land with a pulsing ring on the ground
+pub fn draw_incoming_warnings(
+    mut gizmos: Gizmos,
+    projectile_query: Query<&Projectile, With<Hostile>>,
+) {
+    for projectile in projectile_query.iter().filter(|projectile| !projectile.stuck) {
+        let landing = projectile.target_position;
+        let ground = Vec3::new(landing.x, get_terrain_height(landing.x, landing.z) + 0.1, landing.z);
+        let pulse = 0.5 + 0.5 * (projectile.age * 10.0).sin();
+        let rotation = Quat::from_rotation_x(FRAC_PI_2);
+        gizmos.circle(Isometry3d::new(ground, rotation), projectile.blast_radius, WARNING_COLOR);
+        gizmos.circle(
+            Isometry3d::new(ground, rotation),
+            projectile.blast_radius * (0.3 + 0.4 * pulse),
+            WARNING_COLOR.with_alpha(0.5 + 0.5 * pulse),
+        );
+    }
+}
+
+// Plugin for enemy catapult emplacements
+pub struct CatapultPlugin;
+
+impl Plugin for CatapultPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(RunStart, spawn_catapults)
+            .add_systems(OnExit(GameState::GameOver), despawn_catapults)
+            .add_systems(FixedUpdate, fire_catapults
+                .in_set(SimSet::Movement)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive))
+                .run_if(intro_finished))
+            .add_systems(Update, draw_incoming_warnings.run_if(in_state(GameState::Playing)));
+    }
+}
@@ -0,0 +1,369 @@
+use bevy::prelude::*;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::InputSystem;
+use std::collections::VecDeque;
+use crate::input::update_action_state;
+use crate::network::{player_color, LobbyPlayer, NetMessage, NetNotice, NetSession};
+use crate::photo_mode::PhotoModeState;
+use crate::GameState;
+
+// Chat constants
+const CHAT_SCROLLBACK: usize = 50; // Lines kept for scrolling back; older ones are dropped
+const CHAT_VISIBLE_LINES: usize = 8; // Lines shown at once
+const CHAT_LINE_LIFETIME: f32 = 10.0; // Seconds a line stays on screen while the chat is closed
+const MAX_CHAT_LENGTH: usize = 120; // Longest line that can be typed or is shown from others
+const SYSTEM_COLOR: Color = Color::srgb(0.85, 0.85, 0.55);
+
+// Words masked out of chat lines and player names
+const BLOCKED_WORDS: [&str; 10] = ["fuck", "fucking", "shit", "bitch", "bastard", "cunt", "dick", "piss", "asshole", "wanker"];
+
+// A line in the chat, from a player or from the game itself
+#[derive(Debug, Clone)]
+pub struct ChatLine {
+    // Player who typed it, or None for system messages about joins and knockouts
+    pub author: Option<u8>,
+    pub name: String,
+    pub text: String,
+    // Real time the line arrived, for hiding it again while the chat is closed
+    pub time: f32,
+}
+
+// Every chat line of the session, oldest first
+#[derive(Resource, Default)]
+pub struct ChatLog {
+    pub lines: VecDeque<ChatLine>,
+    // Lines scrolled back from the newest while the chat is open
+    pub scroll: usize,
+    // Who was in the session last time we looked, to announce joins and leaves
+    roster: Vec<LobbyPlayer>,
+}
+
+impl ChatLog {
+    fn push(&mut self, line: ChatLine) {
+        if self.lines.len() >= CHAT_SCROLLBACK {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+        self.scroll = 0;
+    }
+
+    fn system(&mut self, text: String, time: f32) {
+        self.push(ChatLine { author: None, name: String::new(), text, time });
+    }
+}
+
+// The line being typed; while open, the keyboard belongs to the chat
+#[derive(Resource, Default)]
+pub struct ChatInput {
+    pub open: bool,
+    pub draft: String,
+}
+
+// Marker for the chat panel
+#[derive(Component)]
+pub struct ChatRoot;
+
+// A line shown in the chat panel
+#[derive(Component)]
+pub struct ChatLineNode {
+    pub time: f32,
+}
+
+// Move a finished word into the cleaned text, masked if it is blocked
+fn flush_word(word: &mut String, cleaned: &mut String) {
+    if BLOCKED_WORDS.iter().any(|blocked| word.eq_ignore_ascii_case(blocked)) {
+        cleaned.extend(std::iter::repeat_n('*', word.chars().count()));
+    } else {
+        cleaned.push_str(word);
+    }
+    word.clear();
+}
+
+// Mask blocked words with asterisks, keeping everything else as typed
+pub fn clean_text(text: &str) -> String {
+    let mut cleaned = String::with_capacity(text.len());
+    let mut word = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            word.push(c);
+        } else {
+            flush_word(&mut word, &mut cleaned);
+            cleaned.push(c);
+        }
+    }
+    flush_word(&mut word, &mut cleaned);
+    cleaned
+}
+
+// Chat text as it is shown: no control characters, no longer than a typed line and cleaned
+fn sanitize(text: &str) -> String {
+    let trimmed: String = text.chars().filter(|c| !c.is_control()).take(MAX_CHAT_LENGTH).collect();
+    clean_text(trimmed.trim())
+}
+
+// A player's name for chat lines and system messages
+fn display_name(session: &NetSession, id: u8) -> String {
+    clean_text(session.player_name(id).unwrap_or("Someone"))
+}
+
+// Open the chat with Enter and, while it is open, take every key press for typing: Enter sends,
+// Escape discards and Page Up/Down scroll. Runs before actions are resolved so nothing typed also
+// moves the ball, pauses the game or opens photo mode.
+pub fn chat_keyboard(
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut chat: ResMut<ChatInput>,
+    mut log: ResMut<ChatLog>,
+    session: Option<Res<NetSession>>,
+    game_state: Res<State<GameState>>,
+    photo_mode: Res<State<PhotoModeState>>,
+    time: Res<Time<Real>>,
+) {
+    if !chat.open {
+        // Events already seen here must not be typed once the chat opens
+        keyboard_events.clear();
+        let can_open = session.is_some()
+            && *game_state.get() == GameState::Playing
+            && *photo_mode.get() == PhotoModeState::Inactive;
+        if can_open && keys.clear_just_pressed(KeyCode::Enter) {
+            chat.open = true;
+        }
+        return;
+    }
+
+    for event in keyboard_events.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => {
+                let text = sanitize(&chat.draft);
+                if let Some(session) = session.as_ref().filter(|_| !text.is_empty()) {
+                    if let Some(id) = session.local_id {
+                        session.broadcast(&NetMessage::Chat { id, text: text.clone() });
+                        log.push(ChatLine { author: Some(id), name: display_name(session, id), text, time: time.elapsed_secs() });
+                    }
+                }
+                chat.draft.clear();
+                chat.open = false;
+            }
+            Key::Escape => {
+                chat.draft.clear();
+                chat.open = false;
+            }
+            Key::Backspace => {
+                chat.draft.pop();
+            }
+            Key::PageUp => {
+                log.scroll = (log.scroll + CHAT_VISIBLE_LINES / 2).min(log.lines.len().saturating_sub(CHAT_VISIBLE_LINES));
+            }
+            Key::PageDown => {
+                log.scroll = log.scroll.saturating_sub(CHAT_VISIBLE_LINES / 2);
+            }
+            Key::Space if chat.draft.chars().count() < MAX_CHAT_LENGTH => chat.draft.push(' '),
+            Key::Character(text) => {
+                for c in text.chars().filter(|c| !c.is_control()) {
+                    if chat.draft.chars().count() < MAX_CHAT_LENGTH {
+                        chat.draft.push(c);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    // Nothing else sees the keys pressed while typing
+    keys.reset_all();
+}
+
+// Add chat lines and knockouts from the other players
+pub fn record_net_notices(
+    mut notices: EventReader<NetNotice>,
+    mut log: ResMut<ChatLog>,
+    session: Res<NetSession>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed_secs();
+    for notice in notices.read() {
+        match notice {
+            NetNotice::Chat { id, text } => {
+                let text = sanitize(text);
+                if !text.is_empty() {
+                    log.push(ChatLine { author: Some(*id), name: display_name(&session, *id), text, time: now });
+                }
+            }
+            NetNotice::Knockout { victim, attacker } => {
+                let text = format!("{} knocked out {}", display_name(&session, *attacker), display_name(&session, *victim));
+                log.system(text, now);
+            }
+        }
+    }
+}
+
+// Announce players joining and leaving, from the changes to the session's player list
+pub fn announce_roster_changes(mut log: ResMut<ChatLog>, session: Res<NetSession>, time: Res<Time<Real>>) {
+    if !session.is_changed() || log.roster == session.players {
+        return;
+    }
+    let now = time.elapsed_secs();
+    let joined: Vec<LobbyPlayer> = session
+        .players
+        .iter()
+        .filter(|player| !log.roster.iter().any(|known| known.id == player.id))
+        .cloned()
+        .collect();
+    let left: Vec<LobbyPlayer> = log
+        .roster
+        .iter()
+        .filter(|known| !session.players.iter().any(|player| player.id == known.id))
+        .cloned()
+        .collect();
+    for player in joined {
+        let verb = if player.spectator { "is watching" } else { "joined the game" };
+        log.system(format!("{} {}", clean_text(&player.name), verb), now);
+    }
+    for player in left {
+        log.system(format!("{} left the game", clean_text(&player.name)), now);
+    }
+    log.roster = session.players.clone();
+}
+
+// Forget the chat when the session ends
+pub fn clear_chat(mut log: ResMut<ChatLog>, mut chat: ResMut<ChatInput>) {
+    *log = ChatLog::default();
+    *chat = ChatInput::default();
+}
+
+// Close the chat when play stops, dropping whatever was being typed
+pub fn close_chat(mut chat: ResMut<ChatInput>) {
+    if chat.open {
+        *chat = ChatInput::default();
+    }
+}
+
+// Spawn the chat panel on the left side of the screen, above the event log
+pub fn spawn_chat(mut commands: Commands) {
+    commands.spawn((
+        ChatRoot,
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(12.0),
+            bottom: Val::Px(160.0),
+            width: Val::Px(440.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(2.0),
+            padding: UiRect::all(Val::Px(4.0)),
+            ..default()
+        },
+        BackgroundColor(Color::NONE),
+        // Hidden until a networked game starts
+        Visibility::Hidden,
+    ));
+}
+
+// Show the chat while playing a networked game
+pub fn show_chat(session: Option<Res<NetSession>>, mut query: Query<&mut Visibility, With<ChatRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = if session.is_some() { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+// Hide the chat outside of gameplay
+pub fn hide_chat(mut query: Query<&mut Visibility, With<ChatRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Rebuild the chat panel when a line arrives, the view scrolls or the typed line changes
+pub fn refresh_chat(
+    mut commands: Commands,
+    log: Res<ChatLog>,
+    chat: Res<ChatInput>,
+    mut root_query: Query<(Entity, &mut BackgroundColor), With<ChatRoot>>,
+) {
+    if !log.is_changed() && !chat.is_changed() {
+        return;
+    }
+    let Ok((root, mut background)) = root_query.get_single_mut() else {
+        return;
+    };
+    background.0 = if chat.open { Color::srgba(0.0, 0.0, 0.0, 0.45) } else { Color::NONE };
+
+    // Scrolling back only applies while the chat is open
+    let scroll = if chat.open { log.scroll } else { 0 };
+    let end = log.lines.len() - scroll.min(log.lines.len());
+    let start = end.saturating_sub(CHAT_VISIBLE_LINES);
+    let font = TextFont {
+        font_size: 16.0,
+        ..default()
+    };
+
+    commands.entity(root).despawn_descendants().with_children(|parent| {
+        for line in log.lines.range(start..end) {
+            parent
+                .spawn((
+                    ChatLineNode { time: line.time },
+                    Node {
+                        column_gap: Val::Px(6.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.35)),
+                ))
+                .with_children(|row| {
+                    match line.author {
+                        Some(id) => {
+                            row.spawn((Text::new(format!("{}:", line.name)), font.clone(), TextColor(player_color(id))));
+                            row.spawn((Text::new(line.text.clone()), font.clone(), TextColor(Color::WHITE)));
+                        }
+                        None => {
+                            row.spawn((Text::new(line.text.clone()), font.clone(), TextColor(SYSTEM_COLOR)));
+                        }
+                    }
+                });
+        }
+        if chat.open {
+            let scrolled = if scroll > 0 { format!("  ({} newer below)", scroll) } else { String::new() };
+            parent.spawn((
+                Text::new(format!("> {}_{}", chat.draft, scrolled)),
+                font.clone(),
+                TextColor(Color::WHITE),
+            ));
+        }
+    });
+}
+
+// Hide lines that have been up for a while, unless the chat is open for reading back
+pub fn expire_chat_lines(
+    chat: Res<ChatInput>,
+    mut line_query: Query<(&ChatLineNode, &mut Visibility)>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed_secs();
+    for (line, mut visibility) in line_query.iter_mut() {
+        let shown = chat.open || now - line.time < CHAT_LINE_LIFETIME;
+        let wanted = if shown { Visibility::Inherited } else { Visibility::Hidden };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+}
+
+// Plugin for the multiplayer chat overlay
+pub struct ChatPlugin;
+
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<ChatLog>()
+            .init_resource::<ChatInput>()
+            .add_systems(Startup, spawn_chat)
+            // Typing has to claim the keyboard before actions are read from it
+            .add_systems(PreUpdate, chat_keyboard.after(InputSystem).before(update_action_state))
+            .add_systems(OnEnter(GameState::Playing), show_chat)
+            .add_systems(OnExit(GameState::Playing), (close_chat, hide_chat))
+            .add_systems(Update, (record_net_notices, announce_roster_changes)
+                .run_if(resource_exists::<NetSession>))
+            .add_systems(Update, clear_chat.run_if(resource_removed::<NetSession>))
+            .add_systems(Update, (refresh_chat, expire_chat_lines).chain());
+    }
+}
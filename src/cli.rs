@@ -0,0 +1,130 @@
+use std::path::PathBuf;
+use crate::GameMode;
+use crate::bench::BenchConfig;
+use crate::terrain::TerrainConfig;
+
+const USAGE: &str = "Usage: trowback [--seed CODE] [--fullscreen] [--resolution WIDTHxHEIGHT] [--mode MODE] [--headless] [--debug] [--record FILE] [--replay FILE]
+       trowback --bench [--steps N] [--projectiles N] [--seed CODE]
+Modes: freeplay, targetpractice, timetrial, golf, kingofthehill, downhillrace, bossfight";
+
+// Options given on the command line, applied in main before the plugins build
+#[derive(Debug, Default)]
+pub struct CliArgs {
+    // World to generate, as a seed code like the new game page takes
+    pub seed: Option<TerrainConfig>,
+    pub fullscreen: bool,
+    // Window size in logical pixels
+    pub resolution: Option<(f32, f32)>,
+    // Skip the main menu and start a run in this mode
+    pub mode: Option<GameMode>,
+    // Run the simulation without a window or GPU
+    pub headless: bool,
+    // Start with the debug overlay shown
+    pub debug: bool,
+    // Write gameplay events to this file as JSON lines
+    pub record: Option<PathBuf>,
+    // Play back this replay file, in its world and mode, instead of taking input
+    pub replay: Option<PathBuf>,
+    // Run the benchmark instead of the game, with these settings
+    pub bench: bool,
+    pub bench_config: BenchConfig,
+}
+
+impl CliArgs {
+    // Parse the process arguments, printing usage and exiting on anything unrecognized
+    pub fn parse() -> Self {
+        match Self::parse_from(std::env::args().skip(1)) {
+            Ok(args) => args,
+            Err(error) => {
+                println!("{}\n{}", error, USAGE);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    // Parse a list of arguments, not including the program name
+    pub fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        // Whether any benchmark-only flag was given
+        let mut bench_options = false;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // Accept both --flag value and --flag=value
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || inline_value.clone().or_else(|| args.next()).ok_or(format!("{} needs a value", flag));
+            match flag.as_str() {
+                "--seed" => {
+                    let code = value()?;
+                    parsed.seed = Some(TerrainConfig::from_code(&code).ok_or(format!("Invalid seed '{}'", code))?);
+                }
+                "--resolution" => parsed.resolution = Some(parse_resolution(&value()?)?),
+                "--mode" => parsed.mode = Some(parse_mode(&value()?)?),
+                "--fullscreen" => parsed.fullscreen = true,
+                "--headless" => parsed.headless = true,
+                "--debug" => parsed.debug = true,
+                "--record" => parsed.record = Some(PathBuf::from(value()?)),
+                "--replay" => parsed.replay = Some(PathBuf::from(value()?)),
+                "--bench" => parsed.bench = true,
+                "--steps" => {
+                    parsed.bench_config.steps = parse_count(&flag, &value()?)?;
+                    bench_options = true;
+                }
+                "--projectiles" => {
+                    parsed.bench_config.projectiles = parse_count(&flag, &value()?)?;
+                    bench_options = true;
+                }
+                "--help" | "-h" => {
+                    println!("{}", USAGE);
+                    std::process::exit(0);
+                }
+                _ => return Err(format!("Unknown argument '{}'", flag)),
+            }
+        }
+        if bench_options && !parsed.bench {
+            return Err("--steps and --projectiles only apply with --bench".to_string());
+        }
+        Ok(parsed)
+    }
+}
+
+// Read a size such as 1920x1080
+fn parse_resolution(text: &str) -> Result<(f32, f32), String> {
+    let invalid = || format!("Invalid resolution '{}', expected WIDTHxHEIGHT", text);
+    let (width, height) = text.split_once(['x', 'X']).ok_or_else(invalid)?;
+    let width: u32 = width.trim().parse().map_err(|_| invalid())?;
+    let height: u32 = height.trim().parse().map_err(|_| invalid())?;
+    if width == 0 || height == 0 {
+        return Err(invalid());
+    }
+    Ok((width as f32, height as f32))
+}
+
+// Read a positive whole number for a flag
+fn parse_count<T: std::str::FromStr + Default + PartialEq>(flag: &str, text: &str) -> Result<T, String> {
+    match text.trim().parse::<T>() {
+        Ok(count) if count != T::default() => Ok(count),
+        _ => Err(format!("Invalid {} '{}', expected a positive number", flag, text)),
+    }
+}
+
+// Read a mode name, ignoring case, dashes and underscores
+fn parse_mode(text: &str) -> Result<GameMode, String> {
+    let name: String = text
+        .chars()
+        .filter(|c| *c != '-' && *c != '_')
+        .collect::<String>()
+        .to_lowercase();
+    match name.as_str() {
+        "freeplay" | "free" => Ok(GameMode::FreePlay),
+        "targetpractice" | "targets" => Ok(GameMode::TargetPractice),
+        "timetrial" => Ok(GameMode::TimeTrial),
+        "golf" => Ok(GameMode::Golf),
+        "kingofthehill" | "koth" => Ok(GameMode::KingOfTheHill),
+        "downhillrace" | "downhill" => Ok(GameMode::DownhillRace),
+        "bossfight" | "boss" => Ok(GameMode::BossFight),
+        _ => Err(format!("Unknown mode '{}'", text)),
+    }
+}
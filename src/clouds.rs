@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+use bevy::color::{ColorToComponents, Mix};
+use bevy::pbr::{NotShadowCaster, NotShadowReceiver};
+use bevy::render::render_resource::{AsBindGroup, ShaderRef, ShaderType};
+use bevy::render::view::NoFrustumCulling;
+use bevy::transform::TransformSystem;
+use crate::audio::AmbientConditions;
+use crate::camera::FollowCamera;
+use crate::golf::Wind;
+use crate::terrain::ChunkManager;
+use crate::terrain_material::TerrainMaterial;
+
+// Cloud layer constants
+const CLOUD_SHADER: &str = "shaders/clouds.wgsl";
+const CLOUD_NOISE_SHADER: &str = "shaders/cloud_noise.wgsl"; // Noise module the cloud and terrain shaders both import
+const CLOUD_ALTITUDE: f32 = 160.0; // Height of the cloud sheet, well above the highest hills
+const CLOUD_EXTENT: f32 = 600.0; // Half width of the sheet around the camera, inside the far plane at its corners
+const FADE_START: f32 = 250.0; // Distance from the camera where the clouds start thinning toward the sheet's edge
+const FADE_END: f32 = 550.0; // Distance where they are gone
+const CLOUD_SCALE: f32 = 180.0; // World units across one cell of the cloud noise
+const CLEAR_COVERAGE: f32 = 0.35; // Share of the sky the clouds cover on a dry day
+const OVERCAST_COVERAGE: f32 = 0.85; // Share they cover in the heaviest rain
+const CLOUD_SOFTNESS: f32 = 0.25; // Noise range over which a cloud's edge goes from clear to thick
+const CLOUD_OPACITY: f32 = 0.85; // How much of the sky behind the thickest cloud is hidden
+const BREEZE: Vec2 = Vec2::new(3.0, 1.5); // Drift in m/s with no wind to speak of
+const WIND_DRIFT: f32 = 2.5; // Extra drift in m/s for each m/s² of golf wind
+const DRIFT_RESPONSE: f32 = 0.3; // How quickly the drift turns toward a new wind, per second
+const COVERAGE_RESPONSE: f32 = 0.1; // How quickly the coverage follows the rain, per second
+const SHADOW_STRENGTH: f32 = 0.35; // How far the thickest cloud darkens the ground under it in full sun
+const LOW_SUN: f32 = 0.2; // Sun elevation, as the sine of its angle, below which the shadows fade out
+const MAX_SLANT: f32 = 4.0; // Furthest the shadows are pushed sideways per meter below the clouds
+
+// Cloud colors by day, by night and under rain
+const DAY_CLOUD: Color = Color::srgb(0.95, 0.96, 0.98);
+const NIGHT_CLOUD: Color = Color::srgb(0.08, 0.09, 0.12);
+const RAIN_CLOUD: Color = Color::srgb(0.45, 0.47, 0.5);
+
+// Values the cloud shader reads, matching CloudSettings in clouds.wgsl
+#[derive(ShaderType, Reflect, Debug, Clone, Copy)]
+pub struct CloudSettings {
+    pub color: Vec4,
+    pub offset: Vec2,
+    pub scale: f32,
+    pub coverage: f32,
+    pub softness: f32,
+    pub opacity: f32,
+    pub fade_start: f32,
+    pub fade_end: f32,
+}
+
+// Unlit, see-through sheet of drifting noise
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct CloudMaterial {
+    #[uniform(0)]
+    pub settings: CloudSettings,
+}
+
+impl Material for CloudMaterial {
+    fn fragment_shader() -> ShaderRef {
+        CLOUD_SHADER.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Blend
+    }
+}
+
+// Marker for the cloud sheet, kept over the camera
+#[derive(Component)]
+pub struct CloudLayer;
+
+// The cloud layer's state: how far the pattern has drifted, how fast it is going and how much of the
+// sky it covers. The drift follows the wind and the coverage the rain, and the terrain shader is kept
+// in step so the shadows move with the clouds.
+#[derive(Resource)]
+pub struct Clouds {
+    material: Handle<CloudMaterial>,
+    // Held so the shared noise module stays loaded for the shaders importing it
+    _noise_shader: Handle<Shader>,
+    pub offset: Vec2,
+    pub drift: Vec2,
+    pub coverage: f32,
+}
+
+// Spawn the cloud sheet, facing down so it shows from below
+pub fn setup_clouds(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<CloudMaterial>>,
+    asset_server: Res<AssetServer>,
+) {
+    let material = materials.add(CloudMaterial {
+        settings: CloudSettings {
+            color: DAY_CLOUD.to_linear().to_vec4(),
+            offset: Vec2::ZERO,
+            scale: CLOUD_SCALE,
+            coverage: CLEAR_COVERAGE,
+            softness: CLOUD_SOFTNESS,
+            opacity: CLOUD_OPACITY,
+            fade_start: FADE_START,
+            fade_end: FADE_END,
+        },
+    });
+    commands.spawn((
+        CloudLayer,
+        Mesh3d(meshes.add(Plane3d::new(Vec3::NEG_Y, Vec2::splat(CLOUD_EXTENT)))),
+        MeshMaterial3d(material.clone()),
+        Transform::from_xyz(0.0, CLOUD_ALTITUDE, 0.0),
+        NoFrustumCulling,
+        NotShadowCaster,
+        NotShadowReceiver,
+        Name::new("Cloud Layer"),
+    ));
+    commands.insert_resource(Clouds {
+        material,
+        _noise_shader: asset_server.load(CLOUD_NOISE_SHADER),
+        offset: Vec2::ZERO,
+        drift: BREEZE,
+        coverage: CLEAR_COVERAGE,
+    });
+}
+
+// Drift the clouds with the wind, thicken them with the rain, and pass the result to the cloud and
+// terrain shaders along with where the sun throws the shadows
+pub fn update_clouds(
+    mut clouds: ResMut<Clouds>,
+    mut cloud_materials: ResMut<Assets<CloudMaterial>>,
+    mut terrain_materials: ResMut<Assets<TerrainMaterial>>,
+    chunk_manager: Res<ChunkManager>,
+    wind: Res<Wind>,
+    conditions: Res<AmbientConditions>,
+    light_query: Query<&Transform, With<DirectionalLight>>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
+    let target_drift = BREEZE + wind.velocity * WIND_DRIFT;
+    let drift = clouds.drift.lerp(target_drift, (DRIFT_RESPONSE * dt).min(1.0));
+    let target_coverage = CLEAR_COVERAGE + (OVERCAST_COVERAGE - CLEAR_COVERAGE) * conditions.rain;
+    clouds.coverage += (target_coverage - clouds.coverage) * (COVERAGE_RESPONSE * dt).min(1.0);
+    clouds.drift = drift;
+    // The shaders sample at position plus offset, so the pattern moves against the offset
+    clouds.offset -= drift * dt;
+
+    let color = NIGHT_CLOUD.mix(&DAY_CLOUD, conditions.daylight).mix(&RAIN_CLOUD, conditions.rain * conditions.daylight);
+    if let Some(material) = cloud_materials.get_mut(&clouds.material) {
+        material.settings.color = color.to_linear().to_vec4();
+        material.settings.offset = clouds.offset;
+        material.settings.coverage = clouds.coverage;
+    }
+
+    // The light shines along its forward direction, so the sun is behind it
+    let sun = light_query.get_single().map(|transform| transform.back().as_vec3()).unwrap_or(Vec3::Y);
+    let (slant, shadow) = if sun.y > 0.0 {
+        let slant = (Vec2::new(sun.x, sun.z) / sun.y).clamp_length_max(MAX_SLANT);
+        (slant, SHADOW_STRENGTH * (sun.y / LOW_SUN).min(1.0))
+    } else {
+        (Vec2::ZERO, 0.0)
+    };
+    if let Some(material) = terrain_materials.get_mut(&chunk_manager.material_handle) {
+        let settings = &mut material.extension.settings;
+        settings.cloud_offset = clouds.offset;
+        settings.cloud_slant = slant;
+        settings.cloud_scale = CLOUD_SCALE;
+        settings.cloud_coverage = clouds.coverage;
+        settings.cloud_softness = CLOUD_SOFTNESS;
+        settings.cloud_altitude = CLOUD_ALTITUDE;
+        settings.cloud_shadow = shadow;
+    }
+}
+
+// Keep the sheet over the camera at a fixed altitude; the pattern is in world space, so it stays put
+// while the sheet slides under it
+pub fn follow_camera_with_clouds(
+    camera_query: Query<&Transform, With<FollowCamera>>,
+    mut layer_query: Query<&mut Transform, (With<CloudLayer>, Without<FollowCamera>)>,
+) {
+    let (Ok(camera), Ok(mut layer)) = (camera_query.get_single(), layer_query.get_single_mut()) else {
+        return;
+    };
+    layer.translation = Vec3::new(camera.translation.x, CLOUD_ALTITUDE, camera.translation.z);
+}
+
+// Plugin for the drifting cloud layer and its shadows on the terrain
+pub struct CloudPlugin;
+
+impl Plugin for CloudPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugins(MaterialPlugin::<CloudMaterial>::default())
+            .add_systems(Startup, setup_clouds)
+            .add_systems(Update, update_clouds)
+            .add_systems(PostUpdate, follow_camera_with_clouds.before(TransformSystem::TransformPropagate));
+    }
+}
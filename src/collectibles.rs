@@ -0,0 +1,241 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use rand::Rng;
+use std::f32::consts::FRAC_PI_2;
+use crate::terrain::{chunk_rng, get_terrain_height, queue_new_chunks, ScatterQueue, TerrainChunk, CHUNK_SIZE};
+use crate::budget::WorkBudgets;
+use crate::multiball::SplitBall;
+use crate::player::Player;
+use crate::score::Score;
+use crate::save::SaveData;
+use crate::game_events::GameEvent;
+use crate::particles::{spawn_burst, ParticleEffect};
+use crate::spatial::{Spatial, SpatialGrid};
+use crate::GameState;
+
+// Collectible constants
+const COLLECTIBLES_PER_CHUNK: usize = 6; // Pickups scattered over each chunk
+const ORB_CHANCE: f64 = 0.15; // Chance that a pickup is a rare orb instead of a coin
+const HOVER_HEIGHT: f32 = 1.0; // Height of a pickup above the terrain
+const BOB_HEIGHT: f32 = 0.15; // Amplitude of a pickup's bobbing
+const SPIN_SPEED: f32 = 2.5; // Radians per second
+const COLLECT_RADIUS: f32 = 0.9; // Distance from the player's center that picks something up
+const POINTS_PER_COIN: u32 = 25; // Score for each coin of value
+const SPARKLE_COUNT: u32 = 12; // Particles in a collection burst
+const COLLECTIBLE_SALT: u64 = 0; // Chunk random stream for pickups
+
+// Kinds of pickup and what they are worth
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollectibleKind {
+    Coin,
+    Orb,
+}
+
+impl CollectibleKind {
+    pub fn value(self) -> u32 {
+        match self {
+            CollectibleKind::Coin => 1,
+            CollectibleKind::Orb => 5,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            CollectibleKind::Coin => Color::srgb(1.0, 0.8, 0.2),
+            CollectibleKind::Orb => Color::srgb(0.3, 0.7, 1.0),
+        }
+    }
+}
+
+// A spinning pickup, parented to the terrain chunk it was scattered on
+#[derive(Component)]
+pub struct Collectible {
+    pub kind: CollectibleKind,
+    // Chunk and slot it came from, so a collected pickup stays gone when the chunk reloads
+    pub chunk: (i32, i32),
+    pub index: usize,
+    // Resting height relative to the chunk
+    pub base_y: f32,
+}
+
+// Pickups collected this run, by chunk and slot
+#[derive(Resource, Default)]
+pub struct CollectedPickups {
+    pub collected: HashSet<((i32, i32), usize)>,
+}
+
+// Chunks still waiting for their pickups; a resource rather than a local so a reset can refill it
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct PickupQueue(ScatterQueue);
+
+// Shared meshes and materials for pickups
+#[derive(Resource)]
+pub struct CollectibleAssets {
+    pub coin_mesh: Handle<Mesh>,
+    pub orb_mesh: Handle<Mesh>,
+    pub coin_material: Handle<StandardMaterial>,
+    pub orb_material: Handle<StandardMaterial>,
+}
+
+impl CollectibleAssets {
+    fn get(&self, kind: CollectibleKind) -> (Handle<Mesh>, Handle<StandardMaterial>) {
+        match kind {
+            CollectibleKind::Coin => (self.coin_mesh.clone(), self.coin_material.clone()),
+            CollectibleKind::Orb => (self.orb_mesh.clone(), self.orb_material.clone()),
+        }
+    }
+}
+
+// Scatter a chunk's pickups as children of the chunk, skipping ones already collected
+fn populate_chunk(
+    commands: &mut Commands,
+    assets: &CollectibleAssets,
+    collected: &CollectedPickups,
+    chunk_entity: Entity,
+    chunk: (i32, i32),
+) {
+    // A chunk rebuilt before its turn comes back as a new entity
+    let Some(mut chunk_commands) = commands.get_entity(chunk_entity) else {
+        return;
+    };
+    let mut rng = chunk_rng(chunk.0, chunk.1, COLLECTIBLE_SALT);
+    chunk_commands.with_children(|parent| {
+        for index in 0..COLLECTIBLES_PER_CHUNK {
+            // Draw every slot's values even when skipping it, so the layout never shifts
+            let local_x = rng.gen_range(0.0..CHUNK_SIZE);
+            let local_z = rng.gen_range(0.0..CHUNK_SIZE);
+            let kind = if rng.gen_bool(ORB_CHANCE) { CollectibleKind::Orb } else { CollectibleKind::Coin };
+            if collected.collected.contains(&(chunk, index)) {
+                continue;
+            }
+
+            let world_x = chunk.0 as f32 * CHUNK_SIZE + local_x;
+            let world_z = chunk.1 as f32 * CHUNK_SIZE + local_z;
+            let base_y = get_terrain_height(world_x, world_z) + HOVER_HEIGHT;
+            let (mesh, material) = assets.get(kind);
+            parent.spawn((
+                Collectible { kind, chunk, index, base_y },
+                Spatial::new(0.0),
+                Mesh3d(mesh),
+                MeshMaterial3d(material),
+                Transform::from_xyz(local_x, base_y, local_z),
+            ));
+        }
+    });
+}
+
+// Create the shared pickup meshes and materials
+pub fn setup_collectible_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut material = |kind: CollectibleKind| materials.add(StandardMaterial {
+        base_color: kind.color(),
+        emissive: (kind.color().to_linear() * 0.6).into(),
+        metallic: 0.6,
+        perceptual_roughness: 0.3,
+        ..default()
+    });
+    commands.insert_resource(CollectibleAssets {
+        coin_mesh: meshes.add(Cylinder::new(0.3, 0.06)),
+        orb_mesh: meshes.add(Sphere::new(0.25)),
+        coin_material: material(CollectibleKind::Coin),
+        orb_material: material(CollectibleKind::Orb),
+    });
+}
+
+// Scatter pickups over chunks as they are generated, a few chunks a frame within the scatter budget
+pub fn populate_new_chunks(
+    mut commands: Commands,
+    assets: Res<CollectibleAssets>,
+    collected: Res<CollectedPickups>,
+    budgets: Res<WorkBudgets>,
+    mut queue: ResMut<PickupQueue>,
+    chunk_query: Query<(Entity, &TerrainChunk), Added<TerrainChunk>>,
+) {
+    queue_new_chunks(&mut queue, &chunk_query);
+    queue.run(budgets.scatter_ms, |(entity, chunk)| {
+        populate_chunk(&mut commands, &assets, &collected, entity, chunk);
+    });
+}
+
+// Spin and bob pickups in place
+pub fn animate_collectibles(mut query: Query<(&mut Transform, &Collectible)>, time: Res<Time>) {
+    let t = time.elapsed_secs();
+    for (mut transform, collectible) in query.iter_mut() {
+        // Offset each pickup's phase by its slot so they don't move in lockstep
+        let phase = t * SPIN_SPEED + collectible.index as f32;
+        transform.rotation = Quat::from_rotation_y(phase) * Quat::from_rotation_x(FRAC_PI_2);
+        transform.translation.y = collectible.base_y + phase.sin() * BOB_HEIGHT;
+    }
+}
+
+// Pick up anything the player rolls into, paying out score and coins with a burst of sparkles
+pub fn collect_pickups(
+    mut commands: Commands,
+    mut collected: ResMut<CollectedPickups>,
+    mut score: ResMut<Score>,
+    mut save: ResMut<SaveData>,
+    mut game_events: EventWriter<GameEvent>,
+    ball_query: Query<&Transform, Or<(With<Player>, With<SplitBall>)>>,
+    collectible_query: Query<(&Collectible, &GlobalTransform)>,
+    spatial_grid: Res<SpatialGrid>,
+) {
+    // Every ball of a split multiball picks up what it rolls over
+    let nearby = ball_query.iter().flat_map(|ball| {
+        let ball_pos = ball.translation;
+        spatial_grid.nearby(ball_pos, COLLECT_RADIUS).into_iter().map(move |entity| (entity, ball_pos))
+    });
+    for (entity, ball_pos) in nearby {
+        let Ok((collectible, transform)) = collectible_query.get(entity) else {
+            continue;
+        };
+        let position = transform.translation();
+        if position.distance(ball_pos) > COLLECT_RADIUS || !collected.collected.insert((collectible.chunk, collectible.index)) {
+            continue;
+        }
+        commands.entity(entity).despawn_recursive();
+
+        let value = collectible.kind.value();
+        score.points += value * POINTS_PER_COIN;
+        save.coins += value;
+        game_events.send(GameEvent::PickupCollected { value });
+
+        spawn_burst(&mut commands, ParticleEffect::sparkles(collectible.kind.color()), position, SPARKLE_COUNT);
+    }
+}
+
+// Bring back every pickup for a new run
+pub fn reset_collectibles(
+    mut commands: Commands,
+    mut collected: ResMut<CollectedPickups>,
+    collectible_query: Query<Entity, With<Collectible>>,
+    mut queue: ResMut<PickupQueue>,
+    chunk_query: Query<(Entity, &TerrainChunk)>,
+) {
+    for entity in collectible_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    collected.collected.clear();
+    // Every loaded chunk goes back through the scatter queue, replacing any still waiting there
+    queue.clear();
+    queue.extend(chunk_query.iter().map(|(entity, chunk)| (entity, (chunk.chunk_x, chunk.chunk_z))));
+}
+
+// Plugin for coins and orbs scattered over the terrain
+pub struct CollectiblePlugin;
+
+impl Plugin for CollectiblePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<CollectedPickups>()
+            .init_resource::<PickupQueue>()
+            .add_systems(Startup, setup_collectible_assets)
+            .add_systems(OnExit(GameState::GameOver), reset_collectibles)
+            // Chunks generate behind the main menu too, so they need their pickups from the start
+            .add_systems(Update, populate_new_chunks)
+            .add_systems(Update, animate_collectibles)
+            .add_systems(Update, collect_pickups.run_if(in_state(GameState::Playing)));
+    }
+}
@@ -0,0 +1,216 @@
+use bevy::prelude::*;
+use std::f32::consts::{PI, TAU};
+use crate::camera::CameraSettings;
+use crate::player::Player;
+use crate::GameState;
+
+// Compass layout constants
+const COMPASS_WIDTH: f32 = 420.0; // Width of the strip in pixels
+const COMPASS_HEIGHT: f32 = 32.0; // Height of the strip in pixels
+const COMPASS_FOV: f32 = PI; // Bearing range shown across the strip (180 degrees)
+const COMPASS_LABEL_WIDTH: f32 = 30.0; // Width reserved for each direction label
+const COMPASS_ICON_SIZE: f32 = 8.0; // Diameter of marker icons in pixels
+const COMPASS_MARKER_RANGE: f32 = 80.0; // Markers farther than this are left off the compass
+
+// Directions written on the strip, clockwise from north
+const COMPASS_LABELS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+// Marker for the compass strip root
+#[derive(Component)]
+pub struct CompassRoot;
+
+// Direction label on the strip at a fixed bearing (0 = north, clockwise)
+#[derive(Component)]
+pub struct CompassLabel {
+    pub bearing: f32,
+}
+
+// Entities with this component show up as an icon on the compass while nearby
+#[derive(Component)]
+pub struct CompassMarker {
+    pub color: Color,
+}
+
+// UI icon that tracks a world entity carrying a CompassMarker
+#[derive(Component)]
+pub struct CompassIcon {
+    pub target: Entity,
+}
+
+// Bearing of a horizontal offset; north is -Z and east is +X
+pub fn bearing_of(offset: Vec3) -> f32 {
+    offset.x.atan2(-offset.z)
+}
+
+// Signed angle from the heading to a bearing, wrapped into -PI..PI
+fn relative_bearing(bearing: f32, heading: f32) -> f32 {
+    (bearing - heading + PI).rem_euclid(TAU) - PI
+}
+
+// Horizontal position on the strip for a relative bearing, or None when out of view
+fn strip_position(relative: f32) -> Option<f32> {
+    if relative.abs() > COMPASS_FOV * 0.5 {
+        return None;
+    }
+    Some(COMPASS_WIDTH * 0.5 + relative / COMPASS_FOV * COMPASS_WIDTH)
+}
+
+// Build the compass strip at the top of the screen
+pub fn spawn_compass(mut commands: Commands) {
+    commands
+        .spawn((
+            CompassRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.0),
+                left: Val::Percent(50.0),
+                margin: UiRect::left(Val::Px(-COMPASS_WIDTH * 0.5)),
+                width: Val::Px(COMPASS_WIDTH),
+                height: Val::Px(COMPASS_HEIGHT),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.35)),
+            // Hidden until a game starts
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            for (i, label) in COMPASS_LABELS.iter().enumerate() {
+                // Cardinal directions stand out from the intercardinals
+                let cardinal = i % 2 == 0;
+                parent.spawn((
+                    CompassLabel { bearing: i as f32 * TAU / COMPASS_LABELS.len() as f32 },
+                    Text::new(*label),
+                    TextFont {
+                        font_size: if cardinal { 20.0 } else { 14.0 },
+                        ..default()
+                    },
+                    TextColor(if cardinal { Color::WHITE } else { Color::srgb(0.7, 0.7, 0.7) }),
+                    TextLayout::new_with_justify(JustifyText::Center),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        width: Val::Px(COMPASS_LABEL_WIDTH),
+                        top: Val::Px(if cardinal { 4.0 } else { 8.0 }),
+                        ..default()
+                    },
+                ));
+            }
+            // Center tick showing the current heading
+            parent.spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(COMPASS_WIDTH * 0.5 - 1.0),
+                    width: Val::Px(2.0),
+                    height: Val::Px(6.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(1.0, 0.85, 0.2)),
+            ));
+        });
+}
+
+// Show the compass while playing
+pub fn show_compass(mut query: Query<&mut Visibility, With<CompassRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+// Hide the compass outside of gameplay
+pub fn hide_compass(mut query: Query<&mut Visibility, With<CompassRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Create icons for new compass markers and remove icons whose entity is gone
+pub fn sync_compass_icons(
+    mut commands: Commands,
+    root_query: Query<Entity, With<CompassRoot>>,
+    new_markers: Query<(Entity, &CompassMarker), Added<CompassMarker>>,
+    markers: Query<(), With<CompassMarker>>,
+    icons: Query<(Entity, &CompassIcon)>,
+) {
+    let Ok(root) = root_query.get_single() else {
+        return;
+    };
+
+    for (entity, marker) in new_markers.iter() {
+        commands.entity(root).with_children(|parent| {
+            parent.spawn((
+                CompassIcon { target: entity },
+                Node {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(2.0),
+                    width: Val::Px(COMPASS_ICON_SIZE),
+                    height: Val::Px(COMPASS_ICON_SIZE),
+                    ..default()
+                },
+                BackgroundColor(marker.color),
+                BorderRadius::MAX,
+                Visibility::Hidden,
+            ));
+        });
+    }
+
+    for (icon_entity, icon) in icons.iter() {
+        if markers.get(icon.target).is_err() {
+            commands.entity(icon_entity).despawn_recursive();
+        }
+    }
+}
+
+// Slide labels and icons along the strip as the camera turns
+pub fn update_compass(
+    camera_settings: Res<CameraSettings>,
+    player_query: Query<&Transform, With<Player>>,
+    marker_query: Query<&GlobalTransform, With<CompassMarker>>,
+    mut label_query: Query<(&CompassLabel, &mut Node, &mut Visibility), Without<CompassIcon>>,
+    mut icon_query: Query<(&CompassIcon, &mut Node, &mut Visibility), Without<CompassLabel>>,
+) {
+    // The camera sits behind the player at its yaw, so it faces the opposite way
+    let heading = -camera_settings.yaw;
+
+    for (label, mut node, mut visibility) in label_query.iter_mut() {
+        match strip_position(relative_bearing(label.bearing, heading)) {
+            Some(x) => {
+                node.left = Val::Px(x - COMPASS_LABEL_WIDTH * 0.5);
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation;
+
+    for (icon, mut node, mut visibility) in icon_query.iter_mut() {
+        let Ok(marker_transform) = marker_query.get(icon.target) else {
+            continue;
+        };
+        let offset = marker_transform.translation() - player_pos;
+        let in_range = Vec2::new(offset.x, offset.z).length() <= COMPASS_MARKER_RANGE;
+        match strip_position(relative_bearing(bearing_of(offset), heading)).filter(|_| in_range) {
+            Some(x) => {
+                node.left = Val::Px(x - COMPASS_ICON_SIZE * 0.5);
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+// Plugin for the compass strip
+pub struct CompassPlugin;
+
+impl Plugin for CompassPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, spawn_compass)
+            .add_systems(OnEnter(GameState::Playing), show_compass)
+            .add_systems(OnExit(GameState::Playing), hide_compass)
+            .add_systems(Update, (sync_compass_icons, update_compass.after(sync_compass_icons)));
+    }
+}
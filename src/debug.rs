@@ -0,0 +1,408 @@
+use bevy::prelude::*;
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use crate::enemy::{steer_toward, Enemy};
+use crate::player::{Player, PlayerPhysics};
+use crate::projectile::{ballistic_position, Hostile, Projectile};
+use crate::race_ai::RaceOpponent;
+use crate::spatial::Spatial;
+use crate::terrain::{chunk_coords, get_terrain_height, sample, TerrainChunk, CHUNK_SIZE};
+use crate::tuning::LiveTuning;
+
+// Debug gizmo constants
+const GIZMO_RANGE: f32 = 40.0; // Distance from the player within which radii and paths are drawn
+const GIZMO_LIFT: f32 = 0.1; // How far ground-hugging gizmos float above the terrain
+const BALL_RADIUS: f32 = 0.5; // Radius of the rolling balls, which have no Spatial of their own
+const VELOCITY_ARROW_SCALE: f32 = 0.5; // Arrow length per unit of speed
+const GRADIENT_GRID_RADIUS: i32 = 6; // Gradient arrows drawn this many cells each way around the player
+const GRADIENT_GRID_SPACING: f32 = 2.0; // Distance between gradient arrows
+const GRADIENT_ARROW_SCALE: f32 = 2.0; // Arrow length per unit of slope
+const GRADIENT_ARROW_MAX: f32 = 1.8; // Longest a gradient arrow gets, so cliffs don't cross their neighbours
+const STEEP_GRADIENT: f32 = 1.0; // Slope drawn fully red
+const CHUNK_EDGE_SEGMENTS: usize = 20; // Line segments per chunk edge, following the ground
+const ARC_TIME_STEP: f32 = 0.05; // Seconds between points on a predicted projectile arc
+const ARC_MAX_STEPS: usize = 120; // Longest predicted arc drawn
+const STEER_ARROW_LENGTH: f32 = 2.0; // Length of an enemy's steering arrow
+
+// Counters published by gameplay systems for the debug overlay
+#[derive(Resource, Default)]
+pub struct DebugStats {
+    // Live projectiles, updated by the projectile systems
+    pub projectile_count: usize,
+    // Loaded terrain chunks, updated by the terrain streaming system
+    pub loaded_chunks: usize,
+    // Chunk the player is currently in, updated by the terrain streaming system
+    pub player_chunk: (i32, i32),
+}
+
+// Whether the F3 overlay is shown
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    pub visible: bool,
+}
+
+// Layers of gizmos the overlay can draw over the world, each switched with Alt and its number
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GizmoCategory {
+    // Every ball's velocity and momentum
+    Velocity,
+    // Downhill arrows on the ground around the player
+    TerrainGradient,
+    // Edges of the loaded terrain chunks
+    ChunkBounds,
+    // Where each projectile in flight will come down
+    ProjectileArcs,
+    // Collision radii of balls and spatially indexed things
+    CollisionRadii,
+    // Race opponent paths and enemy steering
+    AiPaths,
+}
+
+impl GizmoCategory {
+    pub const ALL: [GizmoCategory; 6] = [
+        GizmoCategory::Velocity,
+        GizmoCategory::TerrainGradient,
+        GizmoCategory::ChunkBounds,
+        GizmoCategory::ProjectileArcs,
+        GizmoCategory::CollisionRadii,
+        GizmoCategory::AiPaths,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            GizmoCategory::Velocity => "Velocity / momentum",
+            GizmoCategory::TerrainGradient => "Terrain gradient",
+            GizmoCategory::ChunkBounds => "Chunk boundaries",
+            GizmoCategory::ProjectileArcs => "Projectile arcs",
+            GizmoCategory::CollisionRadii => "Collision radii",
+            GizmoCategory::AiPaths => "AI paths",
+        }
+    }
+
+    fn key(self) -> KeyCode {
+        match self {
+            GizmoCategory::Velocity => KeyCode::Digit1,
+            GizmoCategory::TerrainGradient => KeyCode::Digit2,
+            GizmoCategory::ChunkBounds => KeyCode::Digit3,
+            GizmoCategory::ProjectileArcs => KeyCode::Digit4,
+            GizmoCategory::CollisionRadii => KeyCode::Digit5,
+            GizmoCategory::AiPaths => KeyCode::Digit6,
+        }
+    }
+}
+
+// Which gizmo layers are switched on; they only draw while the overlay is shown
+#[derive(Resource, Default)]
+pub struct DebugGizmos {
+    pub enabled: Vec<GizmoCategory>,
+}
+
+impl DebugGizmos {
+    pub fn shows(&self, category: GizmoCategory) -> bool {
+        self.enabled.contains(&category)
+    }
+
+    pub fn toggle(&mut self, category: GizmoCategory) {
+        if self.shows(category) {
+            self.enabled.retain(|enabled| *enabled != category);
+        } else {
+            self.enabled.push(category);
+        }
+    }
+}
+
+// Run condition for a gizmo layer's drawing system
+fn gizmos_shown(category: GizmoCategory) -> impl Fn(Res<DebugOverlay>, Res<DebugGizmos>) -> bool {
+    move |overlay, gizmos| overlay.visible && gizmos.shows(category)
+}
+
+// Marker for the overlay text
+#[derive(Component)]
+pub struct DebugOverlayText;
+
+// Spawn the overlay text in the top-right corner, hidden unless the overlay starts on
+pub fn spawn_debug_overlay(mut commands: Commands, overlay: Res<DebugOverlay>) {
+    commands.spawn((
+        DebugOverlayText,
+        Text::new(""),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.8, 1.0, 0.8)),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(12.0),
+            top: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+        GlobalZIndex(5),
+        if overlay.visible { Visibility::Inherited } else { Visibility::Hidden },
+    ));
+}
+
+// Toggle the overlay with F3
+pub fn toggle_debug_overlay(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut query: Query<&mut Visibility, With<DebugOverlayText>>,
+) {
+    if keys.just_pressed(KeyCode::F3) {
+        overlay.visible = !overlay.visible;
+        for mut visibility in query.iter_mut() {
+            *visibility = if overlay.visible { Visibility::Inherited } else { Visibility::Hidden };
+        }
+    }
+}
+
+// Alt and a number switch a gizmo layer while the overlay is shown
+pub fn toggle_debug_gizmos(
+    keys: Res<ButtonInput<KeyCode>>,
+    overlay: Res<DebugOverlay>,
+    mut gizmos: ResMut<DebugGizmos>,
+) {
+    if !overlay.visible || !keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]) {
+        return;
+    }
+    for category in GizmoCategory::ALL {
+        if keys.just_pressed(category.key()) {
+            gizmos.toggle(category);
+        }
+    }
+}
+
+// Refresh the overlay text from the diagnostics and debug stats
+pub fn update_debug_overlay(
+    overlay: Res<DebugOverlay>,
+    stats: Res<DebugStats>,
+    gizmos: Res<DebugGizmos>,
+    diagnostics: Res<DiagnosticsStore>,
+    player_query: Query<&PlayerPhysics, With<Player>>,
+    mut text_query: Query<&mut Text, With<DebugOverlayText>>,
+) {
+    if !overlay.visible {
+        return;
+    }
+
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or(0.0);
+    let frame_time = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|frame_time| frame_time.smoothed())
+        .unwrap_or(0.0);
+    let velocity = player_query
+        .get_single()
+        .map(|physics| physics.velocity)
+        .unwrap_or(Vec3::ZERO);
+    let mut layers = String::from("\nGizmos (Alt+number):");
+    for (number, category) in GizmoCategory::ALL.iter().enumerate() {
+        let mark = if gizmos.shows(*category) { "x" } else { " " };
+        layers.push_str(&format!("\n{} [{}] {}", number + 1, mark, category.label()));
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.0 = format!(
+            "FPS: {:.0} ({:.2} ms)\nProjectiles: {}\nChunks loaded: {}\nPlayer chunk: ({}, {})\nVelocity: ({:.2}, {:.2}, {:.2}) |{:.2}|\n{}",
+            fps,
+            frame_time,
+            stats.projectile_count,
+            stats.loaded_chunks,
+            stats.player_chunk.0,
+            stats.player_chunk.1,
+            velocity.x,
+            velocity.y,
+            velocity.z,
+            velocity.length(),
+            layers,
+        );
+    }
+}
+
+// Velocity in green and momentum per unit of mass in blue from every ball, so the two can be compared
+// as momentum drags the velocity toward it
+pub fn draw_velocity_gizmos(mut gizmos: Gizmos, ball_query: Query<(&Transform, &PlayerPhysics)>) {
+    for (transform, physics) in ball_query.iter() {
+        let position = transform.translation;
+        if physics.velocity.length_squared() > 0.001 {
+            gizmos.arrow(position, position + physics.velocity * VELOCITY_ARROW_SCALE, Color::srgb(0.2, 1.0, 0.3));
+        }
+        let momentum = physics.momentum / physics.mass.max(0.001);
+        if momentum.length_squared() > 0.001 {
+            gizmos.arrow(position, position + momentum * VELOCITY_ARROW_SCALE, Color::srgb(0.3, 0.5, 1.0));
+        }
+    }
+}
+
+// A grid of downhill arrows around the player, longer and redder where the ground is steeper
+pub fn draw_gradient_gizmos(mut gizmos: Gizmos, player_query: Query<&Transform, With<Player>>) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    // Snap the grid to the world so the arrows don't slide along with the player
+    let center = (player_transform.translation / GRADIENT_GRID_SPACING).round() * GRADIENT_GRID_SPACING;
+    for grid_z in -GRADIENT_GRID_RADIUS..=GRADIENT_GRID_RADIUS {
+        for grid_x in -GRADIENT_GRID_RADIUS..=GRADIENT_GRID_RADIUS {
+            let x = center.x + grid_x as f32 * GRADIENT_GRID_SPACING;
+            let z = center.z + grid_z as f32 * GRADIENT_GRID_SPACING;
+            let ground = sample(x, z);
+            let slope = ground.gradient.length();
+            if slope < 0.001 {
+                continue;
+            }
+            let downhill = -ground.gradient / slope;
+            let length = (slope * GRADIENT_ARROW_SCALE).min(GRADIENT_ARROW_MAX);
+            let start = Vec3::new(x, ground.height + GIZMO_LIFT, z);
+            let end_x = x + downhill.x * length;
+            let end_z = z + downhill.y * length;
+            let end = Vec3::new(end_x, get_terrain_height(end_x, end_z) + GIZMO_LIFT, end_z);
+            let steepness = (slope / STEEP_GRADIENT).min(1.0);
+            gizmos.arrow(start, end, Color::srgb(steepness, 1.0 - steepness, 0.2));
+        }
+    }
+}
+
+// Outline every loaded chunk along the ground, with the player's chunk in yellow
+pub fn draw_chunk_gizmos(
+    mut gizmos: Gizmos,
+    player_query: Query<&Transform, With<Player>>,
+    chunk_query: Query<&TerrainChunk>,
+) {
+    let player_chunk = player_query
+        .get_single()
+        .map(|transform| chunk_coords(transform.translation))
+        .ok();
+    for chunk in chunk_query.iter() {
+        let color = if player_chunk == Some((chunk.chunk_x, chunk.chunk_z)) {
+            Color::srgb(1.0, 0.9, 0.2)
+        } else {
+            Color::srgb(0.4, 0.8, 1.0)
+        };
+        let min = Vec2::new(chunk.chunk_x as f32, chunk.chunk_z as f32) * CHUNK_SIZE;
+        let corners = [min, min + Vec2::X * CHUNK_SIZE, min + Vec2::splat(CHUNK_SIZE), min + Vec2::Y * CHUNK_SIZE, min];
+        let points = corners.windows(2).flat_map(|edge| {
+            (0..CHUNK_EDGE_SEGMENTS).map(move |i| edge[0].lerp(edge[1], i as f32 / CHUNK_EDGE_SEGMENTS as f32))
+        });
+        let points: Vec<Vec3> = points
+            .chain(std::iter::once(min))
+            .map(|point| Vec3::new(point.x, get_terrain_height(point.x, point.y) + GIZMO_LIFT, point.y))
+            .collect();
+        gizmos.linestrip(points, color);
+    }
+}
+
+// The rest of each projectile's flight down to the ground, red for boulders thrown at the player
+pub fn draw_projectile_gizmos(
+    mut gizmos: Gizmos,
+    projectile_query: Query<(&Projectile, Has<Hostile>)>,
+    tuning: Res<LiveTuning>,
+) {
+    for (projectile, hostile) in projectile_query.iter() {
+        if projectile.stuck {
+            continue;
+        }
+        let mut points = Vec::new();
+        for i in 0..ARC_MAX_STEPS {
+            let t = projectile.age + i as f32 * ARC_TIME_STEP;
+            let point = ballistic_position(&tuning.0.projectile, projectile.start_position, projectile.initial_velocity, t);
+            points.push(point);
+            if i > 0 && point.y <= get_terrain_height(point.x, point.z) {
+                break;
+            }
+        }
+        let color = if hostile { Color::srgb(1.0, 0.3, 0.2) } else { Color::srgb(1.0, 0.8, 0.2) };
+        if let Some(landing) = points.last() {
+            gizmos.circle(
+                Isometry3d::new(*landing, Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)),
+                projectile.blast_radius,
+                color,
+            );
+        }
+        gizmos.linestrip(points, color);
+    }
+}
+
+// Collision spheres of the balls and everything in the spatial grid near the player
+pub fn draw_collision_gizmos(
+    mut gizmos: Gizmos,
+    player_query: Query<&Transform, With<Player>>,
+    spatial_query: Query<(&GlobalTransform, &Spatial)>,
+    ball_query: Query<&Transform, (With<PlayerPhysics>, Without<Spatial>)>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let near = |position: Vec3| position.distance(player_transform.translation) <= GIZMO_RANGE;
+    for (transform, spatial) in spatial_query.iter() {
+        // Pickups register with no radius; a small marker still shows where they are indexed
+        let position = transform.translation();
+        if near(position) {
+            gizmos.sphere(Isometry3d::from_translation(position), spatial.radius.max(0.1), Color::srgb(1.0, 0.5, 1.0));
+        }
+    }
+    for transform in ball_query.iter() {
+        if near(transform.translation) {
+            gizmos.sphere(Isometry3d::from_translation(transform.translation), BALL_RADIUS, Color::srgb(0.5, 1.0, 1.0));
+        }
+    }
+}
+
+// The path left ahead of each race opponent, and where each enemy is steering on its way to the player
+pub fn draw_ai_gizmos(
+    mut gizmos: Gizmos,
+    player_query: Query<&Transform, With<Player>>,
+    opponent_query: Query<(&Transform, &RaceOpponent)>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<Player>)>,
+) {
+    for (transform, opponent) in opponent_query.iter() {
+        let remaining = opponent.path.get(opponent.path_index..).unwrap_or_default();
+        let points = std::iter::once(transform.translation)
+            .chain(remaining.iter().map(|point| *point + Vec3::Y * GIZMO_LIFT));
+        gizmos.linestrip(points, Color::srgb(0.9, 0.5, 0.1));
+    }
+
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let goal = player_transform.translation;
+    for transform in enemy_query.iter() {
+        let position = transform.translation;
+        if position.distance(goal) > GIZMO_RANGE {
+            continue;
+        }
+        gizmos.line(position, goal, Color::srgba(1.0, 0.2, 0.2, 0.3));
+        let steer = steer_toward(position, goal);
+        if steer != Vec3::ZERO {
+            gizmos.arrow(position, position + steer * STEER_ARROW_LENGTH, Color::srgb(1.0, 0.2, 0.2));
+        }
+    }
+}
+
+// Plugin for the debug overlay and its gizmo layers
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugins(FrameTimeDiagnosticsPlugin)
+            .init_resource::<DebugStats>()
+            .init_resource::<DebugOverlay>()
+            .init_resource::<DebugGizmos>()
+            .add_systems(Startup, spawn_debug_overlay)
+            .add_systems(Update, (
+                toggle_debug_overlay,
+                toggle_debug_gizmos.after(toggle_debug_overlay),
+                update_debug_overlay.after(toggle_debug_gizmos),
+            ))
+            .add_systems(Update, (
+                draw_velocity_gizmos.run_if(gizmos_shown(GizmoCategory::Velocity)),
+                draw_gradient_gizmos.run_if(gizmos_shown(GizmoCategory::TerrainGradient)),
+                draw_chunk_gizmos.run_if(gizmos_shown(GizmoCategory::ChunkBounds)),
+                draw_projectile_gizmos.run_if(gizmos_shown(GizmoCategory::ProjectileArcs)),
+                draw_collision_gizmos.run_if(gizmos_shown(GizmoCategory::CollisionRadii)),
+                draw_ai_gizmos.run_if(gizmos_shown(GizmoCategory::AiPaths)),
+            ));
+    }
+}
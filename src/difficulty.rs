@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+use crate::player::{Player, PlayerPhysics};
+use crate::GameState;
+
+// Difficulty levels picked from the main menu; systems read the scales below instead of fixed constants
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Difficulty {
+    // Slow enemy fire, light damage and generous assists
+    Casual,
+    #[default]
+    Normal,
+    // Fast enemy fire, heavy damage and no assists
+    Hard,
+}
+
+impl Difficulty {
+    // Next difficulty in the menu cycle
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Casual => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Casual,
+        }
+    }
+
+    // Multiplier on how often enemies fire; reload times are divided by it
+    pub fn enemy_fire_rate(self) -> f32 {
+        match self {
+            Difficulty::Casual => 0.6,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    // Multiplier on all damage the player takes
+    pub fn damage_taken(self) -> f32 {
+        match self {
+            Difficulty::Casual => 0.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    // Fraction of the downhill pull on the player that is taken away
+    pub fn slope_assist(self) -> f32 {
+        match self {
+            Difficulty::Casual => 0.5,
+            Difficulty::Normal => 0.2,
+            Difficulty::Hard => 0.0,
+        }
+    }
+
+    // Distance from the aim point within which a throw snaps onto a target
+    pub fn aim_assist(self) -> f32 {
+        match self {
+            Difficulty::Casual => 4.0,
+            Difficulty::Normal => 2.0,
+            Difficulty::Hard => 0.0,
+        }
+    }
+}
+
+// Keep the player's slope assist in line with the difficulty
+pub fn apply_slope_assist(difficulty: Res<Difficulty>, mut player_query: Query<&mut PlayerPhysics, With<Player>>) {
+    let assist = difficulty.slope_assist();
+    for mut physics in player_query.iter_mut() {
+        if physics.slope_assist != assist {
+            physics.slope_assist = assist;
+        }
+    }
+}
+
+// Plugin for difficulty settings
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Difficulty>()
+            .add_systems(Update, apply_slope_assist.run_if(in_state(GameState::Playing)));
+    }
+}
@@ -0,0 +1,293 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
+use crate::terrain::get_terrain_height;
+use crate::player::{Health, Player, PlayerPhysics};
+use crate::projectile::Ammo;
+use crate::game_events::GameEvent;
+use crate::lifetime::Lifetime;
+use crate::multiball::Multiball;
+use crate::sim::{placement_rng, SimSet};
+use crate::spatial::{Spatial, SpatialGrid};
+use crate::GameState;
+
+// Drop constants
+const TARGET_DROP_CHANCE: f64 = 0.35; // Chance a destroyed target leaves a pickup
+const PROP_DROP_CHANCE: f64 = 0.5; // Chance a smashed prop leaves a pickup
+const DROP_HOVER_HEIGHT: f32 = 0.8; // Height of a pickup above the terrain
+const DROP_BOB_HEIGHT: f32 = 0.2; // Amplitude of a pickup's bobbing
+const DROP_SPIN_SPEED: f32 = 2.0; // Radians per second
+const DROP_LIFETIME: f32 = 20.0; // Seconds before an uncollected pickup disappears
+const DROP_COLLECT_RADIUS: f32 = 0.9; // Distance from the player's center that picks a drop up
+const HEALTH_RESTORED: f32 = 25.0; // Health given by a health pickup
+const AMMO_RESTORED: u32 = 3; // Boulders given by an ammo pickup
+const SPEED_BOOST_MULTIPLIER: f32 = 1.5; // Speed multiplier while boosted
+const SPEED_BOOST_DURATION: f32 = 8.0; // Seconds a speed boost lasts
+const DROP_STREAM: u64 = 5; // Simulation random stream for drop rolls
+
+// Relative odds of each pickup when something drops
+const DROP_TABLE: [(DropKind, u32); 4] = [
+    (DropKind::Health, 3),
+    (DropKind::Ammo, 4),
+    (DropKind::SpeedBoost, 2),
+    (DropKind::Multiball, 1),
+];
+
+// Kinds of dropped pickup
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropKind {
+    Health,
+    Ammo,
+    SpeedBoost,
+    Multiball,
+}
+
+impl DropKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            DropKind::Health => "Health",
+            DropKind::Ammo => "Ammo",
+            DropKind::SpeedBoost => "Speed Boost",
+            DropKind::Multiball => "Multiball",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            DropKind::Health => Color::srgb(0.9, 0.2, 0.25),
+            DropKind::Ammo => Color::srgb(0.65, 0.65, 0.6),
+            DropKind::SpeedBoost => Color::srgb(0.4, 1.0, 0.3),
+            DropKind::Multiball => Color::srgb(0.3, 0.6, 1.0),
+        }
+    }
+}
+
+// A dropped pickup waiting to be collected
+#[derive(Component)]
+pub struct Drop {
+    pub kind: DropKind,
+    // Resting height the pickup bobs around
+    pub base_y: f32,
+}
+
+// Pull on nearby pickups; magnet power-ups can raise the radius and strength
+#[derive(Resource)]
+pub struct PickupMagnet {
+    // Pickups closer than this drift toward the player
+    pub radius: f32,
+    // Speed they drift at, in meters per second
+    pub strength: f32,
+}
+
+impl Default for PickupMagnet {
+    fn default() -> Self {
+        Self {
+            radius: 2.5,
+            strength: 4.0,
+        }
+    }
+}
+
+// Time left on the player's speed boost
+#[derive(Resource, Default)]
+pub struct SpeedBoost {
+    pub remaining: f32,
+}
+
+// Shared meshes and materials for each kind of drop
+#[derive(Resource)]
+pub struct DropAssets {
+    pub health_mesh: Handle<Mesh>,
+    pub ammo_mesh: Handle<Mesh>,
+    pub speed_mesh: Handle<Mesh>,
+    pub multiball_mesh: Handle<Mesh>,
+    pub health_material: Handle<StandardMaterial>,
+    pub ammo_material: Handle<StandardMaterial>,
+    pub speed_material: Handle<StandardMaterial>,
+    pub multiball_material: Handle<StandardMaterial>,
+}
+
+impl DropAssets {
+    fn get(&self, kind: DropKind) -> (Handle<Mesh>, Handle<StandardMaterial>) {
+        match kind {
+            DropKind::Health => (self.health_mesh.clone(), self.health_material.clone()),
+            DropKind::Ammo => (self.ammo_mesh.clone(), self.ammo_material.clone()),
+            DropKind::SpeedBoost => (self.speed_mesh.clone(), self.speed_material.clone()),
+            DropKind::Multiball => (self.multiball_mesh.clone(), self.multiball_material.clone()),
+        }
+    }
+}
+
+// Pick a drop from the table by weight
+fn roll_drop_table(rng: &mut StdRng) -> DropKind {
+    let total: u32 = DROP_TABLE.iter().map(|(_, weight)| weight).sum();
+    let mut roll = rng.gen_range(0..total);
+    for (kind, weight) in DROP_TABLE {
+        if roll < weight {
+            return kind;
+        }
+        roll -= weight;
+    }
+    DROP_TABLE[0].0
+}
+
+// Create the shared drop meshes and materials
+pub fn setup_drop_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut material = |kind: DropKind| materials.add(StandardMaterial {
+        base_color: kind.color(),
+        emissive: (kind.color().to_linear() * 0.5).into(),
+        ..default()
+    });
+    commands.insert_resource(DropAssets {
+        health_mesh: meshes.add(Cuboid::new(0.45, 0.45, 0.45)),
+        ammo_mesh: meshes.add(Sphere::new(0.25)),
+        speed_mesh: meshes.add(Torus::new(0.15, 0.3)),
+        multiball_mesh: meshes.add(Capsule3d::new(0.15, 0.4)),
+        health_material: material(DropKind::Health),
+        ammo_material: material(DropKind::Ammo),
+        speed_material: material(DropKind::SpeedBoost),
+        multiball_material: material(DropKind::Multiball),
+    });
+}
+
+// Roll for a drop wherever a target or prop is destroyed
+pub fn spawn_drops(
+    mut commands: Commands,
+    assets: Res<DropAssets>,
+    mut game_events: EventReader<GameEvent>,
+) {
+    for event in game_events.read() {
+        let (position, chance) = match event {
+            GameEvent::TargetDestroyed { position, .. } => (*position, TARGET_DROP_CHANCE),
+            GameEvent::PropDestroyed { position, .. } => (*position, PROP_DROP_CHANCE),
+            _ => continue,
+        };
+        let mut rng = placement_rng(position, DROP_STREAM);
+        if !rng.gen_bool(chance) {
+            continue;
+        }
+
+        let kind = roll_drop_table(&mut rng);
+        let (mesh, material) = assets.get(kind);
+        let base_y = get_terrain_height(position.x, position.z) + DROP_HOVER_HEIGHT;
+        commands.spawn((
+            Drop { kind, base_y },
+            Lifetime::new(DROP_LIFETIME),
+            Spatial::new(0.0),
+            Mesh3d(mesh),
+            MeshMaterial3d(material),
+            Transform::from_xyz(position.x, base_y, position.z),
+            Name::new(kind.label()),
+        ));
+    }
+}
+
+// Spin and bob drops and draw them toward the player within the magnet radius
+pub fn animate_drops(
+    magnet: Res<PickupMagnet>,
+    player_query: Query<&Transform, With<Player>>,
+    mut drop_query: Query<(&mut Transform, &mut Drop, &Lifetime), Without<Player>>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    let player_pos = player_query.get_single().ok().map(|transform| transform.translation);
+
+    for (mut transform, mut drop, lifetime) in drop_query.iter_mut() {
+        if let Some(player_pos) = player_pos {
+            let offset = (player_pos - transform.translation).with_y(0.0);
+            let distance = offset.length();
+            if distance < magnet.radius && distance > 0.001 {
+                transform.translation += offset / distance * (magnet.strength * delta).min(distance);
+                drop.base_y = get_terrain_height(transform.translation.x, transform.translation.z) + DROP_HOVER_HEIGHT;
+            }
+        }
+
+        let phase = lifetime.age * DROP_SPIN_SPEED;
+        transform.rotation = Quat::from_rotation_y(phase);
+        transform.translation.y = drop.base_y + phase.sin() * DROP_BOB_HEIGHT;
+    }
+}
+
+// Apply drops the player touches
+pub fn collect_drops(
+    mut commands: Commands,
+    mut ammo: ResMut<Ammo>,
+    mut boost: ResMut<SpeedBoost>,
+    mut multiball: ResMut<Multiball>,
+    mut game_events: EventWriter<GameEvent>,
+    mut player_query: Query<(&Transform, &mut Health), With<Player>>,
+    drop_query: Query<(&Transform, &Drop), Without<Player>>,
+    spatial_grid: Res<SpatialGrid>,
+) {
+    let Ok((player_transform, mut health)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for entity in spatial_grid.nearby(player_transform.translation, DROP_COLLECT_RADIUS) {
+        let Ok((transform, drop)) = drop_query.get(entity) else {
+            continue;
+        };
+        if transform.translation.distance(player_transform.translation) > DROP_COLLECT_RADIUS {
+            continue;
+        }
+        commands.entity(entity).despawn_recursive();
+        match drop.kind {
+            DropKind::Health => health.current = (health.current + HEALTH_RESTORED).min(health.max),
+            DropKind::Ammo => ammo.current = (ammo.current + AMMO_RESTORED).min(ammo.max),
+            DropKind::SpeedBoost => boost.remaining = SPEED_BOOST_DURATION,
+            DropKind::Multiball => multiball.start(),
+        }
+        game_events.send(GameEvent::DropCollected { name: drop.kind.label().to_string() });
+    }
+}
+
+// Keep the player's speed multiplier up while a boost lasts
+pub fn tick_speed_boost(
+    mut boost: ResMut<SpeedBoost>,
+    mut player_query: Query<&mut PlayerPhysics, With<Player>>,
+    time: Res<Time>,
+) {
+    if boost.remaining > 0.0 {
+        boost.remaining = (boost.remaining - time.delta_secs()).max(0.0);
+    }
+    let multiplier = if boost.remaining > 0.0 { SPEED_BOOST_MULTIPLIER } else { 1.0 };
+    for mut physics in player_query.iter_mut() {
+        if physics.speed_multiplier != multiplier {
+            physics.speed_multiplier = multiplier;
+        }
+    }
+}
+
+// Clear drops and boosts at the end of a run
+pub fn reset_drops(
+    mut commands: Commands,
+    mut boost: ResMut<SpeedBoost>,
+    drop_query: Query<Entity, With<Drop>>,
+) {
+    for entity in drop_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    *boost = SpeedBoost::default();
+}
+
+// Plugin for health, ammo, speed boost and multiball drops
+pub struct DropPlugin;
+
+impl Plugin for DropPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<PickupMagnet>()
+            .init_resource::<SpeedBoost>()
+            .add_systems(Startup, setup_drop_assets)
+            .add_systems(OnExit(GameState::GameOver), reset_drops)
+            .add_systems(Update, (spawn_drops, animate_drops)
+                .chain()
+                .run_if(in_state(GameState::Playing)))
+            .add_systems(FixedUpdate, (tick_speed_boost.in_set(SimSet::Environment), collect_drops.in_set(SimSet::Constraints))
+                .run_if(in_state(GameState::Playing)));
+    }
+}
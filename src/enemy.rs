@@ -0,0 +1,229 @@
+use bevy::prelude::*;
+use rand::Rng;
+use crate::terrain::{get_terrain_height, sample};
+use crate::platforms::PlatformSurfaces;
+use crate::player::{player_spawn_position, roll_sphere, Health, Player, PlayerPhysics, RollInput};
+use crate::camera::CameraShake;
+use crate::difficulty::Difficulty;
+use crate::photo_mode::PhotoModeState;
+use crate::minimap::MinimapBlip;
+use crate::compass::CompassMarker;
+use crate::audio::{PlaySound, SoundEffect};
+use crate::sim::{Interpolated, SimSet, Simulation};
+use crate::spatial::{Spatial, SpatialGrid};
+use crate::{GameState, RunStart};
+use crate::tuning::LiveTuning;
+
+// Enemy constants
+const ENEMY_COUNT: usize = 3; // Enemy balls placed at the start of a run
+const ENEMY_MIN_DISTANCE: f32 = 25.0; // Closest an enemy starts to the player
+const ENEMY_MAX_DISTANCE: f32 = 45.0; // Farthest an enemy starts from the player
+const ENEMY_RADIUS: f32 = 0.5; // Same size as the player ball
+const ENEMY_DRIVE: f32 = 0.8; // Fraction of the player's input force enemies roll with
+const ENEMY_LOOKAHEAD: f32 = 3.0; // Distance ahead an enemy checks the slope
+const ENEMY_MAX_CLIMB: f32 = 0.5; // Uphill gradient an enemy steers around instead of climbing
+const CONTACT_DAMAGE: f32 = 10.0; // Health taken per ram
+const CONTACT_COOLDOWN: f32 = 1.0; // Seconds before the same enemy can hurt the player again
+const KNOCKBACK_SPEED: f32 = 6.0; // Horizontal speed the player is knocked away with
+const KNOCKBACK_LIFT: f32 = 3.0; // Upward speed added by a ram
+const RAM_TRAUMA: f32 = 0.4; // Camera trauma from being rammed
+const ENGAGE_DISTANCE: f32 = 15.0; // An enemy this close puts the player in combat
+const DISENGAGE_DISTANCE: f32 = 22.0; // Every enemy this far away ends combat; farther than engaging so it doesn't flicker
+const ENEMY_STREAM: u64 = 0; // Simulation random stream for placing enemies
+
+// A hostile ball that chases and rams the player; rolls with its own PlayerPhysics
+#[derive(Component, Default)]
+pub struct Enemy {
+    // Seconds until this enemy can damage the player again
+    pub contact_cooldown: f32,
+}
+
+// Pick the direction to roll toward a goal, going around slopes too steep to climb
+pub fn steer_toward(position: Vec3, goal: Vec3) -> Vec3 {
+    let seek = Vec3::new(goal.x - position.x, 0.0, goal.z - position.z).normalize_or_zero();
+    if seek == Vec3::ZERO {
+        return seek;
+    }
+
+    // Follow the contour on whichever side is closer to the goal when the way ahead is too steep
+    let ahead = position + seek * ENEMY_LOOKAHEAD;
+    let gradient = sample(ahead.x, ahead.z).gradient;
+    if gradient.dot(Vec2::new(seek.x, seek.z)) > ENEMY_MAX_CLIMB {
+        let contour = Vec3::new(-gradient.y, 0.0, gradient.x).normalize_or_zero();
+        return if contour.dot(seek) >= 0.0 { contour } else { -contour };
+    }
+    seek
+}
+
+// Shared mesh and material for enemy balls
+#[derive(Resource)]
+pub struct EnemyAssets {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<StandardMaterial>,
+}
+
+// Create the shared enemy mesh and material
+pub fn setup_enemy_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(EnemyAssets {
+        mesh: meshes.add(Sphere::new(ENEMY_RADIUS)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.7, 0.1, 0.1),
+            perceptual_roughness: 0.4,
+            ..default()
+        }),
+    });
+}
+
+// Place an enemy ball resting on the terrain at (x, z)
+pub fn spawn_enemy(commands: &mut Commands, assets: &EnemyAssets, x: f32, z: f32) {
+    let position = Vec3::new(x, get_terrain_height(x, z) + ENEMY_RADIUS, z);
+    commands.spawn((
+        Enemy::default(),
+        Spatial::new(ENEMY_RADIUS),
+        PlayerPhysics {
+            prev_position: position,
+            ..Default::default()
+        },
+        Mesh3d(assets.mesh.clone()),
+        MeshMaterial3d(assets.material.clone()),
+        Transform::from_translation(position),
+        Interpolated::new(position),
+        Name::new("Enemy"),
+        MinimapBlip { color: Color::srgb(1.0, 0.2, 0.2) },
+        CompassMarker { color: Color::srgb(1.0, 0.2, 0.2) },
+    ));
+}
+
+// Place the run's enemies around the spawn point
+pub fn spawn_enemies(mut commands: Commands, assets: Res<EnemyAssets>, simulation: Res<Simulation>) {
+    let mut rng = simulation.rng(ENEMY_STREAM);
+    let spawn = player_spawn_position();
+    for _ in 0..ENEMY_COUNT {
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let distance = rng.gen_range(ENEMY_MIN_DISTANCE..ENEMY_MAX_DISTANCE);
+        spawn_enemy(&mut commands, &assets, spawn.x + angle.cos() * distance, spawn.z + angle.sin() * distance);
+    }
+}
+
+// Remove every enemy so the next run starts fresh
+pub fn despawn_enemies(mut commands: Commands, enemy_query: Query<Entity, With<Enemy>>) {
+    for entity in enemy_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Roll each enemy toward the player using the same physics as the player ball
+pub fn move_enemies(
+    mut enemy_query: Query<(&mut Transform, &mut PlayerPhysics, &mut Enemy), Without<Player>>,
+    player_query: Query<&Transform, With<Player>>,
+    platforms: Res<PlatformSurfaces>,
+    tuning: Res<LiveTuning>,
+    time: Res<Time>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let delta = time.delta_secs();
+
+    for (mut transform, mut physics, mut enemy) in enemy_query.iter_mut() {
+        enemy.contact_cooldown = (enemy.contact_cooldown - delta).max(0.0);
+        let direction = steer_toward(transform.translation, player_transform.translation) * ENEMY_DRIVE;
+        roll_sphere(&mut transform, &mut physics, &tuning.0.player, &platforms.0, RollInput::steer(direction), delta);
+    }
+}
+
+// Knock the player back and take health when an enemy rams into them
+pub fn enemy_contact(
+    mut enemy_query: Query<(&mut Transform, &mut PlayerPhysics, &mut Enemy), Without<Player>>,
+    mut player_query: Query<(&Transform, &mut PlayerPhysics, &mut Health), With<Player>>,
+    mut camera_shake: ResMut<CameraShake>,
+    difficulty: Res<Difficulty>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    let Ok((player_transform, mut player_physics, mut health)) = player_query.get_single_mut() else {
+        return;
+    };
+    let player_pos = player_transform.translation;
+    let contact_distance = ENEMY_RADIUS + 0.5;
+
+    for (mut transform, mut physics, mut enemy) in enemy_query.iter_mut() {
+        let offset = player_pos - transform.translation;
+        if offset.length() >= contact_distance {
+            continue;
+        }
+        let away = Vec3::new(offset.x, 0.0, offset.z).normalize_or(Vec3::X);
+
+        // Separate the two balls and bounce the enemy back the way it came
+        transform.translation = player_pos - away * contact_distance;
+        physics.velocity = -away * KNOCKBACK_SPEED * 0.5;
+        physics.momentum = physics.velocity;
+
+        if enemy.contact_cooldown > 0.0 {
+            continue;
+        }
+        enemy.contact_cooldown = CONTACT_COOLDOWN;
+        let knockback = away * KNOCKBACK_SPEED + Vec3::Y * KNOCKBACK_LIFT;
+        player_physics.velocity += knockback;
+        player_physics.momentum += knockback;
+        player_physics.grounded = false;
+        health.current -= CONTACT_DAMAGE * difficulty.damage_taken();
+        camera_shake.add_trauma(RAM_TRAUMA);
+        sounds.send(PlaySound { effect: SoundEffect::EnemyRam, position: player_pos });
+    }
+}
+
+// Enemies closing in on the player or all backing off, e.g. for switching to combat music
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnemyProximity {
+    Engaged,
+    Disengaged,
+}
+
+// Send EnemyProximity when the nearest enemy crosses the engage or disengage distance
+pub fn detect_enemy_proximity(
+    enemy_query: Query<&Transform, (With<Enemy>, Without<Player>)>,
+    player_query: Query<&Transform, With<Player>>,
+    spatial_grid: Res<SpatialGrid>,
+    mut engaged: Local<bool>,
+    mut events: EventWriter<EnemyProximity>,
+) {
+    // Only enemies within the disengage distance can change anything
+    let nearest = player_query.get_single().ok().and_then(|player_transform| {
+        spatial_grid
+            .nearby(player_transform.translation, DISENGAGE_DISTANCE)
+            .into_iter()
+            .filter_map(|entity| enemy_query.get(entity).ok())
+            .map(|transform| transform.translation.distance(player_transform.translation))
+            .reduce(f32::min)
+    });
+    let nearest = nearest.unwrap_or(f32::MAX);
+    if !*engaged && nearest < ENGAGE_DISTANCE {
+        *engaged = true;
+        events.send(EnemyProximity::Engaged);
+    } else if *engaged && nearest > DISENGAGE_DISTANCE {
+        *engaged = false;
+        events.send(EnemyProximity::Disengaged);
+    }
+}
+
+// Plugin for enemy balls
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup_enemy_assets)
+            .add_systems(RunStart, spawn_enemies)
+            .add_event::<EnemyProximity>()
+            .add_systems(OnExit(GameState::GameOver), despawn_enemies)
+            // Runs in every state so leaving a run with enemies around still disengages
+            .add_systems(Update, detect_enemy_proximity)
+            .add_systems(FixedUpdate, (move_enemies.in_set(SimSet::Movement), enemy_contact.in_set(SimSet::Constraints))
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)));
+    }
+}
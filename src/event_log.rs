@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use crate::game_events::GameEvent;
+use crate::lifetime::Lifetime;
+use crate::photo_mode::PhotoModeState;
+use crate::GameState;
+
+// Event log constants
+const LOG_MAX_ENTRIES: usize = 6; // Oldest entries are dropped beyond this
+const LOG_ENTRY_LIFETIME: f32 = 6.0; // Seconds an entry stays before it is removed
+const LOG_FADE_TIME: f32 = 1.5; // Seconds over which an entry fades out at the end of its life
+
+// Marker for the event log panel
+#[derive(Component)]
+pub struct EventLogRoot;
+
+// A line in the event log
+#[derive(Component)]
+pub struct EventLogEntry;
+
+// Spawn the event log panel on the left side of the screen, above the bottom edge
+pub fn spawn_event_log(mut commands: Commands) {
+    commands.spawn((
+        EventLogRoot,
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(12.0),
+            bottom: Val::Px(12.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(2.0),
+            ..default()
+        },
+        // Hidden until a game starts
+        Visibility::Hidden,
+    ));
+}
+
+// Show the event log while playing
+pub fn show_event_log(mut query: Query<&mut Visibility, With<EventLogRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+// Hide the event log outside of gameplay
+pub fn hide_event_log(mut query: Query<&mut Visibility, With<EventLogRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Add a line for each game event, dropping the oldest when the log is full
+pub fn record_game_events(
+    mut commands: Commands,
+    mut game_events: EventReader<GameEvent>,
+    root_query: Query<(Entity, Option<&Children>), With<EventLogRoot>>,
+) {
+    let Ok((root, children)) = root_query.get_single() else {
+        return;
+    };
+    let existing: &[Entity] = children.map(|children| &children[..]).unwrap_or(&[]);
+    let mut count = existing.len();
+    let mut removed = 0;
+
+    for event in game_events.read() {
+        // Children are kept oldest first
+        if count >= LOG_MAX_ENTRIES {
+            if let Some(&oldest) = existing.get(removed) {
+                commands.entity(oldest).despawn_recursive();
+            }
+            removed += 1;
+        } else {
+            count += 1;
+        }
+        commands.entity(root).with_children(|parent| {
+            parent.spawn((
+                EventLogEntry,
+                Lifetime::new(LOG_ENTRY_LIFETIME),
+                Text::new(event.describe()),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.35)),
+            ));
+        });
+    }
+}
+
+// Fade entries out toward the end of their life
+pub fn fade_event_log(mut entry_query: Query<(&Lifetime, &mut TextColor, &mut BackgroundColor), With<EventLogEntry>>) {
+    for (lifetime, mut color, mut background) in entry_query.iter_mut() {
+        let alpha = (lifetime.remaining() / LOG_FADE_TIME).min(1.0);
+        color.0 = color.0.with_alpha(alpha);
+        background.0 = background.0.with_alpha(0.35 * alpha);
+    }
+}
+
+// Plugin for the on-screen event log
+pub struct EventLogPlugin;
+
+impl Plugin for EventLogPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, spawn_event_log)
+            .add_systems(OnEnter(GameState::Playing), show_event_log)
+            .add_systems(OnExit(GameState::Playing), hide_event_log)
+            .add_systems(Update, (record_game_events, fade_event_log)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)));
+    }
+}
@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+use serde::Serialize;
+
+// Notable gameplay moments, broadcast for the event log and anything else that reacts to them
+#[derive(Event, Clone, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameEvent {
+    // A target at position was destroyed by a boulder thrown from this far away
+    TargetDestroyed { position: Vec3, distance: f32 },
+    // The player entered a region for the first time
+    RegionDiscovered { name: String },
+    // The player picked up a coin or orb worth this many coins
+    PickupCollected { value: u32 },
+    // The player finished a time trial
+    RaceFinished { time: f32, new_best: bool },
+    // A golf hole ended, either sunk or out of strokes or time
+    HoleFinished { hole: u32, strokes: u32, sunk: bool },
+    // The player broke a prop at position
+    PropDestroyed { name: String, position: Vec3 },
+    // The player picked up a dropped item
+    DropCollected { name: String },
+    // A capture zone was taken; count is the total this run
+    ZoneCaptured { count: u32 },
+    // The player was hurt by lava or spikes
+    HazardHit { name: String },
+    // The player warped through a teleporter into a region
+    Teleported { destination: String },
+    // A free play objective ended, either completed or out of time
+    ObjectiveFinished { description: String, completed: bool },
+    // The boss of a boss fight moved on to a harder phase, counting from 1
+    BossPhase { name: String, phase: u32 },
+    // The boss of a boss fight was beaten
+    BossDefeated { name: String },
+    // A wave of a wave-based mod was survived, counting from 1, with the name of the mod
+    WaveSurvived { mode: String, wave: u32 },
+    // The player has rolled this far without stopping, sent at each step of the streak
+    RollingStreak { distance: f32 },
+    // A mod script posted a message
+    ScriptMessage { text: String },
+}
+
+impl GameEvent {
+    // One-line description for the event log
+    pub fn describe(&self) -> String {
+        match self {
+            GameEvent::TargetDestroyed { distance, .. } => format!("Target destroyed at {:.0} m", distance),
+            GameEvent::RegionDiscovered { name } => format!("Discovered {}", name),
+            GameEvent::RaceFinished { time, new_best: true } => format!("Course finished in {:.2} s, a new best!", time),
+            GameEvent::RaceFinished { time, new_best: false } => format!("Course finished in {:.2} s", time),
+            GameEvent::HoleFinished { hole, strokes, sunk: true } => format!("Hole {} sunk in {} strokes", hole, strokes),
+            GameEvent::HoleFinished { hole, sunk: false, .. } => format!("Hole {} missed", hole),
+            GameEvent::PropDestroyed { name, .. } => format!("{} smashed", name),
+            GameEvent::ZoneCaptured { count } => format!("Zone captured ({} total)", count),
+            GameEvent::ObjectiveFinished { description, completed: true } => format!("Objective complete: {}", description),
+            GameEvent::ObjectiveFinished { description, completed: false } => format!("Objective failed: {}", description),
+            GameEvent::Teleported { destination } => format!("Warped to {}", destination),
+            GameEvent::HazardHit { name } => format!("Hurt by {}", name.to_lowercase()),
+            GameEvent::DropCollected { name } => format!("Picked up {}", name),
+            GameEvent::BossPhase { name, phase } => format!("{} enters phase {}", name, phase),
+            GameEvent::BossDefeated { name } => format!("{} defeated", name),
+            GameEvent::WaveSurvived { wave, .. } => format!("Wave {} survived", wave),
+            GameEvent::RollingStreak { distance } => format!("Rolled {:.0} m without stopping", distance),
+            GameEvent::PickupCollected { value } => format!("+{} coin{}", value, if *value == 1 { "" } else { "s" }),
+            GameEvent::ScriptMessage { text } => text.clone(),
+        }
+    }
+
+    // Short snake_case name of the event, as mod scripts receive it
+    pub fn kind(&self) -> &'static str {
+        match self {
+            GameEvent::TargetDestroyed { .. } => "target_destroyed",
+            GameEvent::RegionDiscovered { .. } => "region_discovered",
+            GameEvent::PickupCollected { .. } => "pickup_collected",
+            GameEvent::RaceFinished { .. } => "race_finished",
+            GameEvent::HoleFinished { .. } => "hole_finished",
+            GameEvent::PropDestroyed { .. } => "prop_destroyed",
+            GameEvent::DropCollected { .. } => "drop_collected",
+            GameEvent::ZoneCaptured { .. } => "zone_captured",
+            GameEvent::HazardHit { .. } => "hazard_hit",
+            GameEvent::Teleported { .. } => "teleported",
+            GameEvent::ObjectiveFinished { .. } => "objective_finished",
+            GameEvent::BossPhase { .. } => "boss_phase",
+            GameEvent::BossDefeated { .. } => "boss_defeated",
+            GameEvent::WaveSurvived { .. } => "wave_survived",
+            GameEvent::RollingStreak { .. } => "rolling_streak",
+            GameEvent::ScriptMessage { .. } => "script_message",
+        }
+    }
+}
+
+// Plugin registering the game event bus
+pub struct GameEventPlugin;
+
+impl Plugin for GameEventPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<GameEvent>();
+    }
+}
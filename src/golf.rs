@@ -0,0 +1,340 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::f32::consts::{PI, TAU};
+use crate::terrain::{add_deformation, clear_deformations, get_terrain_height, terrain_gradient, ChunkManager, Deformation};
+use crate::player::{player_spawn_position, Player, PlayerPhysics};
+use crate::compass::{bearing_of, CompassMarker};
+use crate::waypoint::Waypoint;
+use crate::photo_mode::PhotoModeState;
+use crate::game_events::GameEvent;
+use crate::score::Score;
+use crate::sim::{SimSet, Simulation};
+use crate::{GameMode, GameState, RunStart};
+
+// Golf constants
+const HOLE_COUNT: u32 = 3; // Holes in a round
+const HOLE_MIN_DISTANCE: f32 = 25.0; // Closest a hole is placed to where the ball is
+const HOLE_MAX_DISTANCE: f32 = 45.0; // Farthest a hole is placed from where the ball is
+const HOLE_PLACEMENT_ATTEMPTS: usize = 20; // Spots tried when looking for a flat green
+const GREEN_MAX_GRADIENT: f32 = 0.25; // Steepest terrain a hole is carved into
+const BOWL_RADIUS: f32 = 4.0; // Radius of the dip carved around the cup
+const BOWL_DEPTH: f32 = 0.8; // Depth of the dip at the cup
+const CUP_RADIUS: f32 = 0.9; // Horizontal distance from the cup that sinks the ball
+const SINK_MAX_SPEED: f32 = 3.0; // Faster than this and the ball lips out
+const STROKE_BUDGET: u32 = 6; // Dashes allowed per hole
+const TIME_BUDGET: f32 = 60.0; // Seconds allowed per hole
+const HOLE_POINTS: u32 = 500; // Points for sinking a hole
+const STROKE_BONUS: u32 = 100; // Points per unused stroke
+const TIME_BONUS: u32 = 5; // Points per second left
+const MAX_WIND: f32 = 4.0; // Strongest wind, as acceleration in m/s²
+const GROUNDED_WIND_FACTOR: f32 = 0.3; // Share of the wind felt while rolling rather than flying
+const GOLF_STREAM: u64 = 7; // Simulation random stream for hole placement and wind
+const FLAG_HEIGHT: f32 = 3.0;
+
+// Directions for the wind readout, clockwise from north
+const WIND_DIRECTIONS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+// Progress through the current round of golf
+#[derive(Resource, Default)]
+pub struct GolfState {
+    // Holes played so far, including the current one
+    pub hole: u32,
+    pub strokes: u32,
+    pub time_left: f32,
+    // Holes sunk this round
+    pub sunk: u32,
+    // Dash cooldown last frame; it jumping up means a stroke was taken
+    last_dash_cooldown: f32,
+}
+
+// Wind pushing the ball along the ground plane, changing with every hole
+#[derive(Resource, Default)]
+pub struct Wind {
+    pub velocity: Vec2,
+    // Seconds a wind charm still keeps the wind off the ball
+    pub calm: f32,
+}
+
+// The cup of the current hole
+#[derive(Component)]
+pub struct GolfHole;
+
+// Marker for the golf scorecard panel
+#[derive(Component)]
+pub struct GolfHudRoot;
+
+// Marker for the golf scorecard text
+#[derive(Component)]
+pub struct GolfHudText;
+
+// Find a gentle spot at a playable distance from the ball
+fn find_green(from: Vec3, rng: &mut StdRng) -> Vec2 {
+    let mut spot = Vec2::new(from.x + HOLE_MIN_DISTANCE, from.z);
+    for _ in 0..HOLE_PLACEMENT_ATTEMPTS {
+        let angle = rng.gen_range(0.0..TAU);
+        let distance = rng.gen_range(HOLE_MIN_DISTANCE..HOLE_MAX_DISTANCE);
+        spot = Vec2::new(from.x + angle.cos() * distance, from.z + angle.sin() * distance);
+        if terrain_gradient(spot.x, spot.y).length() <= GREEN_MAX_GRADIENT {
+            break;
+        }
+    }
+    spot
+}
+
+// Carve the next hole, plant its flag and pick a new wind
+fn start_hole(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    chunk_manager: &mut ChunkManager,
+    golf: &mut GolfState,
+    wind: &mut Wind,
+    from: Vec3,
+    rng: &mut StdRng,
+) {
+    let spot = find_green(from, rng);
+    add_deformation(commands, chunk_manager, Deformation {
+        center: spot,
+        radius: BOWL_RADIUS,
+        depth: BOWL_DEPTH,
+    });
+
+    golf.hole += 1;
+    golf.strokes = 0;
+    golf.time_left = TIME_BUDGET;
+    let wind_angle = rng.gen_range(0.0..TAU);
+    wind.velocity = Vec2::new(wind_angle.cos(), wind_angle.sin()) * rng.gen_range(0.0..MAX_WIND);
+
+    let position = Vec3::new(spot.x, get_terrain_height(spot.x, spot.y), spot.y);
+    let flag_color = Color::srgb(0.9, 0.15, 0.15);
+    commands
+        .spawn((
+            GolfHole,
+            Transform::from_translation(position),
+            Visibility::default(),
+            Name::new(format!("Hole {}", golf.hole)),
+            Waypoint {
+                label: format!("Hole {}", golf.hole),
+                color: Color::WHITE,
+            },
+            CompassMarker { color: flag_color },
+        ))
+        .with_children(|parent| {
+            // Dark cup
+            parent.spawn((
+                Mesh3d(meshes.add(Cylinder::new(CUP_RADIUS * 0.5, 0.02))),
+                MeshMaterial3d(materials.add(Color::srgb(0.05, 0.05, 0.05))),
+                Transform::from_xyz(0.0, 0.02, 0.0),
+            ));
+            // Flag pole and pennant
+            parent.spawn((
+                Mesh3d(meshes.add(Cylinder::new(0.04, FLAG_HEIGHT))),
+                MeshMaterial3d(materials.add(Color::srgb(0.9, 0.9, 0.9))),
+                Transform::from_xyz(0.0, FLAG_HEIGHT * 0.5, 0.0),
+            ));
+            parent.spawn((
+                Mesh3d(meshes.add(Cuboid::new(0.7, 0.45, 0.02))),
+                MeshMaterial3d(materials.add(flag_color)),
+                Transform::from_xyz(0.35, FLAG_HEIGHT - 0.25, 0.0),
+            ));
+        });
+}
+
+// Tee off the first hole when a round starts
+pub fn start_golf_round(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut chunk_manager: ResMut<ChunkManager>,
+    mut golf: ResMut<GolfState>,
+    mut wind: ResMut<Wind>,
+    mode: Res<GameMode>,
+    simulation: Res<Simulation>,
+) {
+    if *mode != GameMode::Golf {
+        return;
+    }
+    *golf = GolfState::default();
+    let mut rng = simulation.rng(GOLF_STREAM);
+    start_hole(&mut commands, &mut meshes, &mut materials, &mut chunk_manager, &mut golf, &mut wind, player_spawn_position(), &mut rng);
+}
+
+// Remove the hole and fill in every cup at the end of a run
+pub fn end_golf_round(
+    mut commands: Commands,
+    mut chunk_manager: ResMut<ChunkManager>,
+    mut golf: ResMut<GolfState>,
+    mut wind: ResMut<Wind>,
+    hole_query: Query<Entity, With<GolfHole>>,
+) {
+    for entity in hole_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    clear_deformations(&mut commands, &mut chunk_manager);
+    *golf = GolfState::default();
+    *wind = Wind::default();
+}
+
+// Count strokes, run the hole clock and check whether the ball dropped
+pub fn play_golf(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut chunk_manager: ResMut<ChunkManager>,
+    mut golf: ResMut<GolfState>,
+    mut wind: ResMut<Wind>,
+    mut score: ResMut<Score>,
+    mut game_events: EventWriter<GameEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    player_query: Query<(&Transform, &PlayerPhysics), With<Player>>,
+    hole_query: Query<(Entity, &Transform), (With<GolfHole>, Without<Player>)>,
+    simulation: Res<Simulation>,
+    time: Res<Time>,
+) {
+    let (Ok((player_transform, physics)), Ok((hole_entity, hole_transform))) = (
+        player_query.get_single(),
+        hole_query.get_single()
+    ) else {
+        return;
+    };
+
+    // A dash is a stroke; it fires when its cooldown restarts
+    if physics.dash_cooldown > golf.last_dash_cooldown {
+        golf.strokes += 1;
+    }
+    golf.last_dash_cooldown = physics.dash_cooldown;
+    golf.time_left -= time.delta_secs();
+
+    let offset = player_transform.translation - hole_transform.translation;
+    let sunk = physics.grounded
+        && Vec2::new(offset.x, offset.z).length() <= CUP_RADIUS
+        && physics.velocity.length() <= SINK_MAX_SPEED;
+    let out_of_budget = golf.strokes > STROKE_BUDGET || golf.time_left <= 0.0;
+    if !sunk && !out_of_budget {
+        return;
+    }
+
+    if sunk {
+        let strokes_left = STROKE_BUDGET.saturating_sub(golf.strokes);
+        score.points += HOLE_POINTS + strokes_left * STROKE_BONUS + golf.time_left.max(0.0) as u32 * TIME_BONUS;
+        golf.sunk += 1;
+    }
+    game_events.send(GameEvent::HoleFinished { hole: golf.hole, strokes: golf.strokes, sunk });
+    commands.entity(hole_entity).despawn_recursive();
+
+    if golf.hole >= HOLE_COUNT {
+        next_state.set(GameState::GameOver);
+    } else {
+        let from = player_transform.translation;
+        let mut rng = simulation.rng(GOLF_STREAM);
+        start_hole(&mut commands, &mut meshes, &mut materials, &mut chunk_manager, &mut golf, &mut wind, from, &mut rng);
+    }
+}
+
+// Push the ball with the wind, strongest while it is in the air
+pub fn apply_wind(mut wind: ResMut<Wind>, mut player_query: Query<&mut PlayerPhysics, With<Player>>, time: Res<Time>) {
+    if wind.calm > 0.0 {
+        wind.calm = (wind.calm - time.delta_secs()).max(0.0);
+        return;
+    }
+    for mut physics in player_query.iter_mut() {
+        let factor = if physics.grounded { GROUNDED_WIND_FACTOR } else { 1.0 };
+        let push = wind.velocity * factor * time.delta_secs();
+        physics.velocity.x += push.x;
+        physics.velocity.z += push.y;
+    }
+}
+
+// Build the scorecard panel under the compass
+pub fn spawn_golf_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            GolfHudRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(48.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                GolfHudText,
+                Text::new(""),
+                TextFont {
+                    font_size: 22.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+// Show the scorecard while playing golf
+pub fn show_golf_hud(mode: Res<GameMode>, mut query: Query<&mut Visibility, With<GolfHudRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = if *mode == GameMode::Golf { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+// Hide the scorecard outside of gameplay
+pub fn hide_golf_hud(mut query: Query<&mut Visibility, With<GolfHudRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Show the hole, strokes, clock and wind
+pub fn update_golf_hud(
+    golf: Res<GolfState>,
+    wind: Res<Wind>,
+    mut text_query: Query<&mut Text, With<GolfHudText>>,
+) {
+    // Wind is named for where it blows toward
+    let bearing = bearing_of(Vec3::new(wind.velocity.x, 0.0, wind.velocity.y)).rem_euclid(TAU);
+    let sector = ((bearing + PI / 8.0) / (TAU / 8.0)) as usize % WIND_DIRECTIONS.len();
+    let wind_text = if wind.calm > 0.0 {
+        format!("Wind calmed for {:.0} s", wind.calm)
+    } else {
+        format!("Wind {:.1} toward {}", wind.velocity.length(), WIND_DIRECTIONS[sector])
+    };
+    for mut text in text_query.iter_mut() {
+        text.0 = format!(
+            "Hole {}/{}   Strokes {}/{}   {:.0} s   {}",
+            golf.hole,
+            HOLE_COUNT,
+            golf.strokes,
+            STROKE_BUDGET,
+            golf.time_left.max(0.0),
+            wind_text
+        );
+    }
+}
+
+// Plugin for the golf mode
+pub struct GolfPlugin;
+
+impl Plugin for GolfPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<GolfState>()
+            .init_resource::<Wind>()
+            .add_systems(Startup, spawn_golf_hud)
+            .add_systems(RunStart, start_golf_round)
+            .add_systems(OnExit(GameState::GameOver), end_golf_round)
+            .add_systems(OnEnter(GameState::Playing), show_golf_hud)
+            .add_systems(OnExit(GameState::Playing), hide_golf_hud)
+            .add_systems(FixedUpdate, (play_golf, apply_wind)
+                .in_set(SimSet::Constraints)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive))
+                .run_if(resource_equals(GameMode::Golf)))
+            .add_systems(Update, update_golf_hud
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive))
+                .run_if(resource_equals(GameMode::Golf)));
+    }
+}
@@ -0,0 +1,398 @@
+use bevy::prelude::*;
+use bevy::core_pipeline::bloom::Bloom;
+use bevy::core_pipeline::prepass::{DepthPrepass, NormalPrepass};
+use bevy::pbr::{CascadeShadowConfigBuilder, DirectionalLightShadowMap, ScreenSpaceAmbientOcclusion};
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_resource::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages};
+use bevy::render::view::RenderLayers;
+use bevy::window::{PrimaryWindow, WindowRef};
+use serde::{Deserialize, Serialize};
+use crate::camera::{cursor_raycasting, FollowCamera};
+use crate::minimap::MinimapCamera;
+use crate::party::GuestCamera;
+use crate::score::update_score_popups;
+use crate::settings::Settings;
+use crate::terrain::ChunkManager;
+use crate::waypoint::update_waypoint_indicators;
+
+// Steps each option cycles through in the graphics menu
+const SHADOW_RESOLUTION_STEPS: [u32; 4] = [512, 1024, 2048, 4096]; // Shadow map size in texels
+const SHADOW_CASCADE_STEPS: [usize; 3] = [1, 2, 4];
+const MSAA_STEPS: [u32; 3] = [1, 2, 4]; // Samples per pixel; 1 is off
+const RENDER_SCALE_STEPS: [f32; 4] = [0.5, 0.67, 0.75, 1.0];
+const VIEW_DISTANCE_STEPS: [i32; 4] = [1, 2, 3, 4]; // Chunks loaded in each direction around the player
+
+// Upscale pass constants
+const UPSCALE_CAMERA_ORDER: isize = 1; // After the main camera has drawn into the offscreen image
+const UPSCALE_LAYER: usize = 7; // Render layer only the upscale camera and its sprite are on, so gizmos stay off it
+
+// Named starting points for the graphics options; changing any single option makes the settings Custom
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GraphicsPreset {
+    Low,
+    Medium,
+    High,
+    Custom,
+}
+
+impl GraphicsPreset {
+    // Next preset in the menu cycle; Custom is only reached by changing an option
+    pub fn next(self) -> Self {
+        match self {
+            GraphicsPreset::Low => GraphicsPreset::Medium,
+            GraphicsPreset::Medium => GraphicsPreset::High,
+            GraphicsPreset::High | GraphicsPreset::Custom => GraphicsPreset::Low,
+        }
+    }
+}
+
+// One row of the graphics menu
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphicsOption {
+    Preset,
+    ShadowResolution,
+    ShadowCascades,
+    Msaa,
+    Bloom,
+    Ssao,
+    RenderScale,
+    ViewDistance,
+}
+
+impl GraphicsOption {
+    // Every option, in the order shown in the graphics menu
+    pub const ALL: [GraphicsOption; 8] = [
+        GraphicsOption::Preset,
+        GraphicsOption::ShadowResolution,
+        GraphicsOption::ShadowCascades,
+        GraphicsOption::Msaa,
+        GraphicsOption::Bloom,
+        GraphicsOption::Ssao,
+        GraphicsOption::RenderScale,
+        GraphicsOption::ViewDistance,
+    ];
+}
+
+// Rendering quality options, stored with the rest of the settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    pub preset: GraphicsPreset,
+    // Size of the sun's shadow map in texels
+    pub shadow_resolution: u32,
+    // Shadow cascades; more keep nearby shadows sharp while still reaching far away
+    pub shadow_cascades: usize,
+    // Multisample anti-aliasing samples per pixel, 1 for off; can't be combined with SSAO
+    pub msaa: u32,
+    pub bloom: bool,
+    // Screen space ambient occlusion; not available in the browser
+    pub ssao: bool,
+    // Fraction of the window's resolution the world is drawn at; the HUD always draws at full resolution
+    pub render_scale: f32,
+    // Terrain chunks loaded in each direction around the player; the horizon covers the rest
+    pub view_distance: i32,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self::preset(GraphicsPreset::Medium)
+    }
+}
+
+// Next value in a cycle of steps, wrapping after the last; a value not in the steps starts the cycle over
+fn next_in<T: PartialEq + Copy>(steps: &[T], current: T) -> T {
+    let index = steps.iter().position(|step| *step == current).map_or(0, |index| (index + 1) % steps.len());
+    steps[index]
+}
+
+impl GraphicsSettings {
+    // The options a preset stands for; Custom keeps Medium's
+    pub fn preset(preset: GraphicsPreset) -> Self {
+        match preset {
+            GraphicsPreset::Low => Self {
+                preset,
+                shadow_resolution: 1024,
+                shadow_cascades: 1,
+                msaa: 1,
+                bloom: false,
+                ssao: false,
+                render_scale: 0.75,
+                view_distance: 1,
+            },
+            GraphicsPreset::Medium | GraphicsPreset::Custom => Self {
+                preset,
+                shadow_resolution: 2048,
+                shadow_cascades: 4,
+                msaa: 4,
+                bloom: false,
+                ssao: false,
+                render_scale: 1.0,
+                view_distance: 2,
+            },
+            GraphicsPreset::High => Self {
+                preset,
+                shadow_resolution: 4096,
+                shadow_cascades: 4,
+                msaa: 4,
+                bloom: true,
+                ssao: false,
+                render_scale: 1.0,
+                view_distance: 3,
+            },
+        }
+    }
+
+    // Step one option to its next value, as clicking it in the menu does
+    pub fn cycle(&mut self, option: GraphicsOption) {
+        match option {
+            GraphicsOption::Preset => {
+                *self = Self::preset(self.preset.next());
+                return;
+            }
+            GraphicsOption::ShadowResolution => {
+                self.shadow_resolution = next_in(&SHADOW_RESOLUTION_STEPS, self.shadow_resolution);
+            }
+            GraphicsOption::ShadowCascades => {
+                self.shadow_cascades = next_in(&SHADOW_CASCADE_STEPS, self.shadow_cascades);
+            }
+            // Bevy's SSAO only works without MSAA, so turning one on turns the other off
+            GraphicsOption::Msaa => {
+                self.msaa = next_in(&MSAA_STEPS, self.msaa);
+                if self.msaa > 1 {
+                    self.ssao = false;
+                }
+            }
+            GraphicsOption::Bloom => self.bloom = !self.bloom,
+            GraphicsOption::Ssao => {
+                self.ssao = !self.ssao && !cfg!(target_arch = "wasm32");
+                if self.ssao {
+                    self.msaa = 1;
+                }
+            }
+            GraphicsOption::RenderScale => self.render_scale = next_in(&RENDER_SCALE_STEPS, self.render_scale),
+            GraphicsOption::ViewDistance => self.view_distance = next_in(&VIEW_DISTANCE_STEPS, self.view_distance),
+        }
+        self.preset = GraphicsPreset::Custom;
+    }
+
+    // Menu button text for an option
+    pub fn label(&self, option: GraphicsOption) -> String {
+        let on_off = |enabled: bool| if enabled { "On" } else { "Off" };
+        match option {
+            GraphicsOption::Preset => format!("Quality: {:?}", self.preset),
+            GraphicsOption::ShadowResolution => format!("Shadow Resolution: {}", self.shadow_resolution),
+            GraphicsOption::ShadowCascades => format!("Shadow Cascades: {}", self.shadow_cascades),
+            GraphicsOption::Msaa if self.msaa > 1 => format!("MSAA: {}x", self.msaa),
+            GraphicsOption::Msaa => "MSAA: Off".to_string(),
+            GraphicsOption::Bloom => format!("Bloom: {}", on_off(self.bloom)),
+            GraphicsOption::Ssao => format!("Ambient Occlusion: {}", on_off(self.ssao)),
+            GraphicsOption::RenderScale => format!("Render Scale: {:.0}%", self.render_scale * 100.0),
+            GraphicsOption::ViewDistance => format!("View Distance: {} chunks", self.view_distance),
+        }
+    }
+
+    fn msaa(&self) -> Msaa {
+        match self.msaa {
+            0 | 1 => Msaa::Off,
+            2 => Msaa::Sample2,
+            4 => Msaa::Sample4,
+            _ => Msaa::Sample8,
+        }
+    }
+}
+
+// The offscreen image the world is drawn into below full resolution, and how to map between its pixels
+// and the window's
+#[derive(Resource)]
+pub struct RenderScale {
+    pub image: Option<Handle<Image>>,
+    // Main camera viewport pixels per logical window pixel; 1 at full resolution
+    pub factor: f32,
+}
+
+impl Default for RenderScale {
+    fn default() -> Self {
+        Self { image: None, factor: 1.0 }
+    }
+}
+
+impl RenderScale {
+    // A window position, such as the cursor, in the main camera's viewport
+    pub fn window_to_viewport(&self, position: Vec2) -> Vec2 {
+        position * self.factor
+    }
+
+    // A position in the main camera's viewport, such as a projected world point, in the window
+    pub fn viewport_to_window(&self, position: Vec2) -> Vec2 {
+        position / self.factor
+    }
+}
+
+// The camera and sprite that stretch the offscreen image over the window
+#[derive(Component)]
+pub struct UpscaleView;
+
+// Push the graphics settings out to the lights, the 3D cameras and terrain streaming. Cameras and lights
+// spawned later, like a split screen guest's camera, pick them up when they appear.
+pub fn apply_graphics_settings(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut shadow_map: ResMut<DirectionalLightShadowMap>,
+    mut chunk_manager: ResMut<ChunkManager>,
+    light_query: Query<(Entity, Ref<DirectionalLight>)>,
+    mut camera_query: Query<(Entity, Ref<Camera3d>, &mut Camera), Without<MinimapCamera>>,
+) {
+    let graphics = &settings.graphics;
+    if settings.is_changed() {
+        shadow_map.size = graphics.shadow_resolution as usize;
+        chunk_manager.view_distance = graphics.view_distance;
+    }
+
+    for (entity, light) in light_query.iter() {
+        if !settings.is_changed() && !light.is_added() {
+            continue;
+        }
+        commands.entity(entity).insert(CascadeShadowConfigBuilder {
+            num_cascades: graphics.shadow_cascades,
+            ..default()
+        }.build());
+    }
+
+    for (entity, camera3d, mut camera) in camera_query.iter_mut() {
+        if !settings.is_changed() && !camera3d.is_added() {
+            continue;
+        }
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.insert(graphics.msaa());
+        // Bloom spreads light above full brightness, which only an HDR camera keeps
+        camera.hdr = graphics.bloom;
+        if graphics.bloom {
+            entity_commands.insert(Bloom::NATURAL);
+        } else {
+            entity_commands.remove::<Bloom>();
+        }
+        if graphics.ssao && !cfg!(target_arch = "wasm32") {
+            entity_commands.insert((ScreenSpaceAmbientOcclusion::default(), Msaa::Off));
+        } else {
+            entity_commands.remove::<(ScreenSpaceAmbientOcclusion, DepthPrepass, NormalPrepass)>();
+        }
+    }
+}
+
+// Below full render scale, point the main camera at an offscreen image sized to the scaled window and
+// stretch it over the window, with the HUD drawn on top at full resolution. Split screen draws at full
+// resolution, as the guest's half would need an upscale pass of its own.
+pub fn update_render_scale(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut render_scale: ResMut<RenderScale>,
+    mut images: ResMut<Assets<Image>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut camera_query: Query<(Entity, &mut Camera), With<FollowCamera>>,
+    mut sprite_query: Query<&mut Sprite, With<UpscaleView>>,
+    upscale_query: Query<Entity, With<UpscaleView>>,
+    guest_query: Query<(), With<GuestCamera>>,
+) {
+    let (Ok(window), Ok((camera_entity, mut camera))) = (window_query.get_single(), camera_query.get_single_mut()) else {
+        return;
+    };
+    let physical = window.physical_size();
+    if physical.x == 0 || physical.y == 0 {
+        // Minimized; keep whatever was set up
+        return;
+    }
+
+    let scale = settings.graphics.render_scale;
+    if scale >= 1.0 || !guest_query.is_empty() {
+        if render_scale.image.take().is_some() {
+            camera.target = RenderTarget::Window(WindowRef::Primary);
+            commands.entity(camera_entity).insert(IsDefaultUiCamera);
+            for entity in upscale_query.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+            render_scale.factor = 1.0;
+        }
+        return;
+    }
+
+    let size = (physical.as_vec2() * scale).round().as_uvec2().max(UVec2::ONE);
+    let extent = Extent3d {
+        width: size.x,
+        height: size.y,
+        depth_or_array_layers: 1,
+    };
+    match render_scale.image.clone() {
+        Some(handle) => {
+            if let Some(image) = images.get_mut(&handle) {
+                if image.size() != size {
+                    image.resize(extent);
+                }
+            }
+        }
+        None => {
+            let mut image = Image {
+                texture_descriptor: TextureDescriptor {
+                    label: Some("render_scale"),
+                    size: extent,
+                    dimension: TextureDimension::D2,
+                    format: TextureFormat::Bgra8UnormSrgb,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    usage: TextureUsages::TEXTURE_BINDING
+                        | TextureUsages::COPY_DST
+                        | TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                },
+                ..default()
+            };
+            image.resize(extent);
+            let handle = images.add(image);
+            camera.target = RenderTarget::Image(handle.clone());
+
+            // The HUD moves to the upscale camera so it stays sharp and lines up with the window
+            commands.entity(camera_entity).remove::<IsDefaultUiCamera>();
+            commands.spawn((
+                UpscaleView,
+                Camera2d,
+                Camera {
+                    order: UPSCALE_CAMERA_ORDER,
+                    ..default()
+                },
+                IsDefaultUiCamera,
+                RenderLayers::layer(UPSCALE_LAYER),
+            ));
+            commands.spawn((
+                UpscaleView,
+                Sprite {
+                    image: handle.clone(),
+                    custom_size: Some(window.size()),
+                    ..default()
+                },
+                RenderLayers::layer(UPSCALE_LAYER),
+            ));
+            render_scale.image = Some(handle);
+        }
+    }
+    for mut sprite in sprite_query.iter_mut() {
+        if sprite.custom_size != Some(window.size()) {
+            sprite.custom_size = Some(window.size());
+        }
+    }
+    render_scale.factor = size.x as f32 / window.width();
+}
+
+// Plugin for the graphics quality options
+pub struct GraphicsPlugin;
+
+impl Plugin for GraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<RenderScale>()
+            // The scale follows the window before anything maps the cursor or world points through it, so
+            // picking stays lined up in the frame a resize or mode switch lands
+            .add_systems(Update, (
+                apply_graphics_settings,
+                update_render_scale.before(cursor_raycasting).before(update_score_popups).before(update_waypoint_indicators),
+            ));
+    }
+}
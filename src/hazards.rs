@@ -0,0 +1,259 @@
+use bevy::prelude::*;
+use rand::Rng;
+use crate::terrain::{chunk_rng, get_terrain_height, queue_new_chunks, sample, ScatterQueue, TerrainChunk, TerrainSurface, CHUNK_SIZE, LAVA_LEVEL};
+use crate::budget::WorkBudgets;
+use crate::player::{player_spawn_position, Health, Player, PlayerPhysics};
+use crate::camera::CameraShake;
+use crate::game_events::GameEvent;
+use crate::difficulty::Difficulty;
+use crate::photo_mode::PhotoModeState;
+use crate::sim::SimSet;
+use crate::GameState;
+
+// Hazard constants
+const LAVA_SAMPLES: usize = 8; // Height samples per side when checking a chunk for a flooded valley
+const LAVA_DAMAGE: f32 = 40.0; // Health lost falling into lava, before being put back on safe ground
+const LAVA_PULSE_SPEED: f32 = 1.5; // Radians per second of the lava glow pulse
+const SPIKE_LEVEL: f32 = -2.5; // Valley floors below this height can grow spike fields
+const SPIKE_FIELD_ATTEMPTS: usize = 3; // Spots tried for a spike field in each chunk
+const SPIKES_PER_FIELD: usize = 9; // Spikes in a field
+const SPIKE_FIELD_RADIUS: f32 = 3.0; // Spread of the spikes around the field's center
+const SPIKE_RADIUS: f32 = 0.25; // Radius of a spike's base
+const SPIKE_HEIGHT: f32 = 0.8; // Height of a spike
+const SPIKE_DAMAGE: f32 = 15.0; // Health lost rolling onto a spike
+const SPIKE_BOUNCE: f32 = 6.0; // Upward speed a spike knocks the player off with
+const SAFE_CLEARANCE: f32 = 4.0; // Distance from spikes for ground to count as a safe respawn point
+const HAZARD_COOLDOWN: f32 = 1.0; // Seconds of immunity after taking hazard damage
+const HAZARD_TRAUMA: f32 = 0.5; // Camera trauma from a hazard hit
+const PLAYER_RADIUS: f32 = 0.5;
+const HAZARD_SALT: u64 = 1; // Chunk random stream for hazards
+const LAVA_COLOR: Color = Color::srgb(1.0, 0.35, 0.05);
+
+// Flat lava surface at LAVA_LEVEL across a chunk; the terrain hides it everywhere but the valleys
+#[derive(Component)]
+pub struct LavaPool;
+
+// A spike in a spike field, parented to the terrain chunk
+#[derive(Component)]
+pub struct Spike;
+
+// Shared meshes and materials for hazards
+#[derive(Resource)]
+pub struct HazardAssets {
+    pub lava_mesh: Handle<Mesh>,
+    pub lava_material: Handle<StandardMaterial>,
+    pub spike_mesh: Handle<Mesh>,
+    pub spike_material: Handle<StandardMaterial>,
+}
+
+// Where the player is put back after falling into lava, and the immunity after a hit
+#[derive(Resource)]
+pub struct HazardState {
+    // Last grounded position clear of every hazard
+    pub safe_position: Vec3,
+    // Seconds until hazards can hurt the player again
+    pub cooldown: f32,
+}
+
+impl Default for HazardState {
+    fn default() -> Self {
+        Self {
+            safe_position: player_spawn_position(),
+            cooldown: 0.0,
+        }
+    }
+}
+
+// Whether the terrain at a position is flooded with lava
+pub fn in_lava(x: f32, z: f32) -> bool {
+    sample(x, z).surface == TerrainSurface::Lava
+}
+
+// Create the shared hazard meshes and materials
+pub fn setup_hazard_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(HazardAssets {
+        lava_mesh: meshes.add(Plane3d::default().mesh().size(CHUNK_SIZE, CHUNK_SIZE)),
+        lava_material: materials.add(StandardMaterial {
+            base_color: LAVA_COLOR,
+            emissive: (LAVA_COLOR.to_linear() * 2.0).into(),
+            perceptual_roughness: 0.6,
+            ..default()
+        }),
+        spike_mesh: meshes.add(Cone::new(SPIKE_RADIUS, SPIKE_HEIGHT)),
+        spike_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.25, 0.25, 0.28),
+            metallic: 0.8,
+            perceptual_roughness: 0.35,
+            ..default()
+        }),
+    });
+}
+
+// Flood low valleys and scatter spike fields as chunks are generated, a few chunks a frame within the
+// scatter budget
+pub fn populate_hazards(
+    mut commands: Commands,
+    assets: Res<HazardAssets>,
+    budgets: Res<WorkBudgets>,
+    mut queue: Local<ScatterQueue>,
+    chunk_query: Query<(Entity, &TerrainChunk), Added<TerrainChunk>>,
+) {
+    queue_new_chunks(&mut queue, &chunk_query);
+    queue.run(budgets.scatter_ms, |(entity, (chunk_x, chunk_z))| {
+        // A chunk rebuilt before its turn comes back as a new entity
+        let Some(mut chunk_commands) = commands.get_entity(entity) else {
+            return;
+        };
+        let origin = Vec2::new(chunk_x as f32 * CHUNK_SIZE, chunk_z as f32 * CHUNK_SIZE);
+        let step = CHUNK_SIZE / LAVA_SAMPLES as f32;
+        let flooded = (0..=LAVA_SAMPLES).any(|i| {
+            (0..=LAVA_SAMPLES).any(|j| in_lava(origin.x + i as f32 * step, origin.y + j as f32 * step))
+        });
+
+        let mut rng = chunk_rng(chunk_x, chunk_z, HAZARD_SALT);
+        chunk_commands.with_children(|parent| {
+            if flooded {
+                parent.spawn((
+                    LavaPool,
+                    Mesh3d(assets.lava_mesh.clone()),
+                    MeshMaterial3d(assets.lava_material.clone()),
+                    Transform::from_xyz(CHUNK_SIZE * 0.5, LAVA_LEVEL, CHUNK_SIZE * 0.5),
+                ));
+            }
+
+            for _ in 0..SPIKE_FIELD_ATTEMPTS {
+                let center = Vec2::new(
+                    rng.gen_range(SPIKE_FIELD_RADIUS..CHUNK_SIZE - SPIKE_FIELD_RADIUS),
+                    rng.gen_range(SPIKE_FIELD_RADIUS..CHUNK_SIZE - SPIKE_FIELD_RADIUS),
+                );
+                let offsets: Vec<Vec2> = (0..SPIKES_PER_FIELD)
+                    .map(|_| Vec2::new(
+                        rng.gen_range(-SPIKE_FIELD_RADIUS..SPIKE_FIELD_RADIUS),
+                        rng.gen_range(-SPIKE_FIELD_RADIUS..SPIKE_FIELD_RADIUS),
+                    ))
+                    .collect();
+                // Only valley floors that aren't already under lava grow spikes
+                let world = origin + center;
+                let height = get_terrain_height(world.x, world.y);
+                if height >= SPIKE_LEVEL || height < LAVA_LEVEL {
+                    continue;
+                }
+                for offset in offsets {
+                    let local = center + offset;
+                    let ground = get_terrain_height(origin.x + local.x, origin.y + local.y);
+                    parent.spawn((
+                        Spike,
+                        Mesh3d(assets.spike_mesh.clone()),
+                        MeshMaterial3d(assets.spike_material.clone()),
+                        // Cones are centered on their origin, so lift by half the height
+                        Transform::from_xyz(local.x, ground + SPIKE_HEIGHT * 0.5, local.y),
+                    ));
+                }
+            }
+        });
+    });
+}
+
+// Make the lava glow pulse
+pub fn animate_lava(
+    assets: Res<HazardAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+) {
+    if let Some(material) = materials.get_mut(&assets.lava_material) {
+        let glow = 1.5 + (time.elapsed_secs() * LAVA_PULSE_SPEED).sin() * 0.5;
+        material.emissive = (LAVA_COLOR.to_linear() * glow).into();
+    }
+}
+
+// Hurt the player on lava and spikes, putting them back on safe ground when they fall into lava
+pub fn hazard_contact(
+    mut hazard_state: ResMut<HazardState>,
+    mut camera_shake: ResMut<CameraShake>,
+    mut game_events: EventWriter<GameEvent>,
+    mut player_query: Query<(&mut Transform, &mut PlayerPhysics, &mut Health), With<Player>>,
+    spike_query: Query<&GlobalTransform, With<Spike>>,
+    difficulty: Res<Difficulty>,
+    time: Res<Time>,
+) {
+    let Ok((mut transform, mut physics, mut health)) = player_query.get_single_mut() else {
+        return;
+    };
+    hazard_state.cooldown = (hazard_state.cooldown - time.delta_secs()).max(0.0);
+    let position = transform.translation;
+
+    // Falling into lava always sends the player back, but only hurts once per cooldown
+    if position.y - PLAYER_RADIUS < LAVA_LEVEL && in_lava(position.x, position.z) {
+        if hazard_state.cooldown <= 0.0 {
+            health.current -= LAVA_DAMAGE * difficulty.damage_taken();
+            hazard_state.cooldown = HAZARD_COOLDOWN;
+            camera_shake.add_trauma(HAZARD_TRAUMA);
+            game_events.send(GameEvent::HazardHit { name: "Lava".to_string() });
+        }
+        let safe = hazard_state.safe_position;
+        transform.translation = safe;
+        *physics = PlayerPhysics {
+            prev_position: safe,
+            speed_multiplier: physics.speed_multiplier,
+            traction: physics.traction,
+            slope_assist: physics.slope_assist,
+            radius: physics.radius,
+            ..Default::default()
+        };
+        return;
+    }
+
+    let mut near_spikes = false;
+    for spike_transform in spike_query.iter() {
+        let spike = spike_transform.translation();
+        let horizontal = Vec2::new(spike.x - position.x, spike.z - position.z).length();
+        if horizontal < SAFE_CLEARANCE {
+            near_spikes = true;
+        }
+        let tip = spike.y + SPIKE_HEIGHT * 0.5;
+        if horizontal > SPIKE_RADIUS + PLAYER_RADIUS || position.y - PLAYER_RADIUS > tip {
+            continue;
+        }
+        if hazard_state.cooldown <= 0.0 {
+            health.current -= SPIKE_DAMAGE * difficulty.damage_taken();
+            hazard_state.cooldown = HAZARD_COOLDOWN;
+            camera_shake.add_trauma(HAZARD_TRAUMA);
+            game_events.send(GameEvent::HazardHit { name: "Spikes".to_string() });
+        }
+        physics.velocity.y = SPIKE_BOUNCE;
+        physics.momentum.y = SPIKE_BOUNCE;
+        physics.grounded = false;
+        break;
+    }
+
+    if physics.grounded && !near_spikes {
+        hazard_state.safe_position = position;
+    }
+}
+
+// Forget the last safe spot for a new run
+pub fn reset_hazard_state(mut hazard_state: ResMut<HazardState>) {
+    *hazard_state = HazardState::default();
+}
+
+// Plugin for lava pools and spike fields
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<HazardState>()
+            .add_systems(Startup, setup_hazard_assets)
+            .add_systems(OnExit(GameState::GameOver), reset_hazard_state)
+            // Chunks generate behind the main menu too, so they need their hazards from the start
+            .add_systems(Update, (populate_hazards, animate_lava))
+            .add_systems(FixedUpdate, hazard_contact
+                .in_set(SimSet::Constraints)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)));
+    }
+}
@@ -0,0 +1,181 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use std::time::{Duration, Instant};
+use crate::bench::build_windowless_app;
+use crate::intro::CinematicIntro;
+use crate::photo_mode::PhotoModeState;
+use crate::player::PlayerPlugin;
+use crate::progression::Upgrades;
+use crate::projectile::ProjectilePlugin;
+use crate::results::RunStats;
+use crate::sim::{SimInput, SimPlugin, Simulation, SIM_TICK_RATE};
+use crate::spatial::SpatialPlugin;
+use crate::terrain::{ChunkManager, TerrainConfig};
+use crate::GameState;
+
+// Headless app constants
+const TERRAIN_TIMEOUT: Duration = Duration::from_secs(60); // Longest wait for the chunks around the player
+
+// Build a windowless app that plays the simulation: the player, boulders and terrain streaming, stepped in
+// SimSet order without rendering, audio or the menus. Every update advances the clock by exactly one tick,
+// and input comes from inject_input instead of the keyboard.
+pub fn build_sim_app(terrain: Option<TerrainConfig>) -> App {
+    let mut app = build_windowless_app(terrain, GameState::Playing);
+    let tick = Duration::from_secs_f64(1.0 / SIM_TICK_RATE);
+    app
+        .insert_resource(TimeUpdateStrategy::ManualDuration(tick))
+        .init_state::<PhotoModeState>()
+        .init_resource::<CinematicIntro>()
+        .init_resource::<RunStats>()
+        .init_resource::<Upgrades>()
+        .add_plugins((SimPlugin { headless: true }, PlayerPlugin, ProjectilePlugin { headless: true }, SpatialPlugin));
+    app.finish();
+    app.cleanup();
+    app
+}
+
+// Run the app until the simulation has stepped the given number of ticks; the very first update only
+// starts the clock, so updates and ticks don't always line up one to one
+pub fn step_ticks(app: &mut App, ticks: u32) {
+    let target = app.world().resource::<Simulation>().tick + ticks;
+    while app.world().resource::<Simulation>().tick < target {
+        app.update();
+    }
+}
+
+// Hold the given movement and buttons from the next tick on, the way latch_input would for a player;
+// buttons that weren't held before count as pressed
+pub fn inject_input(app: &mut App, move_axis: Vec2, held: u8, aim: Option<Vec3>) {
+    let mut input = app.world_mut().resource_mut::<SimInput>();
+    input.pressed |= held & !input.held;
+    input.move_axis = move_axis.clamp_length_max(1.0);
+    input.aim = aim;
+    input.held = held;
+}
+
+// Step until no chunks are waiting to be built, returning false if that takes too long
+pub fn wait_for_terrain(app: &mut App) -> bool {
+    let start = Instant::now();
+    loop {
+        step_ticks(app, 1);
+        if app.world().resource::<ChunkManager>().pending_count() == 0 {
+            return true;
+        }
+        if start.elapsed() > TERRAIN_TIMEOUT {
+            return false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platforms::{ground_height, PlatformSurfaces};
+    use crate::player::{Player, PlayerPhysics};
+    use crate::projectile::ProjectileImpactEvent;
+    use crate::replay::BUTTON_THROW;
+    use crate::terrain::{chunk_coords, get_terrain_height, CHUNK_SIZE};
+
+    const RADIUS: f32 = 0.5; // Player sphere radius
+
+    fn player(app: &mut App) -> (Vec3, bool) {
+        let world = app.world_mut();
+        let (transform, physics) = world.query_filtered::<(&Transform, &PlayerPhysics), With<Player>>().single(world);
+        (transform.translation, physics.grounded)
+    }
+
+    fn teleport_player(app: &mut App, position: Vec3) {
+        let world = app.world_mut();
+        let (mut transform, mut physics) = world
+            .query_filtered::<(&mut Transform, &mut PlayerPhysics), With<Player>>()
+            .single_mut(world);
+        transform.translation = position;
+        physics.velocity = Vec3::ZERO;
+        physics.momentum = Vec3::ZERO;
+        physics.prev_position = position;
+    }
+
+    // Height the ball should rest at, platforms included
+    fn surface_height(app: &App, position: Vec3) -> f32 {
+        ground_height(&app.world().resource::<PlatformSurfaces>().0, position, RADIUS) + RADIUS
+    }
+
+    fn settled_app() -> App {
+        let mut app = build_sim_app(None);
+        assert!(wait_for_terrain(&mut app), "terrain around the start never finished loading");
+        step_ticks(&mut app, 90);
+        app
+    }
+
+    #[test]
+    fn ball_rests_on_the_ground() {
+        let mut app = settled_app();
+        let (position, grounded) = player(&mut app);
+        assert!(grounded, "ball still in the air at {position}");
+        let surface = surface_height(&app, position);
+        assert!((position.y - surface).abs() < 0.1, "ball at {} but the surface is at {}", position.y, surface);
+    }
+
+    #[test]
+    fn ball_never_sinks_while_rolling() {
+        let mut app = settled_app();
+        inject_input(&mut app, Vec2::new(0.3, 1.0), 0, None);
+        for _ in 0..240 {
+            step_ticks(&mut app, 1);
+            let (position, _) = player(&mut app);
+            let surface = surface_height(&app, position);
+            assert!(position.y >= surface - 1e-3, "ball sank to {} below the surface at {}", position.y, surface);
+        }
+    }
+
+    #[test]
+    fn thrown_boulder_lands_at_the_aim() {
+        let mut app = settled_app();
+        let (position, _) = player(&mut app);
+        let landing = position.xz() + Vec2::new(10.0, 4.0);
+        let aim = Vec3::new(landing.x, get_terrain_height(landing.x, landing.y), landing.y);
+
+        // Tap the throw button: press on one tick, release on the next
+        inject_input(&mut app, Vec2::ZERO, BUTTON_THROW, Some(aim));
+        step_ticks(&mut app, 1);
+        inject_input(&mut app, Vec2::ZERO, 0, Some(aim));
+
+        let mut cursor = app.world().resource::<Events<ProjectileImpactEvent>>().get_cursor();
+        let mut impact = None;
+        for _ in 0..600 {
+            step_ticks(&mut app, 1);
+            let events = app.world().resource::<Events<ProjectileImpactEvent>>();
+            if let Some(event) = cursor.read(events).next() {
+                impact = Some((event.position, event.hostile));
+                break;
+            }
+        }
+        let (impact, hostile) = impact.expect("boulder never hit the ground");
+        assert!(!hostile);
+        assert!(impact.xz().distance(landing) < 1.5, "boulder landed at {impact}, aimed at {aim}");
+        assert!((impact.y - get_terrain_height(impact.x, impact.z)).abs() < 0.01);
+    }
+
+    #[test]
+    fn chunks_follow_the_moving_player() {
+        let mut app = settled_app();
+        for step in 1..=4 {
+            let x = step as f32 * CHUNK_SIZE * 0.75;
+            let z = -(step as f32) * CHUNK_SIZE * 0.5;
+            teleport_player(&mut app, Vec3::new(x, get_terrain_height(x, z) + RADIUS, z));
+            step_ticks(&mut app, 10);
+        }
+        assert!(wait_for_terrain(&mut app), "terrain around the player never finished loading");
+
+        let (position, _) = player(&mut app);
+        let (center_x, center_z) = chunk_coords(position);
+        let chunk_manager = app.world().resource::<ChunkManager>();
+        for z in center_z - 2..=center_z + 2 {
+            for x in center_x - 2..=center_x + 2 {
+                assert!(chunk_manager.loaded_chunks.contains_key(&(x, z)), "chunk ({x}, {z}) not loaded");
+            }
+        }
+        // The starting chunks stay around since nothing unloads yet
+        assert!(chunk_manager.loaded_chunks.contains_key(&(0, 0)));
+    }
+}
@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+use crate::player::{Player, PlayerPhysics};
+use crate::projectile::Ammo;
+use crate::results::RunStats;
+use crate::save::SaveData;
+use crate::terrain::{sample, TerrainSurface};
+use crate::photo_mode::PhotoModeState;
+use crate::GameState;
+
+// Hint constants
+const HINT_DURATION: f32 = 8.0; // Seconds a toast stays up unless dismissed
+const LOW_AMMO_THRESHOLD: u32 = 2; // Boulders left that trigger the low ammo hint
+
+// Tutorial hints, each shown once per save
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hint {
+    SteepSlope,
+    FirstThrow,
+    LowAmmo,
+}
+
+impl Hint {
+    // Stable id stored in the save file
+    pub fn id(self) -> &'static str {
+        match self {
+            Hint::SteepSlope => "steep_slope",
+            Hint::FirstThrow => "first_throw",
+            Hint::LowAmmo => "low_ammo",
+        }
+    }
+
+    pub fn text(self) -> &'static str {
+        match self {
+            Hint::SteepSlope => "Steep slopes pull you downhill. Jump or dash to get over a ridge.",
+            Hint::FirstThrow => "Hold the throw button to charge; charged throws reach farther.",
+            Hint::LowAmmo => "Running low on boulders. They restore over time, so pick your shots.",
+        }
+    }
+}
+
+// The toast currently on screen and the hints waiting behind it
+#[derive(Resource, Default)]
+pub struct HintToast {
+    pub active: Option<Hint>,
+    // Seconds until the active toast hides itself
+    pub timer: f32,
+    pub queue: Vec<Hint>,
+}
+
+// Marker for the toast panel; clicking it dismisses the hint
+#[derive(Component)]
+pub struct HintToastRoot;
+
+// Marker for the toast text
+#[derive(Component)]
+pub struct HintToastText;
+
+// Spawn the toast panel near the bottom of the screen, hidden until a hint shows
+pub fn spawn_hint_toast(mut commands: Commands) {
+    commands
+        .spawn((
+            HintToastRoot,
+            Button,
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(40.0),
+                left: Val::Percent(50.0),
+                width: Val::Px(440.0),
+                margin: UiRect::left(Val::Px(-220.0)),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.8)),
+            BorderRadius::all(Val::Px(6.0)),
+            GlobalZIndex(5),
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                HintToastText,
+                Text::new(""),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            parent.spawn((
+                Text::new("Click or press H to dismiss"),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.6, 0.6, 0.6)),
+            ));
+        });
+}
+
+// Watch gameplay for the situations each hint explains
+pub fn detect_hint_conditions(
+    mut toast: ResMut<HintToast>,
+    mut save: ResMut<SaveData>,
+    player_query: Query<(&Transform, &PlayerPhysics), With<Player>>,
+    ammo: Res<Ammo>,
+    run_stats: Res<RunStats>,
+) {
+    let mut occurred = Vec::new();
+    if let Ok((transform, physics)) = player_query.get_single() {
+        let surface = sample(transform.translation.x, transform.translation.z).surface;
+        if physics.grounded && surface == TerrainSurface::Steep {
+            occurred.push(Hint::SteepSlope);
+        }
+    }
+    if run_stats.boulders_fired > 0 {
+        occurred.push(Hint::FirstThrow);
+    }
+    if ammo.current <= LOW_AMMO_THRESHOLD {
+        occurred.push(Hint::LowAmmo);
+    }
+
+    // Queue each hint the first time it comes up; only touch the save when something is new
+    for hint in occurred {
+        if !save.has_seen_hint(hint.id()) {
+            save.mark_hint_seen(hint.id());
+            toast.queue.push(hint);
+        }
+    }
+}
+
+// Show queued hints one at a time and hide them when they time out or are dismissed
+pub fn update_hint_toast(
+    mut toast: ResMut<HintToast>,
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut root_query: Query<(&Interaction, &mut Visibility), With<HintToastRoot>>,
+    mut text_query: Query<&mut Text, With<HintToastText>>,
+) {
+    let Ok((interaction, mut visibility)) = root_query.get_single_mut() else {
+        return;
+    };
+
+    if toast.active.is_some() {
+        toast.timer -= time.delta_secs();
+        let dismissed = keys.just_pressed(KeyCode::KeyH) || *interaction == Interaction::Pressed;
+        if dismissed || toast.timer <= 0.0 {
+            toast.active = None;
+        }
+    }
+
+    if toast.active.is_none() && !toast.queue.is_empty() {
+        let hint = toast.queue.remove(0);
+        toast.active = Some(hint);
+        toast.timer = HINT_DURATION;
+        for mut text in text_query.iter_mut() {
+            text.0 = hint.text().to_string();
+        }
+    }
+
+    let wanted = if toast.active.is_some() { Visibility::Inherited } else { Visibility::Hidden };
+    if *visibility != wanted {
+        *visibility = wanted;
+    }
+}
+
+// Hide the toast outside of gameplay; queued hints wait for the next time play resumes
+pub fn hide_hint_toast(mut query: Query<&mut Visibility, With<HintToastRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Plugin for tutorial hint toasts
+pub struct HintPlugin;
+
+impl Plugin for HintPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<HintToast>()
+            .add_systems(Startup, spawn_hint_toast)
+            .add_systems(OnExit(GameState::Playing), hide_hint_toast)
+            .add_systems(Update, (detect_hint_conditions, update_hint_toast)
+                .chain()
+                .run_if(in_state(GameState::Playing))
+                // Photo mode hides the UI, so don't bring the toast back mid-shot
+                .run_if(in_state(PhotoModeState::Inactive)));
+    }
+}
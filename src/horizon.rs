@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+use bevy::pbr::NotShadowCaster;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::view::NoFrustumCulling;
+use bevy::utils::HashMap;
+use crate::player::Player;
+use crate::terrain::{chunk_coords, generate_pending_chunks, get_terrain_height, spawn_initial_terrain, world_seed, ChunkManager, CHUNK_SIZE};
+
+// Far terrain constants
+const HORIZON_RADIUS: i32 = 10; // Chunks in each direction the imposter reaches around the player's chunk
+const CELLS_PER_CHUNK: i32 = 2; // Imposter cells along each side of a chunk
+const CELL_SIZE: f32 = CHUNK_SIZE / CELLS_PER_CHUNK as f32;
+const HORIZON_SINK: f32 = 0.3; // How far the imposter sits below the real terrain, so real chunks win at the seams
+
+// Marker for the far terrain mesh
+#[derive(Component)]
+pub struct HorizonTerrain;
+
+// A coarse stand-in for the terrain past the loaded chunks, so the world runs out to the horizon instead
+// of ending at the edge of the chunk grid. It leaves a hole wherever a real chunk is loaded and is rebuilt
+// when the player changes chunk or chunks come and go.
+#[derive(Resource, Default)]
+pub struct Horizon {
+    mesh: Handle<Mesh>,
+    // Player chunk and loaded chunk count the mesh was last built for
+    built_for: Option<((i32, i32), usize)>,
+    // Heights at imposter vertices, by vertex coordinate, for the seed they were sampled from
+    heights: HashMap<(i32, i32), f32>,
+    seed: u32,
+}
+
+// Spawn the far terrain with an empty mesh, sharing the chunks' material
+pub fn setup_horizon(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    chunk_manager: Res<ChunkManager>,
+) {
+    let mesh = meshes.add(Mesh::new(PrimitiveTopology::TriangleList, Default::default()));
+    commands.spawn((
+        HorizonTerrain,
+        Mesh3d(mesh.clone()),
+        MeshMaterial3d(chunk_manager.material_handle.clone()),
+        Transform::default(),
+        // The mesh follows the player, so bounds computed for its first shape would soon be wrong
+        NoFrustumCulling,
+        NotShadowCaster,
+    ));
+    commands.insert_resource(Horizon { mesh, seed: world_seed(), ..default() });
+}
+
+// Build the imposter grid around a chunk, skipping cells over loaded chunks
+fn build_horizon_mesh(heights: &mut HashMap<(i32, i32), f32>, center: (i32, i32), chunk_manager: &ChunkManager) -> Mesh {
+    let min = ((center.0 - HORIZON_RADIUS) * CELLS_PER_CHUNK, (center.1 - HORIZON_RADIUS) * CELLS_PER_CHUNK);
+    let side = (HORIZON_RADIUS * 2 + 1) * CELLS_PER_CHUNK; // Cells along each side
+    let row = side as usize + 1;
+
+    let mut height = |x: i32, z: i32| *heights.entry((x, z)).or_insert_with(|| {
+        get_terrain_height(x as f32 * CELL_SIZE, z as f32 * CELL_SIZE) - HORIZON_SINK
+    });
+    let mut positions = Vec::with_capacity(row * row);
+    let mut normals = Vec::with_capacity(row * row);
+    for z in min.1..=min.1 + side {
+        for x in min.0..=min.0 + side {
+            let y = height(x, z);
+            positions.push([x as f32 * CELL_SIZE, y, z as f32 * CELL_SIZE]);
+            // Central differences, reaching one cell past the edge so the rim shades like the rest
+            let slope_x = (height(x + 1, z) - height(x - 1, z)) / (2.0 * CELL_SIZE);
+            let slope_z = (height(x, z + 1) - height(x, z - 1)) / (2.0 * CELL_SIZE);
+            normals.push(Vec3::new(-slope_x, 1.0, -slope_z).normalize().to_array());
+        }
+    }
+
+    let mut indices = Vec::new();
+    for cell_z in 0..side {
+        for cell_x in 0..side {
+            let chunk = (
+                (min.0 + cell_x).div_euclid(CELLS_PER_CHUNK),
+                (min.1 + cell_z).div_euclid(CELLS_PER_CHUNK),
+            );
+            if chunk_manager.loaded_chunks.contains_key(&chunk) {
+                continue;
+            }
+            let tl = cell_z as u32 * row as u32 + cell_x as u32;
+            let tr = tl + 1;
+            let bl = tl + row as u32;
+            let br = bl + 1;
+            indices.extend_from_slice(&[tl, bl, tr, tr, bl, br]);
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, Default::default());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_indices(Indices::U32(indices));
+    mesh
+}
+
+// Rebuild the far terrain when the player crosses into another chunk or the loaded chunks change
+pub fn update_horizon(
+    mut horizon: ResMut<Horizon>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    chunk_manager: Res<ChunkManager>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    if horizon.seed != world_seed() {
+        horizon.seed = world_seed();
+        horizon.heights.clear();
+        horizon.built_for = None;
+    }
+    let center = chunk_coords(player_transform.translation);
+    let key = (center, chunk_manager.loaded_chunks.len());
+    if horizon.built_for == Some(key) {
+        return;
+    }
+    horizon.built_for = Some(key);
+
+    let mesh = build_horizon_mesh(&mut horizon.heights, center, &chunk_manager);
+    meshes.insert(&horizon.mesh, mesh);
+
+    // Forget heights that have fallen out of reach, keeping the one-cell border the normals use
+    let reach = (HORIZON_RADIUS + 1) * CELLS_PER_CHUNK + 1;
+    let (center_x, center_z) = (center.0 * CELLS_PER_CHUNK, center.1 * CELLS_PER_CHUNK);
+    horizon.heights.retain(|(x, z), _| (x - center_x).abs() <= reach && (z - center_z).abs() <= reach);
+}
+
+// Plugin for the coarse terrain out to the horizon
+pub struct HorizonPlugin;
+
+impl Plugin for HorizonPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup_horizon.after(spawn_initial_terrain))
+            .add_systems(Update, update_horizon.after(generate_pending_chunks));
+    }
+}
@@ -0,0 +1,202 @@
+use bevy::prelude::*;
+use crate::player::{Health, Player, PlayerPhysics};
+use crate::projectile::{Ammo, AmmoKind, SelectedAmmo};
+use crate::score::Score;
+use crate::GameState;
+
+// HUD layout constants
+const HUD_FONT_SIZE: f32 = 18.0;
+const HEALTH_BAR_WIDTH: f32 = 160.0;
+const HEALTH_BAR_HEIGHT: f32 = 10.0;
+
+// Marker for the HUD root, shown only while playing
+#[derive(Component)]
+pub struct HudRoot;
+
+// Markers for the individual HUD readouts
+#[derive(Component)]
+pub struct HudSpeedText;
+
+#[derive(Component)]
+pub struct HudAmmoText;
+
+#[derive(Component)]
+pub struct HudHealthText;
+
+#[derive(Component)]
+pub struct HudHealthBar;
+
+#[derive(Component)]
+pub struct HudPositionText;
+
+#[derive(Component)]
+pub struct HudScoreText;
+
+#[derive(Component)]
+pub struct HudComboText;
+
+// Spawn a single line of HUD text with a marker
+fn spawn_hud_text(parent: &mut ChildBuilder, marker: impl Component) {
+    parent.spawn((
+        marker,
+        Text::new(""),
+        TextFont {
+            font_size: HUD_FONT_SIZE,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    ));
+}
+
+// Build the HUD panel in the top-left corner
+pub fn spawn_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            HudRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(12.0),
+                top: Val::Px(12.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.35)),
+            // Hidden until a game starts
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            spawn_hud_text(parent, HudHealthText);
+            // Health bar: dark track with a colored fill
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(HEALTH_BAR_WIDTH),
+                        height: Val::Px(HEALTH_BAR_HEIGHT),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.8)),
+                ))
+                .with_children(|bar| {
+                    bar.spawn((
+                        HudHealthBar,
+                        Node {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.2, 0.8, 0.3)),
+                    ));
+                });
+            spawn_hud_text(parent, HudAmmoText);
+            spawn_hud_text(parent, HudSpeedText);
+            spawn_hud_text(parent, HudPositionText);
+            spawn_hud_text(parent, HudScoreText);
+            spawn_hud_text(parent, HudComboText);
+        });
+}
+
+// Show the HUD while playing
+pub fn show_hud(mut query: Query<&mut Visibility, With<HudRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+// Hide the HUD outside of gameplay
+pub fn hide_hud(mut query: Query<&mut Visibility, With<HudRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Update speed and position readouts when the player moves
+pub fn update_hud_motion(
+    player_query: Query<(&Transform, &PlayerPhysics), (With<Player>, Or<(Changed<Transform>, Changed<PlayerPhysics>)>)>,
+    mut speed_query: Query<&mut Text, (With<HudSpeedText>, Without<HudPositionText>)>,
+    mut position_query: Query<&mut Text, (With<HudPositionText>, Without<HudSpeedText>)>,
+) {
+    let Ok((transform, physics)) = player_query.get_single() else {
+        return;
+    };
+    let horizontal_speed = Vec2::new(physics.velocity.x, physics.velocity.z).length();
+    for mut text in speed_query.iter_mut() {
+        text.0 = format!("Speed: {:.1} m/s", horizontal_speed);
+    }
+    let position = transform.translation;
+    for mut text in position_query.iter_mut() {
+        text.0 = format!("Pos: {:.0}, {:.0}, {:.0}", position.x, position.y, position.z);
+    }
+}
+
+// Update the health text and bar when health changes
+pub fn update_hud_health(
+    player_query: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut text_query: Query<&mut Text, With<HudHealthText>>,
+    mut bar_query: Query<(&mut Node, &mut BackgroundColor), With<HudHealthBar>>,
+) {
+    let Ok(health) = player_query.get_single() else {
+        return;
+    };
+    for mut text in text_query.iter_mut() {
+        text.0 = format!("Health: {:.0}/{:.0}", health.current.max(0.0), health.max);
+    }
+    let fraction = health.fraction();
+    for (mut node, mut color) in bar_query.iter_mut() {
+        node.width = Val::Percent(fraction * 100.0);
+        // Fade from green to red as health drops
+        *color = BackgroundColor(Color::srgb(0.9 - fraction * 0.7, 0.2 + fraction * 0.6, 0.2));
+    }
+}
+
+// Update the ammo readout when the ammo or the selected kind changes
+pub fn update_hud_ammo(
+    ammo: Res<Ammo>,
+    selected: Res<SelectedAmmo>,
+    mut text_query: Query<&mut Text, With<HudAmmoText>>,
+) {
+    for mut text in text_query.iter_mut() {
+        text.0 = match selected.0 {
+            AmmoKind::Stone => format!("Boulders: {}/{}", ammo.current, ammo.max),
+            kind => format!("{}: {}", kind.label(), ammo.count(kind)),
+        };
+    }
+}
+
+// Update the score and combo readouts when the score changes
+pub fn update_hud_score(
+    score: Res<Score>,
+    mut score_query: Query<&mut Text, (With<HudScoreText>, Without<HudComboText>)>,
+    mut combo_query: Query<&mut Text, (With<HudComboText>, Without<HudScoreText>)>,
+) {
+    for mut text in score_query.iter_mut() {
+        text.0 = format!("Score: {}", score.points);
+    }
+    for mut text in combo_query.iter_mut() {
+        // Only show the combo while one is running
+        text.0 = if score.combo > 1 {
+            format!("Combo x{:.1} ({} hits, {:.1}s)", score.multiplier(), score.combo, score.combo_timer)
+        } else {
+            String::new()
+        };
+    }
+}
+
+// Plugin for the in-game HUD
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, spawn_hud)
+            .add_systems(OnEnter(GameState::Playing), show_hud)
+            .add_systems(OnExit(GameState::Playing), hide_hud)
+            .add_systems(Update, (
+                update_hud_motion,
+                update_hud_health,
+                update_hud_ammo.run_if(resource_changed::<Ammo>.or(resource_changed::<SelectedAmmo>)),
+                update_hud_score.run_if(resource_changed::<Score>),
+            ));
+    }
+}
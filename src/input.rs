@@ -0,0 +1,282 @@
+use bevy::prelude::*;
+use bevy::input::InputSystem;
+use bevy::utils::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+// Gamepad stick dead zone
+const STICK_DEAD_ZONE: f32 = 0.15;
+
+// Gameplay actions that input devices map onto
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Throw,
+    Dash,
+    Shield,
+}
+
+impl Action {
+    // Every action, in the order shown on the controls screen
+    pub const ALL: [Action; 8] = [
+        Action::MoveForward,
+        Action::MoveBack,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Throw,
+        Action::Dash,
+        Action::Shield,
+    ];
+
+    // Human readable name for menus
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveForward => "Move Forward",
+            Action::MoveBack => "Move Back",
+            Action::MoveLeft => "Move Left",
+            Action::MoveRight => "Move Right",
+            Action::Jump => "Jump",
+            Action::Throw => "Throw",
+            Action::Dash => "Dash",
+            Action::Shield => "Shield",
+        }
+    }
+}
+
+// A single physical input that can trigger an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+}
+
+impl InputBinding {
+    // Short name for menus
+    pub fn label(&self) -> String {
+        match self {
+            InputBinding::Key(key) => format!("{:?}", key),
+            InputBinding::Mouse(button) => format!("Mouse {:?}", button),
+            InputBinding::Gamepad(button) => format!("Pad {:?}", button),
+        }
+    }
+
+    // Keyboard and mouse bindings are rebound together; gamepad bindings are kept separately
+    pub fn is_gamepad(&self) -> bool {
+        matches!(self, InputBinding::Gamepad(_))
+    }
+}
+
+// Resource mapping each action to the inputs that trigger it
+#[derive(Resource)]
+pub struct InputMap {
+    pub bindings: HashMap<Action, Vec<InputBinding>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use InputBinding::{Gamepad, Key, Mouse};
+        let mut bindings = HashMap::new();
+        bindings.insert(Action::MoveForward, vec![Key(KeyCode::KeyW), Gamepad(GamepadButton::DPadUp)]);
+        bindings.insert(Action::MoveBack, vec![Key(KeyCode::KeyS), Gamepad(GamepadButton::DPadDown)]);
+        bindings.insert(Action::MoveLeft, vec![Key(KeyCode::KeyA), Gamepad(GamepadButton::DPadLeft)]);
+        bindings.insert(Action::MoveRight, vec![Key(KeyCode::KeyD), Gamepad(GamepadButton::DPadRight)]);
+        bindings.insert(Action::Jump, vec![Key(KeyCode::Space), Gamepad(GamepadButton::South)]);
+        bindings.insert(Action::Throw, vec![Mouse(MouseButton::Left), Gamepad(GamepadButton::RightTrigger2)]);
+        bindings.insert(Action::Dash, vec![Key(KeyCode::ShiftLeft), Gamepad(GamepadButton::West)]);
+        bindings.insert(Action::Shield, vec![Key(KeyCode::KeyQ), Gamepad(GamepadButton::North)]);
+        Self { bindings }
+    }
+}
+
+impl InputMap {
+    // Bindings for an action
+    pub fn get(&self, action: Action) -> &[InputBinding] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    // Replace the keyboard/mouse binding of an action, keeping its gamepad bindings
+    pub fn rebind(&mut self, action: Action, binding: InputBinding) {
+        let entry = self.bindings.entry(action).or_default();
+        entry.retain(|existing| existing.is_gamepad() != binding.is_gamepad());
+        entry.insert(0, binding);
+    }
+}
+
+// Per-frame state of every action, computed from the input map
+#[derive(Resource, Default)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    // Movement as (right, forward), length at most 1; includes analog sticks
+    move_axis: Vec2,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    // Combined movement input as (right, forward)
+    pub fn move_axis(&self) -> Vec2 {
+        self.move_axis
+    }
+
+    // Add an action held on a device outside the input map, such as the touch controls
+    pub fn press(&mut self, action: Action, just_pressed: bool) {
+        self.pressed.insert(action);
+        if just_pressed {
+            self.just_pressed.insert(action);
+        }
+    }
+
+    // Add movement from a device outside the input map, keeping the total at most unit length
+    pub fn add_move_axis(&mut self, axis: Vec2) {
+        self.move_axis = (self.move_axis + axis).clamp_length_max(1.0);
+    }
+}
+
+// A gamepad claimed by a second local player; it drives that player instead of the action state
+#[derive(Component)]
+pub struct GuestGamepad;
+
+// Action currently waiting for a new binding from the controls menu
+#[derive(Resource, Default)]
+pub struct RebindRequest {
+    pub action: Option<Action>,
+    // Set once the click that opened the request has been released
+    ready: bool,
+}
+
+impl RebindRequest {
+    pub fn start(&mut self, action: Action) {
+        self.action = Some(action);
+        self.ready = false;
+    }
+}
+
+// Resolve every action from whether each binding is (pressed, just pressed), adding the analog
+// sticks on top of digital movement
+fn resolve_actions<'a>(
+    input_map: &InputMap,
+    binding_state: impl Fn(&InputBinding) -> (bool, bool),
+    gamepads: impl Iterator<Item = &'a Gamepad>,
+) -> ActionState {
+    let mut state = ActionState::default();
+    for action in Action::ALL {
+        for binding in input_map.get(action) {
+            let (pressed, just_pressed) = binding_state(binding);
+            if pressed {
+                state.pressed.insert(action);
+            }
+            if just_pressed {
+                state.just_pressed.insert(action);
+            }
+        }
+    }
+
+    // Digital movement, then add any analog stick input on top
+    let mut axis = Vec2::ZERO;
+    if state.pressed(Action::MoveForward) { axis.y += 1.0; }
+    if state.pressed(Action::MoveBack) { axis.y -= 1.0; }
+    if state.pressed(Action::MoveLeft) { axis.x -= 1.0; }
+    if state.pressed(Action::MoveRight) { axis.x += 1.0; }
+    for gamepad in gamepads {
+        let stick = gamepad.left_stick();
+        if stick.length() > STICK_DEAD_ZONE {
+            axis += stick;
+        }
+    }
+    state.move_axis = axis.clamp_length_max(1.0);
+    state
+}
+
+// Translate raw device input into action state; a gamepad claimed by a second local player is left out
+pub fn update_action_state(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad, Without<GuestGamepad>>,
+    input_map: Res<InputMap>,
+    mut state: ResMut<ActionState>,
+) {
+    let binding_state = |binding: &InputBinding| match binding {
+        InputBinding::Key(key) => (keys.pressed(*key), keys.just_pressed(*key)),
+        InputBinding::Mouse(button) => (mouse_buttons.pressed(*button), mouse_buttons.just_pressed(*button)),
+        InputBinding::Gamepad(button) => (
+            gamepads.iter().any(|gamepad| gamepad.pressed(*button)),
+            gamepads.iter().any(|gamepad| gamepad.just_pressed(*button)),
+        ),
+    };
+    *state = resolve_actions(&input_map, binding_state, gamepads.iter());
+}
+
+// Action state of a single gamepad, through its bindings in the input map
+pub fn gamepad_actions(input_map: &InputMap, gamepad: &Gamepad) -> ActionState {
+    let binding_state = |binding: &InputBinding| match binding {
+        InputBinding::Gamepad(button) => (gamepad.pressed(*button), gamepad.just_pressed(*button)),
+        _ => (false, false),
+    };
+    resolve_actions(input_map, binding_state, std::iter::once(gamepad))
+}
+
+// Capture the next key or mouse press for a pending rebind; Escape cancels
+pub fn capture_rebind(
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut request: ResMut<RebindRequest>,
+    mut input_map: ResMut<InputMap>,
+) {
+    let Some(action) = request.action else {
+        return;
+    };
+
+    // Ignore the click that opened the request
+    if !request.ready {
+        if mouse_buttons.get_pressed().next().is_none() {
+            request.ready = true;
+        }
+        return;
+    }
+
+    // Consume the key so it doesn't also close the menu or trigger gameplay this frame
+    if keys.clear_just_pressed(KeyCode::Escape) {
+        request.action = None;
+        return;
+    }
+
+    let binding = keys
+        .get_just_pressed()
+        .next()
+        .map(|key| InputBinding::Key(*key))
+        .or_else(|| mouse_buttons.get_just_pressed().next().map(|button| InputBinding::Mouse(*button)));
+
+    if let Some(binding) = binding {
+        if let InputBinding::Key(key) = binding {
+            keys.clear_just_pressed(key);
+        }
+        input_map.rebind(action, binding);
+        request.action = None;
+    }
+}
+
+// Plugin for the action mapping layer
+pub struct InputMappingPlugin;
+
+impl Plugin for InputMappingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .init_resource::<RebindRequest>()
+            // Resolve actions right after Bevy updates the raw input so every Update system sees them
+            .add_systems(PreUpdate, (capture_rebind, update_action_state).chain().after(InputSystem));
+    }
+}
@@ -0,0 +1,20 @@
+use bevy::prelude::*;
+use bevy::input::common_conditions::input_toggle_active;
+use bevy_inspector_egui::quick::{ResourceInspectorPlugin, WorldInspectorPlugin};
+use crate::tuning::LiveTuning;
+
+// Key that shows and hides the inspector windows
+const INSPECTOR_KEY: KeyCode = KeyCode::F10;
+
+// Plugin for the egui inspectors used while balancing: one window lists every entity and resource with
+// their reflected fields, the other edits the live tuning. Only built with the inspector feature.
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            WorldInspectorPlugin::new().run_if(input_toggle_active(false, INSPECTOR_KEY)),
+            ResourceInspectorPlugin::<LiveTuning>::new().run_if(input_toggle_active(false, INSPECTOR_KEY)),
+        ));
+    }
+}
@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+use crate::camera::{CameraSettings, FollowCamera};
+use crate::player::Player;
+use crate::terrain::get_terrain_height;
+use crate::GameState;
+
+// Intro constants
+const INTRO_DURATION: f32 = 7.0; // Length of the fly-over in seconds
+const INTRO_ORBIT_RADIUS: f32 = 35.0; // Radius of the orbit around the spawn area
+const INTRO_ORBIT_HEIGHT: f32 = 18.0; // Height of the orbit above the spawn point
+const INTRO_TERRAIN_CLEARANCE: f32 = 2.0; // Minimum height of the camera above the terrain
+
+// Scripted camera fly-over that plays before the follow camera takes control
+#[derive(Resource)]
+pub struct CinematicIntro {
+    // Seconds since the intro started
+    pub elapsed: f32,
+    // Whether the fly-over is still playing
+    pub active: bool,
+    // Point the intro orbits around, captured when it starts
+    pub center: Option<Vec3>,
+}
+
+impl Default for CinematicIntro {
+    fn default() -> Self {
+        Self {
+            elapsed: 0.0,
+            active: false,
+            center: None,
+        }
+    }
+}
+
+// Restart the fly-over whenever a new game finishes loading
+pub fn start_intro(mut intro: ResMut<CinematicIntro>) {
+    *intro = CinematicIntro {
+        active: true,
+        ..default()
+    };
+}
+
+// Run condition for systems that should wait until the intro is over
+pub fn intro_finished(intro: Res<CinematicIntro>) -> bool {
+    !intro.active
+}
+
+// Evaluate a Catmull-Rom spline through the points at t in 0..1
+fn catmull_rom(points: &[Vec3], t: f32) -> Vec3 {
+    let segments = points.len() - 1;
+    let scaled = t.clamp(0.0, 1.0) * segments as f32;
+    let segment = (scaled.floor() as usize).min(segments - 1);
+    let local_t = scaled - segment as f32;
+
+    // Duplicate the end points so the curve passes through them
+    let p0 = points[segment.saturating_sub(1)];
+    let p1 = points[segment];
+    let p2 = points[segment + 1];
+    let p3 = points[(segment + 2).min(segments)];
+
+    let t2 = local_t * local_t;
+    let t3 = t2 * local_t;
+    0.5 * ((2.0 * p1)
+        + (p2 - p0) * local_t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+// Fly the camera around the spawn area and sweep down behind the player
+pub fn run_intro_flyover(
+    mut intro: ResMut<CinematicIntro>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    player_query: Query<&Transform, With<Player>>,
+    mut camera_query: Query<&mut Transform, (With<FollowCamera>, Without<Player>)>,
+    camera_settings: Res<CameraSettings>,
+    time: Res<Time>,
+) {
+    if !intro.active {
+        return;
+    }
+    let (Ok(player_transform), Ok(mut camera_transform)) = (
+        player_query.get_single(),
+        camera_query.get_single_mut()
+    ) else {
+        return;
+    };
+    let player_pos = player_transform.translation;
+    let center = *intro.center.get_or_insert(player_pos);
+
+    // Any input skips straight to gameplay
+    let skipped = keys.get_just_pressed().next().is_some()
+        || mouse_buttons.get_just_pressed().next().is_some();
+
+    intro.elapsed += time.delta_secs();
+    let t = (intro.elapsed / INTRO_DURATION).min(1.0);
+
+    // Final pose matches where the follow camera wants to be so the handoff is seamless
+    let follow_position = player_pos + camera_settings.orbit_direction() * camera_settings.follow_distance;
+    let follow_look = player_pos + Vec3::new(0.0, 0.5, 0.0);
+
+    if skipped || t >= 1.0 {
+        camera_transform.translation = follow_position;
+        camera_transform.look_at(follow_look, Vec3::Y);
+        intro.active = false;
+        return;
+    }
+
+    // Three-quarter orbit around the spawn point that ends behind the player
+    let yaw_end = camera_settings.yaw;
+    let mut points = Vec::new();
+    for i in 0..4 {
+        let angle = yaw_end + std::f32::consts::PI * 1.5 * (1.0 - i as f32 / 3.0);
+        let height = INTRO_ORBIT_HEIGHT * (1.0 - i as f32 * 0.15);
+        points.push(center + Vec3::new(angle.sin() * INTRO_ORBIT_RADIUS, height, angle.cos() * INTRO_ORBIT_RADIUS));
+    }
+    points.push(follow_position);
+
+    // Ease in and out of the move
+    let eased = t * t * (3.0 - 2.0 * t);
+    let mut position = catmull_rom(&points, eased);
+    let min_height = get_terrain_height(position.x, position.z) + INTRO_TERRAIN_CLEARANCE;
+    position.y = position.y.max(min_height);
+
+    // Look at the spawn area, shifting onto the player for the final sweep
+    let look_target = center.lerp(follow_look, eased);
+    camera_transform.translation = position;
+    camera_transform.look_at(look_target, Vec3::Y);
+}
+
+// Plugin for the intro fly-over
+pub struct IntroPlugin;
+
+impl Plugin for IntroPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<CinematicIntro>()
+            // Resuming from pause also enters Playing, so only start once loading finishes
+            .add_systems(
+                OnTransition { exited: GameState::Loading, entered: GameState::Playing },
+                start_intro,
+            )
+            .add_systems(Update, run_intro_flyover.run_if(in_state(GameState::Playing)));
+    }
+}
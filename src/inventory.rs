@@ -0,0 +1,326 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use crate::golf::Wind;
+use crate::photo_mode::PhotoModeState;
+use crate::player::{Health, Player};
+use crate::progression::Experience;
+use crate::projectile::{Ammo, AmmoKind, SelectedAmmo};
+use crate::replay::{BUTTON_REPAIR_KIT, BUTTON_WIND_CHARM};
+use crate::save::SaveData;
+use crate::sim::{SimInput, SimSet};
+use crate::GameState;
+
+// Inventory constants
+const HOTBAR_SLOTS: usize = 5;
+const HOTBAR_KEYS: [KeyCode; HOTBAR_SLOTS] = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4, KeyCode::Digit5];
+const REPAIR_AMOUNT: f32 = 50.0; // Health a repair kit restores
+const WIND_CHARM_DURATION: f32 = 20.0; // Seconds a wind charm keeps the wind off the ball
+const SLOT_SIZE: f32 = 64.0; // Width and height of a hotbar slot in pixels
+const SLOT_COLOR: Color = Color::srgba(0.05, 0.05, 0.1, 0.75);
+const SELECTED_BORDER: Color = Color::srgb(1.0, 0.85, 0.2);
+const EMPTY_TEXT: Color = Color::srgb(0.45, 0.45, 0.45);
+
+// Items used up from the hotbar, bought at shops and kept in the save's inventory
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Consumable {
+    RepairKit,
+    WindCharm,
+}
+
+impl Consumable {
+    pub const ALL: [Consumable; 2] = [Consumable::RepairKit, Consumable::WindCharm];
+
+    // Stable id stored in the save file
+    pub fn id(self) -> &'static str {
+        match self {
+            Consumable::RepairKit => "repair_kit",
+            Consumable::WindCharm => "wind_charm",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Consumable::RepairKit => "Repair Kit",
+            Consumable::WindCharm => "Wind Charm",
+        }
+    }
+
+    // Simulation input bit pressed to use one
+    pub fn button(self) -> u8 {
+        match self {
+            Consumable::RepairKit => BUTTON_REPAIR_KIT,
+            Consumable::WindCharm => BUTTON_WIND_CHARM,
+        }
+    }
+}
+
+// Consumables carried in the run, taken from the save's inventory when it starts and written back to it
+// as they are used, so the simulation never touches the save itself
+#[derive(Resource, Default)]
+pub struct Consumables {
+    pub stock: HashMap<Consumable, u32>,
+}
+
+impl Consumables {
+    pub fn count(&self, kind: Consumable) -> u32 {
+        self.stock.get(&kind).copied().unwrap_or(0)
+    }
+}
+
+// What a hotbar slot holds: a kind of boulder to switch to, or a consumable to use
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HotbarItem {
+    Ammo(AmmoKind),
+    Consumable(Consumable),
+}
+
+impl HotbarItem {
+    fn label(self) -> &'static str {
+        match self {
+            HotbarItem::Ammo(kind) => kind.label(),
+            HotbarItem::Consumable(kind) => kind.label(),
+        }
+    }
+
+    // How many the player has; stone boulders are the reloading supply
+    fn count(self, consumables: &Consumables, ammo: &Ammo) -> u32 {
+        match self {
+            HotbarItem::Ammo(kind) => ammo.count(kind),
+            HotbarItem::Consumable(kind) => consumables.count(kind),
+        }
+    }
+}
+
+// The items on the hotbar, one per number key from 1
+#[derive(Resource)]
+pub struct Hotbar {
+    pub slots: [HotbarItem; HOTBAR_SLOTS],
+}
+
+impl Default for Hotbar {
+    fn default() -> Self {
+        Self {
+            slots: [
+                HotbarItem::Ammo(AmmoKind::Stone),
+                HotbarItem::Ammo(AmmoKind::Heavy),
+                HotbarItem::Ammo(AmmoKind::Volley),
+                HotbarItem::Consumable(Consumable::RepairKit),
+                HotbarItem::Consumable(Consumable::WindCharm),
+            ],
+        }
+    }
+}
+
+// Marker for the hotbar row
+#[derive(Component)]
+pub struct HotbarRoot;
+
+// One slot of the hotbar, by index
+#[derive(Component)]
+pub struct HotbarSlot(pub usize);
+
+// Text of one slot, by index
+#[derive(Component)]
+pub struct HotbarSlotText(pub usize);
+
+// Build the hotbar along the bottom middle of the screen, hidden until a game starts
+pub fn spawn_hotbar(mut commands: Commands) {
+    commands
+        .spawn((
+            HotbarRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(12.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(4.0),
+                ..default()
+            },
+            PickingBehavior::IGNORE,
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            for index in 0..HOTBAR_SLOTS {
+                parent
+                    .spawn((
+                        HotbarSlot(index),
+                        Node {
+                            width: Val::Px(SLOT_SIZE),
+                            height: Val::Px(SLOT_SIZE),
+                            border: UiRect::all(Val::Px(2.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(SLOT_COLOR),
+                        BorderColor(Color::NONE),
+                        BorderRadius::all(Val::Px(4.0)),
+                    ))
+                    .with_children(|slot| {
+                        slot.spawn((
+                            HotbarSlotText(index),
+                            Text::new(""),
+                            TextFont {
+                                font_size: 11.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                            TextLayout::new_with_justify(JustifyText::Center),
+                        ));
+                    });
+            }
+        });
+}
+
+// Show the hotbar when gameplay starts
+pub fn show_hotbar(mut query: Query<&mut Visibility, With<HotbarRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+// Hide the hotbar outside of gameplay
+pub fn hide_hotbar(mut query: Query<&mut Visibility, With<HotbarRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Use a consumable if it would do anything; returns whether it was used
+fn use_consumable(kind: Consumable, health: Option<Mut<Health>>, wind: &mut Wind) -> bool {
+    match kind {
+        Consumable::RepairKit => {
+            let Some(mut health) = health else {
+                return false;
+            };
+            if health.current >= health.max {
+                return false;
+            }
+            health.current = (health.current + REPAIR_AMOUNT).min(health.max);
+            true
+        }
+        Consumable::WindCharm => {
+            // Only golf has wind to calm
+            if wind.velocity == Vec2::ZERO {
+                return false;
+            }
+            wind.calm = WIND_CHARM_DURATION;
+            true
+        }
+    }
+}
+
+// Number keys pick a slot: boulder slots switch the next throw's kind, consumable slots press the
+// consumable's button for the next tick to use one
+pub fn use_hotbar(
+    keys: Res<ButtonInput<KeyCode>>,
+    hotbar: Res<Hotbar>,
+    experience: Res<Experience>,
+    ammo: Res<Ammo>,
+    consumables: Res<Consumables>,
+    mut selected: ResMut<SelectedAmmo>,
+    mut input: ResMut<SimInput>,
+) {
+    // The same keys pick an upgrade while one is on offer, and with Alt they belong to the debug overlay
+    if experience.pending_choices > 0 || keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]) {
+        return;
+    }
+    let Some(index) = HOTBAR_KEYS.iter().position(|key| keys.just_pressed(*key)) else {
+        return;
+    };
+    let item = hotbar.slots[index];
+    if item.count(&consumables, &ammo) == 0 && item != HotbarItem::Ammo(AmmoKind::Stone) {
+        return;
+    }
+    match item {
+        HotbarItem::Ammo(kind) => selected.0 = kind,
+        HotbarItem::Consumable(kind) => input.pressed |= kind.button(),
+    }
+}
+
+// Use the consumables pressed this tick, if there are any left and they would do anything
+pub fn use_consumables(
+    input: Res<SimInput>,
+    mut consumables: ResMut<Consumables>,
+    mut wind: ResMut<Wind>,
+    mut player_query: Query<&mut Health, With<Player>>,
+) {
+    for kind in Consumable::ALL {
+        if !input.just_pressed(kind.button()) || consumables.count(kind) == 0 {
+            continue;
+        }
+        if use_consumable(kind, player_query.get_single_mut().ok(), &mut wind) {
+            if let Some(count) = consumables.stock.get_mut(&kind) {
+                *count -= 1;
+            }
+        }
+    }
+}
+
+// Bring the consumables from the save into a new run
+pub fn stock_consumables(save: Res<SaveData>, mut consumables: ResMut<Consumables>) {
+    consumables.stock = Consumable::ALL.into_iter().map(|kind| (kind, save.consumable_count(kind))).collect();
+}
+
+// Write the consumables left back to the save, touching it only when a count has changed
+pub fn save_consumables(consumables: Res<Consumables>, mut save: ResMut<SaveData>) {
+    for (kind, count) in consumables.stock.iter() {
+        if save.consumable_count(*kind) != *count {
+            save.set_consumable(*kind, *count);
+        }
+    }
+}
+
+// Show each slot's key, item and count, outlining the boulder kind that is selected
+pub fn update_hotbar(
+    hotbar: Res<Hotbar>,
+    consumables: Res<Consumables>,
+    ammo: Res<Ammo>,
+    selected: Res<SelectedAmmo>,
+    mut slot_query: Query<(&HotbarSlot, &mut BorderColor)>,
+    mut text_query: Query<(&HotbarSlotText, &mut Text, &mut TextColor)>,
+) {
+    for (slot, mut border) in slot_query.iter_mut() {
+        let wanted = if hotbar.slots[slot.0] == HotbarItem::Ammo(selected.0) { SELECTED_BORDER } else { Color::NONE };
+        if border.0 != wanted {
+            border.0 = wanted;
+        }
+    }
+    for (slot, mut text, mut color) in text_query.iter_mut() {
+        let item = hotbar.slots[slot.0];
+        let count = item.count(&consumables, &ammo);
+        text.0 = format!("{}\n{}\n{}", slot.0 + 1, item.label(), count);
+        color.0 = if count > 0 { Color::WHITE } else { EMPTY_TEXT };
+    }
+}
+
+// Plugin for the hotbar and the consumables used from it
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Hotbar>()
+            .init_resource::<Consumables>()
+            .add_systems(Startup, spawn_hotbar)
+            .add_systems(OnExit(GameState::Loading), stock_consumables)
+            .add_systems(OnExit(GameState::GameOver), stock_consumables)
+            .add_systems(OnEnter(GameState::Playing), show_hotbar)
+            .add_systems(OnExit(GameState::Playing), hide_hotbar)
+            .add_systems(Update, use_hotbar
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)))
+            .add_systems(FixedUpdate, use_consumables
+                .in_set(SimSet::Environment)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)))
+            .add_systems(Update, save_consumables
+                .run_if(in_state(GameState::Playing))
+                .run_if(resource_changed::<Consumables>))
+            .add_systems(Update, update_hotbar
+                .after(use_hotbar)
+                .run_if(resource_changed::<Ammo>
+                    .or(resource_changed::<SelectedAmmo>)
+                    .or(resource_changed::<Consumables>)));
+    }
+}
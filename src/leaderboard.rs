@@ -0,0 +1,201 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::profile::ActiveProfile;
+use crate::replay::{save_replay, ReplayPlayback, ReplayRecorder};
+use crate::results::{spawn_results_screen, RunStats};
+use crate::score::Score;
+use crate::terrain::world_seed;
+use crate::{GameMode, GameState};
+
+// Leaderboard file, shared by every profile on this machine
+const LEADERBOARD_PATH: &str = "save/leaderboards.ron";
+const LEADERBOARD_VERSION: u32 = 1;
+pub const LEADERBOARD_SIZE: usize = 10; // Entries kept per board
+
+// One finished run on a board
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub profile: String,
+    pub score: u32,
+    // Finishing time, for modes ranked by time
+    pub time: Option<f32>,
+    // Replay file of the run, if one was recorded
+    pub replay: Option<String>,
+}
+
+impl LeaderboardEntry {
+    // Score or time as shown on a board
+    pub fn result(&self) -> String {
+        match self.time {
+            Some(time) => format!("{:.2} s", time),
+            None => format!("{} pts", self.score),
+        }
+    }
+}
+
+// The best runs in one mode in one world
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub mode: GameMode,
+    pub seed: u32,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+// Scripted runs have no board: how they score depends on which script ran, which a board doesn't say
+pub fn has_board(mode: GameMode) -> bool {
+    mode != GameMode::Scripted
+}
+
+// Races are ranked by time, fastest first; everything else by score
+pub fn ranked_by_time(mode: GameMode) -> bool {
+    matches!(mode, GameMode::TimeTrial | GameMode::DownhillRace)
+}
+
+// Every board, persisted across sessions
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Leaderboards {
+    pub version: u32,
+    pub boards: Vec<Leaderboard>,
+    // Board and rank of the run that just ended, for highlighting on the results screen
+    #[serde(skip)]
+    pub last_placed: Option<(GameMode, u32, usize)>,
+}
+
+impl Default for Leaderboards {
+    fn default() -> Self {
+        Self {
+            version: LEADERBOARD_VERSION,
+            boards: Vec::new(),
+            last_placed: None,
+        }
+    }
+}
+
+impl Leaderboards {
+    // Read the leaderboard file, starting empty if it is missing, invalid or from another version
+    pub fn load() -> Self {
+        match std::fs::read_to_string(LEADERBOARD_PATH) {
+            Ok(contents) => match ron::from_str::<Self>(&contents) {
+                Ok(boards) if boards.version == LEADERBOARD_VERSION => boards,
+                Ok(boards) => {
+                    println!(
+                        "Leaderboard file {} is version {}, this build reads version {}",
+                        LEADERBOARD_PATH, boards.version, LEADERBOARD_VERSION
+                    );
+                    Self::default()
+                }
+                Err(error) => {
+                    println!("Invalid leaderboard file {}: {}", LEADERBOARD_PATH, error);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    // Write the leaderboard file, creating its directory if needed
+    pub fn write(&self) {
+        let result = std::path::Path::new(LEADERBOARD_PATH)
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| {
+                let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+                    .map_err(std::io::Error::other)?;
+                std::fs::write(LEADERBOARD_PATH, contents)
+            });
+        if let Err(error) = result {
+            println!("Failed to write leaderboard file {}: {}", LEADERBOARD_PATH, error);
+        }
+    }
+
+    // Entries for a mode and seed, best first
+    pub fn entries(&self, mode: GameMode, seed: u32) -> &[LeaderboardEntry] {
+        self.boards
+            .iter()
+            .find(|board| board.mode == mode && board.seed == seed)
+            .map(|board| board.entries.as_slice())
+            .unwrap_or(&[])
+    }
+
+    // Put a run on its board; returns its rank from 0, or None if it didn't make the cut
+    pub fn submit(&mut self, mode: GameMode, seed: u32, entry: LeaderboardEntry) -> Option<usize> {
+        let index = match self.boards.iter().position(|board| board.mode == mode && board.seed == seed) {
+            Some(index) => index,
+            None => {
+                self.boards.push(Leaderboard { mode, seed, entries: Vec::new() });
+                self.boards.len() - 1
+            }
+        };
+        let entries = &mut self.boards[index].entries;
+        // Ties go to the run that got there first
+        let rank = if ranked_by_time(mode) {
+            let time = entry.time.unwrap_or(f32::MAX);
+            entries.iter().take_while(|other| other.time.unwrap_or(f32::MAX) <= time).count()
+        } else {
+            entries.iter().take_while(|other| other.score >= entry.score).count()
+        };
+        if rank >= LEADERBOARD_SIZE {
+            return None;
+        }
+        entries.insert(rank, entry);
+        entries.truncate(LEADERBOARD_SIZE);
+        Some(rank)
+    }
+
+    // Board lines for a mode and seed, marking one rank
+    pub fn lines(&self, mode: GameMode, seed: u32, highlight: Option<usize>) -> Vec<String> {
+        let entries = self.entries(mode, seed);
+        if entries.is_empty() {
+            return vec!["No runs yet".to_string()];
+        }
+        entries
+            .iter()
+            .enumerate()
+            .map(|(rank, entry)| {
+                let replay = if entry.replay.is_some() { " [replay]" } else { "" };
+                let marker = if highlight == Some(rank) { " <" } else { "" };
+                format!("{:>2}. {:<16} {:>10}{}{}", rank + 1, entry.profile, entry.result(), replay, marker)
+            })
+            .collect()
+    }
+}
+
+// Put the run that just ended on its board, with its replay file; races only count once finished, and a
+// played back replay, a scripted run or a run that bent time doesn't count
+pub fn record_run(
+    mut leaderboards: ResMut<Leaderboards>,
+    mode: Res<GameMode>,
+    profile: Res<ActiveProfile>,
+    score: Res<Score>,
+    run_stats: Res<RunStats>,
+    recorder: Res<ReplayRecorder>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    leaderboards.last_placed = None;
+    if playback.is_some() || !has_board(*mode) || run_stats.time_assisted || (ranked_by_time(*mode) && run_stats.race_time.is_none()) {
+        return;
+    }
+    let seed = world_seed();
+    let entry = LeaderboardEntry {
+        profile: profile.name.clone(),
+        score: score.points,
+        time: run_stats.race_time,
+        replay: recorder.saved.as_ref().map(|path| path.display().to_string()),
+    };
+    if let Some(rank) = leaderboards.submit(*mode, seed, entry) {
+        leaderboards.last_placed = Some((*mode, seed, rank));
+        leaderboards.write();
+    }
+}
+
+// Plugin for local leaderboards
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Leaderboards::load())
+            .add_systems(OnEnter(GameState::GameOver), record_run.after(save_replay).before(spawn_results_screen));
+    }
+}
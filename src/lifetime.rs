@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+
+// Something that removes itself, children included, once it has been around for its duration; effects
+// read how far along it is to shrink or fade
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Lifetime {
+    pub age: f32,
+    pub duration: f32,
+}
+
+impl Lifetime {
+    pub fn new(duration: f32) -> Self {
+        Self { age: 0.0, duration }
+    }
+
+    // How far through its life, from 0 to 1
+    pub fn fraction(&self) -> f32 {
+        if self.duration > 0.0 { (self.age / self.duration).min(1.0) } else { 1.0 }
+    }
+
+    // Seconds left before it goes
+    pub fn remaining(&self) -> f32 {
+        (self.duration - self.age).max(0.0)
+    }
+
+    pub fn expired(&self) -> bool {
+        self.age >= self.duration
+    }
+}
+
+// Age everything with a lifetime and remove what has run out. The removals go out as one command
+// rather than one per entity, since bursts of debris tend to expire together.
+pub fn expire_lifetimes(mut commands: Commands, mut query: Query<(Entity, &mut Lifetime)>, time: Res<Time>) {
+    let delta = time.delta_secs();
+    let mut expired = Vec::new();
+    for (entity, mut lifetime) in query.iter_mut() {
+        lifetime.age += delta;
+        if lifetime.expired() {
+            expired.push(entity);
+        }
+    }
+    if expired.is_empty() {
+        return;
+    }
+    commands.queue(move |world: &mut World| {
+        for entity in expired {
+            // Something else may have removed it first, like the end of a run
+            if let Ok(entity) = world.get_entity_mut(entity) {
+                entity.despawn_recursive();
+            }
+        }
+    });
+}
+
+// Plugin for entities that remove themselves after a while
+pub struct LifetimePlugin;
+
+impl Plugin for LifetimePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, expire_lifetimes);
+    }
+}
@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+use crate::camera::FollowCamera;
+use crate::player::Player;
+use crate::terrain::{chunk_coords, ChunkManager};
+use crate::GameState;
+
+// Loading screen layout constants
+const PROGRESS_BAR_WIDTH: f32 = 320.0;
+const PROGRESS_BAR_HEIGHT: f32 = 14.0;
+const SPAWN_AREA_RADIUS: i32 = 1; // Chunks around the player that must exist before play (3x3)
+
+// Marker for the loading screen UI root
+#[derive(Component)]
+pub struct LoadingRoot;
+
+// Marker for the progress bar fill
+#[derive(Component)]
+pub struct LoadingProgressBar;
+
+// Marker for the progress text
+#[derive(Component)]
+pub struct LoadingProgressText;
+
+// Build the loading screen: a title, progress bar and status line
+pub fn spawn_loading_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            LoadingRoot,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.05, 0.07, 0.05)),
+            GlobalZIndex(10),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Generating terrain"),
+                TextFont {
+                    font_size: 32.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(PROGRESS_BAR_WIDTH),
+                        height: Val::Px(PROGRESS_BAR_HEIGHT),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.8)),
+                ))
+                .with_children(|bar| {
+                    bar.spawn((
+                        LoadingProgressBar,
+                        Node {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.3, 0.7, 0.35)),
+                    ));
+                });
+            parent.spawn((
+                LoadingProgressText,
+                Text::new(""),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            ));
+        });
+}
+
+// Remove the loading screen
+pub fn despawn_loading_screen(mut commands: Commands, query: Query<Entity, With<LoadingRoot>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Track the spawn area's chunks and start playing once they and the player/camera exist
+pub fn update_loading(
+    chunk_manager: Res<ChunkManager>,
+    player_query: Query<&Transform, With<Player>>,
+    camera_query: Query<(), With<FollowCamera>>,
+    mut bar_query: Query<&mut Node, With<LoadingProgressBar>>,
+    mut text_query: Query<&mut Text, With<LoadingProgressText>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let (center_x, center_z) = chunk_coords(player_transform.translation);
+
+    let side = SPAWN_AREA_RADIUS * 2 + 1;
+    let required = (side * side) as usize;
+    let mut loaded = 0;
+    for z in (center_z - SPAWN_AREA_RADIUS)..=(center_z + SPAWN_AREA_RADIUS) {
+        for x in (center_x - SPAWN_AREA_RADIUS)..=(center_x + SPAWN_AREA_RADIUS) {
+            if chunk_manager.loaded_chunks.contains_key(&(x, z)) {
+                loaded += 1;
+            }
+        }
+    }
+
+    let fraction = loaded as f32 / required as f32;
+    for mut node in bar_query.iter_mut() {
+        node.width = Val::Percent(fraction * 100.0);
+    }
+    for mut text in text_query.iter_mut() {
+        text.0 = format!("{}/{} chunks ({} queued)", loaded, required, chunk_manager.pending_count());
+    }
+
+    if loaded == required && !camera_query.is_empty() {
+        next_state.set(GameState::Playing);
+    }
+}
+
+// Plugin for the loading screen shown before play
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(GameState::Loading), spawn_loading_screen)
+            .add_systems(OnExit(GameState::Loading), despawn_loading_screen)
+            .add_systems(Update, update_loading.run_if(in_state(GameState::Loading)));
+    }
+}
@@ -1,4 +1,14 @@
 use bevy::prelude::*;
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::ecs::schedule::ScheduleLabel;
+#[cfg(target_arch = "wasm32")]
+use bevy::asset::AssetMetaCheck;
+use bevy::render::RenderPlugin;
+use bevy::render::settings::WgpuSettings;
+use bevy::window::ExitCondition;
+use bevy::winit::WinitPlugin;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
 
 // Import our modules
 mod player;
@@ -6,20 +16,350 @@ mod camera;
 mod terrain;
 mod assets;
 mod projectile;
+mod photo_mode;
+mod minimap;
+mod intro;
+mod menu;
+mod hud;
+mod input;
+mod debug;
+mod target;
+mod score;
+mod results;
+mod loading;
+mod compass;
+mod waypoint;
+mod throw_widget;
+mod save;
+mod hints;
+mod accessibility;
+mod game_events;
+mod regions;
+mod event_log;
+mod enemy;
+mod catapult;
+mod collectibles;
+mod race;
+mod golf;
+mod props;
+mod drops;
+mod capture;
+mod nav;
+mod race_ai;
+mod hazards;
+mod pads;
+mod platforms;
+mod teleport;
+mod objectives;
+mod progression;
+mod boss;
+mod difficulty;
+mod blocks;
+mod attract;
+mod savegame;
+mod settings;
+mod screenshot;
+mod cli;
+mod tuning;
+mod profile;
+mod stats;
+mod leaderboard;
+mod replay;
+mod audio;
+mod network;
+mod sim;
+mod spectator;
+mod chat;
+mod party;
+mod bench;
+mod spatial;
+mod budget;
+mod horizon;
+mod preload;
+mod lifetime;
+mod recorder;
+mod touch;
+mod sky;
+mod terrain_material;
+mod scripting;
+mod mods;
+mod particles;
+mod time_control;
+mod graphics;
+mod wildlife;
+mod clouds;
+mod world_map;
+mod achievements;
+mod shop;
+mod inventory;
+mod shield;
+mod multiball;
+mod tether;
+mod weather;
+// Windowless simulation app the test suite drives tick by tick
+#[cfg(test)]
+mod headless;
+#[cfg(feature = "inspector")]
+mod inspector;
 
 // Import specific items we need
 use player::{PlayerPlugin, spawn_player};
 use camera::{CameraPlugin, spawn_camera};
-use terrain::TerrainPlugin;
+use terrain::{TerrainConfig, TerrainPlugin};
 use projectile::ProjectilePlugin;
+use photo_mode::PhotoModePlugin;
+use minimap::MinimapPlugin;
+use intro::IntroPlugin;
+use menu::MenuPlugin;
+use hud::HudPlugin;
+use input::InputMappingPlugin;
+use debug::{DebugOverlay, DebugPlugin};
+use target::TargetPlugin;
+use score::ScorePlugin;
+use results::ResultsPlugin;
+use loading::LoadingPlugin;
+use compass::CompassPlugin;
+use waypoint::WaypointPlugin;
+use throw_widget::ThrowWidgetPlugin;
+use save::SavePlugin;
+use hints::HintPlugin;
+use accessibility::AccessibilityPlugin;
+use game_events::GameEventPlugin;
+use regions::RegionPlugin;
+use event_log::EventLogPlugin;
+use enemy::EnemyPlugin;
+use catapult::CatapultPlugin;
+use collectibles::CollectiblePlugin;
+use race::RacePlugin;
+use golf::GolfPlugin;
+use props::PropPlugin;
+use drops::DropPlugin;
+use capture::CapturePlugin;
+use race_ai::RaceAiPlugin;
+use hazards::HazardPlugin;
+use pads::PadPlugin;
+use platforms::PlatformPlugin;
+use teleport::TeleportPlugin;
+use objectives::ObjectivePlugin;
+use progression::ProgressionPlugin;
+use boss::BossPlugin;
+use difficulty::DifficultyPlugin;
+use blocks::BlockPlugin;
+use attract::AttractPlugin;
+use savegame::SaveGamePlugin;
+use settings::{DisplayMode, Settings, SettingsPlugin};
+use screenshot::ScreenshotPlugin;
+use cli::CliArgs;
+use tuning::TuningPlugin;
+use profile::{ActiveProfile, ProfilePlugin};
+use stats::StatsPlugin;
+use leaderboard::LeaderboardPlugin;
+use audio::AudioPlugin;
+use network::NetworkPlugin;
+use sim::SimPlugin;
+use spectator::SpectatorPlugin;
+use chat::ChatPlugin;
+use party::PartyPlugin;
+use bench::run_bench;
+use spatial::SpatialPlugin;
+use budget::BudgetPlugin;
+use horizon::HorizonPlugin;
+use preload::{PreloadNext, PreloadPlugin, SphereTextures};
+use lifetime::LifetimePlugin;
+use recorder::{EventRecorder, RecorderPlugin};
+use touch::TouchPlugin;
+use sky::SkyPlugin;
+use assets::models::ModelPlugin;
+use terrain_material::TerrainMaterialPlugin;
+use scripting::ScriptingPlugin;
+use mods::{register_mod_assets, ModPacks};
+use particles::ParticlePlugin;
+use time_control::TimeControlPlugin;
+use graphics::GraphicsPlugin;
+use wildlife::WildlifePlugin;
+use clouds::CloudPlugin;
+use world_map::WorldMapPlugin;
+use achievements::AchievementPlugin;
+use shop::ShopPlugin;
+use inventory::InventoryPlugin;
+use shield::ShieldPlugin;
+use multiball::MultiballPlugin;
+use tether::TetherPlugin;
+use weather::WeatherPlugin;
+use replay::{Replay, ReplayPlayback, ReplayPlugin};
+#[cfg(feature = "inspector")]
+use inspector::InspectorPlugin;
+use assets::palette::ColorPalette;
+
+// Top-level game flow; gameplay systems only run while Playing
+#[derive(States, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum GameState {
+    // Generating textures, loading sounds and warming up GPU pipelines before the first screen
+    #[default]
+    Preload,
+    MainMenu,
+    // Waiting for the spawn area terrain before play starts
+    Loading,
+    Playing,
+    Paused,
+    GameOver,
+}
+
+// Rules for the current run, picked from the main menu
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    // Roam freely with a few target posts around
+    #[default]
+    FreePlay,
+    // Targets of several kinds scattered around, each hit pushing the next one farther out
+    TargetPractice,
+    // Roll through a course of checkpoint gates against the clock
+    TimeTrial,
+    // Roll the ball into cups carved in the terrain within a stroke and time budget
+    Golf,
+    // Hold capture zones on hilltops while enemies contest them
+    KingOfTheHill,
+    // Race AI opponents down a course of gates from a countdown start
+    DownhillRace,
+    // Fight a boss with escalating attack phases inside an arena
+    BossFight,
+    // Rules from a script in the mods folder, picked on the main menu's Mods page
+    Scripted,
+}
+
+impl GameMode {
+    // Every mode, in main menu order
+    pub const ALL: [GameMode; 8] = [
+        GameMode::FreePlay,
+        GameMode::TargetPractice,
+        GameMode::TimeTrial,
+        GameMode::Golf,
+        GameMode::KingOfTheHill,
+        GameMode::DownhillRace,
+        GameMode::BossFight,
+        GameMode::Scripted,
+    ];
+
+    // The mode after this one, wrapping around
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|mode| *mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    // Whether a save slot can hold a run in this mode; the others keep course, hole, zone, boss or
+    // script state that a snapshot doesn't capture
+    pub fn can_save(self) -> bool {
+        matches!(self, GameMode::FreePlay | GameMode::TargetPractice)
+    }
+}
+
+// Schedule run once as a run starts, either after loading or by retrying from the results screen;
+// coming back from the pause menu enters Playing too but carries the run on
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RunStart;
+
+// Run the RunStart schedule on the way into a new run
+fn start_run(world: &mut World) {
+    let _ = world.try_run_schedule(RunStart);
+}
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins)
+    let args = CliArgs::parse();
+    if args.bench {
+        run_bench(args.bench_config, args.seed);
+        return;
+    }
+    // The profile and its settings are read before the app is built so the window opens as it was left
+    let profile = ActiveProfile::last_used();
+    let mut settings = Settings::load(&profile);
+    if args.fullscreen {
+        settings.display_mode = DisplayMode::Borderless;
+    }
+    if let Some((width, height)) = args.resolution {
+        settings.resolution = (width as u32, height as u32);
+    }
+    let window = settings.window();
+
+    // Mod packs are found first so their assets can be layered over the game's before the asset plugin builds
+    let mod_packs = ModPacks::discover();
+
+    let mut app = App::new();
+    register_mod_assets(&mut app, &mod_packs);
+    if args.headless {
+        // No window or GPU; the schedule runner stands in for the window's event loop
+        app.add_plugins(DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            })
+            .set(RenderPlugin {
+                render_creation: WgpuSettings { backends: None, ..default() }.into(),
+                ..default()
+            })
+            .disable::<WinitPlugin>())
+            .add_plugins(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(1.0 / 60.0)));
+    } else {
+        let plugins = DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(window),
+            ..default()
+        });
+        // Over HTTP, looking for a .meta file beside every asset only adds failed requests
+        #[cfg(target_arch = "wasm32")]
+        let plugins = plugins.set(AssetPlugin { meta_check: AssetMetaCheck::Never, ..default() });
+        app.add_plugins(plugins);
+    }
+    // Resources from the command line go in before the plugins that read them build
+    if let Some(config) = args.seed {
+        app.insert_resource(config);
+    }
+    if args.debug {
+        app.insert_resource(DebugOverlay { visible: true });
+    }
+    if let Some(path) = &args.record {
+        match EventRecorder::create(path) {
+            Ok(recorder) => {
+                println!("Recording gameplay events to {}", path.display());
+                app.insert_resource(recorder);
+            }
+            Err(error) => println!("Failed to create event record {}: {}", path.display(), error),
+        }
+    }
+    // A replay brings its own world and mode
+    let mut mode = args.mode;
+    if let Some(path) = &args.replay {
+        match Replay::load(path) {
+            Ok(replay) => {
+                app.insert_resource(TerrainConfig { seed: replay.meta.seed });
+                mode = Some(replay.meta.mode);
+                app.insert_resource(ReplayPlayback::new(replay));
+            }
+            Err(error) => println!("Failed to read replay {}: {}", path.display(), error),
+        }
+    }
+    // A mode on the command line skips the main menu
+    let initial_state = if mode.is_some() { GameState::Loading } else { GameState::MainMenu };
+    app
+        .insert_resource(mod_packs)
+        .insert_resource(profile)
+        .insert_resource(settings)
+        .insert_state(GameState::Preload)
+        .insert_resource(PreloadNext(initial_state))
+        .insert_resource(mode.unwrap_or_default())
         // Add our custom plugins
-        .add_plugins((PlayerPlugin, CameraPlugin, TerrainPlugin, ProjectilePlugin))
+        .add_plugins((PlayerPlugin, CameraPlugin, TerrainPlugin, ProjectilePlugin::default(), PhotoModePlugin, MinimapPlugin, IntroPlugin))
+        .add_plugins((MenuPlugin, HudPlugin, InputMappingPlugin, DebugPlugin, TargetPlugin, ScorePlugin, ResultsPlugin, LoadingPlugin))
+        .add_plugins((CompassPlugin, WaypointPlugin, ThrowWidgetPlugin, SavePlugin, HintPlugin, AccessibilityPlugin, GameEventPlugin, RegionPlugin, EventLogPlugin))
+        .add_plugins((EnemyPlugin, CatapultPlugin, CollectiblePlugin, RacePlugin, GolfPlugin, PropPlugin, DropPlugin, CapturePlugin, RaceAiPlugin, HazardPlugin, PadPlugin, PlatformPlugin, TeleportPlugin, ObjectivePlugin, ProgressionPlugin))
+        .add_plugins((BossPlugin, DifficultyPlugin, BlockPlugin, AttractPlugin, SaveGamePlugin, SettingsPlugin, ScreenshotPlugin, TuningPlugin, ProfilePlugin, StatsPlugin, LeaderboardPlugin, AudioPlugin, NetworkPlugin, SimPlugin::default(), SpectatorPlugin))
+        .add_plugins((ChatPlugin, PartyPlugin, SpatialPlugin, BudgetPlugin, HorizonPlugin, PreloadPlugin, LifetimePlugin, RecorderPlugin, TouchPlugin, SkyPlugin, ModelPlugin, TerrainMaterialPlugin, ScriptingPlugin, ParticlePlugin, TimeControlPlugin))
+        .add_plugins((GraphicsPlugin, WildlifePlugin, CloudPlugin, WorldMapPlugin, AchievementPlugin, ShopPlugin, InventoryPlugin, ShieldPlugin, MultiballPlugin, TetherPlugin, ReplayPlugin, WeatherPlugin))
         .add_systems(Startup, setup)
-        .run();
+        .add_systems(OnTransition { exited: GameState::Loading, entered: GameState::Playing }, start_run)
+        .add_systems(OnTransition { exited: GameState::GameOver, entered: GameState::Playing }, start_run);
+    #[cfg(feature = "inspector")]
+    app.add_plugins(InspectorPlugin);
+    app.run();
 }
 
 // Setup function for initializing the game world
@@ -27,7 +367,7 @@ fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    mut textures: ResMut<Assets<Image>>,
+    sphere_textures: Res<SphereTextures>,
 ) {
     // Add a light source
     commands.spawn((
@@ -48,7 +388,7 @@ fn setup(
     spawn_camera(&mut commands, &mut meshes, &mut materials);
 
     // Add player using the player module
-    spawn_player(&mut commands, &mut meshes, &mut materials, &mut textures);
+    spawn_player(&mut commands, &mut meshes, &mut materials, sphere_textures.get(ColorPalette::default()));
 
     // Terrain is now managed by the TerrainPlugin with dynamic chunk loading
 }
\ No newline at end of file
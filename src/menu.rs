@@ -0,0 +1,1067 @@
+use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use crate::{GameMode, GameState};
+use crate::camera::CameraSettings;
+use crate::photo_mode::PhotoModeState;
+use crate::input::{Action, InputMap, RebindRequest};
+use crate::accessibility::AccessibilitySettings;
+use crate::difficulty::Difficulty;
+use crate::attract::AttractState;
+use crate::settings::Settings;
+use crate::terrain::TerrainConfig;
+use crate::savegame::{save_to_slot, slot_summary, SlotRequest, QUICK_SLOT, SLOT_COUNT};
+use crate::stats::LifetimeStats;
+use crate::achievements::achievement_lines;
+use crate::save::SaveData;
+use crate::leaderboard::{has_board, Leaderboards};
+use crate::profile::{profile_names, valid_profile_name, ActiveProfile, MAX_PROFILE_NAME};
+use crate::audio::AudioBus;
+use crate::graphics::GraphicsOption;
+use crate::network::{player_color, NetRequest, NetSession, DEFAULT_PORT};
+use crate::scripting::Scripting;
+use crate::sim::{PauseReason, SimClock};
+
+// Longest seed code that can be typed
+const MAX_SEED_LENGTH: usize = 16;
+// Most profiles listed on the profile page
+const MAX_LISTED_PROFILES: usize = 8;
+// Longest host address that can be typed
+const MAX_ADDRESS_LENGTH: usize = 32;
+
+// Menu colors
+const BUTTON_NORMAL: Color = Color::srgb(0.15, 0.15, 0.15);
+const BUTTON_HOVERED: Color = Color::srgb(0.25, 0.25, 0.25);
+const BUTTON_PRESSED: Color = Color::srgb(0.35, 0.55, 0.35);
+
+// Pages of the main menu
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MainPage {
+    Main,
+    Load,
+    // World seed entry before starting a run in the chosen mode
+    NewGame,
+    Profiles,
+    // Name entry for a new profile
+    NewProfile,
+    // Lifetime statistics of the active profile
+    Stats,
+    // Achievements of the active profile, unlocked or not
+    Achievements,
+    // Best runs in a mode in the current world
+    Leaderboard(GameMode),
+    // Hosting or joining a LAN match
+    Multiplayer,
+    // Host address entry
+    JoinGame,
+    // Players in the session, waiting for the host to start
+    Lobby,
+    // Game mode scripts from the mods folder
+    Mods,
+}
+
+// Text being typed on the new game, new profile or join game page
+#[derive(Resource, Default)]
+pub struct TextEntry {
+    pub text: String,
+}
+
+// Everything the main menu pages show besides the page itself
+#[derive(SystemParam)]
+pub struct MainMenuData<'w> {
+    difficulty: Res<'w, Difficulty>,
+    mode: Res<'w, GameMode>,
+    profile: Res<'w, ActiveProfile>,
+    stats: Res<'w, LifetimeStats>,
+    save: Res<'w, SaveData>,
+    leaderboards: Res<'w, Leaderboards>,
+    terrain_config: Res<'w, TerrainConfig>,
+    text_entry: Res<'w, TextEntry>,
+    session: Option<Res<'w, NetSession>>,
+    scripting: Res<'w, Scripting>,
+}
+
+// Options the pause menu buttons change
+#[derive(SystemParam)]
+pub struct MenuOptions<'w> {
+    camera_settings: ResMut<'w, CameraSettings>,
+    accessibility: ResMut<'w, AccessibilitySettings>,
+    difficulty: ResMut<'w, Difficulty>,
+    settings: ResMut<'w, Settings>,
+    rebind: ResMut<'w, RebindRequest>,
+}
+
+// The profile, mode and world the main menu buttons pick for the next run
+#[derive(SystemParam)]
+pub struct RunSetup<'w> {
+    text_entry: ResMut<'w, TextEntry>,
+    profile: ResMut<'w, ActiveProfile>,
+    terrain_config: ResMut<'w, TerrainConfig>,
+    mode: ResMut<'w, GameMode>,
+    scripting: ResMut<'w, Scripting>,
+}
+
+// Main menu UI root, despawned when leaving the menu; rebuilt when its page changes
+#[derive(Component)]
+pub struct MainMenuRoot {
+    pub page: MainPage,
+}
+
+// Pages of the pause menu
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PausePage {
+    Main,
+    Settings,
+    Audio,
+    Display,
+    Graphics,
+    Controls,
+    Save,
+}
+
+// Pause menu UI root, despawned when leaving the pause state; rebuilt when its page changes
+#[derive(Component)]
+pub struct PauseMenuRoot {
+    pub page: PausePage,
+}
+
+// What a menu button does when clicked
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuAction {
+    Play(GameMode),
+    Mods,
+    PlayScript(usize),
+    Resume,
+    Settings,
+    CycleCameraPreset,
+    CyclePalette,
+    CycleDifficulty,
+    CycleSensitivity,
+    Audio,
+    CycleBusVolume(AudioBus),
+    ToggleMute(AudioBus),
+    Display,
+    CycleDisplayMode,
+    CycleResolution,
+    ToggleVsync,
+    CycleFpsLimit,
+    ToggleShadows,
+    Graphics,
+    CycleGraphics(GraphicsOption),
+    Controls,
+    Rebind(Action),
+    RandomizeSeed,
+    StartGame,
+    Profiles,
+    SelectProfile(usize),
+    NewProfile,
+    CreateProfile,
+    Stats,
+    Achievements,
+    Leaderboards,
+    CycleLeaderboardMode,
+    Multiplayer,
+    HostGame,
+    JoinGame,
+    Connect,
+    Spectate,
+    StartMatch,
+    AddLocalPlayer,
+    RemoveLocalPlayer,
+    CycleTether,
+    LeaveSession,
+    LoadGame,
+    LoadSlot(usize),
+    SaveGame,
+    SaveSlot(usize),
+    Back,
+    EndRun,
+    Retry,
+    MainMenu,
+    Quit,
+}
+
+// Spawn a menu button with a label
+pub fn spawn_menu_button(parent: &mut ChildBuilder, label: &str, action: MenuAction) {
+    parent
+        .spawn((
+            Button,
+            action,
+            Node {
+                width: Val::Px(220.0),
+                height: Val::Px(50.0),
+                margin: UiRect::all(Val::Px(6.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(BUTTON_NORMAL),
+        ))
+        .with_children(|button| {
+            button.spawn((
+                Text::new(label),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+// Build the main menu screen: a dimmed overlay holding the menu panel
+pub fn spawn_main_menu(mut commands: Commands) {
+    commands.spawn((
+        MainMenuRoot { page: MainPage::Main },
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+    ));
+}
+
+// Fill the main menu with the widgets for its current page
+fn spawn_main_page(
+    commands: &mut Commands,
+    root: Entity,
+    page: MainPage,
+    data: &MainMenuData,
+) {
+    let MainMenuData { profile, stats, save, leaderboards, text_entry, scripting, .. } = data;
+    let (difficulty, mode, seed, session) = (*data.difficulty, *data.mode, *data.terrain_config, data.session.as_deref());
+    commands.entity(root).despawn_descendants().with_children(|parent| {
+        let title = match page {
+            MainPage::Main => "Trowback",
+            MainPage::Load => "Load Game",
+            MainPage::NewGame => "New Game",
+            MainPage::Profiles => "Profiles",
+            MainPage::NewProfile => "New Profile",
+            MainPage::Stats => "Stats",
+            MainPage::Achievements => "Achievements",
+            MainPage::Leaderboard(_) => "Leaderboards",
+            MainPage::Multiplayer => "Multiplayer",
+            MainPage::JoinGame => "Join Game",
+            MainPage::Lobby => "Lobby",
+            MainPage::Mods => "Mods",
+        };
+        parent.spawn((
+            Text::new(title),
+            TextFont {
+                font_size: 64.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Node {
+                margin: UiRect::bottom(Val::Px(30.0)),
+                ..default()
+            },
+        ));
+        match page {
+            MainPage::Main => {
+                spawn_menu_button(parent, "Free Play", MenuAction::Play(GameMode::FreePlay));
+                spawn_menu_button(parent, "Target Practice", MenuAction::Play(GameMode::TargetPractice));
+                spawn_menu_button(parent, "Time Trial", MenuAction::Play(GameMode::TimeTrial));
+                spawn_menu_button(parent, "Golf", MenuAction::Play(GameMode::Golf));
+                spawn_menu_button(parent, "King of the Hill", MenuAction::Play(GameMode::KingOfTheHill));
+                spawn_menu_button(parent, "Downhill Race", MenuAction::Play(GameMode::DownhillRace));
+                spawn_menu_button(parent, "Boss Fight", MenuAction::Play(GameMode::BossFight));
+                if !scripting.scripts.is_empty() {
+                    spawn_menu_button(parent, "Mods", MenuAction::Mods);
+                }
+                spawn_menu_button(parent, "Multiplayer", MenuAction::Multiplayer);
+                spawn_menu_button(parent, "Load Game", MenuAction::LoadGame);
+                let difficulty_label = format!("Difficulty: {:?}", difficulty);
+                spawn_menu_button(parent, &difficulty_label, MenuAction::CycleDifficulty);
+                let profile_label = format!("Profile: {}", profile.name);
+                spawn_menu_button(parent, &profile_label, MenuAction::Profiles);
+                spawn_menu_button(parent, "Stats", MenuAction::Stats);
+                spawn_menu_button(parent, "Achievements", MenuAction::Achievements);
+                spawn_menu_button(parent, "Leaderboards", MenuAction::Leaderboards);
+                spawn_menu_button(parent, "Quit", MenuAction::Quit);
+            }
+            MainPage::Load => {
+                let quick_label = format!("Quick Save ({})", slot_summary(profile, QUICK_SLOT));
+                spawn_menu_button(parent, &quick_label, MenuAction::LoadSlot(QUICK_SLOT));
+                for slot in 1..=SLOT_COUNT {
+                    let label = format!("Slot {} ({})", slot, slot_summary(profile, slot));
+                    spawn_menu_button(parent, &label, MenuAction::LoadSlot(slot));
+                }
+                spawn_menu_button(parent, "Back", MenuAction::Back);
+            }
+            MainPage::NewGame => {
+                let mode_name = match (mode, scripting.selected_name()) {
+                    (GameMode::Scripted, Some(name)) => name.to_string(),
+                    _ => format!("{:?}", mode),
+                };
+                parent.spawn((
+                    Text::new(format!("{} - World Seed", mode_name)),
+                    TextFont {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                ));
+                spawn_text_field(parent, &text_entry.text);
+                spawn_menu_button(parent, "Randomize", MenuAction::RandomizeSeed);
+                spawn_menu_button(parent, "Start", MenuAction::StartGame);
+                spawn_menu_button(parent, "Back", MenuAction::Back);
+            }
+            MainPage::Profiles => {
+                for (index, name) in profile_names().iter().take(MAX_LISTED_PROFILES).enumerate() {
+                    let label = if *name == profile.name { format!("> {} <", name) } else { name.clone() };
+                    spawn_menu_button(parent, &label, MenuAction::SelectProfile(index));
+                }
+                spawn_menu_button(parent, "New Profile", MenuAction::NewProfile);
+                spawn_menu_button(parent, "Back", MenuAction::Back);
+            }
+            MainPage::NewProfile => {
+                spawn_text_field(parent, &text_entry.text);
+                spawn_menu_button(parent, "Create", MenuAction::CreateProfile);
+                spawn_menu_button(parent, "Back", MenuAction::Back);
+            }
+            MainPage::Leaderboard(board_mode) => {
+                parent.spawn((
+                    Text::new(format!("World {}", seed.seed_code())),
+                    TextFont {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                ));
+                for line in leaderboards.lines(board_mode, seed.seed, None) {
+                    parent.spawn((
+                        Text::new(line),
+                        TextFont {
+                            font_size: 20.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                }
+                let mode_label = format!("Mode: {:?}", board_mode);
+                spawn_menu_button(parent, &mode_label, MenuAction::CycleLeaderboardMode);
+                spawn_menu_button(parent, "Back", MenuAction::Back);
+            }
+            MainPage::Multiplayer => {
+                spawn_menu_button(parent, "Host Game", MenuAction::HostGame);
+                spawn_menu_button(parent, "Join Game", MenuAction::JoinGame);
+                spawn_menu_button(parent, "Back", MenuAction::Back);
+            }
+            MainPage::JoinGame => {
+                parent.spawn((
+                    Text::new(format!("Host address (port {} if left out)", DEFAULT_PORT)),
+                    TextFont {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                ));
+                spawn_text_field(parent, &text_entry.text);
+                spawn_menu_button(parent, "Connect", MenuAction::Connect);
+                spawn_menu_button(parent, "Spectate", MenuAction::Spectate);
+                spawn_menu_button(parent, "Back", MenuAction::Back);
+            }
+            MainPage::Lobby => {
+                let status = match session {
+                    None => "Not connected".to_string(),
+                    Some(session) if session.local_id.is_none() => "Connecting...".to_string(),
+                    Some(session) if session.is_host() => format!("Hosting on port {}", DEFAULT_PORT),
+                    Some(session) if session.spectating => "Spectating; waiting for the host to start".to_string(),
+                    Some(_) => "Waiting for the host to start".to_string(),
+                };
+                parent.spawn((
+                    Text::new(status),
+                    TextFont {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                ));
+                for player in session.map_or(&[][..], |session| session.players.as_slice()) {
+                    let you = if session.is_some_and(|session| session.is_local(player.id)) { " (you)" } else { "" };
+                    let (line, color) = if player.spectator {
+                        (format!("   {} (spectating){}", player.name, you), Color::srgb(0.6, 0.6, 0.6))
+                    } else {
+                        let knockouts = match session.and_then(|session| session.knockouts.get(&player.id)) {
+                            Some(count) => format!("  {} KO", count),
+                            None => String::new(),
+                        };
+                        (format!("{}. {}{}{}", player.id + 1, player.name, you, knockouts), player_color(player.id))
+                    };
+                    parent.spawn((
+                        Text::new(line),
+                        TextFont {
+                            font_size: 24.0,
+                            ..default()
+                        },
+                        TextColor(color),
+                    ));
+                }
+                if session.is_some_and(|session| session.is_host()) {
+                    spawn_menu_button(parent, "Start", MenuAction::StartMatch);
+                }
+                // A second player on a gamepad can join from this machine and play split-screen
+                match session {
+                    Some(session) if session.local_id.is_some() && !session.spectating && session.guest_id.is_none() => {
+                        spawn_menu_button(parent, "Add Local Player", MenuAction::AddLocalPlayer);
+                    }
+                    Some(session) if session.guest_id.is_some() => {
+                        spawn_menu_button(parent, &format!("Tether: {}", session.tether.label()), MenuAction::CycleTether);
+                        spawn_menu_button(parent, "Remove Local Player", MenuAction::RemoveLocalPlayer);
+                    }
+                    _ => {}
+                }
+                spawn_menu_button(parent, "Leave", MenuAction::LeaveSession);
+            }
+            MainPage::Mods => {
+                for (index, script) in scripting.scripts.iter().enumerate() {
+                    spawn_menu_button(parent, &script.name, MenuAction::PlayScript(index));
+                }
+                spawn_menu_button(parent, "Back", MenuAction::Back);
+            }
+            MainPage::Stats => {
+                for line in stats.lines() {
+                    parent.spawn((
+                        Text::new(line),
+                        TextFont {
+                            font_size: 24.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                        Node {
+                            margin: UiRect::all(Val::Px(4.0)),
+                            ..default()
+                        },
+                    ));
+                }
+                spawn_menu_button(parent, "Back", MenuAction::Back);
+            }
+            MainPage::Achievements => {
+                for (line, unlocked) in achievement_lines(save) {
+                    parent.spawn((
+                        Text::new(line),
+                        TextFont {
+                            font_size: 24.0,
+                            ..default()
+                        },
+                        TextColor(if unlocked { Color::srgb(0.95, 0.8, 0.3) } else { Color::srgb(0.5, 0.5, 0.5) }),
+                        Node {
+                            margin: UiRect::all(Val::Px(4.0)),
+                            ..default()
+                        },
+                    ));
+                }
+                spawn_menu_button(parent, "Back", MenuAction::Back);
+            }
+        }
+    });
+}
+
+// Typed text with a cursor, in a box like a text field
+fn spawn_text_field(parent: &mut ChildBuilder, text: &str) {
+    parent
+        .spawn((
+            Node {
+                width: Val::Px(320.0),
+                height: Val::Px(50.0),
+                margin: UiRect::all(Val::Px(12.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                border: UiRect::all(Val::Px(2.0)),
+                ..default()
+            },
+            BorderColor(Color::WHITE),
+            BackgroundColor(Color::srgb(0.05, 0.05, 0.05)),
+        ))
+        .with_children(|field| {
+            field.spawn((
+                Text::new(format!("{}_", text)),
+                TextFont {
+                    font_size: 28.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+// Set the world from the typed seed code and head into the run; an empty code keeps the current world
+fn start_new_game(text_entry: &TextEntry, terrain_config: &mut TerrainConfig, next_state: &mut NextState<GameState>) {
+    if let Some(config) = TerrainConfig::from_code(&text_entry.text) {
+        *terrain_config = config;
+    }
+    next_state.set(GameState::Loading);
+}
+
+// Ask to join the typed host, to play or to watch, and wait in the lobby
+fn connect_to_host(text_entry: &TextEntry, net_requests: &mut EventWriter<NetRequest>, menu: &mut MainMenuRoot, spectator: bool) {
+    let address = text_entry.text.trim();
+    if address.is_empty() {
+        return;
+    }
+    net_requests.send(NetRequest::Join { address: address.to_string(), spectator });
+    menu.page = MainPage::Lobby;
+}
+
+// Switch to a new profile once its name is valid and not taken
+fn create_profile(text_entry: &TextEntry, profile: &mut ActiveProfile, menu: &mut MainMenuRoot) {
+    let name = text_entry.text.trim();
+    if !valid_profile_name(name) || profile_names().iter().any(|existing| existing.eq_ignore_ascii_case(name)) {
+        return;
+    }
+    profile.name = name.to_string();
+    menu.page = MainPage::Main;
+}
+
+// Type into the text field on the new game, new profile and join game pages; Backspace deletes
+pub fn type_text(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    root_query: Query<&MainMenuRoot>,
+    mut text_entry: ResMut<TextEntry>,
+) {
+    let page = root_query.get_single().map(|menu| menu.page).ok();
+    for event in keyboard_events.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+        // Seeds are codes, profile names also become folder names
+        let (max_length, allowed): (usize, fn(char) -> bool) = match page {
+            Some(MainPage::NewGame) => (MAX_SEED_LENGTH, |c| c.is_ascii_alphanumeric()),
+            Some(MainPage::NewProfile) => (MAX_PROFILE_NAME, |c| c.is_ascii_alphanumeric() || c == ' ' || c == '-'),
+            Some(MainPage::JoinGame) => (MAX_ADDRESS_LENGTH, |c| c.is_ascii_alphanumeric() || c == '.' || c == ':'),
+            _ => continue,
+        };
+        match &event.logical_key {
+            Key::Character(text) => {
+                for c in text.chars().filter(|c| allowed(*c)) {
+                    if text_entry.text.len() < max_length {
+                        text_entry.text.push(c);
+                    }
+                }
+            }
+            Key::Space if allowed(' ') && text_entry.text.len() < max_length => text_entry.text.push(' '),
+            Key::Backspace => {
+                text_entry.text.pop();
+            }
+            _ => {}
+        }
+    }
+}
+
+// Remove the main menu screen
+pub fn despawn_main_menu(mut commands: Commands, query: Query<Entity, With<MainMenuRoot>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Rebuild the main menu when its page, the difficulty, the typed text or the lobby changes
+pub fn refresh_main_menu(
+    mut commands: Commands,
+    root_query: Query<(Entity, Ref<MainMenuRoot>)>,
+    data: MainMenuData,
+    mut had_session: Local<bool>,
+) {
+    let Ok((root, menu)) = root_query.get_single() else {
+        return;
+    };
+    // Joining, leaving or being dropped from a session changes the lobby too
+    let session = &data.session;
+    let session_changed = session.as_ref().is_some_and(|session| session.is_changed()) || *had_session != session.is_some();
+    *had_session = session.is_some();
+    if menu.is_changed() || data.difficulty.is_changed() || data.profile.is_changed() || data.text_entry.is_changed() || session_changed {
+        spawn_main_page(&mut commands, root, menu.page, &data);
+    }
+}
+
+// Build the pause menu: a dimmed overlay holding the menu panel
+pub fn spawn_pause_menu(mut commands: Commands) {
+    commands.spawn((
+        PauseMenuRoot { page: PausePage::Main },
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        // Draw above the HUD and other overlays
+        GlobalZIndex(10),
+    ));
+}
+
+// Fill the pause menu with the widgets for its current page
+fn spawn_pause_page(
+    commands: &mut Commands,
+    root: Entity,
+    page: PausePage,
+    camera_settings: &CameraSettings,
+    accessibility: &AccessibilitySettings,
+    settings: &Settings,
+    profile: &ActiveProfile,
+    input_map: &InputMap,
+    rebind: &RebindRequest,
+) {
+    commands.entity(root).despawn_descendants().with_children(|parent| {
+        let title = match page {
+            PausePage::Main => "Paused",
+            PausePage::Settings => "Settings",
+            PausePage::Audio => "Audio",
+            PausePage::Display => "Display",
+            PausePage::Graphics => "Graphics",
+            PausePage::Controls => "Controls",
+            PausePage::Save => "Save Game",
+        };
+        parent.spawn((
+            Text::new(title),
+            TextFont {
+                font_size: 48.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Node {
+                margin: UiRect::bottom(Val::Px(20.0)),
+                ..default()
+            },
+        ));
+        match page {
+            PausePage::Main => {
+                spawn_menu_button(parent, "Resume", MenuAction::Resume);
+                spawn_menu_button(parent, "Settings", MenuAction::Settings);
+                spawn_menu_button(parent, "Save Game", MenuAction::SaveGame);
+                spawn_menu_button(parent, "End Run", MenuAction::EndRun);
+                spawn_menu_button(parent, "Quit", MenuAction::Quit);
+            }
+            PausePage::Settings => {
+                let camera_label = format!("Camera: {:?}", camera_settings.preset);
+                spawn_menu_button(parent, &camera_label, MenuAction::CycleCameraPreset);
+                let palette_label = format!("Colors: {:?}", accessibility.palette);
+                spawn_menu_button(parent, &palette_label, MenuAction::CyclePalette);
+                let sensitivity_label = format!("Mouse Sensitivity: {:.2}x", settings.mouse_sensitivity);
+                spawn_menu_button(parent, &sensitivity_label, MenuAction::CycleSensitivity);
+                spawn_menu_button(parent, "Audio", MenuAction::Audio);
+                spawn_menu_button(parent, "Display", MenuAction::Display);
+                spawn_menu_button(parent, "Graphics", MenuAction::Graphics);
+                spawn_menu_button(parent, "Controls", MenuAction::Controls);
+                spawn_menu_button(parent, "Back", MenuAction::Back);
+            }
+            PausePage::Audio => {
+                // A volume and a mute button per bus
+                for bus in AudioBus::ALL {
+                    let volume_label = format!("{}: {:.0}%", bus.label(), settings.bus_volume(bus) * 100.0);
+                    spawn_menu_button(parent, &volume_label, MenuAction::CycleBusVolume(bus));
+                    let mute_label = if settings.is_muted(bus) { format!("Unmute {}", bus.label()) } else { format!("Mute {}", bus.label()) };
+                    spawn_menu_button(parent, &mute_label, MenuAction::ToggleMute(bus));
+                }
+                spawn_menu_button(parent, "Back", MenuAction::Back);
+            }
+            PausePage::Display => {
+                let on_off = |enabled: bool| if enabled { "On" } else { "Off" };
+                spawn_menu_button(parent, &format!("Mode: {:?}", settings.display_mode), MenuAction::CycleDisplayMode);
+                // The size only matters in a window; the fullscreen modes use the monitor's resolution
+                let (width, height) = settings.resolution;
+                spawn_menu_button(parent, &format!("Resolution: {}x{}", width, height), MenuAction::CycleResolution);
+                spawn_menu_button(parent, &format!("VSync: {}", on_off(settings.vsync)), MenuAction::ToggleVsync);
+                let fps_label = match settings.fps_limit {
+                    0 => "FPS Limit: Off".to_string(),
+                    limit => format!("FPS Limit: {}", limit),
+                };
+                spawn_menu_button(parent, &fps_label, MenuAction::CycleFpsLimit);
+                spawn_menu_button(parent, "Back", MenuAction::Back);
+            }
+            PausePage::Graphics => {
+                let on_off = |enabled: bool| if enabled { "On" } else { "Off" };
+                spawn_menu_button(parent, &format!("Shadows: {}", on_off(settings.shadows)), MenuAction::ToggleShadows);
+                for option in GraphicsOption::ALL {
+                    spawn_menu_button(parent, &settings.graphics.label(option), MenuAction::CycleGraphics(option));
+                }
+                spawn_menu_button(parent, "Back", MenuAction::Back);
+            }
+            PausePage::Controls => {
+                // One button per action showing its keyboard/mouse binding; click to rebind
+                for action in Action::ALL {
+                    let label = if rebind.action == Some(action) {
+                        format!("{}: press a key...", action.label())
+                    } else {
+                        let binding = input_map
+                            .get(action)
+                            .iter()
+                            .find(|binding| !binding.is_gamepad())
+                            .map(|binding| binding.label())
+                            .unwrap_or_else(|| "Unbound".to_string());
+                        format!("{}: {}", action.label(), binding)
+                    };
+                    spawn_menu_button(parent, &label, MenuAction::Rebind(action));
+                }
+                spawn_menu_button(parent, "Back", MenuAction::Back);
+            }
+            PausePage::Save => {
+                for slot in 1..=SLOT_COUNT {
+                    let label = format!("Slot {} ({})", slot, slot_summary(profile, slot));
+                    spawn_menu_button(parent, &label, MenuAction::SaveSlot(slot));
+                }
+                spawn_menu_button(parent, "Back", MenuAction::Back);
+            }
+        }
+    });
+}
+
+// Rebuild the pause menu when its page, the bindings or a pending rebind change
+pub fn refresh_pause_menu(
+    mut commands: Commands,
+    root_query: Query<(Entity, Ref<PauseMenuRoot>)>,
+    camera_settings: Res<CameraSettings>,
+    accessibility: Res<AccessibilitySettings>,
+    settings: Res<Settings>,
+    profile: Res<ActiveProfile>,
+    input_map: Res<InputMap>,
+    rebind: Res<RebindRequest>,
+) {
+    let Ok((root, menu)) = root_query.get_single() else {
+        return;
+    };
+    if menu.is_changed() || accessibility.is_changed() || settings.is_changed() || input_map.is_changed() || rebind.is_changed() {
+        spawn_pause_page(&mut commands, root, menu.page, &camera_settings, &accessibility, &settings, &profile, &input_map, &rebind);
+    }
+}
+
+// Remove the pause menu
+pub fn despawn_pause_menu(mut commands: Commands, query: Query<Entity, With<PauseMenuRoot>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Stop the simulation clock while paused so FixedUpdate and timers halt too
+pub fn pause_time(mut clock: ResMut<SimClock>) {
+    clock.pause(PauseReason::Menu);
+}
+
+// Restart the simulation clock when leaving the pause menu
+pub fn resume_time(mut clock: ResMut<SimClock>) {
+    clock.resume(PauseReason::Menu);
+}
+
+// Escape toggles between playing and the pause menu
+pub fn toggle_pause(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    photo_state: Res<State<PhotoModeState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keys.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    match state.get() {
+        // Photo mode has its own frozen clock, so don't stack a pause on top of it
+        GameState::Playing if *photo_state.get() == PhotoModeState::Inactive => {
+            next_state.set(GameState::Paused);
+        }
+        GameState::Paused => next_state.set(GameState::Playing),
+        _ => {}
+    }
+}
+
+// Give buttons hover and press feedback
+pub fn button_visuals(
+    mut query: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>)>,
+) {
+    for (interaction, mut color) in query.iter_mut() {
+        *color = match interaction {
+            Interaction::Pressed => BUTTON_PRESSED.into(),
+            Interaction::Hovered => BUTTON_HOVERED.into(),
+            Interaction::None => BUTTON_NORMAL.into(),
+        };
+    }
+}
+
+// Handle menu button clicks
+pub fn handle_menu_actions(
+    query: Query<(&Interaction, &MenuAction), Changed<Interaction>>,
+    mut pause_root: Query<&mut PauseMenuRoot>,
+    mut main_root: Query<&mut MainMenuRoot>,
+    mut slot_requests: EventWriter<SlotRequest>,
+    options: MenuOptions,
+    run_setup: RunSetup,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut net_requests: EventWriter<NetRequest>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let MenuOptions { mut camera_settings, mut accessibility, mut difficulty, mut settings, mut rebind } = options;
+    let RunSetup { mut text_entry, mut profile, mut terrain_config, mut mode, mut scripting } = run_setup;
+    for (interaction, action) in query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match action {
+            MenuAction::Mods => {
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    menu.page = MainPage::Mods;
+                }
+            }
+            MenuAction::PlayScript(index) => {
+                scripting.selected = Some(*index);
+                *mode = GameMode::Scripted;
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    text_entry.text = terrain_config.seed_code();
+                    menu.page = MainPage::NewGame;
+                }
+            }
+            MenuAction::Play(selected) => {
+                *mode = *selected;
+                // Pick the world first, starting from the one already in use
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    text_entry.text = terrain_config.seed_code();
+                    menu.page = MainPage::NewGame;
+                } else {
+                    next_state.set(GameState::Loading);
+                }
+            }
+            MenuAction::RandomizeSeed => text_entry.text = TerrainConfig::random().seed_code(),
+            MenuAction::StartGame => start_new_game(&text_entry, &mut terrain_config, &mut next_state),
+            MenuAction::Profiles => {
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    menu.page = MainPage::Profiles;
+                }
+            }
+            MenuAction::Stats => {
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    menu.page = MainPage::Stats;
+                }
+            }
+            MenuAction::Achievements => {
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    menu.page = MainPage::Achievements;
+                }
+            }
+            MenuAction::Leaderboards => {
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    let board_mode = if has_board(*mode) { *mode } else { GameMode::FreePlay };
+                    menu.page = MainPage::Leaderboard(board_mode);
+                }
+            }
+            MenuAction::CycleLeaderboardMode => {
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    if let MainPage::Leaderboard(board_mode) = menu.page {
+                        let mut next = board_mode.next();
+                        while !has_board(next) {
+                            next = next.next();
+                        }
+                        menu.page = MainPage::Leaderboard(next);
+                    }
+                }
+            }
+            MenuAction::Multiplayer => {
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    menu.page = MainPage::Multiplayer;
+                }
+            }
+            MenuAction::HostGame => {
+                net_requests.send(NetRequest::Host);
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    menu.page = MainPage::Lobby;
+                }
+            }
+            MenuAction::JoinGame => {
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    text_entry.text.clear();
+                    menu.page = MainPage::JoinGame;
+                }
+            }
+            MenuAction::Connect => {
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    connect_to_host(&text_entry, &mut net_requests, &mut menu, false);
+                }
+            }
+            MenuAction::Spectate => {
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    connect_to_host(&text_entry, &mut net_requests, &mut menu, true);
+                }
+            }
+            MenuAction::StartMatch => {
+                net_requests.send(NetRequest::Start);
+            }
+            MenuAction::AddLocalPlayer => {
+                net_requests.send(NetRequest::AddGuest);
+            }
+            MenuAction::RemoveLocalPlayer => {
+                net_requests.send(NetRequest::RemoveGuest);
+            }
+            MenuAction::CycleTether => {
+                net_requests.send(NetRequest::CycleTether);
+            }
+            MenuAction::LeaveSession => {
+                net_requests.send(NetRequest::Leave);
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    menu.page = MainPage::Multiplayer;
+                }
+            }
+            MenuAction::SelectProfile(index) => {
+                if let Some(name) = profile_names().get(*index) {
+                    if *name != profile.name {
+                        profile.name = name.clone();
+                    }
+                }
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    menu.page = MainPage::Main;
+                }
+            }
+            MenuAction::NewProfile => {
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    text_entry.text.clear();
+                    menu.page = MainPage::NewProfile;
+                }
+            }
+            MenuAction::CreateProfile => {
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    create_profile(&text_entry, &mut profile, &mut menu);
+                }
+            }
+            MenuAction::Resume | MenuAction::Retry => next_state.set(GameState::Playing),
+            MenuAction::EndRun => next_state.set(GameState::GameOver),
+            MenuAction::MainMenu => next_state.set(GameState::MainMenu),
+            MenuAction::Settings => {
+                if let Ok(mut menu) = pause_root.get_single_mut() {
+                    menu.page = PausePage::Settings;
+                }
+            }
+            MenuAction::Audio => {
+                if let Ok(mut menu) = pause_root.get_single_mut() {
+                    menu.page = PausePage::Audio;
+                }
+            }
+            MenuAction::Display => {
+                if let Ok(mut menu) = pause_root.get_single_mut() {
+                    menu.page = PausePage::Display;
+                }
+            }
+            MenuAction::Graphics => {
+                if let Ok(mut menu) = pause_root.get_single_mut() {
+                    menu.page = PausePage::Graphics;
+                }
+            }
+            MenuAction::Controls => {
+                if let Ok(mut menu) = pause_root.get_single_mut() {
+                    menu.page = PausePage::Controls;
+                }
+            }
+            MenuAction::Back => {
+                if let Ok(mut menu) = pause_root.get_single_mut() {
+                    menu.page = match menu.page {
+                        PausePage::Controls | PausePage::Audio | PausePage::Display | PausePage::Graphics => PausePage::Settings,
+                        _ => PausePage::Main,
+                    };
+                }
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    menu.page = match menu.page {
+                        MainPage::NewProfile => MainPage::Profiles,
+                        MainPage::JoinGame => MainPage::Multiplayer,
+                        _ => MainPage::Main,
+                    };
+                }
+            }
+            MenuAction::LoadGame => {
+                if let Ok(mut menu) = main_root.get_single_mut() {
+                    menu.page = MainPage::Load;
+                }
+            }
+            MenuAction::LoadSlot(slot) => {
+                slot_requests.send(SlotRequest::Load(*slot));
+            }
+            MenuAction::SaveGame => {
+                if let Ok(mut menu) = pause_root.get_single_mut() {
+                    menu.page = PausePage::Save;
+                }
+            }
+            MenuAction::SaveSlot(slot) => {
+                slot_requests.send(SlotRequest::Save(*slot));
+                // Touch the menu so the slot button shows what was saved
+                if let Ok(mut menu) = pause_root.get_single_mut() {
+                    menu.set_changed();
+                }
+            }
+            MenuAction::CycleCameraPreset => {
+                let next = camera_settings.preset.next();
+                camera_settings.apply_preset(next);
+                // Touch the menu so the button label shows the new preset
+                if let Ok(mut menu) = pause_root.get_single_mut() {
+                    menu.set_changed();
+                }
+            }
+            MenuAction::CyclePalette => accessibility.palette = accessibility.palette.next(),
+            MenuAction::CycleDifficulty => *difficulty = difficulty.next(),
+            MenuAction::CycleSensitivity => settings.cycle_sensitivity(),
+            MenuAction::CycleBusVolume(bus) => settings.cycle_bus_volume(*bus),
+            MenuAction::ToggleMute(bus) => settings.toggle_mute(*bus),
+            MenuAction::CycleDisplayMode => settings.display_mode = settings.display_mode.next(),
+            MenuAction::CycleResolution => settings.cycle_resolution(),
+            MenuAction::ToggleVsync => settings.vsync = !settings.vsync,
+            MenuAction::CycleFpsLimit => settings.cycle_fps_limit(),
+            MenuAction::ToggleShadows => settings.shadows = !settings.shadows,
+            MenuAction::CycleGraphics(option) => settings.graphics.cycle(*option),
+            MenuAction::Rebind(target) => rebind.start(*target),
+            MenuAction::Quit => {
+                exit.send(AppExit::Success);
+            }
+        }
+    }
+}
+
+// Enter also starts the game from the main menu, in the typed world on the new game page,
+// creates the typed profile on the new profile page and connects to the typed host on the join page
+pub fn main_menu_keyboard(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut root_query: Query<&mut MainMenuRoot>,
+    text_entry: Res<TextEntry>,
+    mut profile: ResMut<ActiveProfile>,
+    mut terrain_config: ResMut<TerrainConfig>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut net_requests: EventWriter<NetRequest>,
+) {
+    if !keys.just_pressed(KeyCode::Enter) {
+        return;
+    }
+    match root_query.get_single_mut() {
+        Ok(menu) if menu.page == MainPage::NewGame => start_new_game(&text_entry, &mut terrain_config, &mut next_state),
+        Ok(mut menu) if menu.page == MainPage::NewProfile => create_profile(&text_entry, &mut profile, &mut menu),
+        Ok(mut menu) if menu.page == MainPage::JoinGame => connect_to_host(&text_entry, &mut net_requests, &mut menu, false),
+        // Only the host starts a networked match
+        Ok(menu) if menu.page == MainPage::Lobby => {}
+        _ => next_state.set(GameState::Loading),
+    }
+}
+
+// Plugin for menu screens
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<TextEntry>()
+            .add_systems(OnEnter(GameState::MainMenu), spawn_main_menu)
+            .add_systems(OnExit(GameState::MainMenu), despawn_main_menu)
+            .add_systems(Update, (
+                button_visuals,
+                handle_menu_actions,
+                // Saving first lets a rebuilt save page show the slot that was just written
+                refresh_pause_menu.after(handle_menu_actions).after(save_to_slot),
+            ))
+            // The key that ends the attract demo shouldn't also start a game
+            .add_systems(Update, main_menu_keyboard
+                .run_if(in_state(GameState::MainMenu))
+                .run_if(in_state(AttractState::Inactive)))
+            .add_systems(Update, (type_text, refresh_main_menu.after(handle_menu_actions).after(type_text))
+                .run_if(in_state(GameState::MainMenu)))
+            .add_systems(OnEnter(GameState::Paused), (spawn_pause_menu, pause_time))
+            .add_systems(OnExit(GameState::Paused), (despawn_pause_menu, resume_time))
+            .add_systems(Update, toggle_pause
+                .run_if(in_state(GameState::Playing).or(in_state(GameState::Paused))));
+    }
+}
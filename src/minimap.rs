@@ -0,0 +1,208 @@
+use bevy::prelude::*;
+use bevy::render::camera::{RenderTarget, ScalingMode};
+use bevy::render::render_resource::{
+    Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+};
+use crate::player::Player;
+
+// Minimap constants
+const MINIMAP_TEXTURE_SIZE: u32 = 256; // Resolution of the offscreen render target
+const MINIMAP_WIDGET_SIZE: f32 = 200.0; // On-screen size of the minimap widget in pixels
+const MINIMAP_EXTENT: f32 = 120.0; // World units covered by the minimap, edge to edge
+const MINIMAP_CAMERA_HEIGHT: f32 = 100.0; // Height of the minimap camera above the player
+const BLIP_SIZE: f32 = 8.0; // Blip diameter in pixels
+
+// Marker for the orthographic camera that renders the minimap
+#[derive(Component)]
+pub struct MinimapCamera;
+
+// Marker for the UI frame holding the minimap image and its blips
+#[derive(Component)]
+pub struct MinimapFrame;
+
+// Entities with this component show up as a colored dot on the minimap
+#[derive(Component)]
+pub struct MinimapBlip {
+    pub color: Color,
+}
+
+// UI dot that tracks a world entity carrying a MinimapBlip
+#[derive(Component)]
+pub struct MinimapMarker {
+    pub target: Entity,
+}
+
+// Create the render target, the minimap camera and the corner widget
+pub fn spawn_minimap(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let size = Extent3d {
+        width: MINIMAP_TEXTURE_SIZE,
+        height: MINIMAP_TEXTURE_SIZE,
+        depth_or_array_layers: 1,
+    };
+
+    // Offscreen image the minimap camera renders into
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("minimap"),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    let image_handle = images.add(image);
+
+    // Top-down orthographic camera with north (-Z) at the top of the map
+    commands.spawn((
+        MinimapCamera,
+        Camera3d::default(),
+        Camera {
+            target: RenderTarget::Image(image_handle.clone()),
+            // Render before the main camera
+            order: -1,
+            clear_color: ClearColorConfig::Custom(Color::srgb(0.1, 0.12, 0.1)),
+            ..default()
+        },
+        Projection::from(OrthographicProjection {
+            scaling_mode: ScalingMode::Fixed {
+                width: MINIMAP_EXTENT,
+                height: MINIMAP_EXTENT,
+            },
+            ..OrthographicProjection::default_3d()
+        }),
+        Transform::from_xyz(0.0, MINIMAP_CAMERA_HEIGHT, 0.0).looking_to(Vec3::NEG_Y, Vec3::NEG_Z),
+    ));
+
+    // Corner widget showing the rendered map
+    commands
+        .spawn((
+            MinimapFrame,
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(12.0),
+                bottom: Val::Px(12.0),
+                width: Val::Px(MINIMAP_WIDGET_SIZE),
+                height: Val::Px(MINIMAP_WIDGET_SIZE),
+                border: UiRect::all(Val::Px(2.0)),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BorderColor(Color::srgba(1.0, 1.0, 1.0, 0.6)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ImageNode::new(image_handle),
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+            ));
+        });
+}
+
+// Keep the minimap camera centered over the player
+pub fn follow_player_minimap(
+    player_query: Query<&Transform, With<Player>>,
+    mut minimap_query: Query<&mut Transform, (With<MinimapCamera>, Without<Player>)>,
+) {
+    if let (Ok(player_transform), Ok(mut minimap_transform)) = (
+        player_query.get_single(),
+        minimap_query.get_single_mut()
+    ) {
+        minimap_transform.translation.x = player_transform.translation.x;
+        minimap_transform.translation.z = player_transform.translation.z;
+        minimap_transform.translation.y = player_transform.translation.y + MINIMAP_CAMERA_HEIGHT;
+    }
+}
+
+// Create UI markers for new blips and remove markers whose entity is gone
+pub fn sync_minimap_markers(
+    mut commands: Commands,
+    frame_query: Query<Entity, With<MinimapFrame>>,
+    new_blips: Query<(Entity, &MinimapBlip), Added<MinimapBlip>>,
+    blips: Query<(), With<MinimapBlip>>,
+    markers: Query<(Entity, &MinimapMarker)>,
+) {
+    let Ok(frame) = frame_query.get_single() else {
+        return;
+    };
+
+    for (entity, blip) in new_blips.iter() {
+        commands.entity(frame).with_children(|parent| {
+            parent.spawn((
+                MinimapMarker { target: entity },
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(BLIP_SIZE),
+                    height: Val::Px(BLIP_SIZE),
+                    ..default()
+                },
+                BackgroundColor(blip.color),
+                BorderRadius::MAX,
+            ));
+        });
+    }
+
+    for (marker_entity, marker) in markers.iter() {
+        if blips.get(marker.target).is_err() {
+            commands.entity(marker_entity).despawn_recursive();
+        }
+    }
+}
+
+// Position each marker over the minimap according to its entity's world position
+pub fn update_minimap_markers(
+    minimap_query: Query<&Transform, With<MinimapCamera>>,
+    blip_query: Query<&GlobalTransform, With<MinimapBlip>>,
+    mut marker_query: Query<(&MinimapMarker, &mut Node, &mut Visibility)>,
+) {
+    let Ok(minimap_transform) = minimap_query.get_single() else {
+        return;
+    };
+    let center = minimap_transform.translation;
+
+    for (marker, mut node, mut visibility) in marker_query.iter_mut() {
+        let Ok(blip_transform) = blip_query.get(marker.target) else {
+            continue;
+        };
+        let position = blip_transform.translation();
+
+        // Map world offset to 0..1 across the widget; +X is right and +Z is down
+        let u = 0.5 + (position.x - center.x) / MINIMAP_EXTENT;
+        let v = 0.5 + (position.z - center.z) / MINIMAP_EXTENT;
+
+        if (0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v) {
+            node.left = Val::Px(u * MINIMAP_WIDGET_SIZE - BLIP_SIZE * 0.5);
+            node.top = Val::Px(v * MINIMAP_WIDGET_SIZE - BLIP_SIZE * 0.5);
+            *visibility = Visibility::Inherited;
+        } else {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+// Plugin for the minimap
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, spawn_minimap)
+            .add_systems(Update, (
+                follow_player_minimap,
+                sync_minimap_markers,
+                update_minimap_markers.after(sync_minimap_markers),
+            ));
+    }
+}
@@ -0,0 +1,173 @@
+use bevy::prelude::*;
+use bevy::asset::io::{
+    AssetReader, AssetReaderError, AssetSource, AssetSourceId, ErasedAssetReader, PathStream, Reader,
+};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// Mod pack constants
+const MODS_DIR: &str = "mods";
+const LOAD_ORDER_PATH: &str = "mods/load_order.ron";
+const ASSETS_DIR: &str = "assets"; // The game's own assets, under every pack's overrides
+const WATCH_DELAY: Duration = Duration::from_millis(300); // Wait after a file changes before reloading it
+
+// Which packs load and in what order, read from mods/load_order.ron
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct LoadOrder {
+    // Pack folder names, first to last; later packs win where they overlap
+    order: Vec<String>,
+    // Packs to leave out without deleting them
+    disabled: Vec<String>,
+}
+
+// A content pack: a folder in mods/ that can hold any of
+//   assets/          files replacing the game's assets by the same path, such as textures, sounds or shaders
+//   tuning.ron       tuning values laid over assets/tuning.ron; only the values it changes are needed
+//   structures.ron   extra knockdown structure shapes
+//   scripts/         game mode scripts, like the loose ones in mods/
+#[derive(Debug, Clone)]
+pub struct ModPack {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl ModPack {
+    // Path of a file or folder inside the pack, if the pack has it
+    pub fn file(&self, name: &str) -> Option<PathBuf> {
+        let path = self.path.join(name);
+        path.exists().then_some(path)
+    }
+}
+
+// The enabled packs in load order, found before the app is built so their assets can take over from
+// the game's own
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ModPacks {
+    pub packs: Vec<ModPack>,
+}
+
+impl ModPacks {
+    // Find the packs in the mods folder. Packs named in the load order come first, in its order, and any
+    // others follow in name order.
+    pub fn discover() -> Self {
+        let load_order = match std::fs::read_to_string(LOAD_ORDER_PATH) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|error| {
+                println!("Invalid mod load order {}: {}", LOAD_ORDER_PATH, error);
+                LoadOrder::default()
+            }),
+            Err(_) => LoadOrder::default(),
+        };
+        let Ok(entries) = std::fs::read_dir(MODS_DIR) else {
+            return Self::default();
+        };
+        let mut found: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        found.sort();
+
+        for name in &load_order.order {
+            if !found.contains(name) {
+                println!("Mod pack {} is in the load order but not in {}", name, MODS_DIR);
+            }
+        }
+        let names = load_order
+            .order
+            .iter()
+            .filter(|name| found.contains(name))
+            .chain(found.iter().filter(|name| !load_order.order.contains(name)))
+            .filter(|name| !load_order.disabled.contains(name));
+        let mut packs = Vec::new();
+        for name in names {
+            if packs.iter().any(|pack: &ModPack| pack.name == *name) {
+                continue;
+            }
+            packs.push(ModPack { name: name.clone(), path: Path::new(MODS_DIR).join(name) });
+        }
+        if !packs.is_empty() {
+            let names: Vec<&str> = packs.iter().map(|pack| pack.name.as_str()).collect();
+            println!("Loading mod packs: {}", names.join(", "));
+        }
+        Self { packs }
+    }
+
+    // A file or folder from every pack that has it, in load order
+    pub fn files(&self, name: &str) -> Vec<PathBuf> {
+        self.packs.iter().filter_map(|pack| pack.file(name)).collect()
+    }
+}
+
+// Asset reader that looks in each pack's assets folder, last loaded first, before the game's own, so
+// packs can replace any asset without the code asking for it differently
+struct ModAssetReader {
+    layers: Vec<Box<dyn ErasedAssetReader>>,
+}
+
+impl ModAssetReader {
+    // Try each layer in turn, moving on only when the path isn't there
+    async fn first<'a, T>(
+        &'a self,
+        read: impl Fn(&'a dyn ErasedAssetReader) -> bevy::utils::BoxedFuture<'a, Result<T, AssetReaderError>>,
+        path: &Path,
+    ) -> Result<T, AssetReaderError> {
+        for layer in &self.layers {
+            match read(layer.as_ref()).await {
+                Err(AssetReaderError::NotFound(_)) => continue,
+                result => return result,
+            }
+        }
+        Err(AssetReaderError::NotFound(path.to_path_buf()))
+    }
+}
+
+impl AssetReader for ModAssetReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<Box<dyn Reader + 'a>, AssetReaderError> {
+        self.first(|layer| layer.read(path), path).await
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<Box<dyn Reader + 'a>, AssetReaderError> {
+        self.first(|layer| layer.read_meta(path), path).await
+    }
+
+    // Folders list from the first layer that has them, without merging in the layers below
+    async fn read_directory<'a>(&'a self, path: &'a Path) -> Result<Box<PathStream>, AssetReaderError> {
+        self.first(|layer| layer.read_directory(path), path).await
+    }
+
+    async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
+        self.first(|layer| layer.is_directory(path), path).await
+    }
+}
+
+// Route the default asset source through the packs' assets folders. Has to run before the asset plugin
+// is added; without packs the default source is left alone. Edits to the game's own assets still reload
+// while running, edits inside packs need a restart.
+pub fn register_mod_assets(app: &mut App, packs: &ModPacks) {
+    let folders = packs.files(ASSETS_DIR);
+    if folders.is_empty() {
+        return;
+    }
+    // Pack folders are relative to the working directory, while the asset reader resolves relative paths
+    // against the executable's
+    let folders: Vec<PathBuf> = folders
+        .into_iter()
+        .map(|folder| std::fs::canonicalize(&folder).unwrap_or(folder))
+        .collect();
+    app.register_asset_source(
+        AssetSourceId::Default,
+        AssetSource::build()
+            .with_reader(move || {
+                let mut layers: Vec<Box<dyn ErasedAssetReader>> = folders
+                    .iter()
+                    .rev()
+                    .map(|folder| AssetSource::get_default_reader(folder.to_string_lossy().into_owned())())
+                    .collect();
+                layers.push(AssetSource::get_default_reader(ASSETS_DIR.to_string())());
+                Box::new(ModAssetReader { layers })
+            })
+            .with_watcher(AssetSource::get_default_watcher(ASSETS_DIR.to_string(), WATCH_DELAY)),
+    );
+}
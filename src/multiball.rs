@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+use crate::enemy::enemy_contact;
+use crate::particles::{spawn_burst, ParticleEffect};
+use crate::photo_mode::PhotoModeState;
+use crate::platforms::PlatformSurfaces;
+use crate::player::{roll_sphere, Health, Player, PlayerPhysics, RollInput, PLAYER_RADIUS};
+use crate::sim::{Interpolated, SimInput, SimSet};
+use crate::terrain::get_terrain_height;
+use crate::GameState;
+use crate::tuning::LiveTuning;
+
+// Multiball constants
+const MULTIBALL_DURATION: f32 = 12.0; // Seconds the player stays split
+const SPLIT_SCALE: f32 = 0.6; // Size of each ball while split, against the full ball
+const SPLIT_BALLS: usize = 2; // Balls split off beside the player's own
+const SPLIT_OFFSET: f32 = 1.2; // Distance to each side of the player the extra balls appear
+const MAX_SPREAD: f32 = 2.5; // Distance from the player's ball an extra ball can roll before it is pulled back
+const COHESION: f32 = 8.0; // Pull back in m/s² for each meter past the spread
+const BURST_COUNT: u32 = 16; // Sparkles when the balls split, merge or one is popped
+const BURST_COLOR: Color = Color::srgb(0.3, 0.6, 1.0);
+
+// The multiball power-up: time left on it, and the health a hit has to get past while split
+#[derive(Resource, Default)]
+pub struct Multiball {
+    pub remaining: f32,
+    split: bool,
+    // Player health as of the last check, so a drop in it can be taken by a ball instead
+    guarded_health: f32,
+}
+
+impl Multiball {
+    // Split the player, or top the timer back up if already split
+    pub fn start(&mut self) {
+        self.remaining = MULTIBALL_DURATION;
+    }
+
+    // Measure hits from the player's health as it is now, e.g. after a rewind has put it back, so the
+    // change isn't taken for one
+    pub fn guard_health(&mut self, health: f32) {
+        self.guarded_health = health;
+    }
+}
+
+// One of the extra balls rolling beside the player while split
+#[derive(Component)]
+pub struct SplitBall;
+
+// Shrink the player's ball and roll two more out beside it when the power-up starts
+pub fn split_player(
+    mut commands: Commands,
+    mut multiball: ResMut<Multiball>,
+    mut player_query: Query<(&mut Transform, &mut PlayerPhysics, &Health, &Mesh3d, &MeshMaterial3d<StandardMaterial>), With<Player>>,
+) {
+    if multiball.split || multiball.remaining <= 0.0 {
+        return;
+    }
+    let Ok((mut transform, mut physics, health, mesh, material)) = player_query.get_single_mut() else {
+        return;
+    };
+    transform.scale = Vec3::splat(SPLIT_SCALE);
+    physics.radius = PLAYER_RADIUS * SPLIT_SCALE;
+
+    // Side by side across the direction of travel
+    let heading = physics.velocity.with_y(0.0).normalize_or(Vec3::NEG_Z);
+    let across = heading.cross(Vec3::Y);
+    for i in 0..SPLIT_BALLS {
+        let side = if i % 2 == 0 { 1.0 } else { -1.0 };
+        let mut position = transform.translation + across * SPLIT_OFFSET * side;
+        position.y = position.y.max(get_terrain_height(position.x, position.z) + physics.radius);
+        commands.spawn((
+            SplitBall,
+            PlayerPhysics {
+                prev_position: position,
+                ..physics.clone()
+            },
+            mesh.clone(),
+            material.clone(),
+            Transform::from_translation(position).with_scale(Vec3::splat(SPLIT_SCALE)),
+            Interpolated::new(position),
+            Name::new("Split Ball"),
+        ));
+    }
+    spawn_burst(&mut commands, ParticleEffect::sparkles(BURST_COLOR), transform.translation, BURST_COUNT);
+    multiball.split = true;
+    multiball.guarded_health = health.current;
+}
+
+// Roll the extra balls with the same input as the player's, pulling back any that stray too far
+pub fn move_split_balls(
+    input: Res<SimInput>,
+    player_query: Query<&Transform, With<Player>>,
+    mut split_query: Query<(&mut Transform, &mut PlayerPhysics), (With<SplitBall>, Without<Player>)>,
+    platforms: Res<PlatformSurfaces>,
+    tuning: Res<LiveTuning>,
+    time: Res<Time>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let delta = time.delta_secs();
+    let roll_input = RollInput::from_sim(&input);
+
+    for (mut transform, mut physics) in split_query.iter_mut() {
+        roll_sphere(&mut transform, &mut physics, &tuning.0.player, &platforms.0, roll_input, delta);
+        let offset = (player.translation - transform.translation).with_y(0.0);
+        let stray = offset.length() - MAX_SPREAD;
+        if stray > 0.0 {
+            physics.velocity += offset.normalize() * stray * COHESION * delta;
+        }
+    }
+}
+
+// Put the player back together: remove the extra balls and grow the player's to full size
+fn merge(
+    commands: &mut Commands,
+    multiball: &mut Multiball,
+    player: Option<(&mut Transform, &mut PlayerPhysics)>,
+    balls: &[(Entity, Vec3)],
+) {
+    for (entity, position) in balls {
+        spawn_burst(commands, ParticleEffect::sparkles(BURST_COLOR), *position, BURST_COUNT);
+        commands.entity(*entity).despawn_recursive();
+    }
+    if let Some((transform, physics)) = player {
+        transform.scale = Vec3::ONE;
+        physics.radius = PLAYER_RADIUS;
+        // Grown back in place, so lift it clear of the ground
+        transform.translation.y += PLAYER_RADIUS * (1.0 - SPLIT_SCALE);
+    }
+    multiball.split = false;
+    multiball.remaining = 0.0;
+}
+
+// A hit while split pops one of the extra balls instead of hurting the player; once the time runs out,
+// or there are no extra balls left, the balls merge back into one
+pub fn update_multiball(
+    mut commands: Commands,
+    mut multiball: ResMut<Multiball>,
+    mut player_query: Query<(&mut Transform, &mut PlayerPhysics, &mut Health), With<Player>>,
+    split_query: Query<(Entity, &Transform), (With<SplitBall>, Without<Player>)>,
+    time: Res<Time>,
+) {
+    if !multiball.split {
+        return;
+    }
+    let Ok((mut transform, mut physics, mut health)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    let mut balls: Vec<(Entity, Vec3)> = split_query.iter().map(|(entity, ball)| (entity, ball.translation)).collect();
+    if health.current < multiball.guarded_health {
+        if let Some((entity, position)) = balls.pop() {
+            spawn_burst(&mut commands, ParticleEffect::sparkles(BURST_COLOR), position, BURST_COUNT);
+            commands.entity(entity).despawn_recursive();
+            health.current = multiball.guarded_health;
+        }
+    }
+    multiball.guarded_health = health.current;
+
+    multiball.remaining -= time.delta_secs();
+    if multiball.remaining <= 0.0 || balls.is_empty() {
+        merge(&mut commands, &mut multiball, Some((&mut transform, &mut physics)), &balls);
+    }
+}
+
+// Leave the run as one ball; the player itself is reset to full size with everything else
+pub fn reset_multiball(
+    mut commands: Commands,
+    mut multiball: ResMut<Multiball>,
+    split_query: Query<(Entity, &Transform), With<SplitBall>>,
+) {
+    let balls: Vec<(Entity, Vec3)> = split_query.iter().map(|(entity, ball)| (entity, ball.translation)).collect();
+    merge(&mut commands, &mut multiball, None, &balls);
+}
+
+// Plugin for the multiball power-up
+pub struct MultiballPlugin;
+
+impl Plugin for MultiballPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Multiball>()
+            .add_systems(OnExit(GameState::GameOver), reset_multiball)
+            .add_systems(FixedUpdate, move_split_balls
+                .in_set(SimSet::Movement)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)))
+            // After this tick's hits, and before the death check that runs after the ticks, so a hit a
+            // ball takes can't end the run
+            .add_systems(FixedUpdate, (split_player, update_multiball)
+                .chain()
+                .in_set(SimSet::Constraints)
+                .after(enemy_contact)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)));
+    }
+}
@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use crate::terrain::get_terrain_height;
+
+// Navigation grid constants
+pub const NAV_CELL_SIZE: f32 = 2.0; // Width of a navigation cell in meters
+const NAV_MAX_CLIMB: f32 = 0.6; // Steepest rise per meter a ball can roll up
+const NAV_UPHILL_COST: f32 = 4.0; // Extra cost per meter climbed, so paths prefer going around or down
+const NAV_SEARCH_MARGIN: f32 = 20.0; // How far outside the start-goal box the search may wander
+const NAV_MAX_EXPANSIONS: usize = 20_000; // Cells searched before giving up
+
+// A cell in the navigation grid
+type Cell = (i32, i32);
+
+// Neighbouring cell offsets, with diagonals
+const NEIGHBOURS: [Cell; 8] = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+fn cell_of(position: Vec3) -> Cell {
+    (
+        (position.x / NAV_CELL_SIZE).floor() as i32,
+        (position.z / NAV_CELL_SIZE).floor() as i32,
+    )
+}
+
+fn cell_center(cell: Cell) -> Vec2 {
+    Vec2::new(
+        (cell.0 as f32 + 0.5) * NAV_CELL_SIZE,
+        (cell.1 as f32 + 0.5) * NAV_CELL_SIZE,
+    )
+}
+
+// Terrain heights sampled at cell centers, filled in as the search reaches them
+struct HeightCache(HashMap<Cell, f32>);
+
+impl HeightCache {
+    fn height(&mut self, cell: Cell) -> f32 {
+        *self.0.entry(cell).or_insert_with(|| {
+            let center = cell_center(cell);
+            get_terrain_height(center.x, center.y)
+        })
+    }
+}
+
+// Find a rollable path over the terrain with A* on a grid of NAV_CELL_SIZE cells.
+// Returns points on the terrain surface from start to goal, or None if the goal can't be reached.
+pub fn find_path(start: Vec3, goal: Vec3) -> Option<Vec<Vec3>> {
+    let start_cell = cell_of(start);
+    let goal_cell = cell_of(goal);
+    let margin = (NAV_SEARCH_MARGIN / NAV_CELL_SIZE).ceil() as i32;
+    let min = (start_cell.0.min(goal_cell.0) - margin, start_cell.1.min(goal_cell.1) - margin);
+    let max = (start_cell.0.max(goal_cell.0) + margin, start_cell.1.max(goal_cell.1) + margin);
+
+    let mut heights = HeightCache(HashMap::new());
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<Cell, Cell> = HashMap::new();
+    let mut cost_so_far: HashMap<Cell, f32> = HashMap::new();
+    let heuristic = |cell: Cell| cell_center(cell).distance(cell_center(goal_cell));
+
+    // Costs are ordered as whole millimeters, which is plenty of precision
+    let key = |cost: f32| (cost * 1000.0) as u64;
+    open.push(Reverse((key(heuristic(start_cell)), start_cell)));
+    cost_so_far.insert(start_cell, 0.0);
+
+    let mut expansions = 0;
+    while let Some(Reverse((_, current))) = open.pop() {
+        if current == goal_cell {
+            break;
+        }
+        expansions += 1;
+        if expansions > NAV_MAX_EXPANSIONS {
+            return None;
+        }
+
+        let current_cost = cost_so_far[&current];
+        let current_height = heights.height(current);
+        for (dx, dz) in NEIGHBOURS {
+            let next = (current.0 + dx, current.1 + dz);
+            if next.0 < min.0 || next.1 < min.1 || next.0 > max.0 || next.1 > max.1 {
+                continue;
+            }
+            let run = NAV_CELL_SIZE * ((dx * dx + dz * dz) as f32).sqrt();
+            let rise = heights.height(next) - current_height;
+            if rise / run > NAV_MAX_CLIMB {
+                continue;
+            }
+            let cost = current_cost + run + rise.max(0.0) * NAV_UPHILL_COST;
+            if cost_so_far.get(&next).is_none_or(|&known| cost < known) {
+                cost_so_far.insert(next, cost);
+                came_from.insert(next, current);
+                open.push(Reverse((key(cost + heuristic(next)), next)));
+            }
+        }
+    }
+
+    if !cost_so_far.contains_key(&goal_cell) {
+        return None;
+    }
+
+    // Walk back from the goal, then flip into start-to-goal order
+    let mut cells = vec![goal_cell];
+    let mut current = goal_cell;
+    while let Some(&previous) = came_from.get(&current) {
+        cells.push(previous);
+        current = previous;
+    }
+    cells.reverse();
+
+    let mut path: Vec<Vec3> = cells
+        .into_iter()
+        .skip(1)
+        .map(|cell| {
+            let center = cell_center(cell);
+            Vec3::new(center.x, heights.height(cell), center.y)
+        })
+        .collect();
+    // End exactly on the goal rather than its cell center
+    path.pop();
+    path.push(goal);
+    Some(path)
+}
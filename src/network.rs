@@ -0,0 +1,1110 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{SocketAddr, UdpSocket};
+use crate::player::{Health, Player, PlayerPhysics};
+use crate::profile::ActiveProfile;
+use crate::projectile::{ballistic_position, spawn_boulder, BoulderLaunch, Hostile, Projectile, ProjectileAssets};
+use crate::terrain::{add_deformation, clear_deformations, deformations, ChunkManager, Deformation, TerrainConfig};
+use crate::tuning::LiveTuning;
+use crate::minimap::MinimapBlip;
+use crate::party::PartyGuest;
+use crate::tether::TetherMode;
+use crate::{GameMode, GameState};
+
+// Session constants
+pub const DEFAULT_PORT: u16 = 7878; // UDP port a host listens on
+pub const MAX_PLAYERS: usize = 4; // Players in a session, host included
+const MAX_SPECTATORS: usize = 4; // Spectators in a session, on top of the players
+const HOST_ID: u8 = 0; // The player who opens a session is player 0
+const SEND_INTERVAL: f32 = 0.05; // Seconds between state updates sent to the other players
+const PEER_TIMEOUT: f32 = 5.0; // Seconds of silence before a player is dropped
+const RECONNECT_GRACE: f32 = 30.0; // Seconds a dropped player's place is kept for them to come back
+const REJOIN_INTERVAL: f32 = 1.0; // Seconds between attempts to rejoin after losing the host
+const TAKEOVER_DELAY: f32 = 2.0; // Seconds the elected player waits for the old host before taking over
+const MAX_PACKET: usize = 8192; // Largest message read from the socket; resyncs are the biggest
+
+// Dodgeball constants
+const HIT_DAMAGE: f32 = 35.0; // Health taken by a direct hit from another player's boulder
+const PLAYER_RADIUS: f32 = 0.5; // Radius of every player ball
+const REMOTE_SMOOTHING: f32 = 12.0; // How quickly remote players catch up to their reported position
+const REMOTE_BOULDER_COLOR: Color = Color::srgb(0.55, 0.45, 0.35);
+
+// Colors for players 0 to 3, on the minimap and their balls
+const PLAYER_COLORS: [Color; MAX_PLAYERS] = [
+    Color::srgb(0.9, 0.9, 0.2),
+    Color::srgb(0.2, 0.6, 1.0),
+    Color::srgb(0.3, 0.9, 0.3),
+    Color::srgb(0.9, 0.4, 0.9),
+];
+
+// A player as listed in the lobby
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LobbyPlayer {
+    pub id: u8,
+    pub name: String,
+    // Watching rather than playing
+    pub spectator: bool,
+}
+
+// Everything sent between host and clients; clients only talk to the host, which relays to the rest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum NetMessage {
+    // A client asking to join, as a player or a spectator, and the host's answers; a client coming back
+    // after losing its connection asks for its old id
+    Join { name: String, spectator: bool, rejoin: Option<u8> },
+    Welcome { id: u8 },
+    Full,
+    // A client adding a second player playing split-screen on the same machine, and the host's answer
+    JoinGuest { name: String, rejoin: Option<u8> },
+    GuestWelcome { id: u8 },
+    // Who is in the session and where each client can be reached, sent by the host whenever it changes;
+    // the addresses let the clients carry on if the host goes away
+    Lobby { players: Vec<LobbyPlayer>, addresses: Vec<(u8, SocketAddr)> },
+    // Match state for a player coming back after a dropped connection
+    Resync { knockouts: Vec<(u8, u32)>, deformations: Vec<Deformation>, projectiles: Vec<LiveProjectile> },
+    // The host starting the match in a world
+    Start { seed: u32 },
+    // Sent regularly so the host knows a client in the lobby is still there
+    KeepAlive,
+    // Where a player's ball is
+    PlayerState { id: u8, position: Vec3, velocity: Vec3 },
+    // A player threw a boulder
+    Throw { owner: u8, start: Vec3, target: Vec3, velocity: Vec3, blast_radius: f32 },
+    // The host decided a boulder hit a player
+    Hit { victim: u8, attacker: u8, damage: f32 },
+    // A player's hits ran out, reported by that player
+    Knockout { victim: u8, attacker: u8 },
+    // A line typed into the chat
+    Chat { id: u8, text: String },
+    Leave { id: u8 },
+}
+
+// A boulder in the air, as sent to a player resyncing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveProjectile {
+    pub owner: u8,
+    pub start: Vec3,
+    pub target: Vec3,
+    pub velocity: Vec3,
+    pub blast_radius: f32,
+    // Seconds it has been flying
+    pub age: f32,
+}
+
+// A client looking for the session again after the host went quiet
+#[derive(Debug, Clone)]
+struct Reconnect {
+    // The old host, in case only we lost the connection, and the player elected to take over
+    targets: Vec<SocketAddr>,
+    // Elected to take over from the old host ourselves
+    elected: bool,
+    elapsed: f32,
+    retry_timer: f32,
+}
+
+// What the menu asks of the networking layer
+#[derive(Event, Debug, Clone)]
+pub enum NetRequest {
+    Host,
+    Join { address: String, spectator: bool },
+    // Add or remove a second player on this machine
+    AddGuest,
+    RemoveGuest,
+    // Switch how the two players on this machine are tethered
+    CycleTether,
+    Start,
+    Leave,
+}
+
+// Things other players did that the rest of the game shows, like chat lines and knockouts
+#[derive(Event, Debug, Clone)]
+pub enum NetNotice {
+    Chat { id: u8, text: String },
+    Knockout { victim: u8, attacker: u8 },
+}
+
+// A client as the host sees it
+#[derive(Debug, Clone)]
+pub struct Peer {
+    pub id: u8,
+    pub addr: SocketAddr,
+    pub last_heard: f32,
+    // Spectators only receive; anything else they send is ignored
+    pub spectator: bool,
+}
+
+// Whether this game runs the session or joined one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetRole {
+    Host,
+    Client { host: SocketAddr },
+}
+
+// An open LAN session; present only while hosting or joined
+#[derive(Resource)]
+pub struct NetSession {
+    pub role: NetRole,
+    socket: UdpSocket,
+    // This player's id, once the host has welcomed us
+    pub local_id: Option<u8>,
+    // Joined to watch: no ball of our own, no throws and no say in hits
+    pub spectating: bool,
+    // Id of a second player on this machine, playing split-screen
+    pub guest_id: Option<u8>,
+    // Rope between the main player and the guest, picked in the lobby
+    pub tether: TetherMode,
+    pub players: Vec<LobbyPlayer>,
+    // Clients, on the host only
+    peers: Vec<Peer>,
+    // Players who lost their connection, with the seconds left to come back, on the host only
+    reserved: Vec<(u8, f32)>,
+    // Where each client can be reached, as last told by the host, on clients
+    addresses: HashMap<u8, SocketAddr>,
+    // Looking for the session again after losing the host, on clients
+    reconnect: Option<Reconnect>,
+    // Knockouts scored by each player this session
+    pub knockouts: HashMap<u8, u32>,
+    // Clients that came back and need the match state, on the host
+    resync_targets: Vec<SocketAddr>,
+    // Match state from the host, waiting to be applied
+    pending_resync: Option<NetMessage>,
+    // Last reported position and velocity of every other player
+    pub remote_states: HashMap<u8, (Vec3, Vec3)>,
+    // Boulders thrown by other players, waiting to be spawned here
+    pending_throws: Vec<NetMessage>,
+    // Hits on this machine's players as (victim, attacker, damage), waiting to be applied
+    pending_hits: Vec<(u8, u8, f32)>,
+    send_timer: f32,
+    // Seconds since the host was last heard from, on clients
+    host_silence: f32,
+}
+
+impl NetSession {
+    // Listen for clients on the default port
+    pub fn host(name: &str) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", DEFAULT_PORT))?;
+        socket.set_nonblocking(true)?;
+        let mut session = Self::new(NetRole::Host, socket);
+        session.local_id = Some(HOST_ID);
+        session.players.push(LobbyPlayer { id: HOST_ID, name: name.to_string(), spectator: false });
+        Ok(session)
+    }
+
+    // Ask a host to let us in, to play or to watch; the port defaults to DEFAULT_PORT
+    pub fn join(address: &str, name: &str, spectator: bool) -> std::io::Result<Self> {
+        let host = parse_address(address)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("invalid address '{}'", address)))?;
+        let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+        socket.set_nonblocking(true)?;
+        let mut session = Self::new(NetRole::Client { host }, socket);
+        session.spectating = spectator;
+        session.send_to(&NetMessage::Join { name: name.to_string(), spectator, rejoin: None }, host);
+        Ok(session)
+    }
+
+    fn new(role: NetRole, socket: UdpSocket) -> Self {
+        Self {
+            role,
+            socket,
+            local_id: None,
+            spectating: false,
+            guest_id: None,
+            tether: TetherMode::Off,
+            players: Vec::new(),
+            peers: Vec::new(),
+            reserved: Vec::new(),
+            addresses: HashMap::new(),
+            reconnect: None,
+            knockouts: HashMap::new(),
+            resync_targets: Vec::new(),
+            pending_resync: None,
+            remote_states: HashMap::new(),
+            pending_throws: Vec::new(),
+            pending_hits: Vec::new(),
+            send_timer: 0.0,
+            host_silence: 0.0,
+        }
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.role == NetRole::Host
+    }
+
+    // Whether a player plays on this machine, as the main player or the split-screen guest
+    pub fn is_local(&self, id: u8) -> bool {
+        self.local_id == Some(id) || self.guest_id == Some(id)
+    }
+
+    pub fn player_name(&self, id: u8) -> Option<&str> {
+        self.players.iter().find(|player| player.id == id).map(|player| player.name.as_str())
+    }
+
+    // Whether a message from player id really came from them; clients trust the host, which has
+    // already checked
+    fn sent_by(&self, id: u8, from: SocketAddr) -> bool {
+        match self.role {
+            NetRole::Host => self.peers.iter().any(|peer| peer.addr == from && peer.id == id),
+            NetRole::Client { .. } => true,
+        }
+    }
+
+    // Whether a message about player id's ball came from that player, and not from a spectator or
+    // someone else speaking for them
+    fn speaks_for(&self, id: u8, from: SocketAddr) -> bool {
+        self.sent_by(id, from) && !self.players.iter().any(|player| player.id == id && player.spectator)
+    }
+
+    fn send_to(&self, message: &NetMessage, addr: SocketAddr) {
+        match ron::to_string(message) {
+            Ok(text) => {
+                if let Err(error) = self.socket.send_to(text.as_bytes(), addr) {
+                    println!("Failed to send to {}: {}", addr, error);
+                }
+            }
+            Err(error) => println!("Failed to encode {:?}: {}", message, error),
+        }
+    }
+
+    // Every client machine once, though a machine with a split-screen guest has two peers
+    fn peer_addresses(&self) -> Vec<SocketAddr> {
+        let mut addresses: Vec<SocketAddr> = Vec::new();
+        for peer in self.peers.iter() {
+            if !addresses.contains(&peer.addr) {
+                addresses.push(peer.addr);
+            }
+        }
+        addresses
+    }
+
+    // Send to everyone else: from the host to every client, from a client to the host for relaying
+    pub fn broadcast(&self, message: &NetMessage) {
+        match self.role {
+            NetRole::Host => {
+                for addr in self.peer_addresses() {
+                    self.send_to(message, addr);
+                }
+            }
+            NetRole::Client { host } => self.send_to(message, host),
+        }
+    }
+
+    // Relay a client's message to every other client
+    fn relay(&self, message: &NetMessage, from: SocketAddr) {
+        for addr in self.peer_addresses().into_iter().filter(|addr| *addr != from) {
+            self.send_to(message, addr);
+        }
+    }
+
+    // First free player id, or None when every player slot is taken
+    fn free_player_id(&self) -> Option<u8> {
+        (1..MAX_PLAYERS as u8).find(|id| self.players.iter().all(|player| player.id != *id))
+    }
+
+    // Add a second player on this machine; the host takes a slot right away, a client asks the host
+    pub fn add_guest(&mut self, name: &str) {
+        if self.guest_id.is_some() || self.spectating {
+            return;
+        }
+        match self.role {
+            NetRole::Host => {
+                let Some(id) = self.free_player_id() else {
+                    println!("The session is full");
+                    return;
+                };
+                println!("{} joined as player {}", name, id + 1);
+                self.guest_id = Some(id);
+                self.players.push(LobbyPlayer { id, name: name.to_string(), spectator: false });
+                self.broadcast(&self.lobby_message());
+            }
+            NetRole::Client { host } => self.send_to(&NetMessage::JoinGuest { name: name.to_string(), rejoin: None }, host),
+        }
+    }
+
+    // Let the second player on this machine leave, keeping the session
+    pub fn remove_guest(&mut self) {
+        let Some(id) = self.guest_id.take() else {
+            return;
+        };
+        if self.is_host() {
+            self.remove_player(id);
+        } else {
+            self.broadcast(&NetMessage::Leave { id });
+        }
+    }
+
+    // Every message waiting on the socket
+    fn receive(&self) -> Vec<(NetMessage, SocketAddr)> {
+        let mut messages = Vec::new();
+        let mut buffer = [0u8; MAX_PACKET];
+        while let Ok((length, addr)) = self.socket.recv_from(&mut buffer) {
+            let decoded = std::str::from_utf8(&buffer[..length]).ok().and_then(|text| ron::from_str(text).ok());
+            match decoded {
+                Some(message) => messages.push((message, addr)),
+                None => println!("Ignored a malformed packet from {}", addr),
+            }
+        }
+        messages
+    }
+
+    fn lobby_message(&self) -> NetMessage {
+        let addresses = self.peers.iter().map(|peer| (peer.id, peer.addr)).collect();
+        NetMessage::Lobby { players: self.players.clone(), addresses }
+    }
+
+    // Drop a player from the session and tell everyone
+    fn remove_player(&mut self, id: u8) {
+        self.players.retain(|player| player.id != id);
+        self.peers.retain(|peer| peer.id != id);
+        self.reserved.retain(|(reserved, _)| *reserved != id);
+        self.remote_states.remove(&id);
+        if self.is_host() {
+            self.broadcast(&self.lobby_message());
+        }
+    }
+
+    // Keep the place of a player whose connection dropped, so they can come back to it
+    fn drop_peer(&mut self, id: u8) {
+        self.peers.retain(|peer| peer.id != id);
+        self.remote_states.remove(&id);
+        self.reserved.push((id, RECONNECT_GRACE));
+        self.broadcast(&self.lobby_message());
+    }
+
+    // Count a knockout for the player who scored it
+    fn record_knockout(&mut self, attacker: u8) {
+        *self.knockouts.entry(attacker).or_insert(0) += 1;
+    }
+
+    // Players on the host's machine: the ones the host lists no address for
+    fn host_ids(&self) -> Vec<u8> {
+        self.players.iter().map(|player| player.id).filter(|id| !self.addresses.contains_key(id)).collect()
+    }
+
+    // Who takes over if the host goes away: the lowest player id on a client machine. Every client
+    // works this out from the same lobby, so they all pick the same one.
+    fn elect_host(&self) -> Option<(u8, SocketAddr)> {
+        self.players
+            .iter()
+            .filter(|player| !player.spectator)
+            .filter_map(|player| self.addresses.get(&player.id).map(|addr| (player.id, *addr)))
+            .min_by_key(|(id, _)| *id)
+    }
+
+    // Start looking for the session again after the host went quiet: try the old host, in case only
+    // we were cut off, and the elected player, in case the host is really gone
+    fn start_reconnect(&mut self) {
+        let NetRole::Client { host } = self.role else {
+            return;
+        };
+        println!("Lost connection to the host; reconnecting");
+        let mut targets = vec![host];
+        let mut elected = false;
+        match self.elect_host() {
+            Some((id, _)) if self.is_local(id) => elected = true,
+            Some((_, addr)) => targets.push(addr),
+            None => {}
+        }
+        self.reconnect = Some(Reconnect { targets, elected, elapsed: 0.0, retry_timer: 0.0 });
+    }
+
+    // Ask to rejoin every so often, take over when elected and no one answered, or give up after the
+    // grace period; returns false once the session is lost
+    fn update_reconnect(&mut self, delta: f32) -> bool {
+        let Some(mut reconnect) = self.reconnect.take() else {
+            return true;
+        };
+        reconnect.elapsed += delta;
+        reconnect.retry_timer -= delta;
+        if reconnect.elapsed > RECONNECT_GRACE {
+            println!("Could not reconnect to the session");
+            return false;
+        }
+        if reconnect.elected && reconnect.elapsed >= TAKEOVER_DELAY {
+            self.take_over();
+            return true;
+        }
+        if reconnect.retry_timer <= 0.0 {
+            reconnect.retry_timer = REJOIN_INTERVAL;
+            if let Some(id) = self.local_id {
+                let name = self.player_name(id).unwrap_or_default().to_string();
+                let rejoin = NetMessage::Join { name, spectator: self.spectating, rejoin: Some(id) };
+                for addr in reconnect.targets.iter() {
+                    self.send_to(&rejoin, *addr);
+                }
+            }
+        }
+        self.reconnect = Some(reconnect);
+        true
+    }
+
+    // Become the host after the old one went away, keeping everyone else's place until they rejoin
+    fn take_over(&mut self) {
+        println!("The host is gone; taking over the session");
+        for id in self.host_ids() {
+            self.players.retain(|player| player.id != id);
+            self.remote_states.remove(&id);
+        }
+        self.reserved = self
+            .players
+            .iter()
+            .filter(|player| !self.is_local(player.id))
+            .map(|player| (player.id, RECONNECT_GRACE))
+            .collect();
+        self.addresses.clear();
+        self.peers.clear();
+        self.reconnect = None;
+        self.role = NetRole::Host;
+    }
+
+    // Welcome a player back into the place they held before their connection dropped
+    fn restore_peer(&mut self, id: u8, addr: SocketAddr) {
+        let spectator = self.players.iter().any(|player| player.id == id && player.spectator);
+        println!("Player {} is back", id + 1);
+        self.reserved.retain(|(reserved, _)| *reserved != id);
+        self.peers.push(Peer { id, addr, last_heard: 0.0, spectator });
+        if !self.resync_targets.contains(&addr) {
+            self.resync_targets.push(addr);
+        }
+    }
+}
+
+// "192.168.1.20" or "192.168.1.20:7878"
+pub fn parse_address(address: &str) -> Option<SocketAddr> {
+    address
+        .parse()
+        .ok()
+        .or_else(|| format!("{}:{}", address, DEFAULT_PORT).parse().ok())
+}
+
+// Run condition: this game joined a session to watch rather than play
+pub fn spectating(session: Option<Res<NetSession>>) -> bool {
+    session.is_some_and(|session| session.spectating)
+}
+
+// Color of a player by id
+pub fn player_color(id: u8) -> Color {
+    PLAYER_COLORS[id as usize % MAX_PLAYERS]
+}
+
+// Another player's ball, moved to where they report being
+#[derive(Component)]
+pub struct RemotePlayer {
+    pub id: u8,
+}
+
+// Which player threw a boulder; boulders thrown by other players carry it from the start
+#[derive(Component)]
+pub struct NetProjectile {
+    pub owner: u8,
+}
+
+// A boulder the host has already checked for hits
+#[derive(Component)]
+pub struct HitResolved;
+
+// Open, start or leave a session when the menu asks
+pub fn handle_net_requests(
+    mut commands: Commands,
+    mut requests: EventReader<NetRequest>,
+    mut session: Option<ResMut<NetSession>>,
+    profile: Res<ActiveProfile>,
+    gamepads: Query<(), With<Gamepad>>,
+    terrain_config: Res<TerrainConfig>,
+    mut mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for request in requests.read() {
+        match request {
+            NetRequest::Host | NetRequest::Join { .. } if session.is_some() => {}
+            NetRequest::Host => match NetSession::host(&profile.name) {
+                Ok(session) => {
+                    println!("Hosting on port {}", DEFAULT_PORT);
+                    commands.insert_resource(session);
+                }
+                Err(error) => println!("Failed to host: {}", error),
+            },
+            NetRequest::Join { address, spectator } => match NetSession::join(address, &profile.name, *spectator) {
+                Ok(session) => {
+                    println!("Joining {}{}", address, if *spectator { " as a spectator" } else { "" });
+                    commands.insert_resource(session);
+                }
+                Err(error) => println!("Failed to join {}: {}", address, error),
+            },
+            NetRequest::AddGuest => {
+                let Some(session) = session.as_mut() else {
+                    continue;
+                };
+                // The second player plays on a gamepad while the first keeps the keyboard and mouse
+                if gamepads.is_empty() {
+                    println!("Connect a gamepad for the second player");
+                    continue;
+                }
+                session.add_guest(&format!("{} 2", profile.name));
+            }
+            NetRequest::RemoveGuest => {
+                if let Some(session) = session.as_mut() {
+                    session.remove_guest();
+                }
+            }
+            NetRequest::CycleTether => {
+                if let Some(session) = session.as_mut() {
+                    session.tether = session.tether.next();
+                }
+            }
+            NetRequest::Start => {
+                let Some(session) = session.as_ref().filter(|session| session.is_host()) else {
+                    continue;
+                };
+                session.broadcast(&NetMessage::Start { seed: terrain_config.seed });
+                *mode = GameMode::FreePlay;
+                next_state.set(GameState::Loading);
+            }
+            NetRequest::Leave => {
+                if let Some(session) = session.as_ref() {
+                    for id in session.guest_id.into_iter().chain(session.local_id) {
+                        session.broadcast(&NetMessage::Leave { id });
+                    }
+                    commands.remove_resource::<NetSession>();
+                }
+            }
+        }
+    }
+}
+
+// Read everything the other players sent: lobby changes, positions, throws and hits
+pub fn receive_messages(
+    mut commands: Commands,
+    mut session: ResMut<NetSession>,
+    mut terrain_config: ResMut<TerrainConfig>,
+    mut mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut notices: EventWriter<NetNotice>,
+    time: Res<Time<Real>>,
+) {
+    let delta = time.delta_secs();
+    {
+        // Timers tick every frame; only real lobby changes should count as a change to the session
+        let session = session.bypass_change_detection();
+        session.host_silence += delta;
+        for peer in session.peers.iter_mut() {
+            peer.last_heard += delta;
+        }
+        for (_, time_left) in session.reserved.iter_mut() {
+            *time_left -= delta;
+        }
+    }
+
+    for (message, from) in session.receive() {
+        let session_ref = session.bypass_change_detection();
+        for peer in session_ref.peers.iter_mut().filter(|peer| peer.addr == from) {
+            peer.last_heard = 0.0;
+        }
+        if let NetRole::Client { host } = session_ref.role {
+            // While reconnecting, the old host or the player taking over may answer
+            let rejoining = session_ref.reconnect.as_ref().is_some_and(|reconnect| reconnect.targets.contains(&from));
+            if from != host && !rejoining {
+                continue;
+            }
+            if from == host {
+                session_ref.host_silence = 0.0;
+            }
+        } else {
+            // Only a join may come from outside the session, and nobody tells the host what the session is
+            let known = session_ref.peers.iter().any(|peer| peer.addr == from);
+            let host_only = matches!(
+                message,
+                NetMessage::Hit { .. }
+                    | NetMessage::Start { .. }
+                    | NetMessage::Welcome { .. }
+                    | NetMessage::Full
+                    | NetMessage::Lobby { .. }
+                    | NetMessage::Resync { .. }
+                    | NetMessage::GuestWelcome { .. }
+            );
+            if host_only || (!known && !matches!(message, NetMessage::Join { .. })) {
+                continue;
+            }
+        }
+
+        match message {
+            NetMessage::Join { name, spectator, rejoin } if session.is_host() => {
+                // Someone already here asking again missed our welcome
+                let existing: Vec<u8> = session.peers.iter().filter(|peer| peer.addr == from).map(|peer| peer.id).collect();
+                if !existing.is_empty() {
+                    if let Some(id) = rejoin.filter(|id| existing.contains(id)) {
+                        session.send_to(&NetMessage::Welcome { id }, from);
+                    }
+                    continue;
+                }
+                // A player coming back gets their old place while it is still kept for them
+                if let Some(id) = rejoin.filter(|id| session.reserved.iter().any(|(reserved, _)| reserved == id)) {
+                    session.restore_peer(id, from);
+                    session.send_to(&NetMessage::Welcome { id }, from);
+                    session.broadcast(&session.lobby_message());
+                    continue;
+                }
+                // Spectators get ids after the players', so they never take a player's slot or color
+                let mut ids = if spectator {
+                    MAX_PLAYERS as u8..(MAX_PLAYERS + MAX_SPECTATORS) as u8
+                } else {
+                    1..MAX_PLAYERS as u8
+                };
+                let free_id = ids.find(|id| session.players.iter().all(|player| player.id != *id));
+                let Some(id) = free_id else {
+                    session.send_to(&NetMessage::Full, from);
+                    continue;
+                };
+                if spectator {
+                    println!("{} joined as a spectator", name);
+                } else {
+                    println!("{} joined as player {}", name, id + 1);
+                }
+                session.peers.push(Peer { id, addr: from, last_heard: 0.0, spectator });
+                session.players.push(LobbyPlayer { id, name, spectator });
+                session.send_to(&NetMessage::Welcome { id }, from);
+                session.broadcast(&session.lobby_message());
+            }
+            NetMessage::JoinGuest { name, rejoin } if session.is_host() => {
+                // Only a player already in the session can bring a second player, and only one
+                let from_player = session.peers.iter().any(|peer| peer.addr == from && !peer.spectator);
+                let peers_there = session.peers.iter().filter(|peer| peer.addr == from).count();
+                if !from_player || peers_there > 1 {
+                    continue;
+                }
+                if let Some(id) = rejoin.filter(|id| session.reserved.iter().any(|(reserved, _)| reserved == id)) {
+                    session.restore_peer(id, from);
+                    session.send_to(&NetMessage::GuestWelcome { id }, from);
+                    session.broadcast(&session.lobby_message());
+                    continue;
+                }
+                let Some(id) = session.free_player_id() else {
+                    session.send_to(&NetMessage::Full, from);
+                    continue;
+                };
+                println!("{} joined as player {}", name, id + 1);
+                session.peers.push(Peer { id, addr: from, last_heard: 0.0, spectator: false });
+                session.players.push(LobbyPlayer { id, name, spectator: false });
+                session.send_to(&NetMessage::GuestWelcome { id }, from);
+                session.broadcast(&session.lobby_message());
+            }
+            NetMessage::GuestWelcome { id } => {
+                println!("Second player joined as player {}", id + 1);
+                session.guest_id = Some(id);
+            }
+            NetMessage::Welcome { id } => {
+                if session.reconnect.take().is_some() {
+                    // Whoever answered runs the session now
+                    println!("Reconnected to the session");
+                    session.role = NetRole::Client { host: from };
+                    session.host_silence = 0.0;
+                    if let Some(guest) = session.guest_id {
+                        let name = session.player_name(guest).unwrap_or_default().to_string();
+                        session.send_to(&NetMessage::JoinGuest { name, rejoin: Some(guest) }, from);
+                    }
+                } else if session.spectating {
+                    println!("Joined as a spectator");
+                } else {
+                    println!("Joined as player {}", id + 1);
+                }
+                session.local_id = Some(id);
+            }
+            NetMessage::Full => {
+                println!("The session is full");
+                // A refused second player doesn't cost the first one their place
+                if session.local_id.is_none() {
+                    commands.remove_resource::<NetSession>();
+                    return;
+                }
+            }
+            NetMessage::Lobby { players, addresses } => {
+                session.bypass_change_detection().addresses = addresses.into_iter().collect();
+                if session.players != players {
+                    session.players = players;
+                }
+                // Forget the balls of players who are gone
+                let ids: Vec<u8> = session.players.iter().map(|player| player.id).collect();
+                session.bypass_change_detection().remote_states.retain(|id, _| ids.contains(id));
+            }
+            NetMessage::Resync { .. } => {
+                session.bypass_change_detection().pending_resync = Some(message);
+            }
+            NetMessage::Start { seed } => {
+                terrain_config.seed = seed;
+                *mode = GameMode::FreePlay;
+                next_state.set(GameState::Loading);
+            }
+            NetMessage::KeepAlive => {}
+            NetMessage::PlayerState { id, position, velocity } => {
+                if !session.speaks_for(id, from) {
+                    continue;
+                }
+                if session.is_host() {
+                    session.relay(&message, from);
+                }
+                if !session.is_local(id) {
+                    session.bypass_change_detection().remote_states.insert(id, (position, velocity));
+                }
+            }
+            NetMessage::Throw { owner, .. } => {
+                if !session.speaks_for(owner, from) {
+                    continue;
+                }
+                if session.is_host() {
+                    session.relay(&message, from);
+                }
+                if !session.is_local(owner) {
+                    session.bypass_change_detection().pending_throws.push(message);
+                }
+            }
+            NetMessage::Hit { victim, attacker, damage } => {
+                if session.is_local(victim) {
+                    session.bypass_change_detection().pending_hits.push((victim, attacker, damage));
+                }
+            }
+            NetMessage::Knockout { victim, attacker } => {
+                if !session.sent_by(victim, from) {
+                    continue;
+                }
+                if session.is_host() {
+                    session.relay(&message, from);
+                }
+                session.record_knockout(attacker);
+                notices.send(NetNotice::Knockout { victim, attacker });
+            }
+            NetMessage::Chat { id, ref text } => {
+                if !session.sent_by(id, from) {
+                    continue;
+                }
+                if session.is_host() {
+                    session.relay(&message, from);
+                }
+                notices.send(NetNotice::Chat { id, text: text.clone() });
+            }
+            NetMessage::Leave { id } => {
+                if !session.sent_by(id, from) {
+                    continue;
+                }
+                if session.is_host() {
+                    println!("Player {} left", id + 1);
+                    session.remove_player(id);
+                } else {
+                    session.remove_player(id);
+                    // The host leaving hands the session to someone else rather than ending it
+                    if session.host_ids().is_empty() && session.reconnect.is_none() {
+                        println!("The host left the session");
+                        session.start_reconnect();
+                    }
+                }
+            }
+            NetMessage::Join { .. } | NetMessage::JoinGuest { .. } => {}
+        }
+    }
+
+    // Keep the places of players that went quiet for a while, then give them up
+    let silent: Vec<u8> = session.peers.iter().filter(|peer| peer.last_heard > PEER_TIMEOUT).map(|peer| peer.id).collect();
+    for id in silent {
+        println!("Player {} lost connection; keeping their place for {:.0} seconds", id + 1, RECONNECT_GRACE);
+        session.drop_peer(id);
+    }
+    let expired: Vec<u8> = session.reserved.iter().filter(|(_, time_left)| *time_left <= 0.0).map(|(id, _)| *id).collect();
+    for id in expired {
+        println!("Player {} did not come back", id + 1);
+        session.remove_player(id);
+    }
+
+    // Find the session again if the host went quiet, through the old host or whoever takes over
+    if !session.is_host() {
+        if session.host_silence > PEER_TIMEOUT && session.reconnect.is_none() {
+            session.bypass_change_detection().start_reconnect();
+        }
+        if !session.bypass_change_detection().update_reconnect(delta) {
+            commands.remove_resource::<NetSession>();
+        }
+    }
+}
+
+// Send the match state to players who just came back: knockouts, terrain changes and boulders in the air
+pub fn send_resyncs(mut session: ResMut<NetSession>, projectile_query: Query<(&Projectile, &NetProjectile)>) {
+    if session.resync_targets.is_empty() {
+        return;
+    }
+    let projectiles = projectile_query
+        .iter()
+        .filter(|(projectile, _)| !projectile.stuck)
+        .map(|(projectile, net)| LiveProjectile {
+            owner: net.owner,
+            start: projectile.start_position,
+            target: projectile.target_position,
+            velocity: projectile.initial_velocity,
+            blast_radius: projectile.blast_radius,
+            age: projectile.age,
+        })
+        .collect();
+    let message = NetMessage::Resync {
+        knockouts: session.knockouts.iter().map(|(id, count)| (*id, *count)).collect(),
+        deformations: deformations(),
+        projectiles,
+    };
+    let session = session.bypass_change_detection();
+    for addr in std::mem::take(&mut session.resync_targets) {
+        session.send_to(&message, addr);
+    }
+}
+
+// Take on the host's match state after coming back
+pub fn apply_resync(
+    mut commands: Commands,
+    mut session: ResMut<NetSession>,
+    mut chunk_manager: ResMut<ChunkManager>,
+    projectile_query: Query<(Entity, &NetProjectile)>,
+    mut projectile_assets: ResMut<ProjectileAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    tuning: Res<LiveTuning>,
+) {
+    let Some(NetMessage::Resync { knockouts, deformations, projectiles }) = session.bypass_change_detection().pending_resync.take() else {
+        return;
+    };
+    session.knockouts = knockouts.into_iter().collect();
+    clear_deformations(&mut commands, &mut chunk_manager);
+    for deformation in deformations {
+        add_deformation(&mut commands, &mut chunk_manager, deformation);
+    }
+
+    // Replace the other players' boulders with the host's, picking each flight up where it is now
+    for (entity, net) in projectile_query.iter() {
+        if !session.is_local(net.owner) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    let tuning = &tuning.0.projectile;
+    for live in projectiles.into_iter().filter(|live| !session.is_local(live.owner)) {
+        let start = ballistic_position(tuning, live.start, live.velocity, live.age);
+        let velocity = live.velocity - Vec3::Y * tuning.gravity * live.age;
+        let boulder = spawn_boulder(&mut commands, &mut projectile_assets, &mut materials, tuning, BoulderLaunch { start, target: live.target, velocity, color: REMOTE_BOULDER_COLOR, blast_radius: live.blast_radius });
+        commands.entity(boulder).insert(NetProjectile { owner: live.owner });
+    }
+}
+
+// Tell the other players where this player is, or just that we are still here outside a match
+pub fn send_local_state(
+    mut session: ResMut<NetSession>,
+    player_query: Query<(&Transform, &PlayerPhysics), With<Player>>,
+    guest_query: Query<(&Transform, &PlayerPhysics), (With<PartyGuest>, Without<Player>)>,
+    game_state: Res<State<GameState>>,
+    time: Res<Time<Real>>,
+) {
+    let session = session.bypass_change_detection();
+    session.send_timer -= time.delta_secs();
+    if session.send_timer > 0.0 {
+        return;
+    }
+    session.send_timer = SEND_INTERVAL;
+    let Some(id) = session.local_id else {
+        return;
+    };
+    let message = match player_query.get_single() {
+        Ok((transform, physics)) if *game_state.get() == GameState::Playing && !session.spectating => {
+            NetMessage::PlayerState { id, position: transform.translation, velocity: physics.velocity }
+        }
+        _ => NetMessage::KeepAlive,
+    };
+    session.broadcast(&message);
+    // The split-screen guest is one more player to the others
+    if let (Some(id), Ok((transform, physics))) = (session.guest_id, guest_query.get_single()) {
+        session.broadcast(&NetMessage::PlayerState { id, position: transform.translation, velocity: physics.velocity });
+    }
+}
+
+// Tell the other players about boulders this player throws
+pub fn send_local_throws(
+    mut commands: Commands,
+    session: Res<NetSession>,
+    throw_query: Query<(Entity, &Projectile), (Added<Projectile>, Without<Hostile>, Without<NetProjectile>)>,
+) {
+    let Some(owner) = session.local_id.filter(|_| !session.spectating) else {
+        return;
+    };
+    for (entity, projectile) in throw_query.iter() {
+        commands.entity(entity).insert(NetProjectile { owner });
+        session.broadcast(&NetMessage::Throw {
+            owner,
+            start: projectile.start_position,
+            target: projectile.target_position,
+            velocity: projectile.initial_velocity,
+            blast_radius: projectile.blast_radius,
+        });
+    }
+}
+
+// Spawn the boulders other players threw
+pub fn spawn_remote_throws(
+    mut commands: Commands,
+    mut session: ResMut<NetSession>,
+    mut projectile_assets: ResMut<ProjectileAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    tuning: Res<LiveTuning>,
+) {
+    let session = session.bypass_change_detection();
+    for message in session.pending_throws.drain(..) {
+        let NetMessage::Throw { owner, start, target, velocity, blast_radius } = message else {
+            continue;
+        };
+        let boulder = spawn_boulder(&mut commands, &mut projectile_assets, &mut materials, &tuning.0.projectile, BoulderLaunch { start, target, velocity, color: REMOTE_BOULDER_COLOR, blast_radius });
+        commands.entity(boulder).insert(NetProjectile { owner });
+    }
+}
+
+// Spawn, move and remove the balls of the other players
+pub fn sync_remote_players(
+    mut commands: Commands,
+    session: Res<NetSession>,
+    mut remote_query: Query<(Entity, &RemotePlayer, &mut Transform)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    for (entity, remote, mut transform) in remote_query.iter_mut() {
+        let Some((position, velocity)) = session.remote_states.get(&remote.id) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        // Head for where they should be by now, smoothing over late or lost packets
+        let predicted = *position + *velocity * SEND_INTERVAL;
+        transform.translation = transform.translation.lerp(predicted, (REMOTE_SMOOTHING * delta).min(1.0));
+    }
+
+    for (id, (position, _)) in session.remote_states.iter() {
+        if remote_query.iter().any(|(_, remote, _)| remote.id == *id) {
+            continue;
+        }
+        let color = player_color(*id);
+        commands.spawn((
+            RemotePlayer { id: *id },
+            Mesh3d(meshes.add(Sphere::new(PLAYER_RADIUS))),
+            MeshMaterial3d(materials.add(StandardMaterial { base_color: color, ..default() })),
+            Transform::from_translation(*position),
+            Name::new(format!("Player {}", id + 1)),
+            MinimapBlip { color },
+        ));
+    }
+}
+
+// On the host, decide which players each landed boulder hit and tell everyone
+pub fn resolve_hits(
+    mut commands: Commands,
+    mut session: ResMut<NetSession>,
+    projectile_query: Query<(Entity, &Transform, &Projectile, &NetProjectile), Without<HitResolved>>,
+    player_query: Query<&Transform, With<Player>>,
+    guest_query: Query<&Transform, (With<PartyGuest>, Without<Player>)>,
+) {
+    let local_position = player_query.get_single().map(|transform| transform.translation).ok();
+    let guest_position = session
+        .guest_id
+        .zip(guest_query.get_single().ok())
+        .map(|(id, transform)| (id, transform.translation));
+    for (entity, transform, projectile, net) in projectile_query.iter() {
+        if !projectile.stuck {
+            continue;
+        }
+        commands.entity(entity).insert(HitResolved);
+        let positions = session
+            .remote_states
+            .iter()
+            .map(|(id, (position, _))| (*id, *position))
+            .chain(session.local_id.zip(local_position))
+            .chain(guest_position);
+        let reach = projectile.blast_radius + PLAYER_RADIUS;
+        let hits: Vec<(u8, f32)> = positions
+            .filter(|(id, _)| *id != net.owner)
+            .filter_map(|(id, position)| {
+                let distance = position.distance(transform.translation);
+                (distance < reach).then(|| (id, HIT_DAMAGE * (1.0 - distance / reach)))
+            })
+            .collect();
+        for (victim, damage) in hits {
+            session.broadcast(&NetMessage::Hit { victim, attacker: net.owner, damage });
+            if session.is_local(victim) {
+                session.bypass_change_detection().pending_hits.push((victim, net.owner, damage));
+            }
+        }
+    }
+}
+
+// Take the damage the host says other players dealt this machine's players, and tell everyone who
+// knocked them out
+pub fn apply_hits(
+    mut session: ResMut<NetSession>,
+    mut player_query: Query<&mut Health, With<Player>>,
+    mut guest_query: Query<&mut Health, (With<PartyGuest>, Without<Player>)>,
+    mut notices: EventWriter<NetNotice>,
+) {
+    let session = session.bypass_change_detection();
+    for (victim, attacker, damage) in std::mem::take(&mut session.pending_hits) {
+        let health = if Some(victim) == session.guest_id {
+            guest_query.get_single_mut()
+        } else {
+            player_query.get_single_mut()
+        };
+        let Ok(mut health) = health else {
+            continue;
+        };
+        let was_standing = health.current > 0.0;
+        health.current -= damage;
+        if was_standing && health.current <= 0.0 {
+            session.record_knockout(attacker);
+            session.broadcast(&NetMessage::Knockout { victim, attacker });
+            notices.send(NetNotice::Knockout { victim, attacker });
+        }
+    }
+}
+
+// Remove the other players' balls after a match or when leaving the session
+pub fn despawn_remote_players(mut commands: Commands, remote_query: Query<Entity, With<RemotePlayer>>) {
+    for entity in remote_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Say goodbye so the others don't wait for us to time out
+pub fn leave_on_exit(mut exit_events: EventReader<AppExit>, session: Option<Res<NetSession>>) {
+    let Some(session) = session else {
+        return;
+    };
+    if exit_events.read().next().is_none() {
+        return;
+    }
+    for id in session.guest_id.into_iter().chain(session.local_id) {
+        session.broadcast(&NetMessage::Leave { id });
+    }
+}
+
+// Plugin for LAN multiplayer
+pub struct NetworkPlugin;
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<NetRequest>()
+            .add_event::<NetNotice>()
+            .add_systems(Update, handle_net_requests)
+            .add_systems(Update, (receive_messages, send_local_state)
+                .chain()
+                .run_if(resource_exists::<NetSession>))
+            .add_systems(Update, (send_local_throws, spawn_remote_throws, sync_remote_players, apply_hits)
+                .after(receive_messages)
+                .run_if(resource_exists::<NetSession>)
+                .run_if(in_state(GameState::Playing)))
+            .add_systems(Update, apply_resync
+                .after(receive_messages)
+                .run_if(resource_exists::<NetSession>))
+            .add_systems(Update, (resolve_hits, send_resyncs)
+                .after(receive_messages)
+                .run_if(|session: Option<Res<NetSession>>| session.is_some_and(|session| session.is_host()))
+                .run_if(in_state(GameState::Playing)))
+            .add_systems(OnExit(GameState::GameOver), despawn_remote_players)
+            .add_systems(Update, despawn_remote_players.run_if(resource_removed::<NetSession>))
+            .add_systems(Last, leave_on_exit);
+    }
+}
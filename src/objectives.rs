@@ -0,0 +1,372 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
+use crate::terrain::{chunk_coords, find_hilltop, get_terrain_height, CHUNK_SIZE};
+use crate::regions::{region_name, region_of_chunk, REGION_SIZE_CHUNKS};
+use crate::player::Player;
+use crate::props::Prop;
+use crate::score::{spawn_score_popup, Score};
+use crate::save::SaveData;
+use crate::game_events::GameEvent;
+use crate::waypoint::Waypoint;
+use crate::compass::CompassMarker;
+use crate::photo_mode::PhotoModeState;
+use crate::sim::Simulation;
+use crate::{GameMode, GameState};
+
+// Objective constants
+const FIRST_OBJECTIVE_DELAY: f32 = 5.0; // Seconds into a run before the first task is issued
+const OBJECTIVE_GAP: f32 = 4.0; // Seconds between one task ending and the next being issued
+const TARGETS_MIN: u32 = 3; // Fewest targets a destroy task asks for
+const TARGETS_MAX: u32 = 6; // Most targets a destroy task asks for
+const COINS_MIN: u32 = 5; // Fewest coins a collect task asks for
+const COINS_MAX: u32 = 12; // Most coins a collect task asks for
+const PROPS_MIN: u32 = 2; // Fewest props a smash task asks for
+const PROPS_MAX: u32 = 5; // Most props a smash task asks for
+const BEACON_MIN_DISTANCE: f32 = 60.0; // Closest a beacon is placed to the player
+const BEACON_MAX_DISTANCE: f32 = 100.0; // Farthest a beacon search starts from the player
+const BEACON_RADIUS: f32 = 3.0; // Distance from a beacon that counts as reaching it
+const BEACON_SPEED_ALLOWANCE: f32 = 4.0; // Meters per second the time limit assumes
+const BEACON_EXTRA_TIME: f32 = 15.0; // Seconds added on top of the travel time
+const REWARD_POINTS: u32 = 500; // Score for finishing a task
+const REWARD_COINS: u32 = 10; // Coins banked for finishing a task
+const OBJECTIVE_STREAM: u64 = 11; // Simulation random stream for generating tasks
+const OBJECTIVE_COLOR: Color = Color::srgb(1.0, 0.9, 0.3);
+
+// What a task asks of the player
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ObjectiveKind {
+    // Destroy targets within a region
+    DestroyTargets { region: (i32, i32), required: u32 },
+    // Collect coins anywhere
+    CollectCoins { required: u32 },
+    // Smash props around the catapults
+    SmashProps { required: u32 },
+    // Roll up to a beacon before the time limit
+    ReachBeacon { position: Vec3 },
+}
+
+// A generated task and how far along it is
+#[derive(Clone, Debug)]
+pub struct Objective {
+    pub kind: ObjectiveKind,
+    pub progress: u32,
+    // Seconds left, for tasks with a time limit
+    pub time_left: Option<f32>,
+}
+
+impl Objective {
+    // One-line description, e.g. "Destroy 5 targets in the Western Ridge"
+    pub fn describe(&self) -> String {
+        match self.kind {
+            ObjectiveKind::DestroyTargets { region, required } => {
+                format!("Destroy {} targets in the {}", required, region_name(region))
+            }
+            ObjectiveKind::CollectCoins { required } => format!("Collect {} coins", required),
+            ObjectiveKind::SmashProps { required } => format!("Smash {} crates, barrels or walls", required),
+            ObjectiveKind::ReachBeacon { position } => {
+                let region = region_of_chunk(chunk_coords(position));
+                format!("Reach the beacon in the {} before time runs out", region_name(region))
+            }
+        }
+    }
+
+    // Progress readout for the tracker, e.g. "3/5" or "24 s left"
+    pub fn status(&self) -> String {
+        match self.kind {
+            ObjectiveKind::DestroyTargets { required, .. }
+            | ObjectiveKind::CollectCoins { required }
+            | ObjectiveKind::SmashProps { required } => format!("{}/{}", self.progress.min(required), required),
+            ObjectiveKind::ReachBeacon { .. } => format!("{:.0} s left", self.time_left.unwrap_or(0.0).max(0.0)),
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        match self.kind {
+            ObjectiveKind::DestroyTargets { required, .. }
+            | ObjectiveKind::CollectCoins { required }
+            | ObjectiveKind::SmashProps { required } => self.progress >= required,
+            ObjectiveKind::ReachBeacon { .. } => self.progress > 0,
+        }
+    }
+}
+
+// The task currently issued and the tasks finished this run
+#[derive(Resource, Default)]
+pub struct Objectives {
+    pub active: Option<Objective>,
+    pub completed: u32,
+    // Seconds until the next task is issued
+    pub cooldown: f32,
+}
+
+// Marker for the entity pointing the player toward the active task
+#[derive(Component)]
+pub struct ObjectiveMarker;
+
+// Marker for the objective tracker panel
+#[derive(Component)]
+pub struct ObjectiveHudRoot;
+
+// Marker for the objective tracker text
+#[derive(Component)]
+pub struct ObjectiveHudText;
+
+// Make up a task suited to where the player is
+fn generate_objective(player_position: Vec3, nearest_prop: Option<Vec3>, rng: &mut StdRng) -> Objective {
+    let mut kind = match rng.gen_range(0..4) {
+        0 => ObjectiveKind::DestroyTargets {
+            region: region_of_chunk(chunk_coords(player_position)),
+            required: rng.gen_range(TARGETS_MIN..=TARGETS_MAX),
+        },
+        1 => ObjectiveKind::CollectCoins { required: rng.gen_range(COINS_MIN..=COINS_MAX) },
+        2 => ObjectiveKind::SmashProps { required: rng.gen_range(PROPS_MIN..=PROPS_MAX) },
+        _ => {
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let distance = rng.gen_range(BEACON_MIN_DISTANCE..BEACON_MAX_DISTANCE);
+            let top = find_hilltop(Vec2::new(player_position.x, player_position.z) + Vec2::new(angle.cos(), angle.sin()) * distance);
+            ObjectiveKind::ReachBeacon { position: Vec3::new(top.x, get_terrain_height(top.x, top.y), top.y) }
+        }
+    };
+    // Smashing needs props to smash
+    if matches!(kind, ObjectiveKind::SmashProps { .. }) && nearest_prop.is_none() {
+        kind = ObjectiveKind::CollectCoins { required: rng.gen_range(COINS_MIN..=COINS_MAX) };
+    }
+
+    let time_left = match kind {
+        ObjectiveKind::ReachBeacon { position } => {
+            Some(position.distance(player_position) / BEACON_SPEED_ALLOWANCE + BEACON_EXTRA_TIME)
+        }
+        _ => None,
+    };
+    Objective { kind, progress: 0, time_left }
+}
+
+// Where the waypoint for a task goes, if it has somewhere to point
+fn marker_position(kind: ObjectiveKind, nearest_prop: Option<Vec3>) -> Option<Vec3> {
+    match kind {
+        ObjectiveKind::DestroyTargets { region, .. } => {
+            let size = REGION_SIZE_CHUNKS as f32 * CHUNK_SIZE;
+            let x = (region.0 as f32 + 0.5) * size;
+            let z = (region.1 as f32 + 0.5) * size;
+            Some(Vec3::new(x, get_terrain_height(x, z), z))
+        }
+        ObjectiveKind::SmashProps { .. } => nearest_prop,
+        ObjectiveKind::ReachBeacon { position } => Some(position),
+        ObjectiveKind::CollectCoins { .. } => None,
+    }
+}
+
+// Issue a new task once the gap after the last one has passed
+pub fn issue_objectives(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut objectives: ResMut<Objectives>,
+    player_query: Query<&Transform, With<Player>>,
+    prop_query: Query<&Transform, With<Prop>>,
+    simulation: Res<Simulation>,
+    time: Res<Time>,
+) {
+    if objectives.active.is_some() {
+        return;
+    }
+    objectives.cooldown -= time.delta_secs();
+    if objectives.cooldown > 0.0 {
+        return;
+    }
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation;
+    let nearest_prop = prop_query
+        .iter()
+        .map(|transform| transform.translation)
+        .min_by(|a, b| a.distance_squared(player_position).total_cmp(&b.distance_squared(player_position)));
+
+    let objective = generate_objective(player_position, nearest_prop, &mut simulation.rng(OBJECTIVE_STREAM));
+    if let Some(position) = marker_position(objective.kind, nearest_prop) {
+        let mut marker = commands.spawn((
+            ObjectiveMarker,
+            Transform::from_translation(position),
+            Visibility::default(),
+            Waypoint { label: "Objective".to_string(), color: OBJECTIVE_COLOR },
+            CompassMarker { color: OBJECTIVE_COLOR },
+        ));
+        // Beacons are a tall glowing pillar to roll up to
+        if matches!(objective.kind, ObjectiveKind::ReachBeacon { .. }) {
+            marker.insert((
+                Mesh3d(meshes.add(Cylinder::new(0.4, 12.0))),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: OBJECTIVE_COLOR,
+                    emissive: (OBJECTIVE_COLOR.to_linear() * 2.0).into(),
+                    ..default()
+                })),
+            ));
+        }
+    }
+    objectives.active = Some(objective);
+}
+
+// Advance the active task from game events and the player's position, paying out when it's done
+pub fn track_objectives(
+    mut objectives: ResMut<Objectives>,
+    mut score: ResMut<Score>,
+    mut save: ResMut<SaveData>,
+    mut game_events: ParamSet<(EventReader<GameEvent>, EventWriter<GameEvent>)>,
+    player_query: Query<&Transform, With<Player>>,
+    time: Res<Time>,
+) {
+    let Some(objective) = objectives.active.as_mut() else {
+        return;
+    };
+
+    for event in game_events.p0().read() {
+        match (objective.kind, event) {
+            (ObjectiveKind::DestroyTargets { region, .. }, GameEvent::TargetDestroyed { position, .. })
+                if region_of_chunk(chunk_coords(*position)) == region => objective.progress += 1,
+            (ObjectiveKind::CollectCoins { .. }, GameEvent::PickupCollected { value }) => objective.progress += value,
+            (ObjectiveKind::SmashProps { .. }, GameEvent::PropDestroyed { .. }) => objective.progress += 1,
+            _ => {}
+        }
+    }
+
+    let player_position = player_query.get_single().map(|transform| transform.translation).ok();
+    if let (ObjectiveKind::ReachBeacon { position }, Some(player_position)) = (objective.kind, player_position) {
+        let horizontal = Vec2::new(position.x - player_position.x, position.z - player_position.z).length();
+        if horizontal < BEACON_RADIUS {
+            objective.progress = 1;
+        }
+    }
+    if let Some(time_left) = objective.time_left.as_mut() {
+        *time_left -= time.delta_secs();
+    }
+
+    let completed = objective.is_complete();
+    let failed = !completed && objective.time_left.is_some_and(|time_left| time_left <= 0.0);
+    if !completed && !failed {
+        return;
+    }
+
+    let description = objective.describe();
+    if completed {
+        objectives.completed += 1;
+        score.points += REWARD_POINTS;
+        save.coins += REWARD_COINS;
+    }
+    game_events.p1().send(GameEvent::ObjectiveFinished { description, completed });
+    objectives.active = None;
+    objectives.cooldown = OBJECTIVE_GAP;
+}
+
+// Remove the waypoint once its task is over, with a popup if it was completed
+pub fn clear_objective_markers(
+    mut commands: Commands,
+    mut finished_events: EventReader<GameEvent>,
+    player_query: Query<&Transform, With<Player>>,
+    marker_query: Query<Entity, With<ObjectiveMarker>>,
+) {
+    for event in finished_events.read() {
+        let GameEvent::ObjectiveFinished { completed, .. } = event else {
+            continue;
+        };
+        for entity in marker_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        if let (true, Ok(transform)) = (*completed, player_query.get_single()) {
+            spawn_score_popup(&mut commands, transform.translation, format!("+{}", REWARD_POINTS));
+        }
+    }
+}
+
+// Build the objective tracker on the right side of the screen
+pub fn spawn_objective_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            ObjectiveHudRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(12.0),
+                top: Val::Px(48.0),
+                max_width: Val::Px(280.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.35)),
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ObjectiveHudText,
+                Text::new(""),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(OBJECTIVE_COLOR),
+            ));
+        });
+}
+
+// Show the active task and its progress while playing free play
+pub fn update_objective_hud(
+    objectives: Res<Objectives>,
+    mut root_query: Query<&mut Visibility, With<ObjectiveHudRoot>>,
+    mut text_query: Query<&mut Text, With<ObjectiveHudText>>,
+) {
+    let wanted = if objectives.active.is_some() { Visibility::Inherited } else { Visibility::Hidden };
+    for mut visibility in root_query.iter_mut() {
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+    let Some(objective) = objectives.active.as_ref() else {
+        return;
+    };
+    for mut text in text_query.iter_mut() {
+        text.0 = format!("{}\n{}", objective.describe(), objective.status());
+    }
+}
+
+// Hide the tracker outside of gameplay
+pub fn hide_objective_hud(mut query: Query<&mut Visibility, With<ObjectiveHudRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Drop the active task and its marker at the end of a run
+pub fn reset_objectives(
+    mut commands: Commands,
+    mut objectives: ResMut<Objectives>,
+    marker_query: Query<Entity, With<ObjectiveMarker>>,
+) {
+    for entity in marker_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    *objectives = Objectives {
+        cooldown: FIRST_OBJECTIVE_DELAY,
+        ..default()
+    };
+}
+
+// Plugin for generated free play objectives
+pub struct ObjectivePlugin;
+
+impl Plugin for ObjectivePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Objectives {
+                cooldown: FIRST_OBJECTIVE_DELAY,
+                ..default()
+            })
+            .add_systems(Startup, spawn_objective_hud)
+            .add_systems(OnExit(GameState::Playing), hide_objective_hud)
+            .add_systems(OnExit(GameState::GameOver), reset_objectives)
+            .add_systems(Update, (issue_objectives, track_objectives, clear_objective_markers, update_objective_hud)
+                .chain()
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive))
+                .run_if(resource_equals(GameMode::FreePlay)));
+    }
+}
@@ -0,0 +1,196 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::f32::consts::{FRAC_PI_2, TAU};
+use crate::terrain::{chunk_rng, get_terrain_height, queue_new_chunks, sample, ScatterQueue, TerrainChunk, CHUNK_SIZE};
+use crate::budget::WorkBudgets;
+use crate::player::{Player, PlayerPhysics};
+use crate::drops::SpeedBoost;
+use crate::hazards::in_lava;
+use crate::photo_mode::PhotoModeState;
+use crate::sim::SimSet;
+use crate::GameState;
+
+// Jump pad and boost ring constants
+const JUMP_PAD_CHANCE: f64 = 0.5; // Chance a chunk has a jump pad
+const JUMP_PAD_RADIUS: f32 = 1.2; // Radius of a pad's surface
+const JUMP_PAD_REACH: f32 = 0.3; // Height above the pad the ball can be and still get launched
+const JUMP_PAD_LAUNCH: f32 = 14.0; // Upward speed a pad launches the player with
+const BOOST_RING_CHANCE: f64 = 0.5; // Chance a chunk has a boost ring
+const BOOST_RING_RADIUS: f32 = 2.0; // Radius of the ring opening
+const BOOST_RING_HEIGHT: f32 = 2.5; // Height of the ring's center above the terrain
+const BOOST_RING_THICKNESS: f32 = 0.6; // Distance either side of the ring plane that counts as passing through
+const BOOST_RING_IMPULSE: f32 = 6.0; // Speed added along the direction of travel
+const BOOST_RING_DURATION: f32 = 4.0; // Seconds of speed boost from a ring
+const TRIGGER_COOLDOWN: f32 = 0.5; // Seconds before the same pad or ring can trigger again
+const SPAWN_MARGIN: f32 = 4.0; // Distance from the chunk edge pads and rings keep
+const PAD_SALT: u64 = 2; // Chunk random stream for pads and rings
+const JUMP_PAD_COLOR: Color = Color::srgb(0.3, 0.9, 0.5);
+const BOOST_RING_COLOR: Color = Color::srgb(1.0, 0.6, 0.9);
+
+// A pad lying flat on the terrain that throws the player into the air
+#[derive(Component, Default)]
+pub struct JumpPad {
+    pub cooldown: f32,
+}
+
+// A floating ring that boosts the player when they pass through it
+#[derive(Component, Default)]
+pub struct BoostRing {
+    pub cooldown: f32,
+}
+
+// Shared meshes and materials for pads and rings
+#[derive(Resource)]
+pub struct PadAssets {
+    pub pad_mesh: Handle<Mesh>,
+    pub pad_material: Handle<StandardMaterial>,
+    pub ring_mesh: Handle<Mesh>,
+    pub ring_material: Handle<StandardMaterial>,
+}
+
+// Create the shared pad and ring meshes and materials
+pub fn setup_pad_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut glowing = |color: Color| materials.add(StandardMaterial {
+        base_color: color,
+        emissive: (color.to_linear() * 0.8).into(),
+        ..default()
+    });
+    commands.insert_resource(PadAssets {
+        pad_mesh: meshes.add(Cylinder::new(JUMP_PAD_RADIUS, 0.15)),
+        pad_material: glowing(JUMP_PAD_COLOR),
+        ring_mesh: meshes.add(Torus::new(BOOST_RING_RADIUS - 0.15, BOOST_RING_RADIUS + 0.15)),
+        ring_material: glowing(BOOST_RING_COLOR),
+    });
+}
+
+// Place pads and rings on chunks as they are generated, a few chunks a frame within the scatter budget;
+// the same every time a chunk loads
+pub fn populate_pads(
+    mut commands: Commands,
+    assets: Res<PadAssets>,
+    budgets: Res<WorkBudgets>,
+    mut queue: Local<ScatterQueue>,
+    chunk_query: Query<(Entity, &TerrainChunk), Added<TerrainChunk>>,
+) {
+    queue_new_chunks(&mut queue, &chunk_query);
+    queue.run(budgets.scatter_ms, |(entity, (chunk_x, chunk_z))| {
+        // A chunk rebuilt before its turn comes back as a new entity
+        let Some(mut chunk_commands) = commands.get_entity(entity) else {
+            return;
+        };
+        let origin = Vec2::new(chunk_x as f32 * CHUNK_SIZE, chunk_z as f32 * CHUNK_SIZE);
+        let mut rng = chunk_rng(chunk_x, chunk_z, PAD_SALT);
+        let spot = |rng: &mut StdRng| Vec2::new(
+            rng.gen_range(SPAWN_MARGIN..CHUNK_SIZE - SPAWN_MARGIN),
+            rng.gen_range(SPAWN_MARGIN..CHUNK_SIZE - SPAWN_MARGIN),
+        );
+        // Draw every value up front so the layout doesn't depend on what gets skipped
+        let pad = (rng.gen_bool(JUMP_PAD_CHANCE), spot(&mut rng));
+        let ring = (rng.gen_bool(BOOST_RING_CHANCE), spot(&mut rng), rng.gen_range(0.0..TAU));
+
+        chunk_commands.with_children(|parent| {
+            let (has_pad, local) = pad;
+            let world = origin + local;
+            if has_pad && !in_lava(world.x, world.y) {
+                // Tilted to lie flush with the slope
+                let normal = sample(world.x, world.y).normal;
+                parent.spawn((
+                    JumpPad::default(),
+                    Mesh3d(assets.pad_mesh.clone()),
+                    MeshMaterial3d(assets.pad_material.clone()),
+                    Transform::from_xyz(local.x, get_terrain_height(world.x, world.y), local.y)
+                        .with_rotation(Quat::from_rotation_arc(Vec3::Y, normal)),
+                ));
+            }
+
+            let (has_ring, local, heading) = ring;
+            let world = origin + local;
+            if has_ring && !in_lava(world.x, world.y) {
+                // Stood upright, facing along its heading
+                parent.spawn((
+                    BoostRing::default(),
+                    Mesh3d(assets.ring_mesh.clone()),
+                    MeshMaterial3d(assets.ring_material.clone()),
+                    Transform::from_xyz(local.x, get_terrain_height(world.x, world.y) + BOOST_RING_HEIGHT, local.y)
+                        .with_rotation(Quat::from_rotation_y(heading) * Quat::from_rotation_x(FRAC_PI_2)),
+                ));
+            }
+        });
+    });
+}
+
+// Launch the player off pads and boost them through rings
+pub fn trigger_pads(
+    mut boost: ResMut<SpeedBoost>,
+    mut player_query: Query<(&Transform, &mut PlayerPhysics), With<Player>>,
+    mut pad_query: Query<(&GlobalTransform, &mut JumpPad)>,
+    mut ring_query: Query<(&GlobalTransform, &mut BoostRing)>,
+    time: Res<Time>,
+) {
+    let Ok((player_transform, mut physics)) = player_query.get_single_mut() else {
+        return;
+    };
+    let delta = time.delta_secs();
+    let position = player_transform.translation;
+
+    for (transform, mut pad) in pad_query.iter_mut() {
+        pad.cooldown = (pad.cooldown - delta).max(0.0);
+        let offset = position - transform.translation();
+        let normal = transform.up();
+        let height = offset.dot(*normal) - 0.5;
+        let across = (offset - *normal * offset.dot(*normal)).length();
+        if pad.cooldown > 0.0 || across > JUMP_PAD_RADIUS || !(-0.5..=JUMP_PAD_REACH).contains(&height) {
+            continue;
+        }
+        pad.cooldown = TRIGGER_COOLDOWN;
+        physics.velocity.y = JUMP_PAD_LAUNCH;
+        physics.momentum.y = JUMP_PAD_LAUNCH;
+        physics.grounded = false;
+    }
+
+    for (transform, mut ring) in ring_query.iter_mut() {
+        ring.cooldown = (ring.cooldown - delta).max(0.0);
+        let offset = position - transform.translation();
+        // The torus lies in its local XZ plane, so its axis is local Y
+        let axis = transform.up();
+        let along = offset.dot(*axis);
+        let across = (offset - *axis * along).length();
+        if ring.cooldown > 0.0 || along.abs() > BOOST_RING_THICKNESS || across > BOOST_RING_RADIUS {
+            continue;
+        }
+        ring.cooldown = TRIGGER_COOLDOWN;
+        let travel = Vec3::new(physics.velocity.x, 0.0, physics.velocity.z).normalize_or_zero();
+        physics.velocity += travel * BOOST_RING_IMPULSE;
+        physics.momentum += travel * BOOST_RING_IMPULSE;
+        boost.remaining = boost.remaining.max(BOOST_RING_DURATION);
+    }
+}
+
+// Pulse the rings so they catch the eye
+pub fn animate_rings(mut ring_query: Query<&mut Transform, With<BoostRing>>, time: Res<Time>) {
+    let pulse = 1.0 + (time.elapsed_secs() * 3.0).sin() * 0.05;
+    for mut transform in ring_query.iter_mut() {
+        transform.scale = Vec3::splat(pulse);
+    }
+}
+
+// Plugin for jump pads and boost rings
+pub struct PadPlugin;
+
+impl Plugin for PadPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup_pad_assets)
+            // Chunks generate behind the main menu too, so they need their pads from the start
+            .add_systems(Update, (populate_pads, animate_rings))
+            .add_systems(FixedUpdate, trigger_pads
+                .in_set(SimSet::Constraints)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)));
+    }
+}
@@ -0,0 +1,578 @@
+use bevy::prelude::*;
+use bevy::color::ColorToComponents;
+use bevy::pbr::NotShadowCaster;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::view::NoFrustumCulling;
+use bevy::transform::TransformSystem;
+use rand::Rng;
+use std::f32::consts::{PI, TAU};
+use crate::assets::particle_texture::create_particle_texture;
+use crate::audio::AmbientConditions;
+use crate::camera::FollowCamera;
+use crate::game_events::GameEvent;
+use crate::photo_mode::PhotoModeState;
+use crate::player::{Player, PlayerPhysics};
+use crate::projectile::ProjectileImpactEvent;
+use crate::GameState;
+
+// Particle constants
+const MAX_PARTICLES: usize = 2000; // Most particles one emitter keeps alive; more are dropped until some die
+const DUST_SPEED: f32 = 2.0; // Rolling speed where the ball starts kicking up dust
+const DUST_RATE: f32 = 6.0; // Dust particles per second for each unit of speed above that
+const DUST_OFFSET: f32 = 0.45; // How far below the ball's center the dust comes from
+const IMPACT_PARTICLES: f32 = 12.0; // Dirt particles thrown up per unit of impact radius
+const EXPLOSION_PARTICLES: u32 = 40; // Fire particles in an explosion, with half as many of smoke
+const RAIN_RATE: f32 = 1500.0; // Raindrops per second in the heaviest rain
+const RAIN_HEIGHT: f32 = 12.0; // How far above the camera raindrops start
+const RAIN_AREA: f32 = 20.0; // Half width of the square around the camera that rain falls in
+
+// Values a lifetime curve can blend between
+pub trait Blend: Copy {
+    fn blend(self, other: Self, t: f32) -> Self;
+}
+
+impl Blend for f32 {
+    fn blend(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Blend for LinearRgba {
+    fn blend(self, other: Self, t: f32) -> Self {
+        self.mix(&other, t)
+    }
+}
+
+// A value that changes over a particle's life, as keys from 0 at birth to 1 at death blended linearly
+#[derive(Debug, Clone)]
+pub struct LifetimeCurve<T> {
+    keys: Vec<(f32, T)>,
+}
+
+impl<T: Blend> LifetimeCurve<T> {
+    // Keys as (fraction of life, value), at least one of them
+    pub fn new(mut keys: Vec<(f32, T)>) -> Self {
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { keys }
+    }
+
+    pub fn constant(value: T) -> Self {
+        Self::new(vec![(0.0, value)])
+    }
+
+    pub fn linear(start: T, end: T) -> Self {
+        Self::new(vec![(0.0, start), (1.0, end)])
+    }
+
+    // Value at a fraction of the particle's life; before the first key and after the last it holds steady
+    pub fn sample(&self, t: f32) -> T {
+        match self.keys.iter().position(|(key, _)| *key > t) {
+            Some(0) => self.keys[0].1,
+            Some(next) => {
+                let (start, from) = self.keys[next - 1];
+                let (end, to) = self.keys[next];
+                from.blend(to, (t - start) / (end - start))
+            }
+            None => self.keys[self.keys.len() - 1].1,
+        }
+    }
+}
+
+// Where around the emitter new particles appear
+#[derive(Debug, Clone, Copy)]
+pub enum EmitShape {
+    Point,
+    // Anywhere inside a sphere of this radius
+    Sphere(f32),
+    // Anywhere inside a box with these half extents
+    Box(Vec3),
+}
+
+// How particles mix with what is behind them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParticleBlend {
+    // Covers what is behind, for dust and smoke
+    Alpha,
+    // Adds light, for fire and glints
+    Additive,
+}
+
+// How an emitter's particles start out, move and look over their lives
+#[derive(Debug, Clone)]
+pub struct ParticleEffect {
+    // Range of seconds a particle lives
+    pub lifetime: (f32, f32),
+    pub shape: EmitShape,
+    // Particles set off within `spread` radians of `direction`, at a speed in the range
+    pub direction: Vec3,
+    pub spread: f32,
+    pub speed: (f32, f32),
+    // Downward acceleration, negative to float upward, and the fraction of velocity lost each second
+    pub gravity: f32,
+    pub drag: f32,
+    // Over each particle's life: its width, its color, and how much of its velocity it moves with
+    pub size: LifetimeCurve<f32>,
+    pub color: LifetimeCurve<LinearRgba>,
+    pub velocity: LifetimeCurve<f32>,
+    // Lengthens particles along their motion by this many seconds of travel, for streaks like rain
+    pub stretch: f32,
+    pub blend: ParticleBlend,
+}
+
+impl ParticleEffect {
+    // Puffs kicked up by the ball rolling over the ground
+    pub fn dust() -> Self {
+        let color = LinearRgba::new(0.4, 0.33, 0.24, 0.0);
+        Self {
+            lifetime: (0.6, 1.2),
+            shape: EmitShape::Sphere(0.2),
+            direction: Vec3::Y,
+            spread: 1.2,
+            speed: (0.3, 1.0),
+            gravity: -0.2,
+            drag: 1.5,
+            size: LifetimeCurve::linear(0.25, 0.8),
+            color: LifetimeCurve::new(vec![(0.0, color), (0.15, color.with_alpha(0.45)), (1.0, color)]),
+            velocity: LifetimeCurve::constant(1.0),
+            stretch: 0.0,
+            blend: ParticleBlend::Alpha,
+        }
+    }
+
+    // Dirt thrown up where a boulder lands
+    pub fn impact() -> Self {
+        let color = LinearRgba::new(0.3, 0.24, 0.16, 0.8);
+        Self {
+            lifetime: (0.5, 1.0),
+            shape: EmitShape::Sphere(0.3),
+            direction: Vec3::Y,
+            spread: 0.7,
+            speed: (3.0, 7.0),
+            gravity: 9.8,
+            drag: 0.5,
+            size: LifetimeCurve::linear(0.35, 0.15),
+            color: LifetimeCurve::linear(color, color.with_alpha(0.0)),
+            velocity: LifetimeCurve::constant(1.0),
+            stretch: 0.0,
+            blend: ParticleBlend::Alpha,
+        }
+    }
+
+    // The fireball of a destroyed target or prop
+    pub fn fire() -> Self {
+        Self {
+            lifetime: (0.25, 0.5),
+            shape: EmitShape::Sphere(0.3),
+            direction: Vec3::Y,
+            spread: PI,
+            speed: (2.0, 6.0),
+            gravity: 0.0,
+            drag: 4.0,
+            size: LifetimeCurve::new(vec![(0.0, 0.4), (0.3, 1.0), (1.0, 0.2)]),
+            color: LifetimeCurve::new(vec![
+                (0.0, LinearRgba::new(3.0, 2.4, 1.2, 1.0)),
+                (0.4, LinearRgba::new(2.0, 0.6, 0.1, 0.8)),
+                (1.0, LinearRgba::new(0.5, 0.05, 0.0, 0.0)),
+            ]),
+            velocity: LifetimeCurve::constant(1.0),
+            stretch: 0.0,
+            blend: ParticleBlend::Additive,
+        }
+    }
+
+    // Smoke left rising after an explosion
+    pub fn smoke() -> Self {
+        let color = LinearRgba::new(0.12, 0.12, 0.12, 0.0);
+        Self {
+            lifetime: (1.0, 2.0),
+            shape: EmitShape::Sphere(0.5),
+            direction: Vec3::Y,
+            spread: 0.8,
+            speed: (0.5, 1.5),
+            gravity: -1.0,
+            drag: 1.0,
+            size: LifetimeCurve::linear(0.5, 1.6),
+            color: LifetimeCurve::new(vec![(0.0, color), (0.2, color.with_alpha(0.5)), (1.0, color)]),
+            velocity: LifetimeCurve::constant(1.0),
+            stretch: 0.0,
+            blend: ParticleBlend::Alpha,
+        }
+    }
+
+    // Glints flying out of a collected pickup in its color
+    pub fn sparkles(color: Color) -> Self {
+        let color = color.to_linear();
+        let glint = LinearRgba::new(color.red * 2.0, color.green * 2.0, color.blue * 2.0, 1.0);
+        Self {
+            lifetime: (0.5, 0.7),
+            shape: EmitShape::Point,
+            direction: Vec3::Y,
+            spread: 1.1,
+            speed: (3.0, 4.5),
+            gravity: 9.8,
+            drag: 0.0,
+            size: LifetimeCurve::linear(0.15, 0.0),
+            color: LifetimeCurve::constant(glint),
+            velocity: LifetimeCurve::constant(1.0),
+            stretch: 0.0,
+            blend: ParticleBlend::Additive,
+        }
+    }
+
+    // Raindrops falling in streaks over the area around the camera
+    pub fn rain() -> Self {
+        Self {
+            lifetime: (1.0, 1.2),
+            shape: EmitShape::Box(Vec3::new(RAIN_AREA, 0.5, RAIN_AREA)),
+            direction: Vec3::NEG_Y,
+            spread: 0.05,
+            speed: (12.0, 15.0),
+            gravity: 0.0,
+            drag: 0.0,
+            size: LifetimeCurve::constant(0.03),
+            color: LifetimeCurve::constant(LinearRgba::new(0.7, 0.75, 0.85, 0.35)),
+            velocity: LifetimeCurve::constant(1.0),
+            stretch: 0.04,
+            blend: ParticleBlend::Alpha,
+        }
+    }
+
+    // A new particle leaving an emitter at the given position
+    fn spawn(&self, origin: Vec3, rng: &mut impl Rng) -> Particle {
+        let offset = match self.shape {
+            EmitShape::Point => Vec3::ZERO,
+            EmitShape::Sphere(radius) => loop {
+                let point = Vec3::new(rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0));
+                if point.length_squared() <= 1.0 {
+                    break point * radius;
+                }
+            },
+            EmitShape::Box(half) => Vec3::new(
+                rng.gen_range(-half.x..=half.x),
+                rng.gen_range(-half.y..=half.y),
+                rng.gen_range(-half.z..=half.z),
+            ),
+        };
+        // Even spread over the cone around the direction
+        let cos_angle = rng.gen_range(self.spread.min(PI).cos()..=1.0);
+        let sin_angle = (1.0 - cos_angle * cos_angle).sqrt();
+        let around = rng.gen_range(0.0..TAU);
+        let local = Vec3::new(sin_angle * around.cos(), cos_angle, sin_angle * around.sin());
+        let direction = Quat::from_rotation_arc(Vec3::Y, self.direction.normalize_or(Vec3::Y)) * local;
+        Particle {
+            position: origin + offset,
+            velocity: direction * rng.gen_range(self.speed.0..=self.speed.1),
+            age: 0.0,
+            lifetime: rng.gen_range(self.lifetime.0..=self.lifetime.1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Particle {
+    // World space
+    position: Vec3,
+    velocity: Vec3,
+    age: f32,
+    lifetime: f32,
+}
+
+// Emits particles from its position and simulates them in world space, drawn together as camera-facing
+// quads in one mesh on the emitter. Emitters aren't parented; ones that follow something are moved to it.
+#[derive(Component)]
+pub struct ParticleEmitter {
+    pub effect: ParticleEffect,
+    // Particles per second emitted continuously
+    pub rate: f32,
+    // Particles to emit all at once on the next update
+    pub burst: u32,
+    // Remove the emitter once its burst is out and every particle has died
+    pub one_shot: bool,
+    particles: Vec<Particle>,
+    // Part of a particle owed from the rate, carried over to the next update
+    owed: f32,
+}
+
+impl ParticleEmitter {
+    pub fn new(effect: ParticleEffect, rate: f32) -> Self {
+        Self {
+            effect,
+            rate,
+            burst: 0,
+            one_shot: false,
+            particles: Vec::new(),
+            owed: 0.0,
+        }
+    }
+}
+
+// Shared particle materials, one for each blend
+#[derive(Resource)]
+pub struct ParticleAssets {
+    pub alpha: Handle<StandardMaterial>,
+    pub additive: Handle<StandardMaterial>,
+}
+
+// Dust trail following a ball; a separate entity so the dust comes from under the ball as it rolls
+#[derive(Component)]
+pub struct DustTrail {
+    pub player: Entity,
+}
+
+// Rain falling around the camera as heavily as the ambient conditions say
+#[derive(Component)]
+pub struct Rain;
+
+// Throw out a one-off burst of particles at a point; the emitter goes away once they have all died
+pub fn spawn_burst(commands: &mut Commands, effect: ParticleEffect, position: Vec3, count: u32) {
+    commands.spawn((
+        ParticleEmitter {
+            burst: count,
+            one_shot: true,
+            ..ParticleEmitter::new(effect, 0.0)
+        },
+        Transform::from_translation(position),
+        Visibility::Hidden,
+        Name::new("Particle Burst"),
+    ));
+}
+
+// Create the particle texture and materials, and the rain emitter
+pub fn setup_particles(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let texture = images.add(create_particle_texture());
+    let mut material = |alpha_mode: AlphaMode| materials.add(StandardMaterial {
+        base_color_texture: Some(texture.clone()),
+        unlit: true,
+        alpha_mode,
+        cull_mode: None,
+        ..default()
+    });
+    commands.insert_resource(ParticleAssets {
+        alpha: material(AlphaMode::Blend),
+        additive: material(AlphaMode::Add),
+    });
+    commands.spawn((
+        Rain,
+        ParticleEmitter::new(ParticleEffect::rain(), 0.0),
+        Transform::default(),
+        Visibility::Hidden,
+        Name::new("Rain"),
+    ));
+}
+
+// Give each new emitter its own mesh to draw its particles into
+pub fn attach_particle_meshes(
+    mut commands: Commands,
+    assets: Res<ParticleAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    emitter_query: Query<(Entity, &ParticleEmitter), Added<ParticleEmitter>>,
+) {
+    for (entity, emitter) in emitter_query.iter() {
+        let material = match emitter.effect.blend {
+            ParticleBlend::Alpha => assets.alpha.clone(),
+            ParticleBlend::Additive => assets.additive.clone(),
+        };
+        let mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+        // The mesh is rebuilt every frame, so its bounds are never right for culling
+        commands
+            .entity(entity)
+            .insert((Mesh3d(meshes.add(mesh)), MeshMaterial3d(material), NoFrustumCulling, NotShadowCaster));
+    }
+}
+
+// Age and move every particle, remove the dead ones and emit new ones
+pub fn update_particles(
+    mut commands: Commands,
+    mut emitter_query: Query<(Entity, &mut ParticleEmitter, &Transform)>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    let mut rng = rand::thread_rng();
+    for (entity, mut emitter, transform) in emitter_query.iter_mut() {
+        let emitter = &mut *emitter;
+        emitter.particles.retain_mut(|particle| {
+            particle.age += delta;
+            particle.age < particle.lifetime
+        });
+        let effect = &emitter.effect;
+        for particle in emitter.particles.iter_mut() {
+            particle.velocity.y -= effect.gravity * delta;
+            particle.velocity *= (1.0 - effect.drag * delta).max(0.0);
+            let t = particle.age / particle.lifetime;
+            particle.position += particle.velocity * effect.velocity.sample(t) * delta;
+        }
+
+        emitter.owed += emitter.rate * delta;
+        let count = emitter.owed as usize + std::mem::take(&mut emitter.burst) as usize;
+        emitter.owed = emitter.owed.fract();
+        let origin = transform.translation;
+        let room = MAX_PARTICLES.saturating_sub(emitter.particles.len());
+        for _ in 0..count.min(room) {
+            let particle = emitter.effect.spawn(origin, &mut rng);
+            emitter.particles.push(particle);
+        }
+
+        if emitter.one_shot && emitter.particles.is_empty() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// Rebuild each emitter's mesh from its particles, every quad turned to face the camera. Runs after
+// transforms are propagated so the quads line up with where the camera is this frame.
+pub fn draw_particles(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut emitter_query: Query<(&ParticleEmitter, &Mesh3d, &GlobalTransform, &mut Visibility)>,
+    camera_query: Query<&GlobalTransform, With<FollowCamera>>,
+) {
+    let Some(camera) = camera_query.iter().next() else {
+        return;
+    };
+    let camera_position = camera.translation();
+    let camera_up = *camera.up();
+    for (emitter, mesh, transform, mut visibility) in emitter_query.iter_mut() {
+        // An empty mesh has nothing to upload, so it is hidden instead
+        if emitter.particles.is_empty() {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+        visibility.set_if_neq(Visibility::Inherited);
+        let Some(mesh) = meshes.get_mut(&mesh.0) else {
+            continue;
+        };
+
+        // Particles live in world space, but the mesh is drawn at the emitter
+        let to_local = transform.affine().inverse();
+        let effect = &emitter.effect;
+        let count = emitter.particles.len();
+        let mut positions = Vec::with_capacity(count * 4);
+        let mut uvs = Vec::with_capacity(count * 4);
+        let mut colors = Vec::with_capacity(count * 4);
+        let mut indices = Vec::with_capacity(count * 6);
+        for particle in &emitter.particles {
+            let t = particle.age / particle.lifetime;
+            let half = effect.size.sample(t) * 0.5;
+            let color = effect.color.sample(t).to_f32_array();
+            let facing = (camera_position - particle.position).normalize_or(Vec3::Z);
+            // Streaks line up with their motion, everything else with the camera's up
+            let speed = particle.velocity.length();
+            let (up, length) = if effect.stretch > 0.0 && speed > 0.0 {
+                (particle.velocity / speed, half + speed * effect.stretch * 0.5)
+            } else {
+                (camera_up, half)
+            };
+            let right = up.cross(facing).normalize_or(Vec3::X) * half;
+            let up = up * length;
+
+            let base = positions.len() as u32;
+            for (corner, uv) in [
+                (-right - up, [0.0, 1.0]),
+                (right - up, [1.0, 1.0]),
+                (right + up, [1.0, 0.0]),
+                (-right + up, [0.0, 0.0]),
+            ] {
+                positions.push(to_local.transform_point3(particle.position + corner).to_array());
+                uvs.push(uv);
+                colors.push(color);
+            }
+            indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        mesh.insert_indices(Indices::U32(indices));
+    }
+}
+
+// Dirt thrown up by every boulder landing, and a fireball and smoke for everything destroyed
+pub fn spawn_impact_particles(
+    mut commands: Commands,
+    mut impact_events: EventReader<ProjectileImpactEvent>,
+    mut game_events: EventReader<GameEvent>,
+) {
+    for impact in impact_events.read() {
+        let count = (impact.radius * IMPACT_PARTICLES) as u32;
+        spawn_burst(&mut commands, ParticleEffect::impact(), impact.position, count);
+    }
+    for event in game_events.read() {
+        match event {
+            GameEvent::TargetDestroyed { position, .. } | GameEvent::PropDestroyed { position, .. } => {
+                spawn_burst(&mut commands, ParticleEffect::fire(), *position, EXPLOSION_PARTICLES);
+                spawn_burst(&mut commands, ParticleEffect::smoke(), *position, EXPLOSION_PARTICLES / 2);
+            }
+            _ => {}
+        }
+    }
+}
+
+// Give every new ball a dust trail
+pub fn attach_dust_trails(mut commands: Commands, player_query: Query<Entity, Added<Player>>) {
+    for player in player_query.iter() {
+        commands.spawn((
+            DustTrail { player },
+            ParticleEmitter::new(ParticleEffect::dust(), 0.0),
+            Transform::default(),
+            Visibility::Hidden,
+            Name::new("Dust Trail"),
+        ));
+    }
+}
+
+// Keep each dust trail under its ball, kicking up more dust the faster it rolls along the ground
+pub fn update_dust_trails(
+    mut commands: Commands,
+    mut trail_query: Query<(Entity, &DustTrail, &mut ParticleEmitter, &mut Transform)>,
+    player_query: Query<(&Transform, &PlayerPhysics), (With<Player>, Without<DustTrail>)>,
+) {
+    for (entity, trail, mut emitter, mut transform) in trail_query.iter_mut() {
+        let Ok((player_transform, physics)) = player_query.get(trail.player) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        transform.translation = player_transform.translation - Vec3::Y * DUST_OFFSET;
+        let speed = physics.velocity.with_y(0.0).length();
+        emitter.rate = if physics.grounded { (speed - DUST_SPEED).max(0.0) * DUST_RATE } else { 0.0 };
+    }
+}
+
+// Keep the rain over the camera, falling as heavily as the ambient conditions say
+pub fn update_rain(
+    conditions: Res<AmbientConditions>,
+    camera_query: Query<&Transform, (With<FollowCamera>, Without<Rain>)>,
+    mut rain_query: Query<(&mut Transform, &mut ParticleEmitter), With<Rain>>,
+) {
+    let Some(camera) = camera_query.iter().next() else {
+        return;
+    };
+    for (mut transform, mut emitter) in rain_query.iter_mut() {
+        transform.translation = camera.translation + Vec3::Y * RAIN_HEIGHT;
+        emitter.rate = conditions.rain * RAIN_RATE;
+    }
+}
+
+// Plugin for CPU particles: emitters, bursts, and the dust, impact, explosion, rain and sparkle effects
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup_particles)
+            .add_systems(Update, (
+                attach_dust_trails,
+                update_dust_trails,
+                update_rain,
+                spawn_impact_particles,
+                attach_particle_meshes,
+                // Particles hold still while paused or framing a photo
+                update_particles
+                    .run_if(not(in_state(GameState::Paused)))
+                    .run_if(in_state(PhotoModeState::Inactive)),
+            ).chain())
+            .add_systems(PostUpdate, draw_particles.after(TransformSystem::TransformPropagate));
+    }
+}
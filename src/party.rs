@@ -0,0 +1,372 @@
+use bevy::prelude::*;
+use bevy::render::camera::Viewport;
+use bevy::window::PrimaryWindow;
+use crate::camera::FollowCamera;
+use crate::input::{gamepad_actions, update_action_state, GuestGamepad, InputMap};
+use crate::minimap::MinimapBlip;
+use crate::network::{player_color, NetMessage, NetProjectile, NetSession};
+use crate::photo_mode::PhotoModeState;
+use crate::platforms::PlatformSurfaces;
+use crate::player::{player_spawn_position, roll_sphere, Health, PlayerPhysics, RollInput};
+use crate::projectile::{compute_launch, spawn_boulder, throw_range, Ammo, AmmoKind, BoulderLaunch, ProjectileAssets, PROJECTILE_BLAST_RADIUS};
+use crate::replay::BUTTON_THROW;
+use crate::sim::{Interpolated, SimInput, SimSet};
+use crate::sky::distance_fog;
+use crate::terrain::get_terrain_height;
+use crate::tuning::LiveTuning;
+use crate::GameState;
+
+// Split-screen guest constants
+const GUEST_MAX_HEALTH: f32 = 100.0; // Same health as the main player
+const GUEST_SPAWN_OFFSET: Vec3 = Vec3::new(3.0, 0.0, 0.0); // Start beside the main player
+const GUEST_CAMERA_DISTANCE: f32 = 8.0; // Distance of the guest's camera behind their ball
+const GUEST_CAMERA_PITCH: f32 = 0.45; // Angle of the guest's camera above the horizon
+const GUEST_TURN_SPEED: f32 = 2.5; // Radians per second of camera turn at full right stick
+const STICK_DEAD_ZONE: f32 = 0.15; // Right stick travel ignored when turning the camera
+
+// The second player on this machine, playing split-screen in a LAN session on a gamepad
+#[derive(Component)]
+pub struct PartyGuest {
+    // Gamepad claimed for this player, if one was connected
+    pub gamepad: Option<Entity>,
+    // Throw being charged, in 0..1, while the throw button is held
+    pub charge: Option<f32>,
+    pub ammo: Ammo,
+}
+
+// Camera for the right half of the screen, following the guest
+#[derive(Component)]
+pub struct GuestCamera {
+    pub yaw: f32,
+}
+
+// Marker for the guest's status text in their half of the screen
+#[derive(Component)]
+pub struct GuestHudText;
+
+// The guest's input as the simulation sees it, latched the same way as the main player's
+#[derive(Resource, Default)]
+pub struct GuestInput(pub SimInput);
+
+// Horizontal direction a guest camera faces
+fn camera_forward(yaw: f32) -> Vec3 {
+    Quat::from_rotation_y(yaw) * Vec3::NEG_Z
+}
+
+// Bring the guest into the match beside the main player, with their own camera and status text
+pub fn spawn_guest(
+    mut commands: Commands,
+    session: Option<Res<NetSession>>,
+    guest_query: Query<(), With<PartyGuest>>,
+    gamepad_query: Query<Entity, With<Gamepad>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Some(id) = session.and_then(|session| session.guest_id) else {
+        return;
+    };
+    if !guest_query.is_empty() {
+        return;
+    }
+    let gamepad = gamepad_query.iter().next();
+    match gamepad {
+        Some(entity) => {
+            commands.entity(entity).insert(GuestGamepad);
+        }
+        None => println!("No gamepad connected for player {}", id + 1),
+    }
+
+    let mut position = player_spawn_position() + GUEST_SPAWN_OFFSET;
+    position.y = get_terrain_height(position.x, position.z) + 2.5;
+    let color = player_color(id);
+    commands.spawn((
+        PartyGuest { gamepad, charge: None, ammo: Ammo::default() },
+        PlayerPhysics {
+            prev_position: position,
+            ..Default::default()
+        },
+        Health::new(GUEST_MAX_HEALTH),
+        Mesh3d(meshes.add(Sphere::new(0.5))),
+        MeshMaterial3d(materials.add(StandardMaterial { base_color: color, ..default() })),
+        Transform::from_translation(position),
+        Interpolated::new(position),
+        Name::new(format!("Player {}", id + 1)),
+        MinimapBlip { color },
+    ));
+
+    let camera = commands
+        .spawn((
+            GuestCamera { yaw: 0.0 },
+            Camera3d::default(),
+            Camera {
+                // Render after the main camera, into the right half of the window
+                order: 1,
+                ..default()
+            },
+            distance_fog(),
+            Transform::from_translation(position + Vec3::new(0.0, 3.0, GUEST_CAMERA_DISTANCE)).looking_at(position, Vec3::Y),
+        ))
+        .id();
+    commands.spawn((
+        GuestHudText,
+        Text::new(""),
+        TextFont {
+            font_size: 22.0,
+            ..default()
+        },
+        TextColor(color),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..default()
+        },
+        TargetCamera(camera),
+    ));
+}
+
+// Remove the guest, their camera and status text, and give their gamepad back to the main player
+pub fn despawn_guest(
+    mut commands: Commands,
+    guest_query: Query<(Entity, &PartyGuest)>,
+    camera_query: Query<Entity, With<GuestCamera>>,
+    hud_query: Query<Entity, With<GuestHudText>>,
+) {
+    for (entity, guest) in guest_query.iter() {
+        if let Some(mut gamepad) = guest.gamepad.and_then(|gamepad| commands.get_entity(gamepad)) {
+            gamepad.remove::<GuestGamepad>();
+        }
+        commands.entity(entity).despawn_recursive();
+    }
+    for entity in camera_query.iter().chain(hud_query.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Copy the guest's gamepad into their simulation input
+pub fn latch_guest_input(
+    mut input: ResMut<GuestInput>,
+    input_map: Res<InputMap>,
+    guest_query: Query<&PartyGuest>,
+    gamepad_query: Query<&Gamepad>,
+    camera_query: Query<&GuestCamera>,
+) {
+    let Ok(guest) = guest_query.get_single() else {
+        return;
+    };
+    let Some(gamepad) = guest.gamepad.and_then(|entity| gamepad_query.get(entity).ok()) else {
+        return;
+    };
+    let yaw = camera_query.get_single().map(|camera| camera.yaw).unwrap_or(0.0);
+    // The guest only has stone boulders
+    input.0.latch(&gamepad_actions(&input_map, gamepad), yaw, None, AmmoKind::Stone);
+}
+
+// Roll the guest's ball, once per simulation tick; a knocked out guest stays put
+pub fn move_guest(
+    mut guest_query: Query<(&mut Transform, &mut PlayerPhysics, &Health), With<PartyGuest>>,
+    input: Res<GuestInput>,
+    platforms: Res<PlatformSurfaces>,
+    tuning: Res<LiveTuning>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    for (mut transform, mut physics, health) in guest_query.iter_mut() {
+        if health.current <= 0.0 {
+            continue;
+        }
+        roll_sphere(&mut transform, &mut physics, &tuning.0.player, &platforms.0, RollInput::from_sim(&input.0), delta);
+    }
+}
+
+// Charge a throw while the guest holds the button and throw straight ahead of their camera on release;
+// the main player's aim needs a mouse, so the guest's range comes from the charge alone
+pub fn guest_throw(
+    mut commands: Commands,
+    session: Res<NetSession>,
+    input: Res<GuestInput>,
+    mut guest_query: Query<(&Transform, &mut PartyGuest, &Health)>,
+    camera_query: Query<&GuestCamera>,
+    mut projectile_assets: ResMut<ProjectileAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    tuning: Res<LiveTuning>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    let (Some(owner), Ok((transform, mut guest, health))) = (session.guest_id, guest_query.get_single_mut()) else {
+        return;
+    };
+    let tuning = &tuning.0.projectile;
+
+    // Restore boulders like the main player's ammo
+    if guest.ammo.current < guest.ammo.max {
+        guest.ammo.reload_timer -= delta;
+        if guest.ammo.reload_timer <= 0.0 {
+            guest.ammo.current += 1;
+            guest.ammo.reload_timer = tuning.reload_time;
+        }
+    }
+    if health.current <= 0.0 {
+        guest.charge = None;
+        return;
+    }
+
+    let input = &input.0;
+    if input.just_pressed(BUTTON_THROW) && guest.ammo.current > 0 {
+        guest.charge = Some(0.0);
+    }
+    let Some(level) = guest.charge else {
+        return;
+    };
+    if input.held(BUTTON_THROW) {
+        guest.charge = Some((level + delta / tuning.charge_time).min(1.0));
+        return;
+    }
+
+    // Released: throw as far as the charge reaches, in the direction the camera faces
+    guest.charge = None;
+    guest.ammo.current -= 1;
+    let yaw = camera_query.get_single().map(|camera| camera.yaw).unwrap_or(0.0);
+    let range = throw_range(tuning, level);
+    let mut target = transform.translation + camera_forward(yaw) * range;
+    target.y = get_terrain_height(target.x, target.z);
+    let launch = compute_launch(tuning, transform.translation, target, range);
+    let boulder = spawn_boulder(
+        &mut commands,
+        &mut projectile_assets,
+        &mut materials,
+        tuning,
+        BoulderLaunch {
+            start: launch.start_position,
+            target,
+            velocity: launch.initial_velocity,
+            color: player_color(owner),
+            blast_radius: PROJECTILE_BLAST_RADIUS,
+        },
+    );
+    commands.entity(boulder).insert(NetProjectile { owner });
+    session.broadcast(&NetMessage::Throw {
+        owner,
+        start: launch.start_position,
+        target,
+        velocity: launch.initial_velocity,
+        blast_radius: PROJECTILE_BLAST_RADIUS,
+    });
+}
+
+// Finish a tick for the guest: use up the presses it saw
+pub fn clear_guest_presses(mut input: ResMut<GuestInput>) {
+    input.0.pressed = 0;
+}
+
+// Turn the guest's camera with the right stick and keep it behind their ball
+pub fn guest_camera_control(
+    guest_query: Query<(&Transform, &PartyGuest), Without<GuestCamera>>,
+    gamepad_query: Query<&Gamepad>,
+    mut camera_query: Query<(&mut Transform, &mut GuestCamera)>,
+    time: Res<Time>,
+) {
+    let (Ok((guest_transform, guest)), Ok((mut transform, mut camera))) = (guest_query.get_single(), camera_query.get_single_mut()) else {
+        return;
+    };
+    if let Some(gamepad) = guest.gamepad.and_then(|entity| gamepad_query.get(entity).ok()) {
+        let turn = gamepad.right_stick().x;
+        if turn.abs() > STICK_DEAD_ZONE {
+            camera.yaw -= turn * GUEST_TURN_SPEED * time.delta_secs();
+        }
+    }
+    let target = guest_transform.translation;
+    let offset = -camera_forward(camera.yaw) * GUEST_CAMERA_PITCH.cos() + Vec3::Y * GUEST_CAMERA_PITCH.sin();
+    transform.translation = target + offset * GUEST_CAMERA_DISTANCE;
+    transform.look_at(target, Vec3::Y);
+}
+
+// Split the window between the two players while a guest is playing, and give it back after
+pub fn layout_split_screen(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut main_query: Query<&mut Camera, (With<FollowCamera>, Without<GuestCamera>)>,
+    mut guest_query: Query<&mut Camera, With<GuestCamera>>,
+) {
+    let (Ok(window), Ok(mut main_camera)) = (window_query.get_single(), main_query.get_single_mut()) else {
+        return;
+    };
+    let size = window.physical_size();
+    let half = UVec2::new(size.x / 2, size.y);
+    let (main_viewport, guest_viewport) = if guest_query.is_empty() || half.x == 0 || half.y == 0 {
+        (None, None)
+    } else {
+        let left = Viewport { physical_position: UVec2::ZERO, physical_size: half, ..default() };
+        let right = Viewport { physical_position: UVec2::new(half.x, 0), physical_size: half, ..default() };
+        (Some(left), Some(right))
+    };
+    let same = |current: &Option<Viewport>, wanted: &Option<Viewport>| {
+        current.as_ref().map(|viewport| (viewport.physical_position, viewport.physical_size))
+            == wanted.as_ref().map(|viewport| (viewport.physical_position, viewport.physical_size))
+    };
+    if !same(&main_camera.viewport, &main_viewport) {
+        main_camera.viewport = main_viewport;
+    }
+    for mut camera in guest_query.iter_mut() {
+        if !same(&camera.viewport, &guest_viewport) {
+            camera.viewport = guest_viewport.clone();
+        }
+    }
+}
+
+// Show the guest's name, health and boulders in their half of the screen
+pub fn update_guest_hud(
+    session: Res<NetSession>,
+    guest_query: Query<(&PartyGuest, &Health)>,
+    mut text_query: Query<&mut Text, With<GuestHudText>>,
+) {
+    let (Some(id), Ok((guest, health))) = (session.guest_id, guest_query.get_single()) else {
+        return;
+    };
+    let name = session.player_name(id).unwrap_or("Player");
+    let status = if health.current <= 0.0 {
+        format!("{}  Knocked out", name)
+    } else {
+        let charge = guest.charge.map(|level| format!("  Charge {:.0}%", level * 100.0)).unwrap_or_default();
+        format!("{}  Health {:.0}  Boulders {}/{}{}", name, health.current.max(0.0), guest.ammo.current, guest.ammo.max, charge)
+    };
+    for mut text in text_query.iter_mut() {
+        if text.0 != status {
+            text.0 = status.clone();
+        }
+    }
+}
+
+// Hide the guest's ball once they are knocked out; they sit out the rest of the match
+pub fn hide_knocked_out_guest(mut guest_query: Query<(&Health, &mut Visibility), (With<PartyGuest>, Changed<Health>)>) {
+    for (health, mut visibility) in guest_query.iter_mut() {
+        if health.current <= 0.0 {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+// Plugin for a second local player joining a LAN session in split-screen
+pub struct PartyPlugin;
+
+impl Plugin for PartyPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<GuestInput>()
+            .add_systems(OnEnter(GameState::Playing), spawn_guest)
+            .add_systems(OnExit(GameState::GameOver), despawn_guest)
+            .add_systems(Update, despawn_guest.run_if(resource_removed::<NetSession>))
+            .add_systems(PreUpdate, latch_guest_input
+                .after(update_action_state)
+                .run_if(in_state(GameState::Playing)))
+            .add_systems(FixedUpdate, (move_guest, guest_throw)
+                .in_set(SimSet::Movement)
+                .run_if(resource_exists::<NetSession>)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)))
+            .add_systems(FixedLast, clear_guest_presses)
+            .add_systems(Update, (guest_camera_control, hide_knocked_out_guest)
+                .run_if(in_state(GameState::Playing)))
+            .add_systems(Update, update_guest_hud.run_if(resource_exists::<NetSession>))
+            .add_systems(PostUpdate, layout_split_screen);
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use crate::camera::{FollowCamera, TargetCursor};
+use crate::GameState;
+use crate::screenshot::take_screenshot;
+use crate::sim::{PauseReason, SimClock};
+
+// Photo mode constants
+const PHOTO_MOVE_SPEED: f32 = 8.0; // Free camera speed in units per second
+const PHOTO_FAST_MULTIPLIER: f32 = 3.0; // Speed multiplier while holding Ctrl
+const PHOTO_LOOK_SENSITIVITY: f32 = 0.004; // Radians per pixel of mouse drag
+const PHOTO_ROLL_SPEED: f32 = 1.0; // Radians per second
+const PHOTO_FOV_STEP: f32 = 0.05; // Radians per scroll line
+const PHOTO_MIN_FOV: f32 = 0.2;
+const PHOTO_MAX_FOV: f32 = 2.2;
+
+// Whether photo mode is active
+#[derive(States, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum PhotoModeState {
+    #[default]
+    Inactive,
+    Active,
+}
+
+// Free camera orientation while in photo mode
+#[derive(Resource, Default)]
+pub struct PhotoCamera {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub roll: f32,
+    // Field of view to restore when leaving photo mode
+    pub saved_fov: Option<f32>,
+    // UI roots hidden on entry, with the visibility to restore on exit
+    pub hidden_ui: Vec<(Entity, Visibility)>,
+}
+
+// Toggle photo mode with P
+pub fn toggle_photo_mode(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<PhotoModeState>>,
+    mut next_state: ResMut<NextState<PhotoModeState>>,
+) {
+    if keys.just_pressed(KeyCode::KeyP) {
+        next_state.set(match state.get() {
+            PhotoModeState::Inactive => PhotoModeState::Active,
+            PhotoModeState::Active => PhotoModeState::Inactive,
+        });
+    }
+}
+
+// Freeze the simulation, hide UI and take over the camera
+pub fn enter_photo_mode(
+    mut clock: ResMut<SimClock>,
+    mut photo_camera: ResMut<PhotoCamera>,
+    camera_query: Query<(&Transform, &Projection), With<FollowCamera>>,
+    mut cursor_query: Query<&mut Visibility, With<TargetCursor>>,
+    mut ui_query: Query<(Entity, &mut Visibility), (With<Node>, Without<Parent>, Without<TargetCursor>)>,
+) {
+    // Pausing the clock stops everything driven by Time, including FixedUpdate
+    clock.pause(PauseReason::PhotoMode);
+
+    if let Ok((transform, projection)) = camera_query.get_single() {
+        let (yaw, pitch, roll) = transform.rotation.to_euler(EulerRot::YXZ);
+        photo_camera.yaw = yaw;
+        photo_camera.pitch = pitch;
+        photo_camera.roll = roll;
+        photo_camera.saved_fov = match projection {
+            Projection::Perspective(perspective) => Some(perspective.fov),
+            _ => None,
+        };
+    }
+
+    for mut visibility in cursor_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+    photo_camera.hidden_ui.clear();
+    for (entity, mut visibility) in ui_query.iter_mut() {
+        photo_camera.hidden_ui.push((entity, *visibility));
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Resume the simulation and hand the camera back to the follow systems
+pub fn exit_photo_mode(
+    mut clock: ResMut<SimClock>,
+    mut photo_camera: ResMut<PhotoCamera>,
+    mut camera_query: Query<&mut Projection, With<FollowCamera>>,
+    mut ui_query: Query<&mut Visibility, (With<Node>, Without<Parent>)>,
+) {
+    clock.resume(PauseReason::PhotoMode);
+
+    if let (Ok(mut projection), Some(fov)) = (camera_query.get_single_mut(), photo_camera.saved_fov) {
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov = fov;
+        }
+    }
+
+    // Restore each UI root to what it was, so toggled-off panels stay hidden
+    for (entity, visibility) in photo_camera.hidden_ui.drain(..) {
+        if let Ok(mut current) = ui_query.get_mut(entity) {
+            *current = visibility;
+        }
+    }
+}
+
+// Fly the free camera: WASD to move, Space/Shift for up/down, right-drag to look,
+// Q/E to roll and the mouse wheel to change the field of view
+pub fn photo_camera_control(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut motion_events: EventReader<MouseMotion>,
+    mut scroll_events: EventReader<MouseWheel>,
+    mut photo_camera: ResMut<PhotoCamera>,
+    mut camera_query: Query<(&mut Transform, &mut Projection), With<FollowCamera>>,
+    // The simulation clock is paused, so the free camera runs on real time
+    time: Res<Time<Real>>,
+) {
+    let delta = time.delta_secs();
+    let Ok((mut transform, mut projection)) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    // Look around while dragging with the right mouse button
+    let drag: Vec2 = motion_events.read().map(|event| event.delta).sum();
+    if mouse_buttons.pressed(MouseButton::Right) {
+        photo_camera.yaw -= drag.x * PHOTO_LOOK_SENSITIVITY;
+        photo_camera.pitch = (photo_camera.pitch - drag.y * PHOTO_LOOK_SENSITIVITY).clamp(-1.5, 1.5);
+    }
+
+    // Roll
+    if keys.pressed(KeyCode::KeyQ) { photo_camera.roll += PHOTO_ROLL_SPEED * delta; }
+    if keys.pressed(KeyCode::KeyE) { photo_camera.roll -= PHOTO_ROLL_SPEED * delta; }
+    if keys.just_pressed(KeyCode::KeyR) { photo_camera.roll = 0.0; }
+
+    transform.rotation = Quat::from_euler(EulerRot::YXZ, photo_camera.yaw, photo_camera.pitch, photo_camera.roll);
+
+    // Move relative to the current view
+    let mut movement = Vec3::ZERO;
+    if keys.pressed(KeyCode::KeyW) { movement += *transform.forward(); }
+    if keys.pressed(KeyCode::KeyS) { movement -= *transform.forward(); }
+    if keys.pressed(KeyCode::KeyA) { movement -= *transform.right(); }
+    if keys.pressed(KeyCode::KeyD) { movement += *transform.right(); }
+    if keys.pressed(KeyCode::Space) { movement += Vec3::Y; }
+    if keys.pressed(KeyCode::ShiftLeft) { movement -= Vec3::Y; }
+    if movement.length_squared() > 0.0 {
+        let speed = if keys.pressed(KeyCode::ControlLeft) {
+            PHOTO_MOVE_SPEED * PHOTO_FAST_MULTIPLIER
+        } else {
+            PHOTO_MOVE_SPEED
+        };
+        transform.translation += movement.normalize() * speed * delta;
+    }
+
+    // Field of view from the mouse wheel
+    for event in scroll_events.read() {
+        let lines = match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / 20.0,
+        };
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov = (perspective.fov - lines * PHOTO_FOV_STEP).clamp(PHOTO_MIN_FOV, PHOTO_MAX_FOV);
+        }
+    }
+}
+
+// Save the current frame to disk with Enter
+pub fn photo_mode_screenshot(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    if keys.just_pressed(KeyCode::Enter) {
+        take_screenshot(&mut commands);
+    }
+}
+
+// Plugin for photo mode
+pub struct PhotoModePlugin;
+
+impl Plugin for PhotoModePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_state::<PhotoModeState>()
+            .init_resource::<PhotoCamera>()
+            .add_systems(Update, toggle_photo_mode.run_if(in_state(GameState::Playing)))
+            .add_systems(OnEnter(PhotoModeState::Active), enter_photo_mode)
+            .add_systems(OnExit(PhotoModeState::Active), exit_photo_mode)
+            .add_systems(
+                Update,
+                (photo_camera_control, photo_mode_screenshot).run_if(in_state(PhotoModeState::Active)),
+            );
+    }
+}
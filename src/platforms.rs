@@ -0,0 +1,290 @@
+use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::TAU;
+use crate::terrain::{chunk_rng, get_terrain_height, queue_new_chunks, sample, ScatterQueue, TerrainChunk, CHUNK_SIZE};
+use crate::budget::WorkBudgets;
+use crate::player::{Player, PlayerPhysics};
+use crate::sim::{SimSet, Simulation};
+use crate::catapult::Catapult;
+use crate::photo_mode::PhotoModeState;
+use crate::GameState;
+
+// Moving platform constants
+const FERRY_ATTEMPTS: usize = 2; // Spots tried for a ravine ferry in each chunk
+const FERRY_SPAN: f32 = 16.0; // Distance a ferry travels across its ravine
+const RAVINE_DEPTH: f32 = 3.0; // How far below both banks the middle has to dip to count as a ravine
+const FERRY_PERIOD: f32 = 8.0; // Seconds for a ferry's round trip
+const PLATFORM_HALF_EXTENTS: Vec3 = Vec3::new(2.0, 0.25, 2.0); // Half size of a ferry platform
+const SURFACE_TOLERANCE: f32 = 0.3; // How far into a platform top the ball can sink and still stand on it
+const SWEEPER_DISTANCE: f32 = 9.0; // Distance from a catapult to its sweeper's pivot, away from the origin
+const SWEEPER_LENGTH: f32 = 5.0; // Length of a sweeper arm
+const SWEEPER_HALF_THICKNESS: f32 = 0.25; // Half the thickness of a sweeper arm
+const SWEEPER_HEIGHT: f32 = 0.6; // Height of the arm above the ground at the pivot
+const SWEEPER_SPEED: f32 = 1.2; // Radians per second
+const SWEEPER_LIFT: f32 = 2.5; // Upward speed added when the arm knocks the player
+const PLAYER_RADIUS: f32 = 0.5;
+const PLATFORM_SALT: u64 = 3; // Chunk random stream for platforms
+
+// The top of a platform the ball can stand on, and how far it moved this frame
+#[derive(Clone, Copy, Debug)]
+pub struct PlatformSurface {
+    pub center: Vec2,
+    pub half_extents: Vec2,
+    pub top: f32,
+    pub delta: Vec3,
+}
+
+// Platform tops as of this tick, handed to roll_sphere so the balls can stand on them the way they do terrain
+#[derive(Resource, Default)]
+pub struct PlatformSurfaces(pub Vec<PlatformSurface>);
+
+// The highest platform top under a ball at position that it's resting on or above
+pub fn platform_below(surfaces: &[PlatformSurface], position: Vec3, radius: f32) -> Option<PlatformSurface> {
+    surfaces
+        .iter()
+        .filter(|surface| {
+            let offset = (Vec2::new(position.x, position.z) - surface.center).abs();
+            offset.x <= surface.half_extents.x
+                && offset.y <= surface.half_extents.y
+                && position.y - radius >= surface.top - SURFACE_TOLERANCE
+        })
+        .max_by(|a, b| a.top.total_cmp(&b.top))
+        .copied()
+}
+
+// Height a ball at position rests on: the terrain, or a platform above it
+pub fn ground_height(surfaces: &[PlatformSurface], position: Vec3, radius: f32) -> f32 {
+    let terrain = sample(position.x, position.z).height;
+    platform_below(surfaces, position, radius).map_or(terrain, |surface| surface.top.max(terrain))
+}
+
+// A platform ferrying back and forth across a ravine, parented to its terrain chunk
+#[derive(Component)]
+pub struct MovingPlatform {
+    // Ends of the trip, in chunk space
+    pub start: Vec3,
+    pub end: Vec3,
+    // Offset into the trip so nearby ferries don't move in lockstep
+    pub phase: f32,
+}
+
+// A spinning arm near a catapult that knocks the ball away
+#[derive(Component)]
+pub struct Sweeper {
+    pub pivot: Vec3,
+    pub angle: f32,
+}
+
+// Shared meshes and materials for platforms and sweepers
+#[derive(Resource)]
+pub struct PlatformAssets {
+    pub platform_mesh: Handle<Mesh>,
+    pub platform_material: Handle<StandardMaterial>,
+    pub arm_mesh: Handle<Mesh>,
+    pub post_mesh: Handle<Mesh>,
+    pub sweeper_material: Handle<StandardMaterial>,
+}
+
+// Create the shared platform and sweeper meshes and materials
+pub fn setup_platform_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(PlatformAssets {
+        platform_mesh: meshes.add(Cuboid::from_size(PLATFORM_HALF_EXTENTS * 2.0)),
+        platform_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.55, 0.45, 0.3),
+            perceptual_roughness: 0.8,
+            ..default()
+        }),
+        arm_mesh: meshes.add(Cuboid::new(SWEEPER_LENGTH, SWEEPER_HALF_THICKNESS * 2.0, SWEEPER_HALF_THICKNESS * 2.0)),
+        post_mesh: meshes.add(Cylinder::new(0.3, SWEEPER_HEIGHT * 2.0)),
+        sweeper_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.8, 0.75, 0.2),
+            metallic: 0.5,
+            ..default()
+        }),
+    });
+}
+
+// Put ferries over ravines as chunks are generated, a few chunks a frame within the scatter budget; the
+// same every time a chunk loads
+pub fn populate_platforms(
+    mut commands: Commands,
+    assets: Res<PlatformAssets>,
+    budgets: Res<WorkBudgets>,
+    mut queue: Local<ScatterQueue>,
+    chunk_query: Query<(Entity, &TerrainChunk), Added<TerrainChunk>>,
+) {
+    queue_new_chunks(&mut queue, &chunk_query);
+    queue.run(budgets.scatter_ms, |(entity, (chunk_x, chunk_z))| {
+        // A chunk rebuilt before its turn comes back as a new entity
+        let Some(mut chunk_commands) = commands.get_entity(entity) else {
+            return;
+        };
+        let origin = Vec2::new(chunk_x as f32 * CHUNK_SIZE, chunk_z as f32 * CHUNK_SIZE);
+        let mut rng = chunk_rng(chunk_x, chunk_z, PLATFORM_SALT);
+        for _ in 0..FERRY_ATTEMPTS {
+            let start = Vec2::new(rng.gen_range(0.0..CHUNK_SIZE), rng.gen_range(0.0..CHUNK_SIZE));
+            let heading = rng.gen_range(0.0..TAU);
+            let phase = rng.gen_range(0.0..FERRY_PERIOD);
+            let end = start + Vec2::new(heading.cos(), heading.sin()) * FERRY_SPAN;
+            let middle = (start + end) * 0.5;
+
+            // Both banks have to rise well above the middle of the span
+            let height = |local: Vec2| get_terrain_height(origin.x + local.x, origin.y + local.y);
+            let (start_height, end_height) = (height(start), height(end));
+            if height(middle) > start_height.min(end_height) - RAVINE_DEPTH {
+                continue;
+            }
+            let deck = start_height.max(end_height) - PLATFORM_HALF_EXTENTS.y;
+            let start = Vec3::new(start.x, deck, start.y);
+            let end = Vec3::new(end.x, deck, end.y);
+            chunk_commands.with_children(|parent| {
+                parent.spawn((
+                    MovingPlatform { start, end, phase },
+                    Mesh3d(assets.platform_mesh.clone()),
+                    MeshMaterial3d(assets.platform_material.clone()),
+                    Transform::from_translation(start),
+                ));
+            });
+            break;
+        }
+    });
+}
+
+// Place a sweeper on the far side of each new catapult
+pub fn spawn_sweepers(
+    mut commands: Commands,
+    assets: Res<PlatformAssets>,
+    structure_query: Query<&Transform, Added<Catapult>>,
+) {
+    for transform in structure_query.iter() {
+        let center = transform.translation;
+        let away = Vec2::new(center.x, center.z).normalize_or(Vec2::X);
+        let pivot = Vec2::new(center.x, center.z) + away * SWEEPER_DISTANCE;
+        let pivot = Vec3::new(pivot.x, get_terrain_height(pivot.x, pivot.y) + SWEEPER_HEIGHT, pivot.y);
+        commands
+            .spawn((
+                Sweeper { pivot, angle: 0.0 },
+                Transform::from_translation(pivot),
+                Visibility::default(),
+                Name::new("Sweeper"),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    Mesh3d(assets.post_mesh.clone()),
+                    MeshMaterial3d(assets.sweeper_material.clone()),
+                    Transform::from_xyz(0.0, -SWEEPER_HEIGHT * 0.5, 0.0),
+                ));
+                // The arm reaches out from the pivot along local X
+                parent.spawn((
+                    Mesh3d(assets.arm_mesh.clone()),
+                    MeshMaterial3d(assets.sweeper_material.clone()),
+                    Transform::from_xyz(SWEEPER_LENGTH * 0.5, 0.0, 0.0),
+                ));
+            });
+    }
+}
+
+// Remove sweepers along with the catapults at the end of a run
+pub fn despawn_sweepers(mut commands: Commands, sweeper_query: Query<Entity, With<Sweeper>>) {
+    for entity in sweeper_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Move ferries along their trips and publish their tops for the ball physics
+pub fn update_platforms(
+    mut platform_query: Query<(&mut Transform, &MovingPlatform, &Parent)>,
+    chunk_query: Query<&Transform, (With<TerrainChunk>, Without<MovingPlatform>)>,
+    mut surfaces: ResMut<PlatformSurfaces>,
+    simulation: Res<Simulation>,
+) {
+    let t = simulation.elapsed();
+    surfaces.0.clear();
+    for (mut transform, platform, parent) in platform_query.iter_mut() {
+        // Ease in and out at each bank
+        let progress = (1.0 - ((t + platform.phase) / FERRY_PERIOD * TAU).cos()) * 0.5;
+        let next = platform.start.lerp(platform.end, progress);
+        let delta = next - transform.translation;
+        transform.translation = next;
+
+        let chunk_offset = chunk_query.get(parent.get()).map_or(Vec3::ZERO, |chunk| chunk.translation);
+        let world = next + chunk_offset;
+        surfaces.0.push(PlatformSurface {
+            center: Vec2::new(world.x, world.z),
+            half_extents: Vec2::new(PLATFORM_HALF_EXTENTS.x, PLATFORM_HALF_EXTENTS.z),
+            top: world.y + PLATFORM_HALF_EXTENTS.y,
+            delta,
+        });
+    }
+}
+
+// Spin sweepers and knock the player aside when an arm catches them
+pub fn update_sweepers(
+    mut sweeper_query: Query<(&mut Transform, &mut Sweeper), Without<Player>>,
+    mut player_query: Query<(&mut Transform, &mut PlayerPhysics), With<Player>>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    for (mut transform, mut sweeper) in sweeper_query.iter_mut() {
+        sweeper.angle = (sweeper.angle + SWEEPER_SPEED * delta) % TAU;
+        transform.rotation = Quat::from_rotation_y(sweeper.angle);
+    }
+
+    let Ok((mut player_transform, mut physics)) = player_query.get_single_mut() else {
+        return;
+    };
+    for (transform, sweeper) in sweeper_query.iter() {
+        let position = player_transform.translation;
+        if (position.y - sweeper.pivot.y).abs() > PLAYER_RADIUS + SWEEPER_HALF_THICKNESS {
+            continue;
+        }
+
+        // Closest point on the arm in the horizontal plane
+        let arm_direction = transform.rotation * Vec3::X;
+        let arm = Vec2::new(arm_direction.x, arm_direction.z);
+        let pivot = Vec2::new(sweeper.pivot.x, sweeper.pivot.z);
+        let ball = Vec2::new(position.x, position.z);
+        let along = (ball - pivot).dot(arm).clamp(0.0, SWEEPER_LENGTH);
+        let nearest = pivot + arm * along;
+        let offset = ball - nearest;
+        let reach = PLAYER_RADIUS + SWEEPER_HALF_THICKNESS;
+        if offset.length() >= reach {
+            continue;
+        }
+
+        // Push out of the arm and carry the ball along with the arm's sweep
+        let away = offset.normalize_or(arm.perp());
+        let pushed = nearest + away * reach;
+        player_transform.translation.x = pushed.x;
+        player_transform.translation.z = pushed.y;
+        let sweep = arm.perp() * SWEEPER_SPEED * along;
+        let knock = Vec3::new(sweep.x, SWEEPER_LIFT, sweep.y);
+        physics.velocity += knock;
+        physics.momentum += knock;
+        physics.grounded = false;
+    }
+}
+
+// Plugin for ravine ferries and catapult sweepers
+pub struct PlatformPlugin;
+
+impl Plugin for PlatformPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<PlatformSurfaces>()
+            .add_systems(Startup, setup_platform_assets)
+            .add_systems(OnExit(GameState::GameOver), despawn_sweepers)
+            // Chunks generate behind the main menu too, so they need their ferries from the start
+            .add_systems(Update, (populate_platforms, spawn_sweepers))
+            // Surfaces are published before the ball physics stands on them
+            .add_systems(FixedUpdate, update_platforms.in_set(SimSet::Environment))
+            .add_systems(FixedUpdate, update_sweepers
+                .in_set(SimSet::Constraints)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)));
+    }
+}
@@ -1,15 +1,41 @@
 use bevy::prelude::*;
-// Import the get_terrain_height function from the terrain module
-use crate::terrain::get_terrain_height;
-// Import the texture generator from assets module
-use crate::assets::sphere_texture::create_sphere_texture;
+// Import the terrain queries from the terrain module
+use crate::terrain::{get_terrain_height, sample};
+use crate::platforms::{ground_height, platform_below, PlatformSurface, PlatformSurfaces};
+use crate::camera::CameraShake;
+use crate::photo_mode::PhotoModeState;
+use crate::minimap::MinimapBlip;
+use crate::network::spectating;
+use crate::GameState;
+use crate::replay::{BUTTON_DASH, BUTTON_JUMP};
+use crate::sim::{Interpolated, SimInput, SimSet};
+use crate::tuning::{LiveTuning, PlayerTuning};
 
 // Player component
 #[derive(Component)]
 pub struct Player;
 
-// Physics component for the player
+// Hit points for the player
 #[derive(Component)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+    
+    // Fraction of health remaining in 0..1
+    pub fn fraction(&self) -> f32 {
+        (self.current / self.max).clamp(0.0, 1.0)
+    }
+}
+
+// Physics component for the player
+#[derive(Component, Reflect, Clone)]
+#[reflect(Component)]
 pub struct PlayerPhysics {
     // Velocity in world space
     pub velocity: Vec3,
@@ -23,6 +49,16 @@ pub struct PlayerPhysics {
     pub momentum: Vec3,
     // Previous position - used for calculating proper rotation
     pub prev_position: Vec3,
+    // Seconds until the dash can be used again
+    pub dash_cooldown: f32,
+    // Scales the drive force and top speed, raised by speed boosts
+    pub speed_multiplier: f32,
+    // Grip on the ground: scales the drive force up and the slide down slopes down
+    pub traction: f32,
+    // Fraction of the downhill pull taken away, from the difficulty's slope assist
+    pub slope_assist: f32,
+    // Radius of the ball; the split balls of a multiball are smaller
+    pub radius: f32,
 }
 
 impl Default for PlayerPhysics {
@@ -34,28 +70,23 @@ impl Default for PlayerPhysics {
             grounded: false,
             momentum: Vec3::ZERO,
             prev_position: Vec3::ZERO,
+            dash_cooldown: 0.0,
+            speed_multiplier: 1.0,
+            traction: 1.0,
+            slope_assist: 0.0,
+            radius: PLAYER_RADIUS,
         }
     }
 }
 
-// Player physics constants
-const MOVE_SPEED: f32 = 1.5; // Reduced from 3.0
-const GRAVITY: f32 = 9.8;
-const FRICTION: f32 = 0.95; // Slightly increased friction (was 0.98)
-const TERRAIN_SENSITIVITY: f32 = 0.3; // Reduced from 0.7
-const MOMENTUM_FACTOR: f32 = 0.85; // Reduced from 0.92 (less momentum preservation)
-const RESTITUTION: f32 = 0.4; // Reduced from 0.6 (less bouncy)
-const MASS_FACTOR: f32 = 0.8; // Increased from 0.5 (feels heavier)
-const MAX_SPEED: f32 = 6.0; // Reduced from 10.0
-const JUMP_FORCE: f32 = 8.0; // Force applied when jumping
+// Player constants; the rolling physics values are in GameTuning
+const PLAYER_MAX_HEALTH: f32 = 100.0; // Starting and maximum health
+pub const PLAYER_RADIUS: f32 = 0.5; // Radius of a full-size ball
+const HARD_LANDING_SPEED: f32 = 6.0; // Landing speed above which the camera shakes
+const LANDING_TRAUMA_SCALE: f32 = 0.08; // Camera trauma per unit of speed above the hard landing threshold
 
-// Create a player entity
-pub fn spawn_player(
-    commands: &mut Commands,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
-    texture_assets: &mut ResMut<Assets<Image>>,
-) {
+// Where the player starts a run
+pub fn player_spawn_position() -> Vec3 {
     // Calculate initial terrain height at spawn position
     let initial_x = 0.0;
     let initial_z = 0.0;
@@ -64,12 +95,21 @@ pub fn spawn_player(
     // Add player sphere positioned exactly on the terrain with a small offset to ensure collision
     let sphere_radius = 0.5;
     // Start the sphere higher above the terrain to allow gravity to visibly pull it down
-    let initial_position = Vec3::new(initial_x, terrain_height + sphere_radius + 2.0, initial_z);
+    Vec3::new(initial_x, terrain_height + sphere_radius + 2.0, initial_z)
+}
+
+// Create a player entity, wearing a sphere texture with a pattern to show rotation
+pub fn spawn_player(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    texture: Handle<Image>,
+) {
+    let initial_position = player_spawn_position();
     
-    // Create a textured material for the sphere with a pattern to show rotation
-    let texture_handle = texture_assets.add(create_sphere_texture());
+    // Create a textured material for the sphere
     let material = StandardMaterial {
-        base_color_texture: Some(texture_handle),
+        base_color_texture: Some(texture),
         alpha_mode: AlphaMode::Blend,
         ..default()
     };
@@ -83,204 +123,255 @@ pub fn spawn_player(
         Mesh3d(meshes.add(Mesh::from(bevy::prelude::Sphere { radius: 0.5 }))),
         MeshMaterial3d(materials.add(material)),
         Transform::from_xyz(initial_position.x, initial_position.y, initial_position.z),
+        Interpolated::new(initial_position),
+        MinimapBlip { color: Color::srgb(0.2, 0.6, 1.0) },
+        Health::new(PLAYER_MAX_HEALTH),
     ));
 }
 
-// Handle player movement based on keyboard input and physics
+// What drives a rolling sphere for one step: a direction to push in (at most unit length) and jump and dash requests
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RollInput {
+    pub direction: Vec3,
+    pub jump: bool,
+    pub dash: bool,
+}
+
+impl RollInput {
+    // Push in a direction without jumping or dashing, as the AI balls do
+    pub fn steer(direction: Vec3) -> Self {
+        Self { direction, ..default() }
+    }
+
+    // Read from the latched input of a tick
+    pub fn from_sim(input: &SimInput) -> Self {
+        Self {
+            direction: input.move_direction(),
+            jump: input.just_pressed(BUTTON_JUMP),
+            dash: input.just_pressed(BUTTON_DASH),
+        }
+    }
+}
+
+// Handle player movement based on the latched input and physics, once per simulation tick
 pub fn move_player(
     mut player_query: Query<(&mut Transform, &mut PlayerPhysics), With<Player>>,
-    keys: Res<ButtonInput<KeyCode>>,
+    input: Res<SimInput>,
+    platforms: Res<PlatformSurfaces>,
+    tuning: Res<LiveTuning>,
+    mut camera_shake: ResMut<CameraShake>,
     time: Res<Time>,
 ) {
     let delta = time.delta_secs();
     
     for (mut transform, mut physics) in player_query.iter_mut() {
-        // Store previous position for calculating rotation
-        physics.prev_position = transform.translation;
-        
-        let mut input_direction = Vec3::ZERO;
+        // Directional input already turned to match the camera, with jump and dash requests
+        let impact = roll_sphere(&mut transform, &mut physics, &tuning.0.player, &platforms.0, RollInput::from_sim(&input), delta);
+        if impact > HARD_LANDING_SPEED {
+            // Hard landings jolt the camera
+            camera_shake.add_trauma((impact - HARD_LANDING_SPEED) * LANDING_TRAUMA_SCALE + 0.2);
+        }
+    }
+}
 
-        // Get directional input
-        if keys.pressed(KeyCode::KeyW) { input_direction.z -= 1.0; }
-        if keys.pressed(KeyCode::KeyS) { input_direction.z += 1.0; }
-        if keys.pressed(KeyCode::KeyA) { input_direction.x -= 1.0; }
-        if keys.pressed(KeyCode::KeyD) { input_direction.x += 1.0; }
-        
-        // Detect jump request (spacebar)
-        let jump_requested = keys.just_pressed(KeyCode::Space);
+// Advance a rolling sphere by one frame: terrain slope, platforms, gravity, bounces, jumps, dashes and the
+// drive force from the input's direction. Shared by the player and enemy balls.
+// Returns the downward speed of a landing this frame, or zero.
+pub fn roll_sphere(
+    transform: &mut Transform,
+    physics: &mut PlayerPhysics,
+    tuning: &PlayerTuning,
+    platforms: &[PlatformSurface],
+    input: RollInput,
+    delta: f32,
+) -> f32 {
+    let RollInput { direction: input_direction, jump: jump_requested, dash: dash_requested } = input;
+    let mut landing_impact = 0.0;
+    
+    // Store previous position for calculating rotation
+    physics.prev_position = transform.translation;
+    physics.dash_cooldown = (physics.dash_cooldown - delta).max(0.0);
+    
+    let sphere_radius = physics.radius;
 
-        // Normalize input if there is any
-        if input_direction.length_squared() > 0.0 {
-            input_direction = input_direction.normalize();
+    // Ride along with a platform the ball is resting on
+    let platform = platform_below(platforms, transform.translation, sphere_radius)
+        .filter(|surface| transform.translation.y - sphere_radius <= surface.top + 0.05);
+    if let Some(surface) = platform {
+        transform.translation += surface.delta;
+    }
+
+    // Get current ground height and surrounding terrain heights to calculate slope
+    let pos = transform.translation;
+    let current_height = ground_height(platforms, pos, sphere_radius);
+    
+    // Downhill direction of the terrain, scaled by how steep it is
+    let uphill = sample(pos.x, pos.z).gradient;
+    let gradient = Vec3::new(-uphill.x, 0.0, -uphill.y);
+    
+    // Calculate gradient strength - steeper slopes have stronger effects; platforms are flat
+    let gradient_strength = if platform.is_some() { 0.0 } else { gradient.length() };
+    
+    // Check if player is on the ground
+    let was_grounded = physics.grounded;
+    // Use a slightly larger tolerance for ground detection to prevent flickering between grounded states
+    physics.grounded = pos.y <= current_height + sphere_radius + 0.05;
+    
+    // Calculate effective mass (can be adjusted based on gameplay needs)
+    let effective_mass = physics.mass * tuning.mass_factor;
+    
+    // Apply momentum preservation - but only to horizontal components when grounded
+    if physics.momentum.length_squared() > 0.001 {
+        if physics.grounded {
+            // Only blend horizontal momentum when grounded (preserve y component)
+            let y_vel = physics.velocity.y;
+            physics.velocity = physics.velocity.lerp(physics.momentum * (1.0 / effective_mass), 0.2);
+            physics.velocity.y = y_vel; // Restore original y velocity
+        } else {
+            // Full momentum blending when in air
+            physics.velocity = physics.velocity.lerp(physics.momentum * (1.0 / effective_mass), 0.2);
         }
-        
-        // Get current terrain height and surrounding terrain heights to calculate slope
-        let pos = transform.translation;
-        let current_height = get_terrain_height(pos.x, pos.z);
-        
-        // Sample terrain at nearby points to calculate slope
-        let sample_dist = 0.5;
-        let height_x_pos = get_terrain_height(pos.x + sample_dist, pos.z);
-        let height_x_neg = get_terrain_height(pos.x - sample_dist, pos.z);
-        let height_z_pos = get_terrain_height(pos.x, pos.z + sample_dist);
-        let height_z_neg = get_terrain_height(pos.x, pos.z - sample_dist);
-        
-        // Calculate terrain gradient (slope direction)
-        let gradient = Vec3::new(
-            (height_x_neg - height_x_pos) / (2.0 * sample_dist), // negative X gradient
-            0.0,
-            (height_z_neg - height_z_pos) / (2.0 * sample_dist)  // negative Z gradient
-        );
-        
-        // Calculate gradient strength - steeper slopes have stronger effects
-        let gradient_strength = gradient.length();
-        
-        // Check if player is on the ground
-        let sphere_radius = 0.5;
-        let was_grounded = physics.grounded;
-        // Use a slightly larger tolerance for ground detection to prevent flickering between grounded states
-        physics.grounded = pos.y <= current_height + sphere_radius + 0.05;
-        
-        // Calculate effective mass (can be adjusted based on gameplay needs)
-        let effective_mass = physics.mass * MASS_FACTOR;
-        
-        // Apply momentum preservation - but only to horizontal components when grounded
-        if physics.momentum.length_squared() > 0.001 {
-            if physics.grounded {
-                // Only blend horizontal momentum when grounded (preserve y component)
-                let y_vel = physics.velocity.y;
-                physics.velocity = physics.velocity.lerp(physics.momentum * (1.0 / effective_mass), 0.2);
-                physics.velocity.y = y_vel; // Restore original y velocity
+    }
+    
+    // Apply gravity if not grounded
+    if !physics.grounded {
+        physics.velocity.y -= tuning.gravity * delta;
+    } else {
+        if !was_grounded {
+            // Just landed - apply impact and bounce
+            let impact = physics.velocity.y.abs();
+            landing_impact = impact;
+            if impact > 0.5 {
+                // Bounce based on restitution and impact force
+                physics.velocity.y = impact * tuning.restitution;
             } else {
-                // Full momentum blending when in air
-                physics.velocity = physics.velocity.lerp(physics.momentum * (1.0 / effective_mass), 0.2);
+                physics.velocity.y = 0.0;
             }
-        }
-        
-        // Apply gravity if not grounded
-        if !physics.grounded {
-            physics.velocity.y -= GRAVITY * delta;
         } else {
-            if !was_grounded {
-                // Just landed - apply impact and bounce
-                let impact = physics.velocity.y.abs();
-                if impact > 0.5 {
-                    // Bounce based on restitution and impact force
-                    physics.velocity.y = impact * RESTITUTION;
-                } else {
-                    physics.velocity.y = 0.0;
-                }
-            } else {
-                // On ground - roll due to gradient with mass taken into account
-                if gradient_strength > 0.001 {
-                    // Add force based on terrain gradient (roll downhill)
-                    // Steeper slopes cause more acceleration
-                    let slope_force = gradient.normalize() * gradient_strength * TERRAIN_SENSITIVITY;
-                    
-                    // Apply force with consideration for mass
-                    let slope_acceleration = slope_force * (GRAVITY / effective_mass);
-                    // Apply slope forces gradually to prevent sudden acceleration
-                    physics.velocity.x += slope_acceleration.x * delta * 0.7; // Added dampening factor
-                    physics.velocity.z += slope_acceleration.z * delta * 0.7; // Added dampening factor
-                }
+            // On ground - roll due to gradient with mass taken into account
+            if gradient_strength > 0.001 {
+                // Add force based on terrain gradient (roll downhill)
+                // Steeper slopes cause more acceleration
+                let slope_force = gradient.normalize() * gradient_strength * tuning.terrain_sensitivity;
                 
-                // Apply rolling friction on ground (billiard balls have low friction)
-                physics.velocity.x *= FRICTION; 
-                physics.velocity.z *= FRICTION;
-                
-                // Only zero out y velocity when properly grounded
-                if physics.velocity.y < 0.0 {
-                    physics.velocity.y = 0.0;
-                }
+                // Apply force with consideration for mass
+                let slope_acceleration = slope_force * (tuning.gravity / effective_mass) / physics.traction * (1.0 - physics.slope_assist);
+                // Apply slope forces gradually to prevent sudden acceleration
+                physics.velocity.x += slope_acceleration.x * delta * 0.7; // Added dampening factor
+                physics.velocity.z += slope_acceleration.z * delta * 0.7; // Added dampening factor
             }
-        }
-        
-        // Apply jump force if spacebar is pressed and player is grounded
-        if physics.grounded && jump_requested {
-            // Apply upward force - combined with existing momentum
-            physics.velocity.y = JUMP_FORCE;
-            // Set grounded to false since we're now in the air
-            physics.grounded = false;
-        }
-        
-        // Apply player input force (with mass factored in)
-        if physics.grounded && input_direction.length_squared() > 0.0 {
-            let input_force = input_direction * (MOVE_SPEED / effective_mass);
-            // Reduced multiplier from 5.0 to 2.5
-            physics.velocity.x += input_force.x * delta * 2.5;
-            physics.velocity.z += input_force.z * delta * 2.5;
             
-            // Strictly ensure no y-velocity is added from movement inputs when grounded
-            if physics.velocity.y > 0.0 && physics.grounded && !jump_requested {
+            // Apply rolling friction on ground (billiard balls have low friction)
+            physics.velocity.x *= tuning.friction; 
+            physics.velocity.z *= tuning.friction;
+            
+            // Only zero out y velocity when properly grounded
+            if physics.velocity.y < 0.0 {
                 physics.velocity.y = 0.0;
             }
         }
-        
-        // Update momentum - for horizontal components only when grounded
-        if physics.grounded {
-            // When grounded, only update horizontal momentum
-            let new_momentum = Vec3::new(
-                physics.velocity.x,
-                physics.momentum.y, // Keep vertical momentum separate
-                physics.velocity.z
-            );
-            physics.momentum = physics.momentum.lerp(new_momentum, 1.0 - MOMENTUM_FACTOR);
-            
-            // Force vertical momentum to zero when grounded
-            physics.momentum.y = 0.0;
+    }
+    
+    // Apply jump force if spacebar is pressed and player is grounded
+    if physics.grounded && jump_requested {
+        // Apply upward force - combined with existing momentum
+        physics.velocity.y = tuning.jump_force;
+        // Set grounded to false since we're now in the air
+        physics.grounded = false;
+    }
+    
+    // Dash: a burst of horizontal speed in the input direction, or along the current heading
+    if physics.grounded && dash_requested && physics.dash_cooldown <= 0.0 {
+        let heading = if input_direction.length_squared() > 0.0 {
+            input_direction.normalize()
         } else {
-            // Normal momentum update when in the air
-            physics.momentum = physics.momentum.lerp(physics.velocity, 1.0 - MOMENTUM_FACTOR);
+            Vec3::new(physics.velocity.x, 0.0, physics.velocity.z).normalize_or_zero()
+        };
+        if heading != Vec3::ZERO {
+            physics.velocity += heading * tuning.dash_impulse;
+            physics.dash_cooldown = tuning.dash_cooldown;
         }
+    }
+    
+    // Apply player input force (with mass factored in)
+    if physics.grounded && input_direction.length_squared() > 0.0 {
+        let input_force = input_direction * (tuning.move_speed * physics.speed_multiplier * physics.traction / effective_mass);
+        // Reduced multiplier from 5.0 to 2.5
+        physics.velocity.x += input_force.x * delta * 2.5;
+        physics.velocity.z += input_force.z * delta * 2.5;
         
-        // Cap maximum speed for gameplay reasons
-        let horiz_speed_squared = physics.velocity.x * physics.velocity.x + physics.velocity.z * physics.velocity.z;
-        if horiz_speed_squared > MAX_SPEED * MAX_SPEED {
-            let horiz_speed = horiz_speed_squared.sqrt();
-            let scale = MAX_SPEED / horiz_speed;
-            physics.velocity.x *= scale;
-            physics.velocity.z *= scale;
+        // Strictly ensure no y-velocity is added from movement inputs when grounded
+        if physics.velocity.y > 0.0 && physics.grounded && !jump_requested {
+            physics.velocity.y = 0.0;
         }
+    }
+    
+    // Update momentum - for horizontal components only when grounded
+    if physics.grounded {
+        // When grounded, only update horizontal momentum
+        let new_momentum = Vec3::new(
+            physics.velocity.x,
+            physics.momentum.y, // Keep vertical momentum separate
+            physics.velocity.z
+        );
+        physics.momentum = physics.momentum.lerp(new_momentum, 1.0 - tuning.momentum_factor);
         
-        // Apply velocity to position
-        transform.translation += physics.velocity * delta;
-        
-        // Enforce height constraint based on terrain
-        let terrain_height = get_terrain_height(transform.translation.x, transform.translation.z);
-        let min_height = terrain_height + sphere_radius;
+        // Force vertical momentum to zero when grounded
+        physics.momentum.y = 0.0;
+    } else {
+        // Normal momentum update when in the air
+        physics.momentum = physics.momentum.lerp(physics.velocity, 1.0 - tuning.momentum_factor);
+    }
+    
+    // Cap maximum speed for gameplay reasons
+    let horiz_speed_squared = physics.velocity.x * physics.velocity.x + physics.velocity.z * physics.velocity.z;
+    let max_speed = tuning.max_speed * physics.speed_multiplier;
+    if horiz_speed_squared > max_speed * max_speed {
+        let horiz_speed = horiz_speed_squared.sqrt();
+        let scale = max_speed / horiz_speed;
+        physics.velocity.x *= scale;
+        physics.velocity.z *= scale;
+    }
+    
+    // Apply velocity to position
+    transform.translation += physics.velocity * delta;
+    
+    // Enforce height constraint based on terrain and platforms
+    let min_height = ground_height(platforms, transform.translation, sphere_radius) + sphere_radius;
+    
+    if transform.translation.y < min_height {
+        transform.translation.y = min_height;
+        physics.grounded = true;
         
-        if transform.translation.y < min_height {
-            transform.translation.y = min_height;
-            physics.grounded = true;
-            
-            // Adjust velocity when hitting ground
-            if physics.velocity.y < 0.0 {
-                physics.velocity.y = 0.0;
-            }
+        // Adjust velocity when hitting ground
+        if physics.velocity.y < 0.0 {
+            physics.velocity.y = 0.0;
         }
+    }
+    
+    // Calculate angular velocity based on linear movement
+    if physics.grounded && physics.velocity.length() > 0.1 {
+        // For a sphere, angular velocity is proportional to linear velocity divided by radius
+        // ω = v/r for a rolling sphere
+        let move_dir = Vec3::new(physics.velocity.x, 0.0, physics.velocity.z).normalize();
+        // This is the axis perpendicular to movement direction (right side of sphere)
+        let right_axis = Vec3::new(-move_dir.z, 0.0, move_dir.x);
         
-        // Calculate angular velocity based on linear movement
-        if physics.grounded && physics.velocity.length() > 0.1 {
-            // For a sphere, angular velocity is proportional to linear velocity divided by radius
-            // ω = v/r for a rolling sphere
-            let move_dir = Vec3::new(physics.velocity.x, 0.0, physics.velocity.z).normalize();
-            // This is the axis perpendicular to movement direction (right side of sphere)
-            let right_axis = Vec3::new(-move_dir.z, 0.0, move_dir.x);
-            
-            // Angular velocity around the right axis (perpendicular to movement)
-            // Negative sign creates proper topspin (rotates top of sphere in direction of movement)
-            let speed = physics.velocity.length();
-            physics.angular_velocity = right_axis * (-speed / sphere_radius);
-        } else {
-            // Gradually reduce angular velocity when not moving
-            physics.angular_velocity *= 0.95;
-        }
+        // Angular velocity around the right axis (perpendicular to movement)
+        // Negative sign creates proper topspin (rotates top of sphere in direction of movement)
+        let speed = physics.velocity.length();
+        physics.angular_velocity = right_axis * (-speed / sphere_radius);
+    } else {
+        // Gradually reduce angular velocity when not moving
+        physics.angular_velocity *= 0.95;
     }
+    
+    landing_impact
 }
 
-// Apply visual rotation to match physics rolling
+// Apply visual rotation to match physics rolling, for the player and anything else rolling like it
 pub fn apply_physics(
-    mut player_query: Query<(&mut Transform, &PlayerPhysics), With<Player>>,
+    mut player_query: Query<(&mut Transform, &PlayerPhysics)>,
     time: Res<Time>,
 ) {
     let delta = time.delta_secs();
@@ -309,14 +400,48 @@ pub fn apply_physics(
     }
 }
 
+// End the run when the player runs out of health
+pub fn check_player_death(
+    player_query: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if let Ok(health) = player_query.get_single() {
+        if health.current <= 0.0 {
+            next_state.set(GameState::GameOver);
+        }
+    }
+}
+
+// Put the player back at the spawn point with full health for a new run
+pub fn reset_player(mut player_query: Query<(&mut Transform, &mut PlayerPhysics, &mut Health), With<Player>>) {
+    let initial_position = player_spawn_position();
+    for (mut transform, mut physics, mut health) in player_query.iter_mut() {
+        *transform = Transform::from_translation(initial_position);
+        *physics = PlayerPhysics {
+            prev_position: initial_position,
+            ..Default::default()
+        };
+        health.current = health.max;
+    }
+}
+
 // Plugin for the player module
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(Update, move_player)
+            .register_type::<PlayerPhysics>()
+            .add_systems(FixedUpdate, move_player
+                .in_set(SimSet::Movement)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)))
+            .add_systems(Update, check_player_death
+                .run_if(in_state(GameState::Playing))
+                .run_if(not(spectating)))
+            // Leaving the results screen, by retrying or going to the menu, starts from scratch
+            .add_systems(OnExit(GameState::GameOver), reset_player)
             // Add physics system running at a fixed timestep for consistent physics
-            .add_systems(FixedUpdate, apply_physics);
+            .add_systems(FixedUpdate, apply_physics.run_if(in_state(GameState::Playing)));
     }
 }
@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+use bevy::asset::LoadState;
+use crate::assets::models::ModelRegistry;
+use crate::assets::palette::ColorPalette;
+use crate::assets::sphere_texture::create_sphere_texture;
+use crate::audio::sound_paths;
+use crate::camera::{CursorRingAssets, FollowCamera};
+use crate::catapult::HOSTILE_BOULDER_COLOR;
+use crate::projectile::{ProjectileAssets, PLAYER_BOULDER_COLOR};
+use crate::GameState;
+
+// Preload constants
+const WARMUP_FRAMES: u32 = 3; // Frames the warmup meshes stay in view so their pipelines are compiled
+const LOAD_TIMEOUT: f32 = 10.0; // Longest wait for sound and model files before moving on without them
+const WARMUP_DISTANCE: f32 = 2.0; // How far in front of the camera the warmup meshes sit
+const WARMUP_SCALE: f32 = 0.01; // Small enough to hide behind the first screen
+
+// State to enter once preloading is done, chosen in main from the command line
+#[derive(Resource)]
+pub struct PreloadNext(pub GameState);
+
+// The ball texture in every palette, generated before anything needs one so switching palettes is a
+// handle swap
+#[derive(Resource)]
+pub struct SphereTextures {
+    textures: Vec<(ColorPalette, Handle<Image>)>,
+}
+
+impl SphereTextures {
+    pub fn get(&self, palette: ColorPalette) -> Handle<Image> {
+        self.textures
+            .iter()
+            .find(|(textured, _)| *textured == palette)
+            .map(|(_, texture)| texture.clone())
+            .unwrap_or_default()
+    }
+}
+
+// Sound files held from the start so the first impact or click doesn't wait on the disk
+#[derive(Resource, Default)]
+pub struct PreloadedSounds {
+    sounds: Vec<Handle<AudioSource>>,
+}
+
+// How far preloading has got
+#[derive(Resource, Default)]
+pub struct PreloadProgress {
+    warmup_spawned: bool,
+    warmup_frames: u32,
+    elapsed: f32,
+}
+
+// Marker for meshes shown briefly so the GPU builds their pipelines before they appear in play
+#[derive(Component)]
+pub struct WarmupMesh;
+
+// Marker for the preload screen UI root
+#[derive(Component)]
+pub struct PreloadRoot;
+
+// Generate the ball textures; PreStartup so the player spawned in setup can wear one
+pub fn generate_sphere_textures(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let textures = ColorPalette::ALL
+        .into_iter()
+        .map(|palette| (palette, images.add(create_sphere_texture(palette))))
+        .collect();
+    commands.insert_resource(SphereTextures { textures });
+}
+
+// Start loading every sound effect
+pub fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let sounds = sound_paths().map(|path| asset_server.load(path)).collect();
+    commands.insert_resource(PreloadedSounds { sounds });
+}
+
+// Cover the screen while assets warm up
+pub fn spawn_preload_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            PreloadRoot,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.05, 0.07, 0.05)),
+            GlobalZIndex(10),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Preparing"),
+                TextFont {
+                    font_size: 32.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+// Put a tiny copy of each shared boulder and cursor material in front of the camera; the first time
+// a mesh is drawn with a material its GPU pipeline is compiled, which is the hitch this moves here
+fn spawn_warmup_meshes(
+    commands: &mut Commands,
+    camera: Entity,
+    projectile_assets: &mut ProjectileAssets,
+    ring_assets: &CursorRingAssets,
+    materials: &mut Assets<StandardMaterial>,
+) {
+    let mut pairs = vec![
+        (ring_assets.mesh.clone(), ring_assets.in_range_material.clone()),
+        (ring_assets.mesh.clone(), ring_assets.out_of_range_material.clone()),
+    ];
+    for color in [PLAYER_BOULDER_COLOR, HOSTILE_BOULDER_COLOR] {
+        pairs.push((projectile_assets.mesh.clone(), projectile_assets.material(materials, color)));
+    }
+    commands.entity(camera).with_children(|parent| {
+        for (mesh, material) in pairs {
+            parent.spawn((
+                WarmupMesh,
+                Mesh3d(mesh),
+                MeshMaterial3d(material),
+                Transform::from_xyz(0.0, 0.0, -WARMUP_DISTANCE).with_scale(Vec3::splat(WARMUP_SCALE)),
+            ));
+        }
+    });
+}
+
+// Warm up the pipelines and wait for the sounds and models, then move on to the state main asked for
+pub fn update_preload(
+    mut commands: Commands,
+    mut progress: ResMut<PreloadProgress>,
+    mut projectile_assets: ResMut<ProjectileAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    ring_assets: Res<CursorRingAssets>,
+    sounds: Res<PreloadedSounds>,
+    models: Res<ModelRegistry>,
+    asset_server: Res<AssetServer>,
+    next: Res<PreloadNext>,
+    camera_query: Query<Entity, With<FollowCamera>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    time: Res<Time>,
+) {
+    if !progress.warmup_spawned {
+        let Ok(camera) = camera_query.get_single() else {
+            return;
+        };
+        spawn_warmup_meshes(&mut commands, camera, &mut projectile_assets, &ring_assets, &mut materials);
+        progress.warmup_spawned = true;
+    }
+    progress.warmup_frames += 1;
+    progress.elapsed += time.delta_secs();
+
+    // Missing files count as done; they fail the same way later
+    let sounds_ready = sounds.sounds.iter().all(|sound| {
+        matches!(asset_server.load_state(sound.id()), LoadState::Loaded | LoadState::Failed(_))
+    });
+    let ready = sounds_ready && models.settled(&asset_server);
+    if progress.warmup_frames < WARMUP_FRAMES || !(ready || progress.elapsed > LOAD_TIMEOUT) {
+        return;
+    }
+    if !ready {
+        println!("Sounds or models still loading after {} seconds, starting anyway", LOAD_TIMEOUT);
+    }
+    next_state.set(next.0);
+}
+
+// Remove the warmup meshes and the preload screen
+pub fn finish_preload(mut commands: Commands, query: Query<Entity, Or<(With<WarmupMesh>, With<PreloadRoot>)>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Plugin for the preload state the game starts in
+pub struct PreloadPlugin;
+
+impl Plugin for PreloadPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<PreloadProgress>()
+            .add_systems(PreStartup, (generate_sphere_textures, load_sounds))
+            .add_systems(OnEnter(GameState::Preload), spawn_preload_screen)
+            .add_systems(OnExit(GameState::Preload), finish_preload)
+            .add_systems(Update, update_preload.run_if(in_state(GameState::Preload)));
+    }
+}
@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use std::path::{Path, PathBuf};
+use crate::input::InputMap;
+use crate::save::{SaveData, SaveFlush};
+use crate::settings::{apply_keybinds, Settings};
+use crate::stats::LifetimeStats;
+
+// Profile locations
+const PROFILES_DIR: &str = "save/profiles";
+const LAST_PROFILE_PATH: &str = "save/last_profile.txt"; // Name of the profile to open at launch
+pub const DEFAULT_PROFILE: &str = "Player";
+pub const MAX_PROFILE_NAME: usize = 16;
+
+// The player whose settings, progress, stats and saves are in use
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct ActiveProfile {
+    pub name: String,
+}
+
+impl Default for ActiveProfile {
+    fn default() -> Self {
+        Self { name: DEFAULT_PROFILE.to_string() }
+    }
+}
+
+impl ActiveProfile {
+    // The profile used last time, or the default one on first launch
+    pub fn last_used() -> Self {
+        match std::fs::read_to_string(LAST_PROFILE_PATH) {
+            Ok(name) if valid_profile_name(name.trim()) => Self { name: name.trim().to_string() },
+            _ => Self::default(),
+        }
+    }
+
+    // Open this profile on the next launch
+    pub fn remember(&self) {
+        let result = std::fs::create_dir_all(PROFILES_DIR).and_then(|_| std::fs::write(LAST_PROFILE_PATH, &self.name));
+        if let Err(error) = result {
+            println!("Failed to remember profile {}: {}", self.name, error);
+        }
+    }
+
+    // Folder holding this profile's save data and save slots
+    pub fn dir(&self) -> PathBuf {
+        Path::new(PROFILES_DIR).join(&self.name)
+    }
+
+    // A file inside this profile's folder
+    pub fn file(&self, name: &str) -> PathBuf {
+        self.dir().join(name)
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.name == DEFAULT_PROFILE
+    }
+}
+
+// Profile names are used as folder names, so keep them to letters, digits, spaces and dashes
+pub fn valid_profile_name(name: &str) -> bool {
+    !name.trim().is_empty()
+        && name.len() <= MAX_PROFILE_NAME
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '-')
+}
+
+// Every profile on disk in name order, always including the default one
+pub fn profile_names() -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(PROFILES_DIR)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter(|name| valid_profile_name(name))
+                .collect()
+        })
+        .unwrap_or_default();
+    if !names.iter().any(|name| name == DEFAULT_PROFILE) {
+        names.push(DEFAULT_PROFILE.to_string());
+    }
+    names.sort();
+    names
+}
+
+// Load everything that belongs to a newly selected profile
+pub fn switch_profile(
+    profile: Res<ActiveProfile>,
+    mut save: ResMut<SaveData>,
+    mut flush: ResMut<SaveFlush>,
+    mut stats: ResMut<LifetimeStats>,
+    mut settings: ResMut<Settings>,
+    mut input_map: ResMut<InputMap>,
+) {
+    // Changes still waiting to be written belong to the profile being left
+    flush.write_pending(&save);
+    *save = SaveData::load(&profile);
+    flush.switch_profile(&profile);
+    *stats = LifetimeStats::load(&profile);
+    *settings = Settings::load(&profile);
+    *input_map = InputMap::default();
+    apply_keybinds(&settings, &mut input_map);
+    profile.remember();
+}
+
+// Plugin for player profiles; the ActiveProfile itself is chosen in main before settings load
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<ActiveProfile>()
+            .add_systems(Update, switch_profile
+                .run_if(resource_changed::<ActiveProfile>.and(not(resource_added::<ActiveProfile>))));
+    }
+}
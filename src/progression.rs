@@ -0,0 +1,267 @@
+use bevy::prelude::*;
+use crate::player::{Player, PlayerPhysics};
+use crate::projectile::PROJECTILE_BLAST_RADIUS;
+use crate::score::Score;
+use crate::photo_mode::PhotoModeState;
+use crate::GameState;
+
+// Progression constants
+const FIRST_LEVEL_XP: u32 = 1000; // Experience needed to reach level 2
+const LEVEL_XP_STEP: u32 = 500; // Extra experience each level after that needs
+const BLAST_RADIUS_STEP: f32 = 0.2; // Fraction of the base blast radius added per upgrade
+const RELOAD_STEP: f32 = 0.25; // Fraction of the base reload speed added per upgrade
+const TRACTION_STEP: f32 = 0.15; // Fraction of grip added per upgrade
+
+// Upgrades offered on level up
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpgradeKind {
+    BlastRadius,
+    Reload,
+    Traction,
+}
+
+impl UpgradeKind {
+    pub const ALL: [UpgradeKind; 3] = [UpgradeKind::BlastRadius, UpgradeKind::Reload, UpgradeKind::Traction];
+
+    // Stable id stored in the save file
+    pub fn id(self) -> &'static str {
+        match self {
+            UpgradeKind::BlastRadius => "blast_radius",
+            UpgradeKind::Reload => "reload",
+            UpgradeKind::Traction => "traction",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            UpgradeKind::BlastRadius => "Larger blast radius",
+            UpgradeKind::Reload => "Faster reload",
+            UpgradeKind::Traction => "Better traction",
+        }
+    }
+}
+
+// Experience earned this run; every point of score is a point of experience
+#[derive(Resource)]
+pub struct Experience {
+    pub xp: u32,
+    pub level: u32,
+    // Level ups whose upgrade hasn't been picked yet
+    pub pending_choices: u32,
+    // Score last frame, so only new points count
+    last_score: u32,
+}
+
+impl Default for Experience {
+    fn default() -> Self {
+        Self {
+            xp: 0,
+            level: 1,
+            pending_choices: 0,
+            last_score: 0,
+        }
+    }
+}
+
+impl Experience {
+    // Total experience needed to reach the level after this one
+    pub fn next_level_xp(&self) -> u32 {
+        (1..=self.level).map(|level| FIRST_LEVEL_XP + (level - 1) * LEVEL_XP_STEP).sum()
+    }
+
+    // Count score from here on, e.g. after a saved run restores it
+    pub fn sync_score(&mut self, points: u32) {
+        self.last_score = points;
+    }
+}
+
+// Upgrades picked this run; systems read their values from here instead of fixed constants
+#[derive(Resource, Default)]
+pub struct Upgrades {
+    pub blast_radius: u32,
+    pub reload: u32,
+    pub traction: u32,
+}
+
+impl Upgrades {
+    // Blast radius of the player's boulders
+    pub fn blast_radius(&self) -> f32 {
+        PROJECTILE_BLAST_RADIUS * (1.0 + self.blast_radius as f32 * BLAST_RADIUS_STEP)
+    }
+
+    // Multiplier on how fast boulders are restored
+    pub fn reload_speed(&self) -> f32 {
+        1.0 + self.reload as f32 * RELOAD_STEP
+    }
+
+    // Multiplier on the player ball's grip
+    pub fn traction(&self) -> f32 {
+        1.0 + self.traction as f32 * TRACTION_STEP
+    }
+
+    pub fn apply(&mut self, kind: UpgradeKind) {
+        match kind {
+            UpgradeKind::BlastRadius => self.blast_radius += 1,
+            UpgradeKind::Reload => self.reload += 1,
+            UpgradeKind::Traction => self.traction += 1,
+        }
+    }
+}
+
+// Marker for the level up panel
+#[derive(Component)]
+pub struct LevelUpRoot;
+
+// Marker for the level up heading
+#[derive(Component)]
+pub struct LevelUpText;
+
+// Button picking one upgrade
+#[derive(Component)]
+pub struct UpgradeButton(pub UpgradeKind);
+
+// Turn new score into experience and count up level ups
+pub fn gain_experience(score: Res<Score>, mut experience: ResMut<Experience>) {
+    if score.points == experience.last_score {
+        return;
+    }
+    // Score only drops when a run is reset
+    let gained = score.points.saturating_sub(experience.last_score);
+    experience.last_score = score.points;
+    experience.xp += gained;
+    while experience.xp >= experience.next_level_xp() {
+        experience.level += 1;
+        experience.pending_choices += 1;
+        println!("Level up! Now level {}", experience.level);
+    }
+}
+
+// Build the level up panel with one button per upgrade, hidden until a level up
+pub fn spawn_level_up_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            LevelUpRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(100.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                LevelUpText,
+                Text::new(""),
+                TextFont {
+                    font_size: 22.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.85, 0.2)),
+            ));
+            for (i, kind) in UpgradeKind::ALL.into_iter().enumerate() {
+                parent
+                    .spawn((
+                        UpgradeButton(kind),
+                        Button,
+                        Node {
+                            width: Val::Px(260.0),
+                            padding: UiRect::all(Val::Px(6.0)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgba(0.1, 0.1, 0.15, 0.85)),
+                    ))
+                    .with_children(|button| {
+                        button.spawn((
+                            Text::new(format!("{}: {}", i + 1, kind.label())),
+                            TextFont {
+                                font_size: 16.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                        ));
+                    });
+            }
+        });
+}
+
+// Offer the upgrades while a choice is pending; pick with a click or the number keys
+pub fn choose_upgrade(
+    mut experience: ResMut<Experience>,
+    mut upgrades: ResMut<Upgrades>,
+    keys: Res<ButtonInput<KeyCode>>,
+    button_query: Query<(&Interaction, &UpgradeButton), Changed<Interaction>>,
+    mut root_query: Query<&mut Visibility, With<LevelUpRoot>>,
+    mut text_query: Query<&mut Text, With<LevelUpText>>,
+) {
+    let wanted = if experience.pending_choices > 0 { Visibility::Inherited } else { Visibility::Hidden };
+    for mut visibility in root_query.iter_mut() {
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+    if experience.pending_choices == 0 {
+        return;
+    }
+    for mut text in text_query.iter_mut() {
+        text.0 = format!("Level {}! Choose an upgrade", experience.level - experience.pending_choices + 1);
+    }
+
+    let keyed = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3]
+        .iter()
+        .position(|key| keys.just_pressed(*key))
+        .map(|index| UpgradeKind::ALL[index]);
+    let clicked = button_query
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(_, button)| button.0);
+    if let Some(kind) = keyed.or(clicked) {
+        upgrades.apply(kind);
+        experience.pending_choices -= 1;
+    }
+}
+
+// Keep the player's grip in line with the traction upgrades
+pub fn apply_traction(upgrades: Res<Upgrades>, mut player_query: Query<&mut PlayerPhysics, With<Player>>) {
+    let traction = upgrades.traction();
+    for mut physics in player_query.iter_mut() {
+        if physics.traction != traction {
+            physics.traction = traction;
+        }
+    }
+}
+
+// Hide the level up panel outside of gameplay; pending choices wait for play to resume
+pub fn hide_level_up_panel(mut query: Query<&mut Visibility, With<LevelUpRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Start the next run from level 1 with no upgrades
+pub fn reset_progression(mut experience: ResMut<Experience>, mut upgrades: ResMut<Upgrades>) {
+    *experience = Experience::default();
+    *upgrades = Upgrades::default();
+}
+
+// Plugin for experience, levels and upgrades
+pub struct ProgressionPlugin;
+
+impl Plugin for ProgressionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Experience>()
+            .init_resource::<Upgrades>()
+            .add_systems(Startup, spawn_level_up_panel)
+            .add_systems(OnExit(GameState::Playing), hide_level_up_panel)
+            .add_systems(OnExit(GameState::GameOver), reset_progression)
+            .add_systems(Update, (gain_experience, choose_upgrade, apply_traction)
+                .chain()
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)));
+    }
+}
@@ -1,10 +1,28 @@
 use bevy::prelude::*;
-use crate::player::Player;
-use crate::camera::MouseLook;
+use bevy::utils::HashMap;
+use rand::rngs::StdRng;
+use rand::Rng;
+use crate::player::{Health, Player};
+use crate::camera::{CameraSettings, CameraShake, MouseLook};
 use crate::terrain::get_terrain_height;
+use crate::photo_mode::PhotoModeState;
+use crate::minimap::MinimapBlip;
+use crate::intro::intro_finished;
+use crate::GameState;
+use crate::replay::BUTTON_THROW;
+use crate::sim::{Interpolated, SimInput, SimSet, Simulation};
+use crate::spatial::SpatialGrid;
+use crate::debug::DebugStats;
+use crate::target::Target;
+use crate::results::RunStats;
+use crate::progression::Upgrades;
+use crate::difficulty::Difficulty;
+use crate::tuning::{LiveTuning, ProjectileTuning, DEFAULT_TUNING};
+use crate::save::SaveData;
 
 // Component for projectiles
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Projectile {
     // Initial position
     pub start_position: Vec3,
@@ -20,186 +38,577 @@ pub struct Projectile {
     pub speed: f32,
     // Flag indicating if projectile is stuck to terrain
     pub stuck: bool,
+    // Radius of the impact area when it lands
+    pub blast_radius: f32,
+}
+
+// Marks a boulder fired at the player; it hurts the player instead of hitting targets
+#[derive(Component)]
+pub struct Hostile;
+
+// Sent when a boulder lands on a target
+#[derive(Event)]
+pub struct ProjectileHitEvent {
+    // The target that was hit
+    pub target: Entity,
+    // Where the boulder landed
+    pub position: Vec3,
+    // Horizontal distance from the launch point to the impact
+    pub distance: f32,
+}
+
+// Sent whenever a boulder hits the ground
+#[derive(Event)]
+pub struct ProjectileImpactEvent {
+    pub position: Vec3,
+    // Whether the boulder was fired at the player
+    pub hostile: bool,
+    // Radius of the impact area
+    pub radius: f32,
+}
+
+// Boulders available to throw; one stone is restored every reload interval
+#[derive(Resource)]
+pub struct Ammo {
+    pub current: u32,
+    pub max: u32,
+    // Seconds until the next boulder is restored
+    pub reload_timer: f32,
+    // Bought boulders by kind, taken from the save's inventory when the run starts and written back to it
+    // as they are thrown, so the simulation never touches the save itself
+    pub bought: HashMap<AmmoKind, u32>,
+}
+
+impl Ammo {
+    // Progress toward the next restored boulder in 0..1, or 0 while full
+    pub fn reload_progress(&self, reload_time: f32) -> f32 {
+        if self.current >= self.max {
+            0.0
+        } else {
+            (1.0 - self.reload_timer / reload_time).clamp(0.0, 1.0)
+        }
+    }
+
+    // Boulders of a kind left to throw
+    pub fn count(&self, kind: AmmoKind) -> u32 {
+        match kind {
+            AmmoKind::Stone => self.current,
+            _ => self.bought.get(&kind).copied().unwrap_or(0),
+        }
+    }
+
+    // Use up one boulder of a kind
+    pub fn take(&mut self, kind: AmmoKind) {
+        match kind {
+            AmmoKind::Stone => self.current = self.current.saturating_sub(1),
+            _ => {
+                if let Some(count) = self.bought.get_mut(&kind) {
+                    *count = count.saturating_sub(1);
+                }
+            }
+        }
+    }
+}
+
+// Kinds of boulder the player can throw: stone is the everyday kind that reloads, the others are bought
+// at shops and kept in the save's inventory until thrown
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AmmoKind {
+    #[default]
+    Stone,
+    // A dense boulder with twice the blast
+    Heavy,
+    // Three boulders at once, spread across the aim point
+    Volley,
+}
+
+impl AmmoKind {
+    pub const ALL: [AmmoKind; 3] = [AmmoKind::Stone, AmmoKind::Heavy, AmmoKind::Volley];
+
+    // Stable id stored in the save file
+    pub fn id(self) -> &'static str {
+        match self {
+            AmmoKind::Stone => "stone",
+            AmmoKind::Heavy => "heavy",
+            AmmoKind::Volley => "volley",
+        }
+    }
+
+    // Stable code stored in replay files
+    pub fn code(self) -> u8 {
+        match self {
+            AmmoKind::Stone => 0,
+            AmmoKind::Heavy => 1,
+            AmmoKind::Volley => 2,
+        }
+    }
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.code() == code)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AmmoKind::Stone => "Stone",
+            AmmoKind::Heavy => "Heavy Boulder",
+            AmmoKind::Volley => "Volley",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            AmmoKind::Stone => PLAYER_BOULDER_COLOR,
+            AmmoKind::Heavy => Color::srgb(0.2, 0.2, 0.25),
+            AmmoKind::Volley => Color::srgb(0.55, 0.45, 0.3),
+        }
+    }
+}
+
+// The kind of boulder picked for the next throw; bought kinds fall back to stone once they run out. The
+// simulation only sees it through the SimInput latched each frame
+#[derive(Resource, Default)]
+pub struct SelectedAmmo(pub AmmoKind);
+
+// Charge built up while the throw button is held; the boulder is thrown on release
+#[derive(Resource, Default)]
+pub struct ThrowCharge {
+    // Whether a throw is being charged
+    pub charging: bool,
+    // Charge level in 0..1
+    pub level: f32,
 }
 
-// Constants for projectile behavior
-const GRAVITY: f32 = 19.6; // Double the normal gravity for heavier feel
-const PROJECTILE_LIFETIME: f32 = 8.0; // Initial flight time before hitting something
+impl Default for Ammo {
+    fn default() -> Self {
+        Self {
+            current: MAX_AMMO,
+            max: MAX_AMMO,
+            // reload_ammo resets this to the live tuning's reload time while full
+            reload_timer: DEFAULT_TUNING.projectile.reload_time,
+            bought: HashMap::new(),
+        }
+    }
+}
+
+// Boulder mesh shared by every throw, and one stone material per boulder color, so a long session
+// doesn't pile up identical assets
+#[derive(Resource)]
+pub struct ProjectileAssets {
+    pub mesh: Handle<Mesh>,
+    // Materials keyed by their color's sRGB bits
+    materials: HashMap<[u32; 4], Handle<StandardMaterial>>,
+}
+
+impl ProjectileAssets {
+    // Stone material in a color, made the first time a boulder of that color is thrown
+    pub fn material(&mut self, materials: &mut Assets<StandardMaterial>, color: Color) -> Handle<StandardMaterial> {
+        let srgba = color.to_srgba();
+        let key = [srgba.red, srgba.green, srgba.blue, srgba.alpha].map(f32::to_bits);
+        self.materials
+            .entry(key)
+            .or_insert_with(|| materials.add(StandardMaterial {
+                base_color: color,
+                emissive: Color::srgb(0.0, 0.0, 0.0).into(),
+                perceptual_roughness: 0.9,
+                metallic: 0.0,
+                reflectance: 0.05,
+                ..default()
+            }))
+            .clone()
+    }
+}
+
+// Constants for projectile behavior; flight and throwing values are in GameTuning
+pub const PROJECTILE_RADIUS: f32 = 0.15; // Radius of the boulder mesh
 const PROJECTILE_HEIGHT_FACTOR: f32 = 5.0; // Much higher arc for catapult-like trajectory
-const PROJECTILE_SPEED: f32 = 1.0; // Much slower speed for plodding catapult feel
-const MAX_HORIZONTAL_DIST: f32 = 12.0; // Maximum distance to consider for velocity calculation
-const MAX_HORIZONTAL_VELOCITY: f32 = 20.0; // Maximum horizontal velocity component
 const MAX_VERTICAL_VELOCITY: f32 = 7.0; // Maximum vertical velocity component
+const MAX_AMMO: u32 = 10; // Boulders carried when full
+pub const PROJECTILE_BLAST_RADIUS: f32 = 1.5; // Radius of the boulder's impact area
+pub const PLAYER_BOULDER_COLOR: Color = Color::srgb(0.4, 0.4, 0.4); // Stone color of the player's throws
+const PREVIEW_TIME_STEP: f32 = 0.05; // Seconds between points on the trajectory preview
+const PREVIEW_MAX_STEPS: usize = 400; // Upper bound on preview points for shots that never land
+const PLAYER_RADIUS: f32 = 0.5; // Radius of the player ball, for hostile impacts
+const HOSTILE_IMPACT_DAMAGE: f32 = 20.0; // Health taken by a hostile boulder landing right on the player
+const THROW_STREAM: u64 = 3; // Simulation random stream for the player's throws
+const HEAVY_BLAST_FACTOR: f32 = 2.0; // Blast radius of a heavy boulder against a stone one
+const VOLLEY_SPREAD: f32 = 3.0; // Distance of a volley's outer boulders from the aim point, across the throw
+
+// Launch parameters for a throw from the player toward a target
+pub struct LaunchSolution {
+    // Where the projectile leaves the player
+    pub start_position: Vec3,
+    // Velocity at launch, before any random variation
+    pub initial_velocity: Vec3,
+    // Expected flight time used to pace the throw
+    pub travel_time: f32,
+    // Horizontal distance from the launch point to the target
+    pub horizontal_dist: f32,
+}
 
-// System to spawn projectiles when mouse is clicked
+// Where a boulder leaves from and lands, how fast it leaves and how it looks and hits, for spawn_boulder
+#[derive(Clone, Copy, Debug)]
+pub struct BoulderLaunch {
+    pub start: Vec3,
+    pub target: Vec3,
+    // Velocity at launch, after any random variation
+    pub velocity: Vec3,
+    pub color: Color,
+    pub blast_radius: f32,
+}
+
+// Horizontal distance a throw can reach at a charge level
+pub fn throw_range(tuning: &ProjectileTuning, charge: f32) -> f32 {
+    tuning.max_horizontal_dist + tuning.charge_range_bonus * charge.clamp(0.0, 1.0)
+}
+
+// Calculate the catapult-style launch toward a target position, reaching at most max_dist
+pub fn compute_launch(tuning: &ProjectileTuning, player_pos: Vec3, target_pos: Vec3, max_dist: f32) -> LaunchSolution {
+    // Calculate height difference
+    let height_diff = target_pos.y - player_pos.y;
+    
+    // Calculate projectile trajectory (high arching ballistic path)
+    // Starting position is slightly above the player
+    let start_pos = player_pos + Vec3::new(0.0, 0.3, 0.0);
+    
+    // We need to properly target the mouse position on the ground
+    // This requires calculating the correct launch angle and initial velocity
+    
+    // Vector directly to target (this is what we're aiming for)
+    let target_vector = target_pos - start_pos;
+    
+    // Calculate the horizontal distance and direction (azimuth)
+    let horizontal_vector = Vec3::new(target_vector.x, 0.0, target_vector.z);
+    let horizontal_dist = horizontal_vector.length();
+    
+    // Apply distance clamping to prevent extreme velocities for far targets
+    let effective_dist = horizontal_dist.min(max_dist);
+    
+    // Calculate travel time based on distance for more consistent speed feel
+    // For far targets, increase the time to make them slower
+    let min_travel_time = if horizontal_dist > max_dist {
+        3.0 + (horizontal_dist - max_dist) * 0.3 // Add time for distances beyond the maximum
+    } else {
+        3.0
+    };
+    let travel_time = (effective_dist / tuning.speed).max(min_travel_time);
+    
+    // Calculate azimuth (the direction in the XZ plane)
+    let azimuth = f32::atan2(target_vector.z, target_vector.x);
+    
+    // Use a fixed elevation angle for consistent catapult-like trajectory
+    // 60 degrees gives a good high arc
+    let elevation_angle = std::f32::consts::PI / 3.0; // 60 degrees
+    
+    // Calculate initial speed needed to reach the target
+    // Using the ballistic equation: v² = (g * R) / sin(2θ)
+    // Where R is the horizontal distance, g is gravity, and θ is the elevation angle
+    let two_theta = 2.0 * elevation_angle;
+    let sin_two_theta = f32::sin(two_theta).max(0.01); // Prevent division by zero
+    
+    // Calculate the speed needed accounting for height difference
+    // The height difference affects how much energy is needed
+    let height_factor = if height_diff < 0.0 {
+        // Going uphill requires more speed
+        1.2 - (height_diff / effective_dist).max(-0.5).min(0.0)
+    } else {
+        // Going downhill requires less speed
+        0.9 - (height_diff / effective_dist).min(0.5).max(0.0)
+    };
+    
+    // Calculate the base speed required to hit the target
+    let base_speed = f32::sqrt((tuning.gravity * effective_dist) / sin_two_theta);
+    
+    // Apply height adjustment and clamping to get final speed
+    let adjusted_speed = base_speed * height_factor;
+    let final_speed = adjusted_speed.max(2.0).min(tuning.max_horizontal_velocity * 2.0);
+    
+    // Convert from spherical coordinates (speed, azimuth, elevation) to Cartesian velocity
+    let initial_velocity = Vec3::new(
+        final_speed * f32::cos(elevation_angle) * f32::cos(azimuth),
+        final_speed * f32::sin(elevation_angle),
+        final_speed * f32::cos(elevation_angle) * f32::sin(azimuth)
+    );
+    
+    // Scale down velocity for very distant targets to prevent excessive speeds
+    let scaling_factor = if horizontal_dist > max_dist {
+        0.8 * max_dist / horizontal_dist
+    } else {
+        1.0
+    };
+    
+    LaunchSolution {
+        start_position: start_pos,
+        initial_velocity: initial_velocity * scaling_factor,
+        travel_time,
+        horizontal_dist,
+    }
+}
+
+// Whether a target is close enough for a throw at this charge to land on it rather than fall short
+pub fn in_throw_range(tuning: &ProjectileTuning, player_pos: Vec3, target_pos: Vec3, charge: f32) -> bool {
+    let offset = target_pos - player_pos;
+    Vec2::new(offset.x, offset.z).length() <= throw_range(tuning, charge)
+}
+
+// Exact launch velocity that carries a boulder from start to target in flight_time seconds
+pub fn solve_ballistic(tuning: &ProjectileTuning, start_pos: Vec3, target_pos: Vec3, flight_time: f32) -> Vec3 {
+    let flight_time = flight_time.max(0.1);
+    (target_pos - start_pos) / flight_time + Vec3::Y * (0.5 * tuning.gravity * flight_time)
+}
+
+// Position along a ballistic path after t seconds
+pub fn ballistic_position(tuning: &ProjectileTuning, start_pos: Vec3, initial_vel: Vec3, t: f32) -> Vec3 {
+    Vec3::new(
+        start_pos.x + initial_vel.x * t,
+        start_pos.y + initial_vel.y * t - 0.5 * tuning.gravity * t * t,
+        start_pos.z + initial_vel.z * t
+    )
+}
+
+// Velocity of a boulder t seconds after launch
+pub fn ballistic_velocity(tuning: &ProjectileTuning, initial_vel: Vec3, t: f32) -> Vec3 {
+    Vec3::new(initial_vel.x, initial_vel.y - tuning.gravity * t, initial_vel.z)
+}
+
+// System to charge a throw while the button is held and spawn the projectile on release
 pub fn spawn_projectile(
     mut commands: Commands,
-    mouse_input: Res<ButtonInput<MouseButton>>,
+    input: Res<SimInput>,
+    simulation: Res<Simulation>,
+    mut charge: ResMut<ThrowCharge>,
+    time: Res<Time>,
     player_query: Query<&Transform, With<Player>>,
-    mouse_look: Res<MouseLook>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    mut ammo: ResMut<Ammo>,
+    mut run_stats: ResMut<RunStats>,
+    mut projectile_assets: ResMut<ProjectileAssets>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    upgrades: Res<Upgrades>,
+    difficulty: Res<Difficulty>,
+    target_query: Query<&Transform, With<Target>>,
+    tuning: Res<LiveTuning>,
 ) {
-    // Only spawn when left mouse button is just pressed and we have a valid target
-    if mouse_input.just_pressed(MouseButton::Left) && mouse_look.is_initialized {
+    let tuning = &tuning.0.projectile;
+    // The kind comes with the input, so replays and lockstep peers throw the same boulders
+    let kind = input.ammo;
+    let loaded = ammo.count(kind) > 0;
+
+    // Start charging when throw is pressed with a valid target and a boulder to throw
+    if input.just_pressed(BUTTON_THROW) && input.aim.is_some() && loaded {
+        charge.charging = true;
+        charge.level = 0.0;
+    }
+    if !charge.charging {
+        return;
+    }
+    if input.held(BUTTON_THROW) {
+        charge.level = (charge.level + time.delta_secs() / tuning.charge_time).min(1.0);
+        return;
+    }
+    
+    // Released: throw with the built-up charge
+    let charge_level = charge.level;
+    charge.charging = false;
+    charge.level = 0.0;
+    if loaded {
         // Get player position (if available)
         if let Ok(player_transform) = player_query.get_single() {
+            ammo.take(kind);
+            run_stats.boulders_fired += 1;
             let player_pos = player_transform.translation;
-            let target_pos = mouse_look.target_position;
-            
-            // Calculate horizontal distance to target
-            let _horizontal_dist = Vec3::new(
-                target_pos.x - player_pos.x, 
-                0.0, 
-                target_pos.z - player_pos.z
-            ).length();
-            
-            // Calculate height difference
-            let height_diff = target_pos.y - player_pos.y;
-            
-            // Calculate projectile trajectory (high arching ballistic path)
-            // Starting position is slightly above the player
-            let start_pos = player_pos + Vec3::new(0.0, 0.3, 0.0);
+            let aim_pos = input.aim.unwrap_or(player_pos);
+
+            // Aim assist: snap onto the nearest target close enough to where the cursor is
+            let assist = difficulty.aim_assist();
+            let target_pos = target_query
+                .iter()
+                .map(|transform| transform.translation)
+                .filter(|position| position.distance(aim_pos) <= assist)
+                .min_by(|a, b| a.distance(aim_pos).total_cmp(&b.distance(aim_pos)))
+                .unwrap_or(aim_pos);
             
             // Calculate velocity for ballistic trajectory
-            // We'll use physics formulas for projectile motion to create a nice arc
-            
-            // Direction to target (horizontal only)
-            let _direction = Vec3::new(
-                target_pos.x - player_pos.x,
-                0.0,
-                target_pos.z - player_pos.z
-            ).normalize();
-            
-            // We need to properly target the mouse position on the ground
-            // This requires calculating the correct launch angle and initial velocity
-            
-            // Vector directly to target (this is what we're aiming for)
-            let target_vector = target_pos - start_pos;
-            
-            // Calculate the horizontal distance and direction (azimuth)
-            let horizontal_vector = Vec3::new(target_vector.x, 0.0, target_vector.z);
-            let horizontal_dist = horizontal_vector.length();
-            
-            // Apply distance clamping to prevent extreme velocities for far targets
-            let effective_dist = horizontal_dist.min(MAX_HORIZONTAL_DIST);
-            
-            // Calculate travel time based on distance for more consistent speed feel
-            // For far targets, increase the time to make them slower
-            let min_travel_time = if horizontal_dist > MAX_HORIZONTAL_DIST {
-                3.0 + (horizontal_dist - MAX_HORIZONTAL_DIST) * 0.3 // Add time for distances beyond the maximum
-            } else {
-                3.0
-            };
-            let travel_time = (effective_dist / PROJECTILE_SPEED).max(min_travel_time);
-            
-            // Calculate azimuth (the direction in the XZ plane)
-            let azimuth = f32::atan2(target_vector.z, target_vector.x);
-            
-            // Use a fixed elevation angle for consistent catapult-like trajectory
-            // 60 degrees gives a good high arc
-            let elevation_angle = std::f32::consts::PI / 3.0; // 60 degrees
-            
-            // Apply distance clamping to prevent extreme velocities
-            let effective_dist = horizontal_dist.min(MAX_HORIZONTAL_DIST);
-            
-            // Calculate initial speed needed to reach the target
-            // Using the ballistic equation: v² = (g * R) / sin(2θ)
-            // Where R is the horizontal distance, g is gravity, and θ is the elevation angle
-            let two_theta = 2.0 * elevation_angle;
-            let sin_two_theta = f32::sin(two_theta).max(0.01); // Prevent division by zero
-            
-            // Calculate the speed needed accounting for height difference
-            // The height difference affects how much energy is needed
-            let height_factor = if height_diff < 0.0 {
-                // Going uphill requires more speed
-                1.2 - (height_diff / effective_dist).max(-0.5).min(0.0)
-            } else {
-                // Going downhill requires less speed
-                0.9 - (height_diff / effective_dist).min(0.5).max(0.0)
-            };
-            
-            // Calculate the base speed required to hit the target
-            let base_speed = f32::sqrt((GRAVITY * effective_dist) / sin_two_theta);
-            
-            // Apply height adjustment and clamping to get final speed
-            let adjusted_speed = base_speed * height_factor;
-            let final_speed = adjusted_speed.max(2.0).min(MAX_HORIZONTAL_VELOCITY * 2.0);
-            
-            // Convert from spherical coordinates (speed, azimuth, elevation) to Cartesian velocity
-            let initial_velocity = Vec3::new(
-                final_speed * f32::cos(elevation_angle) * f32::cos(azimuth),
-                final_speed * f32::sin(elevation_angle),
-                final_speed * f32::cos(elevation_angle) * f32::sin(azimuth)
-            );
-            
-            // Scale down velocity for very distant targets to prevent excessive speeds
-            let scaling_factor = if horizontal_dist > MAX_HORIZONTAL_DIST {
-                0.8 * MAX_HORIZONTAL_DIST / horizontal_dist
-            } else {
-                1.0
-            };
-            
-            let initial_velocity = initial_velocity * scaling_factor;
+            let launch = compute_launch(tuning, player_pos, target_pos, throw_range(tuning, charge_level));
+            let start_pos = launch.start_position;
+            let mut rng = simulation.rng(THROW_STREAM);
+            let initial_velocity = vary_launch(launch.initial_velocity, &mut rng);
             
             // Debug info
             println!("Distance: {:.2}, Vel: ({:.2}, {:.2}, {:.2}), Time: {:.2}", 
-                horizontal_dist,
+                launch.horizontal_dist,
                 initial_velocity.x,
                 initial_velocity.y,
                 initial_velocity.z,
-                travel_time
+                launch.travel_time
             );
             
-            // Create larger, boulder-like projectile for catapult feel
-            let arrow_mesh = Mesh::from(Sphere::new(0.15));
-            
-            // Create stone-like material for catapult boulder appearance
-            let arrow_material = StandardMaterial {
-                base_color: Color::srgb(0.4, 0.4, 0.4),
-                emissive: Color::srgb(0.0, 0.0, 0.0).into(),
-                perceptual_roughness: 0.9,
-                metallic: 0.0,
-                reflectance: 0.05,
-                ..default()
+            let blast_radius = match kind {
+                AmmoKind::Heavy => upgrades.blast_radius() * HEAVY_BLAST_FACTOR,
+                _ => upgrades.blast_radius(),
             };
-            
-            // Apply a random slight variation to initial velocity for natural feel
-            let variation = 0.05;
-            let random_variation = Vec3::new(
-                (rand::random::<f32>() - 0.5) * variation,
-                (rand::random::<f32>()) * variation, // Slight positive bias on Y
-                (rand::random::<f32>() - 0.5) * variation
-            );
-            let initial_velocity = initial_velocity + random_variation;
-            
-            // Spawn projectile entity
-            commands.spawn((
-                Projectile {
-                    start_position: start_pos,
-                    target_position: target_pos,
-                    initial_velocity,
-                    lifetime: PROJECTILE_LIFETIME,
-                    age: 0.0,
-                    speed: PROJECTILE_SPEED,
-                    stuck: false, // Initialize as not stuck
+            spawn_boulder(
+                &mut commands,
+                &mut projectile_assets,
+                &mut materials,
+                tuning,
+                BoulderLaunch {
+                    start: start_pos,
+                    target: target_pos,
+                    velocity: initial_velocity,
+                    color: kind.color(),
+                    blast_radius,
                 },
-                Mesh3d(meshes.add(arrow_mesh)),
-                MeshMaterial3d(materials.add(arrow_material)),
-                Transform::from_translation(start_pos),
-                Name::new("Catapult Boulder"),
-            ));
+            );
+
+            // A volley adds a boulder to each side of the aim point, across the line of the throw
+            if kind == AmmoKind::Volley {
+                let across = (target_pos - player_pos).with_y(0.0).normalize_or_zero().cross(Vec3::Y);
+                for side in [-1.0, 1.0] {
+                    let mut flank = target_pos + across * VOLLEY_SPREAD * side;
+                    flank.y = get_terrain_height(flank.x, flank.z);
+                    let launch = compute_launch(tuning, player_pos, flank, throw_range(tuning, charge_level));
+                    let velocity = vary_launch(launch.initial_velocity, &mut rng);
+                    spawn_boulder(
+                        &mut commands,
+                        &mut projectile_assets,
+                        &mut materials,
+                        tuning,
+                        BoulderLaunch {
+                            start: launch.start_position,
+                            target: flank,
+                            velocity,
+                            color: kind.color(),
+                            blast_radius,
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
+
+// Nudge a launch velocity slightly for a natural feel, drawing from a simulation random stream
+pub fn vary_launch(velocity: Vec3, rng: &mut StdRng) -> Vec3 {
+    let variation = 0.05;
+    velocity + Vec3::new(
+        rng.gen_range(-0.5..0.5) * variation,
+        rng.gen_range(0.0..1.0) * variation, // Slight positive bias on Y
+        rng.gen_range(-0.5..0.5) * variation
+    )
+}
+
+// Spawn a boulder on a ballistic path; used for the player's throws and anything else that fires boulders
+pub fn spawn_boulder(
+    commands: &mut Commands,
+    assets: &mut ProjectileAssets,
+    materials: &mut Assets<StandardMaterial>,
+    tuning: &ProjectileTuning,
+    launch: BoulderLaunch,
+) -> Entity {
+    // Stone-like material for catapult boulder appearance, shared by every boulder of this color
+    let material = assets.material(materials, launch.color);
+    
+    // Spawn projectile entity
+    commands.spawn((
+        Projectile {
+            start_position: launch.start,
+            target_position: launch.target,
+            initial_velocity: launch.velocity,
+            lifetime: tuning.lifetime,
+            age: 0.0,
+            speed: tuning.speed,
+            stuck: false, // Initialize as not stuck
+            blast_radius: launch.blast_radius,
+        },
+        Mesh3d(assets.mesh.clone()),
+        MeshMaterial3d(material),
+        Transform::from_translation(launch.start),
+        Interpolated::new(launch.start),
+        Name::new("Catapult Boulder"),
+        MinimapBlip { color: Color::srgb(0.9, 0.9, 0.9) },
+    )).id()
+}
+
+// Create the shared boulder mesh; materials are added per color as boulders are thrown
+pub fn setup_projectile_assets(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    // Larger, boulder-like projectile for catapult feel
+    commands.insert_resource(ProjectileAssets {
+        mesh: meshes.add(Sphere::new(PROJECTILE_RADIUS)),
+        materials: HashMap::new(),
+    });
+}
+
+// System to restore boulders over time
+pub fn reload_ammo(mut ammo: ResMut<Ammo>, upgrades: Res<Upgrades>, tuning: Res<LiveTuning>, time: Res<Time>) {
+    let reload_time = tuning.0.projectile.reload_time;
+    if ammo.current >= ammo.max {
+        // Don't bank reload progress while full
+        if ammo.reload_timer != reload_time {
+            ammo.reload_timer = reload_time;
         }
+        return;
+    }
+    ammo.reload_timer -= time.delta_secs() * upgrades.reload_speed();
+    if ammo.reload_timer <= 0.0 {
+        ammo.current += 1;
+        ammo.reload_timer += reload_time;
+    }
+}
+
+// System to draw the predicted arc of the next throw while planning in the tactical view
+pub fn draw_trajectory_preview(
+    mut gizmos: Gizmos,
+    player_query: Query<&Transform, With<Player>>,
+    mouse_look: Res<MouseLook>,
+    camera_settings: Res<CameraSettings>,
+    charge: Res<ThrowCharge>,
+    tuning: Res<LiveTuning>,
+) {
+    let tuning = &tuning.0.projectile;
+    if camera_settings.tactical_blend <= 0.0 || !mouse_look.is_initialized {
+        return;
+    }
+    
+    if let Ok(player_transform) = player_query.get_single() {
+        let launch = compute_launch(tuning, player_transform.translation, mouse_look.target_position, throw_range(tuning, charge.level));
+        
+        // Step along the arc until it meets the terrain
+        let mut points = Vec::new();
+        for i in 0..PREVIEW_MAX_STEPS {
+            let point = ballistic_position(tuning, launch.start_position, launch.initial_velocity, i as f32 * PREVIEW_TIME_STEP);
+            points.push(point);
+            if i > 0 && point.y <= get_terrain_height(point.x, point.z) {
+                break;
+            }
+        }
+        
+        let alpha = camera_settings.tactical_blend;
+        gizmos.linestrip(points, Color::srgba(1.0, 0.8, 0.2, alpha));
     }
 }
 
 // System to update projectile positions with physics
 pub fn update_projectiles(
     mut commands: Commands,
-    mut projectile_query: Query<(Entity, &mut Transform, &mut Projectile)>,
+    mut projectile_query: Query<(Entity, &mut Transform, &mut Projectile, Has<Hostile>)>,
+    mut player_query: Query<(&Transform, &mut Health), (With<Player>, Without<Projectile>)>,
+    target_query: Query<(Entity, &Transform, &Target), Without<Projectile>>,
+    mut hit_events: EventWriter<ProjectileHitEvent>,
+    mut impact_events: EventWriter<ProjectileImpactEvent>,
+    mut camera_shake: ResMut<CameraShake>,
+    mut debug_stats: ResMut<DebugStats>,
+    spatial_grid: Res<SpatialGrid>,
+    difficulty: Res<Difficulty>,
+    tuning: Res<LiveTuning>,
     time: Res<Time>,
 ) {
-    for (entity, mut transform, mut projectile) in projectile_query.iter_mut() {
+    let tuning = &tuning.0.projectile;
+    debug_stats.projectile_count = projectile_query.iter().len();
+    let mut player = player_query.get_single_mut().ok();
+    let player_pos = player.as_ref().map(|(transform, _)| transform.translation);
+    // Targets already hit this frame, so two boulders can't score the same target
+    let mut hit_targets = Vec::new();
+    
+    for (entity, mut transform, mut projectile, hostile) in projectile_query.iter_mut() {
         // Update projectile age
         projectile.age += time.delta_secs();
         
@@ -217,22 +626,14 @@ pub fn update_projectiles(
         // Only update position if the projectile is not stuck
         if !projectile.stuck {
             // Apply ballistic motion formula: pos = start_pos + initial_vel*t + 0.5*gravity*t²
-            let current_pos = Vec3::new(
-                start_pos.x + initial_vel.x * t,
-                start_pos.y + initial_vel.y * t - 0.5 * GRAVITY * t * t,
-                start_pos.z + initial_vel.z * t
-            );
+            let current_pos = ballistic_position(tuning, start_pos, initial_vel, t);
             
             // Update transform position
             transform.translation = current_pos;
         }
         
         // Calculate current velocity (derivative of position)
-        let current_velocity = Vec3::new(
-            initial_vel.x,
-            initial_vel.y - GRAVITY * t,
-            initial_vel.z
-        );
+        let current_velocity = ballistic_velocity(tuning, initial_vel, t);
 
         // Orient projectile to face in the direction of travel, but only if not stuck
         if !projectile.stuck && t > 0.0 {
@@ -260,6 +661,25 @@ pub fn update_projectiles(
             );
         }
         
+        // Boulders in flight hit targets they pass through, such as balloons and rings
+        if !projectile.stuck && !hostile {
+            for target_entity in spatial_grid.nearby(transform.translation, PROJECTILE_RADIUS) {
+                let Ok((_, target_transform, target)) = target_query.get(target_entity) else {
+                    continue;
+                };
+                let reach = target.radius + PROJECTILE_RADIUS;
+                if target_transform.translation.distance(transform.translation) <= reach && !hit_targets.contains(&target_entity) {
+                    hit_targets.push(target_entity);
+                    let launch_offset = transform.translation - projectile.start_position;
+                    hit_events.send(ProjectileHitEvent {
+                        target: target_entity,
+                        position: transform.translation,
+                        distance: Vec2::new(launch_offset.x, launch_offset.z).length(),
+                    });
+                }
+            }
+        }
+        
         // Only check for collision if the projectile is not already stuck
         if !projectile.stuck {
             // Check for collision with terrain using the proper terrain height function
@@ -286,6 +706,46 @@ pub fn update_projectiles(
                 // Make it a "static" projectile by zeroing its speed
                 projectile.speed = 0.0;
                 
+                // Nearby impacts shake the camera, fading out with distance
+                if let Some(player_pos) = player_pos {
+                    let distance = player_pos.distance(transform.translation);
+                    if distance < tuning.impact_shake_radius {
+                        camera_shake.add_trauma(tuning.impact_shake_trauma * (1.0 - distance / tuning.impact_shake_radius));
+                    }
+                }
+                
+                // Hostile boulders hurt the player if they land close enough
+                let impact = transform.translation;
+                let blast_radius = projectile.blast_radius;
+                impact_events.send(ProjectileImpactEvent { position: impact, hostile, radius: blast_radius });
+                if hostile {
+                    if let Some((player_transform, health)) = player.as_mut() {
+                        let distance = player_transform.translation.distance(impact);
+                        let reach = blast_radius + PLAYER_RADIUS;
+                        if distance < reach {
+                            health.current -= HOSTILE_IMPACT_DAMAGE * (1.0 - distance / reach) * difficulty.damage_taken();
+                        }
+                    }
+                }
+                
+                // Report a hit on any target within the blast radius
+                for target_entity in spatial_grid.nearby(impact, blast_radius).into_iter().filter(|_| !hostile) {
+                    let Ok((_, target_transform, target)) = target_query.get(target_entity) else {
+                        continue;
+                    };
+                    let offset = target_transform.translation - impact;
+                    let horizontal = Vec2::new(offset.x, offset.z).length();
+                    if horizontal <= blast_radius + target.radius && !hit_targets.contains(&target_entity) {
+                        hit_targets.push(target_entity);
+                        let launch_offset = impact - projectile.start_position;
+                        hit_events.send(ProjectileHitEvent {
+                            target: target_entity,
+                            position: impact,
+                            distance: Vec2::new(launch_offset.x, launch_offset.z).length(),
+                        });
+                    }
+                }
+                
                 // Debug output when a projectile sticks
                 println!("Projectile stuck at position: ({:.2}, {:.2}, {:.2})", 
                     transform.translation.x, 
@@ -296,13 +756,93 @@ pub fn update_projectiles(
     }
 }
 
-// Plugin for projectile functionality
-pub struct ProjectilePlugin;
+// X switches to the next kind of boulder there is any of; stone is always there. Out of a bought
+// kind, the next throw is stone again
+pub fn cycle_ammo(keys: Res<ButtonInput<KeyCode>>, ammo: Res<Ammo>, mut selected: ResMut<SelectedAmmo>) {
+    if ammo.count(selected.0) == 0 && selected.0 != AmmoKind::Stone {
+        selected.0 = AmmoKind::Stone;
+    }
+    if !keys.just_pressed(KeyCode::KeyX) {
+        return;
+    }
+    let current = AmmoKind::ALL.iter().position(|kind| *kind == selected.0).unwrap_or(0);
+    let next = (1..AmmoKind::ALL.len())
+        .map(|step| AmmoKind::ALL[(current + step) % AmmoKind::ALL.len()])
+        .find(|kind| *kind == AmmoKind::Stone || ammo.count(*kind) > 0);
+    if let Some(kind) = next {
+        selected.0 = kind;
+    }
+}
+
+// Clear thrown boulders and refill ammo for a new run
+pub fn reset_projectiles(
+    mut commands: Commands,
+    projectile_query: Query<Entity, With<Projectile>>,
+    mut ammo: ResMut<Ammo>,
+    mut charge: ResMut<ThrowCharge>,
+) {
+    for entity in projectile_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    *ammo = Ammo::default();
+    *charge = ThrowCharge::default();
+}
+
+// Bring the bought boulders from the save into a new run
+pub fn stock_bought_ammo(save: Res<SaveData>, mut ammo: ResMut<Ammo>) {
+    ammo.bought = AmmoKind::ALL
+        .into_iter()
+        .filter(|kind| *kind != AmmoKind::Stone)
+        .map(|kind| (kind, save.ammo_count(kind)))
+        .collect();
+}
+
+// Write the bought boulders left back to the save, touching it only when a count has changed
+pub fn save_bought_ammo(ammo: Res<Ammo>, mut save: ResMut<SaveData>) {
+    for (kind, count) in ammo.bought.iter() {
+        if save.ammo_count(*kind) != *count {
+            save.set_ammo(*kind, *count);
+        }
+    }
+}
+
+// Plugin for projectile functionality. A headless app leaves out the ammo keys, the trajectory preview
+// and keeping bought boulders in the save, since it has no keyboard, screen or profile.
+#[derive(Default)]
+pub struct ProjectilePlugin {
+    pub headless: bool,
+}
 
 impl Plugin for ProjectilePlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(Update, spawn_projectile)
-            .add_systems(Update, update_projectiles.after(spawn_projectile));
+            .init_resource::<Ammo>()
+            .init_resource::<ThrowCharge>()
+            .init_resource::<SelectedAmmo>()
+            .register_type::<Projectile>()
+            .add_event::<ProjectileHitEvent>()
+            .add_event::<ProjectileImpactEvent>()
+            .add_systems(Startup, setup_projectile_assets)
+            .add_systems(OnExit(GameState::GameOver), reset_projectiles)
+            .add_systems(Update, reload_ammo.run_if(in_state(GameState::Playing)))
+            .add_systems(FixedUpdate, spawn_projectile
+                .in_set(SimSet::Movement)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive))
+                .run_if(intro_finished))
+            .add_systems(FixedUpdate, update_projectiles
+                .in_set(SimSet::Movement)
+                .after(spawn_projectile)
+                .run_if(in_state(GameState::Playing)));
+        if self.headless {
+            return;
+        }
+        app
+            .add_systems(OnExit(GameState::Loading), stock_bought_ammo)
+            .add_systems(OnExit(GameState::GameOver), stock_bought_ammo.after(reset_projectiles))
+            .add_systems(Update, (cycle_ammo, draw_trajectory_preview).run_if(in_state(GameState::Playing)))
+            .add_systems(Update, save_bought_ammo
+                .run_if(in_state(GameState::Playing))
+                .run_if(resource_changed::<Ammo>));
     }
 }
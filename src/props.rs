@@ -0,0 +1,380 @@
+use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::TAU;
+use crate::terrain::get_terrain_height;
+use crate::player::{Player, PlayerPhysics};
+use crate::projectile::ProjectileImpactEvent;
+use crate::catapult::Catapult;
+use crate::score::{spawn_score_popup, Score};
+use crate::game_events::GameEvent;
+use crate::lifetime::Lifetime;
+use crate::photo_mode::PhotoModeState;
+use crate::sim::{placement_rng, SimSet};
+use crate::spatial::{Spatial, SpatialGrid};
+use crate::assets::models::ModelRegistry;
+use crate::GameState;
+
+// Prop constants
+const PROPS_PER_STRUCTURE: usize = 4; // Crates and barrels scattered around each structure
+const PROP_MIN_DISTANCE: f32 = 2.5; // Closest a prop is placed to its structure
+const PROP_MAX_DISTANCE: f32 = 6.0; // Farthest a prop is placed from its structure
+const WALL_DISTANCE: f32 = 4.0; // Distance from a structure to its protecting wall
+const BLAST_DAMAGE: f32 = 40.0; // Damage from a boulder landing right on a prop
+const BLAST_IMPULSE: f32 = 5.0; // Speed a prop is thrown with by a boulder landing next to it
+const RAM_SPEED: f32 = 4.0; // Player speed needed to damage a prop by rolling into it
+const RAM_DAMAGE_PER_SPEED: f32 = 8.0; // Damage per unit of ramming speed
+const RAM_BOUNCE: f32 = 0.5; // Share of the player's speed into a prop that bounces back
+const PLAYER_RADIUS: f32 = 0.5;
+const PROP_GRAVITY: f32 = 9.8;
+const PROP_FRICTION: f32 = 0.9; // Horizontal speed kept per tick while resting on the ground
+const DEBRIS_COUNT: usize = 8; // Chunks a prop shatters into
+const DEBRIS_SPEED: f32 = 4.0; // Top speed of flying debris
+const DEBRIS_LIFETIME: f32 = 3.0; // Seconds before debris disappears
+const PROP_STREAM: u64 = 9; // Simulation random stream for placing and shattering props
+
+// Kinds of breakable prop
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PropKind {
+    Crate,
+    Barrel,
+    Wall,
+}
+
+impl PropKind {
+    fn max_health(self) -> f32 {
+        match self {
+            PropKind::Crate => 30.0,
+            PropKind::Barrel => 20.0,
+            PropKind::Wall => 80.0,
+        }
+    }
+
+    // Half size of the prop's collision box
+    fn half_extents(self) -> Vec3 {
+        match self {
+            PropKind::Crate => Vec3::splat(0.5),
+            PropKind::Barrel => Vec3::new(0.4, 0.5, 0.4),
+            PropKind::Wall => Vec3::new(2.0, 0.9, 0.25),
+        }
+    }
+
+    fn points(self) -> u32 {
+        match self {
+            PropKind::Crate => 50,
+            PropKind::Barrel => 40,
+            PropKind::Wall => 120,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PropKind::Crate => "Crate",
+            PropKind::Barrel => "Barrel",
+            PropKind::Wall => "Wall",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            PropKind::Crate => Color::srgb(0.6, 0.45, 0.25),
+            PropKind::Barrel => Color::srgb(0.45, 0.3, 0.2),
+            PropKind::Wall => Color::srgb(0.55, 0.55, 0.5),
+        }
+    }
+}
+
+// A breakable prop
+#[derive(Component)]
+pub struct Prop {
+    pub kind: PropKind,
+    pub health: f32,
+    pub velocity: Vec3,
+    // Whether the last damage came from the player, so only their hits score
+    pub damaged_by_player: bool,
+}
+
+// A piece of a shattered prop
+#[derive(Component)]
+pub struct Debris {
+    pub velocity: Vec3,
+    pub spin: Vec3,
+}
+
+// Shared meshes and materials for props and their debris
+#[derive(Resource)]
+pub struct PropAssets {
+    pub crate_mesh: Handle<Mesh>,
+    pub barrel_mesh: Handle<Mesh>,
+    pub wall_mesh: Handle<Mesh>,
+    pub debris_mesh: Handle<Mesh>,
+    pub crate_material: Handle<StandardMaterial>,
+    pub barrel_material: Handle<StandardMaterial>,
+    pub wall_material: Handle<StandardMaterial>,
+}
+
+impl PropAssets {
+    fn get(&self, kind: PropKind) -> (Handle<Mesh>, Handle<StandardMaterial>) {
+        match kind {
+            PropKind::Crate => (self.crate_mesh.clone(), self.crate_material.clone()),
+            PropKind::Barrel => (self.barrel_mesh.clone(), self.barrel_material.clone()),
+            PropKind::Wall => (self.wall_mesh.clone(), self.wall_material.clone()),
+        }
+    }
+}
+
+// Point on a prop's box nearest to a world position
+fn closest_point(transform: &Transform, half_extents: Vec3, point: Vec3) -> Vec3 {
+    let local = transform.rotation.inverse() * (point - transform.translation);
+    transform.translation + transform.rotation * local.clamp(-half_extents, half_extents)
+}
+
+// Place a prop standing on the terrain, shown as its model's scene if one is loaded
+fn spawn_prop(commands: &mut Commands, assets: &PropAssets, scene: Option<Handle<Scene>>, kind: PropKind, x: f32, z: f32, yaw: f32) {
+    let y = get_terrain_height(x, z) + kind.half_extents().y;
+    let mut prop = commands.spawn((
+        Prop {
+            kind,
+            health: kind.max_health(),
+            velocity: Vec3::ZERO,
+            damaged_by_player: false,
+        },
+        Spatial::new(kind.half_extents().length()),
+        Transform::from_xyz(x, y, z).with_rotation(Quat::from_rotation_y(yaw)),
+        Visibility::default(),
+        Name::new(kind.label()),
+    ));
+    match scene {
+        // Models stand on the ground rather than around the middle of the collision box
+        Some(scene) => {
+            prop.with_children(|parent| {
+                parent.spawn((SceneRoot(scene), Transform::from_xyz(0.0, -kind.half_extents().y, 0.0)));
+            });
+        }
+        None => {
+            let (mesh, material) = assets.get(kind);
+            prop.insert((Mesh3d(mesh), MeshMaterial3d(material)));
+        }
+    }
+}
+
+// Create the shared prop meshes and materials
+pub fn setup_prop_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut material = |kind: PropKind| materials.add(StandardMaterial {
+        base_color: kind.color(),
+        perceptual_roughness: 0.9,
+        ..default()
+    });
+    let half = |kind: PropKind| kind.half_extents() * 2.0;
+    commands.insert_resource(PropAssets {
+        crate_mesh: meshes.add(Cuboid::from_size(half(PropKind::Crate))),
+        barrel_mesh: meshes.add(Cylinder::new(PropKind::Barrel.half_extents().x, half(PropKind::Barrel).y)),
+        wall_mesh: meshes.add(Cuboid::from_size(half(PropKind::Wall))),
+        debris_mesh: meshes.add(Cuboid::new(0.25, 0.25, 0.25)),
+        crate_material: material(PropKind::Crate),
+        barrel_material: material(PropKind::Barrel),
+        wall_material: material(PropKind::Wall),
+    });
+}
+
+// Scatter crates and barrels around each new structure, behind a wall facing the player's side
+pub fn spawn_structure_props(
+    mut commands: Commands,
+    assets: Res<PropAssets>,
+    models: Res<ModelRegistry>,
+    asset_server: Res<AssetServer>,
+    structure_query: Query<&Transform, Added<Catapult>>,
+) {
+    for transform in structure_query.iter() {
+        let center = transform.translation;
+        let mut rng = placement_rng(center, PROP_STREAM);
+
+        // The wall stands between the structure and the origin, where runs start
+        let toward_origin = Vec2::new(-center.x, -center.z).normalize_or(Vec2::X);
+        let wall = Vec2::new(center.x, center.z) + toward_origin * WALL_DISTANCE;
+        let yaw = f32::atan2(toward_origin.x, toward_origin.y);
+        spawn_prop(&mut commands, &assets, models.prop(PropKind::Wall, &asset_server), PropKind::Wall, wall.x, wall.y, yaw);
+
+        for _ in 0..PROPS_PER_STRUCTURE {
+            let angle = rng.gen_range(0.0..TAU);
+            let distance = rng.gen_range(PROP_MIN_DISTANCE..PROP_MAX_DISTANCE);
+            let kind = if rng.gen_bool(0.5) { PropKind::Crate } else { PropKind::Barrel };
+            let x = center.x + angle.cos() * distance;
+            let z = center.z + angle.sin() * distance;
+            spawn_prop(&mut commands, &assets, models.prop(kind, &asset_server), kind, x, z, rng.gen_range(0.0..TAU));
+        }
+    }
+}
+
+// Remove every prop and piece of debris at the end of a run
+pub fn despawn_props(mut commands: Commands, query: Query<Entity, Or<(With<Prop>, With<Debris>)>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Damage and knock back props caught in a boulder's blast
+pub fn blast_props(
+    mut impact_events: EventReader<ProjectileImpactEvent>,
+    spatial_grid: Res<SpatialGrid>,
+    mut prop_query: Query<(&Transform, &mut Prop)>,
+) {
+    for event in impact_events.read() {
+        for entity in spatial_grid.nearby(event.position, event.radius) {
+            let Ok((transform, mut prop)) = prop_query.get_mut(entity) else {
+                continue;
+            };
+            let nearest = closest_point(transform, prop.kind.half_extents(), event.position);
+            let distance = nearest.distance(event.position);
+            if distance > event.radius {
+                continue;
+            }
+            let strength = 1.0 - distance / event.radius;
+            prop.health -= BLAST_DAMAGE * strength;
+            prop.damaged_by_player = !event.hostile;
+            // Walls are too heavy to be thrown around
+            if prop.kind != PropKind::Wall {
+                let away = (transform.translation - event.position).with_y(0.0).normalize_or_zero();
+                prop.velocity += (away + Vec3::Y) * BLAST_IMPULSE * strength;
+            }
+        }
+    }
+}
+
+// Props stop the player; rolling into one fast enough damages it and bounces the player back
+pub fn ram_props(
+    mut player_query: Query<(&mut Transform, &mut PlayerPhysics), With<Player>>,
+    mut prop_query: Query<(&Transform, &mut Prop), Without<Player>>,
+    spatial_grid: Res<SpatialGrid>,
+) {
+    let Ok((mut player_transform, mut physics)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for entity in spatial_grid.nearby(player_transform.translation, PLAYER_RADIUS) {
+        let Ok((transform, mut prop)) = prop_query.get_mut(entity) else {
+            continue;
+        };
+        let nearest = closest_point(transform, prop.kind.half_extents(), player_transform.translation);
+        let offset = player_transform.translation - nearest;
+        if offset.length() >= PLAYER_RADIUS {
+            continue;
+        }
+        let normal = offset.with_y(0.0).normalize_or(Vec3::Y);
+
+        // Speed into the prop, which is lost on contact
+        let closing_speed = -physics.velocity.dot(normal);
+        if closing_speed > 0.0 {
+            if closing_speed >= RAM_SPEED {
+                prop.health -= closing_speed * RAM_DAMAGE_PER_SPEED;
+                prop.damaged_by_player = true;
+                physics.velocity += normal * closing_speed * (1.0 + RAM_BOUNCE);
+            } else {
+                physics.velocity += normal * closing_speed;
+            }
+            physics.momentum = physics.velocity;
+        }
+        player_transform.translation = nearest + normal * PLAYER_RADIUS;
+    }
+}
+
+// Let knocked props fall and slide to a stop, and shatter the ones out of health
+pub fn update_props(
+    mut commands: Commands,
+    assets: Res<PropAssets>,
+    mut score: ResMut<Score>,
+    mut game_events: EventWriter<GameEvent>,
+    mut prop_query: Query<(Entity, &mut Transform, &mut Prop)>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+
+    for (entity, mut transform, mut prop) in prop_query.iter_mut() {
+        if prop.health <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            let (_, material) = assets.get(prop.kind);
+            let mut rng = placement_rng(transform.translation, PROP_STREAM);
+            for _ in 0..DEBRIS_COUNT {
+                let direction = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(0.3..1.0), rng.gen_range(-1.0..1.0));
+                commands.spawn((
+                    Debris {
+                        velocity: direction.normalize_or(Vec3::Y) * rng.gen_range(1.0..DEBRIS_SPEED),
+                        spin: Vec3::new(rng.gen_range(-6.0..6.0), rng.gen_range(-6.0..6.0), rng.gen_range(-6.0..6.0)),
+                    },
+                    Lifetime::new(DEBRIS_LIFETIME),
+                    Mesh3d(assets.debris_mesh.clone()),
+                    MeshMaterial3d(material.clone()),
+                    Transform::from_translation(transform.translation),
+                ));
+            }
+            if prop.damaged_by_player {
+                let points = prop.kind.points();
+                score.points += points;
+                spawn_score_popup(&mut commands, transform.translation, format!("+{}", points));
+                game_events.send(GameEvent::PropDestroyed {
+                    name: prop.kind.label().to_string(),
+                    position: transform.translation,
+                });
+            }
+            continue;
+        }
+
+        if prop.velocity == Vec3::ZERO {
+            continue;
+        }
+        prop.velocity.y -= PROP_GRAVITY * delta;
+        transform.translation += prop.velocity * delta;
+        let rest_height = get_terrain_height(transform.translation.x, transform.translation.z) + prop.kind.half_extents().y;
+        if transform.translation.y <= rest_height {
+            transform.translation.y = rest_height;
+            prop.velocity.y = 0.0;
+            prop.velocity.x *= PROP_FRICTION;
+            prop.velocity.z *= PROP_FRICTION;
+            if prop.velocity.length_squared() < 0.01 {
+                prop.velocity = Vec3::ZERO;
+            }
+        }
+    }
+}
+
+// Tumble debris through the air, bounce it off the ground and shrink it away
+pub fn update_debris(mut query: Query<(&mut Transform, &mut Debris, &Lifetime)>, time: Res<Time>) {
+    let delta = time.delta_secs();
+    for (mut transform, mut debris, lifetime) in query.iter_mut() {
+        debris.velocity.y -= PROP_GRAVITY * delta;
+        transform.translation += debris.velocity * delta;
+        let ground = get_terrain_height(transform.translation.x, transform.translation.z) + 0.1;
+        if transform.translation.y < ground {
+            transform.translation.y = ground;
+            debris.velocity *= 0.4;
+            debris.velocity.y = debris.velocity.y.abs();
+            debris.spin *= 0.5;
+        }
+        let spin = Quat::from_scaled_axis(debris.spin * delta);
+        transform.rotation = spin * transform.rotation;
+        transform.scale = Vec3::splat(1.0 - lifetime.fraction().powi(3));
+    }
+}
+
+// Plugin for breakable props
+pub struct PropPlugin;
+
+impl Plugin for PropPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup_prop_assets)
+            .add_systems(OnExit(GameState::GameOver), despawn_props)
+            .add_systems(Update, spawn_structure_props)
+            .add_systems(FixedUpdate, (blast_props, ram_props, update_props)
+                .chain()
+                .in_set(SimSet::Constraints)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)))
+            .add_systems(Update, update_debris
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)));
+    }
+}
@@ -0,0 +1,464 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f32::consts::{FRAC_PI_4, TAU};
+use crate::terrain::{get_terrain_height, terrain_gradient, world_seed};
+use crate::player::{player_spawn_position, Player, PlayerPhysics};
+use crate::sim::SimSet;
+use crate::compass::CompassMarker;
+use crate::waypoint::Waypoint;
+use crate::game_events::GameEvent;
+use crate::results::RunStats;
+use crate::save::SaveData;
+use crate::{GameMode, GameState, RunStart};
+
+// Race course constants
+const GATE_COUNT: usize = 10; // Gates in a course, including the start and finish
+const START_DISTANCE: f32 = 10.0; // Distance from the spawn point to the start gate
+const GATE_SPACING: f32 = 22.0; // Distance between consecutive gates
+const MAX_TURN: f32 = FRAC_PI_4; // Largest change of heading from one gate to the next
+const HEADING_ATTEMPTS: usize = 8; // Headings tried when looking for a reachable next gate
+const MAX_CLIMB: f32 = 0.45; // Uphill gradient along a leg that still counts as reachable
+const CLIMB_SAMPLES: usize = 12; // Slope samples taken along each leg
+pub const GATE_HALF_WIDTH: f32 = 3.0; // Half the distance between a gate's posts
+const GATE_HEIGHT: f32 = 4.0; // Height of the gate posts
+const GATE_DEPTH: f32 = 1.5; // How far in front of or behind a gate still counts as passing through
+const COUNTDOWN_TIME: f32 = 3.0; // Seconds of countdown before a race against opponents
+const GHOST_SAMPLE_INTERVAL: f32 = 0.1; // Seconds between recorded positions for ghost replays
+const GATE_COLOR: Color = Color::srgb(0.2, 0.8, 0.9);
+const FINISH_COLOR: Color = Color::srgb(0.95, 0.95, 0.95);
+
+// A checkpoint gate; the player has to pass through them in order
+#[derive(Component)]
+pub struct Gate {
+    pub index: usize,
+}
+
+// Progress through the current time trial or race
+#[derive(Resource, Default)]
+pub struct RaceState {
+    // Gates in the course
+    pub gate_count: usize,
+    // Center of each gate on the terrain, in order
+    pub course: Vec<Vec3>,
+    // Seconds left before the start of a race against opponents
+    pub countdown: f32,
+    // Player's place among all racers, 1 being first
+    pub position: usize,
+    // Racers including the player
+    pub racers: usize,
+    // Index of the next gate to pass
+    pub next_gate: usize,
+    // Seconds since the start gate, while the clock is running
+    pub elapsed: f32,
+    pub running: bool,
+    // Player positions sampled while racing, for a ghost replay
+    pub ghost_path: Vec<Vec3>,
+    ghost_timer: f32,
+}
+
+impl RaceState {
+    // Rough meters covered along the course, for ranking racers
+    pub fn progress(&self, next_gate: usize, position: Vec3) -> f32 {
+        let Some(gate) = self.course.get(next_gate) else {
+            return f32::MAX;
+        };
+        next_gate as f32 * GATE_SPACING - Vec2::new(gate.x - position.x, gate.z - position.z).length()
+    }
+}
+
+// Whether the selected mode races through a gate course
+pub fn racing_mode(mode: Res<GameMode>) -> bool {
+    matches!(*mode, GameMode::TimeTrial | GameMode::DownhillRace)
+}
+
+// Sent when the finish gate is passed, carrying the recorded path for ghost replays
+#[derive(Event)]
+pub struct RaceFinished {
+    pub time: f32,
+    pub new_best: bool,
+    // Player positions every GHOST_SAMPLE_INTERVAL seconds from the start gate to the finish
+    pub ghost_path: Vec<Vec3>,
+}
+
+// Marker for the race timer panel
+#[derive(Component)]
+pub struct RaceHudRoot;
+
+// Marker for the race timer text
+#[derive(Component)]
+pub struct RaceHudText;
+
+// Marker for the large countdown text in the middle of the screen
+#[derive(Component)]
+pub struct RaceCountdownText;
+
+// Steepest climb met rolling straight from one point to another
+fn leg_climb(from: Vec2, to: Vec2) -> f32 {
+    let direction = (to - from).normalize_or_zero();
+    (0..=CLIMB_SAMPLES)
+        .map(|i| {
+            let point = from.lerp(to, i as f32 / CLIMB_SAMPLES as f32);
+            terrain_gradient(point.x, point.y).dot(direction)
+        })
+        .fold(0.0, f32::max)
+}
+
+// Gate positions and headings for a seed, each gate a manageable roll from the one before.
+// Downhill courses take the lowest reachable heading at each gate.
+pub fn generate_course(seed: u32, start: Vec3, downhill: bool) -> Vec<(Vec3, f32)> {
+    let mut rng = StdRng::seed_from_u64(seed as u64 ^ 0x7261_6365);
+    let mut heading: f32 = rng.gen_range(0.0..TAU);
+    let mut position = Vec2::new(start.x, start.z);
+    let mut gates = Vec::with_capacity(GATE_COUNT);
+
+    for i in 0..GATE_COUNT {
+        let spacing = if i == 0 { START_DISTANCE } else { GATE_SPACING };
+
+        // Take the first reachable heading (the lowest one when downhill), or the gentlest if none is
+        let mut best: Option<(f32, Vec2, f32)> = None;
+        for _ in 0..HEADING_ATTEMPTS {
+            let candidate_heading = heading + rng.gen_range(-MAX_TURN..MAX_TURN);
+            let candidate = position + Vec2::new(candidate_heading.cos(), candidate_heading.sin()) * spacing;
+            let climb = leg_climb(position, candidate);
+            let reachable = climb <= MAX_CLIMB;
+            let mut rank = if reachable { 0.0 } else { 1000.0 + climb };
+            if downhill {
+                rank += get_terrain_height(candidate.x, candidate.y);
+            }
+            if best.is_none_or(|(best_rank, _, _)| rank < best_rank) {
+                best = Some((rank, candidate, candidate_heading));
+            }
+            if reachable && !downhill {
+                break;
+            }
+        }
+        let Some((_, next, next_heading)) = best else {
+            break;
+        };
+
+        gates.push((Vec3::new(next.x, get_terrain_height(next.x, next.y), next.y), next_heading));
+        position = next;
+        heading = next_heading;
+    }
+    gates
+}
+
+// Lay out the course when a time trial or race starts
+pub fn spawn_race_course(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut race: ResMut<RaceState>,
+    mode: Res<GameMode>,
+) {
+    if !matches!(*mode, GameMode::TimeTrial | GameMode::DownhillRace) {
+        return;
+    }
+    let downhill = *mode == GameMode::DownhillRace;
+    *race = RaceState {
+        countdown: if downhill { COUNTDOWN_TIME } else { 0.0 },
+        position: 1,
+        racers: 1,
+        ..default()
+    };
+
+    let post_mesh = meshes.add(Cylinder::new(0.2, GATE_HEIGHT));
+    let bar_mesh = meshes.add(Cuboid::new(GATE_HALF_WIDTH * 2.0 + 0.4, 0.3, 0.3));
+    let gate_material = materials.add(StandardMaterial {
+        base_color: GATE_COLOR,
+        emissive: (GATE_COLOR.to_linear() * 0.3).into(),
+        ..default()
+    });
+    let finish_material = materials.add(StandardMaterial {
+        base_color: FINISH_COLOR,
+        emissive: (FINISH_COLOR.to_linear() * 0.3).into(),
+        ..default()
+    });
+
+    let course = generate_course(world_seed(), player_spawn_position(), downhill);
+    race.gate_count = course.len();
+    race.course = course.iter().map(|(position, _)| *position).collect();
+    let last = course.len().saturating_sub(1);
+    for (index, (position, heading)) in course.into_iter().enumerate() {
+        let material = if index == last { finish_material.clone() } else { gate_material.clone() };
+        // The posts stand across the direction of travel
+        let rotation = Quat::from_rotation_y(-heading - std::f32::consts::FRAC_PI_2);
+        let mut gate = commands.spawn((
+            Gate { index },
+            Transform::from_translation(position).with_rotation(rotation),
+            Visibility::default(),
+            Name::new(format!("Gate {}", index + 1)),
+        ));
+        gate.with_children(|parent| {
+            for side in [-1.0, 1.0] {
+                parent.spawn((
+                    Mesh3d(post_mesh.clone()),
+                    MeshMaterial3d(material.clone()),
+                    Transform::from_xyz(side * GATE_HALF_WIDTH, GATE_HEIGHT * 0.5, 0.0),
+                ));
+            }
+            parent.spawn((
+                Mesh3d(bar_mesh.clone()),
+                MeshMaterial3d(material.clone()),
+                Transform::from_xyz(0.0, GATE_HEIGHT, 0.0),
+            ));
+        });
+        if index == 0 {
+            gate.insert(gate_markers(index, last));
+        }
+    }
+}
+
+// Waypoint and compass marker for the gate to head for next
+fn gate_markers(index: usize, last: usize) -> (Waypoint, CompassMarker) {
+    let (label, color) = if index == 0 {
+        ("Start".to_string(), GATE_COLOR)
+    } else if index == last {
+        ("Finish".to_string(), FINISH_COLOR)
+    } else {
+        (format!("Gate {}", index + 1), GATE_COLOR)
+    };
+    (Waypoint { label, color }, CompassMarker { color })
+}
+
+// Remove the course at the end of a run
+pub fn despawn_race_course(
+    mut commands: Commands,
+    gate_query: Query<Entity, With<Gate>>,
+    mut race: ResMut<RaceState>,
+) {
+    for entity in gate_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    *race = RaceState::default();
+}
+
+// Count down to the start of a race, then start the clock
+pub fn tick_countdown(mut race: ResMut<RaceState>, time: Res<Time>) {
+    if race.countdown <= 0.0 {
+        return;
+    }
+    race.countdown -= time.delta_secs();
+    if race.countdown <= 0.0 {
+        race.countdown = 0.0;
+        race.running = true;
+        race.ghost_timer = 0.0;
+    }
+}
+
+// Keep the player on the start line until the countdown ends
+pub fn hold_at_start(race: Res<RaceState>, mut player_query: Query<&mut PlayerPhysics, With<Player>>) {
+    if race.countdown <= 0.0 {
+        return;
+    }
+    for mut physics in player_query.iter_mut() {
+        physics.velocity.x = 0.0;
+        physics.velocity.z = 0.0;
+        physics.momentum = Vec3::ZERO;
+    }
+}
+
+// Advance through the gates in order, running the clock until the finish.
+// A time trial's clock starts at the start gate; a race's starts after the countdown.
+pub fn check_gates(
+    mut commands: Commands,
+    mut race: ResMut<RaceState>,
+    mut save: ResMut<SaveData>,
+    mut run_stats: ResMut<RunStats>,
+    mut finished_events: EventWriter<RaceFinished>,
+    mut game_events: EventWriter<GameEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mode: Res<GameMode>,
+    player_query: Query<&Transform, With<Player>>,
+    gate_query: Query<(Entity, &Gate, &Transform), Without<Player>>,
+    time: Res<Time>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation;
+
+    if race.running {
+        race.elapsed += time.delta_secs();
+        race.ghost_timer -= time.delta_secs();
+        if race.ghost_timer <= 0.0 {
+            race.ghost_timer += GHOST_SAMPLE_INTERVAL;
+            race.ghost_path.push(player_pos);
+        }
+    }
+
+    let gate_count = race.gate_count;
+    let Some((entity, gate, gate_transform)) = gate_query.iter().find(|(_, gate, _)| gate.index == race.next_gate) else {
+        return;
+    };
+
+    // Through the gate when between the posts, under the bar and close to its plane
+    let local = gate_transform.rotation.inverse() * (player_pos - gate_transform.translation);
+    if local.x.abs() > GATE_HALF_WIDTH || local.z.abs() > GATE_DEPTH || local.y > GATE_HEIGHT {
+        return;
+    }
+    commands.entity(entity).despawn_recursive();
+    race.next_gate = gate.index + 1;
+
+    if gate.index == 0 && !race.running {
+        race.running = true;
+        race.ghost_path.push(player_pos);
+        race.ghost_timer = GHOST_SAMPLE_INTERVAL;
+    }
+
+    if race.next_gate >= gate_count {
+        // Finish line
+        race.running = false;
+        race.ghost_path.push(player_pos);
+        let time = race.elapsed;
+        // Only solo time trials run at full speed count toward the best time
+        let new_best = *mode == GameMode::TimeTrial && !run_stats.time_assisted && save.record_race_time(world_seed(), time);
+        run_stats.race_time = Some(time);
+        if *mode == GameMode::DownhillRace {
+            run_stats.race_position = Some(race.position);
+        }
+        game_events.send(GameEvent::RaceFinished { time, new_best });
+        finished_events.send(RaceFinished {
+            time,
+            new_best,
+            ghost_path: std::mem::take(&mut race.ghost_path),
+        });
+        next_state.set(GameState::GameOver);
+        return;
+    }
+
+    // Point the markers at the next gate
+    if let Some((next_entity, next_gate, _)) = gate_query.iter().find(|(_, gate, _)| gate.index == race.next_gate) {
+        commands.entity(next_entity).insert(gate_markers(next_gate.index, gate_count - 1));
+    }
+}
+
+// Build the race timer panel under the compass
+pub fn spawn_race_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            RaceHudRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(48.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                RaceHudText,
+                Text::new(""),
+                TextFont {
+                    font_size: 22.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+
+    commands.spawn((
+        RaceHudRoot,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(35.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Visibility::Hidden,
+    )).with_children(|parent| {
+        parent.spawn((
+            RaceCountdownText,
+            Text::new(""),
+            TextFont {
+                font_size: 96.0,
+                ..default()
+            },
+            TextColor(Color::srgb(1.0, 0.85, 0.2)),
+        ));
+    });
+}
+
+// Show the race timer while playing a time trial or race
+pub fn show_race_hud(mode: Res<GameMode>, mut query: Query<&mut Visibility, With<RaceHudRoot>>) {
+    for mut visibility in query.iter_mut() {
+        let racing = matches!(*mode, GameMode::TimeTrial | GameMode::DownhillRace);
+        *visibility = if racing { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+// Hide the race timer outside of gameplay
+pub fn hide_race_hud(mut query: Query<&mut Visibility, With<RaceHudRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Show gate progress, the clock and either the best time for this seed or the race position
+pub fn update_race_hud(
+    race: Res<RaceState>,
+    save: Res<SaveData>,
+    mode: Res<GameMode>,
+    mut text_query: Query<&mut Text, (With<RaceHudText>, Without<RaceCountdownText>)>,
+    mut countdown_query: Query<&mut Text, With<RaceCountdownText>>,
+) {
+    let standing = if *mode == GameMode::DownhillRace {
+        format!("Position {}/{}", race.position, race.racers)
+    } else {
+        let best = save
+            .best_race_time(world_seed())
+            .map_or("--".to_string(), |best| format!("{:.2} s", best));
+        format!("Best {}", best)
+    };
+    for mut text in text_query.iter_mut() {
+        text.0 = if race.running || race.next_gate > 0 {
+            format!("Gate {}/{}   {:.2} s   {}", race.next_gate, race.gate_count, race.elapsed, standing)
+        } else if race.countdown > 0.0 {
+            standing.clone()
+        } else {
+            format!("Roll through the start gate   {}", standing)
+        };
+    }
+
+    // 3, 2, 1, then GO! for a second after the start
+    let countdown = if race.countdown > 0.0 {
+        format!("{}", race.countdown.ceil() as u32)
+    } else if *mode == GameMode::DownhillRace && race.running && race.elapsed < 1.0 {
+        "GO!".to_string()
+    } else {
+        String::new()
+    };
+    for mut text in countdown_query.iter_mut() {
+        if text.0 != countdown {
+            text.0 = countdown.clone();
+        }
+    }
+}
+
+// Plugin for the time trial and downhill race modes
+pub struct RacePlugin;
+
+impl Plugin for RacePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<RaceState>()
+            .add_event::<RaceFinished>()
+            .add_systems(Startup, spawn_race_hud)
+            .add_systems(RunStart, spawn_race_course)
+            .add_systems(OnExit(GameState::GameOver), despawn_race_course)
+            .add_systems(OnEnter(GameState::Playing), show_race_hud)
+            .add_systems(OnExit(GameState::Playing), hide_race_hud)
+            .add_systems(FixedUpdate, (tick_countdown, hold_at_start, check_gates)
+                .chain()
+                .in_set(SimSet::Constraints)
+                .run_if(in_state(GameState::Playing))
+                .run_if(racing_mode))
+            .add_systems(Update, update_race_hud
+                .run_if(in_state(GameState::Playing))
+                .run_if(racing_mode));
+    }
+}
@@ -0,0 +1,212 @@
+use bevy::prelude::*;
+use rand::Rng;
+use crate::nav::find_path;
+use crate::race::{spawn_race_course, RaceState, GATE_HALF_WIDTH};
+use crate::terrain::get_terrain_height;
+use crate::platforms::PlatformSurfaces;
+use crate::player::{player_spawn_position, roll_sphere, Player, PlayerPhysics, RollInput};
+use crate::photo_mode::PhotoModeState;
+use crate::minimap::MinimapBlip;
+use crate::compass::CompassMarker;
+use crate::sim::{Interpolated, SimSet, Simulation};
+use crate::{GameMode, GameState, RunStart};
+use crate::tuning::LiveTuning;
+
+// Race opponent constants
+const OPPONENT_COUNT: usize = 3; // AI balls lined up against the player
+const OPPONENT_RADIUS: f32 = 0.5; // Same size as the player ball
+const GRID_SPACING: f32 = 2.5; // Distance between balls on the start line
+const PATH_POINT_REACHED: f32 = 2.0; // Distance at which an opponent moves on to its next path point
+const MIN_SKILL: f32 = 0.8; // Weakest drive an opponent rolls with
+const MAX_SKILL: f32 = 1.0; // Strongest drive an opponent rolls with
+const RUBBER_BAND_STRENGTH: f32 = 0.02; // Speed change per meter of lead or deficit on the player
+const MIN_RUBBER_BAND: f32 = 0.7; // Slowest an opponent leading the pack is held to
+const MAX_RUBBER_BAND: f32 = 1.3; // Fastest an opponent falling behind is pushed to
+const OPPONENT_COLOR: Color = Color::srgb(0.9, 0.5, 0.1);
+const OPPONENT_STREAM: u64 = 6; // Simulation random stream for opponent skill
+
+// An AI ball racing the player through the gates; rolls with its own PlayerPhysics
+#[derive(Component)]
+pub struct RaceOpponent {
+    // Points on the terrain to roll through, from the start line to the finish gate
+    pub path: Vec<Vec3>,
+    // Path point the opponent is heading for
+    pub path_index: usize,
+    // Gate the opponent has to pass next
+    pub next_gate: usize,
+    // Race clock when the opponent crossed the finish
+    pub finish_time: Option<f32>,
+    // Fraction of full drive this opponent rolls with
+    pub skill: f32,
+}
+
+// Nav grid path through every gate in order, falling back to a straight line for legs it can't solve
+fn plan_course_path(start: Vec3, course: &[Vec3]) -> Vec<Vec3> {
+    let mut path = Vec::new();
+    let mut from = start;
+    for &gate in course {
+        match find_path(from, gate) {
+            Some(leg) => path.extend(leg),
+            None => path.push(gate),
+        }
+        from = gate;
+    }
+    path
+}
+
+// Line the opponents up beside the player, facing the start gate
+pub fn spawn_opponents(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut race: ResMut<RaceState>,
+    mode: Res<GameMode>,
+    simulation: Res<Simulation>,
+) {
+    if *mode != GameMode::DownhillRace {
+        return;
+    }
+    let Some(&first_gate) = race.course.first() else {
+        return;
+    };
+    let spawn = player_spawn_position();
+    let path = plan_course_path(spawn, &race.course);
+
+    let forward = Vec3::new(first_gate.x - spawn.x, 0.0, first_gate.z - spawn.z).normalize_or(Vec3::NEG_Z);
+    let side = Vec3::new(-forward.z, 0.0, forward.x);
+    let mut rng = simulation.rng(OPPONENT_STREAM);
+    let mesh = meshes.add(Sphere::new(OPPONENT_RADIUS));
+    let material = materials.add(StandardMaterial {
+        base_color: OPPONENT_COLOR,
+        perceptual_roughness: 0.4,
+        ..default()
+    });
+
+    for i in 0..OPPONENT_COUNT {
+        // Alternate left and right of the player, moving outward
+        let slot = (i / 2 + 1) as f32 * if i % 2 == 0 { 1.0 } else { -1.0 };
+        let ground = spawn + side * slot * GRID_SPACING;
+        let position = Vec3::new(ground.x, get_terrain_height(ground.x, ground.z) + OPPONENT_RADIUS, ground.z);
+        commands.spawn((
+            RaceOpponent {
+                path: path.clone(),
+                path_index: 0,
+                next_gate: 0,
+                finish_time: None,
+                skill: rng.gen_range(MIN_SKILL..MAX_SKILL),
+            },
+            PlayerPhysics {
+                prev_position: position,
+                ..Default::default()
+            },
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(position),
+            Interpolated::new(position),
+            Name::new(format!("Racer {}", i + 1)),
+            MinimapBlip { color: OPPONENT_COLOR },
+            CompassMarker { color: OPPONENT_COLOR },
+        ));
+    }
+    race.racers = OPPONENT_COUNT + 1;
+}
+
+// Remove the opponents at the end of a run
+pub fn despawn_opponents(mut commands: Commands, opponent_query: Query<Entity, With<RaceOpponent>>) {
+    for entity in opponent_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Roll each opponent along its path, easing off when ahead of the player and pushing when behind
+pub fn move_opponents(
+    mut opponent_query: Query<(&mut Transform, &mut PlayerPhysics, &mut RaceOpponent), Without<Player>>,
+    player_query: Query<&Transform, With<Player>>,
+    race: Res<RaceState>,
+    platforms: Res<PlatformSurfaces>,
+    tuning: Res<LiveTuning>,
+    time: Res<Time>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let delta = time.delta_secs();
+    let player_progress = race.progress(race.next_gate, player_transform.translation);
+
+    for (mut transform, mut physics, mut opponent) in opponent_query.iter_mut() {
+        // Held on the start line until the countdown ends, and coasting after the finish
+        if race.countdown > 0.0 {
+            physics.velocity.x = 0.0;
+            physics.velocity.z = 0.0;
+            physics.momentum = Vec3::ZERO;
+        }
+        if !race.running || opponent.finish_time.is_some() {
+            roll_sphere(&mut transform, &mut physics, &tuning.0.player, &platforms.0, RollInput::default(), delta);
+            continue;
+        }
+
+        let position = transform.translation;
+        while opponent.path_index + 1 < opponent.path.len() {
+            let point = opponent.path[opponent.path_index];
+            if Vec2::new(point.x - position.x, point.z - position.z).length() > PATH_POINT_REACHED {
+                break;
+            }
+            opponent.path_index += 1;
+        }
+
+        if let Some(gate) = race.course.get(opponent.next_gate) {
+            if Vec2::new(gate.x - position.x, gate.z - position.z).length() < GATE_HALF_WIDTH {
+                opponent.next_gate += 1;
+                if opponent.next_gate == race.gate_count {
+                    opponent.finish_time = Some(race.elapsed);
+                }
+            }
+        }
+
+        let lead = race.progress(opponent.next_gate, position) - player_progress;
+        physics.speed_multiplier = (1.0 - lead * RUBBER_BAND_STRENGTH).clamp(MIN_RUBBER_BAND, MAX_RUBBER_BAND);
+        let direction = opponent.path.get(opponent.path_index).map_or(Vec3::ZERO, |point| {
+            Vec3::new(point.x - position.x, 0.0, point.z - position.z).normalize_or_zero()
+        });
+        roll_sphere(&mut transform, &mut physics, &tuning.0.player, &platforms.0, RollInput::steer(direction * opponent.skill), delta);
+    }
+}
+
+// Rank the player among the racers by gates passed and distance to the next gate
+pub fn update_race_position(
+    mut race: ResMut<RaceState>,
+    player_query: Query<&Transform, With<Player>>,
+    opponent_query: Query<(&Transform, &RaceOpponent)>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_progress = race.progress(race.next_gate, player_transform.translation);
+    let ahead = opponent_query
+        .iter()
+        .filter(|(transform, opponent)| {
+            opponent.finish_time.is_some() || race.progress(opponent.next_gate, transform.translation) > player_progress
+        })
+        .count();
+    race.position = ahead + 1;
+}
+
+// Plugin for AI opponents in downhill races
+pub struct RaceAiPlugin;
+
+impl Plugin for RaceAiPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(RunStart, spawn_opponents.after(spawn_race_course))
+            .add_systems(OnExit(GameState::GameOver), despawn_opponents)
+            .add_systems(FixedUpdate, move_opponents
+                .in_set(SimSet::Movement)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive))
+                .run_if(resource_equals(GameMode::DownhillRace)))
+            .add_systems(Update, update_race_position
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive))
+                .run_if(resource_equals(GameMode::DownhillRace)));
+    }
+}
@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use crate::game_events::GameEvent;
+use crate::player::{Health, Player};
+use crate::projectile::{Hostile, Projectile, ProjectileImpactEvent};
+use crate::sim::Simulation;
+use crate::terrain::{TerrainChunk, TerrainConfig};
+use crate::{GameMode, GameState, RunStart};
+
+// What happened, as written to the record file; the kind goes in an "event" field next to the timestamps
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RecordKind {
+    // A run started, resetting the tick count
+    RunStarted { mode: GameMode, seed: String },
+    // A boulder was thrown, by the player or at them
+    Throw { start: Vec3, target: Vec3, velocity: Vec3, hostile: bool },
+    // A boulder hit the ground
+    Impact { position: Vec3, hostile: bool, radius: f32 },
+    // The player lost health
+    Damage { amount: f32, health: f32, position: Vec3 },
+    // A terrain chunk finished loading
+    ChunkLoaded { x: i32, z: i32 },
+    // The player's health ran out
+    Death { position: Vec3 },
+    // Anything broadcast on the game event bus
+    Game { detail: GameEvent },
+}
+
+// One line of the record file
+#[derive(Serialize)]
+struct RecordLine<'a> {
+    // Simulation tick and seconds since the run started
+    tick: u32,
+    time: f32,
+    #[serde(flatten)]
+    kind: &'a RecordKind,
+}
+
+// Writes typed gameplay events as JSON lines for looking over a run afterwards, such as while balancing;
+// only present when the game was started with --record
+#[derive(Resource)]
+pub struct EventRecorder {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    // Set after a failed write so the error is printed once
+    failed: bool,
+}
+
+impl EventRecorder {
+    // Start a new record file, replacing any old one at the path
+    pub fn create(path: &Path) -> std::io::Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let writer = BufWriter::new(File::create(path)?);
+        Ok(Self { path: path.to_path_buf(), writer, failed: false })
+    }
+
+    // Add one event, stamped with the simulation clock
+    pub fn record(&mut self, simulation: &Simulation, kind: RecordKind) {
+        let line = RecordLine { tick: simulation.tick, time: simulation.elapsed(), kind: &kind };
+        let result = serde_json::to_string(&line)
+            .map_err(std::io::Error::other)
+            .and_then(|json| writeln!(self.writer, "{}", json));
+        self.report(result);
+    }
+
+    fn flush(&mut self) {
+        let result = self.writer.flush();
+        self.report(result);
+    }
+
+    fn report(&mut self, result: std::io::Result<()>) {
+        if let Err(error) = result {
+            if !self.failed {
+                println!("Failed to write event record {}: {}", self.path.display(), error);
+            }
+            self.failed = true;
+        }
+    }
+}
+
+// Note the start of a run; the simulation clock has just been reset to zero
+pub fn record_run_start(
+    mut recorder: ResMut<EventRecorder>,
+    simulation: Res<Simulation>,
+    mode: Res<GameMode>,
+    config: Res<TerrainConfig>,
+) {
+    recorder.record(&simulation, RecordKind::RunStarted { mode: *mode, seed: config.seed_code() });
+}
+
+// Record new boulders and boulder impacts
+pub fn record_projectiles(
+    mut recorder: ResMut<EventRecorder>,
+    simulation: Res<Simulation>,
+    mut impacts: EventReader<ProjectileImpactEvent>,
+    thrown_query: Query<(&Projectile, Has<Hostile>), Added<Projectile>>,
+) {
+    for (projectile, hostile) in thrown_query.iter() {
+        recorder.record(&simulation, RecordKind::Throw {
+            start: projectile.start_position,
+            target: projectile.target_position,
+            velocity: projectile.initial_velocity,
+            hostile,
+        });
+    }
+    for impact in impacts.read() {
+        recorder.record(&simulation, RecordKind::Impact {
+            position: impact.position,
+            hostile: impact.hostile,
+            radius: impact.radius,
+        });
+    }
+}
+
+// Record the player losing health, and dying when it runs out
+pub fn record_damage(
+    mut recorder: ResMut<EventRecorder>,
+    simulation: Res<Simulation>,
+    mut last_health: Local<Option<f32>>,
+    player_query: Query<(&Transform, &Health), With<Player>>,
+) {
+    let Ok((transform, health)) = player_query.get_single() else {
+        return;
+    };
+    let previous = last_health.replace(health.current).unwrap_or(health.current);
+    if health.current >= previous {
+        return;
+    }
+    let position = transform.translation;
+    recorder.record(&simulation, RecordKind::Damage { amount: previous - health.current, health: health.current, position });
+    if health.current <= 0.0 && previous > 0.0 {
+        recorder.record(&simulation, RecordKind::Death { position });
+    }
+}
+
+// Record chunks as they finish loading, including behind the menus
+pub fn record_chunk_loads(
+    mut recorder: ResMut<EventRecorder>,
+    simulation: Res<Simulation>,
+    chunk_query: Query<&TerrainChunk, Added<TerrainChunk>>,
+) {
+    for chunk in chunk_query.iter() {
+        recorder.record(&simulation, RecordKind::ChunkLoaded { x: chunk.chunk_x, z: chunk.chunk_z });
+    }
+}
+
+// Record everything sent on the game event bus
+pub fn record_game_events(
+    mut recorder: ResMut<EventRecorder>,
+    simulation: Res<Simulation>,
+    mut events: EventReader<GameEvent>,
+) {
+    for event in events.read() {
+        recorder.record(&simulation, RecordKind::Game { detail: event.clone() });
+    }
+}
+
+// Write out the frame's events so the file is complete even if the game is killed
+pub fn flush_recorder(mut recorder: ResMut<EventRecorder>) {
+    recorder.flush();
+}
+
+// Plugin for the gameplay event recorder; main inserts the EventRecorder when --record is given, and
+// without it none of this runs
+pub struct RecorderPlugin;
+
+impl Plugin for RecorderPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(RunStart, record_run_start
+                .run_if(resource_exists::<EventRecorder>))
+            .add_systems(Update, (record_projectiles, record_damage, record_game_events)
+                .run_if(resource_exists::<EventRecorder>)
+                .run_if(in_state(GameState::Playing)))
+            .add_systems(Update, record_chunk_loads.run_if(resource_exists::<EventRecorder>))
+            .add_systems(Last, flush_recorder.run_if(resource_exists::<EventRecorder>));
+    }
+}
@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use crate::game_events::GameEvent;
+use crate::player::Player;
+use crate::terrain::{chunk_coords, world_seed};
+use crate::GameState;
+
+// Region constants
+pub const REGION_SIZE_CHUNKS: i32 = 3; // Regions are square groups of chunks
+const FAR_REGION_DISTANCE: i32 = 3; // Regions this many steps from the start are called "Far"
+
+// Landform names regions are given
+const REGION_FEATURES: [&str; 8] = ["Ridge", "Hollow", "Downs", "Heights", "Vale", "Slopes", "Bluffs", "Meadows"];
+
+// Regions the player has visited
+#[derive(Resource, Default)]
+pub struct Regions {
+    pub discovered: HashSet<(i32, i32)>,
+    // Region the player is currently in
+    pub current: Option<(i32, i32)>,
+}
+
+// Region containing a chunk
+pub fn region_of_chunk(chunk: (i32, i32)) -> (i32, i32) {
+    (chunk.0.div_euclid(REGION_SIZE_CHUNKS), chunk.1.div_euclid(REGION_SIZE_CHUNKS))
+}
+
+// Stable, human readable name for a region, e.g. "Western Ridge"
+pub fn region_name(region: (i32, i32)) -> String {
+    if region == (0, 0) {
+        return "Home Valley".to_string();
+    }
+
+    // Direction from the start, with north as -Z
+    let angle = (region.0 as f32).atan2(-(region.1 as f32));
+    let octant = ((angle / std::f32::consts::FRAC_PI_4).round() as i32).rem_euclid(8);
+    let direction = ["Northern", "Northeastern", "Eastern", "Southeastern", "Southern", "Southwestern", "Western", "Northwestern"][octant as usize];
+
+    // Hash the coordinates with the seed so neighbouring regions get different landforms
+    let hash = (region.0 as u32)
+        .wrapping_mul(73_856_093)
+        ^ (region.1 as u32).wrapping_mul(19_349_663)
+        ^ world_seed().wrapping_mul(83_492_791);
+    let feature = REGION_FEATURES[(hash % REGION_FEATURES.len() as u32) as usize];
+
+    let distance = region.0.abs().max(region.1.abs());
+    if distance >= FAR_REGION_DISTANCE {
+        format!("Far {} {}", direction, feature)
+    } else {
+        format!("{} {}", direction, feature)
+    }
+}
+
+// Report the first visit to each region
+pub fn discover_regions(
+    mut regions: ResMut<Regions>,
+    player_query: Query<&Transform, With<Player>>,
+    mut game_events: EventWriter<GameEvent>,
+) {
+    let Ok(transform) = player_query.get_single() else {
+        return;
+    };
+    let region = region_of_chunk(chunk_coords(transform.translation));
+    if regions.current == Some(region) {
+        return;
+    }
+    regions.current = Some(region);
+    if regions.discovered.insert(region) {
+        game_events.send(GameEvent::RegionDiscovered { name: region_name(region) });
+    }
+}
+
+// Plugin for named regions and discovery
+pub struct RegionPlugin;
+
+impl Plugin for RegionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Regions>()
+            .add_systems(Update, discover_regions.run_if(in_state(GameState::Playing)));
+    }
+}
@@ -0,0 +1,658 @@
+use bevy::prelude::*;
+use bevy::utils::SystemTime;
+use std::path::{Path, PathBuf};
+use crate::network::NetSession;
+use crate::player::{Player, PlayerPhysics};
+use crate::profile::ActiveProfile;
+use crate::projectile::AmmoKind;
+use crate::sim::{advance_simulation, PauseReason, SimClock, SimInput, Simulation, SIM_TICK_RATE};
+use crate::terrain::{world_seed, TerrainConfig};
+use crate::tuning::{tuning_hash, LiveTuning};
+use crate::{GameMode, GameState};
+
+// Replay file layout: an 8-byte magic, the format version that wrote the file and the oldest reader
+// version that can play it, then tagged sections of [tag: 4 bytes][length: u32][payload]. Readers
+// skip sections they don't know, so newer writers can add data without breaking older readers.
+const REPLAY_MAGIC: &[u8; 8] = b"TRWRPLAY";
+pub const REPLAY_FORMAT_VERSION: u16 = 1; // Version this build writes
+const REPLAY_READER_VERSION: u16 = 1; // Newest min_reader_version this build can play
+pub const REPLAY_DIR: &str = "replays";
+const REPLAY_EXTENSION: &str = "trwr";
+pub const CHECKSUM_INTERVAL: u32 = 60; // Ticks between state checksums
+
+// Section tags
+const TAG_META: &[u8; 4] = b"META";
+const TAG_INPUT: &[u8; 4] = b"INPT";
+const TAG_CHECKSUMS: &[u8; 4] = b"CHEK";
+const TAG_AMMO: &[u8; 4] = b"AMMO"; // One AmmoKind code per input sample, in the same order
+
+// Button bits in an input sample
+pub const BUTTON_JUMP: u8 = 1;
+pub const BUTTON_THROW: u8 = 1 << 1;
+pub const BUTTON_DASH: u8 = 1 << 2;
+pub const BUTTON_SHIELD: u8 = 1 << 3;
+// Press-only bits for consumables used from the hotbar
+pub const BUTTON_REPAIR_KIT: u8 = 1 << 4;
+pub const BUTTON_WIND_CHARM: u8 = 1 << 5;
+
+// Why a replay couldn't be read or played
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    // Not a replay file, or cut short
+    Malformed(&'static str),
+    // Written by a newer build that this one can't play
+    TooNew { min_reader_version: u16 },
+    // Recorded with different tuning, so the inputs wouldn't reproduce the run
+    TuningMismatch { recorded: u64, current: u64 },
+    // Inputs or checksums out of order or past the end of the run
+    Inconsistent(&'static str),
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ReplayError::Io(error) => write!(f, "{}", error),
+            ReplayError::Malformed(reason) => write!(f, "malformed replay: {}", reason),
+            ReplayError::TooNew { min_reader_version } => {
+                write!(f, "replay needs reader version {}, this build reads {}", min_reader_version, REPLAY_READER_VERSION)
+            }
+            ReplayError::TuningMismatch { recorded, current } => {
+                write!(f, "replay tuning {:016x} doesn't match current tuning {:016x}", recorded, current)
+            }
+            ReplayError::Inconsistent(reason) => write!(f, "inconsistent replay: {}", reason),
+        }
+    }
+}
+
+impl From<std::io::Error> for ReplayError {
+    fn from(error: std::io::Error) -> Self {
+        ReplayError::Io(error)
+    }
+}
+
+// What a replay is of, readable without decoding the inputs
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayMeta {
+    pub seed: u32,
+    // Fingerprint of the GameTuning the run was played with
+    pub tuning_hash: u64,
+    pub mode: GameMode,
+    pub profile: String,
+    // Simulation ticks per second and ticks recorded
+    pub tick_rate: u16,
+    pub tick_count: u32,
+    // Seconds since the Unix epoch when recording started
+    pub created: u64,
+}
+
+impl ReplayMeta {
+    pub fn duration(&self) -> f32 {
+        self.tick_count as f32 / self.tick_rate.max(1) as f32
+    }
+}
+
+// Player input from a tick on, until the next sample; only changes are stored
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputSample {
+    pub tick: u32,
+    // Movement as (right, forward), each quantized to -127..=127
+    pub move_x: i8,
+    pub move_z: i8,
+    // BUTTON_* bits held
+    pub buttons: u8,
+    // BUTTON_* bits pressed since the tick before, so a tap shorter than a tick plays back too; they only
+    // count on the sample's own tick
+    pub pressed: u8,
+    // Camera yaw, which turns movement into world space
+    pub yaw: f32,
+    // Aim point on the terrain, once the cursor has found it
+    pub aim: Option<[f32; 3]>,
+    // Kind of boulder thrown from this tick on
+    pub ammo: AmmoKind,
+}
+
+impl InputSample {
+    // Quantize a movement axis for storage
+    pub fn new(tick: u32, move_axis: Vec2, buttons: u8, pressed: u8, yaw: f32, aim: Option<Vec3>, ammo: AmmoKind) -> Self {
+        let quantize = |value: f32| (value.clamp(-1.0, 1.0) * 127.0).round() as i8;
+        Self {
+            tick,
+            move_x: quantize(move_axis.x),
+            move_z: quantize(move_axis.y),
+            buttons,
+            pressed,
+            yaw,
+            aim: aim.map(|aim| aim.to_array()),
+            ammo,
+        }
+    }
+
+    pub fn move_axis(&self) -> Vec2 {
+        Vec2::new(self.move_x as f32, self.move_z as f32) / 127.0
+    }
+
+    // Whether this sample would play back the same as another, ignoring when it starts
+    pub fn same_input(&self, other: &InputSample) -> bool {
+        self.move_x == other.move_x
+            && self.move_z == other.move_z
+            && self.buttons == other.buttons
+            && self.pressed == other.pressed
+            && self.yaw == other.yaw
+            && self.aim == other.aim
+            && self.ammo == other.ammo
+    }
+}
+
+// Simulation state fingerprint at a tick, for spotting playback drifting from the recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateChecksum {
+    pub tick: u32,
+    pub hash: u64,
+}
+
+// FNV-1a, stable across builds and platforms unlike the std hasher
+pub fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(0xCBF2_9CE4_8422_2325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3))
+}
+
+// Fingerprint of the player's state, quantized to millimeters so harmless float noise doesn't count
+pub fn state_checksum(position: Vec3, velocity: Vec3) -> u64 {
+    let values = position.to_array().into_iter().chain(velocity.to_array());
+    fnv1a(values.flat_map(|value| ((value * 1000.0).round() as i32).to_le_bytes()))
+}
+
+// A recorded run: what it was, the inputs that drove it and checksums to verify playback
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub meta: ReplayMeta,
+    pub inputs: Vec<InputSample>,
+    pub checksums: Vec<StateChecksum>,
+}
+
+impl Replay {
+    pub fn new(meta: ReplayMeta) -> Self {
+        Self { meta, inputs: Vec::new(), checksums: Vec::new() }
+    }
+
+    // Add the input for a tick, skipping it if nothing changed since the last sample; presses are always
+    // kept since they only count on their own tick
+    pub fn record_input(&mut self, sample: InputSample) {
+        if sample.pressed == 0 && self.inputs.last().is_some_and(|last| last.same_input(&sample)) {
+            return;
+        }
+        self.inputs.push(sample);
+    }
+
+    // Input in effect at a tick
+    pub fn input_at(&self, tick: u32) -> Option<&InputSample> {
+        let index = self.inputs.partition_point(|sample| sample.tick <= tick);
+        index.checked_sub(1).map(|index| &self.inputs[index])
+    }
+
+    // Check the replay can be played back faithfully with the current tuning
+    pub fn validate(&self, current_tuning_hash: u64) -> Result<(), ReplayError> {
+        if self.meta.tuning_hash != current_tuning_hash {
+            return Err(ReplayError::TuningMismatch { recorded: self.meta.tuning_hash, current: current_tuning_hash });
+        }
+        if self.meta.tick_rate == 0 {
+            return Err(ReplayError::Inconsistent("zero tick rate"));
+        }
+        if self.inputs.windows(2).any(|pair| pair[0].tick >= pair[1].tick) {
+            return Err(ReplayError::Inconsistent("inputs out of order"));
+        }
+        if self.checksums.windows(2).any(|pair| pair[0].tick >= pair[1].tick) {
+            return Err(ReplayError::Inconsistent("checksums out of order"));
+        }
+        let past_end = |tick: u32| tick > self.meta.tick_count;
+        if self.inputs.iter().any(|sample| past_end(sample.tick)) || self.checksums.iter().any(|checksum| past_end(checksum.tick)) {
+            return Err(ReplayError::Inconsistent("data past the end of the run"));
+        }
+        Ok(())
+    }
+
+    // Encode in the tagged section format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(REPLAY_MAGIC);
+        bytes.extend_from_slice(&REPLAY_FORMAT_VERSION.to_le_bytes());
+        // Nothing in version 1 needs a newer reader
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+
+        let meta = &self.meta;
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&meta.seed.to_le_bytes());
+        payload.extend_from_slice(&meta.tuning_hash.to_le_bytes());
+        payload.push(mode_id(meta.mode));
+        payload.extend_from_slice(&meta.tick_rate.to_le_bytes());
+        payload.extend_from_slice(&meta.tick_count.to_le_bytes());
+        payload.extend_from_slice(&meta.created.to_le_bytes());
+        let profile = meta.profile.as_bytes();
+        payload.extend_from_slice(&(profile.len() as u16).to_le_bytes());
+        payload.extend_from_slice(profile);
+        write_section(&mut bytes, TAG_META, &payload);
+
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&(self.inputs.len() as u32).to_le_bytes());
+        for sample in self.inputs.iter() {
+            payload.extend_from_slice(&sample.tick.to_le_bytes());
+            payload.push(sample.move_x as u8);
+            payload.push(sample.move_z as u8);
+            payload.push(sample.buttons);
+            payload.push(sample.pressed);
+            payload.extend_from_slice(&sample.yaw.to_le_bytes());
+            payload.push(sample.aim.is_some() as u8);
+            for value in sample.aim.unwrap_or_default() {
+                payload.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        write_section(&mut bytes, TAG_INPUT, &payload);
+
+        // In a section of its own so readers from before it still play the stone throws
+        let payload: Vec<u8> = self.inputs.iter().map(|sample| sample.ammo.code()).collect();
+        write_section(&mut bytes, TAG_AMMO, &payload);
+
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&(self.checksums.len() as u32).to_le_bytes());
+        for checksum in self.checksums.iter() {
+            payload.extend_from_slice(&checksum.tick.to_le_bytes());
+            payload.extend_from_slice(&checksum.hash.to_le_bytes());
+        }
+        write_section(&mut bytes, TAG_CHECKSUMS, &payload);
+        bytes
+    }
+
+    // Decode the tagged section format, skipping sections from newer versions
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let mut reader = ByteReader { bytes, position: 0 };
+        if reader.take(REPLAY_MAGIC.len())? != REPLAY_MAGIC {
+            return Err(ReplayError::Malformed("not a replay file"));
+        }
+        let _format_version = reader.u16()?;
+        let min_reader_version = reader.u16()?;
+        if min_reader_version > REPLAY_READER_VERSION {
+            return Err(ReplayError::TooNew { min_reader_version });
+        }
+
+        let mut meta = None;
+        let mut inputs = Vec::new();
+        let mut checksums = Vec::new();
+        let mut ammo = None;
+        while !reader.at_end() {
+            let tag = reader.take(4)?;
+            let length = reader.u32()? as usize;
+            let mut section = ByteReader { bytes: reader.take(length)?, position: 0 };
+            if tag == TAG_META {
+                let seed = section.u32()?;
+                let tuning_hash = section.u64()?;
+                let mode = mode_from_id(section.u8()?).ok_or(ReplayError::Malformed("unknown mode"))?;
+                let tick_rate = section.u16()?;
+                let tick_count = section.u32()?;
+                let created = section.u64()?;
+                let profile_length = section.u16()? as usize;
+                let profile = String::from_utf8(section.take(profile_length)?.to_vec())
+                    .map_err(|_| ReplayError::Malformed("profile name isn't UTF-8"))?;
+                meta = Some(ReplayMeta { seed, tuning_hash, mode, profile, tick_rate, tick_count, created });
+            } else if tag == TAG_INPUT {
+                let count = section.u32()?;
+                for _ in 0..count {
+                    let tick = section.u32()?;
+                    let move_x = section.u8()? as i8;
+                    let move_z = section.u8()? as i8;
+                    let buttons = section.u8()?;
+                    let pressed = section.u8()?;
+                    let yaw = section.f32()?;
+                    let has_aim = section.u8()? != 0;
+                    let aim = [section.f32()?, section.f32()?, section.f32()?];
+                    let aim = has_aim.then_some(aim);
+                    inputs.push(InputSample { tick, move_x, move_z, buttons, pressed, yaw, aim, ammo: AmmoKind::Stone });
+                }
+            } else if tag == TAG_AMMO {
+                ammo = Some(section.take(length)?.to_vec());
+            } else if tag == TAG_CHECKSUMS {
+                let count = section.u32()?;
+                for _ in 0..count {
+                    checksums.push(StateChecksum { tick: section.u32()?, hash: section.u64()? });
+                }
+            }
+            // Fields appended to a known section by a newer version are left unread
+        }
+        let meta = meta.ok_or(ReplayError::Malformed("missing metadata"))?;
+        // Without the section, from before there were bought boulders, every throw is stone
+        if let Some(ammo) = ammo {
+            if ammo.len() != inputs.len() {
+                return Err(ReplayError::Malformed("ammo doesn't match the inputs"));
+            }
+            for (sample, code) in inputs.iter_mut().zip(ammo) {
+                sample.ammo = AmmoKind::from_code(code).ok_or(ReplayError::Malformed("unknown ammo kind"))?;
+            }
+        }
+        Ok(Self { meta, inputs, checksums })
+    }
+
+    // Write to a file, creating its directory if needed
+    pub fn write(&self, path: &Path) -> Result<(), ReplayError> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    // Read a replay file
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+}
+
+fn write_section(bytes: &mut Vec<u8>, tag: &[u8; 4], payload: &[u8]) {
+    bytes.extend_from_slice(tag);
+    bytes.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(payload);
+}
+
+// Stable ids for modes in replay files; new modes get new ids, never reuse old ones
+fn mode_id(mode: GameMode) -> u8 {
+    match mode {
+        GameMode::FreePlay => 0,
+        GameMode::TargetPractice => 1,
+        GameMode::TimeTrial => 2,
+        GameMode::Golf => 3,
+        GameMode::KingOfTheHill => 4,
+        GameMode::DownhillRace => 5,
+        GameMode::BossFight => 6,
+        GameMode::Scripted => 7,
+    }
+}
+
+fn mode_from_id(id: u8) -> Option<GameMode> {
+    GameMode::ALL.into_iter().find(|mode| mode_id(*mode) == id)
+}
+
+// Little-endian reader over a byte slice
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn at_end(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], ReplayError> {
+        let end = self.position.checked_add(count).filter(|end| *end <= self.bytes.len());
+        let end = end.ok_or(ReplayError::Malformed("unexpected end of data"))?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8, ReplayError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ReplayError> {
+        self.array().map(u16::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, ReplayError> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, ReplayError> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    fn f32(&mut self) -> Result<f32, ReplayError> {
+        self.array().map(f32::from_le_bytes)
+    }
+}
+
+// The run being recorded; LAN sessions, mod scripts and playbacks aren't recorded
+#[derive(Resource, Default)]
+pub struct ReplayRecorder {
+    replay: Option<Replay>,
+    // File the last finished run was written to
+    pub saved: Option<PathBuf>,
+}
+
+// A replay driving the simulation in place of the player, loaded from --replay
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    replay: Replay,
+    // Whether playback has drifted from the recording, so it is only reported once
+    drifted: bool,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self { replay, drifted: false }
+    }
+
+    pub fn meta(&self) -> &ReplayMeta {
+        &self.replay.meta
+    }
+}
+
+// Player state fingerprint as recorded with the replay
+fn player_checksum(player_query: &Query<(&Transform, &PlayerPhysics), With<Player>>) -> Option<u64> {
+    let (transform, physics) = player_query.get_single().ok()?;
+    Some(state_checksum(transform.translation, physics.velocity))
+}
+
+// Start recording a run from its first tick
+pub fn start_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    mode: Res<GameMode>,
+    profile: Res<ActiveProfile>,
+    tuning: Res<LiveTuning>,
+    session: Option<Res<NetSession>>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    recorder.replay = None;
+    recorder.saved = None;
+    // Scripted runs depend on which script ran, which the file doesn't say
+    if session.is_some() || playback.is_some() || *mode == GameMode::Scripted {
+        return;
+    }
+    let created = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    recorder.replay = Some(Replay::new(ReplayMeta {
+        seed: world_seed(),
+        tuning_hash: tuning_hash(&tuning.0),
+        mode: *mode,
+        profile: profile.name.clone(),
+        tick_rate: SIM_TICK_RATE as u16,
+        tick_count: 0,
+        created,
+    }));
+}
+
+// Note the input each tick starts with
+pub fn record_tick_input(mut recorder: ResMut<ReplayRecorder>, simulation: Res<Simulation>, input: Res<SimInput>) {
+    if let Some(replay) = recorder.replay.as_mut() {
+        replay.record_input(InputSample::new(simulation.tick, input.move_axis, input.held, input.pressed, input.yaw, input.aim, input.ammo));
+    }
+}
+
+// Fingerprint the player every CHECKSUM_INTERVAL ticks, at the end of the tick
+pub fn record_checksum(
+    mut recorder: ResMut<ReplayRecorder>,
+    simulation: Res<Simulation>,
+    player_query: Query<(&Transform, &PlayerPhysics), With<Player>>,
+) {
+    let Some(replay) = recorder.replay.as_mut() else {
+        return;
+    };
+    if simulation.tick % CHECKSUM_INTERVAL != 0 {
+        return;
+    }
+    if let Some(hash) = player_checksum(&player_query) {
+        replay.checksums.push(StateChecksum { tick: simulation.tick, hash });
+    }
+}
+
+// Rewinding moves the ball outside the simulation's input, so the recording couldn't reproduce the run
+pub fn drop_rewound_recording(clock: Res<SimClock>, mut recorder: ResMut<ReplayRecorder>) {
+    if clock.paused_by(PauseReason::Rewind) && recorder.replay.take().is_some() {
+        println!("Rewound runs aren't recorded; this run won't have a replay");
+    }
+}
+
+// Write the run that just ended to the replays folder
+pub fn save_replay(mut recorder: ResMut<ReplayRecorder>, simulation: Res<Simulation>) {
+    let Some(mut replay) = recorder.replay.take() else {
+        return;
+    };
+    replay.meta.tick_count = simulation.tick;
+    let seed_code = TerrainConfig { seed: replay.meta.seed }.seed_code();
+    let path = PathBuf::from(format!("{}/{}-{}.{}", REPLAY_DIR, seed_code, replay.meta.created, REPLAY_EXTENSION));
+    match replay.write(&path) {
+        Ok(()) => recorder.saved = Some(path),
+        Err(error) => println!("Failed to write replay {}: {}", path.display(), error),
+    }
+}
+
+// Check a replay still plays back with the current tuning before its run starts, dropping it if not
+pub fn start_playback(mut commands: Commands, playback: Option<Res<ReplayPlayback>>, tuning: Res<LiveTuning>) {
+    let Some(playback) = playback else {
+        return;
+    };
+    let meta = playback.meta();
+    match playback.replay.validate(tuning_hash(&tuning.0)) {
+        Ok(()) => println!("Playing back a {:?} run by {}, {:.0} s long", meta.mode, meta.profile, meta.duration()),
+        Err(error) => {
+            println!("Can't play back the replay: {}", error);
+            commands.remove_resource::<ReplayPlayback>();
+        }
+    }
+}
+
+// Feed the recorded input to the simulation in place of the player's
+pub fn play_back_input(playback: Res<ReplayPlayback>, simulation: Res<Simulation>, mut input: ResMut<SimInput>) {
+    let Some(sample) = playback.replay.input_at(simulation.tick) else {
+        return;
+    };
+    input.move_axis = sample.move_axis();
+    input.yaw = sample.yaw;
+    input.aim = sample.aim.map(Vec3::from_array);
+    input.held = sample.buttons;
+    input.pressed = if sample.tick == simulation.tick { sample.pressed } else { 0 };
+    input.ammo = sample.ammo;
+}
+
+// Compare the player against the recording's checksums, saying once if the run has drifted from it
+pub fn verify_playback(
+    mut playback: ResMut<ReplayPlayback>,
+    simulation: Res<Simulation>,
+    player_query: Query<(&Transform, &PlayerPhysics), With<Player>>,
+) {
+    if playback.drifted {
+        return;
+    }
+    let checksums = &playback.replay.checksums;
+    let Ok(index) = checksums.binary_search_by_key(&simulation.tick, |checksum| checksum.tick) else {
+        return;
+    };
+    if player_checksum(&player_query).is_some_and(|hash| hash != checksums[index].hash) {
+        println!("Replay drifted from the recording at tick {}", simulation.tick);
+        playback.drifted = true;
+    }
+}
+
+// Plugin for recording runs to replay files and playing them back
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<ReplayRecorder>()
+            .add_systems(OnExit(GameState::Loading), (start_recording, start_playback))
+            .add_systems(OnExit(GameState::GameOver), start_recording)
+            .add_systems(OnEnter(GameState::GameOver), save_replay)
+            .add_systems(Update, drop_rewound_recording.run_if(in_state(GameState::Playing)))
+            .add_systems(FixedFirst, (play_back_input.run_if(resource_exists::<ReplayPlayback>), record_tick_input)
+                .chain()
+                .run_if(in_state(GameState::Playing)))
+            .add_systems(FixedLast, (record_checksum, verify_playback.run_if(resource_exists::<ReplayPlayback>))
+                .before(advance_simulation)
+                .run_if(in_state(GameState::Playing)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_replay() -> Replay {
+        let mut replay = Replay::new(ReplayMeta {
+            seed: 0x1234_ABCD,
+            tuning_hash: 42,
+            mode: GameMode::TargetPractice,
+            profile: "Tester".to_string(),
+            tick_rate: 60,
+            tick_count: 300,
+            created: 1_700_000_000,
+        });
+        let aim = Some(Vec3::new(3.0, 1.5, -7.0));
+        replay.record_input(InputSample::new(0, Vec2::ZERO, 0, 0, 0.0, None, AmmoKind::Stone));
+        replay.record_input(InputSample::new(1, Vec2::ZERO, 0, 0, 0.0, None, AmmoKind::Stone));
+        replay.record_input(InputSample::new(10, Vec2::new(0.5, -1.0), BUTTON_THROW, BUTTON_THROW, 1.25, aim, AmmoKind::Heavy));
+        replay.record_input(InputSample::new(11, Vec2::new(0.5, -1.0), BUTTON_THROW, 0, 1.25, aim, AmmoKind::Heavy));
+        replay.checksums.push(StateChecksum { tick: 60, hash: state_checksum(Vec3::ONE, Vec3::X) });
+        replay
+    }
+
+    #[test]
+    fn replay_survives_encoding() {
+        let replay = sample_replay();
+        let decoded = Replay::from_bytes(&replay.to_bytes()).expect("replay didn't decode");
+        assert_eq!(decoded.meta, replay.meta);
+        assert_eq!(decoded.inputs, replay.inputs);
+        assert_eq!(decoded.checksums, replay.checksums);
+        assert!(decoded.validate(42).is_ok());
+    }
+
+    #[test]
+    fn unchanged_input_is_stored_once() {
+        let replay = sample_replay();
+        assert_eq!(replay.inputs.len(), 3);
+        assert_eq!(replay.input_at(5).map(|sample| sample.tick), Some(0));
+        assert_eq!(replay.input_at(10).map(|sample| sample.pressed), Some(BUTTON_THROW));
+        assert_eq!(replay.input_at(200).map(|sample| sample.tick), Some(11));
+    }
+
+    #[test]
+    fn unknown_sections_are_skipped() {
+        let mut bytes = sample_replay().to_bytes();
+        write_section(&mut bytes, b"XTRA", &[1, 2, 3]);
+        assert!(Replay::from_bytes(&bytes).is_ok());
+    }
+
+    #[test]
+    fn replays_without_ammo_throw_stone() {
+        let mut bytes = sample_replay().to_bytes();
+        // Rename the ammo section to one this build doesn't know, as if it was never written
+        let at = bytes.windows(4).position(|window| window == TAG_AMMO).expect("no ammo section");
+        bytes[at..at + 4].copy_from_slice(b"XTRA");
+        let decoded = Replay::from_bytes(&bytes).expect("replay didn't decode");
+        assert!(decoded.inputs.iter().all(|sample| sample.ammo == AmmoKind::Stone));
+    }
+
+    #[test]
+    fn newer_readers_and_other_tuning_are_rejected() {
+        let mut bytes = sample_replay().to_bytes();
+        bytes[10..12].copy_from_slice(&(REPLAY_READER_VERSION + 1).to_le_bytes());
+        assert!(matches!(Replay::from_bytes(&bytes), Err(ReplayError::TooNew { .. })));
+        assert!(matches!(sample_replay().validate(7), Err(ReplayError::TuningMismatch { .. })));
+    }
+}
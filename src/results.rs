@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+use crate::menu::{spawn_menu_button, MenuAction};
+use crate::player::Player;
+use crate::projectile::ProjectileHitEvent;
+use crate::score::Score;
+use crate::leaderboard::{has_board, Leaderboards};
+use crate::terrain::world_seed;
+use crate::{GameMode, GameState};
+
+// Statistics gathered over a run and shown on the results screen
+#[derive(Resource, Default)]
+pub struct RunStats {
+    // Seconds spent playing, excluding pauses and photo mode
+    pub elapsed: f32,
+    // Horizontal distance the player has rolled
+    pub distance_rolled: f32,
+    // Boulders thrown, updated by the projectile systems
+    pub boulders_fired: u32,
+    // Target hits
+    pub hits: u32,
+    // Finishing time of a completed time trial
+    pub race_time: Option<f32>,
+    // Finishing place in a downhill race
+    pub race_position: Option<usize>,
+    // Whether slow motion, freezing or rewinding was used; such runs don't go on the leaderboards or
+    // count as a best time
+    pub time_assisted: bool,
+    // Player position last frame, for measuring distance
+    last_position: Option<Vec3>,
+}
+
+impl RunStats {
+    // Stop measuring from the last position, e.g. after a saved run moves the player
+    pub fn forget_position(&mut self) {
+        self.last_position = None;
+    }
+
+    // Fraction of boulders that hit a target
+    pub fn accuracy(&self) -> f32 {
+        if self.boulders_fired == 0 {
+            0.0
+        } else {
+            (self.hits as f32 / self.boulders_fired as f32).min(1.0)
+        }
+    }
+}
+
+// 1st, 2nd, 3rd, 4th... for race places
+fn ordinal(place: usize) -> String {
+    let suffix = match (place % 10, place % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", place, suffix)
+}
+
+// Marker for the results screen UI root
+#[derive(Component)]
+pub struct ResultsRoot;
+
+// Accumulate play time and distance rolled
+pub fn track_run_stats(
+    mut stats: ResMut<RunStats>,
+    player_query: Query<&Transform, With<Player>>,
+    time: Res<Time>,
+) {
+    stats.elapsed += time.delta_secs();
+    if let Ok(transform) = player_query.get_single() {
+        let position = transform.translation;
+        if let Some(last) = stats.last_position {
+            stats.distance_rolled += Vec2::new(position.x - last.x, position.z - last.z).length();
+        }
+        stats.last_position = Some(position);
+    }
+}
+
+// Count target hits for accuracy
+pub fn count_run_hits(mut stats: ResMut<RunStats>, mut hit_events: EventReader<ProjectileHitEvent>) {
+    stats.hits += hit_events.read().count() as u32;
+}
+
+// Start the next run's statistics from zero
+pub fn reset_run_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+// Build the results screen with the run statistics
+pub fn spawn_results_screen(
+    mut commands: Commands,
+    stats: Res<RunStats>,
+    score: Res<Score>,
+    mode: Res<GameMode>,
+    leaderboards: Res<Leaderboards>,
+) {
+    let minutes = (stats.elapsed / 60.0).floor();
+    let seconds = stats.elapsed - minutes * 60.0;
+    let mut lines = vec![
+        format!("Time: {}:{:04.1}", minutes as u32, seconds),
+        format!("Distance rolled: {:.0} m", stats.distance_rolled),
+        format!("Boulders fired: {}", stats.boulders_fired),
+        format!("Accuracy: {:.0}% ({} hits)", stats.accuracy() * 100.0, stats.hits),
+        format!("Best combo: {}", score.best_combo),
+        format!("Score: {}", score.points),
+    ];
+    if let Some(race_time) = stats.race_time {
+        lines.insert(0, format!("Race time: {:.2} s", race_time));
+    }
+    if let Some(position) = stats.race_position {
+        lines.insert(0, format!("Finished: {}", ordinal(position)));
+    }
+    if stats.time_assisted && has_board(*mode) {
+        lines.push("Time controls used: not ranked".to_string());
+    }
+    // This world's board for the mode, with the run just placed marked
+    let seed = world_seed();
+    let placed = leaderboards
+        .last_placed
+        .filter(|(placed_mode, placed_seed, _)| *placed_mode == *mode && *placed_seed == seed)
+        .map(|(_, _, rank)| rank);
+    let board_lines = has_board(*mode).then(|| leaderboards.lines(*mode, seed, placed));
+
+    commands
+        .spawn((
+            ResultsRoot,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            GlobalZIndex(10),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Run Over"),
+                TextFont {
+                    font_size: 48.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Node {
+                    margin: UiRect::bottom(Val::Px(20.0)),
+                    ..default()
+                },
+            ));
+            for line in lines {
+                parent.spawn((
+                    Text::new(line),
+                    TextFont {
+                        font_size: 24.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                    Node {
+                        margin: UiRect::vertical(Val::Px(3.0)),
+                        ..default()
+                    },
+                ));
+            }
+            if let Some(board_lines) = board_lines {
+                parent.spawn((
+                    Text::new("Leaderboard"),
+                    TextFont {
+                        font_size: 28.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                    Node {
+                        margin: UiRect::top(Val::Px(16.0)),
+                        ..default()
+                    },
+                ));
+                for line in board_lines {
+                    parent.spawn((
+                        Text::new(line),
+                        TextFont {
+                            font_size: 18.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                    ));
+                }
+            }
+            parent.spawn(Node {
+                height: Val::Px(20.0),
+                ..default()
+            });
+            spawn_menu_button(parent, "Retry", MenuAction::Retry);
+            spawn_menu_button(parent, "Main Menu", MenuAction::MainMenu);
+        });
+}
+
+// Remove the results screen
+pub fn despawn_results_screen(mut commands: Commands, query: Query<Entity, With<ResultsRoot>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Plugin for run statistics and the results screen
+pub struct ResultsPlugin;
+
+impl Plugin for ResultsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<RunStats>()
+            .add_systems(Update, (track_run_stats, count_run_hits).run_if(in_state(GameState::Playing)))
+            .add_systems(OnEnter(GameState::GameOver), spawn_results_screen)
+            .add_systems(OnExit(GameState::GameOver), (despawn_results_screen, reset_run_stats));
+    }
+}
@@ -0,0 +1,273 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use crate::budget::WorkBudgets;
+use crate::inventory::Consumable;
+use crate::profile::ActiveProfile;
+use crate::projectile::AmmoKind;
+use crate::GameState;
+
+// Save file name inside the profile folder, and format version
+const SAVE_FILE: &str = "save.ron";
+const LEGACY_SAVE_PATH: &str = "save/save.ron"; // Used before profiles; picked up by the default profile
+const SAVE_VERSION: u32 = 1;
+
+// What the player has bought at shops, keyed by the ids of the ammo kinds, consumables, skins and upgrades
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Inventory {
+    // Bought boulders left of each kind
+    pub ammo: BTreeMap<String, u32>,
+    // Consumables carried on the hotbar
+    pub consumables: BTreeMap<String, u32>,
+    pub skins: Vec<String>,
+    // Skin the ball wears, or none for the plain ball
+    pub skin: Option<String>,
+    // Levels bought of each upgrade, granted at the start of every run
+    pub upgrades: BTreeMap<String, u32>,
+}
+
+// Progress that persists between sessions
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveData {
+    // Format version, bumped when fields change meaning
+    pub version: u32,
+    // Ids of tutorial hints the player has already seen
+    pub seen_hints: Vec<String>,
+    // Ids of the achievements unlocked
+    pub achievements: Vec<String>,
+    // Coins collected across all runs, spent at shops
+    pub coins: u32,
+    pub inventory: Inventory,
+    // Best time-trial time in seconds for each world seed
+    pub best_race_times: BTreeMap<u32, f32>,
+    // Chunks the player has been near in each world seed, which the map shows uncovered
+    pub explored_chunks: BTreeMap<u32, BTreeSet<(i32, i32)>>,
+    // Marker the player placed on the map in each world seed, as world X and Z
+    pub map_markers: BTreeMap<u32, (f32, f32)>,
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        Self {
+            version: SAVE_VERSION,
+            seen_hints: Vec::new(),
+            achievements: Vec::new(),
+            coins: 0,
+            inventory: Inventory::default(),
+            best_race_times: BTreeMap::new(),
+            explored_chunks: BTreeMap::new(),
+            map_markers: BTreeMap::new(),
+        }
+    }
+}
+
+impl SaveData {
+    // Read a profile's save file, starting fresh if it is missing or invalid
+    pub fn load(profile: &ActiveProfile) -> Self {
+        let path = profile.file(SAVE_FILE);
+        let contents = std::fs::read_to_string(&path).or_else(|error| {
+            if profile.is_default() { std::fs::read_to_string(LEGACY_SAVE_PATH) } else { Err(error) }
+        });
+        match contents {
+            Ok(contents) => match ron::from_str(&contents) {
+                Ok(data) => data,
+                Err(error) => {
+                    println!("Invalid save file {}: {}", path.display(), error);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    // Write a profile's save file, creating its directory if needed
+    pub fn write(&self, profile: &ActiveProfile) {
+        let path = profile.file(SAVE_FILE);
+        let result = std::fs::create_dir_all(profile.dir()).and_then(|_| {
+            let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+                .map_err(std::io::Error::other)?;
+            std::fs::write(&path, contents)
+        });
+        if let Err(error) = result {
+            println!("Failed to write save file {}: {}", path.display(), error);
+        }
+    }
+
+    pub fn has_seen_hint(&self, id: &str) -> bool {
+        self.seen_hints.iter().any(|seen| seen == id)
+    }
+
+    pub fn mark_hint_seen(&mut self, id: &str) {
+        if !self.has_seen_hint(id) {
+            self.seen_hints.push(id.to_string());
+        }
+    }
+
+    pub fn has_achievement(&self, id: &str) -> bool {
+        self.achievements.iter().any(|unlocked| unlocked == id)
+    }
+
+    pub fn unlock_achievement(&mut self, id: &str) {
+        if !self.has_achievement(id) {
+            self.achievements.push(id.to_string());
+        }
+    }
+
+    pub fn ammo_count(&self, kind: AmmoKind) -> u32 {
+        self.inventory.ammo.get(kind.id()).copied().unwrap_or(0)
+    }
+
+    pub fn add_ammo(&mut self, kind: AmmoKind, count: u32) {
+        *self.inventory.ammo.entry(kind.id().to_string()).or_default() += count;
+    }
+
+    // Bought boulders of a kind left after a run's throws
+    pub fn set_ammo(&mut self, kind: AmmoKind, count: u32) {
+        self.inventory.ammo.insert(kind.id().to_string(), count);
+    }
+
+    pub fn consumable_count(&self, kind: Consumable) -> u32 {
+        self.inventory.consumables.get(kind.id()).copied().unwrap_or(0)
+    }
+
+    pub fn add_consumable(&mut self, kind: Consumable, count: u32) {
+        *self.inventory.consumables.entry(kind.id().to_string()).or_default() += count;
+    }
+
+    // Consumables of a kind left after a run's uses
+    pub fn set_consumable(&mut self, kind: Consumable, count: u32) {
+        self.inventory.consumables.insert(kind.id().to_string(), count);
+    }
+
+    pub fn owns_skin(&self, id: &str) -> bool {
+        self.inventory.skins.iter().any(|owned| owned == id)
+    }
+
+    pub fn add_skin(&mut self, id: &str) {
+        if !self.owns_skin(id) {
+            self.inventory.skins.push(id.to_string());
+        }
+    }
+
+    pub fn upgrade_level(&self, id: &str) -> u32 {
+        self.inventory.upgrades.get(id).copied().unwrap_or(0)
+    }
+
+    pub fn raise_upgrade(&mut self, id: &str) {
+        *self.inventory.upgrades.entry(id.to_string()).or_default() += 1;
+    }
+
+    // Spend coins if there are enough; returns whether they were spent
+    pub fn spend_coins(&mut self, price: u32) -> bool {
+        if self.coins < price {
+            return false;
+        }
+        self.coins -= price;
+        true
+    }
+
+    pub fn best_race_time(&self, seed: u32) -> Option<f32> {
+        self.best_race_times.get(&seed).copied()
+    }
+
+    // Keep a race time if it beats the best for its seed; returns whether it did
+    pub fn record_race_time(&mut self, seed: u32, time: f32) -> bool {
+        let new_best = self.best_race_time(seed).is_none_or(|best| time < best);
+        if new_best {
+            self.best_race_times.insert(seed, time);
+        }
+        new_best
+    }
+
+    pub fn has_explored(&self, seed: u32, chunk: (i32, i32)) -> bool {
+        self.explored_chunks.get(&seed).is_some_and(|chunks| chunks.contains(&chunk))
+    }
+
+    pub fn mark_explored(&mut self, seed: u32, chunk: (i32, i32)) {
+        self.explored_chunks.entry(seed).or_default().insert(chunk);
+    }
+
+    pub fn map_marker(&self, seed: u32) -> Option<Vec2> {
+        self.map_markers.get(&seed).map(|&(x, z)| Vec2::new(x, z))
+    }
+
+    pub fn set_map_marker(&mut self, seed: u32, marker: Option<Vec2>) {
+        match marker {
+            Some(marker) => self.map_markers.insert(seed, (marker.x, marker.y)),
+            None => self.map_markers.remove(&seed),
+        };
+    }
+}
+
+// Whether the save has changes that aren't on disk yet, and when it was last written
+#[derive(Resource, Default)]
+pub struct SaveFlush {
+    dirty: bool,
+    last_write: f32,
+    // Profile the save in memory was loaded from; writes go here even after the active profile changes
+    profile: ActiveProfile,
+}
+
+impl SaveFlush {
+    // Write pending changes to the profile they belong to
+    pub fn write_pending(&mut self, save: &SaveData) {
+        if self.dirty {
+            save.write(&self.profile);
+            self.dirty = false;
+        }
+    }
+
+    // Hand the save over to a newly loaded profile
+    pub fn switch_profile(&mut self, profile: &ActiveProfile) {
+        self.profile = profile.clone();
+        self.dirty = false;
+    }
+}
+
+// Note that the save changed; the write waits for flush_save so a burst of changes, like a run of
+// coins, costs one write instead of one per frame
+pub fn mark_save_dirty(mut flush: ResMut<SaveFlush>) {
+    flush.dirty = true;
+}
+
+// Write pending changes once the save interval has passed since the last write
+pub fn flush_save(save: Res<SaveData>, budgets: Res<WorkBudgets>, mut flush: ResMut<SaveFlush>, time: Res<Time<Real>>) {
+    let now = time.elapsed_secs();
+    if flush.dirty && now - flush.last_write >= budgets.save_interval_secs {
+        flush.write_pending(&save);
+        flush.last_write = now;
+    }
+}
+
+// Write pending changes right away, at moments nothing should be lost: leaving a run or quitting
+pub fn flush_save_now(save: Res<SaveData>, mut flush: ResMut<SaveFlush>) {
+    flush.write_pending(&save);
+}
+
+// Whether the app is closing this frame
+fn exiting(exit_events: EventReader<AppExit>) -> bool {
+    !exit_events.is_empty()
+}
+
+// Plugin for persistent save data
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        // Main picks the profile before the plugins build
+        let profile = app.world().get_resource::<ActiveProfile>().cloned().unwrap_or_default();
+        app
+            .insert_resource(SaveData::load(&profile))
+            .insert_resource(SaveFlush { profile: profile.clone(), ..default() })
+            .add_systems(Update, (
+                mark_save_dirty.run_if(resource_changed::<SaveData>.and(not(resource_added::<SaveData>))),
+                flush_save,
+            ).chain())
+            .add_systems(OnEnter(GameState::GameOver), flush_save_now)
+            .add_systems(OnEnter(GameState::Paused), flush_save_now)
+            .add_systems(OnEnter(GameState::MainMenu), flush_save_now)
+            .add_systems(Last, flush_save_now.run_if(exiting));
+    }
+}
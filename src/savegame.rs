@@ -0,0 +1,312 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use crate::player::{Health, Player, PlayerPhysics};
+use crate::projectile::Ammo;
+use crate::score::Score;
+use crate::progression::{Experience, Upgrades};
+use crate::regions::Regions;
+use crate::results::RunStats;
+use crate::hazards::HazardState;
+use crate::terrain::{add_deformation, clear_deformations, deformations, world_seed, ChunkManager, Deformation, TerrainConfig};
+use crate::profile::ActiveProfile;
+use crate::{GameMode, GameState};
+
+// Saved game format version; slot files live in the profile folder
+const SNAPSHOT_VERSION: u32 = 2;
+pub const QUICK_SLOT: usize = 0; // Slot used by the quick-save and quick-load keys
+pub const SLOT_COUNT: usize = 3; // Slots offered in the menus, numbered from 1
+
+// The player's ball as it was saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedPlayer {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub velocity: [f32; 3],
+    pub angular_velocity: [f32; 3],
+    pub health: f32,
+}
+
+// A crater or dent in the terrain
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SavedDeformation {
+    pub center: [f32; 2],
+    pub radius: f32,
+    pub depth: f32,
+}
+
+// Everything needed to pick a run back up where it was saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    // Format version, bumped when fields change meaning
+    pub version: u32,
+    pub mode: GameMode,
+    // World seed the run was played in
+    pub seed: u32,
+    pub player: SavedPlayer,
+    pub ammo: u32,
+    pub points: u32,
+    pub best_combo: u32,
+    pub xp: u32,
+    pub level: u32,
+    pub pending_choices: u32,
+    pub upgrades: [u32; 3],
+    pub discovered_regions: Vec<(i32, i32)>,
+    pub deformations: Vec<SavedDeformation>,
+    pub elapsed: f32,
+    pub distance_rolled: f32,
+    pub boulders_fired: u32,
+    pub hits: u32,
+}
+
+impl GameSnapshot {
+    // File a profile's slot is kept in
+    fn path(profile: &ActiveProfile, slot: usize) -> PathBuf {
+        if slot == QUICK_SLOT {
+            profile.file("quicksave.ron")
+        } else {
+            profile.file(&format!("slot{}.ron", slot))
+        }
+    }
+
+    // Read a slot, or None if it is empty, unreadable or from another version
+    pub fn load(profile: &ActiveProfile, slot: usize) -> Option<Self> {
+        let path = Self::path(profile, slot);
+        let contents = std::fs::read_to_string(&path).ok()?;
+        match ron::from_str::<Self>(&contents) {
+            Ok(snapshot) if snapshot.version == SNAPSHOT_VERSION => Some(snapshot),
+            Ok(snapshot) => {
+                println!("Save {} is version {}, expected {}", path.display(), snapshot.version, SNAPSHOT_VERSION);
+                None
+            }
+            Err(error) => {
+                println!("Invalid save {}: {}", path.display(), error);
+                None
+            }
+        }
+    }
+
+    // Write to a slot, creating the profile folder if needed
+    pub fn write(&self, profile: &ActiveProfile, slot: usize) {
+        let path = Self::path(profile, slot);
+        let result = std::fs::create_dir_all(profile.dir()).and_then(|_| {
+            let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+                .map_err(std::io::Error::other)?;
+            std::fs::write(&path, contents)
+        });
+        match result {
+            Ok(()) => println!("Saved game to {}", path.display()),
+            Err(error) => println!("Failed to write save {}: {}", path.display(), error),
+        }
+    }
+
+    // Short description for menu buttons
+    pub fn summary(&self) -> String {
+        format!("{} pts", self.points)
+    }
+}
+
+// What a slot holds, for its menu button
+pub fn slot_summary(profile: &ActiveProfile, slot: usize) -> String {
+    match GameSnapshot::load(profile, slot) {
+        Some(snapshot) => snapshot.summary(),
+        None => "Empty".to_string(),
+    }
+}
+
+// Ask for the current run to be saved to a slot or a slot to be loaded
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotRequest {
+    Save(usize),
+    Load(usize),
+}
+
+// A loaded snapshot waiting for the run it belongs to to be playing
+#[derive(Resource, Default)]
+pub struct PendingLoad(pub Option<GameSnapshot>);
+
+// F5 quick-saves and F9 quick-loads during play
+pub fn quick_save_keys(keys: Res<ButtonInput<KeyCode>>, mut requests: EventWriter<SlotRequest>) {
+    if keys.just_pressed(KeyCode::F5) {
+        requests.send(SlotRequest::Save(QUICK_SLOT));
+    }
+    if keys.just_pressed(KeyCode::F9) {
+        requests.send(SlotRequest::Load(QUICK_SLOT));
+    }
+}
+
+// Capture the run into a snapshot and write it to the requested slot
+pub fn save_to_slot(
+    mut requests: EventReader<SlotRequest>,
+    profile: Res<ActiveProfile>,
+    mode: Res<GameMode>,
+    player_query: Query<(&Transform, &PlayerPhysics, &Health), With<Player>>,
+    ammo: Res<Ammo>,
+    score: Res<Score>,
+    experience: Res<Experience>,
+    upgrades: Res<Upgrades>,
+    regions: Res<Regions>,
+    run_stats: Res<RunStats>,
+) {
+    for request in requests.read() {
+        let SlotRequest::Save(slot) = *request else {
+            continue;
+        };
+        if !mode.can_save() {
+            println!("{:?} runs can't be saved", *mode);
+            continue;
+        }
+        let Ok((transform, physics, health)) = player_query.get_single() else {
+            continue;
+        };
+        let snapshot = GameSnapshot {
+            version: SNAPSHOT_VERSION,
+            mode: *mode,
+            seed: world_seed(),
+            player: SavedPlayer {
+                translation: transform.translation.to_array(),
+                rotation: transform.rotation.to_array(),
+                velocity: physics.velocity.to_array(),
+                angular_velocity: physics.angular_velocity.to_array(),
+                health: health.current,
+            },
+            ammo: ammo.current,
+            points: score.points,
+            best_combo: score.best_combo,
+            xp: experience.xp,
+            level: experience.level,
+            pending_choices: experience.pending_choices,
+            upgrades: [upgrades.blast_radius, upgrades.reload, upgrades.traction],
+            discovered_regions: regions.discovered.iter().copied().collect(),
+            deformations: deformations()
+                .iter()
+                .map(|deformation| SavedDeformation {
+                    center: deformation.center.to_array(),
+                    radius: deformation.radius,
+                    depth: deformation.depth,
+                })
+                .collect(),
+            elapsed: run_stats.elapsed,
+            distance_rolled: run_stats.distance_rolled,
+            boulders_fired: run_stats.boulders_fired,
+            hits: run_stats.hits,
+        };
+        snapshot.write(&profile, slot);
+    }
+}
+
+// Read the requested slot; from the menu this starts a run in the saved mode and world, in play it
+// restores the snapshot in place as long as the mode and world match
+pub fn load_from_slot(
+    mut requests: EventReader<SlotRequest>,
+    profile: Res<ActiveProfile>,
+    mut pending: ResMut<PendingLoad>,
+    mut mode: ResMut<GameMode>,
+    mut terrain_config: ResMut<TerrainConfig>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut player_query: Query<&mut Transform, With<Player>>,
+) {
+    for request in requests.read() {
+        let SlotRequest::Load(slot) = *request else {
+            continue;
+        };
+        let Some(snapshot) = GameSnapshot::load(&profile, slot) else {
+            println!("Nothing to load in slot {}", slot);
+            continue;
+        };
+        // Written before saving was limited to the modes a snapshot fully covers
+        if !snapshot.mode.can_save() {
+            println!("Slot {} holds a {:?} run, which can't be restored", slot, snapshot.mode);
+            continue;
+        }
+        match state.get() {
+            GameState::MainMenu => {
+                // Put the ball where it was saved so loading builds the terrain around it
+                for mut transform in player_query.iter_mut() {
+                    transform.translation = Vec3::from_array(snapshot.player.translation);
+                }
+                *mode = snapshot.mode;
+                terrain_config.seed = snapshot.seed;
+                pending.0 = Some(snapshot);
+                next_state.set(GameState::Loading);
+            }
+            GameState::Playing if snapshot.mode == *mode && snapshot.seed == terrain_config.seed => pending.0 = Some(snapshot),
+            GameState::Playing => println!("Slot {} was saved in another mode or world; load it from the main menu", slot),
+            _ => {}
+        }
+    }
+}
+
+// Restore a pending snapshot once its run is playing, after the run start has reset everything
+pub fn apply_pending_load(
+    mut commands: Commands,
+    mut pending: ResMut<PendingLoad>,
+    mut chunk_manager: ResMut<ChunkManager>,
+    mut player_query: Query<(&mut Transform, &mut PlayerPhysics, &mut Health), With<Player>>,
+    mut ammo: ResMut<Ammo>,
+    mut score: ResMut<Score>,
+    mut experience: ResMut<Experience>,
+    mut upgrades: ResMut<Upgrades>,
+    mut regions: ResMut<Regions>,
+    mut run_stats: ResMut<RunStats>,
+    mut hazard_state: ResMut<HazardState>,
+) {
+    let Some(snapshot) = pending.0.take() else {
+        return;
+    };
+
+    let translation = Vec3::from_array(snapshot.player.translation);
+    for (mut transform, mut physics, mut health) in player_query.iter_mut() {
+        transform.translation = translation;
+        transform.rotation = Quat::from_array(snapshot.player.rotation);
+        physics.velocity = Vec3::from_array(snapshot.player.velocity);
+        physics.momentum = physics.velocity;
+        physics.angular_velocity = Vec3::from_array(snapshot.player.angular_velocity);
+        physics.prev_position = translation;
+        health.current = snapshot.player.health.min(health.max);
+    }
+    hazard_state.safe_position = translation;
+
+    ammo.current = snapshot.ammo.min(ammo.max);
+    score.points = snapshot.points;
+    score.best_combo = snapshot.best_combo;
+    score.combo = 0;
+    experience.xp = snapshot.xp;
+    experience.level = snapshot.level;
+    experience.pending_choices = snapshot.pending_choices;
+    experience.sync_score(snapshot.points);
+    let [blast_radius, reload, traction] = snapshot.upgrades;
+    *upgrades = Upgrades { blast_radius, reload, traction };
+    regions.discovered = snapshot.discovered_regions.iter().copied().collect();
+    regions.current = None;
+    run_stats.elapsed = snapshot.elapsed;
+    run_stats.distance_rolled = snapshot.distance_rolled;
+    run_stats.boulders_fired = snapshot.boulders_fired;
+    run_stats.hits = snapshot.hits;
+    run_stats.forget_position();
+
+    clear_deformations(&mut commands, &mut chunk_manager);
+    for saved in snapshot.deformations {
+        add_deformation(&mut commands, &mut chunk_manager, Deformation {
+            center: Vec2::from_array(saved.center),
+            radius: saved.radius,
+            depth: saved.depth,
+        });
+    }
+    println!("Loaded saved {:?} run", snapshot.mode);
+}
+
+// Plugin for saving and loading runs
+pub struct SaveGamePlugin;
+
+impl Plugin for SaveGamePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<SlotRequest>()
+            .init_resource::<PendingLoad>()
+            .add_systems(Update, quick_save_keys.run_if(in_state(GameState::Playing)))
+            .add_systems(Update, (save_to_slot, load_from_slot).after(quick_save_keys))
+            .add_systems(Update, apply_pending_load.after(load_from_slot).run_if(in_state(GameState::Playing)));
+    }
+}
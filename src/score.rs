@@ -0,0 +1,159 @@
+use bevy::prelude::*;
+use crate::camera::FollowCamera;
+use crate::graphics::RenderScale;
+use crate::lifetime::Lifetime;
+use crate::projectile::ProjectileHitEvent;
+use crate::GameState;
+
+// Scoring constants
+const HIT_POINTS: f32 = 100.0; // Base points for a target hit
+const DISTANCE_BONUS_PER_METER: f32 = 10.0; // Extra points per meter between launch and impact
+const COMBO_WINDOW: f32 = 3.0; // Seconds after a hit in which the next hit extends the combo
+const COMBO_STEP: f32 = 0.5; // Multiplier added for each consecutive hit
+const MAX_COMBO_MULTIPLIER: f32 = 4.0; // Upper bound on the combo multiplier
+const POPUP_DURATION: f32 = 1.2; // Seconds a score popup stays on screen
+const POPUP_RISE: f32 = 60.0; // Pixels a popup drifts upward over its lifetime
+const POPUP_FONT_SIZE: f32 = 26.0;
+
+// Points earned this run and the current combo
+#[derive(Resource, Default)]
+pub struct Score {
+    pub points: u32,
+    // Consecutive hits within the combo window
+    pub combo: u32,
+    // Seconds left to extend the combo
+    pub combo_timer: f32,
+    // Longest combo this run
+    pub best_combo: u32,
+}
+
+impl Score {
+    // Multiplier applied to the current hit
+    pub fn multiplier(&self) -> f32 {
+        (1.0 + self.combo.saturating_sub(1) as f32 * COMBO_STEP).min(MAX_COMBO_MULTIPLIER)
+    }
+}
+
+// Floating "+points" text anchored to a world position
+#[derive(Component)]
+pub struct ScorePopup {
+    pub world_position: Vec3,
+}
+
+// Clear the score when a new run starts
+pub fn reset_score(mut score: ResMut<Score>) {
+    *score = Score::default();
+}
+
+// Award points for target hits and spawn a popup at each hit
+pub fn score_hits(
+    mut commands: Commands,
+    mut hit_events: EventReader<ProjectileHitEvent>,
+    mut score: ResMut<Score>,
+) {
+    for event in hit_events.read() {
+        // Hits inside the window extend the combo, otherwise it starts again
+        score.combo = if score.combo_timer > 0.0 { score.combo + 1 } else { 1 };
+        score.combo_timer = COMBO_WINDOW;
+        score.best_combo = score.best_combo.max(score.combo);
+
+        let multiplier = score.multiplier();
+        let points = ((HIT_POINTS + event.distance * DISTANCE_BONUS_PER_METER) * multiplier).round() as u32;
+        score.points += points;
+
+        let label = if score.combo > 1 {
+            format!("+{} x{:.1}", points, multiplier)
+        } else {
+            format!("+{}", points)
+        };
+        spawn_score_popup(&mut commands, event.position, label);
+    }
+}
+
+// Floating text that rises from a world position and fades out
+pub fn spawn_score_popup(commands: &mut Commands, world_position: Vec3, label: String) {
+    commands.spawn((
+        ScorePopup { world_position },
+        Lifetime::new(POPUP_DURATION),
+        Text::new(label),
+        TextFont {
+            font_size: POPUP_FONT_SIZE,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.85, 0.2)),
+        Node {
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        // Placed by update_score_popups once projected
+        Visibility::Hidden,
+    ));
+}
+
+// Let the combo lapse once the window runs out
+pub fn tick_combo(mut score: ResMut<Score>, time: Res<Time>) {
+    if score.combo_timer <= 0.0 {
+        return;
+    }
+    score.combo_timer -= time.delta_secs();
+    if score.combo_timer <= 0.0 {
+        score.combo_timer = 0.0;
+        score.combo = 0;
+    }
+}
+
+// Project popups to screen space, drift them upward and fade them out
+pub fn update_score_popups(
+    camera_query: Query<(&Camera, &GlobalTransform), With<FollowCamera>>,
+    render_scale: Res<RenderScale>,
+    mut popup_query: Query<(&ScorePopup, &Lifetime, &mut Node, &mut TextColor, &mut Visibility)>,
+) {
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+
+    for (popup, lifetime, mut node, mut color, mut visibility) in popup_query.iter_mut() {
+        let t = lifetime.fraction();
+
+        match camera.world_to_viewport(camera_transform, popup.world_position) {
+            Ok(screen_pos) => {
+                let screen_pos = render_scale.viewport_to_window(screen_pos);
+                node.left = Val::Px(screen_pos.x);
+                node.top = Val::Px(screen_pos.y - POPUP_RISE * t);
+                *visibility = Visibility::Inherited;
+            }
+            // Behind the camera
+            Err(_) => *visibility = Visibility::Hidden,
+        }
+        color.0 = color.0.with_alpha(1.0 - t * t);
+    }
+}
+
+// Remove any popups left over when gameplay ends
+pub fn clear_score_popups(mut commands: Commands, query: Query<Entity, With<ScorePopup>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Plugin for scoring target hits
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Score>()
+            .add_systems(
+                OnTransition { exited: GameState::Loading, entered: GameState::Playing },
+                reset_score,
+            )
+            // The results screen still shows the score, so clear it once that is left
+            .add_systems(OnExit(GameState::GameOver), reset_score)
+            .add_systems(OnExit(GameState::Playing), clear_score_popups)
+            .add_systems(Update, (
+                tick_combo,
+                score_hits,
+                update_score_popups,
+            ).chain().run_if(in_state(GameState::Playing)));
+    }
+}
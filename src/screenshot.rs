@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{save_to_disk, Screenshot, ScreenshotCaptured};
+use bevy::utils::SystemTime;
+
+// Screenshot constants
+const SCREENSHOT_DIR: &str = "screenshots";
+const FLASH_DURATION: f32 = 0.25; // Seconds the white flash takes to fade out
+const FLASH_ALPHA: f32 = 0.6; // Opacity of the flash when it appears
+
+// White overlay shown briefly after a screenshot is captured
+#[derive(Component)]
+pub struct ScreenshotFlash {
+    pub remaining: f32,
+}
+
+// Build a timestamped screenshot path such as screenshots/trowback-1700000000123.png
+pub fn timestamped_screenshot_path() -> String {
+    let millis = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+    format!("{}/trowback-{}.png", SCREENSHOT_DIR, millis)
+}
+
+// Grab the next rendered frame of the primary window and write it to the screenshots folder
+pub fn take_screenshot(commands: &mut Commands) {
+    if let Err(error) = std::fs::create_dir_all(SCREENSHOT_DIR) {
+        println!("Could not create screenshot directory: {}", error);
+        return;
+    }
+    let path = timestamped_screenshot_path();
+    println!("Saving screenshot to {}", path);
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(path))
+        .observe(flash_on_capture);
+}
+
+// Flash once the frame is captured so the flash itself isn't in the picture
+fn flash_on_capture(_trigger: Trigger<ScreenshotCaptured>, mut commands: Commands) {
+    commands.spawn((
+        ScreenshotFlash { remaining: FLASH_DURATION },
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(1.0, 1.0, 1.0, FLASH_ALPHA)),
+        // Above every menu and overlay
+        GlobalZIndex(100),
+        // Let clicks through to whatever is underneath
+        PickingBehavior::IGNORE,
+    ));
+}
+
+// F12 saves a screenshot in any state
+pub fn screenshot_key(mut commands: Commands, keys: Res<ButtonInput<KeyCode>>) {
+    if keys.just_pressed(KeyCode::F12) {
+        take_screenshot(&mut commands);
+    }
+}
+
+// Fade the flash out on real time so it also fades while paused, then remove it
+pub fn fade_screenshot_flash(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut query: Query<(Entity, &mut ScreenshotFlash, &mut BackgroundColor)>,
+) {
+    for (entity, mut flash, mut color) in query.iter_mut() {
+        flash.remaining -= time.delta_secs();
+        if flash.remaining <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        color.0 = Color::srgba(1.0, 1.0, 1.0, FLASH_ALPHA * flash.remaining / FLASH_DURATION);
+    }
+}
+
+// Plugin for the screenshot hotkey
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (screenshot_key, fade_screenshot_flash));
+    }
+}
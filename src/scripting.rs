@@ -0,0 +1,347 @@
+use bevy::prelude::*;
+use bevy::ecs::event::EventCursor;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rhai::{CallFnOptions, Dynamic, Engine, FuncArgs, Map, Scope, AST};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use crate::enemy::{spawn_enemy, Enemy, EnemyAssets};
+use crate::game_events::GameEvent;
+use crate::mods::ModPacks;
+use crate::photo_mode::PhotoModeState;
+use crate::player::{player_spawn_position, Health, Player};
+use crate::score::Score;
+use crate::sim::{SimSet, Simulation};
+use crate::target::{spawn_target_at, Target, TargetAssets, TargetKind};
+use crate::terrain::get_terrain_height;
+use crate::{GameMode, GameState, RunStart};
+
+// Scripting constants
+const MODS_DIR: &str = "mods";
+const SCRIPT_EXTENSION: &str = "rhai";
+const PACK_SCRIPTS_DIR: &str = "scripts"; // Scripts folder in a mod pack
+const SCRIPT_STREAM: u64 = 15; // Simulation random stream for scripts and what they spawn
+const MAX_OPERATIONS: u64 = 200_000; // Most operations a script may run in one call before it is stopped
+
+// What a script asked for during a call, applied once it returns
+#[derive(Debug, Clone)]
+enum ScriptCommand {
+    SpawnTarget { kind: String, x: f32, z: f32 },
+    SpawnEnemy { x: f32, z: f32 },
+    // Positive hurts, negative heals
+    Damage(f32),
+    Score(i64),
+    Message(String),
+    WaveSurvived(u32),
+    EndRun(String),
+}
+
+// State shared with the functions registered on the engine: a snapshot of the game for scripts to read,
+// and the commands they have queued
+struct ScriptContext {
+    player: Vec3,
+    health: f32,
+    score: u32,
+    time: f32,
+    targets: usize,
+    enemies: usize,
+    rng: StdRng,
+    commands: Vec<ScriptCommand>,
+}
+
+impl Default for ScriptContext {
+    fn default() -> Self {
+        Self {
+            player: Vec3::ZERO,
+            health: 0.0,
+            score: 0,
+            time: 0.0,
+            targets: 0,
+            enemies: 0,
+            rng: StdRng::seed_from_u64(0),
+            commands: Vec::new(),
+        }
+    }
+}
+
+// A game mode script from the mods folder
+pub struct ModScript {
+    // From the script's name() function, or the file name without one
+    pub name: String,
+    pub path: PathBuf,
+    ast: AST,
+}
+
+// Custom game modes written in Rhai. Each script in mods/ can define these functions, all optional:
+//   name()           the label on the Mods page
+//   start()          called on the first tick of a run
+//   tick(dt)         called every simulation tick with its length in seconds
+//   event(kind, text) called for each game event, with its snake_case kind and event log line
+// Inside them `this` is a map that lasts for the whole run, for keeping counts and timers.
+#[derive(Resource)]
+pub struct Scripting {
+    engine: Engine,
+    context: Arc<Mutex<ScriptContext>>,
+    pub scripts: Vec<ModScript>,
+    // Script whose rules the Scripted mode plays by
+    pub selected: Option<usize>,
+    // The script's `this` for the current run
+    state: Dynamic,
+    started: bool,
+    // Set when a call fails, so a broken script stops instead of failing every tick
+    failed: bool,
+}
+
+// Read a script argument as a number, whether it was written as an integer or not
+fn number(value: &Dynamic) -> f32 {
+    value
+        .as_float()
+        .map(|value| value as f32)
+        .or_else(|_| value.as_int().map(|value| value as f32))
+        .unwrap_or(0.0)
+}
+
+// Register the functions scripts call to read the game and change it
+fn register_api(engine: &mut Engine, context: &Arc<Mutex<ScriptContext>>) {
+    let push = |context: &Arc<Mutex<ScriptContext>>, command: ScriptCommand| context.lock().unwrap().commands.push(command);
+
+    let shared = context.clone();
+    engine.register_fn("spawn_target", move |kind: &str, x: Dynamic, z: Dynamic| {
+        push(&shared, ScriptCommand::SpawnTarget { kind: kind.to_string(), x: number(&x), z: number(&z) });
+    });
+    let shared = context.clone();
+    engine.register_fn("spawn_enemy", move |x: Dynamic, z: Dynamic| {
+        push(&shared, ScriptCommand::SpawnEnemy { x: number(&x), z: number(&z) });
+    });
+    let shared = context.clone();
+    engine.register_fn("damage_player", move |amount: Dynamic| push(&shared, ScriptCommand::Damage(number(&amount))));
+    let shared = context.clone();
+    engine.register_fn("heal_player", move |amount: Dynamic| push(&shared, ScriptCommand::Damage(-number(&amount))));
+    let shared = context.clone();
+    engine.register_fn("add_score", move |points: i64| push(&shared, ScriptCommand::Score(points)));
+    let shared = context.clone();
+    engine.register_fn("message", move |text: &str| push(&shared, ScriptCommand::Message(text.to_string())));
+    let shared = context.clone();
+    engine.register_fn("wave_survived", move |wave: i64| push(&shared, ScriptCommand::WaveSurvived(wave.max(0) as u32)));
+    let shared = context.clone();
+    engine.register_fn("end_run", move |text: &str| push(&shared, ScriptCommand::EndRun(text.to_string())));
+
+    let shared = context.clone();
+    engine.register_fn("player_x", move || shared.lock().unwrap().player.x as f64);
+    let shared = context.clone();
+    engine.register_fn("player_y", move || shared.lock().unwrap().player.y as f64);
+    let shared = context.clone();
+    engine.register_fn("player_z", move || shared.lock().unwrap().player.z as f64);
+    let shared = context.clone();
+    engine.register_fn("health", move || shared.lock().unwrap().health as f64);
+    let shared = context.clone();
+    engine.register_fn("score", move || shared.lock().unwrap().score as i64);
+    let shared = context.clone();
+    engine.register_fn("time", move || shared.lock().unwrap().time as f64);
+    let shared = context.clone();
+    engine.register_fn("targets_left", move || shared.lock().unwrap().targets as i64);
+    let shared = context.clone();
+    engine.register_fn("enemies_left", move || shared.lock().unwrap().enemies as i64);
+    let shared = context.clone();
+    engine.register_fn("random", move |min: Dynamic, max: Dynamic| {
+        let (min, max) = (number(&min), number(&max));
+        // gen_range panics on an empty, infinite or NaN range, which a script can easily ask for
+        if min.is_finite() && max.is_finite() && (max - min).is_finite() && min < max {
+            shared.lock().unwrap().rng.gen_range(min..max) as f64
+        } else {
+            min as f64
+        }
+    });
+    engine.register_fn("ground_height", |x: Dynamic, z: Dynamic| get_terrain_height(number(&x), number(&z)) as f64);
+    engine.register_fn("spawn_x", || player_spawn_position().x as f64);
+    engine.register_fn("spawn_z", || player_spawn_position().z as f64);
+}
+
+// Compile a script and find its name
+fn load_script(engine: &Engine, path: &Path) -> Result<ModScript, String> {
+    let source = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+    let ast = engine.compile(source).map_err(|error| error.to_string())?;
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let name = if ast.iter_functions().any(|function| function.name == "name") {
+        let options = CallFnOptions::new().eval_ast(false);
+        engine
+            .call_fn_with_options::<String>(options, &mut Scope::new(), &ast, "name", ())
+            .map_err(|error| error.to_string())?
+    } else {
+        stem
+    };
+    Ok(ModScript { name, path: path.to_path_buf(), ast })
+}
+
+impl Scripting {
+    fn new() -> Self {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let context = Arc::new(Mutex::new(ScriptContext::default()));
+        register_api(&mut engine, &context);
+        Self {
+            engine,
+            context,
+            scripts: Vec::new(),
+            selected: None,
+            state: Dynamic::from_map(Map::new()),
+            started: false,
+            failed: false,
+        }
+    }
+
+    // Load the loose scripts in the mods folder, then each pack's scripts folder in load order, each
+    // folder in file name order; ones that don't compile are skipped
+    fn load_scripts(&mut self, packs: Option<&ModPacks>) {
+        let mut folders = vec![PathBuf::from(MODS_DIR)];
+        folders.extend(packs.map(|packs| packs.files(PACK_SCRIPTS_DIR)).unwrap_or_default());
+        for folder in folders {
+            let Ok(entries) = std::fs::read_dir(&folder) else {
+                continue;
+            };
+            let mut paths: Vec<PathBuf> = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == SCRIPT_EXTENSION))
+                .collect();
+            paths.sort();
+            for path in paths {
+                match load_script(&self.engine, &path) {
+                    Ok(script) => {
+                        println!("Loaded mod script {} ({})", script.name, path.display());
+                        self.scripts.push(script);
+                    }
+                    Err(error) => println!("Failed to load mod script {}: {}", path.display(), error),
+                }
+            }
+        }
+    }
+
+    // Name of the selected script, for menus
+    pub fn selected_name(&self) -> Option<&str> {
+        self.selected.and_then(|index| self.scripts.get(index)).map(|script| script.name.as_str())
+    }
+
+    // Call one of the selected script's functions if it has it
+    fn call(&mut self, function: &str, args: impl FuncArgs) {
+        if self.failed {
+            return;
+        }
+        let Some(script) = self.selected.and_then(|index| self.scripts.get(index)) else {
+            return;
+        };
+        if !script.ast.iter_functions().any(|defined| defined.name == function) {
+            return;
+        }
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut self.state);
+        if let Err(error) = self.engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &script.ast, function, args) {
+            println!("Mod script {} stopped in {}(): {}", script.name, function, error);
+            self.failed = true;
+        }
+    }
+}
+
+// Read the scripts in the mods folder
+pub fn setup_scripting(mut commands: Commands, packs: Option<Res<ModPacks>>) {
+    let mut scripting = Scripting::new();
+    scripting.load_scripts(packs.as_deref());
+    commands.insert_resource(scripting);
+}
+
+// Clear the script's state for a new run; start() runs on the run's first tick
+pub fn reset_script(mut scripting: ResMut<Scripting>) {
+    scripting.state = Dynamic::from_map(Map::new());
+    scripting.started = false;
+    scripting.failed = false;
+}
+
+// Give the script the game's events and a tick, then carry out what it asked for
+pub fn run_script(
+    mut commands: Commands,
+    mut scripting: ResMut<Scripting>,
+    mut cursor: Local<EventCursor<GameEvent>>,
+    mut game_events: ResMut<Events<GameEvent>>,
+    mut score: ResMut<Score>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut player_query: Query<(&Transform, &mut Health), With<Player>>,
+    target_query: Query<(), With<Target>>,
+    enemy_query: Query<(), With<Enemy>>,
+    target_assets: Res<TargetAssets>,
+    enemy_assets: Res<EnemyAssets>,
+    simulation: Res<Simulation>,
+    time: Res<Time>,
+) {
+    let Ok((transform, mut health)) = player_query.get_single_mut() else {
+        return;
+    };
+    {
+        let mut context = scripting.context.lock().unwrap();
+        context.player = transform.translation;
+        context.health = health.current;
+        context.score = score.points;
+        context.time = simulation.elapsed();
+        context.targets = target_query.iter().count();
+        context.enemies = enemy_query.iter().count();
+        context.rng = simulation.rng(SCRIPT_STREAM);
+    }
+
+    // The script's own messages aren't handed back to it
+    let events: Vec<GameEvent> = cursor
+        .read(&game_events)
+        .filter(|event| !matches!(event, GameEvent::ScriptMessage { .. }))
+        .cloned()
+        .collect();
+    // What start() spawns only shows up in the snapshot from the next tick on, so the first tick is
+    // left to it alone
+    if !scripting.started {
+        scripting.started = true;
+        scripting.call("start", ());
+    } else {
+        for event in events {
+            scripting.call("event", (event.kind().to_string(), event.describe()));
+        }
+        scripting.call("tick", (time.delta_secs_f64(),));
+    }
+
+    let mode = scripting.selected_name().unwrap_or_default().to_string();
+    let mut context = scripting.context.lock().unwrap();
+    let queued = std::mem::take(&mut context.commands);
+    for command in queued {
+        match command {
+            ScriptCommand::SpawnTarget { kind, x, z } => match TargetKind::from_name(&kind) {
+                Some(kind) => spawn_target_at(&mut commands, &target_assets, kind, x, z, &mut context.rng),
+                None => println!("Mod script asked for an unknown target kind {:?}", kind),
+            },
+            ScriptCommand::SpawnEnemy { x, z } => spawn_enemy(&mut commands, &enemy_assets, x, z),
+            ScriptCommand::Damage(amount) => health.current = (health.current - amount).clamp(0.0, health.max),
+            ScriptCommand::Score(points) => score.points = (score.points as i64 + points).max(0) as u32,
+            ScriptCommand::Message(text) => {
+                game_events.send(GameEvent::ScriptMessage { text });
+            }
+            ScriptCommand::WaveSurvived(wave) => {
+                game_events.send(GameEvent::WaveSurvived { mode: mode.clone(), wave });
+            }
+            ScriptCommand::EndRun(text) => {
+                if !text.is_empty() {
+                    game_events.send(GameEvent::ScriptMessage { text });
+                }
+                next_state.set(GameState::GameOver);
+            }
+        }
+    }
+}
+
+// Plugin for scripted game modes from the mods folder
+pub struct ScriptingPlugin;
+
+impl Plugin for ScriptingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup_scripting)
+            .add_systems(RunStart, reset_script)
+            .add_systems(FixedUpdate, run_script
+                .in_set(SimSet::Environment)
+                .run_if(resource_equals(GameMode::Scripted))
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)));
+    }
+}
@@ -0,0 +1,332 @@
+use bevy::prelude::*;
+use bevy::utils::Instant;
+use bevy::window::{MonitorSelection, PresentMode, PrimaryWindow, WindowMode, WindowResolution};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use crate::audio::AudioBus;
+use crate::budget::WorkBudgets;
+use crate::camera::CameraSettings;
+use crate::graphics::GraphicsSettings;
+use crate::input::{Action, InputBinding, InputMap};
+use crate::profile::ActiveProfile;
+
+// Settings files inside the platform config directory (~/.config/trowback on Linux), one per profile
+const PROFILES_DIR: &str = "profiles";
+const LEGACY_SETTINGS_FILE: &str = "settings.toml"; // Used before profiles; picked up by the default profile
+const WEB_CANVAS: &str = "#trowback"; // Canvas the game draws into when embedded in a web page
+
+// Mouse sensitivity multipliers offered in the settings menu
+const SENSITIVITY_STEPS: [f32; 5] = [0.5, 0.75, 1.0, 1.5, 2.0];
+// Volume levels offered in the settings menu
+const VOLUME_STEPS: [f32; 5] = [0.0, 0.25, 0.5, 0.75, 1.0];
+// Window sizes offered in the settings menu, in logical pixels
+const RESOLUTION_STEPS: [(u32, u32); 5] = [(1280, 720), (1366, 768), (1600, 900), (1920, 1080), (2560, 1440)];
+// Frame rate caps offered in the settings menu; 0 is uncapped
+const FPS_LIMIT_STEPS: [u32; 5] = [0, 30, 60, 120, 144];
+
+// How the window fills the screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisplayMode {
+    Windowed,
+    // A window covering the whole monitor at its desktop resolution; switches in and out instantly
+    Borderless,
+    // Exclusive fullscreen, which can be smoother but flickers when switching
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub fn next(self) -> Self {
+        match self {
+            DisplayMode::Windowed => DisplayMode::Borderless,
+            DisplayMode::Borderless => DisplayMode::Fullscreen,
+            DisplayMode::Fullscreen => DisplayMode::Windowed,
+        }
+    }
+}
+
+// The bindings of one action as stored in the settings file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keybind {
+    pub action: Action,
+    pub bindings: Vec<InputBinding>,
+}
+
+// Player preferences that persist between launches
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // Multiplier on the camera orbit speed per pixel of mouse drag
+    pub mouse_sensitivity: f32,
+    // Bus volumes in 0..1; master scales every sound, the others scale their own kind
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub effects_volume: f32,
+    pub ambient_volume: f32,
+    // Buses silenced without losing their volume
+    pub muted: Vec<AudioBus>,
+    pub display_mode: DisplayMode,
+    // Window size while windowed, in logical pixels
+    pub resolution: (u32, u32),
+    pub vsync: bool,
+    // Most frames drawn per second, 0 for no cap
+    pub fps_limit: u32,
+    pub shadows: bool,
+    // Rendering quality; pushed out to the cameras and lights by the graphics plugin
+    pub graphics: GraphicsSettings,
+    // Every action's bindings; missing actions keep their defaults
+    pub keybinds: Vec<Keybind>,
+    // Frame time the heavy work queues may use
+    pub work_budgets: WorkBudgets,
+    // Settings files from before display modes had a fullscreen switch instead
+    #[serde(rename = "fullscreen", skip_serializing)]
+    legacy_fullscreen: Option<bool>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            mouse_sensitivity: 1.0,
+            master_volume: 1.0,
+            music_volume: 0.8,
+            effects_volume: 1.0,
+            ambient_volume: 0.7,
+            muted: Vec::new(),
+            display_mode: DisplayMode::Windowed,
+            resolution: RESOLUTION_STEPS[0],
+            vsync: true,
+            fps_limit: 0,
+            shadows: true,
+            graphics: GraphicsSettings::default(),
+            keybinds: Vec::new(),
+            work_budgets: WorkBudgets::default(),
+            legacy_fullscreen: None,
+        }
+    }
+}
+
+// Next value in a cycle of steps, wrapping after the last
+fn next_step(steps: &[f32], current: f32) -> f32 {
+    steps.iter().copied().find(|step| *step > current + 0.001).unwrap_or(steps[0])
+}
+
+impl Settings {
+    // Location of a profile's settings file, if the platform has a config directory
+    fn path(profile: &ActiveProfile) -> Option<PathBuf> {
+        ProjectDirs::from("", "", "trowback")
+            .map(|dirs| dirs.config_dir().join(PROFILES_DIR).join(format!("{}.toml", profile.name)))
+    }
+
+    // Read a profile's settings file, falling back to defaults if it is missing or invalid
+    pub fn load(profile: &ActiveProfile) -> Self {
+        let Some(path) = Self::path(profile) else {
+            return Self::default();
+        };
+        let contents = std::fs::read_to_string(&path).or_else(|error| {
+            match (profile.is_default(), path.parent().and_then(|dir| dir.parent())) {
+                (true, Some(config_dir)) => std::fs::read_to_string(config_dir.join(LEGACY_SETTINGS_FILE)),
+                _ => Err(error),
+            }
+        });
+        match contents {
+            Ok(contents) => match toml::from_str::<Self>(&contents) {
+                Ok(mut settings) => {
+                    if settings.legacy_fullscreen.take() == Some(true) {
+                        settings.display_mode = DisplayMode::Borderless;
+                    }
+                    settings
+                }
+                Err(error) => {
+                    println!("Invalid settings file {}: {}", path.display(), error);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    // Write a profile's settings file, creating its directory if needed
+    pub fn write(&self, profile: &ActiveProfile) {
+        let Some(path) = Self::path(profile) else {
+            println!("No config directory to save settings in");
+            return;
+        };
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| {
+                let contents = toml::to_string_pretty(self).map_err(std::io::Error::other)?;
+                std::fs::write(&path, contents)
+            });
+        if let Err(error) = result {
+            println!("Failed to write settings file {}: {}", path.display(), error);
+        }
+    }
+
+    // The primary window as these settings describe it, for building the app
+    pub fn window(&self) -> Window {
+        Window {
+            title: "Trowback".to_string(),
+            mode: self.window_mode(),
+            resolution: WindowResolution::new(self.resolution.0 as f32, self.resolution.1 as f32),
+            present_mode: self.present_mode(),
+            // In a browser the game draws into the page's canvas and grows with its container
+            canvas: cfg!(target_arch = "wasm32").then(|| WEB_CANVAS.to_string()),
+            fit_canvas_to_parent: cfg!(target_arch = "wasm32"),
+            ..default()
+        }
+    }
+
+    fn window_mode(&self) -> WindowMode {
+        match self.display_mode {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+            DisplayMode::Fullscreen => WindowMode::Fullscreen(MonitorSelection::Current),
+        }
+    }
+
+    fn present_mode(&self) -> PresentMode {
+        if self.vsync { PresentMode::AutoVsync } else { PresentMode::AutoNoVsync }
+    }
+
+    pub fn cycle_resolution(&mut self) {
+        let index = RESOLUTION_STEPS
+            .iter()
+            .position(|resolution| *resolution == self.resolution)
+            .map_or(0, |index| (index + 1) % RESOLUTION_STEPS.len());
+        self.resolution = RESOLUTION_STEPS[index];
+    }
+
+    pub fn cycle_fps_limit(&mut self) {
+        self.fps_limit = FPS_LIMIT_STEPS
+            .iter()
+            .copied()
+            .find(|limit| *limit > self.fps_limit)
+            .unwrap_or(FPS_LIMIT_STEPS[0]);
+    }
+
+    pub fn cycle_sensitivity(&mut self) {
+        self.mouse_sensitivity = next_step(&SENSITIVITY_STEPS, self.mouse_sensitivity);
+    }
+
+    pub fn bus_volume(&self, bus: AudioBus) -> f32 {
+        match bus {
+            AudioBus::Master => self.master_volume,
+            AudioBus::Music => self.music_volume,
+            AudioBus::Effects => self.effects_volume,
+            AudioBus::Ambient => self.ambient_volume,
+        }
+    }
+
+    pub fn cycle_bus_volume(&mut self, bus: AudioBus) {
+        let volume = match bus {
+            AudioBus::Master => &mut self.master_volume,
+            AudioBus::Music => &mut self.music_volume,
+            AudioBus::Effects => &mut self.effects_volume,
+            AudioBus::Ambient => &mut self.ambient_volume,
+        };
+        *volume = next_step(&VOLUME_STEPS, *volume);
+    }
+
+    pub fn is_muted(&self, bus: AudioBus) -> bool {
+        self.muted.contains(&bus)
+    }
+
+    pub fn toggle_mute(&mut self, bus: AudioBus) {
+        if self.is_muted(bus) {
+            self.muted.retain(|muted| *muted != bus);
+        } else {
+            self.muted.push(bus);
+        }
+    }
+}
+
+// Put saved keybinds into an input map; actions without one keep what the map has
+pub fn apply_keybinds(settings: &Settings, input_map: &mut InputMap) {
+    for keybind in settings.keybinds.iter() {
+        input_map.bindings.insert(keybind.action, keybind.bindings.clone());
+    }
+}
+
+// Put the saved keybinds into the input map at startup
+pub fn apply_saved_keybinds(settings: Res<Settings>, mut input_map: ResMut<InputMap>) {
+    apply_keybinds(&settings, &mut input_map);
+}
+
+// Copy the input map into the settings whenever a binding changes
+pub fn record_keybinds(input_map: Res<InputMap>, mut settings: ResMut<Settings>) {
+    settings.keybinds = Action::ALL
+        .iter()
+        .map(|action| Keybind {
+            action: *action,
+            bindings: input_map.get(*action).to_vec(),
+        })
+        .collect();
+}
+
+// Push the settings out to the camera, window and lights; the audio mixer follows them itself
+pub fn apply_settings(
+    settings: Res<Settings>,
+    mut applied_window: Local<Option<(DisplayMode, (u32, u32))>>,
+    mut camera_settings: ResMut<CameraSettings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    mut light_query: Query<&mut DirectionalLight>,
+) {
+    camera_settings.orbit_sensitivity = CameraSettings::default().orbit_sensitivity * settings.mouse_sensitivity;
+    for mut window in window_query.iter_mut() {
+        // The mode and size only go out when they change, so changing something else doesn't undo the
+        // window being resized by hand
+        let wanted = (settings.display_mode, settings.resolution);
+        if *applied_window != Some(wanted) {
+            window.mode = settings.window_mode();
+            if settings.display_mode == DisplayMode::Windowed {
+                window.resolution.set(settings.resolution.0 as f32, settings.resolution.1 as f32);
+            }
+            *applied_window = Some(wanted);
+        }
+        window.present_mode = settings.present_mode();
+    }
+    for mut light in light_query.iter_mut() {
+        light.shadows_enabled = settings.shadows;
+    }
+}
+
+// Sleep off what is left of each frame's share of a second under the FPS limit. Runs at the very end of
+// the frame so the sleep covers all of its work; the browser paces frames itself, so it is skipped there.
+pub fn limit_frame_rate(settings: Res<Settings>, mut last_frame: Local<Option<Instant>>) {
+    if settings.fps_limit == 0 || cfg!(target_arch = "wasm32") {
+        *last_frame = None;
+        return;
+    }
+    if let Some(last_frame) = *last_frame {
+        let frame_time = std::time::Duration::from_secs_f64(1.0 / settings.fps_limit as f64);
+        let elapsed = last_frame.elapsed();
+        if elapsed < frame_time {
+            std::thread::sleep(frame_time - elapsed);
+        }
+    }
+    *last_frame = Some(Instant::now());
+}
+
+// Write the settings file whenever the settings change
+pub fn write_settings_on_change(settings: Res<Settings>, profile: Res<ActiveProfile>) {
+    settings.write(&profile);
+}
+
+// Plugin for persistent settings; the Settings resource itself is loaded in main before the window is built
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, apply_saved_keybinds)
+            // PostStartup so the light spawned in setup is there to configure
+            .add_systems(PostStartup, apply_settings)
+            .add_systems(Update, (
+                record_keybinds.run_if(resource_changed::<InputMap>.and(not(resource_added::<InputMap>))),
+                apply_settings.run_if(resource_changed::<Settings>.and(not(resource_added::<Settings>))),
+                write_settings_on_change.run_if(resource_changed::<Settings>.and(not(resource_added::<Settings>))),
+            ).chain())
+            .add_systems(Last, limit_frame_rate);
+    }
+}
@@ -0,0 +1,303 @@
+use bevy::prelude::*;
+use bevy::color::ColorToComponents;
+use bevy::pbr::{NotShadowCaster, NotShadowReceiver};
+use bevy::render::render_resource::{AsBindGroup, ShaderRef, ShaderType};
+use bevy::transform::TransformSystem;
+use crate::camera::CameraShake;
+use crate::player::Player;
+use crate::projectile::{ballistic_velocity, Hostile, Projectile, PROJECTILE_RADIUS};
+use crate::replay::BUTTON_SHIELD;
+use crate::sim::{interpolate_positions, SimInput, SimSet};
+use crate::tuning::LiveTuning;
+use crate::GameState;
+
+// Shield constants
+const SHIELD_SHADER: &str = "shaders/shield.wgsl";
+const SHIELD_RADIUS: f32 = 1.6; // Radius of the bubble around the ball
+const MAX_ENERGY: f32 = 100.0;
+const ACTIVATION_COST: f32 = 20.0; // Energy needed, and spent, to raise the shield
+const DRAIN_RATE: f32 = 12.0; // Energy spent per second while the shield is up
+const DEFLECT_COST: f32 = 15.0; // Energy spent on each boulder turned away
+const RECHARGE_RATE: f32 = 8.0; // Energy regained per second while the shield is down
+const RESTITUTION: f32 = 0.8; // Share of its speed a boulder keeps when it bounces off
+const DEFLECT_TRAUMA: f32 = 0.25; // Camera shake from a boulder hitting the shield
+const RIPPLE_SPEED: f32 = 3.0; // Radians of arc the ripple covers per second
+const RIPPLE_DURATION: f32 = 1.0; // Seconds until the ripple has faded out
+const SHIELD_COLOR: Color = Color::srgba(0.35, 0.75, 1.0, 0.35);
+const LOW_ENERGY_COLOR: Color = Color::srgb(0.3, 0.4, 0.5);
+
+// Values the shield shader reads, matching ShieldSettings in shield.wgsl
+#[derive(ShaderType, Reflect, Debug, Clone, Copy)]
+pub struct ShieldSettings {
+    pub color: Vec4,
+    // Direction from the middle of the bubble to the last impact
+    pub ripple_direction: Vec3,
+    // Arc in radians the ripple has spread from the impact
+    pub ripple_front: f32,
+    // How visible the ripple still is, from 1 at impact to 0
+    pub ripple_strength: f32,
+}
+
+// Unlit, see-through bubble with a ripple where it was hit
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct ShieldMaterial {
+    #[uniform(0)]
+    pub settings: ShieldSettings,
+}
+
+impl Material for ShieldMaterial {
+    fn fragment_shader() -> ShaderRef {
+        SHIELD_SHADER.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Blend
+    }
+}
+
+// The player's shield: its energy, whether it is up, and the last impact's ripple
+#[derive(Resource)]
+pub struct Shield {
+    pub energy: f32,
+    pub active: bool,
+    ripple_direction: Vec3,
+    // Seconds since the last impact
+    ripple_age: f32,
+}
+
+impl Default for Shield {
+    fn default() -> Self {
+        Self {
+            energy: MAX_ENERGY,
+            active: false,
+            ripple_direction: Vec3::Y,
+            ripple_age: RIPPLE_DURATION,
+        }
+    }
+}
+
+// Marker for the bubble around the ball
+#[derive(Component)]
+pub struct ShieldBubble;
+
+// Marker for the energy bar's frame
+#[derive(Component)]
+pub struct ShieldBarRoot;
+
+// Marker for the energy bar's fill
+#[derive(Component)]
+pub struct ShieldBarFill;
+
+// Spawn the hidden bubble and the energy bar above the hotbar
+pub fn setup_shield(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ShieldMaterial>>,
+) {
+    commands.spawn((
+        ShieldBubble,
+        Mesh3d(meshes.add(Sphere::new(SHIELD_RADIUS).mesh().uv(32, 18))),
+        MeshMaterial3d(materials.add(ShieldMaterial {
+            settings: ShieldSettings {
+                color: SHIELD_COLOR.to_linear().to_vec4(),
+                ripple_direction: Vec3::Y,
+                ripple_front: 0.0,
+                ripple_strength: 0.0,
+            },
+        })),
+        Transform::default(),
+        NotShadowCaster,
+        NotShadowReceiver,
+        Visibility::Hidden,
+        Name::new("Shield Bubble"),
+    ));
+
+    commands
+        .spawn((
+            ShieldBarRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(84.0),
+                left: Val::Percent(50.0),
+                width: Val::Px(200.0),
+                height: Val::Px(6.0),
+                margin: UiRect::left(Val::Px(-100.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ShieldBarFill,
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                BackgroundColor(SHIELD_COLOR.with_alpha(1.0)),
+            ));
+        });
+}
+
+// The shield button raises the shield if there is the energy for it and drops it again; it drains
+// while up and recharges while down, once per tick
+pub fn run_shield(mut shield: ResMut<Shield>, input: Res<SimInput>, time: Res<Time>) {
+    if input.just_pressed(BUTTON_SHIELD) {
+        if shield.active {
+            shield.active = false;
+        } else if shield.energy >= ACTIVATION_COST {
+            shield.energy -= ACTIVATION_COST;
+            shield.active = true;
+        }
+    }
+    let delta = time.delta_secs();
+    if shield.active {
+        shield.energy -= DRAIN_RATE * delta;
+        if shield.energy <= 0.0 {
+            shield.energy = 0.0;
+            shield.active = false;
+        }
+    } else {
+        shield.energy = (shield.energy + RECHARGE_RATE * delta).min(MAX_ENERGY);
+    }
+}
+
+// Bounce hostile boulders off the bubble: the velocity is mirrored about the surface normal where
+// they touch it, and the boulder flies on as the player's own
+pub fn deflect_boulders(
+    mut commands: Commands,
+    mut shield: ResMut<Shield>,
+    mut camera_shake: ResMut<CameraShake>,
+    player_query: Query<&Transform, With<Player>>,
+    mut projectile_query: Query<(Entity, &mut Transform, &mut Projectile), (With<Hostile>, Without<Player>)>,
+    tuning: Res<LiveTuning>,
+) {
+    if !shield.active {
+        return;
+    }
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let center = player.translation;
+    let reach = SHIELD_RADIUS + PROJECTILE_RADIUS;
+
+    for (entity, mut transform, mut projectile) in projectile_query.iter_mut() {
+        if projectile.stuck || transform.translation.distance(center) > reach {
+            continue;
+        }
+        let normal = (transform.translation - center).normalize_or(Vec3::Y);
+        let velocity = ballistic_velocity(&tuning.0.projectile, projectile.initial_velocity, projectile.age);
+        // Already on its way out, e.g. bounced last tick
+        if velocity.dot(normal) >= 0.0 {
+            continue;
+        }
+        let reflected = (velocity - 2.0 * velocity.dot(normal) * normal) * RESTITUTION;
+        let surface = center + normal * reach;
+
+        // Start the arc over from the bounce
+        transform.translation = surface;
+        projectile.start_position = surface;
+        projectile.initial_velocity = reflected;
+        projectile.age = 0.0;
+        projectile.lifetime = tuning.0.projectile.lifetime;
+        commands.entity(entity).remove::<Hostile>();
+
+        shield.ripple_direction = normal;
+        shield.ripple_age = 0.0;
+        shield.energy -= DEFLECT_COST;
+        if shield.energy <= 0.0 {
+            shield.energy = 0.0;
+            shield.active = false;
+        }
+        camera_shake.add_trauma(DEFLECT_TRAUMA);
+    }
+}
+
+// Show the bubble while the shield is up, spread the ripple and fill the energy bar
+pub fn update_shield_visuals(
+    mut shield: ResMut<Shield>,
+    mut materials: ResMut<Assets<ShieldMaterial>>,
+    mut bubble_query: Query<(&MeshMaterial3d<ShieldMaterial>, &mut Visibility), With<ShieldBubble>>,
+    mut fill_query: Query<(&mut Node, &mut BackgroundColor), With<ShieldBarFill>>,
+    time: Res<Time>,
+) {
+    if shield.ripple_age < RIPPLE_DURATION {
+        shield.ripple_age += time.delta_secs();
+    }
+    let wanted = if shield.active { Visibility::Inherited } else { Visibility::Hidden };
+    for (material, mut visibility) in bubble_query.iter_mut() {
+        visibility.set_if_neq(wanted);
+        if !shield.active {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(&material.0) {
+            let settings = &mut material.settings;
+            settings.ripple_direction = shield.ripple_direction;
+            settings.ripple_front = shield.ripple_age * RIPPLE_SPEED;
+            settings.ripple_strength = (1.0 - shield.ripple_age / RIPPLE_DURATION).max(0.0);
+        }
+    }
+
+    let ready = shield.active || shield.energy >= ACTIVATION_COST;
+    for (mut node, mut color) in fill_query.iter_mut() {
+        node.width = Val::Percent(shield.energy / MAX_ENERGY * 100.0);
+        *color = BackgroundColor(if ready { SHIELD_COLOR.with_alpha(1.0) } else { LOW_ENERGY_COLOR });
+    }
+}
+
+// Keep the bubble on the ball once the ball has been drawn where it is this frame
+pub fn follow_player_with_shield(
+    player_query: Query<&Transform, With<Player>>,
+    mut bubble_query: Query<&mut Transform, (With<ShieldBubble>, Without<Player>)>,
+) {
+    let (Ok(player), Ok(mut bubble)) = (player_query.get_single(), bubble_query.get_single_mut()) else {
+        return;
+    };
+    bubble.translation = player.translation;
+}
+
+// Show the energy bar during gameplay
+pub fn show_shield_bar(mut query: Query<&mut Visibility, With<ShieldBarRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+// Hide the energy bar outside of gameplay
+pub fn hide_shield_bar(mut query: Query<&mut Visibility, With<ShieldBarRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Start the next run with the shield down and fully charged
+pub fn reset_shield(mut shield: ResMut<Shield>) {
+    *shield = Shield::default();
+}
+
+// Plugin for the player's shield bubble
+pub struct ShieldPlugin;
+
+impl Plugin for ShieldPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugins(MaterialPlugin::<ShieldMaterial>::default())
+            .init_resource::<Shield>()
+            .add_systems(Startup, setup_shield)
+            .add_systems(OnEnter(GameState::Playing), show_shield_bar)
+            .add_systems(OnExit(GameState::Playing), hide_shield_bar)
+            .add_systems(OnExit(GameState::GameOver), reset_shield)
+            .add_systems(FixedUpdate, run_shield
+                .in_set(SimSet::Movement)
+                .run_if(in_state(GameState::Playing)))
+            // After the boulders have moved this tick
+            .add_systems(FixedUpdate, deflect_boulders
+                .in_set(SimSet::Constraints)
+                .run_if(in_state(GameState::Playing)))
+            .add_systems(Update, update_shield_visuals)
+            .add_systems(PostUpdate, follow_player_with_shield
+                .after(interpolate_positions)
+                .before(TransformSystem::TransformPropagate));
+    }
+}
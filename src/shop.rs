@@ -0,0 +1,522 @@
+use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::TAU;
+use crate::compass::CompassMarker;
+use crate::inventory::{Consumable, Consumables};
+use crate::minimap::MinimapBlip;
+use crate::network::NetSession;
+use crate::photo_mode::PhotoModeState;
+use crate::player::Player;
+use crate::progression::{UpgradeKind, Upgrades};
+use crate::projectile::{Ammo, AmmoKind};
+use crate::save::SaveData;
+use crate::replay::BUTTON_THROW;
+use crate::sim::{latch_input, placement_rng, PauseReason, SimClock, SimInput};
+use crate::teleport::Teleporter;
+use crate::terrain::get_terrain_height;
+use crate::{GameState, RunStart};
+
+// Shop constants
+const SHOP_STREAM: u64 = 16; // Placement random stream for shops
+const SHOP_MIN_DISTANCE: f32 = 6.0; // Closest a shop stands to the teleporter it belongs to
+const SHOP_MAX_DISTANCE: f32 = 10.0; // Farthest it stands
+const SHOP_REACH: f32 = 4.0; // Horizontal distance from a shop the ball can shop from
+const AMMO_BUNDLE: u32 = 5; // Boulders in one ammo purchase
+const UPGRADE_BASE_PRICE: u32 = 80; // Price of an upgrade's first level; each level after costs this much more
+const MAX_UPGRADE_LEVEL: u32 = 3; // Levels of each upgrade the shops sell
+const SHOP_COLOR: Color = Color::srgb(1.0, 0.8, 0.2);
+const BUTTON_COLOR: Color = Color::srgb(0.15, 0.15, 0.15);
+
+// Colors the ball can be bought in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Skin {
+    Gold,
+    Obsidian,
+    Mint,
+}
+
+impl Skin {
+    pub const ALL: [Skin; 3] = [Skin::Gold, Skin::Obsidian, Skin::Mint];
+
+    // Stable id stored in the save file
+    pub fn id(self) -> &'static str {
+        match self {
+            Skin::Gold => "gold",
+            Skin::Obsidian => "obsidian",
+            Skin::Mint => "mint",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Skin::Gold => "Gold skin",
+            Skin::Obsidian => "Obsidian skin",
+            Skin::Mint => "Mint skin",
+        }
+    }
+
+    // Tint over the ball's texture
+    fn color(self) -> Color {
+        match self {
+            Skin::Gold => Color::srgb(1.0, 0.8, 0.3),
+            Skin::Obsidian => Color::srgb(0.3, 0.25, 0.35),
+            Skin::Mint => Color::srgb(0.6, 1.0, 0.8),
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Skin> {
+        Skin::ALL.into_iter().find(|skin| skin.id() == id)
+    }
+}
+
+// Something a shop sells
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShopItem {
+    Ammo(AmmoKind),
+    Consumable(Consumable),
+    Skin(Skin),
+    Upgrade(UpgradeKind),
+}
+
+impl ShopItem {
+    // Everything on sale, in the order the panel lists it
+    pub const ALL: [ShopItem; 10] = [
+        ShopItem::Ammo(AmmoKind::Heavy),
+        ShopItem::Ammo(AmmoKind::Volley),
+        ShopItem::Consumable(Consumable::RepairKit),
+        ShopItem::Consumable(Consumable::WindCharm),
+        ShopItem::Upgrade(UpgradeKind::BlastRadius),
+        ShopItem::Upgrade(UpgradeKind::Reload),
+        ShopItem::Upgrade(UpgradeKind::Traction),
+        ShopItem::Skin(Skin::Gold),
+        ShopItem::Skin(Skin::Obsidian),
+        ShopItem::Skin(Skin::Mint),
+    ];
+
+    // Coins the next purchase costs, or None once there is nothing left to buy
+    fn price(self, save: &SaveData) -> Option<u32> {
+        match self {
+            ShopItem::Ammo(AmmoKind::Heavy) => Some(30),
+            ShopItem::Ammo(AmmoKind::Volley) => Some(40),
+            ShopItem::Ammo(AmmoKind::Stone) => None,
+            ShopItem::Consumable(Consumable::RepairKit) => Some(25),
+            ShopItem::Consumable(Consumable::WindCharm) => Some(35),
+            ShopItem::Skin(skin) if save.owns_skin(skin.id()) => None,
+            ShopItem::Skin(Skin::Gold) => Some(100),
+            ShopItem::Skin(_) => Some(60),
+            ShopItem::Upgrade(kind) => {
+                let level = save.upgrade_level(kind.id());
+                (level < MAX_UPGRADE_LEVEL).then_some(UPGRADE_BASE_PRICE * (level + 1))
+            }
+        }
+    }
+
+    // Button text: what the item is, and its price or how much of it the player has
+    fn label(self, save: &SaveData) -> String {
+        let name = match self {
+            ShopItem::Ammo(kind) => format!("{} x{} (have {})", kind.label(), AMMO_BUNDLE, save.ammo_count(kind)),
+            ShopItem::Consumable(kind) => format!("{} (have {})", kind.label(), save.consumable_count(kind)),
+            ShopItem::Skin(skin) => skin.label().to_string(),
+            ShopItem::Upgrade(kind) => format!("{} ({}/{})", kind.label(), save.upgrade_level(kind.id()), MAX_UPGRADE_LEVEL),
+        };
+        match (self, self.price(save)) {
+            (_, Some(price)) => format!("{} - {} coins", name, price),
+            (ShopItem::Skin(skin), None) if save.inventory.skin.as_deref() == Some(skin.id()) => format!("{} - worn", name),
+            (ShopItem::Skin(_), None) => format!("{} - owned, click to wear", name),
+            (_, None) => format!("{} - sold out", name),
+        }
+    }
+}
+
+// A shop stall standing near a teleporter
+#[derive(Component)]
+pub struct Shop;
+
+// Whether the ball is close enough to a shop to use it, and whether its panel is open
+#[derive(Resource, Default)]
+pub struct ShopState {
+    pub open: bool,
+    pub in_reach: bool,
+}
+
+// Marker for the "Press E" prompt
+#[derive(Component)]
+pub struct ShopPrompt;
+
+// Marker for the shop panel
+#[derive(Component)]
+pub struct ShopRoot;
+
+// Marker for the coin readout on the shop panel
+#[derive(Component)]
+pub struct ShopCoinsText;
+
+// Button buying one item
+#[derive(Component)]
+pub struct ShopButton(pub ShopItem);
+
+// Text on an item's button
+#[derive(Component)]
+pub struct ShopButtonText(pub ShopItem);
+
+fn shop_open(state: Res<ShopState>) -> bool {
+    state.open
+}
+
+// Build the prompt and the shop panel, both hidden until the ball reaches a shop
+pub fn spawn_shop_ui(mut commands: Commands) {
+    commands.spawn((
+        ShopPrompt,
+        Text::new("Press E to shop"),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(SHOP_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(160.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Visibility::Hidden,
+    ));
+
+    commands
+        .spawn((
+            ShopRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            GlobalZIndex(7),
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Shop"),
+                TextFont {
+                    font_size: 28.0,
+                    ..default()
+                },
+                TextColor(SHOP_COLOR),
+            ));
+            parent.spawn((
+                ShopCoinsText,
+                Text::new(""),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            for item in ShopItem::ALL {
+                parent
+                    .spawn((
+                        ShopButton(item),
+                        Button,
+                        Node {
+                            width: Val::Px(380.0),
+                            padding: UiRect::all(Val::Px(6.0)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        BackgroundColor(BUTTON_COLOR),
+                    ))
+                    .with_children(|button| {
+                        button.spawn((
+                            ShopButtonText(item),
+                            Text::new(""),
+                            TextFont {
+                                font_size: 16.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                        ));
+                    });
+            }
+            parent.spawn((
+                Text::new("E to close"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+            ));
+        });
+}
+
+// Put a stall near each teleporter pad as it is placed
+pub fn spawn_shops(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    landmark_query: Query<&Transform, Added<Teleporter>>,
+) {
+    if landmark_query.is_empty() {
+        return;
+    }
+    let counter_mesh = meshes.add(Cuboid::new(2.4, 1.0, 1.2));
+    let roof_mesh = meshes.add(Cuboid::new(2.8, 0.15, 1.6));
+    let post_mesh = meshes.add(Cylinder::new(0.08, 1.4));
+    let wood = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.5, 0.35, 0.2),
+        perceptual_roughness: 0.9,
+        ..default()
+    });
+    let awning = materials.add(StandardMaterial {
+        base_color: SHOP_COLOR,
+        perceptual_roughness: 0.7,
+        ..default()
+    });
+
+    for transform in landmark_query.iter() {
+        let mut rng = placement_rng(transform.translation, SHOP_STREAM);
+        let angle = rng.gen_range(0.0..TAU);
+        let distance = rng.gen_range(SHOP_MIN_DISTANCE..SHOP_MAX_DISTANCE);
+        let site = Vec2::new(transform.translation.x, transform.translation.z) + Vec2::from_angle(angle) * distance;
+        let y = get_terrain_height(site.x, site.y) + 0.5;
+        // Face the counter toward the pad
+        let facing = Quat::from_rotation_y(-angle - std::f32::consts::FRAC_PI_2);
+        commands
+            .spawn((
+                Shop,
+                Mesh3d(counter_mesh.clone()),
+                MeshMaterial3d(wood.clone()),
+                Transform::from_xyz(site.x, y, site.y).with_rotation(facing),
+                Name::new("Shop"),
+                MinimapBlip { color: SHOP_COLOR },
+                CompassMarker { color: SHOP_COLOR },
+            ))
+            .with_children(|parent| {
+                for x in [-1.1, 1.1] {
+                    parent.spawn((
+                        Mesh3d(post_mesh.clone()),
+                        MeshMaterial3d(wood.clone()),
+                        Transform::from_xyz(x, 1.2, -0.5),
+                    ));
+                }
+                parent.spawn((
+                    Mesh3d(roof_mesh.clone()),
+                    MeshMaterial3d(awning.clone()),
+                    Transform::from_xyz(0.0, 1.95, -0.2),
+                ));
+            });
+    }
+}
+
+// Remove every shop along with the teleporters at the end of a run
+pub fn despawn_shops(mut commands: Commands, shop_query: Query<Entity, With<Shop>>) {
+    for entity in shop_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Note whether the ball is at a shop and show the prompt while it is and the panel is shut
+pub fn update_shop_prompt(
+    mut state: ResMut<ShopState>,
+    player_query: Query<&Transform, With<Player>>,
+    shop_query: Query<&Transform, With<Shop>>,
+    mut prompt_query: Query<&mut Visibility, With<ShopPrompt>>,
+) {
+    let in_reach = player_query.get_single().is_ok_and(|player| {
+        shop_query
+            .iter()
+            .any(|shop| shop.translation.xz().distance(player.translation.xz()) < SHOP_REACH)
+    });
+    if state.in_reach != in_reach {
+        state.in_reach = in_reach;
+    }
+    let wanted = if in_reach && !state.open { Visibility::Inherited } else { Visibility::Hidden };
+    for mut visibility in prompt_query.iter_mut() {
+        visibility.set_if_neq(wanted);
+    }
+}
+
+// Open or close the panel; alone, the game holds still while it is open
+fn set_shop_open(
+    open: bool,
+    state: &mut ShopState,
+    clock: &mut SimClock,
+    input: &mut SimInput,
+    alone: bool,
+    root_query: &mut Query<&mut Visibility, With<ShopRoot>>,
+) {
+    state.open = open;
+    if open {
+        if alone {
+            clock.pause(PauseReason::Shop);
+        }
+    } else {
+        clock.resume(PauseReason::Shop);
+        // Clicks on the panel are not throws
+        input.pressed = 0;
+    }
+    for mut visibility in root_query.iter_mut() {
+        *visibility = if open { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+// E opens the panel at a shop and closes it again; rolling away in company closes it too
+pub fn toggle_shop(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<ShopState>,
+    mut clock: ResMut<SimClock>,
+    mut input: ResMut<SimInput>,
+    session: Option<Res<NetSession>>,
+    mut root_query: Query<&mut Visibility, With<ShopRoot>>,
+) {
+    let open = if keys.just_pressed(KeyCode::KeyE) {
+        !state.open && state.in_reach
+    } else {
+        state.open && state.in_reach
+    };
+    if open != state.open {
+        set_shop_open(open, &mut state, &mut clock, &mut input, session.is_none(), &mut root_query);
+    }
+}
+
+// Clicks on the open panel are not throws; in company the game runs on while it is open, so they are
+// taken out of the input every frame rather than only dropped when it closes
+pub fn hold_throws_in_shop(mut input: ResMut<SimInput>) {
+    input.release(BUTTON_THROW);
+}
+
+// Put the panel away when leaving play, like for the pause menu
+pub fn close_shop(
+    mut state: ResMut<ShopState>,
+    mut clock: ResMut<SimClock>,
+    mut input: ResMut<SimInput>,
+    mut root_query: Query<&mut Visibility, With<ShopRoot>>,
+    mut prompt_query: Query<&mut Visibility, (With<ShopPrompt>, Without<ShopRoot>)>,
+) {
+    if state.open {
+        set_shop_open(false, &mut state, &mut clock, &mut input, true, &mut root_query);
+    }
+    for mut visibility in prompt_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Buy what was clicked if the coins stretch to it; clicking an owned skin puts it on instead
+pub fn buy_from_shop(
+    mut save: ResMut<SaveData>,
+    mut upgrades: ResMut<Upgrades>,
+    mut ammo: ResMut<Ammo>,
+    mut consumables: ResMut<Consumables>,
+    button_query: Query<(&Interaction, &ShopButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in button_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let item = button.0;
+        let Some(price) = item.price(&save) else {
+            if let ShopItem::Skin(skin) = item {
+                save.inventory.skin = Some(skin.id().to_string());
+            }
+            continue;
+        };
+        if !save.spend_coins(price) {
+            continue;
+        }
+        match item {
+            ShopItem::Ammo(kind) => {
+                save.add_ammo(kind, AMMO_BUNDLE);
+                // Into the run's stock too, which is what throws come out of
+                *ammo.bought.entry(kind).or_default() += AMMO_BUNDLE;
+            }
+            ShopItem::Consumable(kind) => {
+                save.add_consumable(kind, 1);
+                *consumables.stock.entry(kind).or_default() += 1;
+            }
+            ShopItem::Skin(skin) => {
+                save.add_skin(skin.id());
+                save.inventory.skin = Some(skin.id().to_string());
+            }
+            ShopItem::Upgrade(kind) => {
+                save.raise_upgrade(kind.id());
+                // Bought mid-run, it counts right away as well as from the next run on
+                upgrades.apply(kind);
+            }
+        }
+    }
+}
+
+// Keep the coin readout and the button texts in line with the save
+pub fn update_shop_panel(
+    save: Res<SaveData>,
+    mut coins_query: Query<&mut Text, (With<ShopCoinsText>, Without<ShopButtonText>)>,
+    mut button_query: Query<(&mut Text, &ShopButtonText), Without<ShopCoinsText>>,
+) {
+    for mut text in coins_query.iter_mut() {
+        text.0 = format!("Coins: {}", save.coins);
+    }
+    for (mut text, button) in button_query.iter_mut() {
+        text.0 = button.0.label(&save);
+    }
+}
+
+// Start each run with the upgrades bought at shops
+pub fn grant_shop_upgrades(save: Res<SaveData>, mut upgrades: ResMut<Upgrades>) {
+    for kind in UpgradeKind::ALL {
+        for _ in 0..save.upgrade_level(kind.id()) {
+            upgrades.apply(kind);
+        }
+    }
+}
+
+// Tint the ball with the skin it wears
+pub fn apply_skin(
+    save: Res<SaveData>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    player_query: Query<&MeshMaterial3d<StandardMaterial>, With<Player>>,
+) {
+    let color = save
+        .inventory
+        .skin
+        .as_deref()
+        .and_then(Skin::from_id)
+        .map_or(Color::WHITE, Skin::color);
+    for material in player_query.iter() {
+        // Only touch the material when the tint changes, so it isn't re-uploaded every frame
+        if materials.get(&material.0).is_some_and(|current| current.base_color != color) {
+            if let Some(current) = materials.get_mut(&material.0) {
+                current.base_color = color;
+            }
+        }
+    }
+}
+
+// Plugin for the shops near teleporters and what they sell
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<ShopState>()
+            .add_systems(Startup, spawn_shop_ui)
+            .add_systems(PreUpdate, hold_throws_in_shop.after(latch_input).run_if(shop_open))
+            .add_systems(RunStart, grant_shop_upgrades)
+            .add_systems(OnExit(GameState::Playing), close_shop)
+            .add_systems(OnExit(GameState::GameOver), despawn_shops)
+            .add_systems(Update, (spawn_shops, apply_skin))
+            .add_systems(Update, (update_shop_prompt, toggle_shop)
+                .chain()
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)))
+            .add_systems(Update, (buy_from_shop, update_shop_panel)
+                .chain()
+                .after(toggle_shop)
+                .run_if(shop_open));
+    }
+}
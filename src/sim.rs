@@ -0,0 +1,320 @@
+use bevy::prelude::*;
+use bevy::app::RunFixedMainLoopSystem;
+use bevy::transform::TransformSystem;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use crate::camera::{CameraSettings, MouseLook};
+use crate::input::{update_action_state, Action, ActionState};
+use crate::network::spectating;
+use crate::projectile::{AmmoKind, SelectedAmmo};
+use crate::replay::{InputSample, BUTTON_DASH, BUTTON_JUMP, BUTTON_SHIELD, BUTTON_THROW};
+use crate::terrain::world_seed;
+use crate::GameState;
+
+// Simulation ticks per second; physics and gameplay step at exactly this rate on every machine
+pub const SIM_TICK_RATE: f64 = 60.0;
+
+// Actions that drive the simulation and their button bits
+const SIM_BUTTONS: [(Action, u8); 4] = [
+    (Action::Jump, BUTTON_JUMP),
+    (Action::Throw, BUTTON_THROW),
+    (Action::Dash, BUTTON_DASH),
+    (Action::Shield, BUTTON_SHIELD),
+];
+
+// Steps of one simulation tick, in order: moving scenery first, then everything that moves through it,
+// then the rules that push things back into place
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SimSet {
+    Environment,
+    Movement,
+    Constraints,
+}
+
+// Clock of the current run in ticks; gameplay reads time and randomness from here so a run plays out
+// the same for the same seed and inputs, which replays and lockstep multiplayer rely on
+#[derive(Resource, Default)]
+pub struct Simulation {
+    pub tick: u32,
+}
+
+impl Simulation {
+    // Seconds of simulation since the run started
+    pub fn elapsed(&self) -> f32 {
+        (self.tick as f64 / SIM_TICK_RATE) as f32
+    }
+
+    // Random generator for this tick, seeded from the world seed. Each system passes its own stream so
+    // the draws don't depend on which system happens to run first.
+    pub fn rng(&self, stream: u64) -> StdRng {
+        let seed = world_seed() as u64
+            ^ (self.tick as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ stream.wrapping_mul(0x1656_67B1_9E37_79F9);
+        StdRng::seed_from_u64(seed)
+    }
+}
+
+// Slowest and fastest the simulation can be run
+const MIN_TIME_SCALE: f32 = 0.1;
+const MAX_TIME_SCALE: f32 = 2.0;
+
+// Something holding the simulation still; it runs again once nothing is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PauseReason {
+    // The pause menu is open
+    Menu,
+    PhotoMode,
+    // The player froze the game with the pause key
+    Player,
+    // The player is rewinding, which steps the simulation back instead
+    Rewind,
+    // The exploration map is open
+    Map,
+    // A shop's panel is open
+    Shop,
+}
+
+// How fast the simulation runs. Ticks always cover the same time, so slow motion runs fewer of them a
+// second and a pause none, and a run plays out the same at any speed. Everything that stops or slows the
+// game goes through here instead of touching virtual time itself, so lifting one pause can't lift another.
+#[derive(Resource, Debug)]
+pub struct SimClock {
+    time_scale: f32,
+    paused: Vec<PauseReason>,
+}
+
+impl Default for SimClock {
+    fn default() -> Self {
+        Self { time_scale: 1.0, paused: Vec::new() }
+    }
+}
+
+impl SimClock {
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    pub fn set_time_scale(&mut self, scale: f32) {
+        self.time_scale = scale.clamp(MIN_TIME_SCALE, MAX_TIME_SCALE);
+    }
+
+    pub fn pause(&mut self, reason: PauseReason) {
+        if !self.paused.contains(&reason) {
+            self.paused.push(reason);
+        }
+    }
+
+    pub fn resume(&mut self, reason: PauseReason) {
+        self.paused.retain(|paused| *paused != reason);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        !self.paused.is_empty()
+    }
+
+    pub fn paused_by(&self, reason: PauseReason) -> bool {
+        self.paused.contains(&reason)
+    }
+}
+
+// Random generator tied to a spot in the world rather than a tick, for things set off by other
+// spawns or events whose frame can vary; the same spot always gets the same draws
+pub fn placement_rng(position: Vec3, stream: u64) -> StdRng {
+    let bits = position.to_array().map(|value| (value * 100.0).round() as i32 as u32 as u64);
+    let seed = world_seed() as u64
+        ^ bits[0].wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ bits[1].wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+        ^ bits[2].wrapping_mul(0x27D4_EB2F_1656_67C5)
+        ^ stream.wrapping_mul(0x1656_67B1_9E37_79F9);
+    StdRng::seed_from_u64(seed)
+}
+
+// Player input as the simulation sees it, held steady between ticks. Presses are kept until a tick
+// consumes them, so a tap isn't lost when a frame runs no ticks or doubled when it runs two.
+#[derive(Resource, Default)]
+pub struct SimInput {
+    // Movement as (right, forward), quantized the same way a replay stores it
+    pub move_axis: Vec2,
+    // Camera yaw that turns movement into world space
+    pub yaw: f32,
+    // Aim point on the terrain, once the cursor has found it
+    pub aim: Option<Vec3>,
+    // BUTTON_* bits held and pressed since the last tick
+    pub held: u8,
+    pub pressed: u8,
+    // Kind of boulder the next throw uses
+    pub ammo: AmmoKind,
+}
+
+impl SimInput {
+    pub fn held(&self, button: u8) -> bool {
+        self.held & button != 0
+    }
+
+    pub fn just_pressed(&self, button: u8) -> bool {
+        self.pressed & button != 0
+    }
+
+    // Take in one frame of actions, quantized the way a replay stores them; presses add up until a
+    // tick uses them
+    pub fn latch(&mut self, actions: &ActionState, yaw: f32, aim: Option<Vec3>, ammo: AmmoKind) {
+        let mut held = 0;
+        for (action, button) in SIM_BUTTONS {
+            if actions.pressed(action) {
+                held |= button;
+            }
+            if actions.just_pressed(action) {
+                self.pressed |= button;
+            }
+        }
+        let sample = InputSample::new(0, actions.move_axis(), held, 0, yaw, aim, ammo);
+        self.move_axis = sample.move_axis();
+        self.yaw = yaw;
+        self.aim = aim;
+        self.held = held;
+        self.ammo = ammo;
+    }
+
+    // Let go of buttons, held or pressed, e.g. while clicks go to a panel instead of the game
+    pub fn release(&mut self, buttons: u8) {
+        self.held &= !buttons;
+        self.pressed &= !buttons;
+    }
+
+    // Movement in world space, at most unit length (forward is -Z before the yaw)
+    pub fn move_direction(&self) -> Vec3 {
+        let direction = Vec3::new(self.move_axis.x, 0.0, -self.move_axis.y);
+        if direction.length_squared() > 0.0 {
+            Quat::from_rotation_y(self.yaw) * direction
+        } else {
+            direction
+        }
+    }
+}
+
+// Something moved by the simulation whose rendered position is blended between its last two ticks, so
+// motion looks smooth at any frame rate while the simulation itself stays at SIM_TICK_RATE
+#[derive(Component, Default)]
+pub struct Interpolated {
+    // Position before and after the latest tick
+    previous: Vec3,
+    current: Vec3,
+    // Position last written for rendering, to notice when something else moved the entity
+    rendered: Vec3,
+}
+
+impl Interpolated {
+    pub fn new(position: Vec3) -> Self {
+        Self { previous: position, current: position, rendered: position }
+    }
+
+    // Jump straight to a position, like after a teleport or a respawn
+    fn snap(&mut self, position: Vec3) {
+        *self = Self::new(position);
+    }
+}
+
+// Run virtual time, and with it FixedUpdate and everything else driven by Time, as the clock says
+pub fn apply_sim_clock(clock: Res<SimClock>, mut time: ResMut<Time<Virtual>>) {
+    if clock.is_paused() {
+        time.pause();
+    } else {
+        time.unpause();
+    }
+    time.set_relative_speed(clock.time_scale);
+}
+
+// Start every run from tick zero so its timing and random draws only depend on the seed
+pub fn reset_simulation(mut simulation: ResMut<Simulation>, mut input: ResMut<SimInput>) {
+    simulation.tick = 0;
+    *input = SimInput::default();
+}
+
+// Copy this frame's input into the simulation
+pub fn latch_input(
+    mut input: ResMut<SimInput>,
+    actions: Res<ActionState>,
+    camera_settings: Res<CameraSettings>,
+    mouse_look: Res<MouseLook>,
+    selected: Res<SelectedAmmo>,
+) {
+    let aim = mouse_look.is_initialized.then_some(mouse_look.target_position);
+    input.latch(&actions, camera_settings.yaw, aim, selected.0);
+}
+
+// Before a tick, put every interpolated entity back where the simulation left it; anything moved
+// outside the simulation since, like by a teleport, keeps its new spot
+pub fn restore_simulated_positions(mut query: Query<(&mut Transform, &mut Interpolated)>) {
+    for (mut transform, mut interpolated) in query.iter_mut() {
+        if transform.translation != interpolated.rendered {
+            interpolated.snap(transform.translation);
+        }
+        interpolated.previous = interpolated.current;
+        transform.translation = interpolated.current;
+    }
+}
+
+// After a tick, remember where the simulation put everything
+pub fn record_simulated_positions(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        interpolated.current = transform.translation;
+        interpolated.rendered = transform.translation;
+    }
+}
+
+// Draw every interpolated entity part way between its last two ticks, by how far the clock has run
+// into the next one
+pub fn interpolate_positions(fixed_time: Res<Time<Fixed>>, mut query: Query<(&mut Transform, &mut Interpolated)>) {
+    let blend = fixed_time.overstep_fraction();
+    for (mut transform, mut interpolated) in query.iter_mut() {
+        if transform.translation != interpolated.rendered {
+            interpolated.snap(transform.translation);
+            continue;
+        }
+        let position = interpolated.previous.lerp(interpolated.current, blend);
+        transform.translation = position;
+        interpolated.rendered = position;
+    }
+}
+
+// Finish a tick: count it and use up the presses it saw
+pub fn advance_simulation(mut simulation: ResMut<Simulation>, mut input: ResMut<SimInput>) {
+    simulation.tick += 1;
+    input.pressed = 0;
+}
+
+// Plugin for the fixed-rate simulation clock, its input and its random streams. A headless app has no
+// keyboard or screen, so it leaves out latching the input and interpolating what is drawn.
+#[derive(Default)]
+pub struct SimPlugin {
+    pub headless: bool,
+}
+
+impl Plugin for SimPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Time::<Fixed>::from_hz(SIM_TICK_RATE))
+            .init_resource::<Simulation>()
+            .init_resource::<SimClock>()
+            .init_resource::<SimInput>()
+            .configure_sets(FixedUpdate, (SimSet::Environment, SimSet::Movement, SimSet::Constraints).chain())
+            .add_systems(OnExit(GameState::Loading), reset_simulation)
+            .add_systems(OnExit(GameState::GameOver), reset_simulation)
+            // After state transitions, so pauses from opening a menu are in by the time the ticks run
+            .add_systems(RunFixedMainLoop, apply_sim_clock
+                .run_if(resource_changed::<SimClock>)
+                .in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop))
+            .add_systems(FixedFirst, restore_simulated_positions)
+            .add_systems(FixedLast, (record_simulated_positions, advance_simulation));
+        if self.headless {
+            return;
+        }
+        app
+            // Spectators have no say in the simulation, so their input never reaches it
+            .add_systems(PreUpdate, latch_input
+                .after(update_action_state)
+                .run_if(in_state(GameState::Playing))
+                .run_if(not(spectating)))
+            .add_systems(PostUpdate, interpolate_positions.before(TransformSystem::TransformPropagate));
+    }
+}
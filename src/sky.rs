@@ -0,0 +1,255 @@
+use bevy::prelude::*;
+use bevy::color::{ColorToComponents, Mix};
+use bevy::pbr::NotShadowCaster;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_resource::Face;
+use bevy::render::view::NoFrustumCulling;
+use bevy::transform::TransformSystem;
+use std::f32::consts::{FRAC_PI_2, TAU};
+use crate::audio::AmbientConditions;
+use crate::camera::FollowCamera;
+
+// Sky dome constants
+const SKY_RADIUS: f32 = 900.0; // Inside the camera's far plane, past everything else
+const SKY_RINGS: usize = 24; // Rings from just below the horizon up to the zenith
+const SKY_SEGMENTS: usize = 48; // Vertices around each ring
+const SKY_LOWEST: f32 = -0.25; // Elevation of the dome's rim in radians, below the horizon so hills never show its edge
+const SUN_DISTANCE: f32 = SKY_RADIUS * 0.9;
+const SUN_SIZE: f32 = 28.0; // Disc radius at SUN_DISTANCE
+const MOON_SIZE: f32 = 18.0;
+const DUSK_HEIGHT: f32 = 0.3; // Sun elevation, as the sine of its angle, below which the sky around it warms up
+const GLOW_SHARPNESS: f32 = 6.0; // How tightly the dusk glow gathers around the sun
+const SUN_CHANGE: f32 = 0.001; // Sun movement, as a change of direction, that recolors the sky
+
+// Distance fog: terrain fades into the horizon color, gone by the far edge of the horizon imposter
+const FOG_START: f32 = 120.0;
+const FOG_END: f32 = 380.0;
+
+// Sky colors by day and by night
+const DAY_ZENITH: Color = Color::srgb(0.25, 0.5, 0.9);
+const DAY_HORIZON: Color = Color::srgb(0.72, 0.82, 0.92);
+const NIGHT_ZENITH: Color = Color::srgb(0.01, 0.015, 0.04);
+const NIGHT_HORIZON: Color = Color::srgb(0.05, 0.07, 0.12);
+const DUSK_GLOW: Color = Color::srgb(1.0, 0.5, 0.22);
+const SUN_COLOR: Color = Color::srgb(1.0, 0.96, 0.85);
+const MOON_COLOR: Color = Color::srgb(0.75, 0.8, 0.9);
+
+// Marker for the sky dome, kept centered on the camera
+#[derive(Component)]
+pub struct SkyDome;
+
+// Markers for the sun and moon discs, children of the dome
+#[derive(Component)]
+pub struct SunDisc;
+
+#[derive(Component)]
+pub struct MoonDisc;
+
+// The procedural sky: a dome colored from the sun's direction, with the sun and moon on it and the
+// distance fog matched to its horizon. It follows the directional light, so whatever moves the sun
+// moves the sky with it.
+#[derive(Resource)]
+pub struct Sky {
+    mesh: Handle<Mesh>,
+    sun_material: Handle<StandardMaterial>,
+    // Direction toward the sun the sky was last colored for
+    colored_for: Option<Vec3>,
+}
+
+// How much daylight there is with the sun at an elevation, from 0 at night to 1 by day
+pub fn daylight(sun_height: f32) -> f32 {
+    ((sun_height + 0.1) / 0.35).clamp(0.0, 1.0)
+}
+
+// How warm the light around the sun is, strongest with the sun on the horizon
+fn dusk(sun_height: f32) -> f32 {
+    (1.0 - sun_height.abs() / DUSK_HEIGHT).clamp(0.0, 1.0)
+}
+
+// Color at the horizon, which the fog and the clear color share
+pub fn horizon_color(sun: Vec3) -> Color {
+    NIGHT_HORIZON.mix(&DAY_HORIZON, daylight(sun.y))
+}
+
+// Sky color looking in a direction with the sun in another
+fn sky_color(direction: Vec3, sun: Vec3) -> LinearRgba {
+    let zenith = NIGHT_ZENITH.mix(&DAY_ZENITH, daylight(sun.y));
+    let height = direction.y.max(0.0).sqrt();
+    let color = horizon_color(sun).mix(&zenith, height);
+    let glow = direction.dot(sun).max(0.0).powf(GLOW_SHARPNESS) * dusk(sun.y);
+    color.mix(&DUSK_GLOW, glow).to_linear()
+}
+
+// Distance fog for a camera; its colors follow the sky
+pub fn distance_fog() -> DistanceFog {
+    DistanceFog {
+        color: DAY_HORIZON,
+        falloff: FogFalloff::Linear { start: FOG_START, end: FOG_END },
+        ..default()
+    }
+}
+
+// Positions and inward normals of the dome, rim first; colors are filled in by color_sky
+fn create_sky_mesh() -> Mesh {
+    let mut positions = Vec::with_capacity((SKY_RINGS + 1) * (SKY_SEGMENTS + 1));
+    let mut normals = Vec::with_capacity(positions.capacity());
+    for ring in 0..=SKY_RINGS {
+        let elevation = SKY_LOWEST + (FRAC_PI_2 - SKY_LOWEST) * ring as f32 / SKY_RINGS as f32;
+        for segment in 0..=SKY_SEGMENTS {
+            let azimuth = segment as f32 / SKY_SEGMENTS as f32 * TAU;
+            let direction = Vec3::new(elevation.cos() * azimuth.cos(), elevation.sin(), elevation.cos() * azimuth.sin());
+            positions.push((direction * SKY_RADIUS).to_array());
+            normals.push((-direction).to_array());
+        }
+    }
+    let row = SKY_SEGMENTS as u32 + 1;
+    let mut indices = Vec::with_capacity(SKY_RINGS * SKY_SEGMENTS * 6);
+    for ring in 0..SKY_RINGS as u32 {
+        for segment in 0..SKY_SEGMENTS as u32 {
+            let low = ring * row + segment;
+            let high = low + row;
+            indices.extend_from_slice(&[low, high, low + 1, low + 1, high, high + 1]);
+        }
+    }
+    let colors = vec![[1.0; 4]; positions.len()];
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, Default::default());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.insert_indices(Indices::U32(indices));
+    mesh
+}
+
+// Spawn the dome with the sun and moon on it
+pub fn setup_sky(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, mut materials: ResMut<Assets<StandardMaterial>>) {
+    let mesh = meshes.add(create_sky_mesh());
+    // Seen from inside, unlit, and left out of the fog it sets the color of
+    let dome_material = materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        unlit: true,
+        fog_enabled: false,
+        cull_mode: Some(Face::Front),
+        ..default()
+    });
+    let disc_material = |color: Color| StandardMaterial {
+        base_color: color,
+        unlit: true,
+        fog_enabled: false,
+        ..default()
+    };
+    let sun_material = materials.add(disc_material(SUN_COLOR));
+    let moon_material = materials.add(disc_material(MOON_COLOR));
+    let disc = meshes.add(Sphere::new(1.0));
+
+    commands
+        .spawn((
+            SkyDome,
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(dome_material),
+            Transform::default(),
+            NoFrustumCulling,
+            NotShadowCaster,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                SunDisc,
+                Mesh3d(disc.clone()),
+                MeshMaterial3d(sun_material.clone()),
+                Transform::from_scale(Vec3::splat(SUN_SIZE)),
+                NotShadowCaster,
+            ));
+            parent.spawn((
+                MoonDisc,
+                Mesh3d(disc),
+                MeshMaterial3d(moon_material),
+                Transform::from_scale(Vec3::splat(MOON_SIZE)),
+                NotShadowCaster,
+            ));
+        });
+    commands.insert_resource(Sky { mesh, sun_material, colored_for: None });
+}
+
+// Recolor the sky, fog and discs when the sun moves, and tell the ambient sounds how light it is
+pub fn color_sky(
+    mut sky: ResMut<Sky>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut clear_color: ResMut<ClearColor>,
+    mut conditions: ResMut<AmbientConditions>,
+    light_query: Query<&Transform, With<DirectionalLight>>,
+    mut fog_query: Query<&mut DistanceFog>,
+    mut sun_query: Query<(&mut Transform, &mut Visibility), (With<SunDisc>, Without<DirectionalLight>)>,
+    mut moon_query: Query<(&mut Transform, &mut Visibility), (With<MoonDisc>, Without<DirectionalLight>, Without<SunDisc>)>,
+) {
+    let Ok(light_transform) = light_query.get_single() else {
+        return;
+    };
+    // The light shines along its forward direction, so the sun is behind it
+    let sun = light_transform.back().as_vec3();
+    let sun_moved = !sky.colored_for.is_some_and(|colored| colored.dot(sun) > 1.0 - SUN_CHANGE);
+
+    // Cameras spawned later, like a split-screen guest's, pick up the current haze straight away
+    let horizon = horizon_color(sun);
+    for mut fog in fog_query.iter_mut().filter(|fog| sun_moved || fog.is_added()) {
+        fog.color = horizon;
+        // Looking toward a low sun the haze glows like the sky around it
+        fog.directional_light_color = DUSK_GLOW.with_alpha(dusk(sun.y) * 0.5);
+    }
+    if !sun_moved {
+        return;
+    }
+    sky.colored_for = Some(sun);
+
+    if let Some(mesh) = meshes.get_mut(&sky.mesh) {
+        let colors: Option<Vec<[f32; 4]>> = mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+            .and_then(|positions| positions.as_float3())
+            .map(|positions| {
+                positions
+                    .iter()
+                    .map(|position| sky_color(Vec3::from_array(*position).normalize(), sun).to_f32_array())
+                    .collect()
+            });
+        if let Some(colors) = colors {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        }
+    }
+    if let Some(material) = materials.get_mut(&sky.sun_material) {
+        material.base_color = SUN_COLOR.mix(&DUSK_GLOW, dusk(sun.y));
+    }
+
+    clear_color.0 = horizon;
+    conditions.daylight = daylight(sun.y);
+
+    if let Ok((mut transform, mut visibility)) = sun_query.get_single_mut() {
+        transform.translation = sun * SUN_DISTANCE;
+        *visibility = if sun.y > SKY_LOWEST.sin() { Visibility::Inherited } else { Visibility::Hidden };
+    }
+    if let Ok((mut transform, mut visibility)) = moon_query.get_single_mut() {
+        transform.translation = -sun * SUN_DISTANCE;
+        *visibility = if -sun.y > SKY_LOWEST.sin() { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+// Keep the dome centered on the camera so it always looks infinitely far away
+pub fn follow_camera_with_sky(
+    camera_query: Query<&Transform, With<FollowCamera>>,
+    mut dome_query: Query<&mut Transform, (With<SkyDome>, Without<FollowCamera>)>,
+) {
+    let (Ok(camera), Ok(mut dome)) = (camera_query.get_single(), dome_query.get_single_mut()) else {
+        return;
+    };
+    dome.translation = camera.translation;
+}
+
+// Plugin for the sky, sun, moon and distance fog
+pub struct SkyPlugin;
+
+impl Plugin for SkyPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(ClearColor(DAY_HORIZON))
+            .add_systems(Startup, setup_sky)
+            .add_systems(Update, color_sky)
+            .add_systems(PostUpdate, follow_camera_with_sky.before(TransformSystem::TransformPropagate));
+    }
+}
@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+// Spatial grid constants
+const CELL_SIZE: f32 = 8.0; // Width of a grid cell; near the usual query radius so a lookup touches a few cells
+const POSITION_SLACK: f32 = 1.0; // Extra reach for things that moved since the grid was built
+
+// Something other systems look up by position, and how far it reaches from its center
+#[derive(Component)]
+pub struct Spatial {
+    pub radius: f32,
+}
+
+impl Spatial {
+    pub fn new(radius: f32) -> Self {
+        Self { radius }
+    }
+}
+
+// Spatial entities bucketed into square cells on the ground plane, rebuilt at the start of every frame
+// so proximity checks only look at what's near instead of scanning everything
+#[derive(Resource, Default)]
+pub struct SpatialGrid {
+    // Entities in each cell, with their position and reach when the grid was built; an entity is in
+    // every cell its reach touches
+    cells: HashMap<(i32, i32), Vec<(Entity, Vec3, f32)>>,
+}
+
+impl SpatialGrid {
+    // Cells overlapped by a circle on the ground plane
+    fn cells_around(center: Vec3, radius: f32) -> impl Iterator<Item = (i32, i32)> {
+        let cell = |value: f32| (value / CELL_SIZE).floor() as i32;
+        let (min_x, max_x) = (cell(center.x - radius), cell(center.x + radius));
+        let (min_z, max_z) = (cell(center.z - radius), cell(center.z + radius));
+        (min_z..=max_z).flat_map(move |z| (min_x..=max_x).map(move |x| (x, z)))
+    }
+
+    // Empty the grid, keeping the storage of cells that were in use
+    pub fn clear(&mut self) {
+        self.cells.retain(|_, cell| !cell.is_empty());
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+    }
+
+    // Add an entity reaching radius from position
+    pub fn insert(&mut self, entity: Entity, position: Vec3, radius: f32) {
+        for key in Self::cells_around(position, radius + POSITION_SLACK) {
+            self.cells.entry(key).or_default().push((entity, position, radius));
+        }
+    }
+
+    // Entities whose reach may come within radius of a point, measured across the ground so height never
+    // hides anything. This can include a few that are too far, so callers still check the exact distance
+    // against the entity's current transform.
+    pub fn nearby(&self, center: Vec3, radius: f32) -> Vec<Entity> {
+        let mut found: Vec<Entity> = Self::cells_around(center, radius)
+            .filter_map(|key| self.cells.get(&key))
+            .flatten()
+            .filter(|(_, position, reach)| position.xz().distance(center.xz()) <= radius + reach + POSITION_SLACK)
+            .map(|(entity, _, _)| *entity)
+            .collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+}
+
+// Rebuild the grid from where everything was drawn last frame
+pub fn rebuild_spatial_grid(mut grid: ResMut<SpatialGrid>, query: Query<(Entity, &GlobalTransform, &Spatial)>) {
+    grid.clear();
+    for (entity, transform, spatial) in query.iter() {
+        grid.insert(entity, transform.translation(), spatial.radius);
+    }
+}
+
+// Plugin for the spatial grid used by proximity checks
+pub struct SpatialPlugin;
+
+impl Plugin for SpatialPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<SpatialGrid>()
+            .add_systems(First, rebuild_spatial_grid);
+    }
+}
@@ -0,0 +1,250 @@
+use bevy::prelude::*;
+use bevy::input::mouse::MouseMotion;
+use crate::camera::{CameraSettings, FollowCamera, TargetCursor};
+use crate::network::{player_color, spectating, NetProjectile, NetSession, RemotePlayer};
+use crate::photo_mode::PhotoModeState;
+use crate::player::Player;
+use crate::projectile::{ballistic_position, Projectile};
+use crate::terrain::get_terrain_height;
+use crate::tuning::LiveTuning;
+use crate::GameState;
+
+// Spectator camera constants
+const FREE_MOVE_SPEED: f32 = 12.0; // Free camera speed in units per second
+const FREE_FAST_MULTIPLIER: f32 = 3.0; // Speed multiplier while holding Ctrl
+const FREE_LOOK_SENSITIVITY: f32 = 0.004; // Radians per pixel of mouse drag
+const FREE_MIN_CLEARANCE: f32 = 0.5; // Closest the free camera gets to the terrain
+const FOLLOW_SMOOTHING: f32 = 6.0; // How quickly the camera settles behind a followed player
+const ARC_TIME_STEP: f32 = 0.05; // Seconds between points on a drawn boulder arc
+const ARC_MAX_STEPS: usize = 400; // Upper bound on arc points for boulders that never land
+
+// Where a spectator is looking: following a player, or flying freely
+#[derive(Resource, Default)]
+pub struct SpectatorCamera {
+    // Player being followed, or None for the free camera
+    pub following: Option<u8>,
+    // Free camera orientation
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+// Marker for the spectator status panel
+#[derive(Component)]
+pub struct SpectatorHudRoot;
+
+// Marker for the spectator status text
+#[derive(Component)]
+pub struct SpectatorHudText;
+
+// Free camera orientation matching a camera's current view
+fn look_from(transform: &Transform) -> (f32, f32) {
+    let (yaw, pitch, _) = transform.rotation.to_euler(EulerRot::YXZ);
+    (yaw, pitch)
+}
+
+// Start each match on the free camera, looking the way the camera already faces
+pub fn reset_spectator_camera(
+    mut spectator: ResMut<SpectatorCamera>,
+    camera_query: Query<&Transform, With<FollowCamera>>,
+) {
+    spectator.following = None;
+    if let Ok(transform) = camera_query.get_single() {
+        (spectator.yaw, spectator.pitch) = look_from(transform);
+    }
+}
+
+// Hide this game's own ball and aim cursor, which a spectator doesn't use, and show the status panel
+pub fn show_spectator_view(
+    mut player_query: Query<&mut Visibility, With<Player>>,
+    mut cursor_query: Query<&mut Visibility, (With<TargetCursor>, Without<Player>)>,
+    mut hud_query: Query<&mut Visibility, (With<SpectatorHudRoot>, Without<Player>, Without<TargetCursor>)>,
+) {
+    for mut visibility in player_query.iter_mut().chain(cursor_query.iter_mut()) {
+        *visibility = Visibility::Hidden;
+    }
+    for mut visibility in hud_query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+// Bring the ball back and hide the status panel when play stops
+pub fn hide_spectator_view(
+    mut player_query: Query<&mut Visibility, With<Player>>,
+    mut hud_query: Query<&mut Visibility, (With<SpectatorHudRoot>, Without<Player>)>,
+) {
+    for mut visibility in player_query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+    for mut visibility in hud_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Follow the next player with F, going back to the free camera after the last one
+pub fn cycle_follow_target(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut spectator: ResMut<SpectatorCamera>,
+    remote_query: Query<&RemotePlayer>,
+    camera_query: Query<&Transform, With<FollowCamera>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyF) {
+        return;
+    }
+    let mut ids: Vec<u8> = remote_query.iter().map(|remote| remote.id).collect();
+    ids.sort_unstable();
+    let next = match spectator.following {
+        None => ids.first().copied(),
+        Some(current) => ids.into_iter().find(|id| *id > current),
+    };
+    if next.is_none() {
+        // Pick up the free camera from wherever the follow view left it
+        if let Ok(transform) = camera_query.get_single() {
+            (spectator.yaw, spectator.pitch) = look_from(transform);
+        }
+    }
+    spectator.following = next;
+}
+
+// Drive the camera: orbit behind the followed player like the normal follow camera, or fly freely with
+// WASD, E/Q for up/down, right-drag to look and Ctrl to go faster
+pub fn spectator_camera_control(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut motion_events: EventReader<MouseMotion>,
+    mut spectator: ResMut<SpectatorCamera>,
+    settings: Res<CameraSettings>,
+    remote_query: Query<(&RemotePlayer, &Transform), Without<FollowCamera>>,
+    mut camera_query: Query<&mut Transform, With<FollowCamera>>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    let drag: Vec2 = motion_events.read().map(|event| event.delta).sum();
+    let Ok(mut transform) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    // A followed player that left hands back to the free camera
+    let followed = spectator
+        .following
+        .and_then(|id| remote_query.iter().find(|(remote, _)| remote.id == id))
+        .map(|(_, remote_transform)| remote_transform.translation);
+    if let Some(target) = followed {
+        let wanted = target + settings.orbit_direction() * settings.follow_distance;
+        transform.translation = transform.translation.lerp(wanted, (FOLLOW_SMOOTHING * delta).min(1.0));
+        transform.look_at(target + Vec3::Y * 0.5, Vec3::Y);
+        return;
+    }
+    if spectator.following.is_some() {
+        spectator.following = None;
+        (spectator.yaw, spectator.pitch) = look_from(&transform);
+    }
+
+    if mouse_buttons.pressed(MouseButton::Right) {
+        spectator.yaw -= drag.x * FREE_LOOK_SENSITIVITY;
+        spectator.pitch = (spectator.pitch - drag.y * FREE_LOOK_SENSITIVITY).clamp(-1.5, 1.5);
+    }
+    transform.rotation = Quat::from_euler(EulerRot::YXZ, spectator.yaw, spectator.pitch, 0.0);
+
+    let mut movement = Vec3::ZERO;
+    if keys.pressed(KeyCode::KeyW) { movement += *transform.forward(); }
+    if keys.pressed(KeyCode::KeyS) { movement -= *transform.forward(); }
+    if keys.pressed(KeyCode::KeyA) { movement -= *transform.right(); }
+    if keys.pressed(KeyCode::KeyD) { movement += *transform.right(); }
+    if keys.pressed(KeyCode::KeyE) { movement += Vec3::Y; }
+    if keys.pressed(KeyCode::KeyQ) { movement -= Vec3::Y; }
+    if movement.length_squared() > 0.0 {
+        let speed = if keys.pressed(KeyCode::ControlLeft) {
+            FREE_MOVE_SPEED * FREE_FAST_MULTIPLIER
+        } else {
+            FREE_MOVE_SPEED
+        };
+        transform.translation += movement.normalize() * speed * delta;
+    }
+    let position = transform.translation;
+    let floor = get_terrain_height(position.x, position.z) + FREE_MIN_CLEARANCE;
+    transform.translation.y = position.y.max(floor);
+}
+
+// Draw the full arc of every boulder in the air, in the color of the player who threw it
+pub fn draw_player_arcs(
+    mut gizmos: Gizmos,
+    projectile_query: Query<(&Projectile, &NetProjectile)>,
+    tuning: Res<LiveTuning>,
+) {
+    for (projectile, net) in projectile_query.iter().filter(|(projectile, _)| !projectile.stuck) {
+        let mut points = Vec::new();
+        for i in 0..ARC_MAX_STEPS {
+            let point = ballistic_position(&tuning.0.projectile, projectile.start_position, projectile.initial_velocity, i as f32 * ARC_TIME_STEP);
+            points.push(point);
+            if i > 0 && point.y <= get_terrain_height(point.x, point.z) {
+                break;
+            }
+        }
+        gizmos.linestrip(points, player_color(net.owner).with_alpha(0.8));
+    }
+}
+
+// Build the spectator status line at the top of the screen
+pub fn spawn_spectator_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            SpectatorHudRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(48.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                SpectatorHudText,
+                Text::new(""),
+                TextFont {
+                    font_size: 22.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+// Say who is being watched and how to switch
+pub fn update_spectator_hud(
+    spectator: Res<SpectatorCamera>,
+    session: Res<NetSession>,
+    mut text_query: Query<(&mut Text, &mut TextColor), With<SpectatorHudText>>,
+) {
+    let (status, color) = match spectator.following {
+        Some(id) => (
+            format!("Spectating {}", session.player_name(id).unwrap_or("a player")),
+            player_color(id),
+        ),
+        None => ("Spectating: free camera (WASD, E/Q, right-drag)".to_string(), Color::WHITE),
+    };
+    for (mut text, mut text_color) in text_query.iter_mut() {
+        text.0 = format!("{}   F: next player", status);
+        text_color.0 = color;
+    }
+}
+
+// Plugin for watching a multiplayer match without playing
+pub struct SpectatorPlugin;
+
+impl Plugin for SpectatorPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<SpectatorCamera>()
+            .add_systems(Startup, spawn_spectator_hud)
+            .add_systems(OnExit(GameState::Loading), reset_spectator_camera.run_if(spectating))
+            .add_systems(OnEnter(GameState::Playing), show_spectator_view.run_if(spectating))
+            .add_systems(OnExit(GameState::Playing), hide_spectator_view)
+            .add_systems(Update, (cycle_follow_target, spectator_camera_control, draw_player_arcs, update_spectator_hud)
+                .chain()
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive))
+                .run_if(spectating));
+    }
+}
@@ -0,0 +1,150 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::game_events::GameEvent;
+use crate::photo_mode::PhotoModeState;
+use crate::player::{Health, Player, PlayerPhysics};
+use crate::profile::ActiveProfile;
+use crate::projectile::{Hostile, Projectile};
+use crate::GameState;
+
+// Stats file name inside the profile folder
+const STATS_FILE: &str = "stats.ron";
+
+// Totals across every run a profile has played
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifetimeStats {
+    // Horizontal distance rolled in meters
+    pub distance_rolled: f32,
+    // Seconds spent in the air
+    pub airtime: f32,
+    pub boulders_thrown: u32,
+    pub targets_destroyed: u32,
+    // Largest drop from the top of a jump or fall to a landing the player lived through
+    pub highest_fall: f32,
+    // Position last frame and the highest point of the current time in the air, not saved
+    #[serde(skip)]
+    last_position: Option<Vec3>,
+    #[serde(skip)]
+    fall_start: Option<f32>,
+}
+
+impl LifetimeStats {
+    // Read a profile's stats, starting from zero if they are missing or invalid
+    pub fn load(profile: &ActiveProfile) -> Self {
+        let path = profile.file(STATS_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => match ron::from_str(&contents) {
+                Ok(stats) => stats,
+                Err(error) => {
+                    println!("Invalid stats file {}: {}", path.display(), error);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    // Write a profile's stats, creating its folder if needed
+    pub fn write(&self, profile: &ActiveProfile) {
+        let path = profile.file(STATS_FILE);
+        let result = std::fs::create_dir_all(profile.dir()).and_then(|_| {
+            let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+                .map_err(std::io::Error::other)?;
+            std::fs::write(&path, contents)
+        });
+        if let Err(error) = result {
+            println!("Failed to write stats file {}: {}", path.display(), error);
+        }
+    }
+
+    // One line per stat for the stats screen
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            format!("Distance rolled: {:.0} m", self.distance_rolled),
+            format!("Airtime: {:.0} s", self.airtime),
+            format!("Boulders thrown: {}", self.boulders_thrown),
+            format!("Targets destroyed: {}", self.targets_destroyed),
+            format!("Highest fall survived: {:.1} m", self.highest_fall),
+        ]
+    }
+
+    // Start measuring afresh, e.g. when a run starts with the player moved to the spawn point
+    pub fn forget_position(&mut self) {
+        self.last_position = None;
+        self.fall_start = None;
+    }
+}
+
+// Add the player's rolling, time in the air and falls to the totals
+pub fn track_player_stats(
+    mut stats: ResMut<LifetimeStats>,
+    player_query: Query<(&Transform, &PlayerPhysics, &Health), With<Player>>,
+    time: Res<Time>,
+) {
+    let Ok((transform, physics, health)) = player_query.get_single() else {
+        return;
+    };
+    let position = transform.translation;
+    if let Some(last) = stats.last_position {
+        stats.distance_rolled += Vec2::new(position.x - last.x, position.z - last.z).length();
+    }
+    stats.last_position = Some(position);
+
+    if physics.grounded {
+        if let Some(start) = stats.fall_start.take() {
+            let fall = start - position.y;
+            if health.current > 0.0 && fall > stats.highest_fall {
+                stats.highest_fall = fall;
+            }
+        }
+    } else {
+        stats.airtime += time.delta_secs();
+        stats.fall_start = Some(stats.fall_start.map_or(position.y, |start| start.max(position.y)));
+    }
+}
+
+// Count the player's throws and destroyed targets
+pub fn count_stat_events(
+    mut stats: ResMut<LifetimeStats>,
+    mut events: EventReader<GameEvent>,
+    thrown_query: Query<(), (Added<Projectile>, Without<Hostile>)>,
+) {
+    let thrown = thrown_query.iter().count() as u32;
+    if thrown > 0 {
+        stats.boulders_thrown += thrown;
+    }
+    for event in events.read() {
+        if let GameEvent::TargetDestroyed { .. } = event {
+            stats.targets_destroyed += 1;
+        }
+    }
+}
+
+// Measure from scratch at the start of a run
+pub fn reset_stat_tracking(mut stats: ResMut<LifetimeStats>) {
+    stats.forget_position();
+}
+
+// Save the totals when a run ends or the game is paused, which is also on the way to quitting
+pub fn write_lifetime_stats(stats: Res<LifetimeStats>, profile: Res<ActiveProfile>) {
+    stats.write(&profile);
+}
+
+// Plugin for lifetime statistics
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        // Main picks the profile before the plugins build
+        let profile = app.world().get_resource::<ActiveProfile>().cloned().unwrap_or_default();
+        app
+            .insert_resource(LifetimeStats::load(&profile))
+            .add_systems(Update, (track_player_stats, count_stat_events)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)))
+            .add_systems(OnEnter(GameState::Playing), reset_stat_tracking)
+            .add_systems(OnEnter(GameState::GameOver), write_lifetime_stats)
+            .add_systems(OnEnter(GameState::Paused), write_lifetime_stats);
+    }
+}
@@ -0,0 +1,319 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
+use crate::terrain::{chunk_coords, get_terrain_height, CHUNK_SIZE};
+use crate::player::{player_spawn_position, Player};
+use crate::projectile::ProjectileHitEvent;
+use crate::sim::{SimSet, Simulation};
+use crate::spatial::Spatial;
+use crate::minimap::MinimapBlip;
+use crate::compass::CompassMarker;
+use crate::waypoint::Waypoint;
+use crate::game_events::GameEvent;
+use crate::{GameMode, GameState, RunStart};
+
+// Target constants
+const TARGET_COUNT: usize = 8; // Posts placed around the spawn point in free play
+const TARGET_MIN_DISTANCE: f32 = 8.0; // Closest a target is placed to its anchor
+const TARGET_MAX_DISTANCE: f32 = 40.0; // Farthest a target is placed from its anchor
+const PRACTICE_CHUNK_RADIUS: i32 = 1; // Target practice scatters targets over this many chunks around the player
+const PRACTICE_TARGETS_PER_CHUNK: usize = 2; // Targets placed in each of those chunks
+const PRACTICE_DISTANCE_STEP: f32 = 2.0; // Replacement targets move this much farther out per target destroyed
+const PRACTICE_MAX_DISTANCE: f32 = 90.0; // Replacement targets never spawn farther than this
+const BALLOON_BOB_HEIGHT: f32 = 0.4; // Amplitude of a balloon's bobbing
+const BALLOON_BOB_SPEED: f32 = 1.5; // Bobbing frequency in radians per second
+const TARGET_STREAM: u64 = 4; // Simulation random stream for placing targets
+
+// Shapes of target, each with its own size and placement
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetKind {
+    // Upright post, the free play target
+    Post,
+    // Floating hoop to lob a boulder through
+    Ring,
+    // Crate sitting on the ground
+    Block,
+    // Bobbing balloon high above the ground
+    Balloon,
+}
+
+impl TargetKind {
+    // Kinds used by target practice
+    const PRACTICE: [TargetKind; 3] = [TargetKind::Ring, TargetKind::Block, TargetKind::Balloon];
+
+    // Distance from the target's center within which a boulder counts as a hit
+    fn hit_radius(self) -> f32 {
+        match self {
+            TargetKind::Post => 0.6,
+            TargetKind::Ring => 1.2,
+            TargetKind::Block => 0.8,
+            TargetKind::Balloon => 0.9,
+        }
+    }
+
+    // Height of the target's center above the terrain
+    fn height_offset(self) -> f32 {
+        match self {
+            TargetKind::Post => 1.0,
+            TargetKind::Ring => 2.5,
+            TargetKind::Block => 0.6,
+            TargetKind::Balloon => 4.0,
+        }
+    }
+
+    // Kind for a lowercase name, as scripts give it
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "post" => Some(TargetKind::Post),
+            "ring" => Some(TargetKind::Ring),
+            "block" => Some(TargetKind::Block),
+            "balloon" => Some(TargetKind::Balloon),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            TargetKind::Post => "Target",
+            TargetKind::Ring => "Ring",
+            TargetKind::Block => "Block",
+            TargetKind::Balloon => "Balloon",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            TargetKind::Post => Color::srgb(0.85, 0.2, 0.15),
+            TargetKind::Ring => Color::srgb(0.95, 0.75, 0.15),
+            TargetKind::Block => Color::srgb(0.6, 0.4, 0.2),
+            TargetKind::Balloon => Color::srgb(0.9, 0.3, 0.6),
+        }
+    }
+}
+
+// Something to hit with a boulder
+#[derive(Component)]
+pub struct Target {
+    // Distance within which an impact or passing boulder counts as a hit
+    pub radius: f32,
+}
+
+// Vertical bobbing around a resting height
+#[derive(Component)]
+pub struct Bob {
+    pub base_y: f32,
+    pub phase: f32,
+}
+
+// Shared meshes and materials for each kind of target
+#[derive(Resource)]
+pub struct TargetAssets {
+    pub post_mesh: Handle<Mesh>,
+    pub ring_mesh: Handle<Mesh>,
+    pub block_mesh: Handle<Mesh>,
+    pub balloon_mesh: Handle<Mesh>,
+    pub post_material: Handle<StandardMaterial>,
+    pub ring_material: Handle<StandardMaterial>,
+    pub block_material: Handle<StandardMaterial>,
+    pub balloon_material: Handle<StandardMaterial>,
+}
+
+impl TargetAssets {
+    fn get(&self, kind: TargetKind) -> (Handle<Mesh>, Handle<StandardMaterial>) {
+        match kind {
+            TargetKind::Post => (self.post_mesh.clone(), self.post_material.clone()),
+            TargetKind::Ring => (self.ring_mesh.clone(), self.ring_material.clone()),
+            TargetKind::Block => (self.block_mesh.clone(), self.block_material.clone()),
+            TargetKind::Balloon => (self.balloon_mesh.clone(), self.balloon_material.clone()),
+        }
+    }
+}
+
+// Targets destroyed in the current target practice run
+#[derive(Resource, Default)]
+pub struct TargetPractice {
+    pub destroyed: u32,
+}
+
+// Place a target of the given kind on the terrain at (x, z)
+pub fn spawn_target_at(commands: &mut Commands, assets: &TargetAssets, kind: TargetKind, x: f32, z: f32, rng: &mut StdRng) {
+    let y = get_terrain_height(x, z) + kind.height_offset();
+    let (mesh, material) = assets.get(kind);
+
+    // Rings stand upright facing a random direction, so some shots have to come from the side
+    let rotation = match kind {
+        TargetKind::Ring => Quat::from_rotation_y(rng.gen_range(0.0..std::f32::consts::TAU))
+            * Quat::from_rotation_x(std::f32::consts::FRAC_PI_2),
+        _ => Quat::IDENTITY,
+    };
+
+    let mut target = commands.spawn((
+        Target { radius: kind.hit_radius() },
+        Spatial::new(kind.hit_radius()),
+        Mesh3d(mesh),
+        MeshMaterial3d(material),
+        Transform::from_xyz(x, y, z).with_rotation(rotation),
+        Name::new(kind.label()),
+        MinimapBlip { color: kind.color() },
+        CompassMarker { color: kind.color() },
+        Waypoint {
+            label: kind.label().to_string(),
+            color: kind.color().mix(&Color::WHITE, 0.2),
+        },
+    ));
+    if kind == TargetKind::Balloon {
+        target.insert(Bob { base_y: y, phase: rng.gen_range(0.0..std::f32::consts::TAU) });
+    }
+}
+
+// Place a target at a random spot within a distance band around an anchor point
+fn spawn_target_near(commands: &mut Commands, assets: &TargetAssets, kind: TargetKind, anchor: Vec3, min_distance: f32, max_distance: f32, rng: &mut StdRng) {
+    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+    let distance = rng.gen_range(min_distance..max_distance);
+    spawn_target_at(commands, assets, kind, anchor.x + angle.cos() * distance, anchor.z + angle.sin() * distance, rng);
+}
+
+// Pick a random target practice kind
+fn random_practice_kind(rng: &mut StdRng) -> TargetKind {
+    TargetKind::PRACTICE[rng.gen_range(0..TargetKind::PRACTICE.len())]
+}
+
+// Create the shared target meshes and materials
+pub fn setup_target_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut material = |kind: TargetKind| materials.add(StandardMaterial {
+        base_color: kind.color(),
+        perceptual_roughness: 0.7,
+        ..default()
+    });
+    commands.insert_resource(TargetAssets {
+        post_mesh: meshes.add(Cylinder::new(TargetKind::Post.hit_radius(), 2.0)),
+        ring_mesh: meshes.add(Torus::new(1.0, 1.3)),
+        block_mesh: meshes.add(Cuboid::new(1.2, 1.2, 1.2)),
+        balloon_mesh: meshes.add(Sphere::new(0.7)),
+        post_material: material(TargetKind::Post),
+        ring_material: material(TargetKind::Ring),
+        block_material: material(TargetKind::Block),
+        balloon_material: material(TargetKind::Balloon),
+    });
+}
+
+// Lay out the targets for the selected mode at the start of a run
+pub fn spawn_mode_targets(
+    mut commands: Commands,
+    assets: Res<TargetAssets>,
+    mode: Res<GameMode>,
+    simulation: Res<Simulation>,
+) {
+    let spawn = player_spawn_position();
+    let mut rng = simulation.rng(TARGET_STREAM);
+    match *mode {
+        GameMode::FreePlay => {
+            for _ in 0..TARGET_COUNT {
+                spawn_target_near(&mut commands, &assets, TargetKind::Post, spawn, TARGET_MIN_DISTANCE, TARGET_MAX_DISTANCE, &mut rng);
+            }
+        }
+        GameMode::TargetPractice => {
+            // Scatter targets over the chunks around the spawn point, keeping clear of the player
+            let (center_x, center_z) = chunk_coords(spawn);
+            for chunk_z in (center_z - PRACTICE_CHUNK_RADIUS)..=(center_z + PRACTICE_CHUNK_RADIUS) {
+                for chunk_x in (center_x - PRACTICE_CHUNK_RADIUS)..=(center_x + PRACTICE_CHUNK_RADIUS) {
+                    let mut placed = 0;
+                    while placed < PRACTICE_TARGETS_PER_CHUNK {
+                        let x = (chunk_x as f32 + rng.gen_range(0.0..1.0)) * CHUNK_SIZE;
+                        let z = (chunk_z as f32 + rng.gen_range(0.0..1.0)) * CHUNK_SIZE;
+                        if Vec2::new(x - spawn.x, z - spawn.z).length() < TARGET_MIN_DISTANCE {
+                            continue;
+                        }
+                        let kind = random_practice_kind(&mut rng);
+                        spawn_target_at(&mut commands, &assets, kind, x, z, &mut rng);
+                        placed += 1;
+                    }
+                }
+            }
+        }
+        // These modes bring their own objectives, or a script lays them out
+        GameMode::TimeTrial | GameMode::Golf | GameMode::KingOfTheHill | GameMode::DownhillRace | GameMode::BossFight | GameMode::Scripted => {}
+    }
+}
+
+// Remove every target so the next run can lay out its own
+pub fn despawn_targets(
+    mut commands: Commands,
+    target_query: Query<Entity, With<Target>>,
+    mut practice: ResMut<TargetPractice>,
+) {
+    for entity in target_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    *practice = TargetPractice::default();
+}
+
+// Remove targets that were hit and place a replacement somewhere around the player
+pub fn handle_target_hits(
+    mut commands: Commands,
+    mut hit_events: EventReader<ProjectileHitEvent>,
+    mut game_events: EventWriter<GameEvent>,
+    mut practice: ResMut<TargetPractice>,
+    assets: Res<TargetAssets>,
+    mode: Res<GameMode>,
+    simulation: Res<Simulation>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let anchor = player_query
+        .get_single()
+        .map(|transform| transform.translation)
+        .unwrap_or(Vec3::ZERO);
+    let mut rng = simulation.rng(TARGET_STREAM);
+    for event in hit_events.read() {
+        if let Some(mut target) = commands.get_entity(event.target) {
+            target.despawn();
+            game_events.send(GameEvent::TargetDestroyed { position: event.position, distance: event.distance });
+            match *mode {
+                GameMode::FreePlay => {
+                    spawn_target_near(&mut commands, &assets, TargetKind::Post, anchor, TARGET_MIN_DISTANCE, TARGET_MAX_DISTANCE, &mut rng);
+                }
+                GameMode::TargetPractice => {
+                    // Each target destroyed pushes the next one farther out
+                    practice.destroyed += 1;
+                    let min_distance = (TARGET_MIN_DISTANCE + practice.destroyed as f32 * PRACTICE_DISTANCE_STEP)
+                        .min(PRACTICE_MAX_DISTANCE - 10.0);
+                    let max_distance = (min_distance + 20.0).min(PRACTICE_MAX_DISTANCE);
+                    let kind = random_practice_kind(&mut rng);
+                    spawn_target_near(&mut commands, &assets, kind, anchor, min_distance, max_distance, &mut rng);
+                }
+                GameMode::TimeTrial | GameMode::Golf | GameMode::KingOfTheHill | GameMode::DownhillRace | GameMode::BossFight | GameMode::Scripted => {}
+            }
+        }
+    }
+}
+
+// Bob balloons up and down
+pub fn bob_targets(mut query: Query<(&mut Transform, &mut Bob)>, time: Res<Time>) {
+    for (mut transform, mut bob) in query.iter_mut() {
+        bob.phase += time.delta_secs() * BALLOON_BOB_SPEED;
+        transform.translation.y = bob.base_y + bob.phase.sin() * BALLOON_BOB_HEIGHT;
+    }
+}
+
+// Plugin for hittable targets and the target practice mode
+pub struct TargetPlugin;
+
+impl Plugin for TargetPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<TargetPractice>()
+            .add_systems(Startup, setup_target_assets)
+            .add_systems(RunStart, spawn_mode_targets)
+            .add_systems(OnExit(GameState::GameOver), despawn_targets)
+            .add_systems(FixedUpdate, handle_target_hits
+                .in_set(SimSet::Constraints)
+                .run_if(in_state(GameState::Playing)))
+            .add_systems(FixedUpdate, bob_targets
+                .in_set(SimSet::Environment)
+                .run_if(in_state(GameState::Playing)));
+    }
+}
@@ -0,0 +1,249 @@
+use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::TAU;
+use crate::terrain::{chunk_coords, find_hilltop, get_terrain_height, ChunkManager, CHUNK_SIZE};
+use crate::player::{player_spawn_position, Player, PlayerPhysics};
+use crate::regions::{region_name, region_of_chunk, REGION_SIZE_CHUNKS};
+use crate::camera::FollowCamera;
+use crate::hazards::in_lava;
+use crate::game_events::GameEvent;
+use crate::photo_mode::PhotoModeState;
+use crate::minimap::MinimapBlip;
+use crate::compass::CompassMarker;
+use crate::sim::{SimSet, Simulation};
+use crate::assets::models::ModelRegistry;
+use crate::{GameMode, GameState, RunStart};
+
+// Teleporter constants
+const TELEPORTER_PAIRS: usize = 2; // Linked pairs placed at the start of a run
+const NEAR_MIN_DISTANCE: f32 = 25.0; // Closest the home end of a pair is to the spawn point
+const NEAR_MAX_DISTANCE: f32 = 40.0; // Farthest the home end of a pair is from the spawn point
+const FAR_REGIONS: f32 = 3.0; // How many regions away the far end of a pair is
+const TELEPORTER_RADIUS: f32 = 1.5; // Horizontal distance from a pad's center that triggers a warp
+const TELEPORTER_REACH: f32 = 1.5; // Height above a pad the ball can be and still trigger it
+const EXIT_OFFSET: f32 = 3.0; // Distance from the destination pad the ball comes out, so it doesn't warp straight back
+const PRELOAD_RADIUS: i32 = 1; // Chunks around the destination that must exist before arriving
+const MIN_WARP_TIME: f32 = 0.4; // Seconds the warp takes even when the destination is ready
+const TELEPORT_COOLDOWN: f32 = 1.5; // Seconds after arriving before another warp can start
+const TELEPORT_STREAM: u64 = 12; // Simulation random stream for placing teleporters
+const PAIR_COLORS: [Color; TELEPORTER_PAIRS] = [Color::srgb(0.6, 0.3, 1.0), Color::srgb(0.2, 1.0, 0.8)];
+
+// One end of a linked pair of teleporter pads
+#[derive(Component)]
+pub struct Teleporter {
+    // Center of the pad at the other end
+    pub destination: Vec3,
+}
+
+// A warp in progress, waiting for the destination terrain to be generated
+pub struct PendingWarp {
+    // Where the ball comes out
+    pub exit: Vec3,
+    // Velocity the ball had going in, restored on arrival
+    pub velocity: Vec3,
+    // Position the ball is held at until the warp completes
+    pub hold: Vec3,
+    pub elapsed: f32,
+}
+
+// Current warp and the cooldown after the last one
+#[derive(Resource, Default)]
+pub struct TeleportState {
+    pub pending: Option<PendingWarp>,
+    pub cooldown: f32,
+}
+
+// A spot on the terrain for a pad, moved up out of any lava
+fn pad_site(position: Vec2) -> Vec3 {
+    let site = if in_lava(position.x, position.y) { find_hilltop(position) } else { position };
+    Vec3::new(site.x, get_terrain_height(site.x, site.y), site.y)
+}
+
+// Place the linked pairs: one end near the spawn point, the other a few regions out in the same direction
+pub fn spawn_teleporters(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mode: Res<GameMode>,
+    simulation: Res<Simulation>,
+    models: Res<ModelRegistry>,
+    asset_server: Res<AssetServer>,
+) {
+    if !matches!(*mode, GameMode::FreePlay | GameMode::TargetPractice) {
+        return;
+    }
+    let mut rng = simulation.rng(TELEPORT_STREAM);
+    let spawn = player_spawn_position();
+    let spawn = Vec2::new(spawn.x, spawn.z);
+    let pad_mesh = meshes.add(Torus::new(TELEPORTER_RADIUS - 0.2, TELEPORTER_RADIUS));
+    let beam_mesh = meshes.add(Cylinder::new(TELEPORTER_RADIUS - 0.3, 3.0));
+    let far_distance = FAR_REGIONS * REGION_SIZE_CHUNKS as f32 * CHUNK_SIZE;
+    let model = models.structure("teleporter", &asset_server);
+
+    for (pair, color) in PAIR_COLORS.iter().enumerate() {
+        let angle = pair as f32 / TELEPORTER_PAIRS as f32 * TAU + rng.gen_range(0.0..TAU / TELEPORTER_PAIRS as f32);
+        let direction = Vec2::new(angle.cos(), angle.sin());
+        let near = pad_site(spawn + direction * rng.gen_range(NEAR_MIN_DISTANCE..NEAR_MAX_DISTANCE));
+        let far = pad_site(spawn + direction * far_distance);
+
+        let pad_material = materials.add(StandardMaterial {
+            base_color: *color,
+            emissive: (color.to_linear() * 1.5).into(),
+            ..default()
+        });
+        let beam_material = materials.add(StandardMaterial {
+            base_color: color.with_alpha(0.25),
+            emissive: (color.to_linear() * 0.5).into(),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        });
+        for (position, destination) in [(near, far), (far, near)] {
+            let mut pad = commands.spawn((
+                Teleporter { destination },
+                Transform::from_translation(position + Vec3::Y * 0.1),
+                Visibility::default(),
+                Name::new(format!("Teleporter {}", pair + 1)),
+                MinimapBlip { color: *color },
+                CompassMarker { color: *color },
+            ));
+            match &model {
+                Some(scene) => {
+                    pad.with_children(|parent| {
+                        parent.spawn((SceneRoot(scene.clone()), Transform::from_xyz(0.0, -0.1, 0.0)));
+                    });
+                }
+                None => {
+                    pad.insert((Mesh3d(pad_mesh.clone()), MeshMaterial3d(pad_material.clone())));
+                }
+            }
+            // The beam stays either way, since its color tells the pairs apart
+            pad.with_children(|parent| {
+                parent.spawn((
+                    Mesh3d(beam_mesh.clone()),
+                    MeshMaterial3d(beam_material.clone()),
+                    Transform::from_xyz(0.0, 1.5, 0.0),
+                ));
+            });
+        }
+    }
+}
+
+// Remove the pads at the end of a run
+pub fn despawn_teleporters(
+    mut commands: Commands,
+    mut state: ResMut<TeleportState>,
+    teleporter_query: Query<Entity, With<Teleporter>>,
+) {
+    for entity in teleporter_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    *state = TeleportState::default();
+}
+
+// Start a warp when the player rolls onto a pad, queueing the destination chunks ahead of everything else
+pub fn enter_teleporters(
+    mut state: ResMut<TeleportState>,
+    mut chunk_manager: ResMut<ChunkManager>,
+    player_query: Query<(&Transform, &PlayerPhysics), With<Player>>,
+    teleporter_query: Query<(&Transform, &Teleporter), Without<Player>>,
+    time: Res<Time>,
+) {
+    state.cooldown = (state.cooldown - time.delta_secs()).max(0.0);
+    if state.pending.is_some() || state.cooldown > 0.0 {
+        return;
+    }
+    let Ok((player_transform, physics)) = player_query.get_single() else {
+        return;
+    };
+    let position = player_transform.translation;
+
+    for (transform, teleporter) in teleporter_query.iter() {
+        let pad = transform.translation;
+        let across = Vec2::new(position.x - pad.x, position.z - pad.z).length();
+        if across > TELEPORTER_RADIUS || position.y - pad.y > TELEPORTER_REACH {
+            continue;
+        }
+
+        // Come out moving the same way, or straight out of the pad's far side when standing still
+        let heading = Vec3::new(physics.velocity.x, 0.0, physics.velocity.z).normalize_or(Vec3::NEG_Z);
+        let exit = teleporter.destination + heading * EXIT_OFFSET;
+        let exit = Vec3::new(exit.x, get_terrain_height(exit.x, exit.z) + 0.5, exit.z);
+
+        let (center_x, center_z) = chunk_coords(exit);
+        for z in center_z - PRELOAD_RADIUS..=center_z + PRELOAD_RADIUS {
+            for x in center_x - PRELOAD_RADIUS..=center_x + PRELOAD_RADIUS {
+                chunk_manager.prioritize_chunk((x, z));
+            }
+        }
+        state.pending = Some(PendingWarp {
+            exit,
+            velocity: physics.velocity,
+            hold: position,
+            elapsed: 0.0,
+        });
+        return;
+    }
+}
+
+// Hold the player on the pad until the destination chunks exist, then move them and the camera across
+pub fn complete_warp(
+    mut state: ResMut<TeleportState>,
+    chunk_manager: Res<ChunkManager>,
+    mut game_events: EventWriter<GameEvent>,
+    mut player_query: Query<(&mut Transform, &mut PlayerPhysics), With<Player>>,
+    mut camera_query: Query<&mut Transform, (With<FollowCamera>, Without<Player>)>,
+    time: Res<Time>,
+) {
+    let Some(warp) = state.pending.as_mut() else {
+        return;
+    };
+    let Ok((mut transform, mut physics)) = player_query.get_single_mut() else {
+        return;
+    };
+    warp.elapsed += time.delta_secs();
+
+    let (center_x, center_z) = chunk_coords(warp.exit);
+    let ready = (center_z - PRELOAD_RADIUS..=center_z + PRELOAD_RADIUS).all(|z| {
+        (center_x - PRELOAD_RADIUS..=center_x + PRELOAD_RADIUS)
+            .all(|x| chunk_manager.loaded_chunks.contains_key(&(x, z)))
+    });
+    if !ready || warp.elapsed < MIN_WARP_TIME {
+        transform.translation = warp.hold;
+        physics.velocity = Vec3::ZERO;
+        physics.momentum = Vec3::ZERO;
+        return;
+    }
+
+    let offset = warp.exit - transform.translation;
+    transform.translation = warp.exit;
+    physics.prev_position = warp.exit;
+    physics.velocity = warp.velocity;
+    physics.momentum = warp.velocity;
+    // Carry the camera across too, rather than letting it chase the ball over the whole distance
+    for mut camera_transform in camera_query.iter_mut() {
+        camera_transform.translation += offset;
+    }
+
+    let region = region_of_chunk(chunk_coords(warp.exit));
+    game_events.send(GameEvent::Teleported { destination: region_name(region) });
+    state.pending = None;
+    state.cooldown = TELEPORT_COOLDOWN;
+}
+
+// Plugin for linked teleporter pads
+pub struct TeleportPlugin;
+
+impl Plugin for TeleportPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<TeleportState>()
+            .add_systems(RunStart, spawn_teleporters)
+            .add_systems(OnExit(GameState::GameOver), despawn_teleporters)
+            .add_systems(FixedUpdate, (enter_teleporters, complete_warp)
+                .chain()
+                .in_set(SimSet::Constraints)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)));
+    }
+}
@@ -1,60 +1,326 @@
 use bevy::prelude::*;
 use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
 use bevy::utils::HashMap;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::sync::{Arc, LazyLock, PoisonError, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use noise::{NoiseFn, Perlin};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use crate::GameState;
+use crate::budget::{FrameBudget, WorkBudgets, WorkQueue};
+use crate::debug::DebugStats;
+use crate::terrain_material::{create_terrain_material, TerrainMaterial};
 
 // Constants for terrain generation
 pub const CHUNK_SIZE: f32 = 40.0;
 pub const CHUNK_RESOLUTION: usize = 24; // Higher resolution for more detailed terrain
 pub const TERRAIN_HEIGHT_SCALE: f32 = 8.0; // Increased height for more dramatic hills
-pub const TERRAIN_SEED: u32 = 123;
+pub const DEFAULT_SEED: u32 = 123; // World seed used until the player picks another
+pub const LAVA_LEVEL: f32 = -5.0; // Valleys below this height are flooded with lava
+const STEEP_GRADIENT: f32 = 0.6; // Terrain gradient that counts as a steep slope
+const VERTEX_SPACING: f32 = CHUNK_SIZE / CHUNK_RESOLUTION as f32; // Distance between terrain mesh vertices
 
 // Additional noise parameters for varied terrain
 pub const MAIN_NOISE_SCALE: f64 = 80.0; // Base scale for primary features
 pub const DETAIL_NOISE_SCALE: f64 = 30.0; // Scale for secondary details
 pub const TERTIARY_NOISE_SCALE: f64 = 10.0; // Scale for small details
 
+// Terrain raycast parameters
+pub const RAYCAST_STEP: f32 = 0.5; // Marching step, small enough to catch thin ridgelines
+pub const RAYCAST_BISECTION_STEPS: usize = 12; // Refinement iterations once a crossing is found
+
+// Chunk streaming parameters
+pub const DEFAULT_VIEW_DISTANCE: i32 = 2; // Chunks loaded in each direction around the player, a 5x5 grid
+pub const CHUNKS_PER_FRAME: usize = 4; // Most finished chunk meshes spawned in a frame, however much budget is left
+// Chunk meshes built at once on the task pool. In a browser the pool runs on the main thread, so fewer are
+// started at a time to keep each frame's share of the work small.
+#[cfg(not(target_arch = "wasm32"))]
+pub const MAX_CHUNK_TASKS: usize = 8;
+#[cfg(target_arch = "wasm32")]
+pub const MAX_CHUNK_TASKS: usize = 2;
+const HILL_CLIMB_STEPS: usize = 40; // Uphill steps taken when searching for a hilltop
+const HILL_CLIMB_STEP: f32 = 1.0; // Length of each uphill step
+
+// A smooth bowl pressed into the terrain, such as a golf hole
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Deformation {
+    pub center: Vec2,
+    pub radius: f32,
+    pub depth: f32,
+}
+
+// Deformations applied on top of the generated heights; global so get_terrain_height stays a free function.
+// Most runs have none, so the flag lets height lookups skip the lock entirely.
+static DEFORMATIONS: RwLock<Vec<Deformation>> = RwLock::new(Vec::new());
+static DEFORMED: AtomicBool = AtomicBool::new(false);
+
+// Seed the terrain is generated from, mirrored from TerrainConfig for the same reason
+static WORLD_SEED: AtomicU32 = AtomicU32::new(DEFAULT_SEED);
+
+// Heights at the mesh vertices of every built chunk, so sample() can read the terrain without evaluating
+// noise; a chunk's entry goes away whenever it is rebuilt
+static HEIGHT_CACHE: LazyLock<RwLock<HashMap<(i32, i32), Arc<[f32]>>>> = LazyLock::new(Default::default);
+
+// What the ground is like at a point
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TerrainSurface {
+    Ground,
+    // Too steep to stand around on comfortably
+    Steep,
+    // Below LAVA_LEVEL, where the valleys are flooded
+    Lava,
+}
+
+// The terrain at one point, as sample() finds it
+#[derive(Clone, Copy, Debug)]
+pub struct TerrainSample {
+    pub height: f32,
+    // Unit vector pointing straight out of the ground
+    pub normal: Vec3,
+    // Uphill slope as rise per unit along X and Z
+    pub gradient: Vec2,
+    pub surface: TerrainSurface,
+}
+
+// Which world to generate; insert before TerrainPlugin to start in a particular world
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TerrainConfig {
+    pub seed: u32,
+}
+
+impl Default for TerrainConfig {
+    fn default() -> Self {
+        Self { seed: DEFAULT_SEED }
+    }
+}
+
+impl TerrainConfig {
+    // A world nobody has seen yet
+    pub fn random() -> Self {
+        Self { seed: rand::random() }
+    }
+
+    // The seed as a short code players can share
+    pub fn seed_code(&self) -> String {
+        format!("{:08X}", self.seed)
+    }
+
+    // Read a shared code back; anything that isn't a hex code is hashed so words work as seeds too
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.trim();
+        if code.is_empty() {
+            return None;
+        }
+        if code.len() <= 8 {
+            if let Ok(seed) = u32::from_str_radix(code, 16) {
+                return Some(Self { seed });
+            }
+        }
+        // FNV-1a over the uppercased text
+        let seed = code.to_uppercase().bytes().fold(0x811C_9DC5u32, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+        });
+        Some(Self { seed })
+    }
+}
+
+// Seed of the world currently being generated
+pub fn world_seed() -> u32 {
+    WORLD_SEED.load(Ordering::Relaxed)
+}
+
 // Component to mark terrain chunks
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct TerrainChunk {
-    #[allow(dead_code)]
     pub chunk_x: i32,
-    #[allow(dead_code)]
     pub chunk_z: i32,
 }
 
+// New chunks waiting for a scatter pass to place things on them, with their chunk coordinates
+pub type ScatterQueue = WorkQueue<(Entity, (i32, i32))>;
+
+// Queue chunks spawned since a scatter pass last ran
+pub fn queue_new_chunks(queue: &mut ScatterQueue, chunk_query: &Query<(Entity, &TerrainChunk), Added<TerrainChunk>>) {
+    queue.extend(chunk_query.iter().map(|(entity, chunk)| (entity, (chunk.chunk_x, chunk.chunk_z))));
+}
+
 // Resource to track loaded chunks
 #[derive(Resource)]
 pub struct ChunkManager {
     pub loaded_chunks: HashMap<(i32, i32), Entity>,
-    pub material_handle: Handle<StandardMaterial>,
+    // Chunks waiting to be generated, nearest to the player first
+    pub pending_chunks: VecDeque<(i32, i32)>,
+    // Chunks whose meshes and vertex heights are being built on the task pool, oldest first
+    pub building_chunks: Vec<((i32, i32), Task<(Mesh, Vec<f32>)>)>,
+    pub material_handle: Handle<TerrainMaterial>,
+    // Chunks kept loaded in each direction around the player, set from the graphics settings
+    pub view_distance: i32,
 }
 
-// System to spawn initial terrain
+impl ChunkManager {
+    // Queue a chunk for generation unless it is already loaded or queued
+    pub fn request_chunk(&mut self, chunk: (i32, i32)) {
+        if !self.loaded_chunks.contains_key(&chunk) && !self.pending_chunks.contains(&chunk) && !self.is_building(chunk) {
+            self.pending_chunks.push_back(chunk);
+        }
+    }
+
+    // Queue a chunk to be generated before anything else, such as the far end of a teleport
+    pub fn prioritize_chunk(&mut self, chunk: (i32, i32)) {
+        if !self.loaded_chunks.contains_key(&chunk) && !self.is_building(chunk) {
+            self.pending_chunks.retain(|pending| *pending != chunk);
+            self.pending_chunks.push_front(chunk);
+        }
+    }
+
+    // Whether a chunk's mesh is being built right now
+    pub fn is_building(&self, chunk: (i32, i32)) -> bool {
+        self.building_chunks.iter().any(|(building, _)| *building == chunk)
+    }
+
+    // Number of chunks still waiting to be generated or being built
+    pub fn pending_count(&self) -> usize {
+        self.pending_chunks.len() + self.building_chunks.len()
+    }
+
+    // Throw away a chunk's mesh, loaded or still being built, and queue it to be generated again ahead
+    // of other chunks
+    pub fn rebuild_chunk(&mut self, commands: &mut Commands, chunk: (i32, i32)) {
+        HEIGHT_CACHE.write().unwrap().remove(&chunk);
+        let loaded = self.loaded_chunks.remove(&chunk);
+        if let Some(entity) = loaded {
+            commands.entity(entity).despawn_recursive();
+        }
+        // Dropping the task cancels it, so a mesh built from the old terrain never lands
+        let building = self.is_building(chunk);
+        self.building_chunks.retain(|(building, _)| *building != chunk);
+        if loaded.is_some() || building {
+            self.pending_chunks.retain(|pending| *pending != chunk);
+            self.pending_chunks.push_front(chunk);
+        }
+    }
+
+    // Throw away every loaded or building chunk and queue them all to be generated again
+    pub fn rebuild_all(&mut self, commands: &mut Commands) {
+        let mut chunks: Vec<(i32, i32)> = self.loaded_chunks.keys().copied().collect();
+        chunks.extend(self.building_chunks.iter().map(|(chunk, _)| *chunk));
+        for chunk in chunks {
+            self.rebuild_chunk(commands, chunk);
+        }
+    }
+}
+
+// Press a deformation into the terrain and rebuild the chunks it touches
+pub fn add_deformation(commands: &mut Commands, chunk_manager: &mut ChunkManager, deformation: Deformation) {
+    DEFORMATIONS.write().unwrap_or_else(PoisonError::into_inner).push(deformation);
+    DEFORMED.store(true, Ordering::Release);
+    rebuild_deformed_chunks(commands, chunk_manager, &deformation);
+}
+
+// Every deformation currently pressed into the terrain
+pub fn deformations() -> Vec<Deformation> {
+    with_deformations(|deformations| deformations.to_vec())
+}
+
+// Run f with the current deformations, without taking the lock when there are none
+fn with_deformations<T>(f: impl FnOnce(&[Deformation]) -> T) -> T {
+    if !DEFORMED.load(Ordering::Acquire) {
+        return f(&[]);
+    }
+    f(&DEFORMATIONS.read().unwrap_or_else(PoisonError::into_inner))
+}
+
+// Remove every deformation, restoring the generated terrain
+pub fn clear_deformations(commands: &mut Commands, chunk_manager: &mut ChunkManager) {
+    let removed = std::mem::take(&mut *DEFORMATIONS.write().unwrap_or_else(PoisonError::into_inner));
+    DEFORMED.store(false, Ordering::Release);
+    for deformation in removed.iter() {
+        rebuild_deformed_chunks(commands, chunk_manager, deformation);
+    }
+}
+
+// Rebuild every loaded chunk overlapping a deformation
+fn rebuild_deformed_chunks(commands: &mut Commands, chunk_manager: &mut ChunkManager, deformation: &Deformation) {
+    let min = chunk_coords(Vec3::new(deformation.center.x - deformation.radius, 0.0, deformation.center.y - deformation.radius));
+    let max = chunk_coords(Vec3::new(deformation.center.x + deformation.radius, 0.0, deformation.center.y + deformation.radius));
+    for z in min.1..=max.1 {
+        for x in min.0..=max.0 {
+            chunk_manager.rebuild_chunk(commands, (x, z));
+        }
+    }
+}
+
+// Total depth of the deformations at a world position
+fn deformation_depth(deformations: &[Deformation], x: f32, z: f32) -> f32 {
+    deformations
+        .iter()
+        .map(|deformation| {
+            let t = (Vec2::new(x, z).distance(deformation.center) / deformation.radius).min(1.0);
+            // Smooth bowl: full depth at the center, flat at the rim
+            let falloff = 1.0 - t * t * (3.0 - 2.0 * t);
+            deformation.depth * falloff
+        })
+        .sum()
+}
+
+// Chunk containing a world position
+pub fn chunk_coords(position: Vec3) -> (i32, i32) {
+    (
+        (position.x / CHUNK_SIZE).floor() as i32,
+        (position.z / CHUNK_SIZE).floor() as i32,
+    )
+}
+
+// System to set up the terrain material and queue the initial 3x3 grid of chunks
 pub fn spawn_initial_terrain(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut chunk_manager: ResMut<ChunkManager>,
+    mut materials: ResMut<Assets<TerrainMaterial>>,
+    mut images: ResMut<Assets<Image>>,
 ) {
-    // Create a default green material for all terrain chunks
-    let material_handle = materials.add(Color::srgb(0.3, 0.5, 0.3));
+    // Create the shared material for all terrain chunks
+    chunk_manager.material_handle = materials.add(create_terrain_material(&mut images));
     
-    // Create the chunk manager resource
-    commands.insert_resource(ChunkManager {
-        loaded_chunks: HashMap::new(),
-        material_handle: material_handle.clone(),
-    });
-    
-    // Spawn the initial 3x3 grid of chunks
+    // Queue the initial 3x3 grid of chunks, center first
+    chunk_manager.request_chunk((0, 0));
     for z in -1..=1 {
         for x in -1..=1 {
-            spawn_terrain_chunk(&mut commands, &mut meshes, material_handle.clone(), x, z);
+            chunk_manager.request_chunk((x, z));
+        }
+    }
+}
+
+thread_local! {
+    // Per-vertex sums of the surrounding face normals, reused by every mesh built on this thread
+    static NORMAL_SUMS: RefCell<Vec<Vec3>> = const { RefCell::new(Vec::new()) };
+}
+
+// Height at a terrain mesh vertex, by its index counted from the world origin. Chunk meshes and
+// sample() both go through here so they agree on every vertex to the bit.
+fn vertex_height(vertex_x: i32, vertex_z: i32, deformations: &[Deformation]) -> f32 {
+    let (x, z) = (vertex_x as f32 * VERTEX_SPACING, vertex_z as f32 * VERTEX_SPACING);
+    generated_height(x, z) - deformation_depth(deformations, x, z)
+}
+
+// Heights at a chunk's mesh vertices, row by row along X, with the deformations as they were when the
+// chunk was queued so worker threads never touch the lock
+pub fn chunk_heights(chunk_x: i32, chunk_z: i32, deformations: &[Deformation]) -> Vec<f32> {
+    let resolution = CHUNK_RESOLUTION as i32;
+    let mut heights = Vec::with_capacity((CHUNK_RESOLUTION + 1) * (CHUNK_RESOLUTION + 1));
+    for z in 0..=resolution {
+        for x in 0..=resolution {
+            heights.push(vertex_height(chunk_x * resolution + x, chunk_z * resolution + z, deformations));
         }
     }
+    heights
 }
 
-// Creates a procedurally generated terrain mesh for a specific chunk
-pub fn create_terrain_mesh(chunk_x: i32, chunk_z: i32) -> Mesh {
+// Creates the terrain mesh for a chunk from its vertex heights
+pub fn create_terrain_mesh(heights: &[f32]) -> Mesh {
     // Constants for mesh generation
     let width = CHUNK_RESOLUTION;
     let height = CHUNK_RESOLUTION;
@@ -63,8 +329,6 @@ pub fn create_terrain_mesh(chunk_x: i32, chunk_z: i32) -> Mesh {
     // Number of vertices
     let vertex_count = (width + 1) * (height + 1);
     
-    // We don't need to create a perlin noise instance here since we use get_terrain_height
-    
     // Create the terrain vertices
     let mut positions = Vec::with_capacity(vertex_count);
     let mut normals = Vec::with_capacity(vertex_count);
@@ -73,12 +337,8 @@ pub fn create_terrain_mesh(chunk_x: i32, chunk_z: i32) -> Mesh {
     // Generate the vertices grid
     for z in 0..=height {
         for x in 0..=width {
-            // Calculate world position for this vertex
-            let world_x = chunk_x as f32 * size + x as f32 / width as f32 * size;
-            let world_z = chunk_z as f32 * size + z as f32 / height as f32 * size;
-            
-            // Use the global height function to ensure consistency across chunks
-            let y = get_terrain_height(world_x, world_z);
+            // Heights come from the shared vertex grid to ensure consistency across chunks
+            let y = heights[z * (width + 1) + x];
             
             // Add the vertex position relative to chunk origin
             positions.push([x as f32 / width as f32 * size, y, z as f32 / height as f32 * size]);
@@ -114,46 +374,38 @@ pub fn create_terrain_mesh(chunk_x: i32, chunk_z: i32) -> Mesh {
     }
     
     // Calculate better normals by averaging the normals of adjacent triangles
-    let mut normal_sums = vec![[0.0, 0.0, 0.0]; vertex_count];
-    let mut normal_counts = vec![0; vertex_count];
-    
-    // For each triangle, calculate its normal and add it to each vertex
-    for i in (0..indices.len()).step_by(3) {
-        let idx0 = indices[i] as usize;
-        let idx1 = indices[i + 1] as usize;
-        let idx2 = indices[i + 2] as usize;
-        
-        let v0 = Vec3::from(positions[idx0]);
-        let v1 = Vec3::from(positions[idx1]);
-        let v2 = Vec3::from(positions[idx2]);
-        
-        // Calculate the triangle normal using cross product
-        let edge1 = v1 - v0;
-        let edge2 = v2 - v0;
-        let normal = edge1.cross(edge2).normalize();
+    NORMAL_SUMS.with_borrow_mut(|normal_sums| {
+        normal_sums.clear();
+        normal_sums.resize(vertex_count, Vec3::ZERO);
         
-        // Add the normal to each vertex of the triangle
-        for &idx in &[idx0, idx1, idx2] {
-            normal_sums[idx][0] += normal.x;
-            normal_sums[idx][1] += normal.y;
-            normal_sums[idx][2] += normal.z;
-            normal_counts[idx] += 1;
+        // For each triangle, calculate its normal and add it to each vertex
+        for i in (0..indices.len()).step_by(3) {
+            let idx0 = indices[i] as usize;
+            let idx1 = indices[i + 1] as usize;
+            let idx2 = indices[i + 2] as usize;
+            
+            let v0 = Vec3::from(positions[idx0]);
+            let v1 = Vec3::from(positions[idx1]);
+            let v2 = Vec3::from(positions[idx2]);
+            
+            // Calculate the triangle normal using cross product
+            let edge1 = v1 - v0;
+            let edge2 = v2 - v0;
+            let normal = edge1.cross(edge2).normalize();
+            
+            // Add the normal to each vertex of the triangle
+            for &idx in &[idx0, idx1, idx2] {
+                normal_sums[idx] += normal;
+            }
         }
-    }
-    
-    // Normalize all the normals
-    for i in 0..vertex_count {
-        if normal_counts[i] > 0 {
-            let count = normal_counts[i] as f32;
-            let mut normal = Vec3::new(
-                normal_sums[i][0] / count,
-                normal_sums[i][1] / count,
-                normal_sums[i][2] / count,
-            );
-            normal = normal.normalize();
-            normals[i] = [normal.x, normal.y, normal.z];
+        
+        // Normalize all the normals; the average points the same way as the sum
+        for (normal, sum) in normals.iter_mut().zip(normal_sums.iter()) {
+            if let Some(direction) = sum.try_normalize() {
+                *normal = direction.to_array();
+            }
         }
-    }
+    });
     
     // Create the mesh
     let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, Default::default());
@@ -165,12 +417,80 @@ pub fn create_terrain_mesh(chunk_x: i32, chunk_z: i32) -> Mesh {
     mesh
 }
 
+// Catmull-Rom spline through four evenly spaced values, evaluated at t in 0..1 between the middle two;
+// returns the value and its slope per step
+fn catmull_rom(values: [f32; 4], t: f32) -> (f32, f32) {
+    let [p0, p1, p2, p3] = values;
+    let a = -0.5 * p0 + 1.5 * p1 - 1.5 * p2 + 0.5 * p3;
+    let b = p0 - 2.5 * p1 + 2.0 * p2 - 0.5 * p3;
+    let c = 0.5 * (p2 - p0);
+    (((a * t + b) * t + c) * t + p1, (3.0 * a * t + 2.0 * b) * t + c)
+}
+
+// Height, slope and surface of the terrain at a world position, smoothly interpolated through the mesh
+// vertices so the ground gameplay feels is the ground that's drawn. Built chunks answer from the height
+// cache; elsewhere the vertex heights are worked out on the spot, which gives exactly the same result,
+// so the simulation never depends on which chunks have loaded.
+pub fn sample(x: f32, z: f32) -> TerrainSample {
+    let grid_x = x / VERTEX_SPACING;
+    let grid_z = z / VERTEX_SPACING;
+    let (base_x, base_z) = (grid_x.floor() as i32, grid_z.floor() as i32);
+    let resolution = CHUNK_RESOLUTION as i32;
+
+    // The 4x4 vertices around the point, rows along X
+    let mut vertices = [[0.0; 4]; 4];
+    let cache = HEIGHT_CACHE.read().unwrap();
+    with_deformations(|deformations| {
+        for (row, offset_z) in vertices.iter_mut().zip(-1..=2) {
+            for (height, offset_x) in row.iter_mut().zip(-1..=2) {
+                let (vertex_x, vertex_z) = (base_x + offset_x, base_z + offset_z);
+                let chunk = (vertex_x.div_euclid(resolution), vertex_z.div_euclid(resolution));
+                *height = match cache.get(&chunk) {
+                    Some(heights) => {
+                        let index = vertex_z.rem_euclid(resolution) * (resolution + 1) + vertex_x.rem_euclid(resolution);
+                        heights[index as usize]
+                    }
+                    None => vertex_height(vertex_x, vertex_z, deformations),
+                };
+            }
+        }
+    });
+    drop(cache);
+
+    // Interpolate along X within each row, then across the rows along Z
+    let (t_x, t_z) = (grid_x - base_x as f32, grid_z - base_z as f32);
+    let rows = vertices.map(|row| catmull_rom(row, t_x));
+    let (height, slope_z) = catmull_rom(rows.map(|(height, _)| height), t_z);
+    let (slope_x, _) = catmull_rom(rows.map(|(_, slope)| slope), t_z);
+    let gradient = Vec2::new(slope_x, slope_z) / VERTEX_SPACING;
+
+    let surface = if height < LAVA_LEVEL {
+        TerrainSurface::Lava
+    } else if gradient.length() > STEEP_GRADIENT {
+        TerrainSurface::Steep
+    } else {
+        TerrainSurface::Ground
+    };
+    TerrainSample {
+        height,
+        normal: Vec3::new(-gradient.x, 1.0, -gradient.y).normalize(),
+        gradient,
+        surface,
+    }
+}
+
 // Get the height of the terrain at any world position
 pub fn get_terrain_height(x: f32, z: f32) -> f32 {
+    generated_height(x, z) - with_deformations(|deformations| deformation_depth(deformations, x, z))
+}
+
+// Height of the generated terrain before any deformation
+fn generated_height(x: f32, z: f32) -> f32 {
     // Create Perlin noise generators with different seeds for variety
-    let perlin_main = Perlin::new(TERRAIN_SEED);
-    let perlin_detail = Perlin::new(TERRAIN_SEED + 42);
-    let perlin_tertiary = Perlin::new(TERRAIN_SEED + 123);
+    let seed = world_seed();
+    let perlin_main = Perlin::new(seed);
+    let perlin_detail = Perlin::new(seed.wrapping_add(42));
+    let perlin_tertiary = Perlin::new(seed.wrapping_add(123));
     
     // Calculate coordinates at different scales
     let nx_main = x as f64 / MAIN_NOISE_SCALE;
@@ -201,21 +521,94 @@ pub fn get_terrain_height(x: f32, z: f32) -> f32 {
     return curved_height * TERRAIN_HEIGHT_SCALE;
 }
 
+// Random generator for things scattered over a chunk, the same every time the chunk loads.
+// Each kind of scatter passes its own salt so they don't share a layout.
+pub fn chunk_rng(chunk_x: i32, chunk_z: i32, salt: u64) -> StdRng {
+    let seed = (chunk_x as i64 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (chunk_z as i64 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+        ^ world_seed() as u64
+        ^ salt.wrapping_mul(0x1656_67B1_9E37_79F9);
+    StdRng::seed_from_u64(seed)
+}
+
+// Uphill slope of the terrain at a world position, as rise per unit along X and Z
+pub fn terrain_gradient(x: f32, z: f32) -> Vec2 {
+    let sample_dist = 0.5;
+    Vec2::new(
+        get_terrain_height(x + sample_dist, z) - get_terrain_height(x - sample_dist, z),
+        get_terrain_height(x, z + sample_dist) - get_terrain_height(x, z - sample_dist),
+    ) / (2.0 * sample_dist)
+}
+
+// Climb the terrain gradient from a starting point until it levels out at a hilltop
+pub fn find_hilltop(start: Vec2) -> Vec2 {
+    let mut point = start;
+    for _ in 0..HILL_CLIMB_STEPS {
+        let gradient = terrain_gradient(point.x, point.y);
+        if gradient.length() < 0.02 {
+            break;
+        }
+        point += gradient.normalize() * HILL_CLIMB_STEP;
+    }
+    point
+}
+
+// Cast a ray against the terrain heightfield and return the first surface point it hits.
+// Marches in fine steps until the ray drops below the surface, then bisects the crossing.
+pub fn raycast_terrain(origin: Vec3, direction: Vec3, max_distance: f32) -> Option<Vec3> {
+    let direction = direction.normalize_or_zero();
+    if direction == Vec3::ZERO {
+        return None;
+    }
+    
+    // Rays starting underground have no meaningful hit
+    let above = |point: Vec3| point.y > get_terrain_height(point.x, point.z);
+    if !above(origin) {
+        return None;
+    }
+    
+    let mut previous = 0.0;
+    let mut distance = RAYCAST_STEP;
+    while distance <= max_distance + RAYCAST_STEP {
+        let sample_distance = distance.min(max_distance);
+        if !above(origin + direction * sample_distance) {
+            // Narrow down the crossing between the last point above and this one below
+            let mut low = previous;
+            let mut high = sample_distance;
+            for _ in 0..RAYCAST_BISECTION_STEPS {
+                let mid = (low + high) * 0.5;
+                if above(origin + direction * mid) {
+                    low = mid;
+                } else {
+                    high = mid;
+                }
+            }
+            let hit = origin + direction * high;
+            return Some(Vec3::new(hit.x, get_terrain_height(hit.x, hit.z), hit.z));
+        }
+        if sample_distance >= max_distance {
+            break;
+        }
+        previous = sample_distance;
+        distance += RAYCAST_STEP;
+    }
+    
+    None
+}
+
 // Function to spawn a single terrain chunk at the given coordinates
 pub fn spawn_terrain_chunk(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
-    material: Handle<StandardMaterial>,
+    material: Handle<TerrainMaterial>,
     chunk_x: i32,
     chunk_z: i32,
+    chunk_mesh: Mesh,
 ) -> Entity {
     // Calculate world position for this chunk
     let position_x = chunk_x as f32 * CHUNK_SIZE;
     let position_z = chunk_z as f32 * CHUNK_SIZE;
     
-    // Create mesh for this specific chunk
-    let chunk_mesh = create_terrain_mesh(chunk_x, chunk_z);
-    
     // Spawn the chunk entity
     let chunk_entity = commands.spawn((
         TerrainChunk { chunk_x, chunk_z },
@@ -229,9 +622,8 @@ pub fn spawn_terrain_chunk(
 
 // System to manage terrain chunks based on player position
 pub fn manage_terrain_chunks(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
     mut chunk_manager: ResMut<ChunkManager>,
+    mut debug_stats: ResMut<DebugStats>,
     player_query: Query<&Transform, With<crate::player::Player>>,
 ) {
     // Get player position
@@ -239,11 +631,10 @@ pub fn manage_terrain_chunks(
         let player_pos = player_transform.translation;
         
         // Calculate which chunk the player is in
-        let current_chunk_x = (player_pos.x / CHUNK_SIZE).floor() as i32;
-        let current_chunk_z = (player_pos.z / CHUNK_SIZE).floor() as i32;
+        let (current_chunk_x, current_chunk_z) = chunk_coords(player_pos);
         
         // Define the radius of chunks to keep loaded (in chunk coordinates)
-        let chunk_radius = 2; // Keep 5x5 grid of chunks around player (2 in each direction + current)
+        let chunk_radius = chunk_manager.view_distance;
         
         // Determine which chunks should be loaded
         let mut chunks_to_load = Vec::new();
@@ -256,23 +647,86 @@ pub fn manage_terrain_chunks(
             }
         }
         
-        // Spawn new chunks as needed
-        for (x, z) in chunks_to_load {
-            let new_chunk = spawn_terrain_chunk(
-                &mut commands,
-                &mut meshes,
-                chunk_manager.material_handle.clone(),
-                x,
-                z
-            );
-            chunk_manager.loaded_chunks.insert((x, z), new_chunk);
+        // Queue new chunks, closest to the player first
+        chunks_to_load.sort_by_key(|(x, z)| (x - current_chunk_x).abs().max((z - current_chunk_z).abs()));
+        for chunk_key in chunks_to_load {
+            chunk_manager.request_chunk(chunk_key);
         }
         
         // Optional: unload chunks that are too far away
         // This can be implemented later if necessary
+        
+        // Publish streaming stats for the debug overlay
+        debug_stats.loaded_chunks = chunk_manager.loaded_chunks.len();
+        debug_stats.player_chunk = (current_chunk_x, current_chunk_z);
     }
 }
 
+// System to spawn finished chunks within the frame budget and keep the task pool busy building queued ones
+pub fn generate_pending_chunks(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut chunk_manager: ResMut<ChunkManager>,
+    budgets: Res<WorkBudgets>,
+) {
+    // Spawn finished meshes, oldest first so nearer chunks tend to appear first; at least one always
+    // goes in so streaming keeps up on slow machines
+    let budget = FrameBudget::start(budgets.chunk_ms);
+    let mut spawned = 0;
+    let mut index = 0;
+    while spawned < CHUNKS_PER_FRAME && index < chunk_manager.building_chunks.len() {
+        if spawned > 0 && budget.exhausted() {
+            break;
+        }
+        if !chunk_manager.building_chunks[index].1.is_finished() {
+            index += 1;
+            continue;
+        }
+        let ((x, z), task) = chunk_manager.building_chunks.remove(index);
+        let (chunk_mesh, heights) = block_on(task);
+        HEIGHT_CACHE.write().unwrap().insert((x, z), heights.into());
+        let new_chunk = spawn_terrain_chunk(
+            &mut commands,
+            &mut meshes,
+            chunk_manager.material_handle.clone(),
+            x,
+            z,
+            chunk_mesh,
+        );
+        chunk_manager.loaded_chunks.insert((x, z), new_chunk);
+        spawned += 1;
+    }
+
+    // Hand queued chunks to the task pool while there are free slots
+    let pool = AsyncComputeTaskPool::get();
+    let deformations: Arc<[Deformation]> = deformations().into();
+    while chunk_manager.building_chunks.len() < MAX_CHUNK_TASKS {
+        let Some((x, z)) = chunk_manager.pending_chunks.pop_front() else {
+            break;
+        };
+        let deformations = deformations.clone();
+        let task = pool.spawn(async move {
+            let heights = chunk_heights(x, z, &deformations);
+            (create_terrain_mesh(&heights), heights)
+        });
+        chunk_manager.building_chunks.push(((x, z), task));
+    }
+}
+
+// Switch worlds when the seed changes, regenerating every loaded chunk
+pub fn apply_terrain_config(
+    mut commands: Commands,
+    config: Res<TerrainConfig>,
+    mut chunk_manager: ResMut<ChunkManager>,
+) {
+    if config.seed == world_seed() {
+        return;
+    }
+    WORLD_SEED.store(config.seed, Ordering::Relaxed);
+    chunk_manager.rebuild_all(&mut commands);
+    println!("World seed {}", config.seed_code());
+}
+
 // Plugin for the terrain module
 pub struct TerrainPlugin;
 
@@ -281,9 +735,21 @@ impl Plugin for TerrainPlugin {
         app
             .insert_resource(ChunkManager {
                 loaded_chunks: HashMap::new(),
+                pending_chunks: VecDeque::new(),
+                building_chunks: Vec::new(),
                 material_handle: Handle::default(),
+                view_distance: DEFAULT_VIEW_DISTANCE,
             })
+            .init_resource::<TerrainConfig>()
+            .register_type::<TerrainChunk>();
+        // Generate from a config inserted before the plugin right from the first chunk
+        WORLD_SEED.store(app.world().resource::<TerrainConfig>().seed, Ordering::Relaxed);
+        app
             .add_systems(Startup, spawn_initial_terrain)
-            .add_systems(Update, manage_terrain_chunks);
+            .add_systems(Update, manage_terrain_chunks
+                .run_if(in_state(GameState::Playing).or(in_state(GameState::Loading))))
+            // Generation isn't gated so the spawn area builds behind the main menu
+            .add_systems(Update, apply_terrain_config.run_if(resource_changed::<TerrainConfig>))
+            .add_systems(Update, generate_pending_chunks.after(manage_terrain_chunks).after(apply_terrain_config));
     }
 }
@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+use bevy::color::ColorToComponents;
+use bevy::pbr::{ExtendedMaterial, MaterialExtension};
+use bevy::render::render_resource::{AsBindGroup, ShaderRef, ShaderType};
+use crate::assets::detail_normal::create_detail_normal_map;
+
+// Terrain material constants
+const TERRAIN_SHADER: &str = "shaders/terrain.wgsl";
+const GRASS_COLOR: Color = Color::srgb(0.3, 0.5, 0.3); // The original flat terrain green
+const DRY_COLOR: Color = Color::srgb(0.45, 0.5, 0.27); // Patches of drier grass the macro noise mixes in
+const ROCK_COLOR: Color = Color::srgb(0.45, 0.42, 0.38); // Bare rock showing on steep slopes
+const MACRO_SCALE: f32 = 60.0; // Size in world units of the large color patches
+const MACRO_STRENGTH: f32 = 0.25; // How far the patches brighten and darken the ground
+const DETAIL_SCALE: f32 = 4.0; // World units covered by one tile of the detail normal map
+const DETAIL_STRENGTH: f32 = 0.6; // How strongly the detail normals lean the surface
+const DETAIL_DISTANCE: f32 = 40.0; // Distance from the camera where the detail normals have faded out
+const SLOPE_START: f32 = 0.15; // Slope, as one minus the normal's height, where rock starts to show
+const SLOPE_END: f32 = 0.35; // Slope where the ground is all rock
+
+// The terrain's standard material with the terrain shader on top
+pub type TerrainMaterial = ExtendedMaterial<StandardMaterial, TerrainExtension>;
+
+// Values the terrain shader reads, matching TerrainSettings in terrain.wgsl
+#[derive(ShaderType, Reflect, Debug, Clone, Copy)]
+pub struct TerrainSettings {
+    pub grass_color: Vec4,
+    pub dry_color: Vec4,
+    pub rock_color: Vec4,
+    pub macro_scale: f32,
+    pub macro_strength: f32,
+    pub detail_scale: f32,
+    pub detail_strength: f32,
+    pub detail_distance: f32,
+    pub slope_start: f32,
+    pub slope_end: f32,
+    // The cloud layer, kept in step with it by update_clouds; no shadow until then
+    pub cloud_offset: Vec2,
+    // Ground offset per meter of height toward the sun, so shadows fall where the sunlight crosses the clouds
+    pub cloud_slant: Vec2,
+    pub cloud_scale: f32,
+    pub cloud_coverage: f32,
+    pub cloud_softness: f32,
+    pub cloud_altitude: f32,
+    pub cloud_shadow: f32,
+}
+
+impl Default for TerrainSettings {
+    fn default() -> Self {
+        Self {
+            grass_color: GRASS_COLOR.to_linear().to_vec4(),
+            dry_color: DRY_COLOR.to_linear().to_vec4(),
+            rock_color: ROCK_COLOR.to_linear().to_vec4(),
+            macro_scale: MACRO_SCALE,
+            macro_strength: MACRO_STRENGTH,
+            detail_scale: DETAIL_SCALE,
+            detail_strength: DETAIL_STRENGTH,
+            detail_distance: DETAIL_DISTANCE,
+            slope_start: SLOPE_START,
+            slope_end: SLOPE_END,
+            cloud_offset: Vec2::ZERO,
+            cloud_slant: Vec2::ZERO,
+            cloud_scale: 1.0,
+            cloud_coverage: 0.0,
+            cloud_softness: 0.1,
+            cloud_altitude: 0.0,
+            cloud_shadow: 0.0,
+        }
+    }
+}
+
+// Colors the ground by slope and large-scale noise, shades it under the clouds and roughens it up close
+// with a detail normal map, then lights it like any standard material
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct TerrainExtension {
+    #[uniform(100)]
+    pub settings: TerrainSettings,
+    #[texture(101)]
+    #[sampler(102)]
+    pub detail_normal: Handle<Image>,
+}
+
+impl MaterialExtension for TerrainExtension {
+    fn fragment_shader() -> ShaderRef {
+        TERRAIN_SHADER.into()
+    }
+}
+
+// Create the material every terrain chunk and the horizon share
+pub fn create_terrain_material(images: &mut Assets<Image>) -> TerrainMaterial {
+    ExtendedMaterial {
+        // The shader supplies the color; the base only sets how the ground takes the light
+        base: StandardMaterial {
+            base_color: Color::WHITE,
+            perceptual_roughness: 0.85,
+            ..default()
+        },
+        extension: TerrainExtension {
+            settings: TerrainSettings::default(),
+            detail_normal: images.add(create_detail_normal_map()),
+        },
+    }
+}
+
+// Plugin for rendering the terrain material
+pub struct TerrainMaterialPlugin;
+
+impl Plugin for TerrainMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MaterialPlugin::<TerrainMaterial>::default());
+    }
+}
@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+use crate::network::NetSession;
+use crate::party::PartyGuest;
+use crate::photo_mode::PhotoModeState;
+use crate::player::{check_player_death, Health, Player, PlayerPhysics};
+use crate::sim::SimSet;
+use crate::GameState;
+
+// Tether constants
+const REST_LENGTH: f32 = 4.0; // Length of the rope before it starts to stretch
+const MAX_LENGTH: f32 = 9.0; // Length the rope can't stretch past
+const STIFFNESS: f32 = 6.0; // Pull in N for each meter the rope is stretched
+const DAMPING: f32 = 1.5; // Pull in N for each m/s the balls move apart or together while stretched
+const SNAP_DISTANCE: f32 = 30.0; // Gap, e.g. after a teleport, over which the rope is left alone until the balls are back in reach
+const ROPE_SEGMENTS: usize = 16; // Points along the drawn rope
+const ROPE_SAG: f32 = 0.5; // How far a slack rope hangs below the line between the balls, per meter of slack
+const ROPE_COLOR: Color = Color::srgb(0.8, 0.7, 0.5);
+const TAUT_COLOR: Color = Color::srgb(1.0, 0.35, 0.2);
+
+// How the two players on this machine are tied together, picked in the lobby
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TetherMode {
+    #[default]
+    Off,
+    // An elastic rope between the two balls
+    Rope,
+    // The rope, and a knockout of either player ends the run for both
+    SharedFate,
+}
+
+impl TetherMode {
+    // Next mode in the lobby cycle
+    pub fn next(self) -> Self {
+        match self {
+            TetherMode::Off => TetherMode::Rope,
+            TetherMode::Rope => TetherMode::SharedFate,
+            TetherMode::SharedFate => TetherMode::Off,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TetherMode::Off => "Off",
+            TetherMode::Rope => "Rope",
+            TetherMode::SharedFate => "Shared Fate",
+        }
+    }
+}
+
+// The tether picked in the session's lobby; there is none outside a session
+fn active_tether(session: Option<&NetSession>) -> TetherMode {
+    session.map_or(TetherMode::Off, |session| session.tether)
+}
+
+// Pull the two balls together with a spring once the rope is past its rest length, and stop them at
+// its full length: the part of their velocity taking them apart is cancelled, split by mass
+pub fn apply_tether(
+    session: Option<Res<NetSession>>,
+    mut player_query: Query<(&mut Transform, &mut PlayerPhysics, &Health), With<Player>>,
+    mut guest_query: Query<(&mut Transform, &mut PlayerPhysics, &Health), (With<PartyGuest>, Without<Player>)>,
+    time: Res<Time>,
+) {
+    if active_tether(session.as_deref()) == TetherMode::Off {
+        return;
+    }
+    let (Ok((mut a_transform, mut a, a_health)), Ok((mut b_transform, mut b, b_health))) =
+        (player_query.get_single_mut(), guest_query.get_single_mut())
+    else {
+        return;
+    };
+    if a_health.current <= 0.0 || b_health.current <= 0.0 {
+        return;
+    }
+    let offset = b_transform.translation - a_transform.translation;
+    let distance = offset.length();
+    if distance <= REST_LENGTH || distance > SNAP_DISTANCE {
+        return;
+    }
+    let direction = offset / distance;
+    let inverse_a = 1.0 / a.mass;
+    let inverse_b = 1.0 / b.mass;
+    let share_a = inverse_a / (inverse_a + inverse_b);
+    let share_b = 1.0 - share_a;
+
+    // Spring, with damping along the rope so the balls don't bounce on it forever
+    let separating = (b.velocity - a.velocity).dot(direction);
+    let force = (STIFFNESS * (distance - REST_LENGTH) + DAMPING * separating).max(0.0);
+    let delta = time.delta_secs();
+    a.velocity += direction * force * inverse_a * delta;
+    b.velocity -= direction * force * inverse_b * delta;
+
+    // Full length: put them back at the end of the rope and keep them from moving further apart
+    if distance > MAX_LENGTH {
+        let excess = distance - MAX_LENGTH;
+        a_transform.translation += direction * excess * share_a;
+        b_transform.translation -= direction * excess * share_b;
+        let separating = (b.velocity - a.velocity).dot(direction);
+        if separating > 0.0 {
+            a.velocity += direction * separating * share_a;
+            b.velocity -= direction * separating * share_b;
+        }
+    }
+    a.momentum = a.velocity;
+    b.momentum = b.velocity;
+}
+
+// With shared fate, the guest being knocked out takes the main player down too, ending the run
+pub fn share_fate(
+    session: Option<Res<NetSession>>,
+    guest_query: Query<&Health, (With<PartyGuest>, Changed<Health>)>,
+    mut player_query: Query<&mut Health, (With<Player>, Without<PartyGuest>)>,
+) {
+    if active_tether(session.as_deref()) != TetherMode::SharedFate {
+        return;
+    }
+    let (Ok(guest), Ok(mut player)) = (guest_query.get_single(), player_query.get_single_mut()) else {
+        return;
+    };
+    if guest.current <= 0.0 && player.current > 0.0 {
+        player.current = 0.0;
+    }
+}
+
+// Draw the rope between the balls, hanging when slack and turning red as it nears full length
+pub fn draw_tether(
+    mut gizmos: Gizmos,
+    session: Option<Res<NetSession>>,
+    player_query: Query<&Transform, With<Player>>,
+    guest_query: Query<(&Transform, &Health), With<PartyGuest>>,
+) {
+    if active_tether(session.as_deref()) == TetherMode::Off {
+        return;
+    }
+    let (Ok(start), Ok((end, health))) = (player_query.get_single(), guest_query.get_single()) else {
+        return;
+    };
+    let (start, end) = (start.translation, end.translation);
+    let distance = start.distance(end);
+    if health.current <= 0.0 || distance > SNAP_DISTANCE {
+        return;
+    }
+    let sag = (REST_LENGTH - distance).max(0.0) * ROPE_SAG;
+    let tension = ((distance - REST_LENGTH) / (MAX_LENGTH - REST_LENGTH)).clamp(0.0, 1.0);
+    let color = ROPE_COLOR.mix(&TAUT_COLOR, tension);
+    let points = (0..=ROPE_SEGMENTS).map(|i| {
+        let t = i as f32 / ROPE_SEGMENTS as f32;
+        // A parabola is close enough to a hanging rope at this length
+        start.lerp(end, t) - Vec3::Y * sag * 4.0 * t * (1.0 - t)
+    });
+    gizmos.linestrip(points, color);
+}
+
+// Plugin for the rope between the two players on one machine
+pub struct TetherPlugin;
+
+impl Plugin for TetherPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // After both balls have rolled this tick
+            .add_systems(FixedUpdate, apply_tether
+                .in_set(SimSet::Constraints)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)))
+            .add_systems(Update, share_fate
+                .before(check_player_death)
+                .run_if(in_state(GameState::Playing)))
+            .add_systems(Update, draw_tether.run_if(in_state(GameState::Playing)));
+    }
+}
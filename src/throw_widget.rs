@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use std::f32::consts::TAU;
+use crate::projectile::{Ammo, ThrowCharge};
+use crate::photo_mode::PhotoModeState;
+use crate::tuning::LiveTuning;
+use crate::GameState;
+
+// Throw widget layout constants
+const RING_SEGMENTS: usize = 24; // Dots making up each ring
+const CHARGE_RING_RADIUS: f32 = 18.0; // Inner ring showing throw charge, in pixels
+const RELOAD_RING_RADIUS: f32 = 26.0; // Outer ring sweeping through the reload
+const DOT_SIZE: f32 = 4.0; // Diameter of a ring dot in pixels
+const UNLIT_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.15);
+const CHARGE_COLOR: Color = Color::srgb(1.0, 0.75, 0.2);
+const FULL_CHARGE_COLOR: Color = Color::srgb(1.0, 0.35, 0.2);
+const RELOAD_COLOR: Color = Color::srgb(0.5, 0.8, 1.0);
+
+// Which value a ring displays
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WidgetRing {
+    Charge,
+    Reload,
+}
+
+// Marker for the widget root, which follows the mouse cursor
+#[derive(Component)]
+pub struct ThrowWidgetRoot;
+
+// One dot of a ring; lit once the ring's fraction passes its index
+#[derive(Component)]
+pub struct WidgetRingDot {
+    pub ring: WidgetRing,
+    pub index: usize,
+}
+
+// Spawn the two dotted rings around a zero-size root that sits on the cursor
+pub fn spawn_throw_widget(mut commands: Commands) {
+    commands
+        .spawn((
+            ThrowWidgetRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            for (ring, radius) in [(WidgetRing::Charge, CHARGE_RING_RADIUS), (WidgetRing::Reload, RELOAD_RING_RADIUS)] {
+                for index in 0..RING_SEGMENTS {
+                    // Clockwise from the top, like a clock hand
+                    let angle = index as f32 / RING_SEGMENTS as f32 * TAU;
+                    let offset = Vec2::new(angle.sin(), -angle.cos()) * radius;
+                    parent.spawn((
+                        WidgetRingDot { ring, index },
+                        Node {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(offset.x - DOT_SIZE * 0.5),
+                            top: Val::Px(offset.y - DOT_SIZE * 0.5),
+                            width: Val::Px(DOT_SIZE),
+                            height: Val::Px(DOT_SIZE),
+                            ..default()
+                        },
+                        BackgroundColor(UNLIT_COLOR),
+                        BorderRadius::MAX,
+                    ));
+                }
+            }
+        });
+}
+
+// Hide the widget outside of gameplay
+pub fn hide_throw_widget(mut query: Query<&mut Visibility, With<ThrowWidgetRoot>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Keep the widget on the cursor and fill the rings from the charge and reload progress
+pub fn update_throw_widget(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    charge: Res<ThrowCharge>,
+    ammo: Res<Ammo>,
+    tuning: Res<LiveTuning>,
+    mut root_query: Query<(&mut Node, &mut Visibility), With<ThrowWidgetRoot>>,
+    mut dot_query: Query<(&WidgetRingDot, &mut BackgroundColor, &mut Visibility), Without<ThrowWidgetRoot>>,
+) {
+    let Ok((mut node, mut visibility)) = root_query.get_single_mut() else {
+        return;
+    };
+    let cursor = window_query.get_single().ok().and_then(|window| window.cursor_position());
+    let reloading = ammo.current < ammo.max;
+
+    // Only show while there is something to report
+    let Some(cursor) = cursor.filter(|_| charge.charging || reloading) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    node.left = Val::Px(cursor.x);
+    node.top = Val::Px(cursor.y);
+    *visibility = Visibility::Inherited;
+
+    let reload = ammo.reload_progress(tuning.0.projectile.reload_time);
+    for (dot, mut color, mut dot_visibility) in dot_query.iter_mut() {
+        let (shown, fraction, lit_color) = match dot.ring {
+            WidgetRing::Charge => {
+                let lit = if charge.level >= 1.0 { FULL_CHARGE_COLOR } else { CHARGE_COLOR };
+                (charge.charging, charge.level, lit)
+            }
+            WidgetRing::Reload => (reloading, reload, RELOAD_COLOR),
+        };
+        *dot_visibility = if shown { Visibility::Inherited } else { Visibility::Hidden };
+        let lit = (dot.index as f32 + 0.5) / RING_SEGMENTS as f32 <= fraction;
+        color.0 = if lit { lit_color } else { UNLIT_COLOR };
+    }
+}
+
+// Plugin for the throw charge and reload widget
+pub struct ThrowWidgetPlugin;
+
+impl Plugin for ThrowWidgetPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, spawn_throw_widget)
+            .add_systems(OnExit(GameState::Playing), hide_throw_widget)
+            .add_systems(Update, update_throw_widget
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)));
+    }
+}
@@ -0,0 +1,228 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use crate::multiball::Multiball;
+use crate::network::NetSession;
+use crate::photo_mode::PhotoModeState;
+use crate::player::{Health, Player, PlayerPhysics};
+use crate::results::RunStats;
+use crate::sim::{advance_simulation, PauseReason, SimClock, SIM_TICK_RATE};
+use crate::GameState;
+
+// Time control constants
+const SLOW_MOTION_SCALES: [f32; 3] = [1.0, 0.5, 0.25]; // Speeds T cycles through
+const REWIND_SECONDS: f64 = 5.0; // How far back the rewind buffer reaches
+const REWIND_SPEED: f32 = 2.0; // Ticks stepped back for each tick of real time while rewinding
+const INDICATOR_FONT_SIZE: f32 = 22.0;
+
+// A ball's state at the end of a tick
+struct PlayerSnapshot {
+    entity: Entity,
+    translation: Vec3,
+    rotation: Quat,
+    physics: PlayerPhysics,
+    health: f32,
+}
+
+// The last few seconds of every ball's state, one entry per tick, for rewinding after a bad fall. Only
+// the balls go back; the world and the run clock keep their own time.
+#[derive(Resource, Default)]
+pub struct RewindBuffer {
+    ticks: VecDeque<Vec<PlayerSnapshot>>,
+    // Part of a tick owed to stepping back, carried between frames
+    owed: f32,
+}
+
+// Banner at the top of the screen while time runs differently
+#[derive(Component)]
+pub struct TimeIndicator;
+
+// Time controls are for playing alone; a LAN game has to keep every machine on the same tick
+fn playing_alone(session: Option<Res<NetSession>>) -> bool {
+    session.is_none()
+}
+
+// Put time back to normal and forget the rewind history, between runs
+pub fn reset_time_controls(mut clock: ResMut<SimClock>, mut buffer: ResMut<RewindBuffer>) {
+    clock.resume(PauseReason::Player);
+    clock.resume(PauseReason::Rewind);
+    clock.set_time_scale(1.0);
+    *buffer = RewindBuffer::default();
+}
+
+// T cycles through slow motion speeds and F8 freezes the game in place
+pub fn handle_time_keys(keys: Res<ButtonInput<KeyCode>>, mut clock: ResMut<SimClock>) {
+    if keys.just_pressed(KeyCode::KeyT) {
+        let current = SLOW_MOTION_SCALES.iter().position(|scale| *scale == clock.time_scale()).unwrap_or(0);
+        clock.set_time_scale(SLOW_MOTION_SCALES[(current + 1) % SLOW_MOTION_SCALES.len()]);
+    }
+    if keys.just_pressed(KeyCode::F8) {
+        if clock.paused_by(PauseReason::Player) {
+            clock.resume(PauseReason::Player);
+        } else {
+            clock.pause(PauseReason::Player);
+        }
+    }
+}
+
+// Mark the run once time has been slowed, frozen or rewound, so it stays off the leaderboards
+pub fn mark_time_assisted(clock: Res<SimClock>, mut run_stats: ResMut<RunStats>) {
+    let bent = clock.time_scale() != 1.0 || clock.paused_by(PauseReason::Player) || clock.paused_by(PauseReason::Rewind);
+    if bent && !run_stats.time_assisted {
+        run_stats.time_assisted = true;
+    }
+}
+
+// Remember where every ball is at the end of the tick, dropping what has fallen out of the window
+pub fn record_rewind(
+    mut buffer: ResMut<RewindBuffer>,
+    player_query: Query<(Entity, &Transform, &PlayerPhysics, &Health), With<Player>>,
+) {
+    let snapshots = player_query
+        .iter()
+        .map(|(entity, transform, physics, health)| PlayerSnapshot {
+            entity,
+            translation: transform.translation,
+            rotation: transform.rotation,
+            physics: physics.clone(),
+            health: health.current,
+        })
+        .collect();
+    buffer.ticks.push_back(snapshots);
+    let capacity = (REWIND_SECONDS * SIM_TICK_RATE) as usize;
+    while buffer.ticks.len() > capacity {
+        buffer.ticks.pop_front();
+    }
+}
+
+// Hold R to step the balls back through the buffer, with the simulation held still meanwhile; letting
+// go carries on from wherever the rewind stopped
+pub fn rewind(
+    keys: Res<ButtonInput<KeyCode>>,
+    real_time: Res<Time<Real>>,
+    mut clock: ResMut<SimClock>,
+    mut buffer: ResMut<RewindBuffer>,
+    mut multiball: ResMut<Multiball>,
+    mut player_query: Query<(&mut Transform, &mut PlayerPhysics, &mut Health), With<Player>>,
+) {
+    if !keys.pressed(KeyCode::KeyR) {
+        if clock.paused_by(PauseReason::Rewind) {
+            clock.resume(PauseReason::Rewind);
+        }
+        buffer.owed = 0.0;
+        return;
+    }
+    if !clock.paused_by(PauseReason::Rewind) {
+        clock.pause(PauseReason::Rewind);
+    }
+
+    // Virtual time is stopped, so the pace comes from real time
+    buffer.owed += real_time.delta_secs() * SIM_TICK_RATE as f32 * REWIND_SPEED;
+    let mut stepped = false;
+    // The oldest tick stays, so holding R at the end of the buffer rests there
+    while buffer.owed >= 1.0 && buffer.ticks.len() > 1 {
+        buffer.ticks.pop_back();
+        buffer.owed -= 1.0;
+        stepped = true;
+    }
+    if !stepped {
+        return;
+    }
+    let Some(snapshots) = buffer.ticks.back() else {
+        return;
+    };
+    for snapshot in snapshots {
+        if let Ok((mut transform, mut physics, mut health)) = player_query.get_mut(snapshot.entity) {
+            transform.translation = snapshot.translation;
+            transform.rotation = snapshot.rotation;
+            // The size stays as it is now: the scale isn't rewound, and neither is a multiball split
+            let radius = physics.radius;
+            *physics = snapshot.physics.clone();
+            physics.radius = radius;
+            health.current = snapshot.health;
+            multiball.guard_health(health.current);
+        }
+    }
+}
+
+// Create the time banner, hidden until time runs differently
+pub fn spawn_time_indicator(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                // Below the compass and the boss health bar
+                top: Val::Px(100.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            PickingBehavior::IGNORE,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TimeIndicator,
+                Text::new(""),
+                TextFont {
+                    font_size: INDICATOR_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.7, 0.85, 1.0)),
+                Visibility::Hidden,
+            ));
+        });
+}
+
+// Say on the banner what the clock is doing, or hide it when time runs normally
+pub fn update_time_indicator(
+    clock: Res<SimClock>,
+    state: Res<State<GameState>>,
+    mut indicator_query: Query<(&mut Text, &mut Visibility), With<TimeIndicator>>,
+) {
+    let message = if *state.get() != GameState::Playing {
+        None
+    } else if clock.paused_by(PauseReason::Rewind) {
+        Some("<< Rewinding".to_string())
+    } else if clock.paused_by(PauseReason::Player) {
+        Some("Frozen (F8 to resume)".to_string())
+    } else if clock.time_scale() != 1.0 {
+        Some(format!("Slow motion {:.0}%", clock.time_scale() * 100.0))
+    } else {
+        None
+    };
+    for (mut text, mut visibility) in indicator_query.iter_mut() {
+        match &message {
+            Some(message) => {
+                if text.0 != *message {
+                    text.0 = message.clone();
+                }
+                visibility.set_if_neq(Visibility::Inherited);
+            }
+            None => {
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+    }
+}
+
+// Plugin for slow motion, freezing and rewinding a single-player run
+pub struct TimeControlPlugin;
+
+impl Plugin for TimeControlPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<RewindBuffer>()
+            .add_systems(Startup, spawn_time_indicator)
+            .add_systems(OnExit(GameState::Loading), reset_time_controls)
+            .add_systems(OnEnter(GameState::GameOver), reset_time_controls)
+            .add_systems(OnEnter(GameState::MainMenu), reset_time_controls)
+            .add_systems(Update, (handle_time_keys, rewind, mark_time_assisted).chain()
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive))
+                .run_if(playing_alone))
+            .add_systems(Update, update_time_indicator)
+            .add_systems(FixedLast, record_rewind
+                .before(advance_simulation)
+                .run_if(in_state(GameState::Playing))
+                .run_if(playing_alone));
+    }
+}
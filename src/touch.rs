@@ -0,0 +1,294 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use crate::camera::{cursor_raycasting, update_mouse_position, MouseLook};
+use crate::input::{update_action_state, Action, ActionState};
+use crate::sim::latch_input;
+use crate::GameState;
+
+// Touch layout constants, in logical pixels
+const JOYSTICK_ZONE: f32 = 0.4; // Fraction of the screen width, from the left, where a touch grabs the joystick
+const JOYSTICK_RADIUS: f32 = 60.0; // Distance the knob can travel from the joystick's center
+const JOYSTICK_DEAD_ZONE: f32 = 0.12; // Fraction of the radius ignored around the center
+const JOYSTICK_REST: Vec2 = Vec2::new(110.0, 110.0); // Where the joystick waits, from the bottom left corner
+const KNOB_RADIUS: f32 = 26.0;
+const BASE_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.12);
+const KNOB_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.35);
+const BUTTON_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.18);
+const BUTTON_HELD_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.4);
+
+// On-screen buttons: action, margins from the right and bottom edges, radius and label
+const TOUCH_BUTTONS: [(Action, f32, f32, f32, &str); 3] = [
+    (Action::Jump, 24.0, 24.0, 44.0, "Jump"),
+    (Action::Dash, 128.0, 36.0, 34.0, "Dash"),
+    (Action::Shield, 36.0, 128.0, 34.0, "Shield"),
+];
+
+// A finger holding the joystick: where it went down and where it is now
+#[derive(Clone, Copy, Debug)]
+pub struct VirtualJoystick {
+    pub id: u64,
+    pub origin: Vec2,
+    pub position: Vec2,
+}
+
+impl VirtualJoystick {
+    // Movement as (right, forward), at most unit length
+    pub fn move_axis(&self) -> Vec2 {
+        let offset = (self.position - self.origin) / JOYSTICK_RADIUS;
+        // Screen y grows downward, forward is up
+        let axis = Vec2::new(offset.x, -offset.y).clamp_length_max(1.0);
+        if axis.length() < JOYSTICK_DEAD_ZONE { Vec2::ZERO } else { axis }
+    }
+}
+
+// Touch input for phones and tablets: a virtual joystick on the left of the screen, jump and dash buttons
+// in the bottom right, and anywhere else on the right to aim, throwing when the finger lifts
+#[derive(Resource, Default)]
+pub struct TouchControls {
+    // Set by the first touch; the on-screen controls stay hidden until then
+    pub active: bool,
+    pub joystick: Option<VirtualJoystick>,
+    // Finger aiming a throw and where it is
+    pub aim: Option<(u64, Vec2)>,
+    // Fingers on the on-screen buttons
+    pub buttons: Vec<(u64, Action)>,
+    // Actions whose touch started this frame
+    pub just_pressed: Vec<Action>,
+}
+
+impl TouchControls {
+    // Actions held by a finger this frame
+    fn held(&self) -> impl Iterator<Item = Action> + '_ {
+        self.buttons
+            .iter()
+            .map(|(_, action)| *action)
+            .chain(self.aim.map(|_| Action::Throw))
+    }
+
+    // Let go of a finger, whatever it was doing
+    fn release(&mut self, id: u64) {
+        if self.joystick.is_some_and(|joystick| joystick.id == id) {
+            self.joystick = None;
+        }
+        if self.aim.is_some_and(|(aim_id, _)| aim_id == id) {
+            self.aim = None;
+        }
+        self.buttons.retain(|(button_id, _)| *button_id != id);
+    }
+}
+
+// Marker for the joystick's base ring and its knob
+#[derive(Component)]
+pub struct JoystickBase;
+
+#[derive(Component)]
+pub struct JoystickKnob;
+
+// An on-screen button for an action
+#[derive(Component)]
+pub struct TouchButton(pub Action);
+
+// Center of an on-screen button in a window of the given size
+fn button_center(size: Vec2, right: f32, bottom: f32, radius: f32) -> Vec2 {
+    Vec2::new(size.x - right - radius, size.y - bottom - radius)
+}
+
+// The on-screen button under a point, if any
+fn button_at(position: Vec2, size: Vec2) -> Option<Action> {
+    TOUCH_BUTTONS
+        .iter()
+        .find(|(_, right, bottom, radius, _)| position.distance(button_center(size, *right, *bottom, *radius)) <= *radius)
+        .map(|(action, ..)| *action)
+}
+
+// Spawn the joystick and buttons, hidden until the screen is touched
+pub fn spawn_touch_controls(mut commands: Commands) {
+    let base_size = JOYSTICK_RADIUS * 2.0;
+    commands
+        .spawn((
+            JoystickBase,
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(JOYSTICK_REST.x - JOYSTICK_RADIUS),
+                bottom: Val::Px(JOYSTICK_REST.y - JOYSTICK_RADIUS),
+                width: Val::Px(base_size),
+                height: Val::Px(base_size),
+                ..default()
+            },
+            BorderRadius::MAX,
+            BackgroundColor(BASE_COLOR),
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                JoystickKnob,
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(JOYSTICK_RADIUS - KNOB_RADIUS),
+                    top: Val::Px(JOYSTICK_RADIUS - KNOB_RADIUS),
+                    width: Val::Px(KNOB_RADIUS * 2.0),
+                    height: Val::Px(KNOB_RADIUS * 2.0),
+                    ..default()
+                },
+                BorderRadius::MAX,
+                BackgroundColor(KNOB_COLOR),
+            ));
+        });
+
+    for (action, right, bottom, radius, label) in TOUCH_BUTTONS {
+        commands
+            .spawn((
+                TouchButton(action),
+                Node {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(right),
+                    bottom: Val::Px(bottom),
+                    width: Val::Px(radius * 2.0),
+                    height: Val::Px(radius * 2.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BorderRadius::MAX,
+                BackgroundColor(BUTTON_COLOR),
+                Visibility::Hidden,
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new(label),
+                    TextFont {
+                        font_size: 16.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                ));
+            });
+    }
+}
+
+// Follow every finger on the screen and add what they are doing to the action state, the same way a
+// keyboard or gamepad would
+pub fn read_touches(
+    touches: Res<Touches>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut controls: ResMut<TouchControls>,
+    mut state: ResMut<ActionState>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let size = window.size();
+    controls.just_pressed.clear();
+
+    for touch in touches.iter_just_pressed() {
+        controls.active = true;
+        let (id, position) = (touch.id(), touch.position());
+        if let Some(action) = button_at(position, size) {
+            controls.buttons.push((id, action));
+            controls.just_pressed.push(action);
+        } else if position.x < size.x * JOYSTICK_ZONE {
+            if controls.joystick.is_none() {
+                controls.joystick = Some(VirtualJoystick { id, origin: position, position });
+            }
+        } else if controls.aim.is_none() {
+            controls.aim = Some((id, position));
+            controls.just_pressed.push(Action::Throw);
+        }
+    }
+    for touch in touches.iter() {
+        if let Some(joystick) = controls.joystick.as_mut().filter(|joystick| joystick.id == touch.id()) {
+            joystick.position = touch.position();
+        }
+        if let Some(aim) = controls.aim.as_mut().filter(|(id, _)| *id == touch.id()) {
+            aim.1 = touch.position();
+        }
+    }
+
+    // Presses are added before releases so a tap that starts and ends in one frame still counts
+    for action in controls.held().collect::<Vec<_>>() {
+        state.press(action, controls.just_pressed.contains(&action));
+    }
+    if let Some(joystick) = controls.joystick {
+        state.add_move_axis(joystick.move_axis());
+    }
+
+    for touch in touches.iter_just_released().chain(touches.iter_just_canceled()) {
+        controls.release(touch.id());
+    }
+}
+
+// Point the aim at the finger aiming a throw, in place of the mouse cursor
+pub fn aim_with_touch(controls: Res<TouchControls>, mut mouse_look: ResMut<MouseLook>) {
+    if let Some((_, position)) = controls.aim {
+        mouse_look.cursor_position = position;
+    }
+}
+
+// Show the controls once the screen has been touched, with the joystick under the finger holding it
+pub fn update_touch_ui(
+    controls: Res<TouchControls>,
+    mut base_query: Query<(&mut Node, &mut Visibility), With<JoystickBase>>,
+    mut knob_query: Query<&mut Node, (With<JoystickKnob>, Without<JoystickBase>)>,
+    mut button_query: Query<(&TouchButton, &mut BackgroundColor, &mut Visibility), Without<JoystickBase>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let visibility = if controls.active { Visibility::Inherited } else { Visibility::Hidden };
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    if let Ok((mut base, mut base_visibility)) = base_query.get_single_mut() {
+        *base_visibility = visibility;
+        let (center, knob) = match controls.joystick {
+            Some(joystick) => {
+                let offset = (joystick.position - joystick.origin).clamp_length_max(JOYSTICK_RADIUS);
+                (Vec2::new(joystick.origin.x, window.height() - joystick.origin.y), offset)
+            }
+            None => (JOYSTICK_REST, Vec2::ZERO),
+        };
+        base.left = Val::Px(center.x - JOYSTICK_RADIUS);
+        base.bottom = Val::Px(center.y - JOYSTICK_RADIUS);
+        if let Ok(mut knob_node) = knob_query.get_single_mut() {
+            knob_node.left = Val::Px(JOYSTICK_RADIUS - KNOB_RADIUS + knob.x);
+            knob_node.top = Val::Px(JOYSTICK_RADIUS - KNOB_RADIUS + knob.y);
+        }
+    }
+    for (button, mut color, mut button_visibility) in button_query.iter_mut() {
+        *button_visibility = visibility;
+        let held = controls.buttons.iter().any(|(_, action)| *action == button.0);
+        color.0 = if held { BUTTON_HELD_COLOR } else { BUTTON_COLOR };
+    }
+}
+
+// Lift every finger and hide the controls when play stops, so nothing stays held through a menu
+pub fn hide_touch_controls(
+    mut controls: ResMut<TouchControls>,
+    mut query: Query<&mut Visibility, Or<(With<JoystickBase>, With<TouchButton>)>>,
+) {
+    controls.joystick = None;
+    controls.aim = None;
+    controls.buttons.clear();
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Plugin for the touch controls
+pub struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<TouchControls>()
+            .add_systems(Startup, spawn_touch_controls)
+            .add_systems(PreUpdate, read_touches
+                .after(update_action_state)
+                .before(latch_input)
+                .run_if(in_state(GameState::Playing)))
+            .add_systems(Update, aim_with_touch
+                .after(update_mouse_position)
+                .before(cursor_raycasting)
+                .run_if(in_state(GameState::Playing)))
+            .add_systems(Update, update_touch_ui.run_if(in_state(GameState::Playing)))
+            .add_systems(OnExit(GameState::Playing), hide_touch_controls);
+    }
+}
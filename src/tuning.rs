@@ -0,0 +1,331 @@
+use bevy::prelude::*;
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use serde::Deserialize;
+use std::path::PathBuf;
+use crate::camera::CameraSettings;
+use crate::mods::ModPacks;
+use crate::replay::fnv1a;
+
+// Tuning file inside the assets folder; edits to it are picked up while the game runs
+const TUNING_PATH: &str = "tuning.ron";
+
+// Rolling physics for the player and every other ball that uses roll_sphere
+#[derive(Debug, Clone, Copy, Deserialize, Reflect)]
+#[serde(default)]
+pub struct PlayerTuning {
+    pub move_speed: f32,
+    pub gravity: f32,
+    // Horizontal velocity kept each frame while rolling on the ground
+    pub friction: f32,
+    // How strongly slopes pull the ball downhill
+    pub terrain_sensitivity: f32,
+    // How much of the old momentum is kept each frame
+    pub momentum_factor: f32,
+    // Fraction of the landing speed bounced back up
+    pub restitution: f32,
+    pub mass_factor: f32,
+    pub max_speed: f32,
+    pub jump_force: f32,
+    // Horizontal speed added by a dash and seconds between dashes
+    pub dash_impulse: f32,
+    pub dash_cooldown: f32,
+}
+
+// Boulder flight and throwing
+#[derive(Debug, Clone, Copy, Deserialize, Reflect)]
+#[serde(default)]
+pub struct ProjectileTuning {
+    pub gravity: f32,
+    // Flight time before a boulder that never hits anything is removed
+    pub lifetime: f32,
+    // Horizontal units per second used to pace a throw
+    pub speed: f32,
+    // Throw range with no charge, and the extra range at full charge
+    pub max_horizontal_dist: f32,
+    pub charge_range_bonus: f32,
+    pub max_horizontal_velocity: f32,
+    // Seconds of holding throw to reach full charge
+    pub charge_time: f32,
+    // Seconds to restore one boulder
+    pub reload_time: f32,
+    // Impacts closer than the radius shake the camera, hardest right next to the player
+    pub impact_shake_radius: f32,
+    pub impact_shake_trauma: f32,
+}
+
+// Follow camera feel; preset distances and smoothing stay in config/camera.ron
+#[derive(Debug, Clone, Copy, Deserialize, Reflect)]
+#[serde(default)]
+pub struct CameraTuning {
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub zoom_speed: f32,
+    pub zoom_smoothness: f32,
+    pub collision_margin: f32,
+    pub collision_recovery: f32,
+    pub base_fov: f32,
+    pub max_speed_fov: f32,
+    pub speed_distance_bonus: f32,
+    pub speed_curve_exponent: f32,
+    pub speed_response: f32,
+    pub look_ahead_time: f32,
+    pub tactical_height: f32,
+    pub tactical_transition_speed: f32,
+}
+
+// Every balancing value in one asset; fields missing from the file keep their defaults
+#[derive(Asset, Reflect, Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct GameTuning {
+    pub player: PlayerTuning,
+    pub projectile: ProjectileTuning,
+    pub camera: CameraTuning,
+}
+
+// Built-in values, used until the tuning file loads or if it is missing
+pub const DEFAULT_TUNING: GameTuning = GameTuning {
+    player: PlayerTuning {
+        move_speed: 1.5,
+        gravity: 9.8,
+        friction: 0.95,
+        terrain_sensitivity: 0.3,
+        momentum_factor: 0.85,
+        restitution: 0.4,
+        mass_factor: 0.8,
+        max_speed: 6.0,
+        jump_force: 8.0,
+        dash_impulse: 5.0,
+        dash_cooldown: 1.2,
+    },
+    projectile: ProjectileTuning {
+        // Double the normal gravity for a heavier feel
+        gravity: 19.6,
+        lifetime: 8.0,
+        // Slow for a plodding catapult feel
+        speed: 1.0,
+        max_horizontal_dist: 12.0,
+        charge_range_bonus: 8.0,
+        max_horizontal_velocity: 20.0,
+        charge_time: 1.0,
+        reload_time: 1.5,
+        impact_shake_radius: 10.0,
+        impact_shake_trauma: 0.35,
+    },
+    camera: CameraTuning {
+        min_distance: 3.0,
+        max_distance: 20.0,
+        zoom_speed: 1.0,
+        zoom_smoothness: 8.0,
+        collision_margin: 0.5,
+        collision_recovery: 2.0,
+        base_fov: std::f32::consts::FRAC_PI_4,
+        max_speed_fov: 1.05,
+        speed_distance_bonus: 2.5,
+        speed_curve_exponent: 1.5,
+        speed_response: 3.0,
+        look_ahead_time: 0.5,
+        tactical_height: 30.0,
+        tactical_transition_speed: 4.0,
+    },
+};
+
+impl Default for GameTuning {
+    fn default() -> Self {
+        DEFAULT_TUNING
+    }
+}
+
+impl Default for PlayerTuning {
+    fn default() -> Self {
+        DEFAULT_TUNING.player
+    }
+}
+
+impl Default for ProjectileTuning {
+    fn default() -> Self {
+        DEFAULT_TUNING.projectile
+    }
+}
+
+impl Default for CameraTuning {
+    fn default() -> Self {
+        DEFAULT_TUNING.camera
+    }
+}
+
+impl CameraTuning {
+    // Copy the tuned values into the live camera settings
+    pub fn apply(&self, settings: &mut CameraSettings) {
+        settings.min_distance = self.min_distance;
+        settings.max_distance = self.max_distance;
+        settings.target_distance = settings.target_distance.clamp(self.min_distance, self.max_distance);
+        settings.zoom_speed = self.zoom_speed;
+        settings.zoom_smoothness = self.zoom_smoothness;
+        settings.collision_margin = self.collision_margin;
+        settings.collision_recovery = self.collision_recovery;
+        settings.base_fov = self.base_fov;
+        settings.max_speed_fov = self.max_speed_fov;
+        settings.speed_distance_bonus = self.speed_distance_bonus;
+        settings.speed_curve_exponent = self.speed_curve_exponent;
+        settings.speed_response = self.speed_response;
+        settings.look_ahead_time = self.look_ahead_time;
+        settings.tactical_height = self.tactical_height;
+        settings.tactical_transition_speed = self.tactical_transition_speed;
+    }
+}
+
+// The tuning in effect, so an inspector can show it and change it while balancing; systems read it and
+// pass the values down to physics helpers like roll_sphere and compute_launch
+#[derive(Resource, Reflect, Default, Clone, Copy)]
+#[reflect(Resource)]
+pub struct LiveTuning(pub GameTuning);
+
+// Fingerprint of the tuning that affects the simulation, so replays can tell if they still play back
+// the same; camera feel is left out since it doesn't change what happens
+pub fn tuning_hash(tuning: &GameTuning) -> u64 {
+    let player = &tuning.player;
+    let projectile = &tuning.projectile;
+    let values = [
+        player.move_speed,
+        player.gravity,
+        player.friction,
+        player.terrain_sensitivity,
+        player.momentum_factor,
+        player.restitution,
+        player.mass_factor,
+        player.max_speed,
+        player.jump_force,
+        player.dash_impulse,
+        player.dash_cooldown,
+        projectile.gravity,
+        projectile.lifetime,
+        projectile.speed,
+        projectile.max_horizontal_dist,
+        projectile.charge_range_bonus,
+        projectile.max_horizontal_velocity,
+        projectile.charge_time,
+        projectile.reload_time,
+        projectile.impact_shake_radius,
+        projectile.impact_shake_trauma,
+    ];
+    fnv1a(values.into_iter().flat_map(|value| value.to_bits().to_le_bytes()))
+}
+
+// Lay one tuning file's values over another's field by field, so an override only needs the values it
+// changes
+fn merge_tuning(base: &mut ron::Value, overlay: ron::Value) {
+    match (base, overlay) {
+        (ron::Value::Map(base), ron::Value::Map(overlay)) => {
+            for (key, value) in overlay.iter() {
+                let existing = base.iter_mut().find(|(existing, _)| *existing == key).map(|(_, existing)| existing);
+                match existing {
+                    Some(existing) => merge_tuning(existing, value.clone()),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+// Reads tuning.ron into a GameTuning asset, with each mod pack's tuning.ron laid over it in load order
+pub struct GameTuningLoader {
+    overrides: Vec<PathBuf>,
+}
+
+impl FromWorld for GameTuningLoader {
+    fn from_world(world: &mut World) -> Self {
+        let overrides = world.get_resource::<ModPacks>().map(|packs| packs.files(TUNING_PATH)).unwrap_or_default();
+        Self { overrides }
+    }
+}
+
+impl AssetLoader for GameTuningLoader {
+    type Asset = GameTuning;
+    type Settings = ();
+    type Error = std::io::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<GameTuning, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut tuning: ron::Value = ron::de::from_bytes(&bytes).map_err(std::io::Error::other)?;
+        for path in &self.overrides {
+            let overlay = std::fs::read_to_string(path)
+                .map_err(|error| error.to_string())
+                .and_then(|contents| ron::from_str(&contents).map_err(|error| error.to_string()));
+            match overlay {
+                Ok(overlay) => merge_tuning(&mut tuning, overlay),
+                Err(error) => println!("Invalid mod tuning {}: {}", path.display(), error),
+            }
+        }
+        tuning.into_rust().map_err(std::io::Error::other)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+// Handle keeping the tuning asset loaded and watched
+#[derive(Resource)]
+pub struct TuningHandle(pub Handle<GameTuning>);
+
+// Start loading the tuning file
+pub fn load_tuning(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TuningHandle(asset_server.load(TUNING_PATH)));
+}
+
+// Take up the tuning file when it first loads and whenever it is edited
+pub fn load_tuning_changes(
+    mut events: EventReader<AssetEvent<GameTuning>>,
+    handle: Option<Res<TuningHandle>>,
+    assets: Res<Assets<GameTuning>>,
+    mut live: ResMut<LiveTuning>,
+) {
+    let Some(handle) = handle else {
+        return;
+    };
+    for event in events.read() {
+        let updated = match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => *id == handle.0.id(),
+            _ => false,
+        };
+        if !updated {
+            continue;
+        }
+        if let Some(new_tuning) = assets.get(&handle.0) {
+            live.0 = *new_tuning;
+            println!("Applied gameplay tuning from {}", TUNING_PATH);
+        }
+    }
+}
+
+// Put the live camera tuning into the camera settings, whether it came from the file or an inspector
+pub fn apply_tuning(live: Res<LiveTuning>, mut camera_settings: ResMut<CameraSettings>) {
+    live.0.camera.apply(&mut camera_settings);
+}
+
+// Plugin for hot-reloadable gameplay tuning
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_asset::<GameTuning>()
+            .init_asset_loader::<GameTuningLoader>()
+            .init_resource::<LiveTuning>()
+            .register_type::<LiveTuning>()
+            .add_systems(Startup, load_tuning)
+            // The built-in values are already in effect, so only changes are applied
+            .add_systems(Update, (load_tuning_changes, apply_tuning
+                .run_if(resource_changed::<LiveTuning>.and(not(resource_added::<LiveTuning>)))).chain());
+    }
+}
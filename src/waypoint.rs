@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use crate::camera::FollowCamera;
+use crate::graphics::RenderScale;
+use crate::player::Player;
+use crate::GameState;
+
+// Waypoint indicator constants
+const INDICATOR_WIDTH: f32 = 90.0; // Width of an indicator's text box in pixels
+const INDICATOR_LIFT: f32 = 52.0; // Pixels an on-screen marker sits above its waypoint
+const EDGE_MARGIN: f32 = 40.0; // Distance off-screen indicators keep from the window edge
+const INDICATOR_FONT_SIZE: f32 = 14.0;
+
+// An objective the player should be able to find from anywhere on screen
+#[derive(Component)]
+pub struct Waypoint {
+    pub label: String,
+    pub color: Color,
+}
+
+// UI indicator that tracks a world entity carrying a Waypoint
+#[derive(Component)]
+pub struct WaypointIndicator {
+    pub target: Entity,
+}
+
+// Marker for the full-screen layer holding waypoint indicators
+#[derive(Component)]
+pub struct WaypointLayer;
+
+// Arrow pointing along a screen-space direction (y down)
+fn edge_arrow(direction: Vec2) -> &'static str {
+    if direction.x.abs() > direction.y.abs() {
+        if direction.x > 0.0 { ">" } else { "<" }
+    } else if direction.y > 0.0 {
+        "v"
+    } else {
+        "^"
+    }
+}
+
+// Full-screen layer the indicators are positioned in
+pub fn spawn_waypoint_layer(mut commands: Commands) {
+    commands.spawn((
+        WaypointLayer,
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        // Hidden until a game starts
+        Visibility::Hidden,
+    ));
+}
+
+// Show waypoint indicators while playing
+pub fn show_waypoints(mut query: Query<&mut Visibility, With<WaypointLayer>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+// Hide waypoint indicators outside of gameplay
+pub fn hide_waypoints(mut query: Query<&mut Visibility, With<WaypointLayer>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Create indicators for new waypoints and remove indicators whose entity is gone
+pub fn sync_waypoint_indicators(
+    mut commands: Commands,
+    layer_query: Query<Entity, With<WaypointLayer>>,
+    new_waypoints: Query<(Entity, &Waypoint), Added<Waypoint>>,
+    waypoints: Query<(), With<Waypoint>>,
+    indicators: Query<(Entity, &WaypointIndicator)>,
+) {
+    let Ok(layer) = layer_query.get_single() else {
+        return;
+    };
+
+    for (entity, waypoint) in new_waypoints.iter() {
+        commands.entity(layer).with_children(|parent| {
+            parent.spawn((
+                WaypointIndicator { target: entity },
+                Text::new(""),
+                TextFont {
+                    font_size: INDICATOR_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(waypoint.color),
+                TextLayout::new_with_justify(JustifyText::Center),
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(INDICATOR_WIDTH),
+                    ..default()
+                },
+                Visibility::Hidden,
+            ));
+        });
+    }
+
+    for (indicator_entity, indicator) in indicators.iter() {
+        if waypoints.get(indicator.target).is_err() {
+            commands.entity(indicator_entity).despawn_recursive();
+        }
+    }
+}
+
+// Place each indicator over its waypoint, or pinned to the screen edge pointing toward it
+pub fn update_waypoint_indicators(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<FollowCamera>>,
+    render_scale: Res<RenderScale>,
+    player_query: Query<&Transform, With<Player>>,
+    waypoint_query: Query<(&Waypoint, &GlobalTransform)>,
+    mut indicator_query: Query<(&WaypointIndicator, &mut Text, &mut Node, &mut Visibility)>,
+) {
+    let (Ok(window), Ok((camera, camera_transform)), Ok(player_transform)) = (
+        window_query.get_single(),
+        camera_query.get_single(),
+        player_query.get_single()
+    ) else {
+        return;
+    };
+    let screen = Vec2::new(window.width(), window.height());
+    let center = screen * 0.5;
+    let world_to_camera = camera_transform.affine().inverse();
+
+    for (indicator, mut text, mut node, mut visibility) in indicator_query.iter_mut() {
+        let Ok((waypoint, waypoint_transform)) = waypoint_query.get(indicator.target) else {
+            continue;
+        };
+        let position = waypoint_transform.translation();
+        let distance = player_transform.translation.distance(position);
+
+        // On screen: label and distance above the waypoint
+        let on_screen = camera
+            .world_to_viewport(camera_transform, position)
+            .ok()
+            .map(|point| render_scale.viewport_to_window(point))
+            .filter(|point| point.x >= 0.0 && point.y >= 0.0 && point.x <= screen.x && point.y <= screen.y);
+        if let Some(point) = on_screen {
+            text.0 = format!("{}\n{:.0} m\nv", waypoint.label, distance);
+            node.left = Val::Px(point.x - INDICATOR_WIDTH * 0.5);
+            node.top = Val::Px(point.y - INDICATOR_LIFT);
+            *visibility = Visibility::Inherited;
+            continue;
+        }
+
+        // Off screen: direction in camera space, flipped to screen space with y down
+        let local = world_to_camera.transform_point3(position);
+        let mut direction = Vec2::new(local.x, -local.y);
+        if direction.length_squared() < 0.0001 {
+            // Directly behind the camera
+            direction = Vec2::Y;
+        }
+        let half_extent = (center - Vec2::splat(EDGE_MARGIN)).max(Vec2::ONE);
+        let scale = (half_extent.x / direction.x.abs()).min(half_extent.y / direction.y.abs());
+        let point = center + direction * scale;
+
+        text.0 = format!("{} {:.0} m", edge_arrow(direction), distance);
+        node.left = Val::Px(point.x - INDICATOR_WIDTH * 0.5);
+        node.top = Val::Px(point.y - INDICATOR_FONT_SIZE * 0.5);
+        *visibility = Visibility::Inherited;
+    }
+}
+
+// Plugin for waypoint markers and off-screen indicators
+pub struct WaypointPlugin;
+
+impl Plugin for WaypointPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, spawn_waypoint_layer)
+            .add_systems(OnEnter(GameState::Playing), show_waypoints)
+            .add_systems(OnExit(GameState::Playing), hide_waypoints)
+            .add_systems(Update, (
+                sync_waypoint_indicators,
+                update_waypoint_indicators.after(sync_waypoint_indicators),
+            ));
+    }
+}
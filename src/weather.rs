@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+use crate::audio::AmbientConditions;
+use crate::terrain::world_seed;
+use crate::GameState;
+
+// Weather constants
+const SHOWER_PERIOD: f32 = 300.0; // Seconds between the peaks of the slow weather swing
+const SQUALL_PERIOD: f32 = 110.0; // Seconds between the peaks of the faster swing laid over it
+const SQUALL_SHARE: f32 = 0.35; // How much of the weather the faster swing makes up
+const DRY_LEVEL: f32 = 0.6; // Weather level, from 0 to 1, below which it doesn't rain at all
+const RAIN_RESPONSE: f32 = 0.05; // How quickly the rain follows the weather, per second
+
+// Where in its swings the weather starts, picked from the world seed so each world has its own
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct Weather {
+    shower_phase: f32,
+    squall_phase: f32,
+}
+
+// How wet the weather is at a time, from 0 dry to 1 pouring: two slow swings added together, raining
+// only while they peak over the dry level
+pub fn rain_at(weather: &Weather, seconds: f32) -> f32 {
+    let shower = (seconds / SHOWER_PERIOD * TAU + weather.shower_phase).sin();
+    let squall = (seconds / SQUALL_PERIOD * TAU + weather.squall_phase).sin();
+    let level = 0.5 + 0.5 * (shower * (1.0 - SQUALL_SHARE) + squall * SQUALL_SHARE);
+    ((level - DRY_LEVEL) / (1.0 - DRY_LEVEL)).clamp(0.0, 1.0)
+}
+
+// Pick the weather's starting point for this world
+pub fn setup_weather(mut weather: ResMut<Weather>) {
+    let seed = world_seed();
+    weather.shower_phase = (seed & 0xFFFF) as f32 / 65536.0 * TAU;
+    weather.squall_phase = (seed >> 16) as f32 / 65536.0 * TAU;
+}
+
+// Ease the rain the ambient sounds, rain particles and clouds follow toward the weather
+pub fn update_weather(weather: Res<Weather>, mut conditions: ResMut<AmbientConditions>, time: Res<Time>) {
+    let target = rain_at(&weather, time.elapsed_secs());
+    conditions.rain += (target - conditions.rain) * (RAIN_RESPONSE * time.delta_secs()).min(1.0);
+}
+
+// Plugin for the showers that come and go over a run
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Weather>()
+            .add_systems(OnExit(GameState::Loading), setup_weather)
+            .add_systems(Update, update_weather);
+    }
+}
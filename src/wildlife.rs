@@ -0,0 +1,377 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::f32::consts::{FRAC_PI_2, TAU};
+use crate::budget::WorkBudgets;
+use crate::hazards::in_lava;
+use crate::player::Player;
+use crate::projectile::Projectile;
+use crate::terrain::{chunk_rng, find_hilltop, get_terrain_height, queue_new_chunks, sample, ScatterQueue, TerrainChunk, CHUNK_SIZE};
+
+// Bird constants
+const FLOCK_CHANCE: f64 = 0.35; // Chance a chunk has a flock circling its hilltop
+const FLOCK_MIN_SIZE: usize = 5;
+const FLOCK_MAX_SIZE: usize = 9;
+const ORBIT_MIN_RADIUS: f32 = 8.0; // Circle a flock flies around its hilltop
+const ORBIT_MAX_RADIUS: f32 = 16.0;
+const ORBIT_MIN_HEIGHT: f32 = 10.0; // Height above the hilltop a flock circles at
+const ORBIT_MAX_HEIGHT: f32 = 18.0;
+const BIRD_MIN_SPEED: f32 = 4.0;
+const BIRD_MAX_SPEED: f32 = 8.0;
+const BIRD_MAX_STEER: f32 = 6.0; // Most a bird's velocity changes per second
+const BIRD_NEIGHBOR_RADIUS: f32 = 5.0; // Flockmates within this distance are aligned and gathered with
+const BIRD_SEPARATION_RADIUS: f32 = 1.5; // Flockmates closer than this are pushed away from
+const SEPARATION_WEIGHT: f32 = 3.0;
+const ALIGNMENT_WEIGHT: f32 = 1.0;
+const COHESION_WEIGHT: f32 = 0.6;
+const ORBIT_WEIGHT: f32 = 1.5; // Pull onto the circle around the hilltop
+const BIRD_CLEARANCE: f32 = 3.0; // Lowest a bird flies above the terrain
+const FLAP_RATE: f32 = 9.0; // Wing beats per second, in radians
+const BIRD_COLOR: Color = Color::srgb(0.15, 0.15, 0.18);
+
+// Critter constants
+const CRITTER_CHANCE: f64 = 0.6; // Chance a chunk has critters
+const CRITTER_MIN_COUNT: usize = 2;
+const CRITTER_MAX_COUNT: usize = 4;
+const CRITTER_MAX_SLOPE: f32 = 0.4; // Steepest ground critters are placed on
+const CRITTER_SIZE: f32 = 0.18; // Radius of a critter's body
+const SCARE_RADIUS: f32 = 7.0; // A ball or boulder this close sends critters running
+const CALM_DISTANCE: f32 = 14.0; // Critters stop running once every threat is this far away
+const FLEE_SPEED: f32 = 6.0;
+const WANDER_SPEED: f32 = 1.2;
+const WANDER_TIME: f32 = 0.6; // Seconds a calm critter hops before sitting again
+const WANDER_MIN_INTERVAL: f32 = 2.0; // Seconds a calm critter sits between hops
+const WANDER_MAX_INTERVAL: f32 = 5.0;
+const HOP_HEIGHT: f32 = 0.3;
+const HOP_RATE: f32 = 14.0; // Hops per second while moving, in radians
+const SPAWN_MARGIN: f32 = 4.0; // Distance from the chunk edge critters keep
+const WILDLIFE_SALT: u64 = 4; // Chunk random stream for wildlife
+const CRITTER_COLOR: Color = Color::srgb(0.55, 0.4, 0.28);
+
+// A flock circling a landmark hilltop; its birds are its children, placed relative to the center
+#[derive(Component)]
+pub struct Flock {
+    // Hilltop the flock circles, in world space
+    pub center: Vec3,
+    pub radius: f32,
+    pub height: f32,
+}
+
+// A bird flying with its flock
+#[derive(Component)]
+pub struct Bird {
+    pub velocity: Vec3,
+    // Wing beat phase, so birds don't flap in step
+    pub flap: f32,
+}
+
+// A small animal sitting on the ground that runs from the ball and boulders; a child of its chunk
+#[derive(Component)]
+pub struct Critter {
+    // World position of the chunk it belongs to, which its transform is relative to
+    pub origin: Vec3,
+    pub heading: Vec2,
+    pub speed: f32,
+    pub fleeing: bool,
+    // Seconds of hopping left, or until the next hop while sitting
+    pub timer: f32,
+    pub hop: f32,
+}
+
+// Shared meshes and materials for wildlife
+#[derive(Resource)]
+pub struct WildlifeAssets {
+    pub bird_mesh: Handle<Mesh>,
+    pub bird_material: Handle<StandardMaterial>,
+    pub critter_mesh: Handle<Mesh>,
+    pub critter_material: Handle<StandardMaterial>,
+}
+
+// A bird as two flat wings meeting along the body, pointing along -Z. The wingtips sit above the body so
+// scaling Y up and down beats them.
+fn create_bird_mesh() -> Mesh {
+    let nose = [0.0, 0.0, -0.3];
+    let tail = [0.0, 0.0, 0.15];
+    let left_tip = [-0.6, 0.2, 0.2];
+    let right_tip = [0.6, 0.2, 0.2];
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, Default::default());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec![nose, tail, left_tip, right_tip]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 1.0, 0.0]; 4]);
+    mesh.insert_indices(Indices::U32(vec![0, 2, 1, 0, 1, 3]));
+    mesh
+}
+
+// Lay a critter's capsule body along its heading
+fn critter_rotation(heading: Vec2) -> Quat {
+    Quat::from_rotation_y(-heading.to_angle() - FRAC_PI_2) * Quat::from_rotation_x(FRAC_PI_2)
+}
+
+// Create the shared wildlife meshes and materials
+pub fn setup_wildlife_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(WildlifeAssets {
+        bird_mesh: meshes.add(create_bird_mesh()),
+        bird_material: materials.add(StandardMaterial {
+            base_color: BIRD_COLOR,
+            cull_mode: None,
+            double_sided: true,
+            ..default()
+        }),
+        critter_mesh: meshes.add(Capsule3d::new(CRITTER_SIZE, CRITTER_SIZE * 1.5)),
+        critter_material: materials.add(StandardMaterial {
+            base_color: CRITTER_COLOR,
+            perceptual_roughness: 0.9,
+            ..default()
+        }),
+    });
+}
+
+// Place flocks and critters on chunks as they are generated, a few chunks a frame within the scatter
+// budget; the same every time a chunk loads
+pub fn populate_wildlife(
+    mut commands: Commands,
+    assets: Res<WildlifeAssets>,
+    budgets: Res<WorkBudgets>,
+    mut queue: Local<ScatterQueue>,
+    chunk_query: Query<(Entity, &TerrainChunk), Added<TerrainChunk>>,
+) {
+    queue_new_chunks(&mut queue, &chunk_query);
+    queue.run(budgets.scatter_ms, |(entity, (chunk_x, chunk_z))| {
+        // A chunk rebuilt before its turn comes back as a new entity
+        let Some(mut chunk_commands) = commands.get_entity(entity) else {
+            return;
+        };
+        let origin = Vec2::new(chunk_x as f32 * CHUNK_SIZE, chunk_z as f32 * CHUNK_SIZE);
+        let origin3 = Vec3::new(origin.x, 0.0, origin.y);
+        let mut rng = chunk_rng(chunk_x, chunk_z, WILDLIFE_SALT);
+        let spot = |rng: &mut StdRng| origin + Vec2::new(
+            rng.gen_range(SPAWN_MARGIN..CHUNK_SIZE - SPAWN_MARGIN),
+            rng.gen_range(SPAWN_MARGIN..CHUNK_SIZE - SPAWN_MARGIN),
+        );
+
+        // Draw every value up front so the layout doesn't depend on what gets skipped
+        let has_flock = rng.gen_bool(FLOCK_CHANCE);
+        let flock_start = spot(&mut rng);
+        let radius = rng.gen_range(ORBIT_MIN_RADIUS..ORBIT_MAX_RADIUS);
+        let height = rng.gen_range(ORBIT_MIN_HEIGHT..ORBIT_MAX_HEIGHT);
+        let flock_size = rng.gen_range(FLOCK_MIN_SIZE..=FLOCK_MAX_SIZE);
+        let birds: Vec<(f32, f32, f32)> = (0..FLOCK_MAX_SIZE)
+            .map(|_| (rng.gen_range(0.0..TAU), rng.gen_range(-2.0..2.0), rng.gen_range(0.0..TAU)))
+            .collect();
+        let has_critters = rng.gen_bool(CRITTER_CHANCE);
+        let critter_count = rng.gen_range(CRITTER_MIN_COUNT..=CRITTER_MAX_COUNT);
+        let critters: Vec<(Vec2, f32, f32)> = (0..CRITTER_MAX_COUNT)
+            .map(|_| (spot(&mut rng), rng.gen_range(0.0..TAU), rng.gen_range(0.0..WANDER_MAX_INTERVAL)))
+            .collect();
+
+        chunk_commands.with_children(|parent| {
+            if has_flock {
+                let hilltop = find_hilltop(flock_start);
+                let center = Vec3::new(hilltop.x, get_terrain_height(hilltop.x, hilltop.y), hilltop.y);
+                parent
+                    .spawn((
+                        Flock { center, radius, height },
+                        Transform::from_translation(center - origin3),
+                        Visibility::default(),
+                    ))
+                    .with_children(|flock| {
+                        for (angle, lift, flap) in birds.iter().take(flock_size) {
+                            // Spread around the circle, already flying along it
+                            let around = Vec3::new(angle.cos(), 0.0, angle.sin());
+                            let position = around * radius + Vec3::Y * (height + lift);
+                            let velocity = Vec3::new(-around.z, 0.0, around.x) * BIRD_MIN_SPEED;
+                            flock.spawn((
+                                Bird { velocity, flap: *flap },
+                                Mesh3d(assets.bird_mesh.clone()),
+                                MeshMaterial3d(assets.bird_material.clone()),
+                                Transform::from_translation(position).looking_to(velocity, Vec3::Y),
+                            ));
+                        }
+                    });
+            }
+
+            if has_critters {
+                for (world, heading, timer) in critters.iter().take(critter_count) {
+                    let ground = sample(world.x, world.y);
+                    if in_lava(world.x, world.y) || ground.gradient.length() > CRITTER_MAX_SLOPE {
+                        continue;
+                    }
+                    parent.spawn((
+                        Critter {
+                            origin: origin3,
+                            heading: Vec2::from_angle(*heading),
+                            speed: 0.0,
+                            fleeing: false,
+                            timer: *timer,
+                            hop: 0.0,
+                        },
+                        Mesh3d(assets.critter_mesh.clone()),
+                        MeshMaterial3d(assets.critter_material.clone()),
+                        Transform::from_xyz(world.x - origin.x, ground.height + CRITTER_SIZE, world.y - origin.y)
+                            .with_rotation(critter_rotation(Vec2::from_angle(*heading))),
+                    ));
+                }
+            }
+        });
+    });
+}
+
+// Steer each bird with its flockmates and around its hilltop: keep apart from the nearest, match the
+// heading and drift toward the middle of the rest, and keep on the circle, never lower than the ground
+// allows. Positions are relative to the flock's center.
+pub fn update_birds(
+    flock_query: Query<(&Flock, &Children)>,
+    mut bird_query: Query<(&mut Bird, &mut Transform)>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    if delta <= 0.0 {
+        return;
+    }
+    for (flock, children) in flock_query.iter() {
+        let birds: Vec<(Vec3, Vec3)> = children
+            .iter()
+            .filter_map(|child| bird_query.get(*child).ok())
+            .map(|(bird, transform)| (transform.translation, bird.velocity))
+            .collect();
+
+        for child in children.iter() {
+            let Ok((mut bird, mut transform)) = bird_query.get_mut(*child) else {
+                continue;
+            };
+            let position = transform.translation;
+
+            let mut separation = Vec3::ZERO;
+            let mut heading = Vec3::ZERO;
+            let mut middle = Vec3::ZERO;
+            let mut neighbors = 0;
+            for (other_position, other_velocity) in birds.iter() {
+                let offset = position - *other_position;
+                let distance = offset.length();
+                if distance <= 0.001 || distance > BIRD_NEIGHBOR_RADIUS {
+                    continue;
+                }
+                if distance < BIRD_SEPARATION_RADIUS {
+                    separation += offset / (distance * distance);
+                }
+                heading += *other_velocity;
+                middle += *other_position;
+                neighbors += 1;
+            }
+            let mut steer = separation * SEPARATION_WEIGHT;
+            if neighbors > 0 {
+                steer += (heading / neighbors as f32 - bird.velocity).normalize_or_zero() * ALIGNMENT_WEIGHT;
+                steer += (middle / neighbors as f32 - position).normalize_or_zero() * COHESION_WEIGHT;
+            }
+
+            // Toward the nearest point of the circle, and around it
+            let flat = Vec3::new(position.x, 0.0, position.z);
+            let around = flat.normalize_or(Vec3::X);
+            let on_circle = around * flock.radius + Vec3::Y * flock.height;
+            let tangent = Vec3::new(-around.z, 0.0, around.x);
+            steer += ((on_circle - position) * 0.2 + tangent).normalize_or_zero() * ORBIT_WEIGHT;
+
+            let velocity = bird.velocity + steer.clamp_length_max(1.0) * BIRD_MAX_STEER * delta;
+            let speed = velocity.length().clamp(BIRD_MIN_SPEED, BIRD_MAX_SPEED);
+            bird.velocity = velocity.normalize_or(tangent) * speed;
+
+            let mut next = position + bird.velocity * delta;
+            let world = flock.center + next;
+            let floor = get_terrain_height(world.x, world.z) + BIRD_CLEARANCE - flock.center.y;
+            if next.y < floor {
+                next.y = floor;
+                bird.velocity.y = bird.velocity.y.max(0.0);
+            }
+            transform.translation = next;
+            transform.look_to(bird.velocity, Vec3::Y);
+
+            // Kept off zero, which would flatten the wings into a shape with no usable normals
+            bird.flap = (bird.flap + FLAP_RATE * delta) % TAU;
+            let beat = bird.flap.sin();
+            transform.scale = Vec3::new(1.0, beat.signum() * beat.abs().max(0.1), 1.0);
+        }
+    }
+}
+
+// Send critters running from the balls and boulders nearby, and let calm ones hop about now and then
+pub fn update_critters(
+    mut critter_query: Query<(&mut Critter, &mut Transform)>,
+    threat_query: Query<&GlobalTransform, Or<(With<Player>, With<Projectile>)>>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    if delta <= 0.0 {
+        return;
+    }
+    let threats: Vec<Vec2> = threat_query.iter().map(|transform| transform.translation().xz()).collect();
+    let mut rng = rand::thread_rng();
+    for (mut critter, mut transform) in critter_query.iter_mut() {
+        let position = (critter.origin + transform.translation).xz();
+        let nearest = threats
+            .iter()
+            .map(|threat| (*threat, threat.distance(position)))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        match nearest {
+            Some((threat, distance)) if distance < SCARE_RADIUS => {
+                critter.fleeing = true;
+                critter.heading = (position - threat).normalize_or(critter.heading);
+                critter.speed = FLEE_SPEED;
+            }
+            Some((_, distance)) if critter.fleeing && distance < CALM_DISTANCE => {}
+            _ if critter.fleeing => {
+                critter.fleeing = false;
+                critter.speed = 0.0;
+                critter.timer = rng.gen_range(WANDER_MIN_INTERVAL..WANDER_MAX_INTERVAL);
+            }
+            _ => {
+                critter.timer -= delta;
+                if critter.timer <= 0.0 {
+                    if critter.speed > 0.0 {
+                        critter.speed = 0.0;
+                        critter.timer = rng.gen_range(WANDER_MIN_INTERVAL..WANDER_MAX_INTERVAL);
+                    } else {
+                        critter.heading = Vec2::from_angle(rng.gen_range(0.0..TAU));
+                        critter.speed = WANDER_SPEED;
+                        critter.timer = WANDER_TIME;
+                    }
+                }
+            }
+        }
+
+        let mut world = position;
+        if critter.speed > 0.0 {
+            let next = position + critter.heading * critter.speed * delta;
+            // Turn aside rather than run into lava
+            if in_lava(next.x, next.y) {
+                critter.heading = critter.heading.perp();
+            } else {
+                world = next;
+            }
+            critter.hop = (critter.hop + HOP_RATE * delta) % TAU;
+        } else {
+            critter.hop = 0.0;
+        }
+        let hop = critter.hop.sin().abs() * HOP_HEIGHT;
+        let ground = get_terrain_height(world.x, world.y);
+        transform.translation = Vec3::new(world.x, ground + CRITTER_SIZE + hop, world.y) - critter.origin;
+        transform.rotation = critter_rotation(critter.heading);
+    }
+}
+
+// Plugin for the birds and critters that liven up the terrain
+pub struct WildlifePlugin;
+
+impl Plugin for WildlifePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup_wildlife_assets)
+            // Chunks generate behind the main menu too, and wildlife moves there as well; it runs on
+            // virtual time, so it holds still while the game is paused
+            .add_systems(Update, (populate_wildlife, update_birds, update_critters));
+    }
+}
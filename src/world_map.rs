@@ -0,0 +1,445 @@
+use bevy::prelude::*;
+use bevy::color::{ColorToPacked, Mix};
+use bevy::image::ImageSampler;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::ui::RelativeCursorPosition;
+use crate::catapult::Catapult;
+use crate::compass::CompassMarker;
+use crate::minimap::MinimapBlip;
+use crate::network::NetSession;
+use crate::photo_mode::PhotoModeState;
+use crate::player::Player;
+use crate::save::SaveData;
+use crate::replay::BUTTON_THROW;
+use crate::sim::{latch_input, PauseReason, SimClock, SimInput};
+use crate::teleport::Teleporter;
+use crate::terrain::{chunk_coords, get_terrain_height, sample, world_seed, TerrainSurface, CHUNK_SIZE, TERRAIN_HEIGHT_SCALE};
+use crate::waypoint::Waypoint;
+use crate::GameState;
+
+// World map constants
+const MAP_RADIUS: i32 = 12; // Chunks shown on each side of the player's chunk
+const MAP_CHUNKS: i32 = MAP_RADIUS * 2 + 1;
+const CELL_PIXELS: usize = 8; // Map image pixels along each side of a chunk
+const MAP_PIXELS: usize = MAP_CHUNKS as usize * CELL_PIXELS;
+const MAP_WIDGET_SIZE: f32 = 600.0; // On-screen size of the map in pixels
+const EXPLORE_RADIUS: i32 = 1; // Chunks on each side of a ball that count as explored
+const PIN_SIZE: f32 = 10.0; // Pin diameter in pixels
+const PIN_FONT_SIZE: f32 = 13.0;
+const TITLE_FONT_SIZE: f32 = 18.0;
+const MARKER_LABEL: &str = "Marker";
+
+// Map colors
+const UNEXPLORED_COLOR: Color = Color::srgb(0.06, 0.07, 0.08);
+const LOWLAND_COLOR: Color = Color::srgb(0.22, 0.42, 0.22);
+const HIGHLAND_COLOR: Color = Color::srgb(0.55, 0.56, 0.38);
+const ROCK_COLOR: Color = Color::srgb(0.45, 0.42, 0.38);
+const LAVA_COLOR: Color = Color::srgb(0.9, 0.32, 0.05);
+const PLAYER_PIN_COLOR: Color = Color::WHITE;
+const MARKER_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+const LIGHT_DIRECTION: Vec3 = Vec3::new(-0.5, 0.7, -0.5); // Hill shading light, from the top left of the map
+
+// The full-screen exploration map and the image its terrain is drawn into
+#[derive(Resource)]
+pub struct WorldMap {
+    pub open: bool,
+    image: Handle<Image>,
+    // Chunk at the middle of the image when it was last drawn; None redraws it
+    drawn_around: Option<(i32, i32)>,
+}
+
+// Marker for the map's full-screen root
+#[derive(Component)]
+pub struct WorldMapRoot;
+
+// Marker for the map image, which pins are placed over
+#[derive(Component)]
+pub struct WorldMapImage;
+
+// A pin on the map, rebuilt every frame the map is open
+#[derive(Component)]
+pub struct MapPin;
+
+// The marker the player placed from the map; it is a waypoint like any objective
+#[derive(Component)]
+pub struct MapMarker {
+    pub position: Vec2,
+}
+
+fn map_open(map: Res<WorldMap>) -> bool {
+    map.open
+}
+
+// World X and Z of the map's top-left corner, north (-Z) at the top
+fn map_origin(center: (i32, i32)) -> Vec2 {
+    Vec2::new((center.0 - MAP_RADIUS) as f32, (center.1 - MAP_RADIUS) as f32) * CHUNK_SIZE
+}
+
+// Where a world position falls on the map, from 0 to 1 across it, if it is on the map at all
+fn map_point(center: (i32, i32), position: Vec3) -> Option<Vec2> {
+    let point = (Vec2::new(position.x, position.z) - map_origin(center)) / (MAP_CHUNKS as f32 * CHUNK_SIZE);
+    (point.cmpge(Vec2::ZERO).all() && point.cmple(Vec2::ONE).all()).then_some(point)
+}
+
+// Color of the ground at a point: lava, rock or grass by height, shaded by the slope
+fn ground_color(x: f32, z: f32) -> [u8; 4] {
+    let ground = sample(x, z);
+    let base = match ground.surface {
+        TerrainSurface::Lava => LAVA_COLOR,
+        TerrainSurface::Steep => ROCK_COLOR,
+        TerrainSurface::Ground => {
+            let height = (ground.height / TERRAIN_HEIGHT_SCALE * 0.5 + 0.5).clamp(0.0, 1.0);
+            LOWLAND_COLOR.mix(&HIGHLAND_COLOR, height)
+        }
+    };
+    let shade = 0.75 + 0.35 * ground.normal.dot(LIGHT_DIRECTION.normalize()).max(0.0);
+    let color = base.to_srgba();
+    [
+        (color.red * shade).clamp(0.0, 1.0),
+        (color.green * shade).clamp(0.0, 1.0),
+        (color.blue * shade).clamp(0.0, 1.0),
+        1.0,
+    ]
+    .map(|channel| (channel * 255.0) as u8)
+}
+
+// The image the map's terrain is drawn into, one block of pixels per chunk
+fn create_map_image() -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: MAP_PIXELS as u32,
+            height: MAP_PIXELS as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &UNEXPLORED_COLOR.to_srgba().to_u8_array(),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    // Keep chunk edges crisp when the image is stretched over the screen
+    image.sampler = ImageSampler::nearest();
+    image
+}
+
+// Create the map image and the hidden full-screen map
+pub fn spawn_world_map(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let image = images.add(create_map_image());
+    commands
+        .spawn((
+            WorldMapRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+            GlobalZIndex(8),
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Map - click to place a marker, right-click to remove it, M to close"),
+                TextFont {
+                    font_size: TITLE_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.85, 0.85, 0.85)),
+            ));
+            parent.spawn((
+                WorldMapImage,
+                ImageNode::new(image.clone()),
+                Node {
+                    width: Val::Px(MAP_WIDGET_SIZE),
+                    height: Val::Px(MAP_WIDGET_SIZE),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                BorderColor(Color::srgba(1.0, 1.0, 1.0, 0.6)),
+                RelativeCursorPosition::default(),
+            ));
+        });
+    commands.insert_resource(WorldMap { open: false, image, drawn_around: None });
+}
+
+// Uncover the chunks around every ball as it goes
+pub fn explore_chunks(mut save: ResMut<SaveData>, player_query: Query<&Transform, With<Player>>) {
+    let seed = world_seed();
+    for transform in player_query.iter() {
+        let (x, z) = chunk_coords(transform.translation);
+        for dz in -EXPLORE_RADIUS..=EXPLORE_RADIUS {
+            for dx in -EXPLORE_RADIUS..=EXPLORE_RADIUS {
+                let chunk = (x + dx, z + dz);
+                // Checked first so the save only counts as changed when a chunk is new
+                if !save.has_explored(seed, chunk) {
+                    save.mark_explored(seed, chunk);
+                }
+            }
+        }
+    }
+}
+
+// Open or close the map; alone, the game holds still while it is open
+fn set_map_open(
+    open: bool,
+    map: &mut WorldMap,
+    clock: &mut SimClock,
+    input: &mut SimInput,
+    alone: bool,
+    root_query: &mut Query<&mut Visibility, With<WorldMapRoot>>,
+) {
+    map.open = open;
+    if open {
+        map.drawn_around = None;
+        if alone {
+            clock.pause(PauseReason::Map);
+        }
+    } else {
+        clock.resume(PauseReason::Map);
+        // Clicks on the map are not throws
+        input.pressed = 0;
+    }
+    for mut visibility in root_query.iter_mut() {
+        *visibility = if open { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+// M opens and closes the map
+pub fn toggle_world_map(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut map: ResMut<WorldMap>,
+    mut clock: ResMut<SimClock>,
+    mut input: ResMut<SimInput>,
+    session: Option<Res<NetSession>>,
+    mut root_query: Query<&mut Visibility, With<WorldMapRoot>>,
+) {
+    if keys.just_pressed(KeyCode::KeyM) {
+        let open = !map.open;
+        set_map_open(open, &mut map, &mut clock, &mut input, session.is_none(), &mut root_query);
+    }
+}
+
+// Clicks on the open map are not throws; in company the game runs on while it is open, so they are
+// taken out of the input every frame rather than only dropped when it closes
+pub fn hold_throws_on_map(mut input: ResMut<SimInput>) {
+    input.release(BUTTON_THROW);
+}
+
+// Put the map away when leaving play, like for the pause menu
+pub fn close_world_map(
+    mut map: ResMut<WorldMap>,
+    mut clock: ResMut<SimClock>,
+    mut input: ResMut<SimInput>,
+    mut root_query: Query<&mut Visibility, With<WorldMapRoot>>,
+) {
+    if map.open {
+        set_map_open(false, &mut map, &mut clock, &mut input, true, &mut root_query);
+    }
+}
+
+// Draw the terrain around the player into the map image: explored chunks in their colors, the rest dark
+pub fn draw_world_map(
+    mut map: ResMut<WorldMap>,
+    mut images: ResMut<Assets<Image>>,
+    save: Res<SaveData>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let Some(transform) = player_query.iter().next() else {
+        return;
+    };
+    let center = chunk_coords(transform.translation);
+    if map.drawn_around == Some(center) {
+        return;
+    }
+    map.drawn_around = Some(center);
+    let Some(image) = images.get_mut(&map.image) else {
+        return;
+    };
+
+    let seed = world_seed();
+    let origin = map_origin(center);
+    let pixel_size = CHUNK_SIZE / CELL_PIXELS as f32;
+    let unexplored = UNEXPLORED_COLOR.to_srgba().to_u8_array();
+    for row in 0..MAP_PIXELS {
+        for column in 0..MAP_PIXELS {
+            let chunk = (
+                center.0 - MAP_RADIUS + (column / CELL_PIXELS) as i32,
+                center.1 - MAP_RADIUS + (row / CELL_PIXELS) as i32,
+            );
+            let color = if save.has_explored(seed, chunk) {
+                let x = origin.x + (column as f32 + 0.5) * pixel_size;
+                let z = origin.y + (row as f32 + 0.5) * pixel_size;
+                ground_color(x, z)
+            } else {
+                unexplored
+            };
+            let i = (row * MAP_PIXELS + column) * 4;
+            image.data[i..i + 4].copy_from_slice(&color);
+        }
+    }
+}
+
+// Spawn a pin on the map, with a label beside it if it has one
+fn spawn_pin(parent: &mut ChildBuilder, point: Vec2, color: Color, label: Option<&str>) {
+    parent
+        .spawn((
+            MapPin,
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(point.x * MAP_WIDGET_SIZE - PIN_SIZE * 0.5),
+                top: Val::Px(point.y * MAP_WIDGET_SIZE - PIN_SIZE * 0.5),
+                width: Val::Px(PIN_SIZE),
+                height: Val::Px(PIN_SIZE),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            BackgroundColor(color),
+            BorderColor(Color::BLACK),
+            BorderRadius::MAX,
+        ))
+        .with_children(|pin| {
+            if let Some(label) = label {
+                pin.spawn((
+                    Text::new(label),
+                    TextFont {
+                        font_size: PIN_FONT_SIZE,
+                        ..default()
+                    },
+                    TextColor(color),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(PIN_SIZE + 2.0),
+                        top: Val::Px(-PIN_FONT_SIZE * 0.3),
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+// Pin the balls, every waypoint and the landmarks in explored chunks onto the map
+pub fn update_map_pins(
+    mut commands: Commands,
+    map: Res<WorldMap>,
+    save: Res<SaveData>,
+    image_query: Query<Entity, With<WorldMapImage>>,
+    pin_query: Query<Entity, With<MapPin>>,
+    player_query: Query<&Transform, With<Player>>,
+    waypoint_query: Query<(&Waypoint, &GlobalTransform)>,
+    landmark_query: Query<(&GlobalTransform, &MinimapBlip, Has<Catapult>), Or<(With<Catapult>, With<Teleporter>)>>,
+) {
+    let (Ok(image), Some(center)) = (image_query.get_single(), map.drawn_around) else {
+        return;
+    };
+    for pin in pin_query.iter() {
+        commands.entity(pin).despawn_recursive();
+    }
+
+    let seed = world_seed();
+    commands.entity(image).with_children(|parent| {
+        for (transform, blip, is_catapult) in landmark_query.iter() {
+            let position = transform.translation();
+            if !save.has_explored(seed, chunk_coords(position)) {
+                continue;
+            }
+            if let Some(point) = map_point(center, position) {
+                spawn_pin(parent, point, blip.color, Some(if is_catapult { "Catapult" } else { "Teleporter" }));
+            }
+        }
+        for (waypoint, transform) in waypoint_query.iter() {
+            if let Some(point) = map_point(center, transform.translation()) {
+                spawn_pin(parent, point, waypoint.color, Some(&waypoint.label));
+            }
+        }
+        for transform in player_query.iter() {
+            if let Some(point) = map_point(center, transform.translation) {
+                spawn_pin(parent, point, PLAYER_PIN_COLOR, None);
+            }
+        }
+    });
+}
+
+// Click the map to put the marker there, right-click to take it away
+pub fn place_map_marker(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    map: Res<WorldMap>,
+    mut save: ResMut<SaveData>,
+    image_query: Query<&RelativeCursorPosition, With<WorldMapImage>>,
+) {
+    let (Ok(cursor), Some(center)) = (image_query.get_single(), map.drawn_around) else {
+        return;
+    };
+    if mouse_buttons.just_pressed(MouseButton::Right) {
+        save.set_map_marker(world_seed(), None);
+        return;
+    }
+    if !mouse_buttons.just_pressed(MouseButton::Left) || !cursor.mouse_over() {
+        return;
+    }
+    let Some(point) = cursor.normalized else {
+        return;
+    };
+    let position = map_origin(center) + point * MAP_CHUNKS as f32 * CHUNK_SIZE;
+    save.set_map_marker(world_seed(), Some(position));
+}
+
+// Keep the marker in the world where the save has it for this world, spawning, moving or removing it
+pub fn sync_map_marker(
+    mut commands: Commands,
+    save: Res<SaveData>,
+    mut marker_query: Query<(Entity, &mut MapMarker, &mut Transform)>,
+) {
+    let wanted = save.map_marker(world_seed());
+    match (wanted, marker_query.get_single_mut()) {
+        (Some(position), Ok((_, mut marker, mut transform))) => {
+            if marker.position != position {
+                marker.position = position;
+                transform.translation = Vec3::new(position.x, get_terrain_height(position.x, position.y), position.y);
+            }
+        }
+        (Some(position), Err(_)) => {
+            commands.spawn((
+                MapMarker { position },
+                Transform::from_xyz(position.x, get_terrain_height(position.x, position.y), position.y),
+                Visibility::default(),
+                Name::new("Map Marker"),
+                Waypoint {
+                    label: MARKER_LABEL.to_string(),
+                    color: MARKER_COLOR,
+                },
+                CompassMarker { color: MARKER_COLOR },
+                MinimapBlip { color: MARKER_COLOR },
+            ));
+        }
+        (None, Ok((entity, _, _))) => {
+            commands.entity(entity).despawn_recursive();
+        }
+        (None, Err(_)) => {}
+    }
+}
+
+// Plugin for the exploration map and the marker placed from it
+pub struct WorldMapPlugin;
+
+impl Plugin for WorldMapPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, spawn_world_map)
+            .add_systems(PreUpdate, hold_throws_on_map.after(latch_input).run_if(map_open))
+            .add_systems(OnExit(GameState::Playing), close_world_map)
+            .add_systems(Update, (explore_chunks, sync_map_marker)
+                .run_if(in_state(GameState::Playing)))
+            .add_systems(Update, toggle_world_map
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PhotoModeState::Inactive)))
+            .add_systems(Update, (draw_world_map, place_map_marker, update_map_pins)
+                .chain()
+                .after(toggle_world_map)
+                .run_if(map_open));
+    }
+}